@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::gecko_profile::GeckoProfile;
 use crate::sampler::{NativeStack, Sampler};
 use crossbeam_channel::{after, never, unbounded, Receiver, Sender};
 use ipc_channel::ipc::{IpcReceiver, IpcSender};
@@ -186,7 +187,14 @@ struct MonitoredComponent {
     exit_signal: Option<Box<dyn BackgroundHangMonitorExitSignal>>,
 }
 
-struct Sample(MonitoredComponentId, Instant, NativeStack);
+/// A stack of a monitored component, and the annotation of its activity if it wasn't
+/// waiting at the time.
+struct Sample(
+    MonitoredComponentId,
+    Instant,
+    Option<HangAnnotation>,
+    NativeStack,
+);
 
 pub struct BackgroundHangMonitorWorker {
     component_names: HashMap<MonitoredComponentId, String>,
@@ -228,44 +236,43 @@ impl BackgroundHangMonitorWorker {
     }
 
     fn finish_sampled_profile(&mut self) {
-        let mut bytes = vec![];
-        bytes.extend(
-            format!(
-                "{{ \"rate\": {}, \"start\": {}, \"data\": [\n",
-                self.sampling_duration.unwrap().as_millis(),
-                (self.sampling_baseline - self.creation).as_millis(),
-            )
-            .as_bytes(),
+        let mut profile = GeckoProfile::new(
+            self.sampling_duration.unwrap().as_secs_f64() * 1000.,
+            self.sampling_baseline.elapsed().as_secs_f64() * 1000.,
         );
+        // The annotation and the start time of the ongoing annotation marker of each component.
+        let mut open_markers: HashMap<MonitoredComponentId, (HangAnnotation, f64, f64)> =
+            HashMap::new();
 
-        let mut first = true;
         let to_resolve = self.samples.len();
-        for (i, Sample(id, instant, stack)) in self.samples.drain(..).enumerate() {
+        for (i, Sample(id, instant, annotation, stack)) in self.samples.drain(..).enumerate() {
             println!("Resolving {}/{}", i + 1, to_resolve);
-            let profile = stack.to_hangprofile();
-            let name = match self.component_names.get(&id) {
-                Some(ref s) => format!("\"{}\"", s),
-                None => format!("null"),
-            };
-            let json = format!(
-                "{}{{ \"name\": {}, \"namespace\": {}, \"index\": {}, \"type\": \"{:?}\", \
-                 \"time\": {}, \"frames\": {} }}",
-                if !first { ",\n" } else { "" },
-                name,
-                id.0.namespace_id.0,
-                id.0.index.0.get(),
-                id.1,
-                (instant - self.sampling_baseline).as_millis(),
-                serde_json::to_string(&profile.backtrace).unwrap(),
-            );
-            bytes.extend(json.as_bytes());
-            first = false;
+            let time = (instant - self.sampling_baseline).as_secs_f64() * 1000.;
+            let thread = profile.thread(&id, self.component_names.get(&id).map(String::as_str));
+            thread.add_sample(time, &stack.to_hangprofile().backtrace);
+
+            // Consecutive samples taken during the same activity share a single marker.
+            if let Some(&(open_annotation, start, _)) = open_markers.get(&id) {
+                if Some(open_annotation) == annotation {
+                    open_markers.insert(id, (open_annotation, start, time));
+                    continue;
+                }
+                thread.add_annotation_marker("Activity", open_annotation, start, time);
+                open_markers.remove(&id);
+            }
+            if let Some(annotation) = annotation {
+                open_markers.insert(id, (annotation, time, time));
+            }
+        }
+        for (id, (annotation, start, end)) in open_markers {
+            profile
+                .thread(&id, None)
+                .add_annotation_marker("Activity", annotation, start, end);
         }
 
-        bytes.extend(b"\n] }");
         let _ = self
             .constellation_chan
-            .send(HangMonitorAlert::Profile(bytes));
+            .send(HangMonitorAlert::Profile(profile.to_bytes()));
     }
 
     pub fn run(&mut self) -> bool {
@@ -458,8 +465,13 @@ impl BackgroundHangMonitorWorker {
                     // Buffer is full, start discarding older samples.
                     self.samples.pop_front();
                 }
+                let annotation = if monitored.is_waiting {
+                    None
+                } else {
+                    monitored.last_annotation
+                };
                 self.samples
-                    .push_back(Sample(component_id.clone(), instant, stack));
+                    .push_back(Sample(component_id.clone(), instant, annotation, stack));
             }
         }
     }
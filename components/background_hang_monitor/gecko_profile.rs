@@ -0,0 +1,299 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Export of hang monitor samples in the Gecko profile format, which the Firefox
+//! Profiler (<https://profiler.firefox.com>) can load directly.
+//!
+//! <https://github.com/firefox-devtools/profiler/blob/main/docs-developer/gecko-profile-format.md>
+
+use msg::constellation_msg::{HangAnnotation, HangProfile, HangProfileSymbol};
+use msg::constellation_msg::{MonitoredComponentId, MonitoredComponentType};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The version of the Gecko profile format that is written.
+const GECKO_PROFILE_VERSION: u32 = 24;
+
+/// The marker type of the markers that record the annotation of a monitored component.
+const HANG_ANNOTATION_MARKER: &'static str = "HangAnnotation";
+
+/// A profile made of one thread per monitored component.
+pub struct GeckoProfile {
+    /// The sampling interval, in milliseconds.
+    interval: f64,
+    /// The time that sample and marker times are relative to, in milliseconds since the
+    /// Unix epoch.
+    start_time: f64,
+    threads: Vec<GeckoThread>,
+    thread_indices: HashMap<MonitoredComponentId, usize>,
+}
+
+impl GeckoProfile {
+    /// Create an empty profile whose times are relative to `start_time_ago` milliseconds
+    /// before now.
+    pub fn new(interval: f64, start_time_ago: f64) -> GeckoProfile {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0., |duration| duration.as_secs_f64() * 1000.);
+        GeckoProfile {
+            interval,
+            start_time: now - start_time_ago,
+            threads: vec![],
+            thread_indices: HashMap::new(),
+        }
+    }
+
+    /// The thread of a monitored component, created on first use. `name` is the name of
+    /// the thread the component runs on, if known.
+    pub fn thread(&mut self, id: &MonitoredComponentId, name: Option<&str>) -> &mut GeckoThread {
+        let threads = &mut self.threads;
+        let index = *self.thread_indices.entry(id.clone()).or_insert_with(|| {
+            let name = name.map_or_else(|| component_name(id), str::to_owned);
+            threads.push(GeckoThread::new(name, threads.len() + 1, id.clone()));
+            threads.len() - 1
+        });
+        &mut self.threads[index]
+    }
+
+    /// A profile of a single permanent hang, with the stack of the hanging component at the
+    /// time it was detected and a marker with its annotation.
+    pub fn for_permanent_hang(
+        id: &MonitoredComponentId,
+        annotation: HangAnnotation,
+        profile: &HangProfile,
+    ) -> GeckoProfile {
+        let mut gecko_profile = GeckoProfile::new(1., 0.);
+        let thread = gecko_profile.thread(id, None);
+        thread.add_sample(0., &profile.backtrace);
+        thread.add_annotation_marker("Permanent hang", annotation, 0., 0.);
+        gecko_profile
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "meta": {
+                "version": GECKO_PROFILE_VERSION,
+                "interval": self.interval,
+                "startTime": self.start_time,
+                "shutdownTime": null,
+                "processType": 0,
+                "product": "Servo",
+                "stackwalk": 1,
+                "debug": cfg!(debug_assertions),
+                "presymbolicated": true,
+                "categories": [
+                    {
+                        "name": "Other",
+                        "color": "grey",
+                        "subcategories": ["Other"],
+                    },
+                ],
+                "markerSchema": [
+                    {
+                        "name": HANG_ANNOTATION_MARKER,
+                        "display": ["marker-chart", "marker-table", "timeline-overview"],
+                        "tooltipLabel": "{marker.data.annotation}",
+                        "tableLabel": "{marker.data.annotation}",
+                        "chartLabel": "{marker.data.annotation}",
+                        "data": [
+                            {
+                                "key": "component",
+                                "label": "Component",
+                                "format": "string",
+                                "searchable": true,
+                            },
+                            {
+                                "key": "annotation",
+                                "label": "Annotation",
+                                "format": "string",
+                                "searchable": true,
+                            },
+                        ],
+                    },
+                ],
+            },
+            "libs": [],
+            "pausedRanges": [],
+            "processes": [],
+            "threads": self.threads.iter().map(GeckoThread::to_json).collect::<Vec<_>>(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(&self.to_json()).unwrap()
+    }
+}
+
+/// The samples and markers of one monitored component. Frames and stacks are shared
+/// between samples through the frame and stack tables of the format.
+pub struct GeckoThread {
+    name: String,
+    tid: usize,
+    component: MonitoredComponentId,
+    strings: Vec<String>,
+    string_indices: HashMap<String, usize>,
+    /// The location string and line of each frame.
+    frames: Vec<(usize, Option<u32>)>,
+    frame_indices: HashMap<(usize, Option<u32>), usize>,
+    /// The frame and the prefix stack of each stack.
+    stacks: Vec<(usize, Option<usize>)>,
+    stack_indices: HashMap<(usize, Option<usize>), usize>,
+    /// The stack and time of each sample.
+    samples: Vec<(Option<usize>, f64)>,
+    /// The name string, start time, end time and data of each marker.
+    markers: Vec<(usize, f64, f64, Value)>,
+}
+
+impl GeckoThread {
+    fn new(name: String, tid: usize, component: MonitoredComponentId) -> GeckoThread {
+        GeckoThread {
+            name,
+            tid,
+            component,
+            strings: vec![],
+            string_indices: HashMap::new(),
+            frames: vec![],
+            frame_indices: HashMap::new(),
+            stacks: vec![],
+            stack_indices: HashMap::new(),
+            samples: vec![],
+            markers: vec![],
+        }
+    }
+
+    fn string(&mut self, string: String) -> usize {
+        if let Some(index) = self.string_indices.get(&string) {
+            return *index;
+        }
+        self.strings.push(string.clone());
+        self.string_indices.insert(string, self.strings.len() - 1);
+        self.strings.len() - 1
+    }
+
+    /// Add a sample with a backtrace ordered from the outermost frame to the innermost
+    /// one, at a time in milliseconds since the start of the profile.
+    pub fn add_sample(&mut self, time: f64, backtrace: &[HangProfileSymbol]) {
+        let mut prefix = None;
+        for symbol in backtrace {
+            let name = match symbol.name {
+                Some(ref name) => name,
+                None => continue,
+            };
+            // Native frames are identified by their function and, when known, the file
+            // it's in, which the profiler shows as the resource of the function.
+            let location = match symbol.filename {
+                Some(ref filename) => format!("{} (in {})", name, filename),
+                None => name.clone(),
+            };
+            let location = self.string(location);
+            let frames = &mut self.frames;
+            let frame = *self
+                .frame_indices
+                .entry((location, symbol.lineno))
+                .or_insert_with(|| {
+                    frames.push((location, symbol.lineno));
+                    frames.len() - 1
+                });
+            let stacks = &mut self.stacks;
+            let stack = *self
+                .stack_indices
+                .entry((frame, prefix))
+                .or_insert_with(|| {
+                    stacks.push((frame, prefix));
+                    stacks.len() - 1
+                });
+            prefix = Some(stack);
+        }
+        self.samples.push((prefix, time));
+    }
+
+    /// Add an interval marker with the annotation of the component, between two times in
+    /// milliseconds since the start of the profile.
+    pub fn add_annotation_marker(
+        &mut self,
+        name: &str,
+        annotation: HangAnnotation,
+        start: f64,
+        end: f64,
+    ) {
+        let name = self.string(name.to_owned());
+        let data = json!({
+            "type": HANG_ANNOTATION_MARKER,
+            "component": component_name(&self.component),
+            "annotation": format!("{:?}", annotation),
+        });
+        self.markers.push((name, start, end, data));
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "processType": "default",
+            "processName": "Servo",
+            "pid": process::id(),
+            "tid": self.tid,
+            "registerTime": 0,
+            "unregisterTime": null,
+            "samples": {
+                "schema": { "stack": 0, "time": 1, "eventDelay": 2 },
+                "data": self.samples
+                    .iter()
+                    .map(|&(stack, time)| json!([stack, time, 0]))
+                    .collect::<Vec<_>>(),
+            },
+            "markers": {
+                "schema": {
+                    "name": 0,
+                    "startTime": 1,
+                    "endTime": 2,
+                    "phase": 3,
+                    "category": 4,
+                    "data": 5,
+                },
+                // Phase 1 is an interval marker.
+                "data": self.markers
+                    .iter()
+                    .map(|(name, start, end, data)| json!([name, start, end, 1, 0, data]))
+                    .collect::<Vec<_>>(),
+            },
+            "stackTable": {
+                "schema": { "prefix": 0, "frame": 1 },
+                "data": self.stacks
+                    .iter()
+                    .map(|&(frame, prefix)| json!([prefix, frame]))
+                    .collect::<Vec<_>>(),
+            },
+            "frameTable": {
+                "schema": {
+                    "location": 0,
+                    "relevantForJS": 1,
+                    "innerWindowID": 2,
+                    "implementation": 3,
+                    "line": 4,
+                    "column": 5,
+                    "category": 6,
+                    "subcategory": 7,
+                },
+                "data": self.frames
+                    .iter()
+                    .map(|&(location, line)| {
+                        json!([location, false, 0, null, line, null, 0, 0])
+                    })
+                    .collect::<Vec<_>>(),
+            },
+            "stringTable": self.strings,
+        })
+    }
+}
+
+/// A name for a component whose thread has no name.
+fn component_name(id: &MonitoredComponentId) -> String {
+    let component_type = match id.1 {
+        MonitoredComponentType::Layout => "Layout",
+        MonitoredComponentType::Script => "Script",
+    };
+    format!("{} {}", component_type, id.0)
+}
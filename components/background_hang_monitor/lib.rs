@@ -10,6 +10,7 @@ extern crate crossbeam_channel;
 extern crate log;
 
 pub mod background_hang_monitor;
+pub mod gecko_profile;
 mod sampler;
 #[cfg(all(
     target_os = "linux",
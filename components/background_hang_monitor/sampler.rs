@@ -80,19 +80,21 @@ impl NativeStack {
             if ip.is_null() {
                 continue;
             }
+            // An instruction pointer resolves to the innermost inlined function first,
+            // while the backtrace is ordered from the outermost frame.
+            let mut symbols = vec![];
             backtrace::resolve(*ip, |symbol| {
-                // TODO: use the demangled or C++ demangled symbols if available.
-                let name = symbol
-                    .name()
-                    .map(|n| String::from_utf8_lossy(&n.as_bytes()).to_string());
+                // The Display impl of the symbol name demangles Rust and C++ symbols.
+                let name = symbol.name().map(|n| n.to_string());
                 let filename = symbol.filename().map(|n| n.to_string_lossy().to_string());
                 let lineno = symbol.lineno();
-                profile.backtrace.push(HangProfileSymbol {
+                symbols.push(HangProfileSymbol {
                     name,
                     filename,
                     lineno,
                 });
             });
+            profile.backtrace.extend(symbols.into_iter().rev());
         }
         profile
     }
@@ -5,13 +5,14 @@
 #[macro_use]
 extern crate lazy_static;
 
+use background_hang_monitor::gecko_profile::GeckoProfile;
 use background_hang_monitor::HangMonitorRegister;
 use ipc_channel::ipc;
 use msg::constellation_msg::ScriptHangAnnotation;
 use msg::constellation_msg::TEST_PIPELINE_ID;
 use msg::constellation_msg::{
     BackgroundHangMonitorControlMsg, BackgroundHangMonitorExitSignal, HangAlert, HangAnnotation,
-    HangMonitorAlert,
+    HangMonitorAlert, HangProfile, HangProfileSymbol,
 };
 use msg::constellation_msg::{MonitoredComponentId, MonitoredComponentType};
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -212,3 +213,62 @@ fn test_hang_monitoring_exit_signal() {
         }
     }
 }
+
+#[test]
+fn test_permanent_hang_gecko_profile() {
+    let symbol = |name: &str, lineno| HangProfileSymbol {
+        name: Some(name.to_owned()),
+        filename: Some("script_thread.rs".to_owned()),
+        lineno: Some(lineno),
+    };
+    let profile = HangProfile {
+        backtrace: vec![
+            symbol("main", 1),
+            symbol("handle_msgs", 2),
+            symbol("spin", 3),
+        ],
+    };
+    let profile = GeckoProfile::for_permanent_hang(
+        &MonitoredComponentId(TEST_PIPELINE_ID, MonitoredComponentType::Script),
+        HangAnnotation::Script(ScriptHangAnnotation::TimerEvent),
+        &profile,
+    );
+    let json: serde_json::Value = serde_json::from_slice(&profile.to_bytes()).unwrap();
+
+    assert_eq!(json["meta"]["presymbolicated"], true);
+    let threads = json["threads"].as_array().unwrap();
+    assert_eq!(threads.len(), 1);
+    let thread = &threads[0];
+    let strings = thread["stringTable"].as_array().unwrap();
+    let frames = thread["frameTable"]["data"].as_array().unwrap();
+    let stacks = thread["stackTable"]["data"].as_array().unwrap();
+    let samples = thread["samples"]["data"].as_array().unwrap();
+    assert_eq!(frames.len(), 3);
+    assert_eq!(stacks.len(), 3);
+    assert_eq!(samples.len(), 1);
+
+    // Walk the sampled stack from the innermost frame to the root.
+    let mut stack = samples[0][0].clone();
+    let mut locations = vec![];
+    while let Some(index) = stack.as_u64() {
+        let frame = &frames[stacks[index as usize][1].as_u64().unwrap() as usize];
+        locations.push(strings[frame[0].as_u64().unwrap() as usize].clone());
+        stack = stacks[index as usize][0].clone();
+    }
+    assert_eq!(
+        locations,
+        vec![
+            "spin (in script_thread.rs)",
+            "handle_msgs (in script_thread.rs)",
+            "main (in script_thread.rs)",
+        ]
+    );
+
+    let markers = thread["markers"]["data"].as_array().unwrap();
+    assert_eq!(markers.len(), 1);
+    assert_eq!(
+        strings[markers[0][0].as_u64().unwrap() as usize],
+        "Permanent hang"
+    );
+    assert_eq!(markers[0][5]["annotation"], "Script(TimerEvent)");
+}
@@ -19,7 +19,7 @@ use embedder_traits::Cursor;
 use euclid::{Point2D, Rect, Scale, Vector2D};
 use gfx_traits::{Epoch, FontData};
 #[cfg(feature = "gl")]
-use image::{DynamicImage, ImageFormat};
+use image::{DynamicImage, ImageFormat, RgbaImage};
 use ipc_channel::ipc;
 use libc::c_void;
 use log::warn;
@@ -30,8 +30,10 @@ use num_traits::FromPrimitive;
 #[cfg(feature = "gl")]
 use pixels::PixelFormat;
 use profile_traits::time::{self as profile_time, profile, ProfilerCategory};
+use profile_traits::time::{TraceFlowKind, TraceFlowPhase};
 use script_traits::CompositorEvent::{MouseButtonEvent, MouseMoveEvent, TouchEvent, WheelEvent};
-use script_traits::{AnimationState, AnimationTickType, LayoutControlMsg};
+use script_traits::LayoutControlMsg;
+use script_traits::{display_list_trace_flow_id, AnimationState, AnimationTickType};
 use script_traits::{
     MouseButton, MouseEventType, ScrollState, TouchEventType, TouchId, WheelDelta,
 };
@@ -41,13 +43,23 @@ use std::collections::HashMap;
 use std::env;
 use std::fs::{create_dir_all, File};
 use std::io::Write;
+use std::iter;
+use std::mem;
 use std::num::NonZeroU32;
 use std::rc::Rc;
+#[cfg(feature = "gl")]
+use std::time::{Duration, Instant};
 use style_traits::viewport::ViewportConstraints;
 use style_traits::{CSSPixel, DevicePixel, PinchZoomFactor};
 use time::{now, precise_time_ns, precise_time_s};
-use webrender_api::units::{DeviceIntPoint, DeviceIntSize, DevicePoint, LayoutVector2D};
+#[cfg(feature = "gl")]
+use webrender_api::units::LayoutPoint;
+use webrender_api::units::{
+    DeviceIntPoint, DeviceIntSize, DevicePoint, LayoutSize, LayoutVector2D,
+};
 use webrender_api::{self, HitTestFlags, HitTestResult, ScrollLocation};
+#[cfg(feature = "gl")]
+use webrender_api::{ExternalScrollId, ScrollClamping};
 use webrender_surfman::WebrenderSurfman;
 
 #[derive(Debug, PartialEq)]
@@ -66,6 +78,14 @@ enum NotReadyToPaint {
 const MAX_ZOOM: f32 = 8.0;
 const MIN_ZOOM: f32 = 0.1;
 
+/// The largest width or height, in device pixels, of a full page capture.
+#[cfg(feature = "gl")]
+const MAX_FULL_PAGE_CAPTURE_SIZE: i32 = 16384;
+
+/// How long to wait for WebRender to build a scrolled frame of a full page capture.
+#[cfg(feature = "gl")]
+const SCROLL_FRAME_TIMEOUT: Duration = Duration::from_secs(1);
+
 trait ConvertPipelineIdFromWebRender {
     fn from_webrender(&self) -> PipelineId;
 }
@@ -220,6 +240,10 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
     /// taken before the render is complete will not reflect the
     /// most up to date rendering.
     waiting_on_pending_frame: bool,
+
+    /// Messages received while waiting for WebRender to build the frames of a full-page
+    /// capture. They are handled in order once the capture is done.
+    deferred_messages: Vec<Msg>,
 }
 
 #[derive(Clone, Copy)]
@@ -259,6 +283,9 @@ struct PipelineDetails {
 
     /// Whether this pipeline is visible
     visible: bool,
+
+    /// The size of the content of the last display list of this pipeline
+    content_size: LayoutSize,
 }
 
 impl PipelineDetails {
@@ -268,6 +295,7 @@ impl PipelineDetails {
             animations_running: false,
             animation_callbacks_running: false,
             visible: true,
+            content_size: LayoutSize::zero(),
         }
     }
 }
@@ -280,6 +308,9 @@ enum CompositeTarget {
     /// Compose as normal, but also return a PNG of the composed output
     WindowAndPng,
 
+    /// Compose as normal, but also return the RGBA pixels of the composed output
+    WindowAndRgba,
+
     /// Compose to a PNG, write it to disk, and then exit the browser (used for reftests)
     PngFile,
 }
@@ -336,6 +367,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             exit_after_load,
             convert_mouse_to_touch,
             waiting_on_pending_frame: false,
+            deferred_messages: Vec::new(),
         }
     }
 
@@ -463,6 +495,13 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 }
             },
 
+            (Msg::CreateFullPageImage(reply), ShutdownState::NotShuttingDown) => {
+                let img = self.capture_full_page();
+                if let Err(e) = reply.send(img) {
+                    warn!("Sending reply to create full page image failed ({:?}).", e);
+                }
+            },
+
             (
                 Msg::ViewportConstrained(pipeline_id, constraints),
                 ShutdownState::NotShuttingDown,
@@ -621,6 +660,14 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 descriptor,
             )) => {
                 self.waiting_on_pending_frame = true;
+                profile_time::send_trace_flow(
+                    TraceFlowKind::DisplayList,
+                    display_list_trace_flow_id(pipeline, epoch),
+                    TraceFlowPhase::End,
+                    &self.time_profiler_chan,
+                );
+                self.pipeline_details(PipelineId::from_webrender(pipeline))
+                    .content_size = size2;
                 let mut txn = webrender_api::Transaction::new();
                 txn.set_display_list(
                     epoch,
@@ -1419,7 +1466,9 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         self.webrender.update();
 
         let wait_for_stable_image = match target {
-            CompositeTarget::WindowAndPng | CompositeTarget::PngFile => true,
+            CompositeTarget::WindowAndPng |
+            CompositeTarget::WindowAndRgba |
+            CompositeTarget::PngFile => true,
             CompositeTarget::Window => self.exit_after_load,
         };
 
@@ -1442,7 +1491,9 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             #[cfg(feature = "gl")]
             CompositeTarget::Window => gl::RenderTargetInfo::default(),
             #[cfg(feature = "gl")]
-            CompositeTarget::WindowAndPng | CompositeTarget::PngFile => gl::initialize_png(
+            CompositeTarget::WindowAndPng |
+            CompositeTarget::WindowAndRgba |
+            CompositeTarget::PngFile => gl::initialize_png(
                 &*self.webrender_gl,
                 FramebufferUintLength::new(size.width),
                 FramebufferUintLength::new(size.height),
@@ -1541,6 +1592,25 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 })
             },
             #[cfg(feature = "gl")]
+            CompositeTarget::WindowAndRgba => {
+                let img = gl::draw_rgba_img(
+                    &*self.webrender_gl,
+                    rt_info,
+                    x,
+                    y,
+                    FramebufferUintLength::new(width),
+                    FramebufferUintLength::new(height),
+                );
+                Some(Image {
+                    width: img.width(),
+                    height: img.height(),
+                    format: PixelFormat::RGBA8,
+                    bytes: ipc::IpcSharedMemory::from_bytes(&*img),
+                    id: None,
+                    cors_status: CorsStatus::Safe,
+                })
+            },
+            #[cfg(feature = "gl")]
             CompositeTarget::PngFile => {
                 let gl = &*self.webrender_gl;
                 profile(
@@ -1653,7 +1723,9 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         // Check for new messages coming from the other threads in the system.
         let mut compositor_messages = vec![];
         let mut found_recomposite_msg = false;
-        while let Some(msg) = self.port.try_recv_compositor_msg() {
+        let deferred_messages = mem::replace(&mut self.deferred_messages, vec![]);
+        let received_messages = iter::from_fn(|| self.port.try_recv_compositor_msg());
+        for msg in deferred_messages.into_iter().chain(received_messages) {
             match msg {
                 Msg::Recomposite(_) if found_recomposite_msg => {},
                 Msg::Recomposite(_) => {
@@ -1668,6 +1740,14 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 return false;
             }
         }
+        // A full-page capture may have deferred more messages while handling these.
+        while !self.deferred_messages.is_empty() {
+            for msg in mem::replace(&mut self.deferred_messages, vec![]) {
+                if !self.handle_browser_message(msg) {
+                    return false;
+                }
+            }
+        }
         true
     }
 
@@ -1720,6 +1800,187 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         }
     }
 
+    /// Composite and return the RGBA pixels of the viewport, or of a rect of it, once the
+    /// output is stable. Returns `None` while loads, layout or animations are still
+    /// pending; the embedder should try again after the next paint or animation tick.
+    pub fn capture_frame(&mut self, rect: Option<Rect<f32, CSSPixel>>) -> Option<Image> {
+        match self.composite_specific_target(CompositeTarget::WindowAndRgba, rect) {
+            Ok(image) => image,
+            Err(e) => {
+                debug!("Unable to capture frame ({:?}).", e);
+                None
+            },
+        }
+    }
+
+    /// Capture the whole scrollable area of the root document, by scrolling its root scroll
+    /// node over it one viewport at a time and stitching the rendered tiles together. The
+    /// scroll position is restored afterwards. Script isn't told about the scrolling, so
+    /// content that doesn't scroll with the document, like fixed position elements, is
+    /// painted in every tile. Returns `None` until the output is stable, like
+    /// `capture_frame`.
+    #[cfg(feature = "gl")]
+    pub fn capture_full_page(&mut self) -> Option<Image> {
+        // Only start scrolling once the output is stable.
+        self.capture_frame(None)?;
+
+        let pipeline_id = self.get_root_pipeline_id()?;
+        let scroll_id = pipeline_id.root_scroll_id();
+        let scale = self.device_pixels_per_page_px().get();
+        let viewport = self.embedder_coordinates.viewport;
+        let content_size = self.pipeline_details(pipeline_id).content_size;
+        let page_width = ((content_size.width * scale).ceil() as i32)
+            .min(MAX_FULL_PAGE_CAPTURE_SIZE)
+            .max(viewport.size.width);
+        let page_height = ((content_size.height * scale).ceil() as i32)
+            .min(MAX_FULL_PAGE_CAPTURE_SIZE)
+            .max(viewport.size.height);
+
+        let original_offset = self
+            .webrender_api
+            .get_scroll_node_state(self.webrender_document)
+            .into_iter()
+            .find(|state| state.id == scroll_id)
+            .map_or(LayoutVector2D::zero(), |state| state.scroll_offset);
+
+        let stride = page_width as usize * 4;
+        let tile_stride = viewport.size.width as usize * 4;
+        let mut pixels = vec![0; stride * page_height as usize];
+        let mut tile_y = 0;
+        while tile_y < page_height {
+            // The last row and column of tiles end at the end of the page instead of
+            // scrolling past it, and overlap the previous ones.
+            let y = tile_y.min(page_height - viewport.size.height);
+            let mut tile_x = 0;
+            while tile_x < page_width {
+                let x = tile_x.min(page_width - viewport.size.width);
+                if !self.scroll_root_synchronously(
+                    scroll_id,
+                    LayoutPoint::new(x as f32 / scale, y as f32 / scale),
+                ) {
+                    return None;
+                }
+                let tile = self.render_to_rgba();
+                let framebuffer_stride = tile.width() as usize * 4;
+                for row in 0..viewport.size.height as usize {
+                    let src = (viewport.origin.y as usize + row) * framebuffer_stride +
+                        viewport.origin.x as usize * 4;
+                    let dst = (y as usize + row) * stride + x as usize * 4;
+                    pixels[dst..dst + tile_stride].copy_from_slice(&tile[src..src + tile_stride]);
+                }
+                tile_x += viewport.size.width;
+            }
+            tile_y += viewport.size.height;
+        }
+
+        if !self.scroll_root_synchronously(
+            scroll_id,
+            LayoutPoint::new(-original_offset.x, -original_offset.y),
+        ) {
+            return None;
+        }
+
+        Some(Image {
+            width: page_width as u32,
+            height: page_height as u32,
+            format: PixelFormat::RGBA8,
+            bytes: ipc::IpcSharedMemory::from_bytes(&pixels),
+            id: None,
+            cors_status: CorsStatus::Safe,
+        })
+    }
+
+    #[cfg(not(feature = "gl"))]
+    pub fn capture_full_page(&mut self) -> Option<Image> {
+        None
+    }
+
+    /// Scroll a scroll node, and wait until WebRender has built the scrolled frame, or
+    /// for at most `SCROLL_FRAME_TIMEOUT`. Other compositor messages received in the
+    /// meantime aren't handled here, since they could change the page being captured or
+    /// start another capture; they are deferred until the next call to `receive_messages`.
+    /// Returns false if the compositor finished shutting down while waiting.
+    #[cfg(feature = "gl")]
+    fn scroll_root_synchronously(
+        &mut self,
+        scroll_id: ExternalScrollId,
+        origin: LayoutPoint,
+    ) -> bool {
+        // WebRender only notifies us of a scrolled frame if the scroll node moved.
+        let offset = LayoutVector2D::new(-origin.x, -origin.y);
+        let current_offset = self
+            .webrender_api
+            .get_scroll_node_state(self.webrender_document)
+            .into_iter()
+            .find(|state| state.id == scroll_id)
+            .map(|state| state.scroll_offset);
+        match current_offset {
+            Some(current_offset) if current_offset != offset => {},
+            _ => return true,
+        }
+
+        let mut txn = webrender_api::Transaction::new();
+        txn.scroll_node_with_id(origin, scroll_id, ScrollClamping::NoClamping);
+        txn.generate_frame();
+        self.webrender_api
+            .send_transaction(self.webrender_document, txn);
+
+        let deadline = Instant::now() + SCROLL_FRAME_TIMEOUT;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.port.recv_compositor_msg_timeout(timeout) {
+                Some(msg @ Msg::NewScrollFrameReady(_)) => {
+                    self.handle_browser_message(msg);
+                    return true;
+                },
+                // The constellation waits for these while shutting down.
+                Some(msg @ Msg::PipelineExited(..)) => {
+                    self.handle_browser_message(msg);
+                },
+                Some(msg @ Msg::ShutdownComplete) => {
+                    self.handle_browser_message(msg);
+                    return false;
+                },
+                Some(msg) => self.deferred_messages.push(msg),
+                None => {
+                    warn!("Timed out waiting for a scrolled frame.");
+                    return true;
+                },
+            }
+        }
+    }
+
+    /// Render the current WebRender frame to an offscreen framebuffer and read back its
+    /// RGBA pixels, without presenting it or waiting for the output to be stable.
+    #[cfg(feature = "gl")]
+    fn render_to_rgba(&mut self) -> RgbaImage {
+        let size = self.embedder_coordinates.framebuffer.to_u32();
+        if let Err(err) = self.webrender_surfman.make_gl_context_current() {
+            warn!("Failed to make GL context current: {:?}", err);
+        }
+        self.webrender.update();
+
+        let rt_info = gl::initialize_png(
+            &*self.webrender_gl,
+            FramebufferUintLength::new(size.width),
+            FramebufferUintLength::new(size.height),
+        );
+        self.clear_background();
+        self.webrender
+            .render(DeviceIntSize::from_untyped(
+                self.embedder_coordinates.framebuffer.to_untyped(),
+            ))
+            .ok();
+        gl::draw_rgba_img(
+            &*self.webrender_gl,
+            rt_info,
+            0,
+            0,
+            FramebufferUintLength::new(size.width),
+            FramebufferUintLength::new(size.height),
+        )
+    }
+
     pub fn pinch_zoom_level(&self) -> f32 {
         self.viewport_zoom.get()
     }
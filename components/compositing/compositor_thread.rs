@@ -19,6 +19,7 @@ use profile_traits::time;
 use script_traits::{AnimationState, EventResult, MouseButton, MouseEventType};
 use std::fmt::{Debug, Error, Formatter};
 use std::rc::Rc;
+use std::time::Duration;
 use style_traits::viewport::ViewportConstraints;
 use style_traits::CSSPixel;
 use webrender_api;
@@ -61,6 +62,9 @@ impl CompositorReceiver {
     pub fn recv_compositor_msg(&mut self) -> Msg {
         self.receiver.recv().unwrap()
     }
+    pub fn recv_compositor_msg_timeout(&mut self, timeout: Duration) -> Option<Msg> {
+        self.receiver.recv_timeout(timeout).ok()
+    }
 }
 
 impl CompositorProxy {
@@ -85,6 +89,8 @@ pub enum Msg {
     TouchEventProcessed(EventResult),
     /// Composite to a PNG file and return the Image over a passed channel.
     CreatePng(Option<Rect<f32, CSSPixel>>, IpcSender<Option<Image>>),
+    /// Capture an RGBA image of the whole scrollable area of the root document.
+    CreateFullPageImage(IpcSender<Option<Image>>),
     /// Alerts the compositor that the viewport has been constrained in some manner
     ViewportConstrained(PipelineId, ViewportConstraints),
     /// A reply to the compositor asking if the output image is stable.
@@ -158,6 +164,7 @@ impl Debug for Msg {
             Msg::Recomposite(..) => write!(f, "Recomposite"),
             Msg::TouchEventProcessed(..) => write!(f, "TouchEventProcessed"),
             Msg::CreatePng(..) => write!(f, "CreatePng"),
+            Msg::CreateFullPageImage(..) => write!(f, "CreateFullPageImage"),
             Msg::ViewportConstrained(..) => write!(f, "ViewportConstrained"),
             Msg::IsReadyToSaveImageReply(..) => write!(f, "IsReadyToSaveImageReply"),
             Msg::PipelineVisibilityChanged(..) => write!(f, "PipelineVisibilityChanged"),
@@ -3,7 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use gleam::gl;
-use image::RgbImage;
+use image::{RgbImage, RgbaImage};
 use servo_geometry::FramebufferUintLength;
 
 #[derive(Default)]
@@ -87,6 +87,32 @@ pub fn draw_img(
     width: FramebufferUintLength,
     height: FramebufferUintLength,
 ) -> RgbImage {
+    let pixels = read_img(gl, render_target_info, x, y, width, height, gl::RGB, 3);
+    RgbImage::from_raw(width.get(), height.get(), pixels).expect("Flipping image failed!")
+}
+
+pub fn draw_rgba_img(
+    gl: &dyn gl::Gl,
+    render_target_info: RenderTargetInfo,
+    x: i32,
+    y: i32,
+    width: FramebufferUintLength,
+    height: FramebufferUintLength,
+) -> RgbaImage {
+    let pixels = read_img(gl, render_target_info, x, y, width, height, gl::RGBA, 4);
+    RgbaImage::from_raw(width.get(), height.get(), pixels).expect("Flipping image failed!")
+}
+
+fn read_img(
+    gl: &dyn gl::Gl,
+    render_target_info: RenderTargetInfo,
+    x: i32,
+    y: i32,
+    width: FramebufferUintLength,
+    height: FramebufferUintLength,
+    format: gl::GLenum,
+    bytes_per_pixel: usize,
+) -> Vec<u8> {
     let width = width.get() as usize;
     let height = height.get() as usize;
     // For some reason, OSMesa fails to render on the 3rd
@@ -102,7 +128,7 @@ pub fn draw_img(
         y,
         width as gl::GLsizei,
         height as gl::GLsizei,
-        gl::RGB,
+        format,
         gl::UNSIGNED_BYTE,
     );
 
@@ -114,7 +140,7 @@ pub fn draw_img(
 
     // flip image vertically (texture is upside down)
     let orig_pixels = pixels.clone();
-    let stride = width * 3;
+    let stride = width * bytes_per_pixel;
     for y in 0..height {
         let dst_start = y * stride;
         let src_start = (height - y - 1) * stride;
@@ -122,5 +148,5 @@ pub fn draw_img(
         (&mut pixels[dst_start..dst_start + stride]).clone_from_slice(&src_slice[..stride]);
     }
 
-    RgbImage::from_raw(width as u32, height as u32, pixels).expect("Flipping image failed!")
+    pixels
 }
@@ -11,16 +11,18 @@ pub use crate::compositor::CompositingReason;
 pub use crate::compositor::IOCompositor;
 pub use crate::compositor::ShutdownState;
 pub use crate::compositor_thread::CompositorProxy;
-use embedder_traits::Cursor;
+use embedder_traits::{Cursor, MessageToPage};
 use gfx_traits::Epoch;
 use ipc_channel::ipc::IpcSender;
 use keyboard_types::KeyboardEvent;
 use msg::constellation_msg::PipelineId;
 use msg::constellation_msg::TopLevelBrowsingContextId;
 use msg::constellation_msg::{BrowsingContextId, TraversalDirection};
+use net_traits::{BrowsingProfile, SiteDataRequest};
 use script_traits::{
     AnimationTickType, LogEntry, WebDriverCommandMsg, WindowSizeData, WindowSizeType,
 };
+use script_traits::{BrowserSessionState, PageDisplayList, SessionState};
 use script_traits::{
     CompositorEvent, ConstellationControlMsg, LayoutControlMsg, MediaSessionActionType,
 };
@@ -73,6 +75,8 @@ pub enum ConstellationMsg {
     LoadUrl(TopLevelBrowsingContextId, ServoUrl),
     /// Clear the network cache.
     ClearCache,
+    /// The system is running low on memory; purge caches in every component.
+    MemoryPressure,
     /// Request to traverse the joint session history of the provided browsing context.
     TraverseHistory(TopLevelBrowsingContextId, TraversalDirection),
     /// Inform the constellation of a window being resized.
@@ -91,6 +95,8 @@ pub enum ConstellationMsg {
     LogEntry(Option<TopLevelBrowsingContextId>, Option<String>, LogEntry),
     /// Create a new top level browsing context.
     NewBrowser(ServoUrl, TopLevelBrowsingContextId),
+    /// Create a new top level browsing context in an isolated browsing profile.
+    NewBrowserWithProfile(ServoUrl, TopLevelBrowsingContextId, BrowsingProfile),
     /// Close a top level browsing context.
     CloseBrowser(TopLevelBrowsingContextId),
     /// Panic a top level browsing context.
@@ -113,6 +119,17 @@ pub enum ConstellationMsg {
     ChangeBrowserVisibility(TopLevelBrowsingContextId, bool),
     /// Virtual keyboard was dismissed
     IMEDismissed,
+    /// Post a message to the embedder messaging channel of a document.
+    PostMessageToPage(TopLevelBrowsingContextId, Option<PipelineId>, MessageToPage),
+    /// Inspect or remove the site data of a browser's profile.
+    SiteData(TopLevelBrowsingContextId, SiteDataRequest),
+    /// Request the saved state of all the top-level browsing contexts.
+    GetSessionState(IpcSender<SessionState>),
+    /// Create a new top level browsing context from its saved state.
+    RestoreBrowser(TopLevelBrowsingContextId, BrowserSessionState),
+    /// Request the display list of each page of the document of a top-level browsing context,
+    /// laid out for printing.
+    GetPageDisplayLists(TopLevelBrowsingContextId, IpcSender<Vec<PageDisplayList>>),
 }
 
 impl fmt::Debug for ConstellationMsg {
@@ -134,6 +151,7 @@ impl fmt::Debug for ConstellationMsg {
             Reload(..) => "Reload",
             LogEntry(..) => "LogEntry",
             NewBrowser(..) => "NewBrowser",
+            NewBrowserWithProfile(..) => "NewBrowserWithProfile",
             CloseBrowser(..) => "CloseBrowser",
             SendError(..) => "SendError",
             SelectBrowser(..) => "SelectBrowser",
@@ -145,7 +163,13 @@ impl fmt::Debug for ConstellationMsg {
             MediaSessionAction(..) => "MediaSessionAction",
             ChangeBrowserVisibility(..) => "ChangeBrowserVisibility",
             IMEDismissed => "IMEDismissed",
+            PostMessageToPage(..) => "PostMessageToPage",
+            SiteData(..) => "SiteData",
             ClearCache => "ClearCache",
+            MemoryPressure => "MemoryPressure",
+            GetSessionState(..) => "GetSessionState",
+            RestoreBrowser(..) => "RestoreBrowser",
+            GetPageDisplayLists(..) => "GetPageDisplayLists",
         };
         write!(formatter, "ConstellationMsg::{}", variant)
     }
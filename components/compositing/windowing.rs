@@ -4,11 +4,13 @@
 
 //! Abstract windowing methods. The concrete implementations of these can be found in `platform/`.
 
-use embedder_traits::{EmbedderProxy, EventLoopWaker};
+use embedder_traits::{EmbedderProxy, EventLoopWaker, MessageToPage};
 use euclid::Scale;
 use keyboard_types::KeyboardEvent;
 use msg::constellation_msg::{PipelineId, TopLevelBrowsingContextId, TraversalDirection};
-use script_traits::{MediaSessionActionType, MouseButton, TouchEventType, TouchId, WheelDelta};
+use net_traits::{BrowsingProfile, SiteDataRequest};
+use script_traits::{BrowserSessionState, MediaSessionActionType, MouseButton, TouchEventType};
+use script_traits::{TouchId, WheelDelta};
 use servo_geometry::DeviceIndependentPixel;
 use servo_media::player::context::{GlApi, GlContext, NativeDisplay};
 use servo_url::ServoUrl;
@@ -84,6 +86,13 @@ pub enum WindowEvent {
     Reload(TopLevelBrowsingContextId),
     /// Create a new top level browsing context
     NewBrowser(ServoUrl, TopLevelBrowsingContextId),
+    /// Create a new top level browsing context in an isolated browsing profile, which has
+    /// its own cookies, storage and caches. Browsers created with the same profile id
+    /// share them.
+    NewBrowserWithProfile(ServoUrl, TopLevelBrowsingContextId, BrowsingProfile),
+    /// Create a new top level browsing context from the saved state of a browser, loading
+    /// its current entry and restoring the rest of its session history.
+    RestoreBrowser(TopLevelBrowsingContextId, BrowserSessionState),
     /// Close a top level browsing context
     CloseBrowser(TopLevelBrowsingContextId),
     /// Panic a top level browsing context.
@@ -97,6 +106,8 @@ pub enum WindowEvent {
     CaptureWebRender,
     /// Clear the network cache.
     ClearCache,
+    /// The system is running low on memory; purge caches in every component.
+    MemoryPressure,
     /// Toggle sampling profiler with the given sampling rate and max duration.
     ToggleSamplingProfiler(Duration, Duration),
     /// Sent when the user triggers a media action through the UA exposed media UI
@@ -106,6 +117,13 @@ pub enum WindowEvent {
     ChangeBrowserVisibility(TopLevelBrowsingContextId, bool),
     /// Virtual keyboard was dismissed
     IMEDismissed,
+    /// Post a message to the embedder messaging channel of a document.
+    /// Without a pipeline, the message is delivered to the active document of the
+    /// top level browsing context.
+    PostMessageToPage(TopLevelBrowsingContextId, Option<PipelineId>, MessageToPage),
+    /// Inspect or remove the cookies, web storage, HSTS entries or cached credentials
+    /// of the profile a browser belongs to.
+    SiteData(TopLevelBrowsingContextId, SiteDataRequest),
 }
 
 impl Debug for WindowEvent {
@@ -129,6 +147,8 @@ impl Debug for WindowEvent {
             WindowEvent::Quit => write!(f, "Quit"),
             WindowEvent::Reload(..) => write!(f, "Reload"),
             WindowEvent::NewBrowser(..) => write!(f, "NewBrowser"),
+            WindowEvent::NewBrowserWithProfile(..) => write!(f, "NewBrowserWithProfile"),
+            WindowEvent::RestoreBrowser(..) => write!(f, "RestoreBrowser"),
             WindowEvent::SendError(..) => write!(f, "SendError"),
             WindowEvent::CloseBrowser(..) => write!(f, "CloseBrowser"),
             WindowEvent::SelectBrowser(..) => write!(f, "SelectBrowser"),
@@ -139,7 +159,10 @@ impl Debug for WindowEvent {
             WindowEvent::MediaSessionAction(..) => write!(f, "MediaSessionAction"),
             WindowEvent::ChangeBrowserVisibility(..) => write!(f, "ChangeBrowserVisibility"),
             WindowEvent::IMEDismissed => write!(f, "IMEDismissed"),
+            WindowEvent::PostMessageToPage(..) => write!(f, "PostMessageToPage"),
+            WindowEvent::SiteData(..) => write!(f, "SiteData"),
             WindowEvent::ClearCache => write!(f, "ClearCache"),
+            WindowEvent::MemoryPressure => write!(f, "MemoryPressure"),
         }
     }
 }
@@ -131,6 +131,10 @@ pub struct Opts {
     /// Whether we're running inside the sandbox.
     pub sandbox: bool,
 
+    /// Whether the content process sandbox reports the system calls it denies, rather than
+    /// killing the process that makes them.
+    pub sandbox_report_syscalls: bool,
+
     /// Probability of randomly closing a pipeline,
     /// used for testing the hardening of the constellation.
     pub random_pipeline_closure_probability: Option<f32>,
@@ -307,6 +311,10 @@ pub struct DebugOptions {
     /// True to use OS native signposting facilities. This makes profiling events (script activity,
     /// reflow, compositing, etc.) appear in Instruments.app on macOS.
     pub signpost: bool,
+
+    /// Report the system calls denied by the content process sandbox instead of killing
+    /// the process.
+    pub sandbox_report_syscalls: bool,
 }
 
 impl DebugOptions {
@@ -340,6 +348,7 @@ impl DebugOptions {
                 "full-backtraces" => self.full_backtraces = true,
                 "precache-shaders" => self.precache_shaders = true,
                 "signpost" => self.signpost = true,
+                "sandbox-report-syscalls" => self.sandbox_report_syscalls = true,
                 "" => {},
                 _ => return Err(String::from(option)),
             };
@@ -431,6 +440,11 @@ fn print_debug_usage(app: &str) -> ! {
         "signpost",
         "Emit native OS signposts for profile events (currently macOS only)",
     );
+    print_option(
+        "sandbox-report-syscalls",
+        "Report system calls denied by the content process sandbox instead of killing \
+         the process (currently Linux only)",
+    );
 
     println!("");
 
@@ -489,6 +503,7 @@ pub fn default_opts() -> Opts {
         random_pipeline_closure_probability: None,
         random_pipeline_closure_seed: None,
         sandbox: false,
+        sandbox_report_syscalls: false,
         dump_style_tree: false,
         dump_rule_tree: false,
         dump_flow_tree: false,
@@ -534,7 +549,8 @@ pub fn from_cmdline_args(mut opts: Options, args: &[String]) -> ArgumentParsingR
     opts.optflagopt(
         "",
         "profiler-trace-path",
-        "Path to dump a self-contained HTML timeline of profiler traces",
+        "Path to dump a self-contained HTML timeline of profiler traces, \
+         or a Chrome trace event file if the path ends in .json",
         "",
     );
     opts.optflagopt(
@@ -872,6 +888,7 @@ pub fn from_cmdline_args(mut opts: Options, args: &[String]) -> ArgumentParsingR
         multiprocess: opt_match.opt_present("M"),
         background_hang_monitor: opt_match.opt_present("B"),
         sandbox: opt_match.opt_present("S"),
+        sandbox_report_syscalls: debug_options.sandbox_report_syscalls,
         random_pipeline_closure_probability: random_pipeline_closure_probability,
         random_pipeline_closure_seed: random_pipeline_closure_seed,
         show_debug_fragment_borders: debug_options.show_fragment_borders,
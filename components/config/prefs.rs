@@ -133,6 +133,9 @@ mod gen {
                 webgpu: {
                     enabled: bool,
                 },
+                background_throttling: {
+                    enabled: bool,
+                },
                 bluetooth: {
                     enabled: bool,
                     testing: {
@@ -157,9 +160,19 @@ mod gen {
                     dblclick_timeout: i64,
                     dblclick_dist: i64,
                 },
+                embedder_messaging: {
+                    #[serde(default)]
+                    allowed_origins: String,
+                    #[serde(default)]
+                    enabled: bool,
+                },
                 forcetouch: {
                     enabled: bool,
                 },
+                freezing: {
+                    delay_seconds: i64,
+                    enabled: bool,
+                },
                 fullscreen: {
                     test: bool,
                 },
@@ -445,6 +458,15 @@ mod gen {
                 flexbox: {
                     enabled: bool,
                 },
+                floats: {
+                    enabled: bool,
+                },
+                grid: {
+                    enabled: bool,
+                },
+                tables: {
+                    enabled: bool,
+                },
                 #[serde(default = "default_layout_threads")]
                 threads: i64,
                 viewport: {
@@ -478,8 +500,18 @@ mod gen {
                 }
             },
             session_history: {
+                bfcache: {
+                    #[serde(rename = "session-history.bfcache.enabled")]
+                    enabled: bool,
+                    #[serde(rename = "session-history.bfcache.max-memory-mb")]
+                    max_memory_mb: i64,
+                },
                 #[serde(rename = "session-history.max-length")]
                 max_length: i64,
+                restore: {
+                    #[serde(rename = "session-history.restore.enabled")]
+                    enabled: bool,
+                },
             },
             shell: {
                 crash_reporter: {
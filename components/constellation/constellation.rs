@@ -93,14 +93,16 @@ use crate::browsingcontext::NewBrowsingContextInfo;
 use crate::browsingcontext::{
     AllBrowsingContextsIterator, BrowsingContext, FullyActiveBrowsingContextsIterator,
 };
+use crate::crash_recovery::CrashRecovery;
 use crate::event_loop::EventLoop;
 use crate::network_listener::NetworkListener;
 use crate::pipeline::{InitialPipelineState, Pipeline};
 use crate::serviceworker::ServiceWorkerUnprivilegedContent;
 use crate::session_history::{
-    JointSessionHistory, NeedsToReload, SessionHistoryChange, SessionHistoryDiff,
+    JointSessionHistory, NeedsToReload, SessionEntry, SessionHistoryChange, SessionHistoryDiff,
 };
 use crate::timer_scheduler::TimerScheduler;
+use background_hang_monitor::gecko_profile::GeckoProfile;
 use background_hang_monitor::HangMonitorRegister;
 use backtrace::Backtrace;
 use bluetooth_traits::BluetoothRequest;
@@ -116,11 +118,13 @@ use devtools_traits::{
     ChromeToDevtoolsControlMsg, DevtoolsControlMsg, DevtoolsPageInfo, NavigationState,
     ScriptToDevtoolsControlMsg,
 };
-use embedder_traits::{Cursor, EmbedderMsg, EmbedderProxy, EventLoopWaker};
+use embedder_traits::{CrashReport, Cursor, EmbedderMsg, EmbedderProxy, EventLoopWaker};
 use embedder_traits::{MediaSessionEvent, MediaSessionPlaybackState};
+use embedder_traits::{MessageFromPage, MessageToPage};
 use euclid::{default::Size2D as UntypedSize2D, Size2D};
 use gfx::font_cache_thread::FontCacheThread;
 use gfx_traits::Epoch;
+use http::Method;
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
 use ipc_channel::Error as IpcError;
@@ -130,7 +134,7 @@ use layout_traits::LayoutThreadFactory;
 use log::{Level, LevelFilter, Log, Metadata, Record};
 use media::{GLPlayerThreads, WindowGLContext};
 use msg::constellation_msg::{
-    BackgroundHangMonitorControlMsg, BackgroundHangMonitorRegister, HangMonitorAlert,
+    BackgroundHangMonitorControlMsg, BackgroundHangMonitorRegister, HangAlert, HangMonitorAlert,
 };
 use msg::constellation_msg::{
     BroadcastChannelRouterId, MessagePortId, MessagePortRouterId, PipelineNamespace,
@@ -140,19 +144,23 @@ use msg::constellation_msg::{
     BrowsingContextGroupId, BrowsingContextId, HistoryStateId, PipelineId,
     TopLevelBrowsingContextId,
 };
-use net_traits::pub_domains::reg_host;
+use net::resource_thread::new_profile_resource_threads;
+use net_traits::pub_domains::{reg_host, reg_site, Site};
 use net_traits::request::{Referrer, RequestBuilder};
 use net_traits::storage_thread::{StorageThreadMsg, StorageType};
-use net_traits::{self, FetchResponseMsg, IpcSend, ResourceThreads};
+use net_traits::{self, FetchMetadata, FetchResponseMsg, IpcSend, ResourceThreads};
+use net_traits::{BrowsingProfile, CookieSource, CoreResourceMsg, ProfileId, SiteDataRequest};
 use profile_traits::mem;
 use profile_traits::time;
 use script_traits::CompositorEvent::{MouseButtonEvent, MouseMoveEvent};
+use script_traits::{is_embedder_messaging_allowed, MediaSessionActionType, MouseEventType};
 use script_traits::{webdriver_msg, LogEntry, ScriptToConstellationChan, ServiceWorkerMsg};
 use script_traits::{
     AnimationState, AnimationTickType, AuxiliaryBrowsingContextLoadInfo, BroadcastMsg,
     CompositorEvent,
 };
-use script_traits::{ConstellationControlMsg, DiscardBrowsingContext};
+use script_traits::{BrowserSessionState, SessionHistoryEntryState, SessionState};
+use script_traits::{ConstellationControlMsg, DiscardBrowsingContext, PageDisplayList};
 use script_traits::{DocumentActivity, DocumentState, LayoutControlMsg, LoadData, LoadOrigin};
 use script_traits::{HistoryEntryReplacement, IFrameSizeMsg, WindowSizeData, WindowSizeType};
 use script_traits::{
@@ -162,8 +170,7 @@ use script_traits::{
     Job, LayoutMsg as FromLayoutMsg, ScriptMsg as FromScriptMsg, ScriptThreadFactory,
     ServiceWorkerManagerFactory,
 };
-use script_traits::{MediaSessionActionType, MouseEventType};
-use script_traits::{MessagePortMsg, PortMessageTask, StructuredSerializedData};
+use script_traits::{MessagePortMsg, PageState, PortMessageTask, StructuredSerializedData};
 use script_traits::{SWManagerMsg, SWManagerSenders, UpdatePipelineIdReason, WebDriverCommandMsg};
 use serde::{Deserialize, Serialize};
 use servo_config::{opts, pref};
@@ -179,6 +186,7 @@ use std::process;
 use std::rc::{Rc, Weak};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 use style_traits::viewport::ViewportConstraints;
 use style_traits::CSSPixel;
 use webgpu::{self, WebGPU, WebGPURequest};
@@ -244,6 +252,17 @@ struct Browser {
 
     /// The joint session history for this browser.
     session_history: JointSessionHistory,
+
+    /// The isolated browsing profile this browser belongs to, if it doesn't use the
+    /// default resource threads.
+    profile_id: Option<ProfileId>,
+
+    /// When this browser was last hidden, if it is currently hidden.
+    hidden_since: Option<Instant>,
+
+    /// Whether the documents of this browser have been frozen after being hidden
+    /// for longer than `dom.freezing.delay_seconds`.
+    frozen: bool,
 }
 
 /// A browsing context group.
@@ -256,12 +275,14 @@ struct BrowsingContextGroup {
 
     /// The set of all event loops in this BrowsingContextGroup.
     /// We store the event loops in a map
-    /// indexed by registered domain name (as a `Host`) to event loops.
-    /// It is important that scripts with the same eTLD+1,
+    /// indexed by site (scheme and registered domain name) to event loops.
+    /// It is important that scripts with the same scheme and eTLD+1,
     /// who are part of the same browsing-context group
     /// share an event loop, since they can use `document.domain`
     /// to become same-origin, at which point they can share DOM objects.
-    event_loops: HashMap<Host, Weak<EventLoop>>,
+    /// Conversely, documents from different sites never share an event loop,
+    /// so that in multiprocess mode every site gets its own content process.
+    event_loops: HashMap<Site, Weak<EventLoop>>,
 
     /// The set of all WebGPU channels in this BrowsingContextGroup.
     webgpus: HashMap<Host, WebGPU>,
@@ -326,6 +347,10 @@ pub struct Constellation<Message, LTF, STF, SWF> {
     /// A channel for the constellation to receive messages from network listener.
     network_listener_receiver: Receiver<(PipelineId, FetchResponseMsg)>,
 
+    /// The pipelines that were replaced after their navigation was redirected to another
+    /// site, whose remaining fetch messages are dropped.
+    redirected_navigations: HashSet<PipelineId>,
+
     /// A channel for the constellation to receive messages from the compositor thread.
     compositor_receiver: Receiver<FromCompositorMsg>,
 
@@ -355,6 +380,20 @@ pub struct Constellation<Message, LTF, STF, SWF> {
     /// browsing.
     private_resource_threads: ResourceThreads,
 
+    /// The public and private resource threads of each isolated browsing profile,
+    /// created when the first browser of that profile is.
+    profile_resource_threads: HashMap<ProfileId, (ResourceThreads, ResourceThreads)>,
+
+    /// The closed browsers of each isolated browsing profile whose pipelines are still
+    /// exiting. The resource threads of a profile are shut down once it has no browsers
+    /// left and all their pipelines have exited.
+    closed_profile_browsers: HashMap<ProfileId, HashSet<TopLevelBrowsingContextId>>,
+
+    /// Channels to receive signals when the resource and storage threads of the profiles
+    /// that were shut down are done exiting, which they have to be before a profile is
+    /// created again, or Servo exits.
+    exiting_profile_resource_threads: Vec<(ProfileId, IpcReceiver<()>, IpcReceiver<()>)>,
+
     /// A channel for the constellation to send messages to the font
     /// cache thread.
     font_cache_thread: FontCacheThread,
@@ -367,8 +406,8 @@ pub struct Constellation<Message, LTF, STF, SWF> {
     /// bluetooth thread.
     bluetooth_thread: IpcSender<BluetoothRequest>,
 
-    /// A map of origin to sender to a Service worker manager.
-    sw_managers: HashMap<ImmutableOrigin, IpcSender<ServiceWorkerMsg>>,
+    /// A map of browsing profile and origin to sender to a Service worker manager.
+    sw_managers: HashMap<(Option<ProfileId>, ImmutableOrigin), IpcSender<ServiceWorkerMsg>>,
 
     /// An IPC channel for Service Worker Manager threads to send
     /// messages to the constellation.  This is the SW Manager thread's
@@ -495,6 +534,23 @@ pub struct Constellation<Message, LTF, STF, SWF> {
     /// If True, exits on thread failure instead of displaying about:failure
     hard_fail: bool,
 
+    /// Decides whether the content of a top-level browsing context that crashed is
+    /// restarted.
+    crash_recovery: CrashRecovery,
+
+    /// The page state that is restored into pipelines once they have loaded, like the state
+    /// of a crashed pipeline that they replaced, or of a restored session history entry.
+    /// Entries that haven't been loaded yet are indexed by the pipeline of their reloader.
+    page_states_to_restore: HashMap<PipelineId, PageState>,
+
+    /// The history state that is activated in pipelines once they have loaded, for the
+    /// entries of a restored session history, indexed like `page_states_to_restore`.
+    history_states_to_restore: HashMap<PipelineId, HistoryStateId>,
+
+    /// The documents kept in the back/forward cache, with the memory they use,
+    /// least recently cached first.
+    bfcache: VecDeque<(PipelineId, usize)>,
+
     /// If set with --disable-canvas-aa, disable antialiasing on the HTML
     /// canvas element.
     /// Like --disable-text-aa, this is useful for reftests where pixel perfect
@@ -740,6 +796,51 @@ where
     mpsc_receiver
 }
 
+/// Ask the resource and storage threads of a browsing profile to exit, and return channels
+/// to receive signals when they are done exiting.
+fn exit_profile_resource_threads(
+    profile_id: &ProfileId,
+    resource_threads: &ResourceThreads,
+) -> (IpcReceiver<()>, IpcReceiver<()>) {
+    let (core_sender, core_receiver) = ipc::channel().expect("Failed to create IPC channel!");
+    let (storage_sender, storage_receiver) = ipc::channel().expect("Failed to create IPC channel!");
+    // The public and private resource threads of a profile are the same thread,
+    // and they share their storage thread.
+    if let Err(e) = resource_threads.send(net_traits::CoreResourceMsg::Exit(core_sender)) {
+        warn!(
+            "Exit resource thread of profile {} failed ({})",
+            profile_id, e
+        );
+    }
+    if let Err(e) = resource_threads.send(StorageThreadMsg::Exit(storage_sender)) {
+        warn!(
+            "Exit storage thread of profile {} failed ({})",
+            profile_id, e
+        );
+    }
+    (core_receiver, storage_receiver)
+}
+
+/// Block until the resource and storage threads of browsing profiles are done exiting.
+fn wait_for_profile_resource_threads_exit(
+    receivers: Vec<(ProfileId, IpcReceiver<()>, IpcReceiver<()>)>,
+) {
+    for (profile_id, core_receiver, storage_receiver) in receivers {
+        if let Err(e) = core_receiver.recv() {
+            warn!(
+                "Exit resource thread of profile {} failed ({:?})",
+                profile_id, e
+            );
+        }
+        if let Err(e) = storage_receiver.recv() {
+            warn!(
+                "Exit storage thread of profile {} failed ({:?})",
+                profile_id, e
+            );
+        }
+    }
+}
+
 impl<Message, LTF, STF, SWF> Constellation<Message, LTF, STF, SWF>
 where
     LTF: LayoutThreadFactory<Message = Message>,
@@ -864,6 +965,7 @@ where
                     layout_receiver: layout_receiver,
                     network_listener_sender: network_listener_sender,
                     network_listener_receiver: network_listener_receiver,
+                    redirected_navigations: HashSet::new(),
                     embedder_proxy: state.embedder_proxy,
                     compositor_proxy: state.compositor_proxy,
                     active_browser_id: None,
@@ -872,6 +974,9 @@ where
                     bluetooth_thread: state.bluetooth_thread,
                     public_resource_threads: state.public_resource_threads,
                     private_resource_threads: state.private_resource_threads,
+                    profile_resource_threads: HashMap::new(),
+                    closed_profile_browsers: HashMap::new(),
+                    exiting_profile_resource_threads: vec![],
                     font_cache_thread: state.font_cache_thread,
                     sw_managers: Default::default(),
                     swmanager_receiver: swmanager_receiver,
@@ -922,6 +1027,10 @@ where
                     pressed_mouse_buttons: 0,
                     is_running_problem_test,
                     hard_fail,
+                    crash_recovery: CrashRecovery::new(),
+                    page_states_to_restore: HashMap::new(),
+                    history_states_to_restore: HashMap::new(),
+                    bfcache: VecDeque::new(),
                     enable_canvas_antialiasing,
                     glplayer_threads: state.glplayer_threads,
                     player_context: state.player_context,
@@ -964,7 +1073,7 @@ where
 
     fn get_event_loop(
         &mut self,
-        host: &Host,
+        site: &Site,
         top_level_browsing_context_id: &TopLevelBrowsingContextId,
         opener: &Option<BrowsingContextId>,
     ) -> Result<Weak<EventLoop>, &'static str> {
@@ -998,7 +1107,7 @@ where
         };
         bc_group
             .event_loops
-            .get(host)
+            .get(site)
             .ok_or("Trying to get an event-loop from an unknown BC group")
             .map(|event_loop| event_loop.clone())
     }
@@ -1006,7 +1115,7 @@ where
     fn set_event_loop(
         &mut self,
         event_loop: Weak<EventLoop>,
-        host: Host,
+        site: Site,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
         opener: Option<BrowsingContextId>,
     ) {
@@ -1045,17 +1154,141 @@ where
         if let Some(bc_group) = self.browsing_context_group_set.get_mut(&bc_group_id) {
             if !bc_group
                 .event_loops
-                .insert(host.clone(), event_loop)
+                .insert(site.clone(), event_loop)
                 .is_none()
             {
                 warn!(
-                    "Double-setting an event-loop for {:?} at {:?}",
-                    host, relevant_top_level
+                    "Double-setting an event-loop for {} at {:?}",
+                    site, relevant_top_level
                 );
             }
         }
     }
 
+    /// Create the resource threads of a browsing profile, unless a browser in that profile
+    /// already did, and return the profile id.
+    fn ensure_profile_resource_threads(&mut self, profile: BrowsingProfile) -> ProfileId {
+        let profile_id = profile.id.clone();
+        // The profile is in use again, so its resource threads must not be shut down.
+        self.closed_profile_browsers.remove(&profile_id);
+        if !self.profile_resource_threads.contains_key(&profile_id) {
+            // The profile's state is only written to disk when its resource threads exit,
+            // so threads of the same profile that were shut down must be done exiting
+            // before new ones read it.
+            let (exiting, others) = self
+                .exiting_profile_resource_threads
+                .drain(..)
+                .partition(|(exiting_profile_id, ..)| *exiting_profile_id == profile_id);
+            self.exiting_profile_resource_threads = others;
+            wait_for_profile_resource_threads_exit(exiting);
+
+            debug!("Creating resource threads for profile {}.", profile_id);
+            let resource_threads = new_profile_resource_threads(
+                profile,
+                self.user_agent.clone(),
+                self.devtools_chan.clone(),
+                self.time_profiler_chan.clone(),
+                self.mem_profiler_chan.clone(),
+                self.embedder_proxy.clone(),
+                opts::get().certificate_path.clone(),
+            );
+            self.profile_resource_threads
+                .insert(profile_id.clone(), resource_threads);
+        }
+        profile_id
+    }
+
+    /// Shut down the resource threads of a browsing profile if it has no browsers
+    /// left, and the pipelines of its closed browsers have all exited.
+    fn shut_down_profile_if_unused(&mut self, profile_id: &ProfileId) {
+        let pipelines = &self.pipelines;
+        let all_pipelines_exited = match self.closed_profile_browsers.get_mut(profile_id) {
+            Some(closed_browsers) => {
+                closed_browsers.retain(|top_level_browsing_context_id| {
+                    pipelines.values().any(|pipeline| {
+                        pipeline.top_level_browsing_context_id == *top_level_browsing_context_id
+                    })
+                });
+                closed_browsers.is_empty()
+            },
+            None => return,
+        };
+        if !all_pipelines_exited {
+            return;
+        }
+        self.closed_profile_browsers.remove(profile_id);
+        let in_use = self
+            .browsers
+            .values()
+            .any(|browser| browser.profile_id.as_ref() == Some(profile_id));
+        if in_use {
+            return;
+        }
+
+        debug!("Exiting resource threads of profile {}.", profile_id);
+        let profile = Some(profile_id.clone());
+        let sw_manager_keys: Vec<_> = self
+            .sw_managers
+            .keys()
+            .filter(|(sw_manager_profile, _)| *sw_manager_profile == profile)
+            .cloned()
+            .collect();
+        for key in sw_manager_keys {
+            if let Some(mgr) = self.sw_managers.remove(&key) {
+                if let Err(e) = mgr.send(ServiceWorkerMsg::Exit) {
+                    warn!("Exit service worker manager failed ({})", e);
+                }
+            }
+        }
+        if let Some((public_resource_threads, _)) = self.profile_resource_threads.remove(profile_id)
+        {
+            let receivers = exit_profile_resource_threads(profile_id, &public_resource_threads);
+            self.exiting_profile_resource_threads.push((
+                profile_id.clone(),
+                receivers.0,
+                receivers.1,
+            ));
+        }
+    }
+
+    /// The browsing profile of the browser a pipeline belongs to.
+    fn profile_id_for_pipeline(&self, pipeline_id: PipelineId) -> Option<ProfileId> {
+        let top_level_browsing_context_id = self
+            .pipelines
+            .get(&pipeline_id)?
+            .top_level_browsing_context_id;
+        self.browsers
+            .get(&top_level_browsing_context_id)?
+            .profile_id
+            .clone()
+    }
+
+    /// The resource threads used by the pipelines of a browser, which depend on
+    /// the browsing profile of the browser.
+    fn resource_threads_for_browser(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        is_private: bool,
+    ) -> ResourceThreads {
+        let profile_resource_threads = self
+            .browsers
+            .get(&top_level_browsing_context_id)
+            .and_then(|browser| browser.profile_id.as_ref())
+            .and_then(|profile_id| self.profile_resource_threads.get(profile_id));
+        let (public, private) = match profile_resource_threads {
+            Some((public, private)) => (public, private),
+            None => (
+                &self.public_resource_threads,
+                &self.private_resource_threads,
+            ),
+        };
+        if is_private {
+            private.clone()
+        } else {
+            public.clone()
+        }
+    }
+
     /// Helper function for creating a pipeline
     fn new_pipeline(
         &mut self,
@@ -1082,7 +1315,7 @@ where
             pipeline_id, browsing_context_id
         );
 
-        let (event_loop, host) = match sandbox {
+        let (event_loop, site) = match sandbox {
             IFrameSandboxState::IFrameSandboxed => (None, None),
             IFrameSandboxState::IFrameUnsandboxed => {
                 // If this is an about:blank or about:srcdoc load, it must share the creator's
@@ -1091,23 +1324,23 @@ where
                 if load_data.url.as_str() != "about:blank" &&
                     load_data.url.as_str() != "about:srcdoc"
                 {
-                    match reg_host(&load_data.url) {
+                    match reg_site(&load_data.url) {
                         None => (None, None),
-                        Some(host) => {
+                        Some(site) => {
                             match self.get_event_loop(
-                                &host,
+                                &site,
                                 &top_level_browsing_context_id,
                                 &opener,
                             ) {
                                 Err(err) => {
                                     warn!("{}", err);
-                                    (None, Some(host))
+                                    (None, Some(site))
                                 },
                                 Ok(event_loop) => {
                                     if let Some(event_loop) = event_loop.upgrade() {
                                         (Some(event_loop), None)
                                     } else {
-                                        (None, Some(host))
+                                        (None, Some(site))
                                     }
                                 },
                             }
@@ -1128,11 +1361,8 @@ where
             },
         };
 
-        let resource_threads = if is_private {
-            self.private_resource_threads.clone()
-        } else {
-            self.public_resource_threads.clone()
-        };
+        let resource_threads =
+            self.resource_threads_for_browser(top_level_browsing_context_id, is_private);
 
         let result = Pipeline::spawn::<Message, LTF, STF>(InitialPipelineState {
             id: pipeline_id,
@@ -1189,14 +1419,14 @@ where
             self.background_monitor_control_senders.push(chan);
         }
 
-        if let Some(host) = host {
+        if let Some(site) = site {
             debug!(
-                "Adding new host entry {} for top-level browsing context {}.",
-                host, top_level_browsing_context_id
+                "Adding new site entry {} for top-level browsing context {}.",
+                site, top_level_browsing_context_id
             );
             self.set_event_loop(
                 Rc::downgrade(&pipeline.pipeline.event_loop),
-                host,
+                site,
                 top_level_browsing_context_id,
                 opener,
             );
@@ -1329,6 +1559,12 @@ where
             .map(|timeout| after(timeout))
             .unwrap_or(never());
 
+        // A timeout corresponding to the earliest time a hidden browser should be frozen, if any.
+        let freeze_timeout = self
+            .next_freeze_deadline()
+            .map(|deadline| after(deadline.saturating_duration_since(Instant::now())))
+            .unwrap_or(never());
+
         // Get one incoming request.
         // This is one of the few places where the compositor is
         // allowed to panic. If one of the receiver.recv() calls
@@ -1372,6 +1608,9 @@ where
                 // where check_timers will be called.
                 return;
             },
+            recv(freeze_timeout) -> _ => {
+                return self.freeze_hidden_browsers();
+            },
         };
 
         let request = match request {
@@ -1419,12 +1658,32 @@ where
                 // TODO: In case of a permanent hang being reported, add a "kill script" workflow,
                 // via the embedder?
                 warn!("Component hang alert: {:?}", hang);
+                if let HangAlert::Permanent(id, annotation, Some(profile)) = hang {
+                    let profile = GeckoProfile::for_permanent_hang(&id, annotation, &profile);
+                    self.embedder_proxy
+                        .send((None, EmbedderMsg::ReportHangProfile(profile.to_bytes())));
+                }
             },
         }
     }
 
     fn handle_request_from_network_listener(&mut self, message: (PipelineId, FetchResponseMsg)) {
         let (id, message_) = message;
+        if self.redirected_navigations.contains(&id) {
+            if let FetchResponseMsg::ProcessResponseEOF(..) = message_ {
+                self.redirected_navigations.remove(&id);
+            }
+            return;
+        }
+        if let FetchResponseMsg::ProcessResponse(Ok(ref metadata)) = message_ {
+            let metadata = match *metadata {
+                FetchMetadata::Filtered { ref unsafe_, .. } => unsafe_,
+                FetchMetadata::Unfiltered(ref metadata) => metadata,
+            };
+            if self.replace_pipeline_for_redirected_navigation(id, &metadata.final_url) {
+                return;
+            }
+        }
         let result = match self.pipelines.get(&id) {
             Some(pipeline) => {
                 let msg = ConstellationControlMsg::NavigationResponse(id, message_);
@@ -1439,6 +1698,94 @@ where
         }
     }
 
+    /// Once the response to the navigation of a top-level browsing context arrives, make sure
+    /// that its document is created in the event loop of the site that it comes from, which
+    /// isn't the one that its pipeline was created in if the navigation was redirected to
+    /// another site. The pipeline is then replaced by one that loads the final URL in the
+    /// event loop of its site, and whether that happened is returned.
+    ///
+    /// Navigations of nested browsing contexts are left alone, since the script thread of
+    /// their parent knows them by their pipeline, and so are the ones that aren't GETs,
+    /// whose requests can't be resubmitted.
+    fn replace_pipeline_for_redirected_navigation(
+        &mut self,
+        pipeline_id: PipelineId,
+        final_url: &ServoUrl,
+    ) -> bool {
+        let change_index = match self
+            .pending_changes
+            .iter()
+            .position(|change| change.new_pipeline_id == pipeline_id)
+        {
+            Some(change_index) => change_index,
+            None => return false,
+        };
+        let pipeline = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline,
+            None => return false,
+        };
+        let top_level_browsing_context_id = pipeline.top_level_browsing_context_id;
+        let browsing_context_id = pipeline.browsing_context_id;
+        if browsing_context_id != BrowsingContextId::from(top_level_browsing_context_id) ||
+            pipeline.load_data.method != Method::GET
+        {
+            return false;
+        }
+        match (reg_site(&pipeline.load_data.url), reg_site(final_url)) {
+            (Some(ref site), Some(ref final_site)) if site != final_site => {},
+            _ => return false,
+        }
+
+        let opener = pipeline.opener;
+        let mut load_data = pipeline.load_data.clone();
+        load_data.url = final_url.clone();
+        let (is_private, is_visible) =
+            match self.pending_changes[change_index].new_browsing_context_info {
+                Some(ref info) => (info.is_private, info.is_visible),
+                None => match self.browsing_contexts.get(&browsing_context_id) {
+                    Some(browsing_context) => {
+                        (browsing_context.is_private, browsing_context.is_visible)
+                    },
+                    None => return false,
+                },
+            };
+        debug!(
+            "Navigation of pipeline {} was redirected to {}, loading it in another event loop.",
+            pipeline_id, final_url
+        );
+
+        // The state that was to be restored into the document applies to a document of
+        // the original site.
+        self.page_states_to_restore.remove(&pipeline_id);
+        self.history_states_to_restore.remove(&pipeline_id);
+        self.redirected_navigations.insert(pipeline_id);
+        let change = self.pending_changes.remove(change_index);
+        self.close_pipeline(
+            pipeline_id,
+            DiscardBrowsingContext::No,
+            ExitPipelineMode::Normal,
+        );
+
+        let new_pipeline_id = PipelineId::new();
+        self.new_pipeline(
+            new_pipeline_id,
+            browsing_context_id,
+            top_level_browsing_context_id,
+            None,
+            opener,
+            change.window_size,
+            load_data,
+            IFrameSandboxState::IFrameUnsandboxed,
+            is_private,
+            is_visible,
+        );
+        self.add_pending_change(SessionHistoryChange {
+            new_pipeline_id,
+            ..change
+        });
+        true
+    }
+
     fn handle_request_from_swmanager(&mut self, message: SWManagerMsg) {
         match message {
             SWManagerMsg::PostMessageToClient => {
@@ -1530,6 +1877,13 @@ where
             FromCompositorMsg::ClearCache => {
                 self.public_resource_threads.clear_cache();
                 self.private_resource_threads.clear_cache();
+                for (public, private) in self.profile_resource_threads.values() {
+                    public.clear_cache();
+                    private.clear_cache();
+                }
+            },
+            FromCompositorMsg::MemoryPressure => {
+                self.handle_memory_pressure();
             },
             // Load a new page from a typed url
             // If there is already a pending page (self.pending_changes), it will not be overridden;
@@ -1577,7 +1931,23 @@ where
             // Create a new top level browsing context. Will use response_chan to return
             // the browsing context id.
             FromCompositorMsg::NewBrowser(url, top_level_browsing_context_id) => {
-                self.handle_new_top_level_browsing_context(url, top_level_browsing_context_id);
+                self.handle_new_top_level_browsing_context(
+                    url,
+                    top_level_browsing_context_id,
+                    None,
+                );
+            },
+            // Create a new top level browsing context in an isolated browsing profile.
+            FromCompositorMsg::NewBrowserWithProfile(
+                url,
+                top_level_browsing_context_id,
+                profile,
+            ) => {
+                self.handle_new_top_level_browsing_context(
+                    url,
+                    top_level_browsing_context_id,
+                    Some(profile),
+                );
             },
             // Close a top level browsing context.
             FromCompositorMsg::CloseBrowser(top_level_browsing_context_id) => {
@@ -1645,6 +2015,31 @@ where
             FromCompositorMsg::ChangeBrowserVisibility(top_level_browsing_context_id, visible) => {
                 self.handle_change_browser_visibility(top_level_browsing_context_id, visible);
             },
+            FromCompositorMsg::PostMessageToPage(
+                top_level_browsing_context_id,
+                pipeline_id,
+                message,
+            ) => {
+                self.handle_post_message_to_page(
+                    top_level_browsing_context_id,
+                    pipeline_id,
+                    message,
+                );
+            },
+            FromCompositorMsg::SiteData(top_level_browsing_context_id, request) => {
+                self.handle_site_data_request(top_level_browsing_context_id, request);
+            },
+            FromCompositorMsg::GetSessionState(sender) => {
+                if let Err(e) = sender.send(self.session_state()) {
+                    warn!("Sending reply to get session state failed ({:?}).", e);
+                }
+            },
+            FromCompositorMsg::RestoreBrowser(top_level_browsing_context_id, state) => {
+                self.handle_restore_browser(top_level_browsing_context_id, state);
+            },
+            FromCompositorMsg::GetPageDisplayLists(top_level_browsing_context_id, sender) => {
+                self.handle_get_page_display_lists(top_level_browsing_context_id, sender);
+            },
         }
     }
 
@@ -1733,6 +2128,9 @@ where
             FromScriptMsg::DiscardDocument => {
                 self.handle_discard_document(source_top_ctx_id, source_pipeline_id);
             },
+            FromScriptMsg::CachedDocumentMemory(size) => {
+                self.handle_cached_document_memory(source_pipeline_id, size);
+            },
             FromScriptMsg::DiscardTopLevelBrowsingContext => {
                 self.handle_close_top_level_browsing_context(source_top_ctx_id);
             },
@@ -1810,9 +2208,29 @@ where
                     data,
                 );
             },
+            FromScriptMsg::NavigateBrowsingContext(browsing_context_id, load_data, replace) => {
+                let msg = |pipeline_id| {
+                    ConstellationControlMsg::NavigateWindow(pipeline_id, load_data, replace)
+                };
+                self.send_to_active_pipeline(browsing_context_id, msg);
+            },
+            FromScriptMsg::CloseBrowsingContext(browsing_context_id) => {
+                self.send_to_active_pipeline(
+                    browsing_context_id,
+                    ConstellationControlMsg::CloseWindow,
+                );
+            },
             FromScriptMsg::Focus => {
                 self.handle_focus_msg(source_pipeline_id);
             },
+            FromScriptMsg::FocusBrowsingContext(browsing_context_id) => {
+                match self.browsing_contexts.get(&browsing_context_id) {
+                    Some(browsing_context) => {
+                        self.handle_focus_msg(browsing_context.pipeline_id);
+                    },
+                    None => warn!("Focus of closed browsing context {}.", browsing_context_id),
+                }
+            },
             FromScriptMsg::VisibilityChangeComplete(is_visible) => {
                 self.handle_visibility_change_complete(source_pipeline_id, is_visible);
             },
@@ -1885,11 +2303,28 @@ where
                     );
                 }
             },
+            FromScriptMsg::GetChildBrowsingContextCount(browsing_context_id, sender) => {
+                let result = self
+                    .browsing_contexts
+                    .get(&browsing_context_id)
+                    .and_then(|bc| self.pipelines.get(&bc.pipeline_id))
+                    .map_or(0, |pipeline| pipeline.children.len() as u32);
+                if let Err(e) = sender.send(result) {
+                    warn!(
+                        "Sending reply to get child browsing context count failed ({:?}).",
+                        e
+                    );
+                }
+            },
             FromScriptMsg::ScheduleJob(job) => {
                 self.handle_schedule_serviceworker_job(source_pipeline_id, job);
             },
             FromScriptMsg::ForwardDOMMessage(msg_vec, scope_url) => {
-                if let Some(mgr) = self.sw_managers.get(&scope_url.origin()) {
+                let key = (
+                    self.profile_id_for_pipeline(source_pipeline_id),
+                    scope_url.origin(),
+                );
+                if let Some(mgr) = self.sw_managers.get(&key) {
                     let _ = mgr.send(ServiceWorkerMsg::ForwardDOMMessage(msg_vec, scope_url));
                 } else {
                     warn!("Unable to forward DOMMessage for postMessage call");
@@ -1945,6 +2380,18 @@ where
                     pipeline.title = title;
                 }
             },
+            FromScriptMsg::PostMessageToEmbedder(message) => {
+                self.handle_post_message_to_embedder(
+                    source_top_ctx_id,
+                    source_pipeline_id,
+                    message,
+                );
+            },
+            FromScriptMsg::PageStateChanged(state) => {
+                if let Some(pipeline) = self.pipelines.get_mut(&source_pipeline_id) {
+                    pipeline.page_state = state;
+                }
+            },
         }
     }
 
@@ -2591,15 +3038,26 @@ where
             );
         }
 
+        // Service workers fetch and cache through the resource threads of the browsing
+        // profile of the pipeline that registered them, so each profile has its own
+        // manager for an origin.
+        let top_level_browsing_context_id = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.top_level_browsing_context_id,
+            None => return warn!("Pipeline {} scheduled a job after closure.", pipeline_id),
+        };
+        let profile_id = self.profile_id_for_pipeline(pipeline_id);
+        let resource_threads =
+            self.resource_threads_for_browser(top_level_browsing_context_id, false);
+
         // This match is equivalent to Entry.or_insert_with but allows for early return.
-        let sw_manager = match self.sw_managers.entry(origin.clone()) {
+        let sw_manager = match self.sw_managers.entry((profile_id, origin.clone())) {
             Entry::Occupied(entry) => entry.into_mut(),
             Entry::Vacant(entry) => {
                 let (own_sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
 
                 let sw_senders = SWManagerSenders {
                     swmanager_sender: self.swmanager_sender.clone(),
-                    resource_sender: self.public_resource_threads.sender(),
+                    resource_sender: resource_threads.sender(),
                     own_sender: own_sender.clone(),
                     receiver,
                 };
@@ -2758,6 +3216,15 @@ where
             warn!("Exit storage thread failed ({})", e);
         }
 
+        debug!("Exiting browsing profile resource threads.");
+        let mut profile_receivers = replace(&mut self.exiting_profile_resource_threads, vec![]);
+        for (profile_id, (public_resource_threads, _)) in self.profile_resource_threads.drain() {
+            let (core_receiver, storage_receiver) =
+                exit_profile_resource_threads(&profile_id, &public_resource_threads);
+            profile_receivers.push((profile_id, core_receiver, storage_receiver));
+        }
+        self.closed_profile_browsers.clear();
+
         debug!("Exiting bluetooth thread.");
         if let Err(e) = self.bluetooth_thread.send(BluetoothRequest::Exit) {
             warn!("Exit bluetooth thread failed ({})", e);
@@ -2823,6 +3290,7 @@ where
         if let Err(e) = storage_receiver.recv() {
             warn!("Exit storage thread failed ({:?})", e);
         }
+        wait_for_profile_resource_threads_exit(profile_receivers);
 
         debug!("Asking compositor to complete shutdown.");
         self.compositor_proxy
@@ -2834,20 +3302,39 @@ where
 
     fn handle_pipeline_exited(&mut self, pipeline_id: PipelineId) {
         debug!("Pipeline {:?} exited.", pipeline_id);
-        self.pipelines.remove(&pipeline_id);
+        if let Some(pipeline) = self.pipelines.remove(&pipeline_id) {
+            let top_level_browsing_context_id = pipeline.top_level_browsing_context_id;
+            let closed_profile_id = self
+                .closed_profile_browsers
+                .iter()
+                .find(|(_, closed_browsers)| {
+                    closed_browsers.contains(&top_level_browsing_context_id)
+                })
+                .map(|(profile_id, _)| profile_id.clone());
+            if let Some(profile_id) = closed_profile_id {
+                self.shut_down_profile_if_unused(&profile_id);
+            }
+        }
+        self.page_states_to_restore.remove(&pipeline_id);
+        self.history_states_to_restore.remove(&pipeline_id);
     }
 
     fn handle_send_error(&mut self, pipeline_id: PipelineId, err: IpcError) {
         // Treat send error the same as receiving a panic message
         error!("Pipeline {} send error ({}).", pipeline_id, err);
-        let top_level_browsing_context_id = self
-            .pipelines
-            .get(&pipeline_id)
-            .map(|pipeline| pipeline.top_level_browsing_context_id);
-        if let Some(top_level_browsing_context_id) = top_level_browsing_context_id {
-            let reason = format!("Send failed ({})", err);
-            self.handle_panic(top_level_browsing_context_id, reason, None);
+        let pipeline = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline,
+            None => return,
+        };
+        // Messages to the other pipelines of an event loop that already crashed fail too,
+        // and the crash has already been handled.
+        if pipeline.event_loop.has_crashed() {
+            return;
         }
+        pipeline.event_loop.mark_crashed();
+        let top_level_browsing_context_id = pipeline.top_level_browsing_context_id;
+        let reason = format!("Send failed ({})", err);
+        self.handle_panic(top_level_browsing_context_id, reason, None);
     }
 
     fn handle_panic(
@@ -2870,25 +3357,69 @@ where
 
         let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
 
-        self.embedder_proxy.send((
-            Some(top_level_browsing_context_id),
-            EmbedderMsg::Panic(reason, backtrace),
-        ));
-
         let browsing_context = match self.browsing_contexts.get(&browsing_context_id) {
             Some(context) => context,
-            None => return warn!("failed browsing context is missing"),
+            None => {
+                self.embedder_proxy.send((
+                    Some(top_level_browsing_context_id),
+                    EmbedderMsg::Panic(reason, backtrace),
+                ));
+                return warn!("failed browsing context is missing");
+            },
         };
         let window_size = browsing_context.size;
         let pipeline_id = browsing_context.pipeline_id;
         let is_visible = browsing_context.is_visible;
+        let is_private = browsing_context.is_private;
 
         let pipeline = match self.pipelines.get(&pipeline_id) {
             Some(p) => p,
-            None => return warn!("failed pipeline is missing"),
+            None => {
+                self.embedder_proxy.send((
+                    Some(top_level_browsing_context_id),
+                    EmbedderMsg::Panic(reason, backtrace),
+                ));
+                return warn!("failed pipeline is missing");
+            },
         };
         let pipeline_url = pipeline.url.clone();
         let opener = pipeline.opener;
+        let crashed_event_loop = pipeline.event_loop.clone();
+        let crashed_load_data = pipeline.load_data.clone();
+        let crashed_page_state = pipeline.page_state.clone();
+
+        // Content that keeps crashing, or that can't be loaded again without resubmitting
+        // its request, is replaced by about:failure.
+        let verdict = self.crash_recovery.record_crash(
+            top_level_browsing_context_id,
+            &crashed_load_data,
+            Instant::now(),
+        );
+        let recent_crash_count = verdict.recent_crashes;
+        let recover = verdict.recover;
+
+        let failure_url = ServoUrl::parse("about:failure").expect("infallible");
+
+        self.embedder_proxy.send((
+            Some(top_level_browsing_context_id),
+            EmbedderMsg::CrashReport(CrashReport {
+                reason: reason.clone(),
+                backtrace: backtrace.clone(),
+                url: pipeline_url.clone(),
+                recent_crashes: recent_crash_count,
+                recovered: recover,
+            }),
+        ));
+        if recover {
+            // Messages to the pipelines of the crashed event loop, like the ones that close
+            // them, fail from now on, which mustn't be handled as another crash.
+            crashed_event_loop.mark_crashed();
+        } else {
+            self.embedder_proxy.send((
+                Some(top_level_browsing_context_id),
+                EmbedderMsg::Panic(reason, backtrace),
+            ));
+        }
 
         self.close_browsing_context_children(
             browsing_context_id,
@@ -2896,7 +3427,45 @@ where
             ExitPipelineMode::Force,
         );
 
-        let failure_url = ServoUrl::parse("about:failure").expect("infallible");
+        if recover {
+            warn!("creating replacement pipeline for crashed {}", pipeline_url);
+
+            // The event loop that crashed can't run any more pipelines, so make sure that the
+            // replacement, and any other pipeline that would have shared it, gets a new one.
+            for bc_group in self.browsing_context_group_set.values_mut() {
+                bc_group.event_loops.retain(|_, event_loop| {
+                    event_loop
+                        .upgrade()
+                        .map_or(false, |event_loop| !event_loop.has_crashed())
+                });
+            }
+
+            let new_pipeline_id = PipelineId::new();
+            let sandbox = IFrameSandboxState::IFrameUnsandboxed;
+            self.new_pipeline(
+                new_pipeline_id,
+                browsing_context_id,
+                top_level_browsing_context_id,
+                None,
+                opener,
+                window_size,
+                crashed_load_data,
+                sandbox,
+                is_private,
+                is_visible,
+            );
+            self.page_states_to_restore
+                .insert(new_pipeline_id, crashed_page_state);
+            self.add_pending_change(SessionHistoryChange {
+                top_level_browsing_context_id: top_level_browsing_context_id,
+                browsing_context_id: browsing_context_id,
+                new_pipeline_id: new_pipeline_id,
+                replace: Some(NeedsToReload::No(pipeline_id)),
+                new_browsing_context_info: None,
+                window_size,
+            });
+            return;
+        }
 
         if pipeline_url == failure_url {
             return error!("about:failure failed");
@@ -3012,6 +3581,7 @@ where
         &mut self,
         url: ServoUrl,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
+        profile: Option<BrowsingProfile>,
     ) {
         let window_size = self.window_size.initial_viewport;
         let pipeline_id = PipelineId::new();
@@ -3032,6 +3602,8 @@ where
         let is_private = false;
         let is_visible = true;
 
+        let profile_id = profile.map(|profile| self.ensure_profile_resource_threads(profile));
+
         // Register this new top-level browsing context id as a browser and set
         // its focused browsing context to be itself.
         self.browsers.insert(
@@ -3039,6 +3611,9 @@ where
             Browser {
                 focused_browsing_context_id: browsing_context_id,
                 session_history: JointSessionHistory::new(),
+                profile_id,
+                hidden_since: None,
+                frozen: false,
             },
         );
 
@@ -3077,13 +3652,206 @@ where
         });
     }
 
+    /// Create a new top-level browsing context from its saved state. Its current entry is
+    /// loaded, and the other entries are loaded when they are traversed to.
+    fn handle_restore_browser(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        state: BrowserSessionState,
+    ) {
+        let current_entry = match state.entries.get(state.current_index) {
+            Some(entry) => entry.url.clone(),
+            None => return warn!("Restored browser has no current entry."),
+        };
+        self.handle_new_top_level_browsing_context(
+            current_entry,
+            top_level_browsing_context_id,
+            None,
+        );
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let current_pipeline_id = match self
+            .pending_changes
+            .iter()
+            .find(|change| change.browsing_context_id == browsing_context_id)
+        {
+            Some(change) => change.new_pipeline_id,
+            None => return,
+        };
+        let resource_threads =
+            self.resource_threads_for_browser(top_level_browsing_context_id, false);
+
+        let mut reloaders = vec![];
+        for (index, entry) in state.entries.into_iter().enumerate() {
+            let pipeline_id = if index == state.current_index {
+                current_pipeline_id
+            } else {
+                PipelineId::new()
+            };
+            if let Some(history_state) = entry.history_state {
+                let history_state_id = HistoryStateId::new();
+                let msg = CoreResourceMsg::SetHistoryState(history_state_id, history_state);
+                if let Err(e) = resource_threads.send(msg) {
+                    warn!("Restoring history state failed ({:?}).", e);
+                }
+                self.history_states_to_restore
+                    .insert(pipeline_id, history_state_id);
+            }
+            if let Some(page_state) = entry.page_state {
+                self.page_states_to_restore.insert(pipeline_id, page_state);
+            }
+            if index == state.current_index {
+                reloaders.push(NeedsToReload::No(pipeline_id));
+            } else {
+                let load_data = LoadData::new(
+                    LoadOrigin::Constellation,
+                    entry.url,
+                    None,
+                    Referrer::NoReferrer,
+                    None,
+                );
+                reloaders.push(NeedsToReload::Yes(pipeline_id, load_data));
+            }
+        }
+
+        *self.get_joint_session_history(top_level_browsing_context_id) =
+            JointSessionHistory::restored(browsing_context_id, &reloaders, state.current_index);
+    }
+
+    /// The saved state of the top-level browsers.
+    fn session_state(&self) -> SessionState {
+        let mut top_level_browsing_context_ids: Vec<_> = self.browsers.keys().cloned().collect();
+        top_level_browsing_context_ids.sort();
+        SessionState {
+            browsers: top_level_browsing_context_ids
+                .into_iter()
+                .filter_map(|id| self.browser_session_state(id))
+                .collect(),
+        }
+    }
+
+    /// The saved state of a top-level browser, unless it is in private browsing mode.
+    fn browser_session_state(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> Option<BrowserSessionState> {
+        let browser = self.browsers.get(&top_level_browsing_context_id)?;
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let browsing_context = self.browsing_contexts.get(&browsing_context_id)?;
+        if browsing_context.is_private {
+            return None;
+        }
+
+        let current = self.session_entry(&NeedsToReload::No(browsing_context.pipeline_id))?;
+        let (entries, current_index) =
+            browser
+                .session_history
+                .entries(top_level_browsing_context_id, current, |reloader| {
+                    self.session_entry(reloader)
+                });
+
+        // The history states of all the entries are retrieved at once, rather than waiting
+        // for the resource thread once per entry.
+        let history_state_ids: Vec<_> = entries
+            .iter()
+            .filter_map(|entry| entry.history_state_id)
+            .collect();
+        let history_states: HashMap<_, _> = if history_state_ids.is_empty() {
+            HashMap::new()
+        } else {
+            let resource_threads =
+                self.resource_threads_for_browser(top_level_browsing_context_id, false);
+            let (sender, receiver) = ipc::channel().ok()?;
+            let msg = CoreResourceMsg::GetHistoryStates(history_state_ids.clone(), sender);
+            let history_states = match resource_threads.send(msg) {
+                Ok(()) => receiver.recv().unwrap_or_default(),
+                Err(e) => {
+                    warn!("Getting history states failed ({:?}).", e);
+                    vec![]
+                },
+            };
+            history_state_ids
+                .into_iter()
+                .zip(history_states)
+                .filter_map(|(id, state)| state.map(|state| (id, state)))
+                .collect()
+        };
+
+        let entries = entries
+            .into_iter()
+            .map(|entry| SessionHistoryEntryState {
+                page_state: self.session_entry_page_state(&entry),
+                history_state: entry
+                    .history_state_id
+                    .and_then(|id| history_states.get(&id).cloned()),
+                url: entry.url,
+            })
+            .collect();
+        Some(BrowserSessionState {
+            entries,
+            current_index,
+        })
+    }
+
+    /// The entry of the session history of a top-level browsing context that shows the
+    /// document of a reloader.
+    fn session_entry(&self, reloader: &NeedsToReload) -> Option<SessionEntry> {
+        match *reloader {
+            NeedsToReload::No(pipeline_id) => {
+                self.pipelines
+                    .get(&pipeline_id)
+                    .map(|pipeline| SessionEntry {
+                        reloader: reloader.clone(),
+                        url: pipeline.url.clone(),
+                        history_state_id: pipeline.history_state_id,
+                    })
+            },
+            NeedsToReload::Yes(pipeline_id, ref load_data) => Some(SessionEntry {
+                reloader: reloader.clone(),
+                url: load_data.url.clone(),
+                history_state_id: self.history_states_to_restore.get(&pipeline_id).cloned(),
+            }),
+        }
+    }
+
+    /// The page state of an entry of the session history, which is known for the entry that
+    /// a document currently shows, and for entries whose state is still to be restored.
+    fn session_entry_page_state(&self, entry: &SessionEntry) -> Option<PageState> {
+        match entry.reloader {
+            NeedsToReload::No(pipeline_id) => {
+                let pipeline = self.pipelines.get(&pipeline_id)?;
+                if pipeline.url != entry.url || pipeline.history_state_id != entry.history_state_id
+                {
+                    return None;
+                }
+                Some(pipeline.page_state.clone())
+            },
+            NeedsToReload::Yes(pipeline_id, ref load_data) => {
+                if load_data.url != entry.url {
+                    return None;
+                }
+                self.page_states_to_restore.get(&pipeline_id).cloned()
+            },
+        }
+    }
+
     fn handle_close_top_level_browsing_context(
         &mut self,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
     ) {
         let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
         self.close_browsing_context(browsing_context_id, ExitPipelineMode::Normal);
-        self.browsers.remove(&top_level_browsing_context_id);
+        let profile_id = self
+            .browsers
+            .remove(&top_level_browsing_context_id)
+            .and_then(|browser| browser.profile_id);
+        if let Some(profile_id) = profile_id {
+            self.closed_profile_browsers
+                .entry(profile_id.clone())
+                .or_default()
+                .insert(top_level_browsing_context_id);
+            self.shut_down_profile_if_unused(&profile_id);
+        }
+        self.crash_recovery.forget(top_level_browsing_context_id);
         if self.active_browser_id == Some(top_level_browsing_context_id) {
             self.active_browser_id = None;
         }
@@ -3156,10 +3924,16 @@ where
         request_builder: RequestBuilder,
         cancel_chan: IpcReceiver<()>,
     ) {
+        let resource_threads = match self.pipelines.get(&id) {
+            Some(pipeline) => {
+                self.resource_threads_for_browser(pipeline.top_level_browsing_context_id, false)
+            },
+            None => return warn!("Pipeline {} requested navigation after closure.", id),
+        };
         let listener = NetworkListener::new(
             request_builder,
             id,
-            self.public_resource_threads.clone(),
+            resource_threads,
             self.network_listener_sender.clone(),
         );
 
@@ -3346,9 +4120,13 @@ where
             new_pipeline_id,
         } = load_info;
 
-        let (script_sender, opener_browsing_context_id) =
+        let (script_sender, opener_browsing_context_id, opener_top_level_browsing_context_id) =
             match self.pipelines.get(&opener_pipeline_id) {
-                Some(pipeline) => (pipeline.event_loop.clone(), pipeline.browsing_context_id),
+                Some(pipeline) => (
+                    pipeline.event_loop.clone(),
+                    pipeline.browsing_context_id,
+                    pipeline.top_level_browsing_context_id,
+                ),
                 None => {
                     return warn!(
                         "Auxiliary loaded url in closed iframe {}.",
@@ -3378,6 +4156,12 @@ where
             load_data,
         );
 
+        // Auxiliary browsers share the browsing profile of their opener.
+        let profile_id = self
+            .browsers
+            .get(&opener_top_level_browsing_context_id)
+            .and_then(|browser| browser.profile_id.clone());
+
         assert!(!self.pipelines.contains_key(&new_pipeline_id));
         self.pipelines.insert(new_pipeline_id, pipeline);
         self.browsers.insert(
@@ -3385,6 +4169,9 @@ where
             Browser {
                 focused_browsing_context_id: new_browsing_context_id,
                 session_history: JointSessionHistory::new(),
+                profile_id,
+                hidden_since: None,
+                frozen: false,
             },
         );
 
@@ -3637,6 +4424,35 @@ where
         }
     }
 
+    /// Restore the history state and page state that were kept for a pipeline, once it has
+    /// loaded.
+    fn restore_document_state(&mut self, pipeline_id: PipelineId) {
+        let history_state_id = self.history_states_to_restore.remove(&pipeline_id);
+        let page_state = self.page_states_to_restore.remove(&pipeline_id);
+        let pipeline = match self.pipelines.get_mut(&pipeline_id) {
+            Some(pipeline) => pipeline,
+            None => return,
+        };
+        let mut result = Ok(());
+        if let Some(history_state_id) = history_state_id {
+            pipeline.history_state_id = Some(history_state_id);
+            pipeline.history_states.insert(history_state_id);
+            let msg = ConstellationControlMsg::UpdateHistoryState(
+                pipeline_id,
+                Some(history_state_id),
+                pipeline.url.clone(),
+            );
+            result = pipeline.event_loop.send(msg);
+        }
+        if let Some(page_state) = page_state {
+            let msg = ConstellationControlMsg::RestorePageState(pipeline_id, page_state);
+            result = result.and(pipeline.event_loop.send(msg));
+        }
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
     fn handle_load_complete_msg(
         &mut self,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
@@ -3659,6 +4475,8 @@ where
             pipeline.completely_loaded = true;
         }
 
+        self.restore_document_state(pipeline_id);
+
         // Notify the embedder that the TopLevelBrowsingContext current document
         // has finished loading.
         // We need to make sure the pipeline that has finished loading is the current
@@ -3910,6 +4728,13 @@ where
                     is_private,
                     is_visible,
                 );
+                if let Some(state) = self.page_states_to_restore.remove(&pipeline_id) {
+                    self.page_states_to_restore.insert(new_pipeline_id, state);
+                }
+                if let Some(state) = self.history_states_to_restore.remove(&pipeline_id) {
+                    self.history_states_to_restore
+                        .insert(new_pipeline_id, state);
+                }
                 self.add_pending_change(SessionHistoryChange {
                     top_level_browsing_context_id: top_level_id,
                     browsing_context_id: browsing_context_id,
@@ -4259,6 +5084,30 @@ where
         }
     }
 
+    /// Send a message to the script thread of the active document of a browsing context,
+    /// on behalf of a script in another script thread that holds a proxy for its window.
+    fn send_to_active_pipeline<F>(&mut self, browsing_context_id: BrowsingContextId, msg: F)
+    where
+        F: FnOnce(PipelineId) -> ConstellationControlMsg,
+    {
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => browsing_context.pipeline_id,
+            None => {
+                return warn!(
+                    "Message sent to closed browsing context {}.",
+                    browsing_context_id
+                )
+            },
+        };
+        let result = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(msg(pipeline_id)),
+            None => return warn!("Message sent to closed pipeline {}.", pipeline_id),
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
     fn handle_focus_msg(&mut self, pipeline_id: PipelineId) {
         let (browsing_context_id, top_level_browsing_context_id) =
             match self.pipelines.get(&pipeline_id) {
@@ -4522,6 +5371,10 @@ where
                 self.compositor_proxy
                     .send(ToCompositorMsg::CreatePng(rect, reply));
             },
+            WebDriverCommandMsg::TakeFullPageScreenshot(_, reply) => {
+                self.compositor_proxy
+                    .send(ToCompositorMsg::CreateFullPageImage(reply));
+            },
         }
     }
 
@@ -4530,25 +5383,111 @@ where
         top_level_browsing_context_id: TopLevelBrowsingContextId,
         visible: bool,
     ) {
-        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
-        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
-            Some(browsing_context) => browsing_context.pipeline_id,
-            None => {
-                return warn!(
-                    "Browsing context {} got visibility change event after closure.",
-                    browsing_context_id
-                );
+        let was_frozen = match self.browsers.get_mut(&top_level_browsing_context_id) {
+            Some(browser) => {
+                browser.hidden_since = if visible {
+                    None
+                } else {
+                    browser.hidden_since.or(Some(Instant::now()))
+                };
+                replace(&mut browser.frozen, false)
             },
-        };
-        match self.pipelines.get(&pipeline_id) {
             None => {
                 return warn!(
-                    "Pipeline {} got visibility change event after closure.",
-                    pipeline_id
-                )
+                    "Browser {} got visibility change event after closure.",
+                    top_level_browsing_context_id
+                );
             },
-            Some(pipeline) => pipeline.notify_visibility(visible),
         };
+
+        // Every document in the browser is hidden along with it, so that their
+        // timers, animation frames and tasks can be throttled.
+        let browsing_context_ids: Vec<BrowsingContextId> = self
+            .fully_active_browsing_contexts_iter(top_level_browsing_context_id)
+            .map(|browsing_context| browsing_context.id)
+            .collect();
+        for browsing_context_id in browsing_context_ids {
+            let pipeline_id = match self.browsing_contexts.get_mut(&browsing_context_id) {
+                Some(browsing_context) => {
+                    browsing_context.is_visible = visible;
+                    browsing_context.pipeline_id
+                },
+                None => continue,
+            };
+            let pipeline = match self.pipelines.get(&pipeline_id) {
+                Some(pipeline) => pipeline,
+                None => {
+                    warn!(
+                        "Pipeline {} got visibility change event after closure.",
+                        pipeline_id
+                    );
+                    continue;
+                },
+            };
+            if was_frozen {
+                pipeline.set_frozen(false);
+            }
+            pipeline.notify_visibility(visible);
+        }
+    }
+
+    /// The earliest time at which a hidden browser should be frozen, if any.
+    fn next_freeze_deadline(&self) -> Option<Instant> {
+        if !pref!(dom.freezing.enabled) {
+            return None;
+        }
+        let delay = Duration::from_secs(pref!(dom.freezing.delay_seconds).max(0) as u64);
+        let media_browser = self.media_browser();
+        self.browsers
+            .iter()
+            .filter(|(top_level_id, _)| Some(**top_level_id) != media_browser)
+            .filter(|(_, browser)| !browser.frozen)
+            .filter_map(|(_, browser)| browser.hidden_since)
+            .map(|hidden_since| hidden_since + delay)
+            .min()
+    }
+
+    /// The browser that is playing media, which keeps running in the background.
+    fn media_browser(&self) -> Option<TopLevelBrowsingContextId> {
+        self.active_media_session
+            .and_then(|pipeline_id| self.pipelines.get(&pipeline_id))
+            .map(|pipeline| pipeline.top_level_browsing_context_id)
+    }
+
+    /// Freeze the documents of the browsers that have been hidden for longer than
+    /// `dom.freezing.delay_seconds`.
+    /// <https://wicg.github.io/page-lifecycle/#freezing>
+    fn freeze_hidden_browsers(&mut self) {
+        let now = Instant::now();
+        let delay = Duration::from_secs(pref!(dom.freezing.delay_seconds).max(0) as u64);
+
+        let media_browser = self.media_browser();
+        let to_freeze: Vec<TopLevelBrowsingContextId> = self
+            .browsers
+            .iter_mut()
+            .filter(|(top_level_id, _)| Some(**top_level_id) != media_browser)
+            .filter(|(_, browser)| !browser.frozen)
+            .filter(|(_, browser)| {
+                browser
+                    .hidden_since
+                    .map_or(false, |hidden_since| hidden_since + delay <= now)
+            })
+            .map(|(top_level_id, browser)| {
+                browser.frozen = true;
+                *top_level_id
+            })
+            .collect();
+
+        for top_level_browsing_context_id in to_freeze {
+            debug!("Freezing hidden browser {}.", top_level_browsing_context_id);
+            for browsing_context in
+                self.fully_active_browsing_contexts_iter(top_level_browsing_context_id)
+            {
+                if let Some(pipeline) = self.pipelines.get(&browsing_context.pipeline_id) {
+                    pipeline.set_frozen(true);
+                }
+            }
+        }
     }
 
     fn notify_history_changed(&self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
@@ -5357,6 +6296,84 @@ where
         );
     }
 
+    /// Record a document that was kept in the back/forward cache, and evict the least
+    /// recently cached documents once the cache uses more than
+    /// `session-history.bfcache.max-memory-mb`.
+    fn handle_cached_document_memory(&mut self, pipeline_id: PipelineId, size: usize) {
+        self.bfcache
+            .retain(|(cached_id, _)| *cached_id != pipeline_id);
+        self.bfcache.push_back((pipeline_id, size));
+
+        // Documents that were restored or closed since they were cached are no longer
+        // in the cache.
+        let bfcache = replace(&mut self.bfcache, VecDeque::new());
+        self.bfcache = bfcache
+            .into_iter()
+            .filter(|(cached_id, _)| {
+                self.pipelines.contains_key(cached_id) &&
+                    self.get_activity(*cached_id) == DocumentActivity::Inactive
+            })
+            .collect();
+
+        let max_size = pref!(session_history.bfcache.max_memory_mb).max(0) as usize * 1024 * 1024;
+        let mut total_size: usize = self.bfcache.iter().map(|(_, size)| size).sum();
+        while total_size > max_size {
+            let (evicted_id, evicted_size) = match self.bfcache.pop_front() {
+                Some(entry) => entry,
+                None => break,
+            };
+            total_size -= evicted_size;
+            let top_level_browsing_context_id = match self.pipelines.get(&evicted_id) {
+                Some(pipeline) => pipeline.top_level_browsing_context_id,
+                None => continue,
+            };
+            debug!("Evicting {} from the back/forward cache.", evicted_id);
+            self.handle_discard_document(top_level_browsing_context_id, evicted_id);
+        }
+    }
+
+    /// Ask every component to drop what it can because the system is running low on
+    /// memory: the HTTP caches, the documents kept in the back/forward cache, the font and
+    /// style caches of each layout thread, and the image caches and JS heaps of each
+    /// script thread.
+    fn handle_memory_pressure(&mut self) {
+        debug!("Handling memory pressure.");
+        self.public_resource_threads.handle_memory_pressure();
+        self.private_resource_threads.handle_memory_pressure();
+        for (public, private) in self.profile_resource_threads.values() {
+            public.handle_memory_pressure();
+            private.handle_memory_pressure();
+        }
+
+        while let Some((evicted_id, _)) = self.bfcache.pop_front() {
+            let top_level_browsing_context_id = match self.pipelines.get(&evicted_id) {
+                Some(pipeline) => pipeline.top_level_browsing_context_id,
+                None => continue,
+            };
+            if self.get_activity(evicted_id) == DocumentActivity::Inactive {
+                self.handle_discard_document(top_level_browsing_context_id, evicted_id);
+            }
+        }
+
+        let mut event_loops: Vec<Rc<EventLoop>> = vec![];
+        for pipeline in self.pipelines.values() {
+            if let Err(e) = pipeline.layout_chan.send(LayoutControlMsg::MemoryPressure) {
+                warn!("Sending memory pressure to layout failed ({:?}).", e);
+            }
+            if !event_loops
+                .iter()
+                .any(|event_loop| Rc::ptr_eq(event_loop, &pipeline.event_loop))
+            {
+                event_loops.push(pipeline.event_loop.clone());
+            }
+        }
+        for event_loop in event_loops {
+            if let Err(e) = event_loop.send(ConstellationControlMsg::MemoryPressure) {
+                warn!("Sending memory pressure to script failed ({:?}).", e);
+            }
+        }
+    }
+
     // Send a message to script requesting the document associated with this pipeline runs the 'unload' algorithm.
     fn unload_document(&self, pipeline_id: PipelineId) {
         if let Some(pipeline) = self.pipelines.get(&pipeline_id) {
@@ -5479,6 +6496,9 @@ where
             .or_insert_with(|| Browser {
                 focused_browsing_context_id: BrowsingContextId::from(top_level_id),
                 session_history: JointSessionHistory::new(),
+                profile_id: None,
+                hidden_since: None,
+                frozen: false,
             })
             .session_history
     }
@@ -5567,4 +6587,163 @@ where
             error!("Got a media session action but no active media session is registered");
         }
     }
+
+    fn handle_post_message_to_embedder(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        pipeline_id: PipelineId,
+        message: MessageFromPage,
+    ) {
+        let url = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.url.clone(),
+            None => return warn!("Pipeline {} posted a message after closure.", pipeline_id),
+        };
+        // The content process is not trusted to have checked the allow-list itself.
+        if !is_embedder_messaging_allowed(&url.origin()) {
+            return warn!(
+                "Pipeline {} is not allowed to post messages to the embedder.",
+                pipeline_id
+            );
+        }
+        self.embedder_proxy.send((
+            Some(top_level_browsing_context_id),
+            EmbedderMsg::MessageFromPage(pipeline_id, url, message),
+        ));
+    }
+
+    fn handle_post_message_to_page(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        pipeline_id: Option<PipelineId>,
+        message: MessageToPage,
+    ) {
+        let pipeline_id = match pipeline_id {
+            Some(pipeline_id) => pipeline_id,
+            None => {
+                let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+                match self.browsing_contexts.get(&browsing_context_id) {
+                    Some(browsing_context) => browsing_context.pipeline_id,
+                    None => {
+                        return warn!(
+                            "Browsing context {} got embedder message after closure.",
+                            browsing_context_id
+                        );
+                    },
+                }
+            },
+        };
+        let pipeline = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline,
+            None => {
+                return warn!(
+                    "Pipeline {} got embedder message after closure.",
+                    pipeline_id
+                )
+            },
+        };
+        if pipeline.top_level_browsing_context_id != top_level_browsing_context_id {
+            return warn!(
+                "Pipeline {} does not belong to browser {}.",
+                pipeline_id, top_level_browsing_context_id
+            );
+        }
+        if !is_embedder_messaging_allowed(&pipeline.url.origin()) {
+            return warn!(
+                "Pipeline {} is not allowed to receive messages from the embedder.",
+                pipeline_id
+            );
+        }
+        let msg = ConstellationControlMsg::PostMessageFromEmbedder(pipeline_id, message);
+        let result = pipeline.event_loop.send(msg);
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
+    fn handle_get_page_display_lists(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        sender: IpcSender<Vec<PageDisplayList>>,
+    ) {
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => browsing_context.pipeline_id,
+            None => {
+                return warn!(
+                    "Browsing context {} printed after closure.",
+                    browsing_context_id
+                );
+            },
+        };
+        let msg = ConstellationControlMsg::GetPageDisplayLists(pipeline_id, sender);
+        let result = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(msg),
+            None => return warn!("Pipeline {} printed after closure.", pipeline_id),
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
+    fn handle_site_data_request(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        request: SiteDataRequest,
+    ) {
+        if !self.browsers.contains_key(&top_level_browsing_context_id) {
+            return warn!(
+                "Browser {} got site data request after closure.",
+                top_level_browsing_context_id
+            );
+        }
+        let resource_threads =
+            self.resource_threads_for_browser(top_level_browsing_context_id, false);
+        let private_resource_threads =
+            self.resource_threads_for_browser(top_level_browsing_context_id, true);
+        // Data is only ever read from or added to the public state, but removals
+        // also apply to the private state so that clearing site data leaves no
+        // trace of the site behind.
+        let result = match request {
+            SiteDataRequest::GetCookies(url, sender) => {
+                resource_threads.send(CoreResourceMsg::GetAllCookies(url, sender))
+            },
+            SiteDataRequest::SetCookie(url, cookie) => resource_threads.send(
+                CoreResourceMsg::SetCookieForUrl(url, cookie, CookieSource::HTTP),
+            ),
+            SiteDataRequest::DeleteCookie(url, name) => resource_threads
+                .send(CoreResourceMsg::DeleteCookie(url.clone(), name.clone()))
+                .and_then(|_| {
+                    private_resource_threads.send(CoreResourceMsg::DeleteCookie(url, name))
+                }),
+            SiteDataRequest::ClearCookies(Some(url)) => resource_threads
+                .send(CoreResourceMsg::DeleteCookies(url.clone()))
+                .and_then(|_| private_resource_threads.send(CoreResourceMsg::DeleteCookies(url))),
+            SiteDataRequest::ClearCookies(None) => resource_threads
+                .send(CoreResourceMsg::DeleteAllCookies)
+                .and_then(|_| private_resource_threads.send(CoreResourceMsg::DeleteAllCookies)),
+            // The public and private resource threads share a storage thread.
+            SiteDataRequest::ClearStorage(url, storage_type) => {
+                resource_threads.send(StorageThreadMsg::ClearOrigins(url, storage_type))
+            },
+            SiteDataRequest::ClearHstsEntries(host) => resource_threads
+                .send(CoreResourceMsg::ClearHstsEntries(host.clone()))
+                .and_then(|_| {
+                    private_resource_threads.send(CoreResourceMsg::ClearHstsEntries(host))
+                }),
+            SiteDataRequest::ClearAuthCache(url) => {
+                let origin = url.map(|url| url.origin());
+                resource_threads
+                    .send(CoreResourceMsg::ClearAuthCache(origin.clone()))
+                    .and_then(|_| {
+                        private_resource_threads.send(CoreResourceMsg::ClearAuthCache(origin))
+                    })
+            },
+        };
+        if let Err(e) = result {
+            warn!(
+                "Sending site data request to resource threads failed ({:?}).",
+                e
+            );
+        }
+    }
 }
@@ -0,0 +1,184 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use http::Method;
+use msg::constellation_msg::TopLevelBrowsingContextId;
+use script_traits::LoadData;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// The number of times that crashed content is restarted within `CRASH_RECOVERY_PERIOD`,
+/// after which it is replaced by about:failure.
+const MAX_CRASH_RECOVERIES: usize = 3;
+
+/// The period over which crashes of the content of a top-level browsing context are counted.
+const CRASH_RECOVERY_PERIOD: Duration = Duration::from_secs(60);
+
+/// Decides whether crashed content is loaded again, or replaced by about:failure.
+pub struct CrashRecovery {
+    /// The times that the content of each top-level browsing context crashed recently, so
+    /// that content that keeps crashing isn't restarted again and again.
+    recent_crashes: HashMap<TopLevelBrowsingContextId, VecDeque<Instant>>,
+}
+
+/// What to do about a crash of the content of a top-level browsing context.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CrashVerdict {
+    /// The number of crashes of the content within `CRASH_RECOVERY_PERIOD`,
+    /// including this one.
+    pub recent_crashes: usize,
+    /// Whether the crashed content is loaded again.
+    pub recover: bool,
+}
+
+impl CrashRecovery {
+    pub fn new() -> CrashRecovery {
+        CrashRecovery {
+            recent_crashes: HashMap::new(),
+        }
+    }
+
+    /// Record a crash at `now` of the content of a top-level browsing context that was
+    /// loaded with `load_data`.
+    ///
+    /// Content that keeps crashing isn't restarted, and neither is content that was loaded
+    /// with anything but a GET, since loading it again would silently resubmit its request.
+    pub fn record_crash(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        load_data: &LoadData,
+        now: Instant,
+    ) -> CrashVerdict {
+        let recent_crashes = self
+            .recent_crashes
+            .entry(top_level_browsing_context_id)
+            .or_insert_with(VecDeque::new);
+        while recent_crashes
+            .front()
+            .map_or(false, |time| now - *time > CRASH_RECOVERY_PERIOD)
+        {
+            recent_crashes.pop_front();
+        }
+        recent_crashes.push_back(now);
+        let recent_crash_count = recent_crashes.len();
+
+        let recover = load_data.url.as_str() != "about:failure" &&
+            load_data.method == Method::GET &&
+            recent_crash_count <= MAX_CRASH_RECOVERIES;
+
+        CrashVerdict {
+            recent_crashes: recent_crash_count,
+            recover,
+        }
+    }
+
+    /// Forget the crashes of the content of a top-level browsing context that was closed.
+    pub fn forget(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
+        self.recent_crashes.remove(&top_level_browsing_context_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use msg::constellation_msg::{PipelineNamespace, PipelineNamespaceId};
+    use net_traits::request::Referrer;
+    use script_traits::LoadOrigin;
+    use servo_url::ServoUrl;
+
+    fn load_data(url: &str) -> LoadData {
+        LoadData::new(
+            LoadOrigin::Constellation,
+            ServoUrl::parse(url).unwrap(),
+            None,
+            Referrer::NoReferrer,
+            None,
+        )
+    }
+
+    fn browser(namespace: u32) -> TopLevelBrowsingContextId {
+        PipelineNamespace::install(PipelineNamespaceId(namespace));
+        TopLevelBrowsingContextId::new()
+    }
+
+    #[test]
+    fn test_crashed_content_is_restarted_until_the_limit() {
+        let browser = browser(5001);
+        let load_data = load_data("https://example.com/");
+        let mut crash_recovery = CrashRecovery::new();
+        let start = Instant::now();
+
+        for crash in 1..=MAX_CRASH_RECOVERIES {
+            let now = start + Duration::from_secs(crash as u64);
+            let verdict = crash_recovery.record_crash(browser, &load_data, now);
+            assert_eq!(verdict.recent_crashes, crash);
+            assert!(verdict.recover);
+        }
+
+        let now = start + Duration::from_secs(MAX_CRASH_RECOVERIES as u64 + 1);
+        let verdict = crash_recovery.record_crash(browser, &load_data, now);
+        assert_eq!(verdict.recent_crashes, MAX_CRASH_RECOVERIES + 1);
+        assert!(!verdict.recover);
+    }
+
+    #[test]
+    fn test_old_crashes_are_not_counted() {
+        let browser = browser(5002);
+        let load_data = load_data("https://example.com/");
+        let mut crash_recovery = CrashRecovery::new();
+        let start = Instant::now();
+
+        for _ in 0..MAX_CRASH_RECOVERIES {
+            crash_recovery.record_crash(browser, &load_data, start);
+        }
+
+        let later = start + CRASH_RECOVERY_PERIOD + Duration::from_secs(1);
+        let verdict = crash_recovery.record_crash(browser, &load_data, later);
+        assert_eq!(verdict.recent_crashes, 1);
+        assert!(verdict.recover);
+    }
+
+    #[test]
+    fn test_crashes_are_counted_per_browser() {
+        let first = browser(5003);
+        let second = TopLevelBrowsingContextId::new();
+        let load_data = load_data("https://example.com/");
+        let mut crash_recovery = CrashRecovery::new();
+        let now = Instant::now();
+
+        for _ in 0..=MAX_CRASH_RECOVERIES {
+            crash_recovery.record_crash(first, &load_data, now);
+        }
+        let verdict = crash_recovery.record_crash(second, &load_data, now);
+        assert_eq!(verdict.recent_crashes, 1);
+        assert!(verdict.recover);
+
+        crash_recovery.forget(first);
+        let verdict = crash_recovery.record_crash(first, &load_data, now);
+        assert_eq!(verdict.recent_crashes, 1);
+        assert!(verdict.recover);
+    }
+
+    #[test]
+    fn test_crashed_post_is_not_restarted() {
+        let browser = browser(5004);
+        let mut load_data = load_data("https://example.com/form");
+        load_data.method = Method::POST;
+        let mut crash_recovery = CrashRecovery::new();
+
+        let verdict = crash_recovery.record_crash(browser, &load_data, Instant::now());
+        assert_eq!(verdict.recent_crashes, 1);
+        assert!(!verdict.recover);
+    }
+
+    #[test]
+    fn test_crashed_failure_page_is_not_restarted() {
+        let browser = browser(5005);
+        let load_data = load_data("about:failure");
+        let mut crash_recovery = CrashRecovery::new();
+
+        let verdict = crash_recovery.record_crash(browser, &load_data, Instant::now());
+        assert!(!verdict.recover);
+    }
+}
@@ -9,12 +9,14 @@
 use ipc_channel::ipc::IpcSender;
 use ipc_channel::Error;
 use script_traits::ConstellationControlMsg;
+use std::cell::Cell;
 use std::marker::PhantomData;
 use std::rc::Rc;
 
 /// <https://html.spec.whatwg.org/multipage/#event-loop>
 pub struct EventLoop {
     script_chan: IpcSender<ConstellationControlMsg>,
+    crashed: Cell<bool>,
     dont_send_or_sync: PhantomData<Rc<()>>,
 }
 
@@ -31,6 +33,7 @@ impl EventLoop {
     pub fn new(script_chan: IpcSender<ConstellationControlMsg>) -> Rc<EventLoop> {
         Rc::new(EventLoop {
             script_chan: script_chan,
+            crashed: Cell::new(false),
             dont_send_or_sync: PhantomData,
         })
     }
@@ -40,6 +43,17 @@ impl EventLoop {
         self.script_chan.send(msg)
     }
 
+    /// Note that the script thread of the event loop crashed, so that no new pipelines are
+    /// started in it.
+    pub fn mark_crashed(&self) {
+        self.crashed.set(true);
+    }
+
+    /// Whether the script thread of the event loop crashed.
+    pub fn has_crashed(&self) -> bool {
+        self.crashed.get()
+    }
+
     /// The underlying channel to the script thread.
     pub fn sender(&self) -> IpcSender<ConstellationControlMsg> {
         self.script_chan.clone()
@@ -13,9 +13,13 @@ extern crate serde;
 
 mod browsingcontext;
 mod constellation;
+mod crash_recovery;
 mod event_loop;
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+mod linux_sandbox;
 mod network_listener;
 mod pipeline;
+mod sandbox_broker;
 mod sandboxing;
 mod serviceworker;
 mod session_history;
@@ -24,5 +28,10 @@ mod timer_scheduler;
 pub use crate::constellation::{
     Constellation, FromCompositorLogger, FromScriptLogger, InitialConstellationState,
 };
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+pub use crate::linux_sandbox::enter_sandbox;
 pub use crate::pipeline::UnprivilegedPipelineContent;
-pub use crate::sandboxing::{content_process_sandbox_profile, UnprivilegedContent};
+pub use crate::sandbox_broker::FileBrokerRequest;
+pub use crate::sandboxing::{
+    content_process_sandbox_profile, ContentProcessBootstrap, UnprivilegedContent,
+};
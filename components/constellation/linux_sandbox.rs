@@ -0,0 +1,495 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The content process sandbox on x86-64 Linux.
+//!
+//! Content processes are started in their own user and network namespaces, so they can't
+//! reach the network nor gain privileges. Once a content process has received its content,
+//! it installs a seccomp-bpf filter that only allows the system calls on an explicit
+//! allow-list. Opening files is refused, so that files are read through the file broker of
+//! the parent process instead, and any other system call kills the process, or, when
+//! tuning the policy with `-Z sandbox-report-syscalls`, is reported and fails with `ENOSYS`.
+//!
+//! <https://www.kernel.org/doc/html/latest/userspace-api/seccomp_filter.html>
+
+#![allow(unsafe_code)]
+
+use crate::sandbox_broker::{BrokeredResourceReader, FileBrokerRequest};
+use embedder_traits::resources;
+use ipc_channel::ipc::IpcSender;
+use libc::{c_int, c_long, c_ulong, c_ushort, c_void};
+use std::ffi::CStr;
+use std::io;
+use std::mem;
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+use std::ptr;
+
+/// A BPF instruction, `struct sock_filter` in `<linux/filter.h>`.
+#[repr(C)]
+struct SockFilter {
+    code: u16,
+    jt: u8,
+    jf: u8,
+    k: u32,
+}
+
+/// A BPF program, `struct sock_fprog` in `<linux/filter.h>`.
+#[repr(C)]
+struct SockFprog {
+    len: c_ushort,
+    filter: *const SockFilter,
+}
+
+const BPF_LD_W_ABS: u16 = 0x20;
+const BPF_JMP_JEQ_K: u16 = 0x15;
+const BPF_RET_K: u16 = 0x06;
+
+/// The offsets of the system call number and of the architecture in `struct seccomp_data`.
+const SECCOMP_DATA_NR: u32 = 0;
+const SECCOMP_DATA_ARCH: u32 = 4;
+
+/// The offset of the lower half of an argument of the system call in `struct seccomp_data`.
+fn seccomp_data_arg(index: u32) -> u32 {
+    16 + index * 8
+}
+
+const AUDIT_ARCH_X86_64: u32 = 0xc000_003e;
+
+const SECCOMP_SET_MODE_FILTER: c_ulong = 1;
+const SECCOMP_FILTER_FLAG_TSYNC: c_ulong = 1;
+const SECCOMP_RET_KILL_PROCESS: u32 = 0x8000_0000;
+const SECCOMP_RET_TRAP: u32 = 0x0003_0000;
+const SECCOMP_RET_ERRNO: u32 = 0x0005_0000;
+const SECCOMP_RET_ALLOW: u32 = 0x7fff_0000;
+
+/// System calls that the minimum version of libc doesn't have constants for yet, from
+/// `<asm/unistd_64.h>`.
+const SYS_RSEQ: c_long = 334;
+const SYS_CLONE3: c_long = 435;
+
+/// The `clone` flags that glibc's `pthread_create` uses. Content processes may create
+/// threads, but not processes.
+const THREAD_CLONE_FLAGS: c_int = libc::CLONE_VM |
+    libc::CLONE_FS |
+    libc::CLONE_FILES |
+    libc::CLONE_SIGHAND |
+    libc::CLONE_THREAD |
+    libc::CLONE_SYSVSEM |
+    libc::CLONE_SETTLS |
+    libc::CLONE_PARENT_SETTID |
+    libc::CLONE_CHILD_CLEARTID;
+
+/// The `prctl` options that content processes may use, to name their threads.
+const ALLOWED_PRCTL_OPTIONS: &'static [c_int] = &[libc::PR_SET_NAME, libc::PR_GET_NAME];
+
+/// The system calls that content processes may make.
+const ALLOWED_SYSCALLS: &'static [c_long] = &[
+    // Reading and writing the descriptors the process already has.
+    libc::SYS_read,
+    libc::SYS_write,
+    libc::SYS_readv,
+    libc::SYS_writev,
+    libc::SYS_pread64,
+    libc::SYS_pwrite64,
+    libc::SYS_lseek,
+    libc::SYS_close,
+    libc::SYS_dup,
+    libc::SYS_dup3,
+    libc::SYS_fcntl,
+    libc::SYS_fstat,
+    libc::SYS_newfstatat,
+    libc::SYS_statx,
+    libc::SYS_ftruncate,
+    // Memory.
+    libc::SYS_brk,
+    libc::SYS_mmap,
+    libc::SYS_munmap,
+    libc::SYS_mremap,
+    libc::SYS_mprotect,
+    libc::SYS_madvise,
+    libc::SYS_memfd_create,
+    // Threads and synchronization.
+    libc::SYS_set_tid_address,
+    libc::SYS_set_robust_list,
+    SYS_RSEQ,
+    libc::SYS_arch_prctl,
+    libc::SYS_futex,
+    libc::SYS_sched_yield,
+    libc::SYS_sched_getaffinity,
+    libc::SYS_gettid,
+    libc::SYS_getpid,
+    libc::SYS_exit,
+    libc::SYS_exit_group,
+    // Signals.
+    libc::SYS_rt_sigaction,
+    libc::SYS_rt_sigprocmask,
+    libc::SYS_rt_sigreturn,
+    libc::SYS_sigaltstack,
+    libc::SYS_restart_syscall,
+    // Time.
+    libc::SYS_clock_gettime,
+    libc::SYS_clock_getres,
+    libc::SYS_gettimeofday,
+    libc::SYS_nanosleep,
+    libc::SYS_clock_nanosleep,
+    // IPC channels, which are Unix sockets that are created in pairs, and the event loops
+    // that wait on them.
+    libc::SYS_socketpair,
+    libc::SYS_sendmsg,
+    libc::SYS_recvmsg,
+    libc::SYS_shutdown,
+    libc::SYS_pipe2,
+    libc::SYS_eventfd2,
+    libc::SYS_poll,
+    libc::SYS_ppoll,
+    libc::SYS_epoll_create1,
+    libc::SYS_epoll_ctl,
+    libc::SYS_epoll_wait,
+    libc::SYS_epoll_pwait,
+    // Process and system information.
+    libc::SYS_getrusage,
+    libc::SYS_getrlimit,
+    libc::SYS_prlimit64,
+    libc::SYS_uname,
+    libc::SYS_sysinfo,
+    libc::SYS_getuid,
+    libc::SYS_geteuid,
+    libc::SYS_getgid,
+    libc::SYS_getegid,
+    libc::SYS_getrandom,
+];
+
+/// The system calls that fail with `EACCES`, rather than being violations of the policy.
+/// Files are read through the file broker instead.
+const REFUSED_SYSCALLS: &'static [c_long] = &[
+    libc::SYS_open,
+    libc::SYS_openat,
+    libc::SYS_creat,
+    libc::SYS_access,
+    libc::SYS_faccessat,
+    libc::SYS_readlink,
+    libc::SYS_readlinkat,
+];
+
+/// Prepare a command to start a content process in new user and network namespaces.
+pub fn start_in_new_namespaces(command: &mut Command) {
+    // The maps keep the user and group of the process the same in the new user namespace.
+    // They are formatted here, since only async-signal-safe functions may be called
+    // between fork and exec.
+    let uid = unsafe { libc::getuid() };
+    let gid = unsafe { libc::getgid() };
+    let uid_map = format!("{} {} 1\n", uid, uid);
+    let gid_map = format!("{} {} 1\n", gid, gid);
+
+    unsafe {
+        command.pre_exec(move || {
+            if libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            // Setting the group map is only permitted once setgroups(2) is denied.
+            write_proc_file(
+                CStr::from_bytes_with_nul_unchecked(b"/proc/self/setgroups\0"),
+                b"deny",
+            )?;
+            write_proc_file(
+                CStr::from_bytes_with_nul_unchecked(b"/proc/self/uid_map\0"),
+                uid_map.as_bytes(),
+            )?;
+            write_proc_file(
+                CStr::from_bytes_with_nul_unchecked(b"/proc/self/gid_map\0"),
+                gid_map.as_bytes(),
+            )
+        });
+    }
+}
+
+fn write_proc_file(path: &CStr, contents: &[u8]) -> io::Result<()> {
+    unsafe {
+        let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let written = libc::write(fd, contents.as_ptr() as *const c_void, contents.len());
+        libc::close(fd);
+        if written != contents.len() as isize {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Enter the sandbox in a content process. Resources and files are read through the file
+/// broker from then on.
+pub fn enter_sandbox(file_broker: IpcSender<FileBrokerRequest>, report_syscalls: bool) {
+    resources::set(Box::new(BrokeredResourceReader::new(file_broker)));
+
+    let violation_action = if report_syscalls {
+        install_denied_syscall_reporter();
+        SECCOMP_RET_TRAP
+    } else {
+        SECCOMP_RET_KILL_PROCESS
+    };
+    install_syscall_filter(violation_action).expect("Failed to activate sandbox!");
+}
+
+fn statement(code: u16, k: u32) -> SockFilter {
+    SockFilter {
+        code,
+        jt: 0,
+        jf: 0,
+        k,
+    }
+}
+
+fn jump(code: u16, k: u32, jt: u8, jf: u8) -> SockFilter {
+    SockFilter { code, jt, jf, k }
+}
+
+/// Build the filter program, which returns `violation_action` for any system call that
+/// isn't allowed or refused.
+fn syscall_filter(violation_action: u32) -> Vec<SockFilter> {
+    let mut program = vec![
+        // System call numbers differ between architectures, so a process that makes
+        // system calls of another architecture is killed, whatever the action is.
+        statement(BPF_LD_W_ABS, SECCOMP_DATA_ARCH),
+        jump(BPF_JMP_JEQ_K, AUDIT_ARCH_X86_64, 1, 0),
+        statement(BPF_RET_K, SECCOMP_RET_KILL_PROCESS),
+        statement(BPF_LD_W_ABS, SECCOMP_DATA_NR),
+    ];
+    for &syscall in ALLOWED_SYSCALLS {
+        program.push(jump(BPF_JMP_JEQ_K, syscall as u32, 0, 1));
+        program.push(statement(BPF_RET_K, SECCOMP_RET_ALLOW));
+    }
+    for &syscall in REFUSED_SYSCALLS {
+        program.push(jump(BPF_JMP_JEQ_K, syscall as u32, 0, 1));
+        program.push(statement(
+            BPF_RET_K,
+            SECCOMP_RET_ERRNO | libc::EACCES as u32,
+        ));
+    }
+
+    // glibc falls back to `clone` when `clone3` isn't implemented. The flags of `clone3`
+    // are in memory, where the filter can't check them.
+    program.push(jump(BPF_JMP_JEQ_K, SYS_CLONE3 as u32, 0, 1));
+    program.push(statement(
+        BPF_RET_K,
+        SECCOMP_RET_ERRNO | libc::ENOSYS as u32,
+    ));
+
+    // The flags are the lower half of the first argument, and the upper half is unused.
+    push_argument_check(
+        &mut program,
+        libc::SYS_clone,
+        0,
+        &[THREAD_CLONE_FLAGS as u32],
+        violation_action,
+    );
+    push_argument_check(
+        &mut program,
+        libc::SYS_prctl,
+        0,
+        &ALLOWED_PRCTL_OPTIONS
+            .iter()
+            .map(|&option| option as u32)
+            .collect::<Vec<_>>(),
+        SECCOMP_RET_ERRNO | libc::EPERM as u32,
+    );
+    // Signals may only be sent to the threads of the process itself.
+    push_argument_check(
+        &mut program,
+        libc::SYS_tgkill,
+        0,
+        &[unsafe { libc::getpid() } as u32],
+        violation_action,
+    );
+
+    program.push(statement(BPF_RET_K, violation_action));
+    program
+}
+
+/// Add the instructions that allow `syscall` when the lower half of its argument `index`
+/// is one of `values`, and otherwise return `otherwise_action`. The system call number is
+/// still loaded after these instructions when the system call is another one.
+fn push_argument_check(
+    program: &mut Vec<SockFilter>,
+    syscall: c_long,
+    index: u32,
+    values: &[u32],
+    otherwise_action: u32,
+) {
+    let check_length = 1 + values.len() * 2 + 1;
+    program.push(jump(BPF_JMP_JEQ_K, syscall as u32, 0, check_length as u8));
+    program.push(statement(BPF_LD_W_ABS, seccomp_data_arg(index)));
+    for &value in values {
+        program.push(jump(BPF_JMP_JEQ_K, value, 0, 1));
+        program.push(statement(BPF_RET_K, SECCOMP_RET_ALLOW));
+    }
+    program.push(statement(BPF_RET_K, otherwise_action));
+}
+
+fn install_syscall_filter(violation_action: u32) -> io::Result<()> {
+    let program = syscall_filter(violation_action);
+    let program = SockFprog {
+        len: program.len() as c_ushort,
+        filter: program.as_ptr(),
+    };
+    unsafe {
+        // Required to install a filter without CAP_SYS_ADMIN, and keeps the process from
+        // gaining privileges by executing a setuid binary.
+        if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // The filter is installed on all threads, since the content process already has
+        // threads like the IPC router at this point.
+        let result = libc::syscall(
+            libc::SYS_seccomp,
+            SECCOMP_SET_MODE_FILTER,
+            SECCOMP_FILTER_FLAG_TSYNC,
+            &program as *const SockFprog,
+        );
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+fn install_denied_syscall_reporter() {
+    unsafe {
+        let mut action: libc::sigaction = mem::zeroed();
+        action.sa_sigaction = report_denied_syscall as usize;
+        action.sa_flags = libc::SA_SIGINFO;
+        libc::sigemptyset(&mut action.sa_mask);
+        if libc::sigaction(libc::SIGSYS, &action, ptr::null_mut()) != 0 {
+            panic!("Failed to install the SIGSYS handler!");
+        }
+    }
+}
+
+/// The handler of the `SIGSYS` signal that a denied system call raises in report mode. It
+/// prints the number of the system call, which can be looked up in
+/// `<asm/unistd_64.h>`, and makes the call fail with `ENOSYS`.
+extern "C" fn report_denied_syscall(
+    _signal: c_int,
+    _info: *mut libc::siginfo_t,
+    context: *mut c_void,
+) {
+    unsafe {
+        let context = &mut *(context as *mut libc::ucontext_t);
+        let registers = &mut context.uc_mcontext.gregs;
+        // The kernel leaves the system call number in rax, and the return value of the call
+        // is read back from it.
+        let syscall = registers[libc::REG_RAX as usize];
+        registers[libc::REG_RAX as usize] = -(libc::ENOSYS as i64);
+
+        // Only async-signal-safe functions can be called here, so the message is formatted
+        // by hand rather than with the logger.
+        let prefix = b"Sandbox: denied system call ";
+        let mut message = [0u8; 64];
+        message[..prefix.len()].copy_from_slice(prefix);
+        let mut length = prefix.len();
+        let mut divisor = 1;
+        while syscall / divisor >= 10 {
+            divisor *= 10;
+        }
+        while divisor > 0 {
+            message[length] = b'0' + (syscall / divisor % 10) as u8;
+            length += 1;
+            divisor /= 10;
+        }
+        message[length] = b'\n';
+        libc::write(
+            libc::STDERR_FILENO,
+            message.as_ptr() as *const c_void,
+            length + 1,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    /// Run `test` in a child process that has entered the sandbox, and return its wait
+    /// status. The child exits with status 0 when `test` returns true.
+    fn run_sandboxed(test: fn() -> bool) -> c_int {
+        unsafe {
+            let pid = libc::fork();
+            assert!(pid >= 0, "Failed to fork!");
+            if pid == 0 {
+                let passed = install_syscall_filter(SECCOMP_RET_KILL_PROCESS).is_ok() && test();
+                libc::_exit(if passed { 0 } else { 1 });
+            }
+            let mut status = 0;
+            assert_eq!(libc::waitpid(pid, &mut status, 0), pid);
+            status
+        }
+    }
+
+    fn last_error() -> Option<c_int> {
+        io::Error::last_os_error().raw_os_error()
+    }
+
+    fn assert_passed(status: c_int) {
+        assert!(libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0);
+    }
+
+    fn assert_killed(status: c_int) {
+        assert!(libc::WIFSIGNALED(status) && libc::WTERMSIG(status) == libc::SIGSYS);
+    }
+
+    #[test]
+    fn test_opening_files_is_refused() {
+        assert_passed(run_sandboxed(|| unsafe {
+            let path = CStr::from_bytes_with_nul_unchecked(b"/etc/passwd\0");
+            libc::open(path.as_ptr(), libc::O_RDONLY) == -1 && last_error() == Some(libc::EACCES)
+        }));
+    }
+
+    #[test]
+    fn test_disallowed_syscall_kills_process() {
+        assert_killed(run_sandboxed(|| unsafe {
+            libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0);
+            true
+        }));
+    }
+
+    #[test]
+    fn test_creating_processes_kills_process() {
+        assert_killed(run_sandboxed(|| unsafe {
+            libc::fork();
+            true
+        }));
+    }
+
+    #[test]
+    fn test_creating_threads_is_allowed() {
+        assert_passed(run_sandboxed(|| {
+            thread::Builder::new()
+                .name("SandboxedThread".to_owned())
+                .spawn(|| 1)
+                .map_or(false, |thread| thread.join().ok() == Some(1))
+        }));
+    }
+
+    #[test]
+    fn test_clone3_is_not_implemented() {
+        assert_passed(run_sandboxed(|| unsafe {
+            libc::syscall(SYS_CLONE3, ptr::null::<c_void>(), 0) == -1 &&
+                last_error() == Some(libc::ENOSYS)
+        }));
+    }
+
+    #[test]
+    fn test_only_allowed_prctl_options() {
+        assert_passed(run_sandboxed(|| unsafe {
+            let name = CStr::from_bytes_with_nul_unchecked(b"sandboxed\0");
+            libc::prctl(libc::PR_SET_NAME, name.as_ptr(), 0, 0, 0) == 0 &&
+                libc::prctl(libc::PR_SET_DUMPABLE, 1, 0, 0, 0) == -1 &&
+                last_error() == Some(libc::EPERM)
+        }));
+    }
+}
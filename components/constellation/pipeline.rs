@@ -38,7 +38,7 @@ use script_traits::{
 };
 use script_traits::{DocumentActivity, InitialScriptState};
 use script_traits::{LayoutControlMsg, LayoutMsg, LoadData};
-use script_traits::{NewLayoutInfo, SWManagerMsg};
+use script_traits::{NewLayoutInfo, PageState, SWManagerMsg};
 use script_traits::{ScriptThreadFactory, TimerSchedulerMsg, WindowSizeData};
 use servo_config::opts::{self, Opts};
 use servo_config::{prefs, prefs::PrefValue};
@@ -100,6 +100,10 @@ pub struct Pipeline {
 
     /// The title of this pipeline's document.
     pub title: String,
+
+    /// The last reported state of this pipeline's page, which is restored if the pipeline
+    /// crashes and is loaded again.
+    pub page_state: PageState,
 }
 
 /// Initial setup data needed to construct a pipeline.
@@ -383,6 +387,7 @@ impl Pipeline {
             history_states: HashSet::new(),
             completely_loaded: false,
             title: String::new(),
+            page_state: PageState::default(),
         };
 
         pipeline.notify_visibility(is_visible);
@@ -436,6 +441,14 @@ impl Pipeline {
         }
     }
 
+    /// Notify this pipeline whether its document should be frozen.
+    pub fn set_frozen(&self, frozen: bool) {
+        let msg = ConstellationControlMsg::SetDocumentFrozen(self.id, frozen);
+        if let Err(e) = self.event_loop.send(msg) {
+            warn!("Sending freeze message failed ({}).", e);
+        }
+    }
+
     /// The compositor's view of a pipeline.
     pub fn to_sendable(&self) -> CompositionPipeline {
         CompositionPipeline {
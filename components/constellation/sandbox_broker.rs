@@ -0,0 +1,211 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A broker that reads files on behalf of a sandboxed content process, which isn't allowed
+//! to open files itself. The broker runs in the parent process, and only reads the
+//! embedder's resources and files below the directories that content processes need,
+//! like the system font directories.
+
+use embedder_traits::resources::{self, Resource, ResourceReaderMethods};
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender, IpcSharedMemory};
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// A request from a sandboxed content process to its file broker.
+#[derive(Deserialize, Serialize)]
+pub enum FileBrokerRequest {
+    /// Read one of the embedder's resources.
+    ReadResource(Resource, IpcSender<Vec<u8>>),
+    /// Read a file, which is refused unless it is in one of the allowed directories.
+    ReadFile(PathBuf, IpcSender<Result<IpcSharedMemory, String>>),
+}
+
+/// The directories that fonts are installed in.
+fn font_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![
+        PathBuf::from("/usr/share/fonts"),
+        PathBuf::from("/usr/local/share/fonts"),
+    ];
+    if let Some(home) = env::var_os("HOME") {
+        let home = PathBuf::from(home);
+        dirs.push(home.join(".fonts"));
+        dirs.push(home.join(".local/share/fonts"));
+    }
+    if let Some(data_home) = env::var_os("XDG_DATA_HOME") {
+        dirs.push(PathBuf::from(data_home).join("fonts"));
+    }
+    dirs
+}
+
+/// Start a file broker for a new content process, and return the sender that the content
+/// process makes its requests with. The broker stops when that sender is dropped.
+pub fn start_file_broker() -> IpcSender<FileBrokerRequest> {
+    let mut allowed_files = resources::sandbox_access_files();
+    let mut allowed_dirs = resources::sandbox_access_files_dirs();
+    allowed_dirs.extend(font_dirs());
+
+    // Paths are compared once symbolic links are resolved, so that a link in an allowed
+    // directory can't be used to read a file outside of it.
+    allowed_files = allowed_files
+        .iter()
+        .filter_map(|path| path.canonicalize().ok())
+        .collect();
+    allowed_dirs = allowed_dirs
+        .iter()
+        .filter_map(|path| path.canonicalize().ok())
+        .collect();
+
+    let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+    thread::Builder::new()
+        .name("FileBroker".to_owned())
+        .spawn(move || {
+            let broker = FileBroker {
+                allowed_files,
+                allowed_dirs,
+            };
+            broker.run(receiver);
+        })
+        .expect("Thread spawning failed");
+    sender
+}
+
+struct FileBroker {
+    allowed_files: Vec<PathBuf>,
+    allowed_dirs: Vec<PathBuf>,
+}
+
+impl FileBroker {
+    fn run(&self, receiver: IpcReceiver<FileBrokerRequest>) {
+        while let Ok(request) = receiver.recv() {
+            match request {
+                FileBrokerRequest::ReadResource(resource, sender) => {
+                    let _ = sender.send(resources::read_bytes(resource));
+                },
+                FileBrokerRequest::ReadFile(path, sender) => {
+                    let result = self
+                        .read_file(&path)
+                        .map(|bytes| IpcSharedMemory::from_bytes(&bytes))
+                        .map_err(|error| error.to_string());
+                    if let Err(ref error) = result {
+                        warn!("Content process can't read {}: {}", path.display(), error);
+                    }
+                    let _ = sender.send(result);
+                },
+            }
+        }
+    }
+
+    fn read_file(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let path = path.canonicalize()?;
+        let allowed = self.allowed_files.iter().any(|file| *file == path) ||
+            self.allowed_dirs.iter().any(|dir| path.starts_with(dir));
+        if !allowed {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "not in a directory that content processes may read",
+            ));
+        }
+        std::fs::read(path)
+    }
+}
+
+/// The resource reader of a sandboxed content process, which reads resources and files
+/// through the file broker.
+pub struct BrokeredResourceReader {
+    broker: IpcSender<FileBrokerRequest>,
+}
+
+impl BrokeredResourceReader {
+    pub fn new(broker: IpcSender<FileBrokerRequest>) -> BrokeredResourceReader {
+        BrokeredResourceReader { broker }
+    }
+}
+
+impl ResourceReaderMethods for BrokeredResourceReader {
+    fn read(&self, resource: Resource) -> Vec<u8> {
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        self.broker
+            .send(FileBrokerRequest::ReadResource(resource, sender))
+            .expect("File broker has gone away");
+        receiver.recv().expect("File broker has gone away")
+    }
+
+    fn sandbox_access_files(&self) -> Vec<PathBuf> {
+        vec![]
+    }
+
+    fn sandbox_access_files_dirs(&self) -> Vec<PathBuf> {
+        vec![]
+    }
+
+    fn read_file(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fn broker_gone<E>(_: E) -> io::Error {
+            io::Error::new(io::ErrorKind::BrokenPipe, "file broker has gone away")
+        }
+
+        let (sender, receiver) = ipc::channel()?;
+        self.broker
+            .send(FileBrokerRequest::ReadFile(path.to_owned(), sender))
+            .map_err(broker_gone)?;
+        match receiver.recv().map_err(broker_gone)? {
+            Ok(bytes) => Ok(bytes.to_vec()),
+            Err(error) => Err(io::Error::new(io::ErrorKind::PermissionDenied, error)),
+        }
+    }
+
+    fn is_file_access_brokered(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileBroker;
+    use std::env;
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+    use std::process;
+
+    #[test]
+    fn test_files_outside_allowed_directories_are_refused() {
+        let root = env::temp_dir().join(format!("servo-file-broker-{}", process::id()));
+        let allowed_dir = root.join("allowed");
+        let other_dir = root.join("other");
+        fs::create_dir_all(&allowed_dir).unwrap();
+        fs::create_dir_all(&other_dir).unwrap();
+        fs::write(allowed_dir.join("font.ttf"), b"allowed").unwrap();
+        fs::write(other_dir.join("secret"), b"secret").unwrap();
+
+        let broker = FileBroker {
+            allowed_files: vec![],
+            allowed_dirs: vec![allowed_dir.canonicalize().unwrap()],
+        };
+        assert_eq!(
+            broker.read_file(&allowed_dir.join("font.ttf")).unwrap(),
+            b"allowed"
+        );
+        let refused = |path: &Path| broker.read_file(path).unwrap_err().kind();
+        assert_eq!(
+            refused(&other_dir.join("secret")),
+            io::ErrorKind::PermissionDenied
+        );
+        assert_eq!(
+            refused(&allowed_dir.join("../other/secret")),
+            io::ErrorKind::PermissionDenied
+        );
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(other_dir.join("secret"), allowed_dir.join("link")).unwrap();
+            assert_eq!(
+                refused(&allowed_dir.join("link")),
+                io::ErrorKind::PermissionDenied
+            );
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
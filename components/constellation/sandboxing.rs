@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::pipeline::UnprivilegedPipelineContent;
+use crate::sandbox_broker::FileBrokerRequest;
 use crate::serviceworker::ServiceWorkerUnprivilegedContent;
 #[cfg(all(
     not(target_os = "windows"),
@@ -12,6 +13,7 @@ use crate::serviceworker::ServiceWorkerUnprivilegedContent;
     not(target_arch = "aarch64")
 ))]
 use gaol::profile::{Operation, PathPattern, Profile};
+use ipc_channel::ipc::IpcSender;
 use ipc_channel::Error;
 use servo_config::opts::Opts;
 use servo_config::prefs::PrefValue;
@@ -27,6 +29,15 @@ pub enum UnprivilegedContent {
     ServiceWorker(ServiceWorkerUnprivilegedContent),
 }
 
+/// What a content process receives from the constellation when it starts.
+#[derive(Deserialize, Serialize)]
+pub struct ContentProcessBootstrap {
+    pub content: UnprivilegedContent,
+    /// The broker that reads files for the process once it is sandboxed, on platforms
+    /// where the sandbox doesn't allow the process to open files itself.
+    pub file_broker: Option<IpcSender<FileBrokerRequest>>,
+}
+
 impl UnprivilegedContent {
     pub fn opts(&self) -> Opts {
         match self {
@@ -133,11 +144,11 @@ pub fn content_process_sandbox_profile() {
     all(target_arch = "aarch64", not(target_os = "windows"))
 ))]
 pub fn spawn_multiprocess(content: UnprivilegedContent) -> Result<(), Error> {
-    use ipc_channel::ipc::{IpcOneShotServer, IpcSender};
+    use ipc_channel::ipc::IpcOneShotServer;
     // Note that this function can panic, due to process creation,
     // avoiding this panic would require a mechanism for dealing
     // with low-resource scenarios.
-    let (server, token) = IpcOneShotServer::<IpcSender<UnprivilegedContent>>::new()
+    let (server, token) = IpcOneShotServer::<IpcSender<ContentProcessBootstrap>>::new()
         .expect("Failed to create IPC one-shot server.");
 
     let path_to_self = env::current_exe().expect("Failed to get current executor.");
@@ -148,7 +159,10 @@ pub fn spawn_multiprocess(content: UnprivilegedContent) -> Result<(), Error> {
         .expect("Failed to start unsandboxed child process!");
 
     let (_receiver, sender) = server.accept().expect("Server failed to accept.");
-    sender.send(content)?;
+    sender.send(ContentProcessBootstrap {
+        content,
+        file_broker: None,
+    })?;
 
     Ok(())
 }
@@ -161,8 +175,64 @@ pub fn spawn_multiprocess(content: UnprivilegedContent) -> Result<(), Error> {
     not(target_arch = "aarch64")
 ))]
 pub fn spawn_multiprocess(content: UnprivilegedContent) -> Result<(), Error> {
+    use ipc_channel::ipc::IpcOneShotServer;
+
+    // Note that this function can panic, due to process creation,
+    // avoiding this panic would require a mechanism for dealing
+    // with low-resource scenarios.
+    let (server, token) = IpcOneShotServer::<IpcSender<ContentProcessBootstrap>>::new()
+        .expect("Failed to create IPC one-shot server.");
+
+    let file_broker = if content.opts().sandbox {
+        spawn_sandboxed(token)
+    } else {
+        let path_to_self = env::current_exe().expect("Failed to get current executor.");
+        let mut child_process = process::Command::new(path_to_self);
+        setup_common(&mut child_process, token);
+        let _ = child_process
+            .spawn()
+            .expect("Failed to start unsandboxed child process!");
+        None
+    };
+
+    let (_receiver, sender) = server.accept().expect("Server failed to accept.");
+    sender.send(ContentProcessBootstrap {
+        content,
+        file_broker,
+    })?;
+
+    Ok(())
+}
+
+/// Start a sandboxed content process in new namespaces, with a file broker for the files
+/// it can't open once it has entered its seccomp sandbox.
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+fn spawn_sandboxed(token: String) -> Option<IpcSender<FileBrokerRequest>> {
+    use crate::linux_sandbox;
+    use crate::sandbox_broker;
+
+    let path_to_self = env::current_exe().expect("Failed to get current executor.");
+    let mut child_process = process::Command::new(path_to_self);
+    setup_common(&mut child_process, token);
+    linux_sandbox::start_in_new_namespaces(&mut child_process);
+    let _ = child_process
+        .spawn()
+        .expect("Failed to start sandboxed child process!");
+
+    Some(sandbox_broker::start_file_broker())
+}
+
+/// Start a sandboxed content process with the `gaol` API.
+#[cfg(all(
+    not(target_os = "windows"),
+    not(target_os = "ios"),
+    not(target_os = "android"),
+    not(target_arch = "arm"),
+    not(target_arch = "aarch64"),
+    not(all(target_os = "linux", target_arch = "x86_64"))
+))]
+fn spawn_sandboxed(token: String) -> Option<IpcSender<FileBrokerRequest>> {
     use gaol::sandbox::{self, Sandbox, SandboxMethods};
-    use ipc_channel::ipc::{IpcOneShotServer, IpcSender};
 
     impl CommandMethods for sandbox::Command {
         fn arg<T>(&mut self, arg: T)
@@ -181,34 +251,15 @@ pub fn spawn_multiprocess(content: UnprivilegedContent) -> Result<(), Error> {
         }
     }
 
-    // Note that this function can panic, due to process creation,
-    // avoiding this panic would require a mechanism for dealing
-    // with low-resource scenarios.
-    let (server, token) = IpcOneShotServer::<IpcSender<UnprivilegedContent>>::new()
-        .expect("Failed to create IPC one-shot server.");
-
-    // If there is a sandbox, use the `gaol` API to create the child process.
-    if content.opts().sandbox {
-        let mut command = sandbox::Command::me().expect("Failed to get current sandbox.");
-        setup_common(&mut command, token);
+    let mut command = sandbox::Command::me().expect("Failed to get current sandbox.");
+    setup_common(&mut command, token);
 
-        let profile = content_process_sandbox_profile();
-        let _ = Sandbox::new(profile)
-            .start(&mut command)
-            .expect("Failed to start sandboxed child process!");
-    } else {
-        let path_to_self = env::current_exe().expect("Failed to get current executor.");
-        let mut child_process = process::Command::new(path_to_self);
-        setup_common(&mut child_process, token);
-        let _ = child_process
-            .spawn()
-            .expect("Failed to start unsandboxed child process!");
-    }
+    let profile = content_process_sandbox_profile();
+    let _ = Sandbox::new(profile)
+        .start(&mut command)
+        .expect("Failed to start sandboxed child process!");
 
-    let (_receiver, sender) = server.accept().expect("Server failed to accept.");
-    sender.send(content)?;
-
-    Ok(())
+    None
 }
 
 #[cfg(any(target_os = "windows", target_os = "ios"))]
@@ -34,6 +34,65 @@ impl JointSessionHistory {
         }
     }
 
+    /// The session history of a restored top-level browsing context, whose entries each
+    /// show a separate document of the top-level browsing context, so that there is a diff
+    /// between every pair of adjacent entries.
+    pub fn restored(
+        browsing_context_id: BrowsingContextId,
+        reloaders: &[NeedsToReload],
+        current_index: usize,
+    ) -> JointSessionHistory {
+        let diff = |index: usize| SessionHistoryDiff::BrowsingContextDiff {
+            browsing_context_id,
+            old_reloader: reloaders[index - 1].clone(),
+            new_reloader: reloaders[index].clone(),
+        };
+        JointSessionHistory {
+            past: (1..current_index + 1).map(&diff).collect(),
+            future: (current_index + 1..reloaders.len())
+                .rev()
+                .map(&diff)
+                .collect(),
+        }
+    }
+
+    /// The entries of the session history of a top-level browsing context from the oldest to
+    /// the newest, and the index of the current entry, which is `current`. The entry that
+    /// shows the document of a reloader is given by `session_entry`.
+    pub fn entries<F>(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        current: SessionEntry,
+        session_entry: F,
+    ) -> (Vec<SessionEntry>, usize)
+    where
+        F: Fn(&NeedsToReload) -> Option<SessionEntry>,
+    {
+        let mut entries = vec![];
+        let mut entry = current.clone();
+        for diff in self.past.iter().rev() {
+            if let Some(past_entry) =
+                entry.adjacent(top_level_browsing_context_id, diff, true, &session_entry)
+            {
+                entries.push(past_entry.clone());
+                entry = past_entry;
+            }
+        }
+        entries.reverse();
+        let current_index = entries.len();
+        entries.push(current.clone());
+        let mut entry = current;
+        for diff in self.future.iter().rev() {
+            if let Some(future_entry) =
+                entry.adjacent(top_level_browsing_context_id, diff, false, &session_entry)
+            {
+                entries.push(future_entry.clone());
+                entry = future_entry;
+            }
+        }
+        (entries, current_index)
+    }
+
     pub fn history_length(&self) -> usize {
         self.past.len() + 1 + self.future.len()
     }
@@ -106,6 +165,84 @@ impl JointSessionHistory {
     }
 }
 
+/// An entry of the session history of a top-level browsing context, as it is saved: the
+/// document that the entry shows, and its URL and history state.
+#[derive(Clone, Debug)]
+pub struct SessionEntry {
+    pub reloader: NeedsToReload,
+    pub url: ServoUrl,
+    pub history_state_id: Option<HistoryStateId>,
+}
+
+impl SessionEntry {
+    /// The entry that is reached by traversing a diff from this entry, into the past or
+    /// into the future. Diffs that only change nested browsing contexts don't lead to
+    /// another entry.
+    fn adjacent<F>(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        diff: &SessionHistoryDiff,
+        into_past: bool,
+        session_entry: &F,
+    ) -> Option<SessionEntry>
+    where
+        F: Fn(&NeedsToReload) -> Option<SessionEntry>,
+    {
+        match *diff {
+            SessionHistoryDiff::BrowsingContextDiff {
+                browsing_context_id,
+                ref old_reloader,
+                ref new_reloader,
+            } => {
+                if browsing_context_id != top_level_browsing_context_id {
+                    return None;
+                }
+                session_entry(if into_past {
+                    old_reloader
+                } else {
+                    new_reloader
+                })
+            },
+            SessionHistoryDiff::PipelineDiff {
+                ref pipeline_reloader,
+                old_history_state_id,
+                ref old_url,
+                new_history_state_id,
+                ref new_url,
+            } => {
+                if *pipeline_reloader != self.reloader {
+                    return None;
+                }
+                let (url, history_state_id) = if into_past {
+                    (old_url, old_history_state_id)
+                } else {
+                    (new_url, Some(new_history_state_id))
+                };
+                Some(SessionEntry {
+                    reloader: self.reloader.clone(),
+                    url: url.clone(),
+                    history_state_id,
+                })
+            },
+            SessionHistoryDiff::HashDiff {
+                ref pipeline_reloader,
+                ref old_url,
+                ref new_url,
+            } => {
+                if *pipeline_reloader != self.reloader {
+                    return None;
+                }
+                let url = if into_past { old_url } else { new_url };
+                Some(SessionEntry {
+                    reloader: self.reloader.clone(),
+                    url: url.clone(),
+                    history_state_id: self.history_state_id,
+                })
+            },
+        }
+    }
+}
+
 /// Represents a pending change in a session history, that will be applied
 /// once the new pipeline has loaded and completed initial layout / paint.
 pub struct SessionHistoryChange {
@@ -270,3 +407,135 @@ impl SessionHistoryDiff {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use msg::constellation_msg::{PipelineNamespace, PipelineNamespaceId};
+    use net_traits::request::Referrer;
+    use script_traits::LoadOrigin;
+
+    fn url(url: &str) -> ServoUrl {
+        ServoUrl::parse(url).unwrap()
+    }
+
+    fn reloader(url: ServoUrl) -> NeedsToReload {
+        let load_data = LoadData::new(
+            LoadOrigin::Constellation,
+            url,
+            None,
+            Referrer::NoReferrer,
+            None,
+        );
+        NeedsToReload::Yes(PipelineId::new(), load_data)
+    }
+
+    /// A restored session history with three entries whose middle one is current, and
+    /// the entry that its current document shows.
+    fn restored_history(
+        namespace: u32,
+    ) -> (TopLevelBrowsingContextId, JointSessionHistory, SessionEntry) {
+        PipelineNamespace::install(PipelineNamespaceId(namespace));
+        let top_level_browsing_context_id = TopLevelBrowsingContextId::new();
+        let current = SessionEntry {
+            reloader: NeedsToReload::No(PipelineId::new()),
+            url: url("https://example.com/b"),
+            history_state_id: None,
+        };
+        let reloaders = vec![
+            reloader(url("https://example.com/a")),
+            current.reloader.clone(),
+            reloader(url("https://example.org/c")),
+        ];
+        let history = JointSessionHistory::restored(
+            BrowsingContextId::from(top_level_browsing_context_id),
+            &reloaders,
+            1,
+        );
+        (top_level_browsing_context_id, history, current)
+    }
+
+    fn entries(
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        history: &JointSessionHistory,
+        current: &SessionEntry,
+    ) -> (Vec<(String, Option<HistoryStateId>)>, usize) {
+        let (entries, current_index) =
+            history.entries(top_level_browsing_context_id, current.clone(), |reloader| {
+                match *reloader {
+                    NeedsToReload::No(_) if *reloader == current.reloader => Some(current.clone()),
+                    NeedsToReload::No(_) => None,
+                    NeedsToReload::Yes(_, ref load_data) => Some(SessionEntry {
+                        reloader: reloader.clone(),
+                        url: load_data.url.clone(),
+                        history_state_id: None,
+                    }),
+                }
+            });
+        let entries = entries
+            .into_iter()
+            .map(|entry| (entry.url.into_string(), entry.history_state_id))
+            .collect();
+        (entries, current_index)
+    }
+
+    #[test]
+    fn test_restored_history_has_the_saved_entries() {
+        let (top_level_browsing_context_id, history, current) = restored_history(6001);
+        assert_eq!(history.history_length(), 3);
+        assert_eq!(history.past.len(), 1);
+        assert_eq!(history.future.len(), 1);
+
+        let (entries, current_index) = entries(top_level_browsing_context_id, &history, &current);
+        assert_eq!(
+            entries,
+            vec![
+                ("https://example.com/a".to_owned(), None),
+                ("https://example.com/b".to_owned(), None),
+                ("https://example.org/c".to_owned(), None),
+            ]
+        );
+        assert_eq!(current_index, 1);
+    }
+
+    #[test]
+    fn test_entries_of_history_changes_after_restoring() {
+        let (top_level_browsing_context_id, mut history, current) = restored_history(6002);
+        let history_state_id = HistoryStateId::new();
+
+        // A nested browsing context that navigates doesn't add an entry of its own to the
+        // saved session history.
+        history.push_diff(SessionHistoryDiff::BrowsingContextDiff {
+            browsing_context_id: BrowsingContextId::new(),
+            old_reloader: NeedsToReload::No(PipelineId::new()),
+            new_reloader: NeedsToReload::No(PipelineId::new()),
+        });
+        // The current document pushes a history state.
+        history.push_diff(SessionHistoryDiff::PipelineDiff {
+            pipeline_reloader: current.reloader.clone(),
+            old_history_state_id: None,
+            old_url: current.url.clone(),
+            new_history_state_id: history_state_id,
+            new_url: url("https://example.com/b?pushed"),
+        });
+        let current = SessionEntry {
+            reloader: current.reloader,
+            url: url("https://example.com/b?pushed"),
+            history_state_id: Some(history_state_id),
+        };
+
+        let (entries, current_index) = entries(top_level_browsing_context_id, &history, &current);
+        assert_eq!(
+            entries,
+            vec![
+                ("https://example.com/a".to_owned(), None),
+                ("https://example.com/b".to_owned(), None),
+                (
+                    "https://example.com/b?pushed".to_owned(),
+                    Some(history_state_id)
+                ),
+            ]
+        );
+        assert_eq!(current_index, 2);
+    }
+}
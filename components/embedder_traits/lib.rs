@@ -190,6 +190,9 @@ pub enum EmbedderMsg {
     CloseBrowser,
     /// A pipeline panicked. First string is the reason, second one is the backtrace.
     Panic(String, Option<String>),
+    /// The content of a browser crashed, and was either loaded again or replaced by an
+    /// error page.
+    CrashReport(CrashReport),
     /// Open dialog to select bluetooth device.
     GetSelectedBluetoothDevice(Vec<String>, IpcSender<Option<String>>),
     /// Open file dialog to select files. Set boolean flag to true allows to select multiple files.
@@ -202,13 +205,18 @@ pub enum EmbedderMsg {
     HideIME,
     /// Servo has shut down
     Shutdown,
-    /// Report a complete sampled profile
+    /// Report a complete sampled profile, in the Gecko profile format
     ReportProfile(Vec<u8>),
+    /// Report the profile of a permanent hang, in the Gecko profile format
+    ReportHangProfile(Vec<u8>),
     /// Notifies the embedder about media session events
     /// (i.e. when there is metadata for the active media session, playback state changes...).
     MediaSessionEvent(MediaSessionEvent),
     /// Report the status of Devtools Server with a token that can be used to bypass the permission prompt.
     OnDevtoolsStarted(Result<u16, ()>, String),
+    /// A message posted by a document through its embedder messaging channel,
+    /// along with the pipeline and URL of that document.
+    MessageFromPage(PipelineId, ServoUrl, MessageFromPage),
 }
 
 impl Debug for EmbedderMsg {
@@ -233,6 +241,7 @@ impl Debug for EmbedderMsg {
             EmbedderMsg::LoadStart => write!(f, "LoadStart"),
             EmbedderMsg::LoadComplete => write!(f, "LoadComplete"),
             EmbedderMsg::Panic(..) => write!(f, "Panic"),
+            EmbedderMsg::CrashReport(..) => write!(f, "CrashReport"),
             EmbedderMsg::GetSelectedBluetoothDevice(..) => write!(f, "GetSelectedBluetoothDevice"),
             EmbedderMsg::SelectFiles(..) => write!(f, "SelectFiles"),
             EmbedderMsg::PromptPermission(..) => write!(f, "PromptPermission"),
@@ -242,13 +251,52 @@ impl Debug for EmbedderMsg {
             EmbedderMsg::AllowOpeningBrowser(..) => write!(f, "AllowOpeningBrowser"),
             EmbedderMsg::BrowserCreated(..) => write!(f, "BrowserCreated"),
             EmbedderMsg::ReportProfile(..) => write!(f, "ReportProfile"),
+            EmbedderMsg::ReportHangProfile(..) => write!(f, "ReportHangProfile"),
             EmbedderMsg::MediaSessionEvent(..) => write!(f, "MediaSessionEvent"),
             EmbedderMsg::OnDevtoolsStarted(..) => write!(f, "OnDevtoolsStarted"),
             EmbedderMsg::ShowContextMenu(..) => write!(f, "ShowContextMenu"),
+            EmbedderMsg::MessageFromPage(..) => write!(f, "MessageFromPage"),
         }
     }
 }
 
+/// A message posted by a document through its embedder messaging channel.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MessageFromPage {
+    /// The message as serialized by the structured clone algorithm, which keeps values
+    /// such as dates, maps, typed arrays and cyclic objects. It can be posted to a document
+    /// as is with `MessageToPage::StructuredClone`.
+    pub serialized: Vec<u8>,
+    /// The message as JSON, for embedders that can't deserialize structured clones,
+    /// or `None` if it has no JSON representation, like `undefined` or a cyclic object.
+    pub json: Option<String>,
+}
+
+/// A message posted by the embedder to the embedder messaging channel of a document.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum MessageToPage {
+    /// A message structured-cloned by a document, from `MessageFromPage::serialized`.
+    StructuredClone(Vec<u8>),
+    /// A message in JSON, which the document parses.
+    Json(String),
+}
+
+/// A report of a crash of the content of a browser.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CrashReport {
+    /// The reason of the crash.
+    pub reason: String,
+    /// The backtrace of the crash, if known.
+    pub backtrace: Option<String>,
+    /// The URL of the page that crashed.
+    pub url: ServoUrl,
+    /// The number of times that the content of the browser crashed recently, including
+    /// this crash.
+    pub recent_crashes: usize,
+    /// Whether the page was loaded again, rather than replaced by an error page.
+    pub recovered: bool,
+}
+
 /// Filter for file selection;
 /// the `String` content is expected to be extension (e.g, "doc", without the prefixing ".")
 #[derive(Clone, Debug, Deserialize, Serialize)]
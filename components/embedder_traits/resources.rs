@@ -2,7 +2,9 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use std::path::PathBuf;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::sync::{Once, RwLock};
 
 lazy_static! {
@@ -47,6 +49,24 @@ pub fn sandbox_access_files_dirs() -> Vec<PathBuf> {
         .sandbox_access_files_dirs()
 }
 
+/// Read a file that isn't a resource, such as a system font.
+pub fn read_file(path: &Path) -> io::Result<Vec<u8>> {
+    match *RES.read().unwrap() {
+        Some(ref reader) => reader.read_file(path),
+        None => fs::read(path),
+    }
+}
+
+/// Whether files have to be read with `read_file`, because this process is sandboxed and
+/// can't open them itself.
+pub fn is_file_access_brokered() -> bool {
+    RES.read()
+        .unwrap()
+        .as_ref()
+        .map_or(false, |reader| reader.is_file_access_brokered())
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub enum Resource {
     Preferences,
     BluetoothBlocklist,
@@ -62,12 +82,21 @@ pub enum Resource {
     RippyPNG,
     MediaControlsCSS,
     MediaControlsJS,
+    EnglishHyphenationPatterns,
+    FrenchHyphenationPatterns,
+    GermanHyphenationPatterns,
 }
 
 pub trait ResourceReaderMethods {
     fn read(&self, res: Resource) -> Vec<u8>;
     fn sandbox_access_files(&self) -> Vec<PathBuf>;
     fn sandbox_access_files_dirs(&self) -> Vec<PathBuf>;
+    fn read_file(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+    fn is_file_access_brokered(&self) -> bool {
+        false
+    }
 }
 
 fn resources_for_tests() -> Box<dyn ResourceReaderMethods + Sync + Send> {
@@ -98,6 +127,9 @@ fn resources_for_tests() -> Box<dyn ResourceReaderMethods + Sync + Send> {
                 Resource::RippyPNG => "rippy.png",
                 Resource::MediaControlsCSS => "media-controls.css",
                 Resource::MediaControlsJS => "media-controls.js",
+                Resource::EnglishHyphenationPatterns => "hyphenation/hyph-en-us.txt",
+                Resource::FrenchHyphenationPatterns => "hyphenation/hyph-fr.txt",
+                Resource::GermanHyphenationPatterns => "hyphenation/hyph-de-1996.txt",
             };
             let mut path = env::current_exe().unwrap();
             path = path.canonicalize().unwrap();
@@ -10,6 +10,7 @@ use crate::platform::font_template::FontTemplateData;
 use crate::text::glyph::GlyphId;
 use crate::text::util::fixed_to_float;
 use app_units::Au;
+use embedder_traits::resources;
 use freetype::freetype::FT_Sfnt_Tag;
 use freetype::freetype::{FT_Done_Face, FT_New_Face, FT_New_Memory_Face};
 use freetype::freetype::{FT_F26Dot6, FT_Face, FT_FaceRec};
@@ -22,9 +23,11 @@ use freetype::freetype::{FT_SizeRec, FT_Size_Metrics, FT_UInt, FT_Vector};
 use freetype::succeeded;
 use freetype::tt_os2::TT_OS2;
 use servo_atoms::Atom;
+use std::collections::HashMap;
 use std::ffi::CString;
 use std::os::raw::{c_char, c_long};
-use std::sync::Arc;
+use std::path::Path;
+use std::sync::{Arc, Mutex, Weak};
 use std::{mem, ptr};
 use style::computed_values::font_stretch::T as FontStretch;
 use style::computed_values::font_weight::T as FontWeight;
@@ -41,6 +44,28 @@ const FT_LOAD_TARGET_LIGHT: FT_Int32 = 1 << 16;
 // TODO(gw): Make this configurable.
 const GLYPH_LOAD_FLAGS: FT_Int32 = FT_LOAD_TARGET_LIGHT;
 
+lazy_static! {
+    /// The data of the font files read through the file broker, so that each file is
+    /// only read once while fonts loaded from it are alive.
+    static ref BROKERED_FONT_DATA: Mutex<HashMap<Atom, Weak<FontTemplateData>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Returns the data of a font file, read through the file broker unless it is still
+/// loaded for another font.
+fn brokered_font_data(identifier: &Atom) -> Result<Arc<FontTemplateData>, ()> {
+    let mut font_data = BROKERED_FONT_DATA.lock().unwrap();
+    if let Some(data) = font_data.get(identifier).and_then(|data| data.upgrade()) {
+        return Ok(data);
+    }
+    font_data.retain(|_, data| data.strong_count() > 0);
+
+    let bytes = resources::read_file(Path::new(&**identifier)).map_err(|_| ())?;
+    let data = Arc::new(FontTemplateData::new(identifier.clone(), Some(bytes)).map_err(|_| ())?);
+    font_data.insert(identifier.clone(), Arc::downgrade(&data));
+    Ok(data)
+}
+
 fn fixed_to_float_ft(f: i32) -> f64 {
     fixed_to_float(6, f)
 }
@@ -139,6 +164,14 @@ impl FontHandleMethods for FontHandle {
             return Err(());
         }
 
+        // FreeType can't open the font file itself in a sandboxed content process, so the
+        // font is read through the file broker and loaded from memory instead.
+        let template = if template.bytes.is_none() && resources::is_file_access_brokered() {
+            brokered_font_data(&template.identifier)?
+        } else {
+            template
+        };
+
         let face = create_face(ft_ctx, &template, pt_size)?;
 
         let mut handle = FontHandle {
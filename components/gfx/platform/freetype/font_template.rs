@@ -2,11 +2,11 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use embedder_traits::resources;
 use servo_atoms::Atom;
 use std::fmt;
-use std::fs::File;
-use std::io::{Error, Read};
-use std::path::PathBuf;
+use std::io::Error;
+use std::path::{Path, PathBuf};
 use webrender_api::NativeFontHandle;
 
 /// Platform specific font representation for Linux.
@@ -45,10 +45,7 @@ impl FontTemplateData {
     /// and should never be done lightly.
     pub fn bytes(&self) -> Vec<u8> {
         self.bytes_if_in_memory().unwrap_or_else(|| {
-            let mut file = File::open(&*self.identifier).expect("Couldn't open font file!");
-            let mut buffer = vec![];
-            file.read_to_end(&mut buffer).unwrap();
-            buffer
+            resources::read_file(Path::new(&*self.identifier)).expect("Couldn't read font file!")
         })
     }
 
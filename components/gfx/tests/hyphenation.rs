@@ -0,0 +1,47 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use gfx::text::hyphenation::Hyphenator;
+
+fn hyphenate(hyphenator: &Hyphenator, text: &str) -> String {
+    let mut hyphenated = String::new();
+    let mut start = 0;
+    for point in hyphenator.hyphenation_points(text) {
+        hyphenated.push_str(&text[start..point]);
+        hyphenated.push('-');
+        start = point;
+    }
+    hyphenated.push_str(&text[start..]);
+    hyphenated
+}
+
+#[test]
+fn test_hyphenation_points() {
+    // From Liang's thesis.
+    let hyphenator = Hyphenator::new("1na\nn1a % comment\n.hy3ph he2n hena4 hen5at", 1, 1);
+    assert_eq!(hyphenate(&hyphenator, "hyphenation"), "hy-phen-ation");
+    assert_eq!(
+        hyphenate(&hyphenator, "Hyphenation, hyphenation."),
+        "Hy-phen-ation, hy-phen-ation."
+    );
+}
+
+#[test]
+fn test_hyphenation_minimum_lengths() {
+    let hyphenator = Hyphenator::new("1na\nn1a .hy3ph he2n hena4 hen5at", 3, 5);
+    assert_eq!(hyphenate(&hyphenator, "hyphenation"), "hyphen-ation");
+}
+
+#[test]
+fn test_hyphenator_for_language() {
+    embedder_traits::resources::set_for_tests();
+
+    let english = Hyphenator::for_language("en-US").unwrap();
+    assert_eq!(hyphenate(&english, "hyphenation"), "hy-phen-ation");
+    assert!(Hyphenator::for_language("EN_gb").is_some());
+    assert!(Hyphenator::for_language("fr").is_some());
+    assert!(Hyphenator::for_language("de-CH").is_some());
+    assert!(Hyphenator::for_language("xx").is_none());
+    assert!(Hyphenator::for_language("").is_none());
+}
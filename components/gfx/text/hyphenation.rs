@@ -0,0 +1,224 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Hyphenation, which lets lines break within words at soft hyphens, and with
+//! `hyphens: auto` where the hyphenation patterns of their language allow it.
+//! https://drafts.csswg.org/css-text-3/#hyphenation
+
+use embedder_traits::resources::{self, Resource};
+use std::collections::HashMap;
+use std::iter;
+use std::sync::{Arc, Mutex};
+use style::computed_values::hyphens::T as Hyphens;
+use style::properties::ComputedValues;
+
+/// U+00AD SOFT HYPHEN, which marks a hyphenation opportunity and is otherwise invisible.
+pub const SOFT_HYPHEN: char = '\u{00AD}';
+
+/// U+2010 HYPHEN, which is rendered at the end of lines broken at a hyphenation opportunity,
+/// or `-` if the font has no glyph for it.
+pub const HYPHEN: char = '\u{2010}';
+
+/// How words can be hyphenated when breaking lines.
+#[derive(Clone, Debug)]
+pub enum Hyphenation {
+    /// Words are never hyphenated, not even at soft hyphens.
+    None,
+    /// Words are only hyphenated at soft hyphens.
+    Manual,
+    /// Words are hyphenated at soft hyphens, or where the hyphenation patterns
+    /// of their language allow it if they have none.
+    Auto(Arc<Hyphenator>),
+}
+
+impl Hyphenation {
+    /// Returns how the text of an element with the given style can be hyphenated, from its
+    /// `hyphens` property and its language. `hyphens: auto` behaves like `hyphens: manual`
+    /// for languages without hyphenation patterns.
+    pub fn from_style(style: &ComputedValues) -> Self {
+        match style.get_inherited_text().hyphens {
+            Hyphens::None => Hyphenation::None,
+            Hyphens::Manual => Hyphenation::Manual,
+            Hyphens::Auto => match Hyphenator::for_language(&style.get_font()._x_lang.0) {
+                Some(hyphenator) => Hyphenation::Auto(hyphenator),
+                None => Hyphenation::Manual,
+            },
+        }
+    }
+
+    /// Whether lines can break after soft hyphens.
+    pub fn breaks_at_soft_hyphens(&self) -> bool {
+        !matches!(*self, Hyphenation::None)
+    }
+}
+
+/// A language for which hyphenation patterns are bundled as resources.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum PatternLanguage {
+    English,
+    French,
+    German,
+}
+
+impl PatternLanguage {
+    /// Returns the language of a BCP 47 language tag like `en-GB`, from its primary subtag.
+    fn from_language_tag(tag: &str) -> Option<Self> {
+        let primary_subtag = tag.split(|c| c == '-' || c == '_').next()?;
+        if primary_subtag.eq_ignore_ascii_case("en") {
+            Some(PatternLanguage::English)
+        } else if primary_subtag.eq_ignore_ascii_case("fr") {
+            Some(PatternLanguage::French)
+        } else if primary_subtag.eq_ignore_ascii_case("de") {
+            Some(PatternLanguage::German)
+        } else {
+            None
+        }
+    }
+
+    fn resource(self) -> Resource {
+        match self {
+            PatternLanguage::English => Resource::EnglishHyphenationPatterns,
+            PatternLanguage::French => Resource::FrenchHyphenationPatterns,
+            PatternLanguage::German => Resource::GermanHyphenationPatterns,
+        }
+    }
+
+    /// The minimum numbers of characters before and after a hyphenation point,
+    /// which are those TeX uses with the patterns.
+    fn minimum_lengths(self) -> (usize, usize) {
+        match self {
+            PatternLanguage::English => (2, 3),
+            PatternLanguage::French | PatternLanguage::German => (2, 2),
+        }
+    }
+}
+
+lazy_static! {
+    static ref HYPHENATORS: Mutex<HashMap<PatternLanguage, Arc<Hyphenator>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Finds where words can be hyphenated with Frank Liang's algorithm, as used by TeX.
+#[derive(Debug)]
+pub struct Hyphenator {
+    /// The priorities of the positions between the letters of each pattern,
+    /// keyed by these letters. Odd priorities allow hyphenation, even ones forbid it.
+    patterns: HashMap<String, Vec<u8>>,
+    /// The number of letters of the longest pattern.
+    max_pattern_length: usize,
+    /// The minimum number of characters before a hyphenation point.
+    left_min: usize,
+    /// The minimum number of characters after a hyphenation point.
+    right_min: usize,
+}
+
+impl Hyphenator {
+    /// Parses hyphenation patterns as in the argument of TeX's `\patterns`, where `%` starts
+    /// a comment, like `.ach4` or `4b1ly`.
+    pub fn new(patterns: &str, left_min: usize, right_min: usize) -> Self {
+        let mut hyphenator = Hyphenator {
+            patterns: HashMap::new(),
+            max_pattern_length: 0,
+            left_min,
+            right_min,
+        };
+        let patterns = patterns
+            .lines()
+            .flat_map(|line| line.split('%').next().unwrap_or("").split_whitespace());
+        for pattern in patterns {
+            let mut letters = String::new();
+            let mut priorities = vec![0];
+            for c in pattern.chars() {
+                match c.to_digit(10) {
+                    Some(priority) => *priorities.last_mut().unwrap() = priority as u8,
+                    None => {
+                        letters.push(c);
+                        priorities.push(0);
+                    },
+                }
+            }
+            hyphenator.max_pattern_length = hyphenator.max_pattern_length.max(priorities.len() - 1);
+            hyphenator.patterns.insert(letters, priorities);
+        }
+        hyphenator
+    }
+
+    /// Returns the hyphenator for a BCP 47 language tag, or `None` if there are no
+    /// hyphenation patterns for its language. Patterns are loaded the first time
+    /// they are needed, and shared afterwards.
+    pub fn for_language(tag: &str) -> Option<Arc<Hyphenator>> {
+        let language = PatternLanguage::from_language_tag(tag)?;
+        let mut hyphenators = HYPHENATORS.lock().unwrap();
+        let hyphenator = hyphenators.entry(language).or_insert_with(|| {
+            let (left_min, right_min) = language.minimum_lengths();
+            let patterns = resources::read_string(language.resource());
+            Arc::new(Hyphenator::new(&patterns, left_min, right_min))
+        });
+        Some(hyphenator.clone())
+    }
+
+    /// Returns the byte indices in `text` where its words can be hyphenated, in order.
+    pub fn hyphenation_points(&self, text: &str) -> Vec<usize> {
+        let mut points = Vec::new();
+        let mut word_start = None;
+        // A space at the end finishes the last word.
+        for (index, c) in text.char_indices().chain(iter::once((text.len(), ' '))) {
+            match (word_start, c.is_alphabetic()) {
+                (None, true) => word_start = Some(index),
+                (Some(start), false) => {
+                    let word = &text[start..index];
+                    points.extend(self.hyphenate_word(word).map(|point| start + point));
+                    word_start = None;
+                },
+                _ => {},
+            }
+        }
+        points
+    }
+
+    /// Returns the byte indices in `word` where it can be hyphenated, in order.
+    fn hyphenate_word<'a>(&self, word: &'a str) -> impl Iterator<Item = usize> + 'a {
+        // Patterns are lowercase, and match the start and end of words with `.`.
+        let lowercase = |c: char| {
+            let mut lowercase = c.to_lowercase();
+            match (lowercase.next(), lowercase.next()) {
+                (Some(lowercase), None) => lowercase,
+                _ => c,
+            }
+        };
+        let letters: Vec<char> = iter::once('.')
+            .chain(word.chars().map(lowercase))
+            .chain(iter::once('.'))
+            .collect();
+
+        // The priority of the position before each letter,
+        // and after the last one, is the highest of all matching patterns.
+        let mut priorities = vec![0; letters.len() + 1];
+        let mut key = String::new();
+        for start in 0..letters.len() {
+            key.clear();
+            let end = letters.len().min(start + self.max_pattern_length);
+            for letter in &letters[start..end] {
+                key.push(*letter);
+                if let Some(pattern) = self.patterns.get(&key) {
+                    for (priority, pattern_priority) in priorities[start..].iter_mut().zip(pattern)
+                    {
+                        *priority = (*priority).max(*pattern_priority);
+                    }
+                }
+            }
+        }
+
+        let (left_min, right_min) = (self.left_min.max(1), self.right_min.max(1));
+        let char_count = letters.len() - 2;
+        word.char_indices()
+            .enumerate()
+            .filter(move |&(i, _)| {
+                // `i` characters come before the position before character `i`,
+                // which is position `i + 1` with the leading `.`.
+                i >= left_min && i + right_min <= char_count && priorities[i + 1] % 2 == 1
+            })
+            .map(|(_, (index, _))| index)
+    }
+}
@@ -6,6 +6,7 @@ pub use crate::text::shaping::Shaper;
 pub use crate::text::text_run::TextRun;
 
 pub mod glyph;
+pub mod hyphenation;
 pub mod shaping;
 pub mod text_run;
 pub mod util;
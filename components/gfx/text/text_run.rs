@@ -6,10 +6,12 @@ use crate::font::{Font, FontHandleMethods, FontMetrics, ShapingFlags};
 use crate::font::{RunMetrics, ShapingOptions};
 use crate::platform::font_template::FontTemplateData;
 use crate::text::glyph::{ByteIndex, GlyphStore};
+use crate::text::hyphenation::{Hyphenation, HYPHEN, SOFT_HYPHEN};
 use app_units::Au;
 use range::Range;
 use std::cell::Cell;
 use std::cmp::{max, Ordering};
+use std::iter;
 use std::slice::Iter;
 use std::sync::Arc;
 use style::str::char_is_whitespace;
@@ -56,6 +58,9 @@ pub struct GlyphRun {
     pub glyph_store: Arc<GlyphStore>,
     /// The byte range of characters in the containing run.
     pub range: Range<ByteIndex>,
+    /// The glyphs of the hyphen to render at the end of a line broken after this glyph run,
+    /// if the end of this glyph run is a hyphenation opportunity.
+    pub hyphen: Option<Arc<GlyphStore>>,
 }
 
 pub struct NaturalWordSliceIterator<'a> {
@@ -181,10 +186,12 @@ impl<'a> TextRun {
         font: &mut Font,
         text: String,
         options: &ShapingOptions,
+        hyphenation: &Hyphenation,
         bidi_level: bidi::Level,
         breaker: &mut Option<LineBreakLeafIter>,
     ) -> (TextRun, bool) {
-        let (glyphs, break_at_zero) = TextRun::break_and_shape(font, &text, options, breaker);
+        let (glyphs, break_at_zero) =
+            TextRun::break_and_shape(font, &text, options, hyphenation, breaker);
         (
             TextRun {
                 text: Arc::new(text),
@@ -204,6 +211,7 @@ impl<'a> TextRun {
         font: &mut Font,
         text: &str,
         options: &ShapingOptions,
+        hyphenation: &Hyphenation,
         breaker: &mut Option<LineBreakLeafIter>,
     ) -> (Vec<GlyphRun>, bool) {
         let mut glyphs = vec![];
@@ -211,6 +219,8 @@ impl<'a> TextRun {
 
         let mut finished = false;
         let mut break_at_zero = false;
+        let mut after_soft_hyphen = false;
+        let mut hyphen_glyphs = None;
 
         if breaker.is_none() {
             if text.len() == 0 {
@@ -236,6 +246,7 @@ impl<'a> TextRun {
 
             // Split off any trailing whitespace into a separate glyph run.
             let mut whitespace = slice.end..slice.end;
+            let at_soft_hyphen = word.ends_with(SOFT_HYPHEN);
             if let Some((i, _)) = word
                 .char_indices()
                 .rev()
@@ -244,20 +255,55 @@ impl<'a> TextRun {
             {
                 whitespace.start = slice.start + i;
                 slice.end = whitespace.start;
-            } else if idx != text.len() && options.flags.contains(ShapingFlags::KEEP_ALL_FLAG) {
-                // If there's no whitespace and word-break is set to
-                // keep-all, try increasing the slice.
-                continue;
+            } else if idx != text.len() {
+                // If there's no whitespace and word-break is set to keep-all,
+                // or if this is a soft hyphen that is not a hyphenation opportunity,
+                // try increasing the slice.
+                let keep = if at_soft_hyphen {
+                    !hyphenation.breaks_at_soft_hyphens()
+                } else {
+                    options.flags.contains(ShapingFlags::KEEP_ALL_FLAG)
+                };
+                if keep {
+                    continue;
+                }
             }
             if slice.len() > 0 {
-                glyphs.push(GlyphRun {
-                    glyph_store: font.shape_text(&text[slice.clone()], options),
-                    range: Range::new(
-                        ByteIndex(slice.start as isize),
-                        ByteIndex(slice.len() as isize),
-                    ),
-                });
+                // Automatic hyphenation only applies to words without soft hyphens.
+                // https://drafts.csswg.org/css-text-3/#valdef-hyphens-auto
+                let hyphenation_points = match *hyphenation {
+                    Hyphenation::Auto(ref hyphenator) if !at_soft_hyphen && !after_soft_hyphen => {
+                        hyphenator.hyphenation_points(&text[slice.clone()])
+                    },
+                    _ => vec![],
+                };
+                let mut start = slice.start;
+                let ends = hyphenation_points
+                    .into_iter()
+                    .map(|point| (slice.start + point, true))
+                    .chain(iter::once((slice.end, at_soft_hyphen)));
+                for (end, is_hyphenation_opportunity) in ends {
+                    let hyphen = if is_hyphenation_opportunity {
+                        Some(
+                            hyphen_glyphs
+                                .get_or_insert_with(|| Self::shape_hyphen(font, options))
+                                .clone(),
+                        )
+                    } else {
+                        None
+                    };
+                    glyphs.push(GlyphRun {
+                        glyph_store: font.shape_text(&text[start..end], options),
+                        range: Range::new(
+                            ByteIndex(start as isize),
+                            ByteIndex((end - start) as isize),
+                        ),
+                        hyphen,
+                    });
+                    start = end;
+                }
             }
+            after_soft_hyphen = at_soft_hyphen;
             if whitespace.len() > 0 {
                 let mut options = options.clone();
                 options
@@ -269,6 +315,7 @@ impl<'a> TextRun {
                         ByteIndex(whitespace.start as isize),
                         ByteIndex(whitespace.len() as isize),
                     ),
+                    hyphen: None,
                 });
             }
             slice.start = whitespace.end;
@@ -276,6 +323,16 @@ impl<'a> TextRun {
         (glyphs, break_at_zero)
     }
 
+    /// Shapes the hyphen rendered at the end of lines broken at hyphenation opportunities.
+    fn shape_hyphen(font: &mut Font, options: &ShapingOptions) -> Arc<GlyphStore> {
+        let hyphen = if font.glyph_index(HYPHEN).is_some() {
+            HYPHEN
+        } else {
+            '-'
+        };
+        font.shape_text(hyphen.encode_utf8(&mut [0; 4]), options)
+    }
+
     pub fn ascent(&self) -> Au {
         self.font_metrics.ascent
     }
@@ -354,6 +411,21 @@ impl<'a> TextRun {
         })
     }
 
+    /// Returns the glyphs of the hyphen to render at the end of a line broken at `index`,
+    /// if it is a hyphenation opportunity.
+    pub fn hyphen_at(&self, index: ByteIndex) -> Option<&Arc<GlyphStore>> {
+        if index == ByteIndex(0) {
+            return None;
+        }
+        let glyph_run =
+            &self.glyphs[self.index_of_first_glyph_run_containing(index - ByteIndex(1))?];
+        if glyph_run.range.end() == index {
+            glyph_run.hyphen.as_ref()
+        } else {
+            None
+        }
+    }
+
     pub fn on_glyph_run_boundary(&self, index: ByteIndex) -> bool {
         if let Some(glyph_index) = self.index_of_first_glyph_run_containing(index) {
             self.glyphs[glyph_index].range.begin() == index
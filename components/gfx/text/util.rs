@@ -99,7 +99,8 @@ pub fn transform_text(
     }
 
     fn is_always_discardable_char(ch: char) -> bool {
-        // TODO: check for soft hyphens.
+        // Soft hyphens are kept, as they are hyphenation opportunities when breaking lines,
+        // and shaping leaves them invisible.
         is_bidi_control(ch)
     }
 }
@@ -1009,7 +1009,9 @@ impl Fragment {
                     };
                     DisplayItem::RadialGradient(CommonDisplayItem::with_data(base, item, stops))
                 },
-                Gradient::Conic { .. } => unimplemented!(),
+                // Conic gradients are not supported by this layout engine,
+                // so they are not painted at all.
+                Gradient::Conic { .. } => return,
             };
             state.add_display_item(display_item);
         });
@@ -1251,7 +1253,7 @@ impl Fragment {
                     stops = radial_stops;
                     NinePatchBorderSource::RadialGradient(wr_gradient)
                 },
-                Gradient::Conic { .. } => unimplemented!(),
+                Gradient::Conic { .. } => return None,
             },
             _ => return None,
         };
@@ -2104,6 +2106,7 @@ impl Fragment {
         let mut glyphs = convert_text_run_to_glyphs(
             text_fragment.run.clone(),
             text_fragment.range,
+            text_fragment.hyphenated(),
             baseline_origin,
         );
 
@@ -2985,9 +2988,11 @@ pub enum BorderPaintingMode<'a> {
     Hidden,
 }
 
+/// Converts the glyphs of a range of a text run, followed by a hyphen if `hyphenated`.
 fn convert_text_run_to_glyphs(
     text_run: Arc<TextRun>,
     range: Range<ByteIndex>,
+    hyphenated: bool,
     mut origin: Point2D<Au>,
 ) -> Vec<GlyphInstance> {
     let mut glyphs = vec![];
@@ -3011,6 +3016,19 @@ fn convert_text_run_to_glyphs(
             origin.x += glyph_advance;
         }
     }
+    if hyphenated {
+        if let Some(hyphen) = text_run.hyphen_at(range.end()) {
+            let range = Range::new(ByteIndex(0), hyphen.len());
+            for glyph in hyphen.iter_glyphs_for_byte_range(&range) {
+                let glyph_offset = glyph.offset().unwrap_or(Point2D::zero());
+                glyphs.push(GlyphInstance {
+                    index: glyph.id(),
+                    point: (origin + glyph_offset.to_vector()).to_layout(),
+                });
+                origin.x += glyph.advance();
+            }
+        }
+    }
     return glyphs;
 }
 
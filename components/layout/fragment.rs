@@ -539,6 +539,10 @@ bitflags! {
         ///
         /// This handles cases like Foo<span>bar</span>
         const SUPPRESS_LINE_BREAK_BEFORE = 0x04;
+
+        /// Does this fragment end with a hyphen, because the line is broken at a hyphenation
+        /// opportunity after it?
+        const HYPHENATED = 0x08;
     }
 }
 
@@ -573,6 +577,10 @@ impl ScannedTextFragmentInfo {
     pub fn selected(&self) -> bool {
         self.flags.contains(ScannedTextFlags::SELECTED)
     }
+
+    pub fn hyphenated(&self) -> bool {
+        self.flags.contains(ScannedTextFlags::HYPHENATED)
+    }
 }
 
 /// Describes how to split a fragment. This is used during line breaking as part of the return
@@ -583,6 +591,9 @@ pub struct SplitInfo {
     // currently needed for splitting in the `inline::try_append_*` functions.
     pub range: Range<ByteIndex>,
     pub inline_size: Au,
+    /// Whether the line is broken at a hyphenation opportunity after this part, so that it
+    /// ends with a hyphen, which is included in `inline_size`.
+    pub hyphenated: bool,
 }
 
 impl SplitInfo {
@@ -591,6 +602,7 @@ impl SplitInfo {
         SplitInfo {
             range: range,
             inline_size: inline_size,
+            hyphenated: false,
         }
     }
 }
@@ -812,6 +824,7 @@ impl Fragment {
         if !first {
             flags.set(ScannedTextFlags::SUPPRESS_LINE_BREAK_BEFORE, false);
         }
+        flags.set(ScannedTextFlags::HYPHENATED, split.hyphenated);
 
         let info = Box::new(ScannedTextFragmentInfo::new(
             text_run,
@@ -1787,7 +1800,7 @@ impl Fragment {
             }
         }
 
-        match self.style().get_inherited_text().word_break {
+        let mut split_result = match self.style().get_inherited_text().word_break {
             WordBreak::Normal | WordBreak::KeepAll => {
                 // Break at normal word boundaries. keep-all forbids soft wrap opportunities.
                 let natural_word_breaking_strategy = text_fragment_info
@@ -1811,7 +1824,21 @@ impl Fragment {
                     flags,
                 )
             },
+        }?;
+
+        // If the line is broken at a hyphenation opportunity, it ends with a hyphen.
+        //
+        // FIXME: The hyphen is not taken into account when looking for the split point,
+        // so it can overflow the line.
+        if split_result.inline_end.is_some() {
+            if let Some(ref mut inline_start) = split_result.inline_start {
+                if let Some(hyphen) = split_result.text_run.hyphen_at(inline_start.range.end()) {
+                    inline_start.inline_size += hyphen.total_advance();
+                    inline_start.hyphenated = true;
+                }
+            }
         }
+        Some(split_result)
     }
 
     /// Does this fragment start on a glyph run boundary?
@@ -1837,7 +1864,9 @@ impl Fragment {
         let info = self.calculate_truncate_to_inline_size(max_inline_size);
         let (size, text_info) = match info {
             Some(TruncationResult {
-                split: SplitInfo { inline_size, range },
+                split: SplitInfo {
+                    inline_size, range, ..
+                },
                 text_run,
             }) => {
                 let size = LogicalSize::new(
@@ -2054,8 +2083,8 @@ impl Fragment {
         self.meld_with_next_inline_fragment(&next_fragment);
     }
 
-    /// Restore any whitespace that was stripped from a text fragment, and recompute inline metrics
-    /// if necessary.
+    /// Restore any whitespace that was stripped from a text fragment, remove any hyphen it ended
+    /// with, and recompute inline metrics if necessary.
     pub fn reset_text_range_and_inline_size(&mut self) {
         if let SpecificFragmentInfo::ScannedText(ref mut info) = self.specific {
             if info.run.extra_word_spacing != Au(0) {
@@ -2064,9 +2093,10 @@ impl Fragment {
 
             // FIXME (mbrubeck): Do we need to restore leading too?
             let range_end = info.range_end_including_stripped_whitespace;
-            if info.range.end() == range_end {
+            if info.range.end() == range_end && !info.hyphenated() {
                 return;
             }
+            info.flags.remove(ScannedTextFlags::HYPHENATED);
             info.range.extend_to(range_end);
             info.content_size.inline = info.run.metrics_for_range(&info.range).advance_width;
             self.border_box.size.inline =
@@ -2510,7 +2540,9 @@ impl Fragment {
                 if self.style().get_font() != other.style().get_font() ||
                     self.text_decoration_line() != other.text_decoration_line() ||
                     self.white_space() != other.white_space() ||
-                    self.color() != other.color()
+                    self.color() != other.color() ||
+                    self.style().get_inherited_text().hyphens !=
+                        other.style().get_inherited_text().hyphens
                 {
                     return false;
                 }
@@ -22,13 +22,13 @@ use msg::constellation_msg::PipelineId;
 use script_layout_interface::rpc::TextIndexResponse;
 use script_layout_interface::rpc::{ContentBoxResponse, ContentBoxesResponse, LayoutRPC};
 use script_layout_interface::rpc::{NodeGeometryResponse, NodeScrollIdResponse};
-use script_layout_interface::rpc::{OffsetParentResponse, ResolvedStyleResponse};
+use script_layout_interface::rpc::{OffsetParentResponse, PagesResponse, ResolvedStyleResponse};
 use script_layout_interface::wrapper_traits::{
     LayoutNode, ThreadSafeLayoutElement, ThreadSafeLayoutNode,
 };
 use script_layout_interface::{LayoutElementType, LayoutNodeType};
 use script_traits::LayoutMsg as ConstellationMsg;
-use script_traits::UntrustedNodeAddress;
+use script_traits::{PageDisplayList, UntrustedNodeAddress};
 use servo_arc::Arc as ServoArc;
 use servo_url::ServoUrl;
 use std::cmp::{max, min};
@@ -100,6 +100,12 @@ pub struct LayoutThreadData {
 
     /// A queued response for the viewport dimensions for a given browsing context.
     pub inner_window_dimensions_response: Option<TypedSize2D<f32, CSSPixel>>,
+
+    /// A queued response for the page areas of the pages of the document.
+    pub pages_response: Vec<Rect<Au>>,
+
+    /// A queued response for the display lists of the pages of the document.
+    pub page_display_lists_response: Vec<PageDisplayList>,
 }
 
 pub struct LayoutRPCImpl(pub Arc<Mutex<LayoutThreadData>>);
@@ -209,6 +215,18 @@ impl LayoutRPC for LayoutRPCImpl {
         let rw_data = rw_data.lock().unwrap();
         rw_data.inner_window_dimensions_response.clone()
     }
+
+    fn pages(&self) -> PagesResponse {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let rw_data = rw_data.lock().unwrap();
+        PagesResponse(rw_data.pages_response.clone())
+    }
+
+    fn page_display_lists(&self) -> Vec<PageDisplayList> {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let rw_data = rw_data.lock().unwrap();
+        rw_data.page_display_lists_response.clone()
+    }
 }
 
 struct UnioningFragmentBorderBoxIterator {
@@ -12,6 +12,7 @@ use crate::linked_list::split_off_head;
 use app_units::Au;
 use gfx::font::{self, FontMetrics, FontRef, RunMetrics, ShapingFlags, ShapingOptions};
 use gfx::text::glyph::ByteIndex;
+use gfx::text::hyphenation::Hyphenation;
 use gfx::text::text_run::TextRun;
 use gfx::text::util::{self, CompressionMode};
 use range::Range;
@@ -181,6 +182,7 @@ impl TextRunScanner {
             let word_spacing;
             let text_rendering;
             let word_break;
+            let hyphenation;
             {
                 let in_fragment = self.clump.front().unwrap();
                 let font_style = in_fragment.style().clone_font();
@@ -215,6 +217,7 @@ impl TextRunScanner {
                     });
                 text_rendering = inherited_text_style.text_rendering;
                 word_break = inherited_text_style.word_break;
+                hyphenation = Hyphenation::from_style(in_fragment.style());
             }
 
             // First, transform/compress text of all the nodes.
@@ -372,6 +375,7 @@ impl TextRunScanner {
                     &mut *font.borrow_mut(),
                     run_info.text,
                     &options,
+                    &hyphenation,
                     run_info.bidi_level,
                     linebreaker,
                 );
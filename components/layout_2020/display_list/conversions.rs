@@ -6,6 +6,7 @@ use crate::geom::{PhysicalPoint, PhysicalRect, PhysicalSides, PhysicalSize};
 use style::computed_values::mix_blend_mode::T as ComputedMixBlendMode;
 use style::computed_values::text_decoration_style::T as ComputedTextDecorationStyle;
 use style::computed_values::transform_style::T as ComputedTransformStyle;
+use style::values::computed::BorderStyle;
 use style::values::computed::Filter as ComputedFilter;
 use style::values::computed::Length;
 use webrender_api as wr;
@@ -114,3 +115,21 @@ impl ToWebRender for ComputedTextDecorationStyle {
         }
     }
 }
+
+impl ToWebRender for BorderStyle {
+    type Type = wr::BorderStyle;
+    fn to_webrender(&self) -> Self::Type {
+        match *self {
+            BorderStyle::None => wr::BorderStyle::None,
+            BorderStyle::Solid => wr::BorderStyle::Solid,
+            BorderStyle::Double => wr::BorderStyle::Double,
+            BorderStyle::Dotted => wr::BorderStyle::Dotted,
+            BorderStyle::Dashed => wr::BorderStyle::Dashed,
+            BorderStyle::Hidden => wr::BorderStyle::Hidden,
+            BorderStyle::Groove => wr::BorderStyle::Groove,
+            BorderStyle::Ridge => wr::BorderStyle::Ridge,
+            BorderStyle::Inset => wr::BorderStyle::Inset,
+            BorderStyle::Outset => wr::BorderStyle::Outset,
+        }
+    }
+}
@@ -4,7 +4,8 @@
 
 use style::properties::ComputedValues;
 use style::values::computed::image::{EndingShape, Gradient, LineDirection};
-use style::values::computed::{Color, Length, LengthPercentage, Position};
+use style::values::computed::{Angle, AngleOrPercentage, Color, Length, LengthPercentage};
+use style::values::computed::{Percentage, Position};
 use style::values::generics::image::{Circle, ColorStop, Ellipse, GradientItem, ShapeExtent};
 use webrender_api::{self as wr, units};
 
@@ -51,7 +52,24 @@ pub(super) fn build(
             &layer,
             builder,
         ),
-        Gradient::Conic { .. } => unimplemented!(),
+        Gradient::Conic {
+            ref angle,
+            ref position,
+            ref items,
+            ref repeating,
+        } => build_conic(
+            style,
+            items,
+            *angle,
+            position,
+            if *repeating {
+                wr::ExtendMode::Repeat
+            } else {
+                wr::ExtendMode::Clamp
+            },
+            &layer,
+            builder,
+        ),
     }
 }
 
@@ -166,16 +184,7 @@ pub(super) fn build_radial(
     builder: &mut super::DisplayListBuilder,
 ) {
     let gradient_box = layer.tile_size;
-    let center = units::LayoutPoint::new(
-        center
-            .horizontal
-            .percentage_relative_to(Length::new(gradient_box.width))
-            .px(),
-        center
-            .vertical
-            .percentage_relative_to(Length::new(gradient_box.height))
-            .px(),
-    );
+    let center = resolve_center(center, gradient_box);
     let radii = match shape {
         EndingShape::Circle(circle) => {
             let radius = match circle {
@@ -255,41 +264,96 @@ pub(super) fn build_radial(
     )
 }
 
+/// https://drafts.csswg.org/css-images-4/#conic-gradients
+pub(super) fn build_conic(
+    style: &ComputedValues,
+    items: &[GradientItem<Color, AngleOrPercentage>],
+    angle: Angle,
+    center: &Position,
+    extend_mode: wr::ExtendMode,
+    layer: &super::background::BackgroundLayer,
+    builder: &mut super::DisplayListBuilder,
+) {
+    let center = resolve_center(center, layer.tile_size);
+
+    // The gradient line of a conic gradient goes around the center, starting at `angle`,
+    // so the positions of color stops are fractions of a full turn.
+    let stops = fixup_stops_with(style, items, |position| match position {
+        AngleOrPercentage::Percentage(Percentage(fraction)) => *fraction,
+        AngleOrPercentage::Angle(angle) => angle.degrees() / 360.,
+    });
+    let conic_gradient =
+        builder
+            .wr
+            .create_conic_gradient(center, angle.radians(), stops, extend_mode);
+    builder.wr.push_conic_gradient(
+        &layer.common,
+        layer.bounds,
+        conic_gradient,
+        layer.tile_size,
+        layer.tile_spacing,
+    )
+}
+
+/// Resolves the center of a radial or conic gradient within its gradient box.
+fn resolve_center(center: &Position, gradient_box: units::LayoutSize) -> units::LayoutPoint {
+    units::LayoutPoint::new(
+        center
+            .horizontal
+            .percentage_relative_to(Length::new(gradient_box.width))
+            .px(),
+        center
+            .vertical
+            .percentage_relative_to(Length::new(gradient_box.height))
+            .px(),
+    )
+}
+
 /// https://drafts.csswg.org/css-images-4/#color-stop-fixup
 fn fixup_stops(
     style: &ComputedValues,
     items: &[GradientItem<Color, LengthPercentage>],
     gradient_line_length: Length,
 ) -> Vec<wr::GradientStop> {
-    // Remove color transititon hints, which are not supported yet.
-    // https://drafts.csswg.org/css-images-4/#color-transition-hint
-    //
-    // This gives an approximation of the gradient that might be visibly wrong,
-    // but maybe better than not parsing that value at all?
-    // It’s debatble whether that’s better or worse
-    // than not parsing and allowing authors to set a fallback.
-    // Either way, the best outcome is to add support.
-    // Gecko does so by approximating the non-linear interpolation
-    // by up to 10 piece-wise linear segments (9 intermediate color stops)
+    fixup_stops_with(style, items, |position| {
+        if gradient_line_length.px() == 0. {
+            0.
+        } else {
+            position.percentage_relative_to(gradient_line_length).px() / gradient_line_length.px()
+        }
+    })
+}
+
+/// Like `fixup_stops`, with positions resolved to fractions of the gradient line
+/// by `position_to_offset`.
+fn fixup_stops_with<T>(
+    style: &ComputedValues,
+    items: &[GradientItem<Color, T>],
+    position_to_offset: impl Fn(&T) -> f32,
+) -> Vec<wr::GradientStop> {
+    // Double-position color stops are already split into two color stops by the parser.
+    // Color transition hints are kept along with the color stop that follows them.
     let mut stops = Vec::with_capacity(items.len());
+    let mut hints = Vec::with_capacity(items.len());
+    let mut pending_hint = None;
     for item in items {
         match item {
-            GradientItem::SimpleColorStop(color) => stops.push(ColorStop {
-                color: super::rgba(style.resolve_color(*color)),
-                position: None,
-            }),
-            GradientItem::ComplexColorStop { color, position } => stops.push(ColorStop {
-                color: super::rgba(style.resolve_color(*color)),
-                position: Some(if gradient_line_length.px() == 0. {
-                    0.
-                } else {
-                    position.percentage_relative_to(gradient_line_length).px() /
-                        gradient_line_length.px()
-                }),
-            }),
-            GradientItem::InterpolationHint(_) => {
-                // FIXME: approximate like in:
-                // https://searchfox.org/mozilla-central/rev/f98dad153b59a985efd4505912588d4651033395/layout/painting/nsCSSRenderingGradients.cpp#315-391
+            GradientItem::SimpleColorStop(color) => {
+                stops.push(ColorStop {
+                    color: super::rgba(style.resolve_color(*color)),
+                    position: None,
+                });
+                hints.push(pending_hint.take());
+            },
+            GradientItem::ComplexColorStop { color, position } => {
+                stops.push(ColorStop {
+                    color: super::rgba(style.resolve_color(*color)),
+                    position: Some(position_to_offset(position)),
+                });
+                hints.push(pending_hint.take());
+            },
+            GradientItem::InterpolationHint(position) => {
+                pending_hint = Some(position_to_offset(position));
             },
         }
     }
@@ -303,10 +367,14 @@ fn fixup_stops(
         *last_position = Some(1.);
     }
 
-    let mut iter = stops.iter_mut();
-    let mut max_so_far = iter.next().unwrap().position.unwrap();
-    for stop in iter {
-        if let Some(position) = &mut stop.position {
+    // “If a color stop or transition hint has a position that is less than
+    //  the specified position of any color stop or transition hint before it
+    //  in the list, set its position to be equal to the largest specified position
+    //  of any color stop or transition hint before it.”
+    let mut iter = stops.iter_mut().zip(&mut hints);
+    let mut max_so_far = iter.next().unwrap().0.position.unwrap();
+    for (stop, hint) in iter {
+        for position in hint.iter_mut().chain(stop.position.as_mut()) {
             if *position < max_so_far {
                 *position = max_so_far
             } else {
@@ -346,5 +414,90 @@ fn fixup_stops(
         }
     }
 
-    wr_stops
+    if hints.iter().all(Option::is_none) {
+        return wr_stops;
+    }
+    let mut hinted_stops = Vec::with_capacity(wr_stops.len() + 9 * hints.len());
+    hinted_stops.push(wr_stops[0]);
+    for (i, hint) in hints.iter().enumerate().skip(1) {
+        if let Some(hint) = *hint {
+            push_transition_hint_stops(&mut hinted_stops, wr_stops[i - 1], wr_stops[i], hint);
+        }
+        hinted_stops.push(wr_stops[i]);
+    }
+    hinted_stops
+}
+
+/// Pushes the color stops between `start` and `end` for a color transition hint at `hint`.
+/// https://drafts.csswg.org/css-images-4/#coloring-gradient-line
+///
+/// WebRender only interpolates linearly between color stops, so like Gecko this approximates
+/// the non-linear interpolation by up to 10 piece-wise linear segments (9 intermediate color stops):
+/// https://searchfox.org/mozilla-central/rev/f98dad153b59a985efd4505912588d4651033395/layout/painting/nsCSSRenderingGradients.cpp#315-391
+fn push_transition_hint_stops(
+    stops: &mut Vec<wr::GradientStop>,
+    start: wr::GradientStop,
+    end: wr::GradientStop,
+    hint: f32,
+) {
+    let length = end.offset - start.offset;
+    if length <= 0. {
+        return;
+    }
+    let hint = hint.max(start.offset).min(end.offset);
+
+    // A hint at either color stop makes the color transition sharp.
+    if hint == start.offset {
+        stops.push(wr::GradientStop {
+            offset: start.offset,
+            color: end.color,
+        });
+        return;
+    }
+    if hint == end.offset {
+        stops.push(wr::GradientStop {
+            offset: end.offset,
+            color: start.color,
+        });
+        return;
+    }
+
+    // A hint in the middle is the same as linear interpolation.
+    let midpoint = (hint - start.offset) / length;
+    if midpoint == 0.5 {
+        return;
+    }
+
+    // Most of the intermediate stops go on the side of the hint
+    // where the color changes the fastest.
+    let offset = |i: usize| {
+        let i = i as f32;
+        if midpoint > 0.5 {
+            if i < 7. {
+                start.offset + (hint - start.offset) * (7. + i) / 13.
+            } else {
+                hint + (end.offset - hint) * (i - 6.) / 3.
+            }
+        } else if i < 2. {
+            start.offset + (hint - start.offset) * (i + 1.) / 3.
+        } else {
+            hint + (end.offset - hint) * (i - 2.) / 13.
+        }
+    };
+    // The color at `hint` is halfway between the colors of the color stops.
+    let exponent = 0.5_f32.ln() / midpoint.ln();
+    stops.extend((0..9).map(|i| {
+        let offset = offset(i);
+        let weight = ((offset - start.offset) / length).powf(exponent);
+        let interpolate = |from: f32, to: f32| from + (to - from) * weight;
+        wr::GradientStop {
+            offset,
+            color: wr::ColorF::new(
+                interpolate(start.color.r, end.color.r),
+                interpolate(start.color.g, end.color.g),
+                interpolate(start.color.b, end.color.b),
+                interpolate(start.color.a, end.color.a),
+            ),
+        }
+    }))
 }
@@ -5,7 +5,7 @@
 use crate::context::LayoutContext;
 use crate::display_list::conversions::ToWebRender;
 use crate::fragments::{BoxFragment, Fragment, Tag, TextFragment};
-use crate::geom::{PhysicalPoint, PhysicalRect};
+use crate::geom::{flow_relative, PhysicalPoint, PhysicalRect};
 use crate::replaced::IntrinsicSizes;
 use crate::style_ext::ComputedValuesExt;
 use embedder_traits::Cursor;
@@ -21,6 +21,7 @@ use style::properties::ComputedValues;
 use style::values::computed::{BorderStyle, Length, LengthPercentage};
 use style::values::specified::text::TextDecorationLine;
 use style::values::specified::ui::CursorKind;
+use style::Zero;
 use webrender_api::{self as wr, units};
 
 mod background;
@@ -145,28 +146,78 @@ impl Fragment {
 
         builder.is_contentful = true;
 
-        let rect = fragment
+        let writing_mode = fragment.parent_style.writing_mode;
+        let mut rect = fragment
             .rect
-            .to_physical(fragment.parent_style.writing_mode, containing_block)
+            .to_physical(writing_mode, containing_block)
             .translate(containing_block.origin.to_vector());
+
+        // Sideways text is laid out horizontally in a reference frame rotated by a quarter turn,
+        // clockwise for `sideways-rl` and counter-clockwise for `sideways-lr`.
+        let rotation = if writing_mode.is_sideways() {
+            let (origin, angle) = if writing_mode.is_vertical_lr() {
+                (
+                    PhysicalPoint::new(rect.min_x(), rect.max_y()),
+                    euclid::Angle::radians(std::f32::consts::FRAC_PI_2),
+                )
+            } else {
+                (
+                    PhysicalPoint::new(rect.max_x(), rect.min_y()),
+                    euclid::Angle::radians(-std::f32::consts::FRAC_PI_2),
+                )
+            };
+            rect = PhysicalRect::new(
+                PhysicalPoint::zero(),
+                Size2D::new(rect.size.height, rect.size.width),
+            );
+            Some((origin, angle))
+        } else {
+            None
+        };
+        // FIXME: with `text-orientation: mixed`, glyphs of horizontal-only scripts
+        // should be rotated rather than upright, and the vertical metrics of the font
+        // should be used.
+        let upright = writing_mode.is_vertical() && rotation.is_none();
+
         let mut baseline_origin = rect.origin.clone();
         baseline_origin.y += fragment.font_metrics.ascent;
-        let glyphs = glyphs(&fragment.glyphs, baseline_origin);
+        let glyphs = if upright {
+            glyphs(&fragment.glyphs, baseline_origin, Some(rect.size.width))
+        } else {
+            glyphs(&fragment.glyphs, baseline_origin, None)
+        };
         if glyphs.is_empty() {
             return;
         }
 
+        let original_space_and_clip = builder.current_space_and_clip;
+        if let Some((origin, angle)) = rotation {
+            builder.current_space_and_clip.spatial_id = builder.wr.push_reference_frame(
+                origin.to_webrender(),
+                builder.current_space_and_clip.spatial_id,
+                wr::TransformStyle::Flat,
+                wr::PropertyBinding::Value(units::LayoutTransform::create_rotation(
+                    0., 0., 1., angle,
+                )),
+                wr::ReferenceFrameKind::Transform,
+            );
+        }
+
         let mut common = builder.common_properties(rect.to_webrender(), &fragment.parent_style);
         common.hit_info = hit_info(&fragment.parent_style, fragment.tag, Cursor::Text);
 
         let color = fragment.parent_style.clone_color();
         let font_metrics = &fragment.font_metrics;
 
+        // FIXME: draw text decorations of upright text along the vertical axis.
+        let text_decoration_line = if upright {
+            TextDecorationLine::NONE
+        } else {
+            fragment.text_decoration_line
+        };
+
         // Underline.
-        if fragment
-            .text_decoration_line
-            .contains(TextDecorationLine::UNDERLINE)
-        {
+        if text_decoration_line.contains(TextDecorationLine::UNDERLINE) {
             let mut rect = rect;
             rect.origin.y = rect.origin.y + font_metrics.ascent - font_metrics.underline_offset;
             rect.size.height = font_metrics.underline_size;
@@ -174,10 +225,7 @@ impl Fragment {
         }
 
         // Overline.
-        if fragment
-            .text_decoration_line
-            .contains(TextDecorationLine::OVERLINE)
-        {
+        if text_decoration_line.contains(TextDecorationLine::OVERLINE) {
             let mut rect = rect;
             rect.size.height = font_metrics.underline_size;
             self.build_display_list_for_text_decoration(fragment, builder, &rect, color);
@@ -194,16 +242,18 @@ impl Fragment {
         );
 
         // Line-through.
-        if fragment
-            .text_decoration_line
-            .contains(TextDecorationLine::LINE_THROUGH)
-        {
+        if text_decoration_line.contains(TextDecorationLine::LINE_THROUGH) {
             let mut rect = rect;
             rect.origin.y = rect.origin.y + font_metrics.ascent - font_metrics.strikeout_offset;
             // XXX(ferjm) This does not work on MacOS #942
             rect.size.height = font_metrics.strikeout_size;
             self.build_display_list_for_text_decoration(fragment, builder, &rect, color);
         }
+
+        if rotation.is_some() {
+            builder.wr.pop_reference_frame();
+            builder.current_space_and_clip = original_space_and_clip;
+        }
     }
 
     fn build_display_list_for_text_decoration(
@@ -251,7 +301,7 @@ impl<'a> BuilderForBoxFragment<'a> {
     fn new(fragment: &'a BoxFragment, containing_block: &'a PhysicalRect<Length>) -> Self {
         let border_rect: units::LayoutRect = fragment
             .border_rect()
-            .to_physical(fragment.style.writing_mode, containing_block)
+            .to_physical(fragment.containing_block_writing_mode, containing_block)
             .translate(containing_block.origin.to_vector())
             .to_webrender();
 
@@ -291,7 +341,10 @@ impl<'a> BuilderForBoxFragment<'a> {
         self.content_rect.init_once(|| {
             self.fragment
                 .content_rect
-                .to_physical(self.fragment.style.writing_mode, self.containing_block)
+                .to_physical(
+                    self.fragment.containing_block_writing_mode,
+                    self.containing_block,
+                )
                 .translate(self.containing_block.origin.to_vector())
                 .to_webrender()
         })
@@ -301,7 +354,10 @@ impl<'a> BuilderForBoxFragment<'a> {
         self.padding_rect.init_once(|| {
             self.fragment
                 .padding_rect()
-                .to_physical(self.fragment.style.writing_mode, self.containing_block)
+                .to_physical(
+                    self.fragment.containing_block_writing_mode,
+                    self.containing_block,
+                )
                 .translate(self.containing_block.origin.to_vector())
                 .to_webrender()
         })
@@ -320,7 +376,7 @@ impl<'a> BuilderForBoxFragment<'a> {
                     self.border_radius,
                     self.fragment
                         .border
-                        .to_physical(self.fragment.style.writing_mode)
+                        .to_physical(self.fragment.containing_block_writing_mode)
                         .to_webrender(),
                 ),
                 self.border_rect,
@@ -335,7 +391,7 @@ impl<'a> BuilderForBoxFragment<'a> {
                 inner_radii(
                     self.border_radius,
                     (&self.fragment.border + &self.fragment.padding)
-                        .to_physical(self.fragment.style.writing_mode)
+                        .to_physical(self.fragment.containing_block_writing_mode)
                         .to_webrender(),
                 ),
                 self.border_rect,
@@ -348,6 +404,7 @@ impl<'a> BuilderForBoxFragment<'a> {
         self.build_hit_test(builder);
         self.build_background(builder);
         self.build_border(builder);
+        self.build_column_rules(builder);
     }
 
     fn build_hit_test(&self, builder: &mut DisplayListBuilder) {
@@ -480,29 +537,19 @@ impl<'a> BuilderForBoxFragment<'a> {
 
     fn build_border(&mut self, builder: &mut DisplayListBuilder) {
         let b = self.fragment.style.get_border();
-        let widths = SideOffsets2D::new(
-            b.border_top_width.px(),
-            b.border_right_width.px(),
-            b.border_bottom_width.px(),
-            b.border_left_width.px(),
-        );
+        // Use the used border widths rather than the computed ones: table layout
+        // resolves collapsed borders and suppresses them on rows and columns.
+        let widths = self
+            .fragment
+            .border
+            .to_physical(self.fragment.containing_block_writing_mode)
+            .to_webrender();
         if widths == SideOffsets2D::zero() {
             return;
         }
-        let side = |style, color| wr::BorderSide {
+        let side = |style: BorderStyle, color| wr::BorderSide {
             color: rgba(self.fragment.style.resolve_color(color)),
-            style: match style {
-                BorderStyle::None => wr::BorderStyle::None,
-                BorderStyle::Solid => wr::BorderStyle::Solid,
-                BorderStyle::Double => wr::BorderStyle::Double,
-                BorderStyle::Dotted => wr::BorderStyle::Dotted,
-                BorderStyle::Dashed => wr::BorderStyle::Dashed,
-                BorderStyle::Hidden => wr::BorderStyle::Hidden,
-                BorderStyle::Groove => wr::BorderStyle::Groove,
-                BorderStyle::Ridge => wr::BorderStyle::Ridge,
-                BorderStyle::Inset => wr::BorderStyle::Inset,
-                BorderStyle::Outset => wr::BorderStyle::Outset,
-            },
+            style: style.to_webrender(),
         };
         let common = builder.common_properties(self.border_rect, &self.fragment.style);
         let details = wr::BorderDetails::Normal(wr::NormalBorder {
@@ -517,6 +564,110 @@ impl<'a> BuilderForBoxFragment<'a> {
             .wr
             .push_border(&common, self.border_rect, widths, details)
     }
+
+    /// Paints the rules between the columns of a multi-column container, which are
+    /// its anonymous child fragments. Rules are only drawn between two columns of the
+    /// same row that both have content.
+    /// https://drafts.csswg.org/css-multicol/#column-gaps-and-rules
+    fn build_column_rules(&mut self, builder: &mut DisplayListBuilder) {
+        let style = &self.fragment.style;
+        if !style.is_multicol_container() {
+            return;
+        }
+        let column = style.get_column();
+        let rule_width = column.column_rule_width.0;
+        if matches!(
+            column.column_rule_style,
+            BorderStyle::None | BorderStyle::Hidden
+        ) || rule_width <= Length::zero()
+        {
+            return;
+        }
+
+        let writing_mode = style.writing_mode;
+        let content_rect = self
+            .fragment
+            .content_rect
+            .to_physical(
+                self.fragment.containing_block_writing_mode,
+                self.containing_block,
+            )
+            .translate(self.containing_block.origin.to_vector());
+        let rule_side = wr::BorderSide {
+            color: rgba(style.resolve_color(column.column_rule_color)),
+            style: column.column_rule_style.to_webrender(),
+        };
+        let no_side = wr::BorderSide {
+            color: wr::ColorF::TRANSPARENT,
+            style: wr::BorderStyle::None,
+        };
+        // The rules go along the block axis of the container.
+        let (widths, details) = if writing_mode.is_horizontal() {
+            (
+                SideOffsets2D::new(0., 0., 0., rule_width.px()),
+                wr::NormalBorder {
+                    top: no_side,
+                    right: no_side,
+                    bottom: no_side,
+                    left: rule_side,
+                    radius: wr::BorderRadius::zero(),
+                    do_aa: true,
+                },
+            )
+        } else {
+            (
+                SideOffsets2D::new(rule_width.px(), 0., 0., 0.),
+                wr::NormalBorder {
+                    top: rule_side,
+                    right: no_side,
+                    bottom: no_side,
+                    left: no_side,
+                    radius: wr::BorderRadius::zero(),
+                    do_aa: true,
+                },
+            )
+        };
+
+        let children = self.fragment.children.iter().map(|child| child.borrow());
+        let mut previous_column: Option<flow_relative::Rect<Length>> = None;
+        for child in children {
+            let column = match &*child {
+                Fragment::Anonymous(column) if !column.children.is_empty() => column,
+                _ => {
+                    previous_column = None;
+                    continue;
+                },
+            };
+            if let Some(previous) = previous_column.take() {
+                if previous.start_corner.block == column.rect.start_corner.block {
+                    let previous_end = previous.start_corner.inline + previous.size.inline;
+                    let gap = column.rect.start_corner.inline - previous_end;
+                    let rule_rect = flow_relative::Rect {
+                        start_corner: flow_relative::Vec2 {
+                            inline: previous_end + (gap - rule_width) / 2.,
+                            block: column.rect.start_corner.block,
+                        },
+                        size: flow_relative::Vec2 {
+                            inline: rule_width,
+                            block: column.rect.size.block,
+                        },
+                    };
+                    let rule_rect = rule_rect
+                        .to_physical(writing_mode, &content_rect)
+                        .translate(content_rect.origin.to_vector())
+                        .to_webrender();
+                    let common = builder.common_properties(rule_rect, style);
+                    builder.wr.push_border(
+                        &common,
+                        rule_rect,
+                        widths,
+                        wr::BorderDetails::Normal(details),
+                    );
+                }
+            }
+            previous_column = Some(column.rect.clone());
+        }
+    }
 }
 
 fn rgba(rgba: cssparser::RGBA) -> wr::ColorF {
@@ -528,9 +679,13 @@ fn rgba(rgba: cssparser::RGBA) -> wr::ColorF {
     )
 }
 
+/// Places glyphs from `origin`, which is on the baseline. They go along the x axis,
+/// or are stacked along the y axis and centered in a line of width `upright_line_width`
+/// if it is given.
 fn glyphs(
     glyph_runs: &[Arc<GlyphStore>],
     mut origin: PhysicalPoint<Length>,
+    upright_line_width: Option<Length>,
 ) -> Vec<wr::GlyphInstance> {
     use gfx_traits::ByteIndex;
     use range::Range;
@@ -538,10 +693,15 @@ fn glyphs(
     let mut glyphs = vec![];
     for run in glyph_runs {
         for glyph in run.iter_glyphs_for_byte_range(&Range::new(ByteIndex(0), run.len())) {
+            let advance = Length::from(glyph.advance());
             if !run.is_whitespace() {
                 let glyph_offset = glyph.offset().unwrap_or(Point2D::zero());
+                let centering_offset = match upright_line_width {
+                    Some(line_width) => (line_width - advance) / 2.,
+                    None => Length::zero(),
+                };
                 let point = units::LayoutPoint::new(
-                    origin.x.px() + glyph_offset.x.to_f32_px(),
+                    origin.x.px() + centering_offset.px() + glyph_offset.x.to_f32_px(),
                     origin.y.px() + glyph_offset.y.to_f32_px(),
                 );
                 let glyph = wr::GlyphInstance {
@@ -550,7 +710,10 @@ fn glyphs(
                 };
                 glyphs.push(glyph);
             }
-            origin.x += Length::from(glyph.advance());
+            match upright_line_width {
+                Some(_) => origin.y += advance,
+                None => origin.x += advance,
+            }
         }
     }
     glyphs
@@ -8,7 +8,7 @@ use crate::display_list::DisplayListBuilder;
 use crate::fragments::{
     AbsoluteOrFixedPositionedFragment, AnonymousFragment, BoxFragment, Fragment,
 };
-use crate::geom::PhysicalRect;
+use crate::geom::{LengthPercentageOrAuto, PhysicalRect};
 use crate::style_ext::ComputedValuesExt;
 use euclid::default::Rect;
 use servo_arc::Arc as ServoArc;
@@ -69,14 +69,22 @@ pub(crate) struct StackingContextBuilder<'a> {
     /// The id of the nearest ancestor reference frame for this `DisplayListBuilder`.
     nearest_reference_frame: wr::SpatialId,
 
+    /// The padding rect of the nearest ancestor scroll frame, or the initial containing
+    /// block if there is none. Sticky positioning offsets are relative to it.
+    nearest_scrollport: PhysicalRect<Length>,
+
     wr: &'a mut wr::DisplayListBuilder,
 }
 
 impl<'a> StackingContextBuilder<'a> {
-    pub fn new(wr: &'a mut wr::DisplayListBuilder) -> Self {
+    pub fn new(
+        wr: &'a mut wr::DisplayListBuilder,
+        initial_containing_block: &PhysicalRect<Length>,
+    ) -> Self {
         Self {
             current_space_and_clip: wr::SpaceAndClipInfo::root_scroll(wr.pipeline_id),
             nearest_reference_frame: wr::SpatialId::root_reference_frame(wr.pipeline_id),
+            nearest_scrollport: *initial_containing_block,
             wr,
         }
     }
@@ -84,11 +92,13 @@ impl<'a> StackingContextBuilder<'a> {
     fn clipping_and_scrolling_scope<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
         let previous_space_and_clip = self.current_space_and_clip;
         let previous_nearest_reference_frame = self.nearest_reference_frame;
+        let previous_nearest_scrollport = self.nearest_scrollport;
 
         let result = f(self);
 
         self.current_space_and_clip = previous_space_and_clip;
         self.nearest_reference_frame = previous_nearest_reference_frame;
+        self.nearest_scrollport = previous_nearest_scrollport;
 
         result
     }
@@ -567,6 +577,7 @@ impl BoxFragment {
     ) {
         builder.clipping_and_scrolling_scope(|builder| {
             self.adjust_spatial_id_for_positioning(builder);
+            self.build_sticky_frame_if_necessary(builder, containing_block_info);
 
             match self.get_stacking_context_type() {
                 Some(context_type) => {
@@ -675,12 +686,18 @@ impl BoxFragment {
 
         let padding_rect = self
             .padding_rect()
-            .to_physical(self.style.writing_mode, &containing_block_info.rect)
+            .to_physical(
+                self.containing_block_writing_mode,
+                &containing_block_info.rect,
+            )
             .translate(containing_block_info.rect.origin.to_vector());
         let mut new_containing_block_info = containing_block_info.clone();
         new_containing_block_info.rect = self
             .content_rect
-            .to_physical(self.style.writing_mode, &new_containing_block_info.rect)
+            .to_physical(
+                self.containing_block_writing_mode,
+                &new_containing_block_info.rect,
+            )
             .translate(new_containing_block_info.rect.origin.to_vector());
 
         // If we establish a containing block we use the padding rect as the offset. This is
@@ -710,6 +727,71 @@ impl BoxFragment {
         builder.current_space_and_clip.spatial_id = builder.nearest_reference_frame;
     }
 
+    /// Defines a sticky frame for a `position: sticky` box, in which WebRender moves the box
+    /// while scrolling so that it stays within its insets from the nearest scrollport, but
+    /// does not leave its containing block.
+    ///
+    /// https://drafts.csswg.org/css-position/#stickypos-insets
+    fn build_sticky_frame_if_necessary(
+        &self,
+        builder: &mut StackingContextBuilder,
+        containing_block_info: &ContainingBlockInfo,
+    ) {
+        if self.style.get_box().position != ComputedPosition::Sticky {
+            return;
+        }
+
+        // Percentages in the insets are relative to the size of the scrollport.
+        let scrollport_size = builder.nearest_scrollport.size;
+        let position = self.style.get_position();
+        let inset = |value: LengthPercentageOrAuto, basis: Length| {
+            value
+                .percentage_relative_to(basis)
+                .non_auto()
+                .map(|v| v.px())
+        };
+        let margins = euclid::SideOffsets2D::new(
+            inset(position.top.as_ref(), scrollport_size.height),
+            inset(position.right.as_ref(), scrollport_size.width),
+            inset(position.bottom.as_ref(), scrollport_size.height),
+            inset(position.left.as_ref(), scrollport_size.width),
+        );
+        if margins.top.is_none() &&
+            margins.right.is_none() &&
+            margins.bottom.is_none() &&
+            margins.left.is_none()
+        {
+            return;
+        }
+
+        // The margin box of the sticky box never leaves its containing block, which is
+        // the content box of its parent. These are the smallest negative and largest
+        // positive offsets that keep it there.
+        let containing_block_rect = &containing_block_info.rect;
+        let border_rect = self
+            .border_rect()
+            .to_physical(self.containing_block_writing_mode, containing_block_rect)
+            .translate(containing_block_rect.origin.to_vector());
+        let box_margins = self.margin.to_physical(self.containing_block_writing_mode);
+        let vertical_offset_bounds = wr::StickyOffsetBounds::new(
+            (containing_block_rect.min_y() - (border_rect.min_y() - box_margins.top)).px(),
+            (containing_block_rect.max_y() - (border_rect.max_y() + box_margins.bottom)).px(),
+        );
+        let horizontal_offset_bounds = wr::StickyOffsetBounds::new(
+            (containing_block_rect.min_x() - (border_rect.min_x() - box_margins.left)).px(),
+            (containing_block_rect.max_x() - (border_rect.max_x() + box_margins.right)).px(),
+        );
+
+        builder.current_space_and_clip.spatial_id = builder.wr.define_sticky_frame(
+            builder.current_space_and_clip.spatial_id,
+            border_rect.to_webrender(),
+            margins,
+            vertical_offset_bounds,
+            horizontal_offset_bounds,
+            LayoutVector2D::zero(),
+        );
+    }
+
     fn build_clip_frame_if_necessary(
         &self,
         builder: &mut StackingContextBuilder,
@@ -721,9 +803,10 @@ impl BoxFragment {
         if position == ComputedPosition::Absolute || position == ComputedPosition::Fixed {
             let clip = self.style.get_effects().clip;
             if let ClipRectOrAuto::Rect(r) = clip {
-                let border_rect = self
-                    .border_rect()
-                    .to_physical(self.style.writing_mode, &containing_block_info.rect);
+                let border_rect = self.border_rect().to_physical(
+                    self.containing_block_writing_mode,
+                    &containing_block_info.rect,
+                );
                 let clip_rect = r
                     .for_border_rect(border_rect)
                     .translate(containing_block_info.rect.origin.to_vector())
@@ -761,18 +844,21 @@ impl BoxFragment {
 
             let padding_rect = self
                 .padding_rect()
-                .to_physical(self.style.writing_mode, &containing_block_info.rect)
-                .translate(containing_block_info.rect.origin.to_vector())
-                .to_webrender();
+                .to_physical(
+                    self.containing_block_writing_mode,
+                    &containing_block_info.rect,
+                )
+                .translate(containing_block_info.rect.origin.to_vector());
             builder.current_space_and_clip = builder.wr.define_scroll_frame(
                 &original_scroll_and_clip_info,
                 Some(external_id),
                 self.scrollable_overflow(&containing_block_info.rect)
                     .to_webrender(),
-                padding_rect,
+                padding_rect.to_webrender(),
                 sensitivity,
                 LayoutVector2D::zero(),
             );
+            builder.nearest_scrollport = padding_rect;
         }
     }
 
@@ -787,7 +873,7 @@ impl BoxFragment {
 
         let relative_border_rect = self
             .border_rect()
-            .to_physical(self.style.writing_mode, &containing_block_rect);
+            .to_physical(self.containing_block_writing_mode, &containing_block_rect);
         let border_rect = relative_border_rect.translate(containing_block_rect.origin.to_vector());
         let untyped_border_rect = border_rect.to_untyped();
 
@@ -956,10 +1042,12 @@ impl AbsoluteOrFixedPositionedFragment {
                 .nearest_containing_block
                 .as_ref()
                 .unwrap_or(&containing_block_info.containing_block_for_all_descendants),
-            ComputedPosition::Static | ComputedPosition::Relative => unreachable!(
-                "Found an AbsoluteOrFixedPositionedFragment for a \
+            ComputedPosition::Static | ComputedPosition::Relative | ComputedPosition::Sticky => {
+                unreachable!(
+                    "Found an AbsoluteOrFixedPositionedFragment for a \
                               non-absolutely or fixed position fragment."
-            ),
+                )
+            },
         };
 
         builder.clipping_and_scrolling_scope(|builder| {
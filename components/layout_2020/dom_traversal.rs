@@ -112,6 +112,32 @@ where
     );
 }
 
+/// A call to a `TraversalHandler` that was recorded so that it can be replayed
+/// later, once the anonymous box that should contain it has been determined.
+pub(super) enum TraversalEvent<'dom, Node> {
+    Text(NodeAndStyleInfo<Node>, Cow<'dom, str>),
+    Element(
+        NodeAndStyleInfo<Node>,
+        DisplayGeneratingBox,
+        Contents,
+        BoxSlot<'dom>,
+    ),
+}
+
+impl<'dom, Node> TraversalEvent<'dom, Node>
+where
+    Node: 'dom,
+{
+    pub(crate) fn replay(self, handler: &mut impl TraversalHandler<'dom, Node>) {
+        match self {
+            TraversalEvent::Text(info, text) => handler.handle_text(&info, text),
+            TraversalEvent::Element(info, display, contents, box_slot) => {
+                handler.handle_element(&info, display, contents, box_slot)
+            },
+        }
+    }
+}
+
 fn traverse_children_of<'dom, Node>(
     parent_element: Node,
     context: &LayoutContext,
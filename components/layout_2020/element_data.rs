@@ -6,6 +6,7 @@ use crate::cell::ArcRefCell;
 use crate::flexbox::FlexLevelBox;
 use crate::flow::inline::InlineLevelBox;
 use crate::flow::BlockLevelBox;
+use crate::grid::GridLevelBox;
 
 #[derive(Default)]
 pub struct LayoutDataForElement {
@@ -19,4 +20,8 @@ pub(super) enum LayoutBox {
     BlockLevel(ArcRefCell<BlockLevelBox>),
     InlineLevel(ArcRefCell<InlineLevelBox>),
     FlexLevel(ArcRefCell<FlexLevelBox>),
+    GridLevel(ArcRefCell<GridLevelBox>),
+    /// The box of an internal table element or of a caption, which is owned
+    /// by the `Table` it belongs to.
+    TableLevel,
 }
@@ -165,6 +165,9 @@ where
                 } => {
                     let display_inside = match display {
                         DisplayGeneratingBox::OutsideInside { inside, .. } => inside,
+                        // Flex items are blockified, which turns internal table
+                        // displays into 'block'.
+                        DisplayGeneratingBox::LayoutInternal(_) => unreachable!(),
                     };
                     let box_ = if info.style.get_box().position.is_absolutely_positioned() {
                         // https://drafts.csswg.org/css-flexbox/#abspos-items
@@ -407,12 +407,6 @@ impl<'a> FlexItem<'a> {
         let containing_block = flex_context.containing_block;
         let box_style = box_.style();
 
-        // https://drafts.csswg.org/css-writing-modes/#orthogonal-flows
-        assert_eq!(
-            containing_block.style.writing_mode, box_style.writing_mode,
-            "Mixed writing modes are not supported yet"
-        );
-
         let container_is_horizontal = containing_block.style.writing_mode.is_horizontal();
         let item_is_horizontal = box_style.writing_mode.is_horizontal();
         let item_is_orthogonal = item_is_horizontal != container_is_horizontal;
@@ -548,11 +542,40 @@ fn flex_base_size(
                     .inline_content_sizes(flex_context.layout_context)
                     .max_content
             } else {
-                // FIXME: block-axis content sizing requires another pass
-                // of "full" layout
-                todo!()
-                // Note: this panic shouldn’t happen since the start of `FlexContainer::layout`
-                // forces `FlexAxis::Row` and the `writing-mode` property is disabled.
+                // The main axis is the block axis of the item. Since `FlexContainer::layout`
+                // forces `FlexAxis::Row`, this only happens for items in an orthogonal flow:
+                // lay them out once with an auto block size to find their content size.
+                // https://drafts.csswg.org/css-writing-modes/#orthogonal-flows
+                let containing_block = flex_context.containing_block;
+                match flex_item {
+                    IndependentFormattingContext::Replaced(replaced) => {
+                        let pbm = replaced.style.padding_border_margin(containing_block);
+                        replaced
+                            .contents
+                            .used_size_as_if_inline_element(containing_block, &replaced.style, &pbm)
+                            .inline
+                    },
+                    IndependentFormattingContext::NonReplaced(non_replaced) => {
+                        let pbm = non_replaced.style.padding_border_margin(containing_block);
+                        let content_box_size =
+                            non_replaced.style.content_box_size(containing_block, &pbm);
+                        // Absolutely-positioned descendants are collected again
+                        // when the item is actually laid out.
+                        let mut positioning_context = PositioningContext::new_for_rayon(false);
+                        let (_, size) = non_replaced.layout_in_orthogonal_flow(
+                            flex_context.layout_context,
+                            &mut positioning_context,
+                            containing_block,
+                            &pbm,
+                            &Vec2 {
+                                inline: LengthOrAuto::Auto,
+                                block: content_box_size.block,
+                            },
+                            0,
+                        );
+                        size.inline
+                    },
+                }
             }
         },
     }
@@ -745,6 +768,7 @@ impl FlexLine<'_> {
                     item.box_.tag(),
                     item.box_.style().clone(),
                     fragments,
+                    flex_context.containing_block.style.writing_mode,
                     content_rect,
                     flex_context.sides_to_flow_relative(item.padding),
                     flex_context.sides_to_flow_relative(item.border),
@@ -930,15 +954,8 @@ impl<'a> FlexItem<'a> {
         );
         match flex_context.flex_axis {
             FlexAxis::Row => {
-                // The main axis is the container’s inline axis
-
-                // https://drafts.csswg.org/css-writing-modes/#orthogonal-flows
-                assert_eq!(
-                    flex_context.containing_block.style.writing_mode,
-                    self.box_.style().writing_mode,
-                    "Mixed writing modes are not supported yet"
-                );
-                // … and also the item’s inline axis.
+                // The main axis is the container’s inline axis,
+                // and also the item’s inline axis unless it is in an orthogonal flow.
 
                 match self.box_ {
                     IndependentFormattingContext::Replaced(replaced) => {
@@ -964,6 +981,40 @@ impl<'a> FlexItem<'a> {
                             None => self.content_box_size.cross,
                         };
 
+                        // https://drafts.csswg.org/css-writing-modes/#orthogonal-flows
+                        if non_replaced.style.writing_mode.is_horizontal() !=
+                            flex_context
+                                .containing_block
+                                .style
+                                .writing_mode
+                                .is_horizontal()
+                        {
+                            let pbm = non_replaced
+                                .style
+                                .padding_border_margin(flex_context.containing_block);
+                            let (independent_layout, size) = non_replaced
+                                .layout_in_orthogonal_flow(
+                                    flex_context.layout_context,
+                                    &mut positioning_context,
+                                    flex_context.containing_block,
+                                    &pbm,
+                                    &Vec2 {
+                                        inline: LengthOrAuto::LengthPercentage(used_main_size),
+                                        block: block_size,
+                                    },
+                                    self.tree_rank,
+                                );
+                            let hypothetical_cross_size = size.block.clamp_between_extremums(
+                                self.content_min_size.cross,
+                                self.content_max_size.cross,
+                            );
+                            return FlexItemLayoutResult {
+                                hypothetical_cross_size,
+                                fragments: independent_layout.fragments,
+                                positioning_context,
+                            };
+                        }
+
                         let item_as_containing_block = ContainingBlock {
                             inline_size: used_main_size,
                             block_size,
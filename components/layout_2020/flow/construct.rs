@@ -5,7 +5,8 @@
 use crate::cell::ArcRefCell;
 use crate::context::LayoutContext;
 use crate::dom_traversal::{
-    BoxSlot, Contents, NodeAndStyleInfo, NodeExt, NonReplacedContents, TraversalHandler,
+    BoxSlot, Contents, NodeAndStyleInfo, NodeExt, NonReplacedContents, TraversalEvent,
+    TraversalHandler,
 };
 use crate::element_data::LayoutBox;
 use crate::flow::float::FloatBox;
@@ -14,7 +15,7 @@ use crate::flow::{BlockContainer, BlockFormattingContext, BlockLevelBox};
 use crate::formatting_contexts::IndependentFormattingContext;
 use crate::fragments::Tag;
 use crate::positioned::AbsolutelyPositionedBox;
-use crate::style_ext::{DisplayGeneratingBox, DisplayInside, DisplayOutside};
+use crate::style_ext::{ComputedValuesExt, DisplayGeneratingBox, DisplayInside, DisplayOutside};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use rayon_croissant::ParallelIteratorExt;
 use servo_arc::Arc;
@@ -51,6 +52,29 @@ impl BlockFormattingContext {
         bfc
     }
 
+    /// Construct the contents of an anonymous box, such as an anonymous table
+    /// cell, from the children that were collected for it by another builder.
+    pub(crate) fn construct_for_anonymous_children<'dom, Node>(
+        context: &LayoutContext,
+        info: &NodeAndStyleInfo<Node>,
+        children: Vec<TraversalEvent<'dom, Node>>,
+        propagated_text_decoration_line: TextDecorationLine,
+    ) -> Self
+    where
+        Node: NodeExt<'dom>,
+    {
+        let (contents, contains_floats) = BlockContainer::construct_for_anonymous_children(
+            context,
+            info,
+            children,
+            propagated_text_decoration_line,
+        );
+        Self {
+            contents,
+            contains_floats: contains_floats == ContainsFloats::Yes,
+        }
+    }
+
     pub fn construct_for_text_runs<'dom>(
         runs: impl Iterator<Item = TextRun>,
         text_decoration_line: TextDecorationLine,
@@ -76,10 +100,10 @@ impl BlockFormattingContext {
 struct BlockLevelJob<'dom, Node> {
     info: NodeAndStyleInfo<Node>,
     box_slot: BoxSlot<'dom>,
-    kind: BlockLevelCreator,
+    kind: BlockLevelCreator<'dom, Node>,
 }
 
-enum BlockLevelCreator {
+enum BlockLevelCreator<'dom, Node> {
     SameFormattingContextBlock(IntermediateBlockContainer),
     Independent {
         display_inside: DisplayInside,
@@ -94,6 +118,12 @@ enum BlockLevelCreator {
         display_inside: DisplayInside,
         contents: Contents,
     },
+    /// An anonymous table wrapping internal table boxes that are not in a table.
+    /// <https://drafts.csswg.org/css2/tables.html#anonymous-boxes>
+    AnonymousTable {
+        children: Vec<TraversalEvent<'dom, Node>>,
+        propagated_text_decoration_line: TextDecorationLine,
+    },
 }
 
 /// A block container that may still have to be constructed.
@@ -168,6 +198,10 @@ struct BlockContainerBuilder<'dom, 'style, Node> {
 
     /// Whether the resulting block container contains any float box.
     contains_floats: ContainsFloats,
+
+    /// The ongoing run of internal table boxes found outside of a table, which
+    /// get wrapped in an anonymous table (see `end_ongoing_anonymous_table`).
+    ongoing_anonymous_table: Vec<TraversalEvent<'dom, Node>>,
 }
 
 impl BlockContainer {
@@ -178,6 +212,47 @@ impl BlockContainer {
         propagated_text_decoration_line: TextDecorationLine,
         is_list_item: bool,
     ) -> (BlockContainer, ContainsFloats)
+    where
+        Node: NodeExt<'dom>,
+    {
+        Self::construct_with(
+            context,
+            info,
+            propagated_text_decoration_line,
+            is_list_item,
+            |builder| contents.traverse(context, info, builder),
+        )
+    }
+
+    fn construct_for_anonymous_children<'dom, Node>(
+        context: &LayoutContext,
+        info: &NodeAndStyleInfo<Node>,
+        children: Vec<TraversalEvent<'dom, Node>>,
+        propagated_text_decoration_line: TextDecorationLine,
+    ) -> (BlockContainer, ContainsFloats)
+    where
+        Node: NodeExt<'dom>,
+    {
+        Self::construct_with(
+            context,
+            info,
+            propagated_text_decoration_line,
+            /* is_list_item = */ false,
+            |builder| {
+                for child in children {
+                    child.replay(builder)
+                }
+            },
+        )
+    }
+
+    fn construct_with<'dom, Node>(
+        context: &LayoutContext,
+        info: &NodeAndStyleInfo<Node>,
+        propagated_text_decoration_line: TextDecorationLine,
+        is_list_item: bool,
+        traverse: impl FnOnce(&mut BlockContainerBuilder<'dom, '_, Node>),
+    ) -> (BlockContainer, ContainsFloats)
     where
         Node: NodeExt<'dom>,
     {
@@ -191,6 +266,7 @@ impl BlockContainer {
             ongoing_inline_boxes_stack: Vec::new(),
             anonymous_style: None,
             contains_floats: ContainsFloats::No,
+            ongoing_anonymous_table: Vec::new(),
         };
 
         if is_list_item {
@@ -211,7 +287,8 @@ impl BlockContainer {
             }
         }
 
-        contents.traverse(context, info, &mut builder);
+        traverse(&mut builder);
+        builder.end_ongoing_anonymous_table();
 
         debug_assert!(builder.ongoing_inline_boxes_stack.is_empty());
 
@@ -273,27 +350,46 @@ where
         box_slot: BoxSlot<'dom>,
     ) {
         match display {
-            DisplayGeneratingBox::OutsideInside { outside, inside } => match outside {
-                DisplayOutside::Inline => box_slot.set(LayoutBox::InlineLevel(
-                    self.handle_inline_level_element(info, inside, contents),
-                )),
-                DisplayOutside::Block => {
-                    let box_style = info.style.get_box();
-                    // Floats and abspos cause blockification, so they only happen in this case.
-                    // https://drafts.csswg.org/css2/visuren.html#dis-pos-flo
-                    if box_style.position.is_absolutely_positioned() {
-                        self.handle_absolutely_positioned_element(info, inside, contents, box_slot)
-                    } else if box_style.float.is_floating() {
-                        self.handle_float_element(info, inside, contents, box_slot)
-                    } else {
-                        self.handle_block_level_element(info, inside, contents, box_slot)
-                    }
-                },
+            DisplayGeneratingBox::OutsideInside { outside, inside } => {
+                self.end_ongoing_anonymous_table();
+                match outside {
+                    DisplayOutside::Inline => box_slot.set(LayoutBox::InlineLevel(
+                        self.handle_inline_level_element(info, inside, contents),
+                    )),
+                    DisplayOutside::Block => {
+                        let box_style = info.style.get_box();
+                        // Floats and abspos cause blockification, so they only happen in this case.
+                        // https://drafts.csswg.org/css2/visuren.html#dis-pos-flo
+                        if box_style.position.is_absolutely_positioned() {
+                            self.handle_absolutely_positioned_element(
+                                info, inside, contents, box_slot,
+                            )
+                        } else if box_style.float.is_floating() {
+                            self.handle_float_element(info, inside, contents, box_slot)
+                        } else {
+                            self.handle_block_level_element(info, inside, contents, box_slot)
+                        }
+                    },
+                }
             },
+            DisplayGeneratingBox::LayoutInternal(_) => self.ongoing_anonymous_table.push(
+                TraversalEvent::Element(info.clone(), display, contents, box_slot),
+            ),
         }
     }
 
     fn handle_text(&mut self, info: &NodeAndStyleInfo<Node>, input: Cow<'dom, str>) {
+        if !self.ongoing_anonymous_table.is_empty() {
+            // White space between internal table boxes belongs to their anonymous table,
+            // which ignores it.
+            if input.bytes().all(|byte| byte.is_ascii_whitespace()) {
+                self.ongoing_anonymous_table
+                    .push(TraversalEvent::Text(info.clone(), input));
+                return;
+            }
+            self.end_ongoing_anonymous_table();
+        }
+
         // Skip any leading whitespace as dictated by the node's style.
         let white_space = info.style.get_inherited_text().white_space;
         let (preserved_leading_whitespace, mut input) =
@@ -484,8 +580,13 @@ where
         display_inside: DisplayInside,
         contents: Contents,
     ) -> ArcRefCell<InlineLevelBox> {
+        // “If the box would otherwise become an in-flow box with a computed `display`
+        //  of `inline`, its `display` computes instead to `inline-block`.”
+        // https://drafts.csswg.org/css-writing-modes/#block-flow
+        let is_atomic_inline_level_box =
+            contents.is_replaced() || self.has_different_writing_mode(info);
         let box_ = if let (DisplayInside::Flow { is_list_item }, false) =
-            (display_inside, contents.is_replaced())
+            (display_inside, is_atomic_inline_level_box)
         {
             // We found un inline box.
             // Whatever happened before, all we need to do before recurring
@@ -511,6 +612,7 @@ where
             NonReplacedContents::try_from(contents)
                 .unwrap()
                 .traverse(self.context, info, self);
+            self.end_ongoing_anonymous_table();
 
             let mut inline_box = self
                 .ongoing_inline_boxes_stack
@@ -541,6 +643,63 @@ where
         contents: Contents,
         box_slot: BoxSlot<'dom>,
     ) {
+        // “If the box is a block container, then it establishes
+        //  a new block formatting context.”
+        // https://drafts.csswg.org/css-writing-modes/#block-flow
+        // Multi-column containers establish one too:
+        // https://drafts.csswg.org/css-multicol/#the-multi-column-model
+        let establishes_formatting_context =
+            self.has_different_writing_mode(info) || info.style.is_multicol_container();
+        let propagated_text_decoration_line = self.split_around_block_level_box();
+
+        let kind = match contents.try_into() {
+            Ok(contents) => match display_inside {
+                DisplayInside::Flow { is_list_item } if !establishes_formatting_context => {
+                    BlockLevelCreator::SameFormattingContextBlock(
+                        IntermediateBlockContainer::Deferred {
+                            contents,
+                            propagated_text_decoration_line,
+                            is_list_item,
+                        },
+                    )
+                },
+                _ => BlockLevelCreator::Independent {
+                    display_inside,
+                    contents: contents.into(),
+                    propagated_text_decoration_line,
+                },
+            },
+            Err(contents) => {
+                let contents = Contents::Replaced(contents);
+                BlockLevelCreator::Independent {
+                    display_inside,
+                    contents,
+                    propagated_text_decoration_line,
+                }
+            },
+        };
+        self.block_level_boxes.push(BlockLevelJob {
+            info: info.clone(),
+            box_slot,
+            kind,
+        });
+    }
+
+    /// Whether the `writing-mode` of an element differs from the one of its parent box,
+    /// which is either the ongoing inline box or the block container.
+    fn has_different_writing_mode(&self, info: &NodeAndStyleInfo<Node>) -> bool {
+        let parent_style = match self.ongoing_inline_boxes_stack.last() {
+            Some(inline_box) => &inline_box.style,
+            None => &self.info.style,
+        };
+        info.style.get_inherited_box().writing_mode != parent_style.get_inherited_box().writing_mode
+    }
+
+    /// Split all ongoing inline level boxes around a block level box that is
+    /// about to be pushed, and end the ongoing inline formatting context.
+    ///
+    /// Returns the text decoration lines to propagate to the block level box.
+    fn split_around_block_level_box(&mut self) -> TextDecorationLine {
         // We just found a block level element, all ongoing inline level boxes
         // need to be split around it. We iterate on the fragmented inline
         // level box stack to take their contents and set their first_fragment
@@ -584,41 +743,10 @@ where
         let propagated_text_decoration_line =
             self.ongoing_inline_formatting_context.text_decoration_line;
 
-        // We found a block level element, so the ongoing inline formatting
+        // We found a block level box, so the ongoing inline formatting
         // context needs to be ended.
         self.end_ongoing_inline_formatting_context();
-
-        let kind = match contents.try_into() {
-            Ok(contents) => match display_inside {
-                DisplayInside::Flow { is_list_item } => {
-                    BlockLevelCreator::SameFormattingContextBlock(
-                        IntermediateBlockContainer::Deferred {
-                            contents,
-                            propagated_text_decoration_line,
-                            is_list_item,
-                        },
-                    )
-                },
-                _ => BlockLevelCreator::Independent {
-                    display_inside,
-                    contents: contents.into(),
-                    propagated_text_decoration_line,
-                },
-            },
-            Err(contents) => {
-                let contents = Contents::Replaced(contents);
-                BlockLevelCreator::Independent {
-                    display_inside,
-                    contents,
-                    propagated_text_decoration_line,
-                }
-            },
-        };
-        self.block_level_boxes.push(BlockLevelJob {
-            info: info.clone(),
-            box_slot,
-            kind,
-        });
+        propagated_text_decoration_line
     }
 
     fn handle_absolutely_positioned_element(
@@ -720,6 +848,32 @@ where
         });
     }
 
+    fn end_ongoing_anonymous_table(&mut self) {
+        if self.ongoing_anonymous_table.is_empty() {
+            return;
+        }
+
+        let children = std::mem::take(&mut self.ongoing_anonymous_table);
+        let propagated_text_decoration_line = self.split_around_block_level_box();
+        let anonymous_table_style = self
+            .context
+            .shared_context()
+            .stylist
+            .style_for_anonymous::<Node::ConcreteElement>(
+                &self.context.shared_context().guards,
+                &PseudoElement::ServoAnonymousTable,
+                &self.info.style,
+            );
+        self.block_level_boxes.push(BlockLevelJob {
+            info: self.info.new_replacing_style(anonymous_table_style),
+            box_slot: BoxSlot::dummy(),
+            kind: BlockLevelCreator::AnonymousTable {
+                children,
+                propagated_text_decoration_line,
+            },
+        });
+    }
+
     fn current_inline_level_boxes(&mut self) -> &mut Vec<ArcRefCell<InlineLevelBox>> {
         match self.ongoing_inline_boxes_stack.last_mut() {
             Some(last) => &mut last.children,
@@ -789,6 +943,21 @@ where
                 ));
                 (block_level_box, ContainsFloats::Yes)
             },
+            BlockLevelCreator::AnonymousTable {
+                children,
+                propagated_text_decoration_line,
+            } => {
+                let context = IndependentFormattingContext::construct_for_anonymous_table(
+                    context,
+                    info,
+                    children,
+                    propagated_text_decoration_line,
+                );
+                (
+                    ArcRefCell::new(BlockLevelBox::Independent(context)),
+                    ContainsFloats::No,
+                )
+            },
         };
         self.box_slot
             .set(LayoutBox::BlockLevel(block_level_box.clone()));
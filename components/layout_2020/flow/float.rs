@@ -8,13 +8,19 @@
 
 use crate::context::LayoutContext;
 use crate::dom_traversal::{Contents, NodeAndStyleInfo, NodeExt};
-use crate::formatting_contexts::IndependentFormattingContext;
+use crate::formatting_contexts::{IndependentFormattingContext, IndependentLayout};
+use crate::fragments::{BoxFragment, CollapsedBlockMargins, CollapsedMargin};
 use crate::geom::flow_relative::{Rect, Vec2};
-use crate::style_ext::DisplayInside;
+use crate::positioned::PositioningContext;
+use crate::style_ext::{ComputedValuesExt, DisplayInside};
+use crate::ContainingBlock;
 use euclid::num::Zero;
 use servo_arc::Arc;
 use std::f32;
 use std::ops::Range;
+use style::computed_values::clear::T as ComputedClear;
+use style::computed_values::float::T as ComputedFloat;
+use style::properties::ComputedValues;
 use style::values::computed::Length;
 use style::values::specified::text::TextDecorationLine;
 
@@ -39,6 +45,10 @@ pub struct FloatContext {
     /// The current (logically) vertical position. No new floats may be placed (logically) above
     /// this line.
     pub ceiling: Length,
+    /// The (logically) lowest margin edge of the last left float.
+    pub clear_left_position: Length,
+    /// The (logically) lowest margin edge of the last right float.
+    pub clear_right_position: Length,
 }
 
 impl FloatContext {
@@ -59,6 +69,8 @@ impl FloatContext {
         FloatContext {
             bands,
             ceiling: Length::zero(),
+            clear_left_position: Length::zero(),
+            clear_right_position: Length::zero(),
         }
     }
 
@@ -115,6 +127,64 @@ impl FloatContext {
         }
     }
 
+    /// Returns the range of inline positions between the floats that intersect the given range of
+    /// block positions, and the given left and right walls.
+    pub fn available_space(
+        &self,
+        block_range: Range<Length>,
+        left_wall: Length,
+        right_wall: Length,
+    ) -> Range<Length> {
+        let mut available = left_wall..right_wall;
+        let mut band = self.bands.find(block_range.start).unwrap();
+        loop {
+            if let Some(left) = band.left {
+                available.start = available.start.max(left);
+            }
+            if let Some(right) = band.right {
+                available.end = available.end.min(right);
+            }
+            match self.bands.find_next(band.top) {
+                Some(next_band) if next_band.top < block_range.end => band = next_band,
+                _ => return available,
+            }
+        }
+    }
+
+    /// Determines the first block position, no higher than `block_start`, where an object of the
+    /// given size fits between the floats, and returns it along with the range of inline positions
+    /// available there. If the object is wider than the space between the walls, this is the first
+    /// position past all the floats.
+    ///
+    /// Unlike `place_object`, this makes sure the object fits beside all the floats it intersects.
+    /// It is used to place boxes that must not overlap floats, but that do not float themselves.
+    pub fn place_object_beside_floats(
+        &self,
+        size: &Vec2<Length>,
+        block_start: Length,
+        left_wall: Length,
+        right_wall: Length,
+    ) -> (Length, Range<Length>) {
+        let mut block_position = block_start;
+        loop {
+            let available = self.available_space(
+                block_position..block_position + size.block,
+                left_wall,
+                right_wall,
+            );
+            let no_floats = available.start == left_wall && available.end == right_wall;
+            if no_floats || size.inline <= available.end - available.start {
+                return (block_position, available);
+            }
+            match self.bands.find_next(block_position) {
+                Some(next_band) if !next_band.top.px().is_infinite() => {
+                    block_position = next_band.top
+                },
+                _ => return (block_position, available),
+            }
+        }
+    }
+
     /// Places a new float and adds it to the list. Returns the start corner of its margin box.
     pub fn add_float(&mut self, new_float: &PlacementInfo) -> Vec2<Length> {
         // Place the float.
@@ -150,6 +220,19 @@ impl FloatContext {
         // CSS 2.1 § 9.5.1 rule 6: The outer top of a floating box may not be higher than the outer
         // top of any block or floated box generated by an element earlier in the source document.
         self.ceiling = self.ceiling.max(new_float_rect.start_corner.block);
+
+        match new_float.side {
+            FloatSide::Left => {
+                self.clear_left_position = self
+                    .clear_left_position
+                    .max(new_float_rect.max_block_position())
+            },
+            FloatSide::Right => {
+                self.clear_right_position = self
+                    .clear_right_position
+                    .max(new_float_rect.max_block_position())
+            },
+        }
         new_float_rect.start_corner
     }
 }
@@ -191,6 +274,27 @@ pub enum ClearSide {
     Both = 3,
 }
 
+impl FloatSide {
+    pub(crate) fn from_style(style: &ComputedValues) -> Option<FloatSide> {
+        match style.get_box().float {
+            ComputedFloat::None => None,
+            ComputedFloat::Left => Some(FloatSide::Left),
+            ComputedFloat::Right => Some(FloatSide::Right),
+        }
+    }
+}
+
+impl ClearSide {
+    pub(crate) fn from_style(style: &ComputedValues) -> ClearSide {
+        match style.get_box().clear {
+            ComputedClear::None => ClearSide::None,
+            ComputedClear::Left => ClearSide::Left,
+            ComputedClear::Right => ClearSide::Right,
+            ComputedClear::Both => ClearSide::Both,
+        }
+    }
+}
+
 /// Internal data structure that describes a nonoverlapping vertical region in which floats may be
 /// placed. Floats must go between "left edge + `left`" and "right edge - `right`".
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -542,6 +646,104 @@ impl FloatBandLink {
 // Float boxes
 
 impl FloatBox {
+    /// Lays out the float box, leaving it at the start corner of its containing block.
+    /// It is moved to its final position by `SequentialLayoutState::place_float_fragment`.
+    ///
+    /// https://drafts.csswg.org/css2/#float-width
+    /// https://drafts.csswg.org/css2/#block-root-margin
+    pub fn layout(
+        &mut self,
+        layout_context: &LayoutContext,
+        positioning_context: &mut PositioningContext,
+        containing_block: &ContainingBlock,
+        tree_rank: usize,
+    ) -> BoxFragment {
+        let style = self.contents.style().clone();
+        let tag = self.contents.tag();
+        positioning_context.layout_maybe_position_relative_fragment(
+            layout_context,
+            containing_block,
+            &style,
+            |positioning_context| {
+                let pbm = style.padding_border_margin(containing_block);
+                let margin = pbm.margin.auto_is(Length::zero);
+                let pbm_sums = &(&pbm.padding + &pbm.border) + &margin;
+
+                let (content_size, fragments) = match &mut self.contents {
+                    IndependentFormattingContext::Replaced(replaced) => {
+                        let size = replaced.contents.used_size_as_if_inline_element(
+                            containing_block,
+                            &replaced.style,
+                            &pbm,
+                        );
+                        let fragments = replaced
+                            .contents
+                            .make_fragments(&replaced.style, size.clone());
+                        (size, fragments)
+                    },
+                    IndependentFormattingContext::NonReplaced(non_replaced) => {
+                        let box_size = style.content_box_size(containing_block, &pbm);
+                        let max_box_size = style.content_max_box_size(containing_block, &pbm);
+                        let min_box_size = style
+                            .content_min_box_size(containing_block, &pbm)
+                            .auto_is(Length::zero);
+
+                        let tentative_inline_size = box_size.inline.auto_is(|| {
+                            let available_size =
+                                containing_block.inline_size - pbm_sums.inline_sum();
+                            non_replaced
+                                .inline_content_sizes(layout_context)
+                                .shrink_to_fit(available_size)
+                        });
+                        let inline_size = tentative_inline_size
+                            .clamp_between_extremums(min_box_size.inline, max_box_size.inline);
+
+                        let containing_block_for_children = ContainingBlock {
+                            inline_size,
+                            block_size: box_size.block,
+                            style: &non_replaced.style,
+                        };
+                        let IndependentLayout {
+                            fragments,
+                            content_block_size,
+                        } = non_replaced.layout(
+                            layout_context,
+                            positioning_context,
+                            &containing_block_for_children,
+                            tree_rank,
+                        );
+                        let block_size = box_size
+                            .block
+                            .auto_is(|| content_block_size)
+                            .clamp_between_extremums(min_box_size.block, max_box_size.block);
+                        (
+                            Vec2 {
+                                inline: inline_size,
+                                block: block_size,
+                            },
+                            fragments,
+                        )
+                    },
+                };
+                let content_rect = Rect {
+                    start_corner: Vec2::zero(),
+                    size: content_size,
+                };
+                BoxFragment::new(
+                    tag,
+                    style.clone(),
+                    fragments,
+                    containing_block.style.writing_mode,
+                    content_rect,
+                    pbm.padding,
+                    pbm.border,
+                    margin,
+                    CollapsedBlockMargins::zero(),
+                )
+            },
+        )
+    }
+
     /// Creates a new float box.
     pub fn construct<'dom>(
         context: &LayoutContext,
@@ -561,3 +763,181 @@ impl FloatBox {
         }
     }
 }
+
+// Sequential layout
+
+/// The position of the containing block of the boxes being laid out, relative to the block
+/// formatting context, used to convert between the coordinates of the two.
+#[derive(Clone, Debug)]
+pub(crate) struct ContainingBlockPositionInfo {
+    /// The block position of the start of the content box of the containing block,
+    /// before the margins in `block_start_margins_not_collapsed` are collapsed.
+    pub block_start: Length,
+    /// The margins at the start of the containing block that may still collapse
+    /// with those of its first in-flow child.
+    pub block_start_margins_not_collapsed: CollapsedMargin,
+    /// The inline position of the start of the content box of the containing block.
+    pub inline_start: Length,
+    /// The inline position of the end of the content box of the containing block.
+    pub inline_end: Length,
+}
+
+/// The state of a block formatting context that contains floats while its boxes are laid out,
+/// in tree order and without parallelism.
+///
+/// Every box of the block formatting context advances `bfc_relative_block_position` past itself
+/// once laid out, so that floats, clearance and boxes that avoid floats can be placed relative to
+/// the preceding content.
+pub(crate) struct SequentialLayoutState {
+    /// The floats placed so far.
+    pub floats: FloatContext,
+    /// The block position of the end of the content laid out so far, before `current_margin`.
+    pub bfc_relative_block_position: Length,
+    /// The margins after the content laid out so far, which may still collapse
+    /// with those of the next box.
+    pub current_margin: CollapsedMargin,
+    /// The position of the containing block of the boxes being laid out.
+    pub containing_block_info: ContainingBlockPositionInfo,
+}
+
+impl SequentialLayoutState {
+    /// Returns the state of a new block formatting context whose root has the given
+    /// content inline-size.
+    pub fn new(inline_size: Length) -> Self {
+        SequentialLayoutState {
+            floats: FloatContext::new(),
+            bfc_relative_block_position: Length::zero(),
+            current_margin: CollapsedMargin::zero(),
+            containing_block_info: ContainingBlockPositionInfo {
+                block_start: Length::zero(),
+                block_start_margins_not_collapsed: CollapsedMargin::zero(),
+                inline_start: Length::zero(),
+                inline_end: inline_size,
+            },
+        }
+    }
+
+    /// Moves past content of the given block size. No float may be placed above it afterwards.
+    pub fn advance_block_position(&mut self, block_distance: Length) {
+        self.bfc_relative_block_position += block_distance;
+        self.floats.lower_ceiling(self.bfc_relative_block_position);
+    }
+
+    /// Collapses the margins adjoined so far, which can no longer collapse with the next ones.
+    pub fn collapse_margins(&mut self) {
+        let margin = std::mem::replace(&mut self.current_margin, CollapsedMargin::zero());
+        self.advance_block_position(margin.solve());
+    }
+
+    /// Adjoins a margin to the ones that have not been collapsed yet.
+    pub fn adjoin_assign(&mut self, margin: &CollapsedMargin) {
+        self.current_margin.adjoin_assign(margin)
+    }
+
+    /// Replaces the position of the containing block, returning the previous one.
+    pub fn replace_containing_block_position_info(
+        &mut self,
+        info: ContainingBlockPositionInfo,
+    ) -> ContainingBlockPositionInfo {
+        std::mem::replace(&mut self.containing_block_info, info)
+    }
+
+    /// Returns the clearance of a box with the given `clear` value and block-start margin,
+    /// placed after the content laid out so far, or `None` if it does not need any.
+    ///
+    /// https://drafts.csswg.org/css2/#clearance
+    pub fn calculate_clearance(
+        &self,
+        clear: ClearSide,
+        block_start_margin: &CollapsedMargin,
+    ) -> Option<Length> {
+        let clear_position = match clear {
+            ClearSide::None => return None,
+            ClearSide::Left => self.floats.clear_left_position,
+            ClearSide::Right => self.floats.clear_right_position,
+            ClearSide::Both => self
+                .floats
+                .clear_left_position
+                .max(self.floats.clear_right_position),
+        };
+        // “Then the amount of clearance is set to the greater of: […]” the amount
+        // necessary to place the border edge of the block even with the bottom outer edge
+        // of the lowest float that is to be cleared, if its hypothetical position is not past it.
+        let hypothetical_position = self.bfc_relative_block_position +
+            self.current_margin.adjoin(block_start_margin).solve();
+        if hypothetical_position >= clear_position {
+            return None;
+        }
+        Some(
+            clear_position -
+                self.bfc_relative_block_position -
+                self.current_margin.solve() -
+                block_start_margin.solve(),
+        )
+    }
+
+    /// Returns the block position of the border edge of a box with the given clearance and
+    /// block-start margin, placed after the content laid out so far.
+    pub fn position_with_clearance(
+        &self,
+        clearance: Option<Length>,
+        block_start_margin: &CollapsedMargin,
+    ) -> Length {
+        match clearance {
+            Some(clearance) => {
+                self.bfc_relative_block_position +
+                    self.current_margin.solve() +
+                    clearance +
+                    block_start_margin.solve()
+            },
+            None => {
+                self.bfc_relative_block_position +
+                    self.current_margin.adjoin(block_start_margin).solve()
+            },
+        }
+    }
+
+    /// Places a float fragment laid out by `FloatBox::layout`, whose hypothetical position is
+    /// at `block_offset_from_containing_block_top` in its containing block. The margins in
+    /// `margins_collapsing_with_parent_containing_block` are those of the preceding boxes
+    /// that collapse with the start margin of the containing block.
+    pub fn place_float_fragment(
+        &mut self,
+        box_fragment: &mut BoxFragment,
+        margins_collapsing_with_parent_containing_block: CollapsedMargin,
+        block_offset_from_containing_block_top: Length,
+    ) {
+        let containing_block_info = &self.containing_block_info;
+        let block_start_of_containing_block_in_bfc = containing_block_info.block_start +
+            containing_block_info
+                .block_start_margins_not_collapsed
+                .adjoin(&margins_collapsing_with_parent_containing_block)
+                .solve();
+        self.floats.lower_ceiling(
+            block_start_of_containing_block_in_bfc + block_offset_from_containing_block_top,
+        );
+
+        let pbm_sums = &(&box_fragment.padding + &box_fragment.border) + &box_fragment.margin;
+        let margin_box_start_corner = self.floats.add_float(&PlacementInfo {
+            size: Vec2 {
+                inline: box_fragment.content_rect.size.inline + pbm_sums.inline_sum(),
+                block: box_fragment.content_rect.size.block + pbm_sums.block_sum(),
+            },
+            side: FloatSide::from_style(&box_fragment.style).expect("Float box wasn't floated!"),
+            clear: ClearSide::from_style(&box_fragment.style),
+            left_wall: containing_block_info.inline_start,
+            right_wall: containing_block_info.inline_end,
+        });
+
+        // The start corner of the fragment may already include a relative positioning offset.
+        let containing_block_info = &self.containing_block_info;
+        box_fragment.content_rect.start_corner = Vec2 {
+            inline: margin_box_start_corner.inline + pbm_sums.inline_start -
+                containing_block_info.inline_start +
+                box_fragment.content_rect.start_corner.inline,
+            block: margin_box_start_corner.block + pbm_sums.block_start -
+                block_start_of_containing_block_in_bfc +
+                box_fragment.content_rect.start_corner.block,
+        };
+    }
+}
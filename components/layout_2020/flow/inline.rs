@@ -4,12 +4,12 @@
 
 use crate::cell::ArcRefCell;
 use crate::context::LayoutContext;
-use crate::flow::float::FloatBox;
+use crate::flow::float::{FloatBox, SequentialLayoutState};
 use crate::flow::FlowLayout;
 use crate::formatting_contexts::IndependentFormattingContext;
 use crate::fragments::{
     AbsoluteOrFixedPositionedFragment, AnonymousFragment, BoxFragment, CollapsedBlockMargins,
-    DebugId, FontMetrics, Fragment, Tag, TextFragment,
+    CollapsedMargin, DebugId, FontMetrics, Fragment, Tag, TextFragment,
 };
 use crate::geom::flow_relative::{Rect, Sides, Vec2};
 use crate::positioned::{
@@ -21,14 +21,20 @@ use crate::style_ext::{ComputedValuesExt, Display, DisplayGeneratingBox, Display
 use crate::ContainingBlock;
 use app_units::Au;
 use atomic_refcell::AtomicRef;
+use gfx::text::glyph::GlyphStore;
+use gfx::text::hyphenation::Hyphenation;
 use gfx::text::text_run::GlyphRun;
+use gfx_traits::ByteIndex;
+use range::Range;
 use servo_arc::Arc;
+use std::sync::Arc as StdArc;
 use style::logical_geometry::WritingMode;
 use style::properties::ComputedValues;
 use style::values::computed::{Length, LengthPercentage, Percentage};
 use style::values::specified::text::TextAlignKeyword;
 use style::values::specified::text::TextDecorationLine;
 use style::Zero;
+use unicode_bidi as bidi;
 use webrender_api::FontInstanceKey;
 
 #[derive(Debug, Default, Serialize)]
@@ -68,6 +74,8 @@ pub(crate) struct TextRun {
 struct InlineNestingLevelState<'box_tree> {
     remaining_boxes: InlineBoxChildIter<'box_tree>,
     fragments_so_far: Vec<Fragment>,
+    /// The bidi embedding level of each fragment in `fragments_so_far`.
+    bidi_levels_so_far: Vec<bidi::Level>,
     inline_start: Length,
     max_block_size_of_fragments_so_far: Length,
     positioning_context: Option<PositioningContext>,
@@ -86,6 +94,8 @@ struct PartialInlineBoxFragment<'box_tree> {
     border: Sides<Length>,
     margin: Sides<Length>,
     last_box_tree_fragment: bool,
+    /// The bidi embedding level of this fragment when it has no content.
+    bidi_level: bidi::Level,
     parent_nesting_level: InlineNestingLevelState<'box_tree>,
 }
 
@@ -96,6 +106,11 @@ struct InlineFormattingContextState<'box_tree, 'a, 'b> {
     inline_position: Length,
     partial_inline_boxes_stack: Vec<PartialInlineBoxFragment<'box_tree>>,
     current_nesting_level: InlineNestingLevelState<'box_tree>,
+    sequential_layout_state: Option<&'a mut SequentialLayoutState>,
+    /// Floats that did not fit on the current line, to be placed below it.
+    pending_floats: Vec<BoxFragment>,
+    /// `None` when all the content is left-to-right.
+    bidi_levels: Option<BidiLevels>,
 }
 
 impl<'box_tree, 'a, 'b> InlineFormattingContextState<'box_tree, 'a, 'b> {
@@ -121,12 +136,299 @@ impl<'box_tree, 'a, 'b> InlineFormattingContextState<'box_tree, 'a, 'b> {
 
         self.positioning_context.push(hoisted_box);
     }
+
+    fn current_bidi_level(&self) -> bidi::Level {
+        match &self.bidi_levels {
+            Some(bidi_levels) => bidi_levels.current(),
+            None => bidi::Level::ltr(),
+        }
+    }
+
+    /// Places a float found in the inline content. It goes beside the current line if it fits
+    /// there along with the content of the line so far, and below that line otherwise.
+    ///
+    /// https://drafts.csswg.org/css2/#floats
+    fn place_float_fragment(&mut self, mut fragment: BoxFragment) {
+        let sequential_layout_state = match self.sequential_layout_state.as_mut() {
+            Some(sequential_layout_state) => sequential_layout_state,
+            None => return self.lines.fragments.push(Fragment::Box(fragment)),
+        };
+        let margin_box_inline_size = fragment.content_rect.size.inline +
+            fragment.padding.inline_sum() +
+            fragment.border.inline_sum() +
+            fragment.margin.inline_sum();
+        if self.inline_position != Length::zero() &&
+            self.inline_position + margin_box_inline_size > self.lines.current_line_inline_size
+        {
+            self.pending_floats.push(fragment);
+            return;
+        }
+        sequential_layout_state.place_float_fragment(
+            &mut fragment,
+            CollapsedMargin::zero(),
+            self.lines.next_line_block_position,
+        );
+        self.lines.fragments.push(Fragment::Box(fragment));
+        self.update_line_for_floats();
+    }
+
+    /// Places the floats that did not fit on the line that was just finished,
+    /// and sets up the next line beside the floats.
+    fn start_new_line(&mut self) {
+        for mut fragment in std::mem::take(&mut self.pending_floats) {
+            if let Some(sequential_layout_state) = self.sequential_layout_state.as_mut() {
+                sequential_layout_state.place_float_fragment(
+                    &mut fragment,
+                    CollapsedMargin::zero(),
+                    self.lines.next_line_block_position,
+                );
+            }
+            self.lines.fragments.push(Fragment::Box(fragment));
+        }
+        self.update_line_for_floats();
+    }
+
+    /// Shortens the current line box so that it does not overlap the floats beside its start.
+    fn update_line_for_floats(&mut self) {
+        let sequential_layout_state = match self.sequential_layout_state.as_ref() {
+            Some(sequential_layout_state) => sequential_layout_state,
+            None => return,
+        };
+        // The content of this inline formatting context starts at the current position
+        // of the sequential layout state, see `InlineFormattingContext::layout`.
+        let containing_block_info = &sequential_layout_state.containing_block_info;
+        let line_block_start = sequential_layout_state.bfc_relative_block_position +
+            self.lines.next_line_block_position;
+        let available = sequential_layout_state.floats.available_space(
+            line_block_start..line_block_start,
+            containing_block_info.inline_start,
+            containing_block_info.inline_end,
+        );
+        self.lines.current_line_inline_start = available.start - containing_block_info.inline_start;
+        self.lines.current_line_inline_size = (available.end - available.start).max(Length::zero());
+    }
 }
 
 struct Lines {
     // One anonymous fragment per line
     fragments: Vec<Fragment>,
     next_line_block_position: Length,
+    /// The inline offset of the current line box in the containing block,
+    /// which is only non-zero when floats are beside it.
+    current_line_inline_start: Length,
+    /// The inline size of the current line box, shortened by the floats beside it.
+    current_line_inline_size: Length,
+}
+
+/// The resolved bidi embedding levels of the content of an inline formatting context,
+/// consumed in content order as its inline-level boxes are laid out.
+///
+/// https://drafts.csswg.org/css-writing-modes/#text-direction
+struct BidiLevels {
+    /// One level per byte of the text passed to the Unicode Bidirectional Algorithm.
+    levels: Vec<bidi::Level>,
+    position: usize,
+}
+
+impl BidiLevels {
+    /// Returns `None` when all the content is left-to-right, so that no reordering is needed.
+    fn new(
+        inline_formatting_context: &InlineFormattingContext,
+        writing_mode: WritingMode,
+    ) -> Option<Self> {
+        fn collect_text(inline_level_boxes: &[ArcRefCell<InlineLevelBox>], text: &mut String) {
+            for inline_level_box in inline_level_boxes {
+                match &*inline_level_box.borrow() {
+                    InlineLevelBox::InlineBox(inline_box) => {
+                        let control_chars = bidi_control_chars(&inline_box.style);
+                        if let Some((open, _)) = control_chars {
+                            text.push_str(open)
+                        }
+                        collect_text(&inline_box.children, text);
+                        if let Some((_, close)) = control_chars {
+                            text.push_str(close)
+                        }
+                    },
+                    InlineLevelBox::TextRun(text_run) => text.push_str(&text_run.text),
+                    // Atomic inlines are treated as neutral characters.
+                    // https://drafts.csswg.org/css-writing-modes/#unicode-bidi
+                    InlineLevelBox::Atomic(_) => text.push(OBJECT_REPLACEMENT_CHARACTER),
+                    InlineLevelBox::OutOfFlowAbsolutelyPositionedBox(_) |
+                    InlineLevelBox::OutOfFlowFloatBox(_) => {},
+                }
+            }
+        }
+
+        let mut text = String::new();
+        collect_text(&inline_formatting_context.inline_level_boxes, &mut text);
+        let paragraph_level = writing_mode.to_bidi_level();
+        let info = bidi::BidiInfo::new(&text, Some(paragraph_level));
+        if paragraph_level.is_ltr() && !bidi::level::has_rtl(&info.levels) {
+            return None;
+        }
+        Some(BidiLevels {
+            levels: info.levels,
+            position: 0,
+        })
+    }
+
+    /// The levels of the next `len` bytes of content.
+    fn advance(&mut self, len: usize) -> &[bidi::Level] {
+        let start = self.position;
+        self.position += len;
+        &self.levels[start..self.position]
+    }
+
+    /// The level at the current position in the content.
+    fn current(&self) -> bidi::Level {
+        self.levels
+            .get(self.position)
+            .or_else(|| self.levels.last())
+            .copied()
+            .unwrap_or_else(bidi::Level::ltr)
+    }
+}
+
+const OBJECT_REPLACEMENT_CHARACTER: char = '\u{FFFC}';
+
+/// If the 'unicode-bidi' property has a value other than 'normal', return the bidi control codes
+/// to inject before and after the text content of the element.
+fn bidi_control_chars(style: &ComputedValues) -> Option<(&'static str, &'static str)> {
+    use style::computed_values::direction::T::*;
+    use style::computed_values::unicode_bidi::T::*;
+
+    let unicode_bidi = style.get_text().unicode_bidi;
+    let direction = style.get_inherited_box().direction;
+
+    // See the table in https://drafts.csswg.org/css-writing-modes/#unicode-bidi
+    match (unicode_bidi, direction) {
+        (Normal, _) => None,
+        (Embed, Ltr) => Some(("\u{202A}", "\u{202C}")),
+        (Embed, Rtl) => Some(("\u{202B}", "\u{202C}")),
+        (Isolate, Ltr) => Some(("\u{2066}", "\u{2069}")),
+        (Isolate, Rtl) => Some(("\u{2067}", "\u{2069}")),
+        (BidiOverride, Ltr) => Some(("\u{202D}", "\u{202C}")),
+        (BidiOverride, Rtl) => Some(("\u{202E}", "\u{202C}")),
+        (IsolateOverride, Ltr) => Some(("\u{2068}\u{202D}", "\u{202C}\u{2069}")),
+        (IsolateOverride, Rtl) => Some(("\u{2068}\u{202E}", "\u{202C}\u{2069}")),
+        (Plaintext, _) => Some(("\u{2068}", "\u{2069}")),
+    }
+}
+
+/// Reorders the fragments of a line, or of the part of an inline box on a line, from content
+/// order to visual order, and moves them along the inline axis accordingly.
+///
+/// https://www.unicode.org/reports/tr9/#L2
+fn reorder_fragments_for_bidi(
+    fragments: &mut [Fragment],
+    levels: &[bidi::Level],
+    writing_mode: WritingMode,
+) {
+    debug_assert_eq!(fragments.len(), levels.len());
+    if writing_mode.line_left_is_inline_start() && levels.iter().all(|level| level.is_ltr()) {
+        return;
+    }
+
+    // “From the highest level found in the text to the lowest odd level on each line,
+    //  reverse any contiguous sequence of characters that are at that level or higher.”
+    let mut visual_order: Vec<usize> = (0..fragments.len()).collect();
+    let highest_level = levels.iter().map(|level| level.number()).max().unwrap_or(0);
+    let lowest_odd_level = levels
+        .iter()
+        .map(|level| level.number())
+        .min()
+        .map_or(1, |lowest| lowest | 1);
+    for level in (lowest_odd_level..=highest_level).rev() {
+        let mut index = 0;
+        while index < visual_order.len() {
+            if levels[visual_order[index]].number() < level {
+                index += 1;
+                continue;
+            }
+            let start = index;
+            while index < visual_order.len() && levels[visual_order[index]].number() >= level {
+                index += 1;
+            }
+            visual_order[start..index].reverse();
+        }
+    }
+    // The visual order goes from line-left to line-right,
+    // but fragments are positioned from the inline start.
+    if !writing_mode.line_left_is_inline_start() {
+        visual_order.reverse();
+    }
+
+    let margin_box_inline_extent = |fragment: &Fragment| match fragment {
+        Fragment::Box(fragment) => Some((
+            fragment.content_rect.start_corner.inline -
+                fragment.padding.inline_start -
+                fragment.border.inline_start -
+                fragment.margin.inline_start,
+            fragment.content_rect.size.inline +
+                fragment.padding.inline_sum() +
+                fragment.border.inline_sum() +
+                fragment.margin.inline_sum(),
+        )),
+        Fragment::Text(fragment) => {
+            Some((fragment.rect.start_corner.inline, fragment.rect.size.inline))
+        },
+        Fragment::Image(fragment) => {
+            Some((fragment.rect.start_corner.inline, fragment.rect.size.inline))
+        },
+        Fragment::Anonymous(_) | Fragment::AbsoluteOrFixedPositioned(_) => None,
+    };
+    let mut inline_position = match fragments
+        .iter()
+        .filter_map(margin_box_inline_extent)
+        .map(|(start, _)| start)
+        .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+    {
+        Some(start) => start,
+        None => return,
+    };
+    for index in visual_order {
+        let fragment = &mut fragments[index];
+        if let Some((start, size)) = margin_box_inline_extent(&*fragment) {
+            fragment.offset_inline(&(inline_position - start));
+            inline_position += size;
+        }
+    }
+}
+
+/// Converts the positions of the fragments of an inline box, which are laid out along the
+/// inline axis of its inline formatting context, to the writing mode of the inline box
+/// when the two have opposite inline directions.
+fn flip_fragments_inline_axis(
+    fragments: &mut [Fragment],
+    content_inline_size: Length,
+    writing_mode: WritingMode,
+) {
+    for fragment in fragments {
+        let rect = match fragment {
+            Fragment::Box(fragment) => {
+                std::mem::swap(
+                    &mut fragment.padding.inline_start,
+                    &mut fragment.padding.inline_end,
+                );
+                std::mem::swap(
+                    &mut fragment.border.inline_start,
+                    &mut fragment.border.inline_end,
+                );
+                std::mem::swap(
+                    &mut fragment.margin.inline_start,
+                    &mut fragment.margin.inline_end,
+                );
+                fragment.containing_block_writing_mode = writing_mode;
+                &mut fragment.content_rect
+            },
+            Fragment::Anonymous(fragment) => &mut fragment.rect,
+            Fragment::Text(fragment) => &mut fragment.rect,
+            Fragment::Image(fragment) => &mut fragment.rect,
+            Fragment::AbsoluteOrFixedPositioned(_) => continue,
+        };
+        rect.start_corner.inline =
+            content_inline_size - rect.start_corner.inline - rect.size.inline;
+    }
 }
 
 impl InlineFormattingContext {
@@ -185,16 +487,22 @@ impl InlineFormattingContext {
                                 runs,
                                 break_at_start,
                                 ..
-                            } = text_run.break_and_shape(self.layout_context);
+                            } = text_run.break_and_shape(self.layout_context, None);
                             if break_at_start {
                                 self.line_break_opportunity()
                             }
-                            for run in &runs {
+                            for (run, _) in &runs {
                                 let advance = Length::from(run.glyph_store.total_advance());
                                 if run.glyph_store.is_whitespace() {
                                     self.line_break_opportunity()
                                 } else {
-                                    self.current_line.min_content += advance
+                                    self.current_line.min_content += advance;
+                                    // Lines broken at a hyphenation opportunity end with a hyphen.
+                                    if let Some(hyphen) = &run.hyphen {
+                                        self.current_line.min_content +=
+                                            Length::from(hyphen.total_advance());
+                                        self.line_break_opportunity()
+                                    }
                                 }
                                 self.current_line.max_content += advance
                             }
@@ -208,7 +516,16 @@ impl InlineFormattingContext {
                             self.current_line.max_content += outer.max_content;
                             self.current_line_percentages += pc;
                         },
-                        InlineLevelBox::OutOfFlowFloatBox(_) |
+                        InlineLevelBox::OutOfFlowFloatBox(float_box) => {
+                            // A float fits beside the line it is on, unless it is too wide
+                            // to fit beside anything.
+                            let outer = float_box.contents.outer_inline_content_sizes(
+                                self.layout_context,
+                                self.containing_block_writing_mode,
+                            );
+                            self.paragraph.min_content.max_assign(outer.min_content);
+                            self.current_line.max_content += outer.max_content;
+                        },
                         InlineLevelBox::OutOfFlowAbsolutelyPositionedBox(_) => {},
                     }
                 }
@@ -264,7 +581,14 @@ impl InlineFormattingContext {
         positioning_context: &mut PositioningContext,
         containing_block: &ContainingBlock,
         tree_rank: usize,
+        mut sequential_layout_state: Option<&mut SequentialLayoutState>,
     ) -> FlowLayout {
+        // Lines and the floats among them are positioned from the start of the content,
+        // which no longer collapses with any margin.
+        if let Some(ref mut sequential_layout_state) = sequential_layout_state {
+            sequential_layout_state.collapse_margins();
+        }
+
         let mut ifc = InlineFormattingContextState {
             positioning_context,
             containing_block,
@@ -272,17 +596,24 @@ impl InlineFormattingContext {
             lines: Lines {
                 fragments: Vec::new(),
                 next_line_block_position: Length::zero(),
+                current_line_inline_start: Length::zero(),
+                current_line_inline_size: containing_block.inline_size,
             },
             inline_position: Length::zero(),
             current_nesting_level: InlineNestingLevelState {
                 remaining_boxes: InlineBoxChildIter::from_formatting_context(self),
                 fragments_so_far: Vec::with_capacity(self.inline_level_boxes.len()),
+                bidi_levels_so_far: Vec::with_capacity(self.inline_level_boxes.len()),
                 inline_start: Length::zero(),
                 max_block_size_of_fragments_so_far: Length::zero(),
                 positioning_context: None,
                 text_decoration_line: self.text_decoration_line,
             },
+            sequential_layout_state,
+            pending_floats: Vec::new(),
+            bidi_levels: BidiLevels::new(self, containing_block.style.writing_mode),
         };
+        ifc.update_line_for_floats();
 
         loop {
             if let Some(child) = ifc.current_nesting_level.remaining_boxes.next() {
@@ -307,6 +638,13 @@ impl InlineFormattingContext {
                                     },
                                     block: ifc.lines.next_line_block_position,
                                 },
+                                // Internal table boxes are block-level.
+                                Display::GeneratingBox(DisplayGeneratingBox::LayoutInternal(_)) => {
+                                    Vec2 {
+                                        inline: Length::zero(),
+                                        block: ifc.lines.next_line_block_position,
+                                    }
+                                },
                                 Display::Contents => {
                                     panic!("display:contents does not generate an abspos box")
                                 },
@@ -322,6 +660,10 @@ impl InlineFormattingContext {
                         );
                         let hoisted_fragment = hoisted_box.fragment.clone();
                         ifc.push_hoisted_box_to_positioning_context(hoisted_box);
+                        let bidi_level = ifc.current_bidi_level();
+                        ifc.current_nesting_level
+                            .bidi_levels_so_far
+                            .push(bidi_level);
                         ifc.current_nesting_level.fragments_so_far.push(
                             Fragment::AbsoluteOrFixedPositioned(
                                 AbsoluteOrFixedPositionedFragment {
@@ -331,15 +673,27 @@ impl InlineFormattingContext {
                             ),
                         );
                     },
-                    InlineLevelBox::OutOfFlowFloatBox(_box_) => {
-                        // TODO
+                    InlineLevelBox::OutOfFlowFloatBox(float_box) => {
+                        let fragment = float_box.layout(
+                            layout_context,
+                            ifc.positioning_context,
+                            containing_block,
+                            tree_rank,
+                        );
+                        ifc.place_float_fragment(fragment);
                     },
                 }
             } else
             // Reached the end of ifc.remaining_boxes
             if let Some(mut partial) = ifc.partial_inline_boxes_stack.pop() {
+                if let (Some(bidi_levels), Some((_, close))) =
+                    (ifc.bidi_levels.as_mut(), bidi_control_chars(&partial.style))
+                {
+                    bidi_levels.advance(close.len());
+                }
                 partial.finish_layout(
                     layout_context,
+                    containing_block.style.writing_mode,
                     &mut ifc.current_nesting_level,
                     &mut ifc.inline_position,
                     false,
@@ -351,9 +705,14 @@ impl InlineFormattingContext {
                     containing_block,
                     ifc.inline_position,
                 );
+                ifc.start_new_line();
+                let content_block_size = ifc.lines.next_line_block_position;
+                if let Some(sequential_layout_state) = ifc.sequential_layout_state {
+                    sequential_layout_state.advance_block_position(content_block_size);
+                }
                 return FlowLayout {
                     fragments: ifc.lines.fragments,
-                    content_block_size: ifc.lines.next_line_block_position,
+                    content_block_size,
                     collapsible_margins_in_children: CollapsedBlockMargins::zero(),
                 };
             }
@@ -369,6 +728,12 @@ impl Lines {
         line_content_inline_size: Length,
     ) {
         let mut line_contents = std::mem::take(&mut top_nesting_level.fragments_so_far);
+        let bidi_levels = std::mem::take(&mut top_nesting_level.bidi_levels_so_far);
+        reorder_fragments_for_bidi(
+            &mut line_contents,
+            &bidi_levels,
+            containing_block.style.writing_mode,
+        );
         let line_block_size = std::mem::replace(
             &mut top_nesting_level.max_block_size_of_fragments_so_far,
             Length::zero(),
@@ -403,8 +768,8 @@ impl Lines {
         };
         let move_by = match text_align {
             TextAlign::Start => Length::zero(),
-            TextAlign::Center => (containing_block.inline_size - line_content_inline_size) / 2.,
-            TextAlign::End => containing_block.inline_size - line_content_inline_size,
+            TextAlign::Center => (self.current_line_inline_size - line_content_inline_size) / 2.,
+            TextAlign::End => self.current_line_inline_size - line_content_inline_size,
         };
         if move_by > Length::zero() {
             for fragment in &mut line_contents {
@@ -412,11 +777,11 @@ impl Lines {
             }
         }
         let start_corner = Vec2 {
-            inline: Length::zero(),
+            inline: self.current_line_inline_start,
             block: self.next_line_block_position,
         };
         let size = Vec2 {
-            inline: containing_block.inline_size,
+            inline: self.current_line_inline_size,
             block: line_block_size,
         };
         self.next_line_block_position += size.block;
@@ -459,6 +824,12 @@ impl InlineBox {
         let positioning_context = PositioningContext::new_for_style(&style);
         let text_decoration_line =
             ifc.current_nesting_level.text_decoration_line | style.clone_text_decoration_line();
+        if let (Some(bidi_levels), Some((open, _))) =
+            (ifc.bidi_levels.as_mut(), bidi_control_chars(&style))
+        {
+            bidi_levels.advance(open.len());
+        }
+        let bidi_level = ifc.current_bidi_level();
         PartialInlineBoxFragment {
             tag: self.tag,
             style,
@@ -467,6 +838,7 @@ impl InlineBox {
             border,
             margin,
             last_box_tree_fragment: self.last_fragment,
+            bidi_level,
             parent_nesting_level: std::mem::replace(
                 &mut ifc.current_nesting_level,
                 InlineNestingLevelState {
@@ -474,6 +846,7 @@ impl InlineBox {
                         this_inline_level_box,
                     ),
                     fragments_so_far: Vec::with_capacity(self.children.len()),
+                    bidi_levels_so_far: Vec::with_capacity(self.children.len()),
                     inline_start: ifc.inline_position,
                     max_block_size_of_fragments_so_far: Length::zero(),
                     positioning_context,
@@ -488,6 +861,7 @@ impl<'box_tree> PartialInlineBoxFragment<'box_tree> {
     fn finish_layout(
         &mut self,
         layout_context: &LayoutContext,
+        containing_block_writing_mode: WritingMode,
         nesting_level: &mut InlineNestingLevelState,
         inline_position: &mut Length,
         at_line_break: bool,
@@ -500,10 +874,23 @@ impl<'box_tree> PartialInlineBoxFragment<'box_tree> {
             start_corner: self.start_corner.clone(),
         };
 
+        let mut children = std::mem::take(&mut nesting_level.fragments_so_far);
+        let bidi_levels = std::mem::take(&mut nesting_level.bidi_levels_so_far);
+        reorder_fragments_for_bidi(&mut children, &bidi_levels, containing_block_writing_mode);
+        // Children are laid out along the inline axis of the inline formatting context,
+        // but their positions are relative to this box in its own writing mode.
+        let writing_mode = self.style.writing_mode;
+        if writing_mode.inline_start_physical_side() !=
+            containing_block_writing_mode.inline_start_physical_side()
+        {
+            flip_fragments_inline_axis(&mut children, content_rect.size.inline, writing_mode);
+        }
+
         let mut fragment = BoxFragment::new(
             self.tag,
             self.style.clone(),
-            std::mem::take(&mut nesting_level.fragments_so_far),
+            children,
+            containing_block_writing_mode,
             content_rect,
             self.padding.clone(),
             self.border.clone(),
@@ -533,6 +920,9 @@ impl<'box_tree> PartialInlineBoxFragment<'box_tree> {
             context.layout_collected_children(layout_context, &mut fragment);
         }
 
+        self.parent_nesting_level
+            .bidi_levels_so_far
+            .push(bidi_levels.into_iter().min().unwrap_or(self.bidi_level));
         self.parent_nesting_level
             .fragments_so_far
             .push(Fragment::Box(fragment));
@@ -572,6 +962,7 @@ fn layout_atomic(
                 replaced.tag,
                 replaced.style.clone(),
                 fragments,
+                ifc.containing_block.style.writing_mode,
                 content_rect,
                 pbm.padding,
                 pbm.border,
@@ -579,6 +970,36 @@ fn layout_atomic(
                 CollapsedBlockMargins::zero(),
             )
         },
+        IndependentFormattingContext::NonReplaced(non_replaced)
+            if non_replaced.style.writing_mode.is_horizontal() !=
+                ifc.containing_block.style.writing_mode.is_horizontal() =>
+        {
+            // https://drafts.csswg.org/css-writing-modes/#orthogonal-flows
+            let box_size = non_replaced
+                .style
+                .content_box_size(&ifc.containing_block, &pbm);
+            // FIXME is this correct?
+            let dummy_tree_rank = 0;
+            let (independent_layout, size) = non_replaced.layout_in_orthogonal_flow(
+                layout_context,
+                ifc.positioning_context,
+                ifc.containing_block,
+                &pbm,
+                &box_size,
+                dummy_tree_rank,
+            );
+            BoxFragment::new(
+                non_replaced.tag,
+                non_replaced.style.clone(),
+                independent_layout.fragments,
+                ifc.containing_block.style.writing_mode,
+                Rect { start_corner, size },
+                pbm.padding,
+                pbm.border,
+                margin,
+                CollapsedBlockMargins::zero(),
+            )
+        }
         IndependentFormattingContext::NonReplaced(non_replaced) => {
             let box_size = non_replaced
                 .style
@@ -610,11 +1031,6 @@ fn layout_atomic(
                 block_size: box_size.block,
                 style: &non_replaced.style,
             };
-            assert_eq!(
-                ifc.containing_block.style.writing_mode,
-                containing_block_for_children.style.writing_mode,
-                "Mixed writing modes are not supported yet"
-            );
             // FIXME is this correct?
             let dummy_tree_rank = 0;
             // FIXME: Do we need to call `adjust_static_positions` somewhere near here?
@@ -647,6 +1063,7 @@ fn layout_atomic(
                 non_replaced.tag,
                 non_replaced.style.clone(),
                 independent_layout.fragments,
+                ifc.containing_block.style.writing_mode,
                 content_rect,
                 pbm.padding,
                 pbm.border,
@@ -660,6 +1077,13 @@ fn layout_atomic(
     ifc.current_nesting_level
         .max_block_size_of_fragments_so_far
         .max_assign(pbm_sums.block_sum() + fragment.content_rect.size.block);
+    let bidi_level = match ifc.bidi_levels.as_mut() {
+        Some(bidi_levels) => bidi_levels.advance(OBJECT_REPLACEMENT_CHARACTER.len_utf8())[0],
+        None => bidi::Level::ltr(),
+    };
+    ifc.current_nesting_level
+        .bidi_levels_so_far
+        .push(bidi_level);
     ifc.current_nesting_level
         .fragments_so_far
         .push(Fragment::Box(fragment));
@@ -668,17 +1092,25 @@ fn layout_atomic(
 struct BreakAndShapeResult {
     font_metrics: FontMetrics,
     font_key: FontInstanceKey,
-    runs: Vec<GlyphRun>,
+    /// Glyph runs along with their bidi embedding level.
+    runs: Vec<(GlyphRun, bidi::Level)>,
     break_at_start: bool,
 }
 
 impl TextRun {
-    fn break_and_shape(&self, layout_context: &LayoutContext) -> BreakAndShapeResult {
+    /// `bidi_levels` has one level per byte of the text, or is `None` when it is all
+    /// left-to-right.
+    fn break_and_shape(
+        &self,
+        layout_context: &LayoutContext,
+        bidi_levels: Option<&[bidi::Level]>,
+    ) -> BreakAndShapeResult {
         use gfx::font::ShapingFlags;
         use style::computed_values::text_rendering::T as TextRendering;
         use style::computed_values::word_break::T as WordBreak;
 
         let font_style = self.parent_style.clone_font();
+        let hyphenation = Hyphenation::from_style(&self.parent_style);
         let inherited_text_style = self.parent_style.get_inherited_text();
         let letter_spacing = if inherited_text_style.letter_spacing.0.px() != 0. {
             Some(app_units::Au::from(inherited_text_style.letter_spacing.0))
@@ -729,8 +1161,69 @@ impl TextRun {
                 &mut font,
                 &self.text,
                 &shaping_options,
+                &hyphenation,
                 &mut None,
             );
+            let bidi_levels = match bidi_levels {
+                Some(bidi_levels) => bidi_levels,
+                None => {
+                    let runs = runs
+                        .into_iter()
+                        .map(|run| (run, bidi::Level::ltr()))
+                        .collect();
+                    return BreakAndShapeResult {
+                        font_metrics: (&font.metrics).into(),
+                        font_key: font.font_key,
+                        runs,
+                        break_at_start,
+                    };
+                },
+            };
+
+            // Split runs at bidi level changes, and shape right-to-left ones accordingly.
+            let mut runs_with_levels = Vec::with_capacity(runs.len());
+            for run in runs {
+                let start = run.range.begin().to_usize();
+                let end = run.range.end().to_usize();
+                if bidi_levels[start..end]
+                    .iter()
+                    .all(|level| *level == bidi_levels[start]) &&
+                    bidi_levels[start].is_ltr()
+                {
+                    runs_with_levels.push((run, bidi_levels[start]));
+                    continue;
+                }
+                let mut options = shaping_options.clone();
+                if run.glyph_store.is_whitespace() {
+                    options
+                        .flags
+                        .insert(ShapingFlags::IS_WHITESPACE_SHAPING_FLAG);
+                }
+                let mut sub_run_start = start;
+                while sub_run_start < end {
+                    let level = bidi_levels[sub_run_start];
+                    let sub_run_end = (sub_run_start..end)
+                        .find(|&index| bidi_levels[index] != level)
+                        .unwrap_or(end);
+                    options.flags.set(ShapingFlags::RTL_FLAG, level.is_rtl());
+                    let glyph_run = GlyphRun {
+                        glyph_store: font
+                            .shape_text(&self.text[sub_run_start..sub_run_end], &options),
+                        range: Range::new(
+                            ByteIndex(sub_run_start as isize),
+                            ByteIndex((sub_run_end - sub_run_start) as isize),
+                        ),
+                        hyphen: if sub_run_end == end {
+                            run.hyphen.clone()
+                        } else {
+                            None
+                        },
+                    };
+                    runs_with_levels.push((glyph_run, level));
+                    sub_run_start = sub_run_end;
+                }
+            }
+            let runs = runs_with_levels;
 
             BreakAndShapeResult {
                 font_metrics: (&font.metrics).into(),
@@ -749,7 +1242,13 @@ impl TextRun {
             font_key,
             runs,
             break_at_start: _,
-        } = self.break_and_shape(layout_context);
+        } = {
+            let bidi_levels = ifc
+                .bidi_levels
+                .as_mut()
+                .map(|bidi_levels| bidi_levels.advance(self.text.len()));
+            self.break_and_shape(layout_context, bidi_levels)
+        };
         let font_size = self.parent_style.get_font().font_size.size.0;
         let mut runs = runs.iter();
         loop {
@@ -757,31 +1256,61 @@ impl TextRun {
             let mut advance_width = Length::zero();
             let mut last_break_opportunity = None;
             let mut force_line_break = false;
+            // The hyphen ending the line, if it is broken at a hyphenation opportunity.
+            let mut line_hyphen = None;
+            // The hyphen of the previous run, if it ends at a hyphenation opportunity.
+            let mut previous_hyphen: Option<(StdArc<GlyphStore>, bidi::Level)> = None;
             // Fit as many glyphs within a single line as possible.
             loop {
+                let runs_before_next = runs.clone();
                 let next = runs.next();
                 // If there are no more text runs we still need to check if the last
                 // run was a forced line break
                 if next
                     .as_ref()
-                    .map_or(true, |run| run.glyph_store.is_whitespace())
+                    .map_or(true, |(run, _)| run.glyph_store.is_whitespace())
                 {
                     // If this run exceeds the bounds of the containing block, then
                     // we need to attempt to break the line.
-                    if advance_width > ifc.containing_block.inline_size - ifc.inline_position {
-                        // Reset the text run iterator to the last whitespace if possible,
+                    if advance_width > ifc.lines.current_line_inline_size - ifc.inline_position {
+                        // Reset the text run iterator to the last break opportunity if possible,
                         // to attempt to re-layout the most recent glyphs on a new line.
-                        if let Some((len, width, iter)) = last_break_opportunity.take() {
+                        if let Some((len, width, iter, hyphen)) = last_break_opportunity.take() {
+                            glyphs.truncate(len);
+                            advance_width = width;
+                            runs = iter;
+                            line_hyphen = hyphen;
+                        }
+                        break;
+                    }
+                } else if let Some((hyphen, level)) = previous_hyphen.take() {
+                    // Breaking the line here needs room for the hyphen too.
+                    let hyphen_width = Length::from(hyphen.total_advance());
+                    if advance_width + hyphen_width >
+                        ifc.lines.current_line_inline_size - ifc.inline_position
+                    {
+                        if let Some((len, width, iter, hyphen)) = last_break_opportunity.take() {
                             glyphs.truncate(len);
                             advance_width = width;
                             runs = iter;
+                            line_hyphen = hyphen;
+                        } else {
+                            runs = runs_before_next;
+                            line_hyphen = Some((hyphen, level));
                         }
                         break;
                     }
+                    last_break_opportunity = Some((
+                        glyphs.len(),
+                        advance_width,
+                        runs_before_next,
+                        Some((hyphen, level)),
+                    ));
                 }
-                if let Some(run) = next {
+                if let Some((run, level)) = next {
                     if run.glyph_store.is_whitespace() {
-                        last_break_opportunity = Some((glyphs.len(), advance_width, runs.clone()));
+                        last_break_opportunity =
+                            Some((glyphs.len(), advance_width, runs.clone(), None));
                         // If this whitespace ends with a newline, we need to check if
                         // it's meaningful within the current style. If so, we force
                         // a line break immediately.
@@ -796,54 +1325,86 @@ impl TextRun {
                             break;
                         }
                     }
-                    glyphs.push(run.glyph_store.clone());
+                    glyphs.push((run.glyph_store.clone(), *level));
                     advance_width += Length::from(run.glyph_store.total_advance());
+                    previous_hyphen = run.hyphen.clone().map(|hyphen| (hyphen, *level));
                 } else {
                     // No more runs, so we can end the line.
                     break;
                 }
             }
+            if let Some(hyphen) = line_hyphen {
+                glyphs.push(hyphen);
+            }
             let line_height = match self.parent_style.get_inherited_text().line_height {
                 LineHeight::Normal => font_metrics.line_gap,
                 LineHeight::Number(n) => font_size * n.0,
                 LineHeight::Length(l) => l.0,
             };
-            let rect = Rect {
-                start_corner: Vec2 {
-                    block: Length::zero(),
-                    inline: ifc.inline_position - ifc.current_nesting_level.inline_start,
-                },
-                size: Vec2 {
-                    block: line_height,
-                    inline: advance_width,
-                },
-            };
-            ifc.inline_position += advance_width;
+            // One fragment per sequence of glyphs with the same bidi level,
+            // so that they can be reordered at the end of the line.
+            let mut glyphs_by_level: Vec<(bidi::Level, Vec<StdArc<GlyphStore>>)> = Vec::new();
+            for (glyph_store, level) in glyphs {
+                if let Some((last_level, glyphs)) = glyphs_by_level.last_mut() {
+                    if *last_level == level {
+                        glyphs.push(glyph_store);
+                        continue;
+                    }
+                }
+                glyphs_by_level.push((level, vec![glyph_store]));
+            }
+            if glyphs_by_level.is_empty() {
+                glyphs_by_level.push((ifc.current_bidi_level(), Vec::new()));
+            }
+            for (level, mut glyphs) in glyphs_by_level {
+                // Right-to-left glyph stores are already in visual order internally,
+                // but the words themselves go from line-right to line-left.
+                if level.is_rtl() {
+                    glyphs.reverse();
+                }
+                let inline_size = glyphs.iter().fold(Length::zero(), |size, glyph_store| {
+                    size + Length::from(glyph_store.total_advance())
+                });
+                let rect = Rect {
+                    start_corner: Vec2 {
+                        block: Length::zero(),
+                        inline: ifc.inline_position - ifc.current_nesting_level.inline_start,
+                    },
+                    size: Vec2 {
+                        block: line_height,
+                        inline: inline_size,
+                    },
+                };
+                ifc.inline_position += inline_size;
+                ifc.current_nesting_level.bidi_levels_so_far.push(level);
+                ifc.current_nesting_level
+                    .fragments_so_far
+                    .push(Fragment::Text(TextFragment {
+                        tag: self.tag,
+                        debug_id: DebugId::new(),
+                        parent_style: self.parent_style.clone(),
+                        rect,
+                        font_metrics,
+                        font_key,
+                        glyphs,
+                        text_decoration_line: ifc.current_nesting_level.text_decoration_line,
+                    }));
+            }
             ifc.current_nesting_level
                 .max_block_size_of_fragments_so_far
                 .max_assign(line_height);
-            ifc.current_nesting_level
-                .fragments_so_far
-                .push(Fragment::Text(TextFragment {
-                    tag: self.tag,
-                    debug_id: DebugId::new(),
-                    parent_style: self.parent_style.clone(),
-                    rect,
-                    font_metrics,
-                    font_key,
-                    glyphs,
-                    text_decoration_line: ifc.current_nesting_level.text_decoration_line,
-                }));
             // If this line is being broken because of a trailing newline, we can't ignore it.
             if runs.as_slice().is_empty() && !force_line_break {
                 break;
             } else {
                 // New line
                 ifc.current_nesting_level.inline_start = Length::zero();
+                let containing_block_writing_mode = ifc.containing_block.style.writing_mode;
                 let mut nesting_level = &mut ifc.current_nesting_level;
                 for partial in ifc.partial_inline_boxes_stack.iter_mut().rev() {
                     partial.finish_layout(
                         layout_context,
+                        containing_block_writing_mode,
                         nesting_level,
                         &mut ifc.inline_position,
                         true,
@@ -858,6 +1419,7 @@ impl TextRun {
                 ifc.lines
                     .finish_line(nesting_level, ifc.containing_block, ifc.inline_position);
                 ifc.inline_position = Length::zero();
+                ifc.start_new_line();
             }
         }
     }
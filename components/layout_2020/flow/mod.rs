@@ -6,14 +6,14 @@
 
 use crate::cell::ArcRefCell;
 use crate::context::LayoutContext;
-use crate::flow::float::{FloatBox, FloatContext};
+use crate::flow::float::{ClearSide, ContainingBlockPositionInfo, FloatBox, SequentialLayoutState};
 use crate::flow::inline::InlineFormattingContext;
 use crate::formatting_contexts::{
     IndependentFormattingContext, IndependentLayout, NonReplacedFormattingContext,
 };
 use crate::fragments::{
-    AbsoluteOrFixedPositionedFragment, AnonymousFragment, BoxFragment, CollapsedBlockMargins,
-    CollapsedMargin, Fragment, Tag,
+    AbsoluteOrFixedPositionedFragment, BoxFragment, CollapsedBlockMargins, CollapsedMargin,
+    Fragment, Tag,
 };
 use crate::geom::flow_relative::{Rect, Sides, Vec2};
 use crate::positioned::{AbsolutelyPositionedBox, PositioningContext};
@@ -32,6 +32,7 @@ use style::Zero;
 mod construct;
 pub mod float;
 pub mod inline;
+pub mod multicol;
 mod root;
 
 pub use root::{BoxTree, FragmentTree};
@@ -78,10 +79,8 @@ impl BlockFormattingContext {
         containing_block: &ContainingBlock,
         tree_rank: usize,
     ) -> IndependentLayout {
-        let mut float_context;
-        let float_context = if self.contains_floats {
-            float_context = FloatContext::new();
-            Some(&mut float_context)
+        let mut sequential_layout_state = if self.contains_floats {
+            Some(SequentialLayoutState::new(containing_block.inline_size))
         } else {
             None
         };
@@ -90,7 +89,7 @@ impl BlockFormattingContext {
             positioning_context,
             containing_block,
             tree_rank,
-            float_context,
+            sequential_layout_state.as_mut(),
             CollapsibleWithParentStartMargin(false),
         );
         assert!(
@@ -98,10 +97,22 @@ impl BlockFormattingContext {
                 .collapsible_margins_in_children
                 .collapsed_through
         );
+
+        // “In addition, if the element has any floating descendants whose bottom margin edge
+        //  is below the element's bottom content edge, then the height is increased to include
+        //  those edges.”
+        // https://drafts.csswg.org/css2/#root-height
+        let floats_block_end = sequential_layout_state.map_or(Length::zero(), |state| {
+            state
+                .floats
+                .clear_left_position
+                .max(state.floats.clear_right_position)
+        });
         IndependentLayout {
             fragments: flow_layout.fragments,
-            content_block_size: flow_layout.content_block_size +
-                flow_layout.collapsible_margins_in_children.end.solve(),
+            content_block_size: (flow_layout.content_block_size +
+                flow_layout.collapsible_margins_in_children.end.solve())
+            .max(floats_block_end),
         }
     }
 }
@@ -113,7 +124,7 @@ impl BlockContainer {
         positioning_context: &mut PositioningContext,
         containing_block: &ContainingBlock,
         tree_rank: usize,
-        float_context: Option<&mut FloatContext>,
+        sequential_layout_state: Option<&mut SequentialLayoutState>,
         collapsible_with_parent_start_margin: CollapsibleWithParentStartMargin,
     ) -> FlowLayout {
         match self {
@@ -123,7 +134,7 @@ impl BlockContainer {
                 child_boxes,
                 containing_block,
                 tree_rank,
-                float_context,
+                sequential_layout_state,
                 collapsible_with_parent_start_margin,
             ),
             BlockContainer::InlineFormattingContext(ifc) => ifc.layout(
@@ -131,6 +142,7 @@ impl BlockContainer {
                 positioning_context,
                 containing_block,
                 tree_rank,
+                sequential_layout_state,
             ),
         }
     }
@@ -141,13 +153,43 @@ impl BlockContainer {
         writing_mode: WritingMode,
     ) -> ContentSizes {
         match &self {
-            Self::BlockLevelBoxes(boxes) => boxes
-                .par_iter()
-                .map(|box_| {
-                    box_.borrow_mut()
-                        .inline_content_sizes(layout_context, writing_mode)
-                })
-                .reduce(ContentSizes::zero, ContentSizes::max),
+            Self::BlockLevelBoxes(boxes) => {
+                let contributions = boxes
+                    .par_iter()
+                    .map(|box_| {
+                        let mut box_ = box_.borrow_mut();
+                        let float_clear = match &*box_ {
+                            BlockLevelBox::OutOfFlowFloatBox(float_box) => {
+                                Some(ClearSide::from_style(float_box.contents.style()))
+                            },
+                            _ => None,
+                        };
+                        (
+                            box_.inline_content_sizes(layout_context, writing_mode),
+                            float_clear,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+
+                // Floats are laid out side by side until one of them clears the previous ones,
+                // so their max-content contributions add up.
+                let mut sizes = ContentSizes::zero();
+                let mut floats = ContentSizes::zero();
+                for (contribution, float_clear) in contributions {
+                    match float_clear {
+                        Some(clear) => {
+                            if clear != ClearSide::None {
+                                sizes =
+                                    sizes.max(std::mem::replace(&mut floats, ContentSizes::zero()));
+                            }
+                            floats.min_content.max_assign(contribution.min_content);
+                            floats.max_content += contribution.max_content;
+                        },
+                        None => sizes = sizes.max(contribution),
+                    }
+                }
+                sizes.max(floats)
+            },
             Self::InlineFormattingContext(context) => {
                 context.inline_content_sizes(layout_context, writing_mode)
             },
@@ -161,18 +203,42 @@ fn layout_block_level_children(
     child_boxes: &[ArcRefCell<BlockLevelBox>],
     containing_block: &ContainingBlock,
     tree_rank: usize,
-    mut float_context: Option<&mut FloatContext>,
+    mut sequential_layout_state: Option<&mut SequentialLayoutState>,
     collapsible_with_parent_start_margin: CollapsibleWithParentStartMargin,
 ) -> FlowLayout {
-    fn place_block_level_fragment(fragment: &mut Fragment, placement_state: &mut PlacementState) {
+    fn place_block_level_fragment(
+        fragment: &mut Fragment,
+        placement_state: &mut PlacementState,
+        sequential_layout_state: Option<&mut SequentialLayoutState>,
+    ) {
         match fragment {
+            Fragment::Box(fragment) if fragment.style.get_box().float.is_floating() => {
+                // The hypothetical position of a float is where the next in-flow box would go.
+                if let Some(sequential_layout_state) = sequential_layout_state {
+                    let block_offset_from_containing_block_top = placement_state
+                        .current_block_direction_position +
+                        placement_state.current_margin.solve();
+                    sequential_layout_state.place_float_fragment(
+                        fragment,
+                        placement_state.start_margin,
+                        block_offset_from_containing_block_top,
+                    );
+                }
+            },
             Fragment::Box(fragment) => {
                 let fragment_block_margins = &fragment.block_margins_collapsed_with_children;
                 let fragment_block_size = fragment.padding.block_sum() +
                     fragment.border.block_sum() +
                     fragment.content_rect.size.block;
 
-                if placement_state.next_in_flow_margin_collapses_with_parent_start_margin {
+                if let Some(clearance) = fragment.clearance {
+                    // Clearance separates the block-start margin of the box from the preceding
+                    // margins, including those of its parent.
+                    placement_state.next_in_flow_margin_collapses_with_parent_start_margin = false;
+                    placement_state.current_block_direction_position +=
+                        placement_state.current_margin.solve() + clearance;
+                    placement_state.current_margin = fragment_block_margins.start;
+                } else if placement_state.next_in_flow_margin_collapses_with_parent_start_margin {
                     assert_eq!(placement_state.current_margin.solve(), Length::zero());
                     placement_state
                         .start_margin
@@ -232,10 +298,11 @@ fn layout_block_level_children(
         current_block_direction_position: Length::zero(),
     };
     let fragments = positioning_context.adjust_static_positions(tree_rank, |positioning_context| {
-        if float_context.is_some() || !layout_context.use_rayon {
+        if sequential_layout_state.is_some() || !layout_context.use_rayon {
             // Because floats are involved, we do layout for this block formatting context
             // in tree order without parallelism. This enables mutable access
-            // to a `FloatContext` that tracks every float encountered so far (again in tree order).
+            // to a `SequentialLayoutState` that tracks every float encountered so far
+            // (again in tree order).
             child_boxes
                 .iter()
                 .enumerate()
@@ -245,9 +312,13 @@ fn layout_block_level_children(
                         positioning_context,
                         containing_block,
                         tree_rank,
-                        float_context.as_mut().map(|c| &mut **c),
+                        sequential_layout_state.as_mut().map(|state| &mut **state),
+                    );
+                    place_block_level_fragment(
+                        &mut fragment,
+                        &mut placement_state,
+                        sequential_layout_state.as_mut().map(|state| &mut **state),
                     );
-                    place_block_level_fragment(&mut fragment, &mut placement_state);
                     fragment
                 })
                 .collect()
@@ -265,7 +336,7 @@ fn layout_block_level_children(
                             positioning_context,
                             containing_block,
                             tree_rank,
-                            /* float_context = */ None,
+                            /* sequential_layout_state = */ None,
                         )
                     },
                     || PositioningContext::new_for_rayon(collects_for_nearest_positioned_ancestor),
@@ -273,7 +344,7 @@ fn layout_block_level_children(
                 )
                 .collect();
             for fragment in &mut fragments {
-                place_block_level_fragment(fragment, &mut placement_state)
+                place_block_level_fragment(fragment, &mut placement_state, None)
             }
             fragments
         }
@@ -292,13 +363,13 @@ fn layout_block_level_children(
 }
 
 impl BlockLevelBox {
-    fn layout(
+    pub(crate) fn layout(
         &mut self,
         layout_context: &LayoutContext,
         positioning_context: &mut PositioningContext,
         containing_block: &ContainingBlock,
         tree_rank: usize,
-        float_context: Option<&mut FloatContext>,
+        sequential_layout_state: Option<&mut SequentialLayoutState>,
     ) -> Fragment {
         match self {
             BlockLevelBox::SameFormattingContextBlock {
@@ -318,7 +389,7 @@ impl BlockLevelBox {
                         style,
                         NonReplacedContents::SameFormattingContextBlock(contents),
                         tree_rank,
-                        float_context,
+                        sequential_layout_state,
                     )
                 },
             )),
@@ -334,6 +405,7 @@ impl BlockLevelBox {
                                 replaced.tag,
                                 &replaced.style,
                                 &replaced.contents,
+                                sequential_layout_state,
                             )
                         },
                     ))
@@ -344,6 +416,18 @@ impl BlockLevelBox {
                         containing_block,
                         &non_replaced.style,
                         |positioning_context| {
+                            if non_replaced.style.writing_mode.is_horizontal() !=
+                                containing_block.style.writing_mode.is_horizontal()
+                            {
+                                return layout_in_flow_orthogonal_block_level(
+                                    layout_context,
+                                    positioning_context,
+                                    containing_block,
+                                    non_replaced,
+                                    tree_rank,
+                                    sequential_layout_state,
+                                );
+                            }
                             layout_in_flow_non_replaced_block_level(
                                 layout_context,
                                 positioning_context,
@@ -354,7 +438,7 @@ impl BlockLevelBox {
                                     non_replaced,
                                 ),
                                 tree_rank,
-                                float_context,
+                                sequential_layout_state,
                             )
                         },
                     ))
@@ -377,16 +461,16 @@ impl BlockLevelBox {
                     position: box_.borrow().context.style().clone_position(),
                 })
             },
-            BlockLevelBox::OutOfFlowFloatBox(_box_) => {
-                // FIXME: call layout_maybe_position_relative_fragment here
-                Fragment::Anonymous(AnonymousFragment::no_op(
-                    containing_block.style.writing_mode,
-                ))
-            },
+            BlockLevelBox::OutOfFlowFloatBox(box_) => Fragment::Box(box_.layout(
+                layout_context,
+                positioning_context,
+                containing_block,
+                tree_rank,
+            )),
         }
     }
 
-    fn inline_content_sizes(
+    pub(crate) fn inline_content_sizes(
         &mut self,
         layout_context: &LayoutContext,
         containing_block_writing_mode: WritingMode,
@@ -400,10 +484,9 @@ impl BlockLevelBox {
             Self::Independent(independent) => independent
                 .outer_inline_content_sizes(layout_context, containing_block_writing_mode),
             BlockLevelBox::OutOfFlowAbsolutelyPositionedBox(_) => ContentSizes::zero(),
-            BlockLevelBox::OutOfFlowFloatBox(_box_) => {
-                // TODO: Actually implement that.
-                ContentSizes::zero()
-            },
+            BlockLevelBox::OutOfFlowFloatBox(box_) => box_
+                .contents
+                .outer_inline_content_sizes(layout_context, containing_block_writing_mode),
         }
     }
 }
@@ -423,7 +506,7 @@ fn layout_in_flow_non_replaced_block_level(
     style: &Arc<ComputedValues>,
     block_level_kind: NonReplacedContents,
     tree_rank: usize,
-    float_context: Option<&mut FloatContext>,
+    mut sequential_layout_state: Option<&mut SequentialLayoutState>,
 ) -> BoxFragment {
     let pbm = style.padding_border_margin(containing_block);
     let box_size = style.content_box_size(containing_block, &pbm);
@@ -436,17 +519,42 @@ fn layout_in_flow_non_replaced_block_level(
     let solve_inline_margins = |inline_size| {
         solve_inline_margins_for_in_flow_block_level(containing_block, &pbm, inline_size)
     };
+
+    // Tables shrink to fit their contents, and are never narrower than them.
+    // https://drafts.csswg.org/css2/tables.html#auto-table-layout
+    let table_content_sizes = match &block_level_kind {
+        NonReplacedContents::EstablishesAnIndependentFormattingContext(non_replaced)
+            if non_replaced.is_table() =>
+        {
+            Some(
+                non_replaced
+                    .contents
+                    .inline_content_sizes(layout_context, style.writing_mode),
+            )
+        },
+        _ => None,
+    };
+
     let (mut inline_size, mut inline_margins) =
         if let Some(inline_size) = box_size.inline.non_auto() {
             (inline_size, solve_inline_margins(inline_size))
         } else {
             let margin_inline_start = pbm.margin.inline_start.auto_is(Length::zero);
             let margin_inline_end = pbm.margin.inline_end.auto_is(Length::zero);
-            let inline_size = containing_block.inline_size -
+            let available_inline_size = containing_block.inline_size -
                 pbm.padding_border_sums.inline -
                 margin_inline_start -
                 margin_inline_end;
-            (inline_size, (margin_inline_start, margin_inline_end))
+            match &table_content_sizes {
+                Some(content_sizes) => {
+                    let inline_size = content_sizes.shrink_to_fit(available_inline_size);
+                    (inline_size, solve_inline_margins(inline_size))
+                },
+                None => (
+                    available_inline_size,
+                    (margin_inline_start, margin_inline_end),
+                ),
+            }
         };
     if let Some(max_inline_size) = max_box_size.inline {
         if inline_size > max_inline_size {
@@ -458,6 +566,12 @@ fn layout_in_flow_non_replaced_block_level(
         inline_size = min_box_size.inline;
         inline_margins = solve_inline_margins(inline_size);
     }
+    if let Some(content_sizes) = &table_content_sizes {
+        if inline_size < content_sizes.min_content {
+            inline_size = content_sizes.min_content;
+            inline_margins = solve_inline_margins(inline_size);
+        }
+    }
 
     let margin = Sides {
         inline_start: inline_margins.0,
@@ -477,31 +591,70 @@ fn layout_in_flow_non_replaced_block_level(
         block_size,
         style,
     };
+    // Orthogonal flows are laid out by `layout_in_flow_orthogonal_block_level`,
+    // only the inline direction can differ here.
     // https://drafts.csswg.org/css-writing-modes/#orthogonal-flows
-    assert_eq!(
-        containing_block.style.writing_mode, containing_block_for_children.style.writing_mode,
-        "Mixed writing modes are not supported yet"
+    debug_assert_eq!(
+        containing_block.style.writing_mode.is_horizontal(),
+        containing_block_for_children
+            .style
+            .writing_mode
+            .is_horizontal(),
     );
 
     let mut block_margins_collapsed_with_children = CollapsedBlockMargins::from_margin(&margin);
 
+    let start_margin_can_collapse_with_children =
+        pbm.padding.block_start == Length::zero() && pbm.border.block_start == Length::zero();
+    let end_margin_can_collapse_with_children = pbm.padding.block_end == Length::zero() &&
+        pbm.border.block_end == Length::zero() &&
+        block_size == LengthOrAuto::Auto &&
+        min_box_size.block == Length::zero();
+
+    let mut clearance = None;
+    let mut parent_containing_block_position_info = None;
     let fragments;
     let mut content_block_size;
     match block_level_kind {
         NonReplacedContents::SameFormattingContextBlock(contents) => {
-            let start_margin_can_collapse_with_children = pbm.padding.block_start == Length::zero() &&
-                pbm.border.block_start == Length::zero();
-            let end_margin_can_collapse_with_children = pbm.padding.block_end == Length::zero() &&
-                pbm.border.block_end == Length::zero() &&
-                block_size == LengthOrAuto::Auto &&
-                min_box_size.block == Length::zero();
+            if let Some(ref mut sequential_layout_state) = sequential_layout_state {
+                let block_start_margin = CollapsedMargin::new(margin.block_start);
+                clearance = sequential_layout_state
+                    .calculate_clearance(ClearSide::from_style(style), &block_start_margin);
+                if let Some(clearance) = clearance {
+                    sequential_layout_state.collapse_margins();
+                    sequential_layout_state.advance_block_position(clearance);
+                }
+                sequential_layout_state.adjoin_assign(&block_start_margin);
+                if !start_margin_can_collapse_with_children {
+                    sequential_layout_state.collapse_margins();
+                }
+                sequential_layout_state
+                    .advance_block_position(pbm.padding.block_start + pbm.border.block_start);
+
+                // Floats inside this box are positioned relative to its content box.
+                let inline_start = sequential_layout_state.containing_block_info.inline_start +
+                    margin.inline_start +
+                    pbm.border.inline_start +
+                    pbm.padding.inline_start;
+                let containing_block_info = ContainingBlockPositionInfo {
+                    block_start: sequential_layout_state.bfc_relative_block_position,
+                    block_start_margins_not_collapsed: sequential_layout_state.current_margin,
+                    inline_start,
+                    inline_end: inline_start + inline_size,
+                };
+                parent_containing_block_position_info = Some(
+                    sequential_layout_state
+                        .replace_containing_block_position_info(containing_block_info),
+                );
+            }
 
             let flow_layout = contents.layout(
                 layout_context,
                 positioning_context,
                 &containing_block_for_children,
                 tree_rank,
-                float_context,
+                sequential_layout_state.as_mut().map(|state| &mut **state),
                 CollapsibleWithParentStartMargin(start_margin_can_collapse_with_children),
             );
             fragments = flow_layout.fragments;
@@ -557,16 +710,36 @@ fn layout_in_flow_non_replaced_block_level(
             inline: inline_size,
         },
     };
-    BoxFragment::new(
+    let mut fragment = BoxFragment::new(
         tag,
         style.clone(),
         fragments,
+        containing_block.style.writing_mode,
         content_rect,
         pbm.padding,
         pbm.border,
         margin,
         block_margins_collapsed_with_children,
-    )
+    );
+
+    if let Some(sequential_layout_state) = sequential_layout_state {
+        match parent_containing_block_position_info {
+            Some(parent_containing_block_position_info) => {
+                sequential_layout_state
+                    .replace_containing_block_position_info(parent_containing_block_position_info);
+                if !end_margin_can_collapse_with_children {
+                    sequential_layout_state.collapse_margins();
+                }
+                sequential_layout_state.advance_block_position(
+                    block_size - content_block_size + pbm.padding.block_end + pbm.border.block_end,
+                );
+                sequential_layout_state.adjoin_assign(&CollapsedMargin::new(margin.block_end));
+                fragment.clearance = clearance;
+            },
+            None => place_block_level_box_among_floats(&mut fragment, sequential_layout_state),
+        }
+    }
+    fragment
 }
 
 /// https://drafts.csswg.org/css2/visudet.html#block-replaced-width
@@ -577,6 +750,7 @@ fn layout_in_flow_replaced_block_level<'a>(
     tag: Tag,
     style: &Arc<ComputedValues>,
     replaced: &ReplacedContent,
+    sequential_layout_state: Option<&mut SequentialLayoutState>,
 ) -> BoxFragment {
     let pbm = style.padding_border_margin(containing_block);
     let size = replaced.used_size_as_if_inline_element(containing_block, style, &pbm);
@@ -598,16 +772,140 @@ fn layout_in_flow_replaced_block_level<'a>(
         size,
     };
     let block_margins_collapsed_with_children = CollapsedBlockMargins::from_margin(&margin);
-    BoxFragment::new(
+    let mut fragment = BoxFragment::new(
         tag,
         style.clone(),
         fragments,
+        containing_block.style.writing_mode,
         content_rect,
         pbm.padding,
         pbm.border,
         margin,
         block_margins_collapsed_with_children,
-    )
+    );
+    if let Some(sequential_layout_state) = sequential_layout_state {
+        place_block_level_box_among_floats(&mut fragment, sequential_layout_state);
+    }
+    fragment
+}
+
+/// Lays out a block-level box whose writing mode is orthogonal to the one of its containing
+/// block. Such a box always establishes an independent formatting context.
+/// https://drafts.csswg.org/css-writing-modes/#orthogonal-flows
+fn layout_in_flow_orthogonal_block_level(
+    layout_context: &LayoutContext,
+    positioning_context: &mut PositioningContext,
+    containing_block: &ContainingBlock,
+    non_replaced: &NonReplacedFormattingContext,
+    tree_rank: usize,
+    sequential_layout_state: Option<&mut SequentialLayoutState>,
+) -> BoxFragment {
+    let style = &non_replaced.style;
+    let pbm = style.padding_border_margin(containing_block);
+    let box_size = style.content_box_size(containing_block, &pbm);
+    let (independent_layout, size) = non_replaced.layout_in_orthogonal_flow(
+        layout_context,
+        positioning_context,
+        containing_block,
+        &pbm,
+        &box_size,
+        tree_rank,
+    );
+
+    let (margin_inline_start, margin_inline_end) =
+        solve_inline_margins_for_in_flow_block_level(containing_block, &pbm, size.inline);
+    let margin = Sides {
+        inline_start: margin_inline_start,
+        inline_end: margin_inline_end,
+        block_start: pbm.margin.block_start.auto_is(Length::zero),
+        block_end: pbm.margin.block_end.auto_is(Length::zero),
+    };
+    let content_rect = Rect {
+        start_corner: Vec2 {
+            block: pbm.padding.block_start + pbm.border.block_start,
+            inline: pbm.padding.inline_start + pbm.border.inline_start + margin.inline_start,
+        },
+        size,
+    };
+    let block_margins_collapsed_with_children = CollapsedBlockMargins::from_margin(&margin);
+    let mut fragment = BoxFragment::new(
+        non_replaced.tag,
+        style.clone(),
+        independent_layout.fragments,
+        containing_block.style.writing_mode,
+        content_rect,
+        pbm.padding,
+        pbm.border,
+        margin,
+        block_margins_collapsed_with_children,
+    );
+    if let Some(sequential_layout_state) = sequential_layout_state {
+        place_block_level_box_among_floats(&mut fragment, sequential_layout_state);
+    }
+    fragment
+}
+
+/// Moves a box that establishes an independent formatting context (or a replaced box) down
+/// and along the inline axis so that its border box does not overlap the margin box of any
+/// float, then advances the sequential layout state past it.
+/// https://drafts.csswg.org/css2/#floats
+fn place_block_level_box_among_floats(
+    fragment: &mut BoxFragment,
+    sequential_layout_state: &mut SequentialLayoutState,
+) {
+    let block_start_margin = CollapsedMargin::new(fragment.margin.block_start);
+    let mut clearance = sequential_layout_state
+        .calculate_clearance(ClearSide::from_style(&fragment.style), &block_start_margin);
+    let border_box_size = Vec2 {
+        inline: fragment.content_rect.size.inline +
+            fragment.padding.inline_sum() +
+            fragment.border.inline_sum(),
+        block: fragment.content_rect.size.block +
+            fragment.padding.block_sum() +
+            fragment.border.block_sum(),
+    };
+
+    let containing_block_info = &sequential_layout_state.containing_block_info;
+    let hypothetical_block_position =
+        sequential_layout_state.position_with_clearance(clearance, &block_start_margin);
+    let (block_position, available_inline_range) =
+        sequential_layout_state.floats.place_object_beside_floats(
+            &border_box_size,
+            hypothetical_block_position,
+            containing_block_info.inline_start,
+            containing_block_info.inline_end,
+        );
+    if block_position > hypothetical_block_position {
+        clearance = Some(
+            block_position -
+                sequential_layout_state.bfc_relative_block_position -
+                sequential_layout_state.current_margin.solve() -
+                block_start_margin.solve(),
+        );
+    }
+
+    // Keep the box where it is if it already avoids the floats, otherwise move it to the
+    // start of the space left beside them.
+    let border_box_inline_start = containing_block_info.inline_start +
+        fragment.content_rect.start_corner.inline -
+        fragment.padding.inline_start -
+        fragment.border.inline_start;
+    if border_box_inline_start < available_inline_range.start ||
+        border_box_inline_start + border_box_size.inline > available_inline_range.end
+    {
+        fragment.content_rect.start_corner.inline +=
+            available_inline_range.start - border_box_inline_start;
+    }
+
+    if let Some(clearance) = clearance {
+        sequential_layout_state.collapse_margins();
+        sequential_layout_state.advance_block_position(clearance);
+    }
+    sequential_layout_state.adjoin_assign(&block_start_margin);
+    sequential_layout_state.collapse_margins();
+    sequential_layout_state.advance_block_position(border_box_size.block);
+    sequential_layout_state.adjoin_assign(&CollapsedMargin::new(fragment.margin.block_end));
+    fragment.clearance = clearance;
 }
 
 fn solve_inline_margins_for_in_flow_block_level(
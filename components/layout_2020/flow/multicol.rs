@@ -0,0 +1,344 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Multi-column layout.
+//! https://drafts.csswg.org/css-multicol/
+
+use crate::context::LayoutContext;
+use crate::flow::float::SequentialLayoutState;
+use crate::flow::{
+    layout_block_level_children, BlockContainer, BlockFormattingContext, BlockLevelBox,
+    CollapsibleWithParentStartMargin, FlowLayout,
+};
+use crate::formatting_contexts::IndependentLayout;
+use crate::fragmentation::{self, FragmentationContextKind, Fragmenter};
+use crate::fragments::{AnonymousFragment, Fragment};
+use crate::geom::flow_relative::{Rect, Vec2};
+use crate::geom::LengthOrAuto;
+use crate::positioned::PositioningContext;
+use crate::sizing::ContentSizes;
+use crate::ContainingBlock;
+use servo_arc::Arc;
+use style::computed_values::column_fill::T as ColumnFill;
+use style::computed_values::column_span::T as ColumnSpan;
+use style::logical_geometry::WritingMode;
+use style::properties::ComputedValues;
+use style::values::computed::{ColumnCount, Length};
+use style::values::generics::length::GenericLengthPercentageOrNormal as LengthPercentageOrNormal;
+use style::Zero;
+
+#[derive(Debug, Serialize)]
+pub(crate) struct MulticolContainer {
+    #[serde(skip_serializing)]
+    style: Arc<ComputedValues>,
+    contents: BlockFormattingContext,
+}
+
+/// The used count, width and gap of the columns of a multi-column container.
+/// https://drafts.csswg.org/css-multicol/#pseudo-algorithm
+struct ColumnGeometry {
+    count: usize,
+    width: Length,
+    gap: Length,
+}
+
+impl MulticolContainer {
+    pub fn new(style: &Arc<ComputedValues>, contents: BlockFormattingContext) -> Self {
+        Self {
+            style: style.clone(),
+            contents,
+        }
+    }
+
+    /// https://drafts.csswg.org/css-multicol/#intrinsic-sizing
+    pub fn inline_content_sizes(
+        &self,
+        layout_context: &LayoutContext,
+        writing_mode: WritingMode,
+    ) -> ContentSizes {
+        let contents = self
+            .contents
+            .contents
+            .inline_content_sizes(layout_context, writing_mode);
+        let column = self.style.get_column();
+        let count = match column.column_count {
+            ColumnCount::Integer(count) => count.0.max(1) as f32,
+            ColumnCount::Auto => 1.,
+        };
+        let gaps = column_gap(&self.style, Length::zero()) * (count - 1.);
+        match column.column_width.non_auto() {
+            // “The min-content inline size of a multi-column container with a computed
+            //  column-width not auto is the smaller of its column-width and the largest
+            //  min-content contribution of its contents.”
+            Some(width) => ContentSizes {
+                min_content: contents.min_content.min(width.0),
+                max_content: contents.max_content.max(width.0) * count + gaps,
+            },
+            None => ContentSizes {
+                min_content: contents.min_content * count + gaps,
+                max_content: contents.max_content * count + gaps,
+            },
+        }
+    }
+
+    pub(crate) fn layout(
+        &self,
+        layout_context: &LayoutContext,
+        positioning_context: &mut PositioningContext,
+        containing_block: &ContainingBlock,
+        tree_rank: usize,
+    ) -> IndependentLayout {
+        let columns = ColumnGeometry::new(&self.style, containing_block.inline_size);
+        let mut rows = ColumnRows {
+            container: self,
+            containing_block,
+            columns,
+            fragments: Vec::new(),
+            block_position: Length::zero(),
+        };
+
+        match &self.contents.contents {
+            // Spanners split the content of the multi-column container into rows of columns.
+            // Only the in-flow block-level children can be spanners.
+            // https://drafts.csswg.org/css-multicol/#column-span
+            BlockContainer::BlockLevelBoxes(child_boxes) => {
+                let mut row_start = 0;
+                for (index, child) in child_boxes.iter().enumerate() {
+                    if !is_column_spanner(&child.borrow()) {
+                        continue;
+                    }
+                    let row = &child_boxes[row_start..index];
+                    rows.layout_row(/* is_last = */ false, |containing_block, state| {
+                        layout_block_level_children(
+                            layout_context,
+                            positioning_context,
+                            row,
+                            containing_block,
+                            tree_rank,
+                            state,
+                            CollapsibleWithParentStartMargin(false),
+                        )
+                    });
+                    let spanner = &child_boxes[index..index + 1];
+                    rows.layout_spanner(|containing_block, state| {
+                        layout_block_level_children(
+                            layout_context,
+                            positioning_context,
+                            spanner,
+                            containing_block,
+                            tree_rank,
+                            state,
+                            CollapsibleWithParentStartMargin(false),
+                        )
+                    });
+                    row_start = index + 1;
+                }
+                let row = &child_boxes[row_start..];
+                rows.layout_row(/* is_last = */ true, |containing_block, state| {
+                    layout_block_level_children(
+                        layout_context,
+                        positioning_context,
+                        row,
+                        containing_block,
+                        tree_rank,
+                        state,
+                        CollapsibleWithParentStartMargin(false),
+                    )
+                });
+            },
+            BlockContainer::InlineFormattingContext(_) => {
+                rows.layout_row(/* is_last = */ true, |containing_block, state| {
+                    self.contents.contents.layout(
+                        layout_context,
+                        positioning_context,
+                        containing_block,
+                        tree_rank,
+                        state,
+                        CollapsibleWithParentStartMargin(false),
+                    )
+                });
+            },
+        }
+
+        IndependentLayout {
+            fragments: rows.fragments,
+            content_block_size: rows.block_position,
+        }
+    }
+}
+
+/// The state of the layout of a multi-column container,
+/// whose content is laid out in rows of columns separated by spanners.
+struct ColumnRows<'a> {
+    container: &'a MulticolContainer,
+    containing_block: &'a ContainingBlock<'a>,
+    columns: ColumnGeometry,
+    fragments: Vec<Fragment>,
+    block_position: Length,
+}
+
+impl ColumnRows<'_> {
+    /// Lays out a flow as if it was in a single column, and splits it into columns.
+    fn layout_row(
+        &mut self,
+        is_last: bool,
+        layout_flow: impl FnOnce(&ContainingBlock, Option<&mut SequentialLayoutState>) -> FlowLayout,
+    ) {
+        let container = self.container;
+        let style = &container.style;
+        let containing_block_for_columns = ContainingBlock {
+            inline_size: self.columns.width,
+            block_size: LengthOrAuto::Auto,
+            style,
+        };
+        let (flow_layout, flow_block_size) =
+            self.layout_flow(&containing_block_for_columns, layout_flow);
+        if flow_layout.fragments.is_empty() {
+            return;
+        }
+
+        // “In continuous media, this property does not have any effect in overflow columns”,
+        // and rows followed by a spanner are always balanced.
+        // https://drafts.csswg.org/css-multicol/#cf
+        let available_block_size = self
+            .containing_block
+            .block_size
+            .non_auto()
+            .map(|block_size| (block_size - self.block_position).max(Length::zero()));
+        let column_block_size = match available_block_size {
+            Some(available_block_size)
+                if is_last && style.get_column().column_fill == ColumnFill::Auto =>
+            {
+                available_block_size
+            },
+            _ => fragmentation::balanced_fragmentainer_block_size(
+                FragmentationContextKind::Column,
+                style.writing_mode,
+                &flow_layout.fragments,
+                self.columns.count,
+                flow_block_size,
+                available_block_size,
+            ),
+        };
+
+        // Columns that do not fit in the row are overflow columns,
+        // which continue in the inline direction.
+        let fragmenter = Fragmenter {
+            kind: FragmentationContextKind::Column,
+            writing_mode: style.writing_mode,
+            fragmentainer_block_size: column_block_size,
+        };
+        let starts = fragmenter.fragmentainer_starts(&flow_layout.fragments);
+        let columns = fragmenter.fragment(flow_layout.fragments, &starts);
+        for (index, children) in columns.into_iter().enumerate() {
+            let rect = Rect {
+                start_corner: Vec2 {
+                    inline: (self.columns.width + self.columns.gap) * index as f32,
+                    block: self.block_position,
+                },
+                size: Vec2 {
+                    inline: self.columns.width,
+                    block: column_block_size,
+                },
+            };
+            self.fragments
+                .push(Fragment::Anonymous(AnonymousFragment::new(
+                    rect,
+                    children,
+                    style.writing_mode,
+                )));
+        }
+        self.block_position += column_block_size;
+    }
+
+    /// Lays out a spanner, which takes the whole inline size of the container.
+    fn layout_spanner(
+        &mut self,
+        layout_flow: impl FnOnce(&ContainingBlock, Option<&mut SequentialLayoutState>) -> FlowLayout,
+    ) {
+        let containing_block = ContainingBlock {
+            inline_size: self.containing_block.inline_size,
+            block_size: self.containing_block.block_size,
+            style: &self.container.style,
+        };
+        let (flow_layout, flow_block_size) = self.layout_flow(&containing_block, layout_flow);
+        for mut fragment in flow_layout.fragments {
+            fragment.offset_block(&self.block_position);
+            self.fragments.push(fragment);
+        }
+        self.block_position += flow_block_size;
+    }
+
+    /// Lays out a flow in its own block formatting context, and returns its block size.
+    fn layout_flow(
+        &self,
+        containing_block: &ContainingBlock,
+        layout_flow: impl FnOnce(&ContainingBlock, Option<&mut SequentialLayoutState>) -> FlowLayout,
+    ) -> (FlowLayout, Length) {
+        let mut sequential_layout_state = if self.container.contents.contains_floats {
+            Some(SequentialLayoutState::new(containing_block.inline_size))
+        } else {
+            None
+        };
+        let flow_layout = layout_flow(containing_block, sequential_layout_state.as_mut());
+        let floats_block_end = sequential_layout_state.map_or(Length::zero(), |state| {
+            state
+                .floats
+                .clear_left_position
+                .max(state.floats.clear_right_position)
+        });
+        let block_size = (flow_layout.content_block_size +
+            flow_layout.collapsible_margins_in_children.end.solve())
+        .max(floats_block_end);
+        (flow_layout, block_size)
+    }
+}
+
+impl ColumnGeometry {
+    fn new(style: &ComputedValues, available_inline_size: Length) -> Self {
+        let gap = column_gap(style, available_inline_size);
+        let column = style.get_column();
+        let count = match column.column_count {
+            ColumnCount::Integer(count) => Some(count.0.max(1) as usize),
+            ColumnCount::Auto => None,
+        };
+        let count = match column.column_width.non_auto() {
+            Some(width) => {
+                let pitch = width.0 + gap;
+                let fitting_count = if pitch > Length::zero() {
+                    ((available_inline_size + gap).px() / pitch.px())
+                        .floor()
+                        .max(1.) as usize
+                } else {
+                    count.unwrap_or(1)
+                };
+                count.map_or(fitting_count, |count| count.min(fitting_count))
+            },
+            None => count.unwrap_or(1),
+        };
+        let width = ((available_inline_size + gap) / count as f32 - gap).max(Length::zero());
+        Self { count, width, gap }
+    }
+}
+
+/// The used value of `column-gap`, where `normal` is `1em` in multi-column containers.
+/// https://drafts.csswg.org/css-multicol/#column-gap
+fn column_gap(style: &ComputedValues, percentage_basis: Length) -> Length {
+    match &style.get_position().column_gap {
+        LengthPercentageOrNormal::Normal => style.get_font().font_size.size(),
+        LengthPercentageOrNormal::LengthPercentage(length) => {
+            length.0.percentage_relative_to(percentage_basis)
+        },
+    }
+}
+
+fn is_column_spanner(box_: &BlockLevelBox) -> bool {
+    let style = match box_ {
+        BlockLevelBox::SameFormattingContextBlock { style, .. } => style,
+        BlockLevelBox::Independent(context) => context.style(),
+        BlockLevelBox::OutOfFlowAbsolutelyPositionedBox(_) |
+        BlockLevelBox::OutOfFlowFloatBox(_) => return false,
+    };
+    style.get_column().column_span == ColumnSpan::All
+}
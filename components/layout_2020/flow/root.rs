@@ -17,8 +17,10 @@ use crate::flow::inline::InlineLevelBox;
 use crate::flow::{BlockContainer, BlockFormattingContext, BlockLevelBox};
 use crate::formatting_contexts::IndependentFormattingContext;
 use crate::fragments::{Fragment, Tag};
-use crate::geom::flow_relative::Vec2;
+use crate::geom::flow_relative::{self, Vec2};
 use crate::geom::{PhysicalPoint, PhysicalRect, PhysicalSize};
+use crate::grid::GridLevelBox;
+use crate::opaque_node::OpaqueNodeMethods;
 use crate::positioned::AbsolutelyPositionedBox;
 use crate::positioned::PositioningContext;
 use crate::replaced::ReplacedContent;
@@ -31,14 +33,21 @@ use atomic_refcell::AtomicRef;
 use euclid::default::{Point2D, Rect, Size2D};
 use fxhash::FxHashSet;
 use gfx_traits::print_tree::PrintTree;
+use gfx_traits::ByteIndex;
+use range::Range;
+use script_layout_interface::rpc::OffsetParentResponse;
 use script_layout_interface::wrapper_traits::LayoutNode;
 use script_layout_interface::{LayoutElementType, LayoutNodeType};
 use servo_arc::Arc;
 use style::animation::AnimationSetKey;
+use style::computed_values::position::T as ComputedPosition;
 use style::dom::OpaqueNode;
+use style::logical_geometry::PhysicalCorner;
 use style::properties::ComputedValues;
 use style::selector_parser::PseudoElement;
 use style::values::computed::Length;
+use style::values::specified::box_::DisplayInside as StyloDisplayInside;
+use style::Zero;
 use style_traits::CSSPixel;
 
 #[derive(Serialize)]
@@ -61,7 +70,7 @@ pub struct FragmentTree {
     /// * The first fragment is generated by the root element.
     /// * There may be additional fragments generated by positioned boxes
     ///   that have the initial containing block.
-    root_fragments: Vec<ArcRefCell<Fragment>>,
+    pub(crate) root_fragments: Vec<ArcRefCell<Fragment>>,
 
     /// The scrollable overflow rectangle for the entire tree
     /// https://drafts.csswg.org/css-overflow/#scrollable
@@ -122,6 +131,7 @@ impl BoxTree {
             AbsolutelyPositionedBlockLevelBox(ArcRefCell<BlockLevelBox>),
             AbsolutelyPositionedInlineLevelBox(ArcRefCell<InlineLevelBox>),
             AbsolutelyPositionedFlexLevelBox(ArcRefCell<FlexLevelBox>),
+            AbsolutelyPositionedGridLevelBox(ArcRefCell<GridLevelBox>),
         }
 
         fn update_point<'dom, Node>(
@@ -172,7 +182,7 @@ impl BoxTree {
 
             let update_point =
                 match &*AtomicRef::filter_map(layout_data.self_box.borrow(), Option::as_ref)? {
-                    LayoutBox::DisplayContents => return None,
+                    LayoutBox::DisplayContents | LayoutBox::TableLevel => return None,
                     LayoutBox::BlockLevel(block_level_box) => match &*block_level_box.borrow() {
                         BlockLevelBox::OutOfFlowAbsolutelyPositionedBox(_)
                             if box_style.position.is_absolutely_positioned() =>
@@ -199,6 +209,14 @@ impl BoxTree {
                         },
                         _ => return None,
                     },
+                    LayoutBox::GridLevel(grid_level_box) => match &*grid_level_box.borrow() {
+                        GridLevelBox::OutOfFlowAbsolutelyPositionedBox(_)
+                            if box_style.position.is_absolutely_positioned() =>
+                        {
+                            UpdatePoint::AbsolutelyPositionedGridLevelBox(grid_level_box.clone())
+                        },
+                        _ => return None,
+                    },
                 };
             Some((primary_style.clone(), display_inside, update_point))
         }
@@ -230,6 +248,12 @@ impl BoxTree {
                                 out_of_flow_absolutely_positioned_box,
                             );
                     },
+                    UpdatePoint::AbsolutelyPositionedGridLevelBox(grid_level_box) => {
+                        *grid_level_box.borrow_mut() =
+                            GridLevelBox::OutOfFlowAbsolutelyPositionedBox(
+                                out_of_flow_absolutely_positioned_box,
+                            );
+                    },
                 }
                 return true;
             }
@@ -261,6 +285,8 @@ fn construct_for_root_element<'dom>(
         },
         // The root element is blockified, ignore DisplayOutside
         Display::GeneratingBox(DisplayGeneratingBox::OutsideInside { inside, .. }) => inside,
+        // Unreachable because blockification turns internal table displays into 'block'.
+        Display::GeneratingBox(DisplayGeneratingBox::LayoutInternal(_)) => unreachable!(),
     };
 
     let contents =
@@ -308,19 +334,21 @@ impl BoxTree {
         layout_context: &LayoutContext,
         viewport: euclid::Size2D<f32, CSSPixel>,
     ) -> FragmentTree {
-        let style = ComputedValues::initial_values();
-
-        // FIXME: use the document’s mode:
+        // The initial containing block takes the writing mode of the root element.
+        // FIXME: the `writing-mode` of the `body` element should be propagated too:
         // https://drafts.csswg.org/css-writing-modes/#principal-flow
+        let root_style = self.root_style();
+        let style = match &root_style {
+            Some(style) => &**style,
+            None => ComputedValues::initial_values(),
+        };
+
         let physical_containing_block = PhysicalRect::new(
             PhysicalPoint::zero(),
             PhysicalSize::new(Length::new(viewport.width), Length::new(viewport.height)),
         );
         let initial_containing_block = DefiniteContainingBlock {
-            size: Vec2 {
-                inline: physical_containing_block.size.width,
-                block: physical_containing_block.size.height,
-            },
+            size: Vec2::from_physical_size(&physical_containing_block.size, style.writing_mode),
             style,
         };
 
@@ -382,13 +410,33 @@ impl BoxTree {
             canvas_background: self.canvas_background.clone(),
         }
     }
+
+    /// The style of the box of the root element, if any.
+    pub(crate) fn root_style(&self) -> Option<Arc<ComputedValues>> {
+        match &self.root.contents {
+            BlockContainer::BlockLevelBoxes(boxes) => {
+                boxes.first().map(|root_box| match &*root_box.borrow() {
+                    BlockLevelBox::SameFormattingContextBlock { style, .. } => style.clone(),
+                    BlockLevelBox::OutOfFlowAbsolutelyPositionedBox(box_) => {
+                        box_.borrow().context.style().clone()
+                    },
+                    BlockLevelBox::OutOfFlowFloatBox(float_box) => {
+                        float_box.contents.style().clone()
+                    },
+                    BlockLevelBox::Independent(context) => context.style().clone(),
+                })
+            },
+            BlockContainer::InlineFormattingContext(_) => None,
+        }
+    }
 }
 
 impl FragmentTree {
     pub fn build_display_list(&self, builder: &mut crate::display_list::DisplayListBuilder) {
         let mut stacking_context = StackingContext::create_root(&builder.wr);
         {
-            let mut stacking_context_builder = StackingContextBuilder::new(&mut builder.wr);
+            let mut stacking_context_builder =
+                StackingContextBuilder::new(&mut builder.wr, &self.initial_containing_block);
             let containing_block_info = ContainingBlockInfo {
                 rect: self.initial_containing_block,
                 nearest_containing_block: None,
@@ -469,7 +517,7 @@ impl FragmentTree {
             let fragment_relative_rect = match fragment {
                 Fragment::Box(fragment) => fragment
                     .border_rect()
-                    .to_physical(fragment.style.writing_mode, &containing_block),
+                    .to_physical(fragment.containing_block_writing_mode, &containing_block),
                 Fragment::Text(fragment) => fragment
                     .rect
                     .to_physical(fragment.parent_style.writing_mode, &containing_block),
@@ -484,24 +532,45 @@ impl FragmentTree {
             None::<()>
         });
 
-        Rect::new(
-            Point2D::new(
-                Au::from_f32_px(bounding_box.origin.x.px()),
-                Au::from_f32_px(bounding_box.origin.y.px()),
-            ),
-            Size2D::new(
-                Au::from_f32_px(bounding_box.size.width.px()),
-                Au::from_f32_px(bounding_box.size.height.px()),
-            ),
-        )
+        physical_rect_to_au_rect(&bounding_box)
+    }
+
+    pub fn get_content_boxes_for_node(&self, requested_node: OpaqueNode) -> Vec<Rect<Au>> {
+        let mut content_boxes = Vec::new();
+        let tag_to_find = Tag::Node(requested_node);
+        self.find(|fragment, containing_block| {
+            if fragment.tag() != Some(tag_to_find) {
+                return None::<()>;
+            }
+
+            let fragment_relative_rect = match fragment {
+                Fragment::Box(fragment) => fragment
+                    .border_rect()
+                    .to_physical(fragment.containing_block_writing_mode, &containing_block),
+                Fragment::Text(fragment) => fragment
+                    .rect
+                    .to_physical(fragment.parent_style.writing_mode, &containing_block),
+                Fragment::AbsoluteOrFixedPositioned(_) |
+                Fragment::Image(_) |
+                Fragment::Anonymous(_) => return None,
+            };
+
+            content_boxes.push(physical_rect_to_au_rect(
+                &fragment_relative_rect.translate(containing_block.origin.to_vector()),
+            ));
+            None::<()>
+        });
+        content_boxes
     }
 
     pub fn get_border_dimensions_for_node(&self, requested_node: OpaqueNode) -> Rect<i32> {
         self.find(|fragment, containing_block| {
-            let (style, padding_rect) = match fragment {
-                Fragment::Box(fragment) if fragment.tag.node() == requested_node => {
-                    (&fragment.style, fragment.padding_rect())
-                },
+            let (style, padding_rect, writing_mode) = match fragment {
+                Fragment::Box(fragment) if fragment.tag.node() == requested_node => (
+                    &fragment.style,
+                    fragment.padding_rect(),
+                    fragment.containing_block_writing_mode,
+                ),
                 Fragment::AbsoluteOrFixedPositioned(_) |
                 Fragment::Box(_) |
                 Fragment::Text(_) |
@@ -521,7 +590,7 @@ impl FragmentTree {
                 return Some(Rect::zero());
             }
 
-            let padding_rect = padding_rect.to_physical(style.writing_mode, &containing_block);
+            let padding_rect = padding_rect.to_physical(writing_mode, &containing_block);
             let border = style.get_border();
             Some(Rect::new(
                 Point2D::new(
@@ -536,6 +605,339 @@ impl FragmentTree {
         })
         .unwrap_or_else(Rect::zero)
     }
+
+    /// https://drafts.csswg.org/cssom-view/#scrolling-area
+    pub fn get_scrolling_area_for_node(&self, requested_node: OpaqueNode) -> Rect<i32> {
+        self.find(|fragment, containing_block| {
+            let fragment = match fragment {
+                Fragment::Box(fragment) if fragment.tag == Tag::Node(requested_node) => fragment,
+                _ => return None,
+            };
+
+            let writing_mode = fragment.containing_block_writing_mode;
+            let border_rect = fragment
+                .border_rect()
+                .to_physical(writing_mode, &containing_block);
+            let padding_rect = fragment
+                .padding_rect()
+                .to_physical(writing_mode, &containing_block);
+            let overflow = fragment.scrollable_overflow(&containing_block);
+
+            // Overflow past the start edges of the padding box cannot be scrolled to, so
+            // the scrolling area only grows towards the end edges of the scroll container.
+            let (left_is_start, top_is_start) =
+                match fragment.style.writing_mode.start_start_physical_corner() {
+                    PhysicalCorner::TopLeft => (true, true),
+                    PhysicalCorner::TopRight => (false, true),
+                    PhysicalCorner::BottomLeft => (true, false),
+                    PhysicalCorner::BottomRight => (false, false),
+                };
+            let (left, right) = if left_is_start {
+                (padding_rect.min_x(), overflow.max_x())
+            } else {
+                (overflow.min_x(), padding_rect.max_x())
+            };
+            let (top, bottom) = if top_is_start {
+                (padding_rect.min_y(), overflow.max_y())
+            } else {
+                (overflow.min_y(), padding_rect.max_y())
+            };
+
+            // The result is relative to the border box, like the one of
+            // `get_border_dimensions_for_node`.
+            Some(Rect::new(
+                Point2D::new(
+                    (left - border_rect.min_x()).px() as i32,
+                    (top - border_rect.min_y()).px() as i32,
+                ),
+                Size2D::new((right - left).px() as i32, (bottom - top).px() as i32),
+            ))
+        })
+        .unwrap_or_else(Rect::zero)
+    }
+
+    /// https://drafts.csswg.org/cssom-view/#dom-htmlelement-offsetparent
+    pub fn get_offset_parent_for_node(&self, requested_node: OpaqueNode) -> OffsetParentResponse {
+        let mut search = OffsetParentSearch {
+            requested_node,
+            body: None,
+            border_box: None,
+            offset_parent: None,
+        };
+        for (index, fragment) in self.root_fragments.iter().enumerate() {
+            // The first root fragment is generated by the root element. The other ones
+            // are absolutely positioned boxes that were hoisted up to the initial
+            // containing block, and whose offset parent is the body element.
+            let (depth, ancestors) = if index == 0 {
+                (0, OffsetParentAncestors::default())
+            } else {
+                (
+                    2,
+                    OffsetParentAncestors {
+                        positioned: search.body,
+                        positioned_or_table: search.body,
+                    },
+                )
+            };
+            search.visit(
+                &fragment.borrow(),
+                &self.initial_containing_block,
+                depth,
+                ancestors,
+            );
+        }
+
+        let border_box = match search.border_box {
+            Some(border_box) => border_box,
+            None => return OffsetParentResponse::empty(),
+        };
+        let (node_address, parent_origin) = match search.offset_parent.flatten() {
+            Some((node, origin)) => (Some(node.to_untrusted_node_address()), origin),
+            None => (None, PhysicalPoint::zero()),
+        };
+        OffsetParentResponse {
+            node_address,
+            rect: physical_rect_to_au_rect(&border_box.translate(-parent_origin.to_vector())),
+        }
+    }
+
+    /// Returns the index of the character in the text of the given node that is the
+    /// closest to the given point, which is relative to the border box of that node.
+    pub fn get_text_index_for_node(
+        &self,
+        requested_node: OpaqueNode,
+        point_in_node: Point2D<Au>,
+    ) -> Option<usize> {
+        let point_in_node = PhysicalPoint::new(
+            Length::new(point_in_node.x.to_f32_px()),
+            Length::new(point_in_node.y.to_f32_px()),
+        );
+        self.find(|fragment, containing_block| {
+            let fragment = match fragment {
+                Fragment::Box(fragment) if fragment.tag == Tag::Node(requested_node) => fragment,
+                _ => return None,
+            };
+
+            let border_rect = fragment
+                .border_rect()
+                .to_physical(fragment.containing_block_writing_mode, &containing_block)
+                .translate(containing_block.origin.to_vector());
+            let content_rect = fragment
+                .content_rect
+                .to_physical(fragment.containing_block_writing_mode, &containing_block)
+                .translate(containing_block.origin.to_vector());
+
+            let mut search = TextIndexSearch {
+                point: border_rect.origin + point_in_node.to_vector(),
+                index: 0,
+                found_text: false,
+            };
+            let mut process = |fragment: &Fragment, containing_block: &PhysicalRect<Length>| {
+                search.process(fragment, containing_block)
+            };
+            let index = fragment
+                .children
+                .iter()
+                .find_map(|child| child.borrow().find(&content_rect, &mut process));
+            Some(index.or_else(|| Some(search.index).filter(|_| search.found_text)))
+        })
+        .flatten()
+    }
+}
+
+fn physical_rect_to_au_rect(rect: &PhysicalRect<Length>) -> Rect<Au> {
+    Rect::new(
+        Point2D::new(
+            Au::from_f32_px(rect.origin.x.px()),
+            Au::from_f32_px(rect.origin.y.px()),
+        ),
+        Size2D::new(
+            Au::from_f32_px(rect.size.width.px()),
+            Au::from_f32_px(rect.size.height.px()),
+        ),
+    )
+}
+
+/// The nearest ancestors of a fragment that could be the offset parent of
+/// its element, along with the origin of their padding box.
+#[derive(Clone, Copy, Default)]
+struct OffsetParentAncestors {
+    /// The nearest positioned ancestor, or the body element.
+    positioned: Option<(OpaqueNode, PhysicalPoint<Length>)>,
+    /// The nearest positioned, table or table cell ancestor, or the body element.
+    /// Only statically positioned elements may have a table as offset parent.
+    positioned_or_table: Option<(OpaqueNode, PhysicalPoint<Length>)>,
+}
+
+struct OffsetParentSearch {
+    requested_node: OpaqueNode,
+    /// The body element, which is the box generated at depth one.
+    body: Option<(OpaqueNode, PhysicalPoint<Length>)>,
+    /// The union of the border boxes of the requested node.
+    border_box: Option<PhysicalRect<Length>>,
+    /// `Some` once the requested node has been found, containing its offset parent
+    /// if it has one.
+    offset_parent: Option<Option<(OpaqueNode, PhysicalPoint<Length>)>>,
+}
+
+impl OffsetParentSearch {
+    fn visit(
+        &mut self,
+        fragment: &Fragment,
+        containing_block: &PhysicalRect<Length>,
+        depth: usize,
+        ancestors: OffsetParentAncestors,
+    ) {
+        let (children, child_containing_block, child_depth, child_ancestors) = match fragment {
+            Fragment::Box(fragment) => {
+                let writing_mode = fragment.containing_block_writing_mode;
+                let to_absolute = |rect: flow_relative::Rect<Length>| {
+                    rect.to_physical(writing_mode, containing_block)
+                        .translate(containing_block.origin.to_vector())
+                };
+
+                let is_element = matches!(fragment.tag, Tag::Node(_));
+                if fragment.tag == Tag::Node(self.requested_node) {
+                    let border_rect = to_absolute(fragment.border_rect());
+                    self.border_box = Some(match self.border_box {
+                        Some(border_box) => border_box.union(&border_rect),
+                        None => border_rect,
+                    });
+                    if self.offset_parent.is_none() {
+                        // The root element, the body element, and fixed positioned
+                        // elements have no offset parent.
+                        self.offset_parent = Some(match fragment.style.clone_position() {
+                            _ if depth <= 1 => None,
+                            ComputedPosition::Fixed => None,
+                            ComputedPosition::Static => ancestors.positioned_or_table,
+                            _ => ancestors.positioned,
+                        });
+                    }
+                }
+
+                let mut child_ancestors = ancestors;
+                if is_element {
+                    let candidate = Some((
+                        fragment.tag.node(),
+                        to_absolute(fragment.padding_rect()).origin,
+                    ));
+                    let display_inside = fragment.style.get_box().display.inside();
+                    if depth == 1 {
+                        self.body = candidate;
+                    }
+                    if depth == 1 || fragment.style.establishes_containing_block() {
+                        child_ancestors.positioned = candidate;
+                        child_ancestors.positioned_or_table = candidate;
+                    } else if display_inside == StyloDisplayInside::Table ||
+                        display_inside == StyloDisplayInside::TableCell
+                    {
+                        child_ancestors.positioned_or_table = candidate;
+                    }
+                }
+
+                (
+                    &fragment.children,
+                    to_absolute(fragment.content_rect),
+                    depth + 1,
+                    child_ancestors,
+                )
+            },
+            Fragment::Anonymous(fragment) => (
+                &fragment.children,
+                fragment
+                    .rect
+                    .to_physical(fragment.mode, containing_block)
+                    .translate(containing_block.origin.to_vector()),
+                depth,
+                ancestors,
+            ),
+            Fragment::AbsoluteOrFixedPositioned(_) | Fragment::Text(_) | Fragment::Image(_) => {
+                return
+            },
+        };
+
+        for child in children {
+            self.visit(
+                &child.borrow(),
+                &child_containing_block,
+                child_depth,
+                child_ancestors,
+            );
+        }
+    }
+}
+
+struct TextIndexSearch {
+    /// The point to look for, in the coordinate space of the fragment tree.
+    point: PhysicalPoint<Length>,
+    /// The number of characters in the text fragments processed so far.
+    index: usize,
+    found_text: bool,
+}
+
+impl TextIndexSearch {
+    fn process(
+        &mut self,
+        fragment: &Fragment,
+        containing_block: &PhysicalRect<Length>,
+    ) -> Option<usize> {
+        let fragment = match fragment {
+            Fragment::Text(fragment) => fragment,
+            _ => return None,
+        };
+        self.found_text = true;
+
+        let writing_mode = fragment.parent_style.writing_mode;
+        let rect = fragment
+            .rect
+            .to_physical(writing_mode, containing_block)
+            .translate(containing_block.origin.to_vector());
+
+        // Map the point and the fragment into flow-relative coordinates, in which
+        // lines progress downwards and text progresses to the right.
+        // FIXME: This does not account for right-to-left text.
+        let (x, y) = (self.point.x, self.point.y);
+        let (point_inline, point_block, inline_start, inline_end, block_start, block_end) =
+            if !writing_mode.is_vertical() {
+                (x, y, rect.min_x(), rect.max_x(), rect.min_y(), rect.max_y())
+            } else if writing_mode.is_vertical_lr() {
+                (y, x, rect.min_y(), rect.max_y(), rect.min_x(), rect.max_x())
+            } else {
+                (
+                    y,
+                    -x,
+                    rect.min_y(),
+                    rect.max_y(),
+                    -rect.max_x(),
+                    -rect.min_x(),
+                )
+            };
+
+        // A point before the line of this fragment belongs at its start.
+        if point_block < block_start {
+            return Some(self.index);
+        }
+
+        let mut advance = Length::zero();
+        for glyph_store in &fragment.glyphs {
+            let range = Range::new(ByteIndex(0), glyph_store.len());
+            for glyph in glyph_store.iter_glyphs_for_byte_range(&range) {
+                let glyph_advance: Length = glyph.advance().into();
+                if point_block < block_end &&
+                    point_inline < inline_start + advance + glyph_advance / 2.
+                {
+                    return Some(self.index);
+                }
+                advance += glyph_advance;
+                self.index += 1;
+            }
+        }
+
+        if point_block < block_end && point_inline < inline_end {
+            return Some(self.index);
+        }
+        None
+    }
 }
 
 /// https://drafts.csswg.org/css-backgrounds/#root-background
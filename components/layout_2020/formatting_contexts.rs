@@ -3,14 +3,19 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::context::LayoutContext;
-use crate::dom_traversal::{Contents, NodeAndStyleInfo, NodeExt};
+use crate::dom_traversal::{Contents, NodeAndStyleInfo, NodeExt, TraversalEvent};
 use crate::flexbox::FlexContainer;
+use crate::flow::multicol::MulticolContainer;
 use crate::flow::BlockFormattingContext;
 use crate::fragments::{Fragment, Tag};
+use crate::geom::flow_relative::Vec2;
+use crate::geom::{LengthOrAuto, PhysicalSize};
+use crate::grid::GridContainer;
 use crate::positioned::PositioningContext;
 use crate::replaced::ReplacedContent;
 use crate::sizing::{self, ContentSizes};
-use crate::style_ext::DisplayInside;
+use crate::style_ext::{ComputedValuesExt, DisplayInside, PaddingBorderMargin};
+use crate::table::Table;
 use crate::ContainingBlock;
 use servo_arc::Arc;
 use std::convert::TryInto;
@@ -18,6 +23,7 @@ use style::logical_geometry::WritingMode;
 use style::properties::ComputedValues;
 use style::values::computed::{Length, Percentage};
 use style::values::specified::text::TextDecorationLine;
+use style::Zero;
 
 /// https://drafts.csswg.org/css-display/#independent-formatting-context
 #[derive(Debug, Serialize)]
@@ -49,7 +55,10 @@ pub(crate) struct ReplacedFormattingContext {
 #[derive(Debug, Serialize)]
 pub(crate) enum NonReplacedFormattingContextContents {
     Flow(BlockFormattingContext),
+    Multicol(MulticolContainer),
     Flex(FlexContainer),
+    Grid(GridContainer),
+    Table(Table),
     // Other layout modes go here
 }
 
@@ -73,15 +82,21 @@ impl IndependentFormattingContext {
                 let contents = match display_inside {
                     DisplayInside::Flow { is_list_item } |
                     DisplayInside::FlowRoot { is_list_item } => {
-                        NonReplacedFormattingContextContents::Flow(
-                            BlockFormattingContext::construct(
-                                context,
-                                info,
-                                non_replaced,
-                                propagated_text_decoration_line,
-                                is_list_item,
-                            ),
-                        )
+                        let bfc = BlockFormattingContext::construct(
+                            context,
+                            info,
+                            non_replaced,
+                            propagated_text_decoration_line,
+                            is_list_item,
+                        );
+                        if info.style.is_multicol_container() {
+                            NonReplacedFormattingContextContents::Multicol(MulticolContainer::new(
+                                &info.style,
+                                bfc,
+                            ))
+                        } else {
+                            NonReplacedFormattingContextContents::Flow(bfc)
+                        }
                     },
                     DisplayInside::Flex => {
                         NonReplacedFormattingContextContents::Flex(FlexContainer::construct(
@@ -91,6 +106,22 @@ impl IndependentFormattingContext {
                             propagated_text_decoration_line,
                         ))
                     },
+                    DisplayInside::Grid => {
+                        NonReplacedFormattingContextContents::Grid(GridContainer::construct(
+                            context,
+                            info,
+                            non_replaced,
+                            propagated_text_decoration_line,
+                        ))
+                    },
+                    DisplayInside::Table => {
+                        NonReplacedFormattingContextContents::Table(Table::construct(
+                            context,
+                            info,
+                            non_replaced,
+                            propagated_text_decoration_line,
+                        ))
+                    },
                 };
                 Self::NonReplaced(NonReplacedFormattingContext {
                     tag: Tag::from_node_and_style_info(info),
@@ -122,6 +153,31 @@ impl IndependentFormattingContext {
         })
     }
 
+    /// Construct the anonymous table wrapping a run of internal table boxes
+    /// that are not children of a table.
+    pub fn construct_for_anonymous_table<'dom, Node>(
+        context: &LayoutContext,
+        info: &NodeAndStyleInfo<Node>,
+        children: Vec<TraversalEvent<'dom, Node>>,
+        propagated_text_decoration_line: TextDecorationLine,
+    ) -> Self
+    where
+        Node: NodeExt<'dom>,
+    {
+        let table = Table::construct_for_anonymous_children(
+            context,
+            info,
+            children,
+            propagated_text_decoration_line,
+        );
+        Self::NonReplaced(NonReplacedFormattingContext {
+            tag: Tag::from_node_and_style_info(info),
+            style: Arc::clone(&info.style),
+            content_sizes: None,
+            contents: NonReplacedFormattingContextContents::Table(table),
+        })
+    }
+
     pub fn style(&self) -> &Arc<ComputedValues> {
         match self {
             Self::NonReplaced(inner) => &inner.style,
@@ -200,15 +256,97 @@ impl NonReplacedFormattingContext {
                 containing_block,
                 tree_rank,
             ),
+            NonReplacedFormattingContextContents::Multicol(multicol) => multicol.layout(
+                layout_context,
+                positioning_context,
+                containing_block,
+                tree_rank,
+            ),
             NonReplacedFormattingContextContents::Flex(fc) => fc.layout(
                 layout_context,
                 positioning_context,
                 containing_block,
                 tree_rank,
             ),
+            NonReplacedFormattingContextContents::Grid(grid) => grid.layout(
+                layout_context,
+                positioning_context,
+                containing_block,
+                tree_rank,
+            ),
+            NonReplacedFormattingContextContents::Table(table) => table.layout(
+                layout_context,
+                positioning_context,
+                containing_block,
+                tree_rank,
+            ),
         }
     }
 
+    /// Lay out this formatting context when its writing mode is orthogonal to the one of its
+    /// containing block. `content_box_size` is in the writing mode of the containing block,
+    /// and so is the used size of the content box returned along with the layout.
+    ///
+    /// https://drafts.csswg.org/css-writing-modes/#orthogonal-flows
+    pub fn layout_in_orthogonal_flow(
+        &self,
+        layout_context: &LayoutContext,
+        positioning_context: &mut PositioningContext,
+        containing_block: &ContainingBlock,
+        pbm: &PaddingBorderMargin,
+        content_box_size: &Vec2<LengthOrAuto>,
+        tree_rank: usize,
+    ) -> (IndependentLayout, Vec2<Length>) {
+        // The inline axis of this box is the block axis of its containing block.
+        // “If the orthogonal flow root’s inline size is auto, it is the fit-content size
+        //  in that axis, using the containing block’s size as the available space if it
+        //  is definite, or else the size of the initial containing block.”
+        // https://drafts.csswg.org/css-writing-modes/#orthogonal-auto
+        let inline_size = content_box_size.block.auto_is(|| {
+            let available_size = containing_block.block_size.auto_is(|| {
+                let viewport_size = layout_context.shared_context().viewport_size();
+                let viewport_size = PhysicalSize::new(
+                    Length::from(viewport_size.width),
+                    Length::from(viewport_size.height),
+                );
+                Vec2::from_physical_size(&viewport_size, containing_block.style.writing_mode).block
+            });
+            let margin = pbm.margin.auto_is(Length::zero);
+            let available_size =
+                available_size - pbm.padding_border_sums.block - margin.block_sum();
+            self.contents
+                .inline_content_sizes(layout_context, self.style.writing_mode)
+                .shrink_to_fit(available_size.max(Length::zero()))
+        });
+        let containing_block_for_children = ContainingBlock {
+            inline_size,
+            block_size: content_box_size.inline,
+            style: &self.style,
+        };
+        let layout = self.layout(
+            layout_context,
+            positioning_context,
+            &containing_block_for_children,
+            tree_rank,
+        );
+        let content_size = Vec2 {
+            inline: content_box_size
+                .inline
+                .auto_is(|| layout.content_block_size),
+            block: inline_size,
+        };
+        (layout, content_size)
+    }
+
+    /// Whether this is a table, which is sized like a float when its width is `auto`:
+    /// <https://drafts.csswg.org/css2/tables.html#auto-table-layout>
+    pub fn is_table(&self) -> bool {
+        matches!(
+            self.contents,
+            NonReplacedFormattingContextContents::Table(_)
+        )
+    }
+
     pub fn inline_content_sizes(&mut self, layout_context: &LayoutContext) -> ContentSizes {
         let writing_mode = self.style.writing_mode;
         let contents = &self.contents;
@@ -228,7 +366,10 @@ impl NonReplacedFormattingContextContents {
             Self::Flow(inner) => inner
                 .contents
                 .inline_content_sizes(layout_context, writing_mode),
+            Self::Multicol(inner) => inner.inline_content_sizes(layout_context, writing_mode),
             Self::Flex(inner) => inner.inline_content_sizes(),
+            Self::Grid(inner) => inner.inline_content_sizes(layout_context, writing_mode),
+            Self::Table(inner) => inner.inline_content_sizes(layout_context, writing_mode),
         }
     }
 }
@@ -0,0 +1,854 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Block fragmentation, which splits a laid out flow across a sequence of
+//! fragmentainers such as columns or pages.
+//! https://drafts.csswg.org/css-break/
+//!
+//! The flow is first laid out as if it was not fragmented, then its fragments
+//! are distributed across fragmentainers of a given block size. Content which
+//! cannot be broken, like line boxes or monolithic boxes, is pushed as a whole
+//! to the next fragmentainer when it does not fit in the current one.
+
+use crate::cell::ArcRefCell;
+use crate::fragments::{AnonymousFragment, BoxFragment, CollapsedBlockMargins, Fragment};
+use crate::geom::flow_relative::{Rect, Sides, Vec2};
+use crate::style_ext::{
+    ComputedValuesExt, Display, DisplayGeneratingBox, DisplayInside, DisplayLayoutInternal,
+};
+use std::cmp::Ordering;
+use style::computed_values::overflow_x::T as ComputedOverflow;
+use style::logical_geometry::WritingMode;
+use style::values::computed::{BreakBetween, BreakWithin, Length};
+use style::Zero;
+
+/// The kind of a fragmentation context, which determines the forced breaks and
+/// the break avoidance that apply in it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum FragmentationContextKind {
+    Column,
+    Page,
+}
+
+impl FragmentationContextKind {
+    /// https://drafts.csswg.org/css-break/#break-between
+    fn forces_break(self, value: BreakBetween) -> bool {
+        match value {
+            // Page breaks also break the columns that they are in.
+            BreakBetween::Always |
+            BreakBetween::Page |
+            BreakBetween::Left |
+            BreakBetween::Right => true,
+            BreakBetween::Column => self == FragmentationContextKind::Column,
+            BreakBetween::Auto |
+            BreakBetween::Avoid |
+            BreakBetween::AvoidPage |
+            BreakBetween::AvoidColumn => false,
+        }
+    }
+
+    /// https://drafts.csswg.org/css-break/#break-within
+    fn avoids_break_inside(self, value: BreakWithin) -> bool {
+        match value {
+            BreakWithin::Auto => false,
+            BreakWithin::Avoid => true,
+            BreakWithin::AvoidColumn => self == FragmentationContextKind::Column,
+            BreakWithin::AvoidPage => self == FragmentationContextKind::Page,
+        }
+    }
+}
+
+/// Distributes the fragments of a flow across fragmentainers of a given block size.
+pub(crate) struct Fragmenter {
+    pub kind: FragmentationContextKind,
+    /// The writing mode of the fragmented flow.
+    pub writing_mode: WritingMode,
+    pub fragmentainer_block_size: Length,
+}
+
+/// The places where a flow can or must break.
+pub struct BreakOpportunities {
+    /// The monolithic content of the flow, sorted by its block-start position.
+    pub monolithic: Vec<MonolithicContent>,
+    /// The positions of the forced breaks, relative to the start of the flow, in order.
+    pub forced_breaks: Vec<Length>,
+    pub repeated_headers: Vec<RepeatedHeader>,
+}
+
+/// Content that can only be moved to the next fragmentainer as a whole.
+pub struct MonolithicContent {
+    /// The block-start position of the content, relative to the start of the flow.
+    pub start: Length,
+    /// The block-end position of the content, relative to the start of the flow.
+    pub end: Length,
+    /// The space needed after the content by the repeated footers of the tables it is in.
+    pub reserved_end: Length,
+}
+
+/// A table header, which takes space at the start of the fragmentainers
+/// that start in the body of its table.
+pub struct RepeatedHeader {
+    /// The block-start and block-end positions of the body of the table,
+    /// relative to the start of the flow.
+    pub body: (Length, Length),
+    pub block_size: Length,
+}
+
+/// The header and footer groups of a table, which are repeated in each fragmentainer
+/// that its body spans. Their positions are relative to the content box of the table.
+/// https://drafts.csswg.org/css-tables/#repeated-headers
+struct RepeatedTableGroups {
+    header: Option<(Length, Length)>,
+    footer: Option<(Length, Length)>,
+    /// The content between the header and the footer.
+    body: (Length, Length),
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum TableGroup {
+    Header,
+    Body,
+    Footer,
+}
+
+/// The state of the splitting of a flow across fragmentainers.
+struct SplitState<'a> {
+    starts: &'a [Length],
+    /// The fragmentainer of the preceding content.
+    current_index: usize,
+    /// The block size added so far in each fragmentainer by repeated table headers,
+    /// which moves the content that follows them.
+    inserted: Vec<Length>,
+}
+
+impl Fragmenter {
+    /// Returns the block position in the unfragmented flow at which each
+    /// fragmentainer starts. There is always at least one fragmentainer.
+    pub fn fragmentainer_starts(&self, fragments: &[Fragment]) -> Vec<Length> {
+        let mut opportunities = BreakOpportunities {
+            monolithic: Vec::new(),
+            forced_breaks: Vec::new(),
+            repeated_headers: Vec::new(),
+        };
+        for fragment in fragments {
+            self.collect_break_opportunities(
+                fragment,
+                Length::zero(),
+                Length::zero(),
+                &mut opportunities,
+            );
+        }
+        opportunities
+            .monolithic
+            .sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap_or(Ordering::Equal));
+        opportunities
+            .forced_breaks
+            .sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        opportunities.fragmentainer_starts(self.fragmentainer_block_size)
+    }
+
+    /// Splits the fragments of a flow into the given fragmentainers, as returned by
+    /// `fragmentainer_starts`. The fragments of each fragmentainer are positioned
+    /// relative to its start.
+    pub fn fragment(&self, fragments: Vec<Fragment>, starts: &[Length]) -> Vec<Vec<Fragment>> {
+        let mut fragmentainers: Vec<Vec<Fragment>> = starts.iter().map(|_| Vec::new()).collect();
+        let mut state = SplitState {
+            starts,
+            current_index: 0,
+            inserted: vec![Length::zero(); starts.len()],
+        };
+        for fragment in fragments {
+            for (index, mut piece) in self.split(fragment, Length::zero(), &mut state) {
+                piece.offset_block(&-starts[index]);
+                fragmentainers[index].push(piece);
+            }
+        }
+        fragmentainers
+    }
+
+    /// Whether a box cannot be broken across fragmentainers.
+    /// https://drafts.csswg.org/css-break/#monolithic
+    fn is_monolithic(&self, fragment: &BoxFragment) -> bool {
+        let style = &fragment.style;
+        let box_style = style.get_box();
+        let is_breakable_layout = match Display::from(box_style.display) {
+            Display::GeneratingBox(DisplayGeneratingBox::OutsideInside { inside, .. }) => matches!(
+                inside,
+                DisplayInside::Flow { .. } | DisplayInside::FlowRoot { .. } | DisplayInside::Table
+            ),
+            // Tables only break between rows, the other table-internal boxes
+            // are broken along with the cells and captions that they span.
+            Display::GeneratingBox(DisplayGeneratingBox::LayoutInternal(internal)) => {
+                internal != DisplayLayoutInternal::TableCell
+            },
+            _ => false,
+        };
+        if !is_breakable_layout ||
+            box_style.float.is_floating() ||
+            box_style.overflow_x != ComputedOverflow::Visible ||
+            box_style.overflow_y != ComputedOverflow::Visible ||
+            style.is_multicol_container() ||
+            !has_same_block_flow(style.writing_mode, self.writing_mode) ||
+            fragment
+                .children
+                .iter()
+                .any(|child| matches!(&*child.borrow(), Fragment::Image(_)))
+        {
+            return true;
+        }
+
+        // Avoiding breaks inside a box that does not fit in a fragmentainer
+        // would make it overflow, so it is broken anyway.
+        let border_block_size = fragment.content_rect.size.block +
+            fragment.padding.block_sum() +
+            fragment.border.block_sum();
+        self.kind.avoids_break_inside(box_style.break_inside) &&
+            border_block_size <= self.fragmentainer_block_size
+    }
+
+    /// The header and footer groups of a table box, which are repeated when it is broken.
+    fn repeated_table_groups(&self, fragment: &BoxFragment) -> Option<RepeatedTableGroups> {
+        let is_table = matches!(
+            Display::from(fragment.style.get_box().display),
+            Display::GeneratingBox(DisplayGeneratingBox::OutsideInside {
+                inside: DisplayInside::Table,
+                ..
+            })
+        );
+        if !is_table {
+            return None;
+        }
+
+        let mut header = None;
+        let mut footer = None;
+        let mut rows_end = Length::zero();
+        for child in &fragment.children {
+            let child = child.borrow();
+            let child = match &*child {
+                Fragment::Box(child) => child,
+                _ => continue,
+            };
+            let range = border_block_range(child);
+            let internal = match Display::from(child.style.get_box().display) {
+                Display::GeneratingBox(DisplayGeneratingBox::LayoutInternal(internal)) => internal,
+                _ => continue,
+            };
+            match internal {
+                DisplayLayoutInternal::TableHeaderGroup if header.is_none() => header = Some(range),
+                DisplayLayoutInternal::TableFooterGroup if footer.is_none() => footer = Some(range),
+                DisplayLayoutInternal::TableRowGroup |
+                DisplayLayoutInternal::TableRow |
+                DisplayLayoutInternal::TableCell => rows_end = rows_end.max(range.1),
+                _ => {},
+            }
+        }
+        let groups = RepeatedTableGroups {
+            header,
+            footer,
+            body: (
+                header.map_or(Length::zero(), |header| header.1),
+                footer.map_or(rows_end, |footer| footer.0),
+            ),
+        };
+
+        // Like in other browsers, the header and footer are only repeated
+        // when they leave enough room for the body in each fragmentainer.
+        let repeated_block_size = groups.header_block_size() + groups.footer_block_size();
+        if (header.is_none() && footer.is_none()) ||
+            repeated_block_size > self.fragmentainer_block_size / 2.
+        {
+            return None;
+        }
+        Some(groups)
+    }
+
+    /// Collects the break opportunities in a fragment whose containing block starts at
+    /// `containing_block_start` in the flow, and which needs `reserved_end` after it
+    /// for the repeated footers of the tables it is in.
+    fn collect_break_opportunities(
+        &self,
+        fragment: &Fragment,
+        containing_block_start: Length,
+        reserved_end: Length,
+        opportunities: &mut BreakOpportunities,
+    ) {
+        // Absolutely positioned boxes are out of flow.
+        if is_hoisted(fragment) {
+            return;
+        }
+        let rect = match fragment {
+            Fragment::Box(fragment) => {
+                let content_start =
+                    containing_block_start + fragment.content_rect.start_corner.block;
+                let (border_start, border_end) = border_block_range(fragment);
+                let border_start = containing_block_start + border_start;
+                let border_end = containing_block_start + border_end;
+
+                // Floats are out of flow, so they are not between sibling boxes.
+                let box_style = fragment.style.get_box();
+                let in_flow = !box_style.float.is_floating();
+                if in_flow && self.kind.forces_break(box_style.break_before) {
+                    opportunities.forced_breaks.push(border_start);
+                }
+                if self.is_monolithic(fragment) {
+                    opportunities.monolithic.push(MonolithicContent {
+                        start: border_start,
+                        end: border_end,
+                        reserved_end,
+                    });
+                } else {
+                    let groups = self.repeated_table_groups(fragment);
+                    if let Some(groups) = &groups {
+                        groups.collect_break_opportunities(
+                            content_start,
+                            reserved_end,
+                            opportunities,
+                        );
+                    }
+                    for child in &fragment.children {
+                        let child = child.borrow();
+                        let reserved_end = match &groups {
+                            Some(groups) => match groups.group_of(&child) {
+                                Some(TableGroup::Header) | Some(TableGroup::Footer) => continue,
+                                Some(TableGroup::Body) => reserved_end + groups.footer_block_size(),
+                                None => reserved_end,
+                            },
+                            None => reserved_end,
+                        };
+                        self.collect_break_opportunities(
+                            &child,
+                            content_start,
+                            reserved_end,
+                            opportunities,
+                        );
+                    }
+                }
+                if in_flow && self.kind.forces_break(box_style.break_after) {
+                    opportunities.forced_breaks.push(border_end);
+                }
+                return;
+            },
+            Fragment::Anonymous(fragment) => &fragment.rect,
+            Fragment::Text(fragment) => &fragment.rect,
+            Fragment::Image(fragment) => &fragment.rect,
+            Fragment::AbsoluteOrFixedPositioned(_) => return,
+        };
+        let start = containing_block_start + rect.start_corner.block;
+        opportunities.monolithic.push(MonolithicContent {
+            start,
+            end: start + rect.size.block,
+            reserved_end,
+        });
+    }
+
+    /// Splits a fragment whose containing block starts at `containing_block_start` in the
+    /// unfragmented flow. Returns its pieces, positioned relative to the start of the flow
+    /// with the space inserted by repeated table headers, along with the index of their
+    /// fragmentainer.
+    fn split(
+        &self,
+        fragment: Fragment,
+        containing_block_start: Length,
+        state: &mut SplitState,
+    ) -> Vec<(usize, Fragment)> {
+        let mut fragment = match fragment {
+            Fragment::Box(fragment) if !self.is_monolithic(&fragment) => {
+                return self.split_box(fragment, containing_block_start, state);
+            },
+            fragment => fragment,
+        };
+        // Out-of-flow boxes stay with the content that precedes them.
+        if let Fragment::AbsoluteOrFixedPositioned(_) = fragment {
+            return vec![(state.current_index, fragment)];
+        }
+        let (block_start, _) = fragment_block_range(&fragment);
+        let index = fragmentainer_index(state.starts, containing_block_start + block_start);
+        state.current_index = index;
+        fragment.offset_block(&(containing_block_start + state.inserted[index]));
+        vec![(index, fragment)]
+    }
+
+    fn split_box(
+        &self,
+        fragment: BoxFragment,
+        containing_block_start: Length,
+        state: &mut SplitState,
+    ) -> Vec<(usize, Fragment)> {
+        let groups = self.repeated_table_groups(&fragment);
+        let BoxFragment {
+            tag,
+            style,
+            children,
+            containing_block_writing_mode,
+            content_rect,
+            padding,
+            border,
+            margin,
+            ..
+        } = fragment;
+        let starts = state.starts;
+        let content_start = containing_block_start + content_rect.start_corner.block;
+        let content_end = content_start + content_rect.size.block;
+        let first = fragmentainer_index(
+            starts,
+            content_start - padding.block_start - border.block_start,
+        );
+        state.current_index = first;
+        let inserted_at_start = state.inserted.clone();
+
+        // The header of a table takes space at the start of the fragmentainers
+        // in which its body continues.
+        let repeats_header = |index: usize| {
+            index > first &&
+                groups.as_ref().map_or(false, |groups| {
+                    groups.repeats_header_at(content_start, starts[index])
+                })
+        };
+        if let Some(groups) = &groups {
+            for index in 0..starts.len() {
+                if repeats_header(index) {
+                    state.inserted[index] += groups.header_block_size();
+                }
+            }
+        }
+
+        let mut split_children = Vec::new();
+        let mut header_pieces = Vec::new();
+        let mut footer_pieces = Vec::new();
+        let mut has_body = vec![false; starts.len()];
+        let mut hoisted_children = Vec::new();
+        for child in children {
+            // Absolutely positioned boxes are shared with the placeholders that paint
+            // them, so they are moved to the piece that they start in, without being split.
+            if is_hoisted(&child.borrow()) {
+                let (block_start, _) = fragment_block_range(&child.borrow());
+                let index = fragmentainer_index(starts, content_start + block_start);
+                hoisted_children.push((index.max(first), child));
+                continue;
+            }
+            let child = std::mem::replace(
+                &mut *child.borrow_mut(),
+                Fragment::Anonymous(AnonymousFragment::no_op(self.writing_mode)),
+            );
+            let group = groups.as_ref().and_then(|groups| groups.group_of(&child));
+            let pieces = self.split(child, content_start, state);
+            match group {
+                Some(TableGroup::Header) => header_pieces.extend(pieces),
+                Some(TableGroup::Footer) => footer_pieces.extend(pieces),
+                Some(TableGroup::Body) => {
+                    for (index, _) in &pieces {
+                        has_body[*index] = true;
+                    }
+                    split_children.extend(pieces);
+                },
+                None => split_children.extend(pieces),
+            }
+        }
+        let last = split_children
+            .iter()
+            .chain(&header_pieces)
+            .chain(&footer_pieces)
+            .map(|(index, _)| *index)
+            .chain(Some(fragmentainer_index_before(starts, content_end)))
+            .fold(first, usize::max);
+
+        let piece_start = |index: usize| {
+            let start = if index == first {
+                content_start
+            } else {
+                starts[index]
+            };
+            start + inserted_at_start[index]
+        };
+        let mut children_per_piece: Vec<Vec<Fragment>> =
+            (first..=last).map(|_| Vec::new()).collect();
+        let mut hoisted_children_per_piece: Vec<Vec<ArcRefCell<Fragment>>> =
+            (first..=last).map(|_| Vec::new()).collect();
+        for (index, child) in hoisted_children {
+            let index = index.min(last);
+            child
+                .borrow_mut()
+                .offset_block(&(content_start + state.inserted[index] - piece_start(index)));
+            hoisted_children_per_piece[index - first].push(child);
+        }
+        for (index, mut child) in split_children {
+            let index = index.max(first);
+            child.offset_block(&-piece_start(index));
+            children_per_piece[index - first].push(child);
+        }
+
+        // The fragments of the header and footer are shared by all of the pieces that
+        // repeat them, which is only possible when they are in a single piece.
+        let mut group_in_one_piece = |pieces: Vec<(usize, Fragment)>| {
+            let index = pieces.first()?.0.max(first);
+            if pieces.iter().any(|(other, _)| other.max(first) != index) {
+                for (other, mut piece) in pieces {
+                    let other = other.max(first);
+                    piece.offset_block(&-piece_start(other));
+                    children_per_piece[other - first].push(piece);
+                }
+                return None;
+            }
+            let mut range = (Length::new(f32::INFINITY), Length::zero());
+            let fragments: Vec<Fragment> = pieces
+                .into_iter()
+                .map(|(_, mut piece)| {
+                    piece.offset_block(&-piece_start(index));
+                    let (start, end) = fragment_block_range(&piece);
+                    range = (range.0.min(start), range.1.max(end));
+                    piece
+                })
+                .collect();
+            let rect = Rect {
+                start_corner: Vec2::zero(),
+                size: Vec2 {
+                    inline: content_rect.size.inline,
+                    block: range.1,
+                },
+            };
+            let group = AnonymousFragment::new(rect, fragments, style.writing_mode);
+            Some((index, range, group))
+        };
+        let header = group_in_one_piece(header_pieces);
+        let footer = group_in_one_piece(footer_pieces);
+
+        children_per_piece
+            .into_iter()
+            .zip(hoisted_children_per_piece)
+            .enumerate()
+            .map(|(offset, (mut children, hoisted_children))| {
+                let index = first + offset;
+                let piece_start = piece_start(index);
+                let flow_end = if index == last {
+                    content_end
+                } else {
+                    starts[index + 1]
+                };
+                let piece_end = flow_end + state.inserted[index];
+                let mut piece_block_size = (piece_end - piece_start).max(Length::zero());
+
+                // The header is placed before the cells, so that it is painted
+                // above the backgrounds of the columns.
+                // https://drafts.csswg.org/css-tables/#drawing-cell-backgrounds
+                if let Some((header_index, (header_start, _), header)) = &header {
+                    let block_offset = if index == *header_index {
+                        Some(Length::zero())
+                    } else if index > *header_index && repeats_header(index) {
+                        Some(-*header_start)
+                    } else {
+                        None
+                    };
+                    if let Some(block_offset) = block_offset {
+                        let mut rect = header.rect.clone();
+                        rect.start_corner.block = block_offset;
+                        let position = children
+                            .iter()
+                            .position(is_table_cell)
+                            .unwrap_or(children.len());
+                        children.insert(position, Fragment::Anonymous(header.repeated_at(rect)));
+                    }
+                }
+
+                // The footer is repeated right after the rows of the pieces that precede it.
+                if let Some((footer_index, (footer_start, footer_end), footer)) = &footer {
+                    let block_offset = if index == *footer_index {
+                        Some(Length::zero())
+                    } else if index < *footer_index && has_body[index] {
+                        let body_end = children
+                            .iter()
+                            .map(|child| fragment_block_range(child).1)
+                            .fold(Length::zero(), Length::max);
+                        piece_block_size =
+                            piece_block_size.max(body_end + *footer_end - *footer_start);
+                        Some(body_end - *footer_start)
+                    } else {
+                        None
+                    };
+                    if let Some(block_offset) = block_offset {
+                        let mut rect = footer.rect.clone();
+                        rect.start_corner.block = block_offset;
+                        children.push(Fragment::Anonymous(footer.repeated_at(rect)));
+                    }
+                }
+
+                // Only the first piece has the block-start edges of the box,
+                // and only the last one has its block-end edges.
+                // https://drafts.csswg.org/css-break/#break-decoration
+                let sides = |sides: &Sides<Length>| Sides {
+                    inline_start: sides.inline_start,
+                    inline_end: sides.inline_end,
+                    block_start: if index == first {
+                        sides.block_start
+                    } else {
+                        Length::zero()
+                    },
+                    block_end: if index == last {
+                        sides.block_end
+                    } else {
+                        Length::zero()
+                    },
+                };
+                let piece_rect = Rect {
+                    start_corner: Vec2 {
+                        inline: content_rect.start_corner.inline,
+                        block: piece_start,
+                    },
+                    size: Vec2 {
+                        inline: content_rect.size.inline,
+                        block: piece_block_size,
+                    },
+                };
+                let mut piece = BoxFragment::new(
+                    tag,
+                    style.clone(),
+                    children,
+                    containing_block_writing_mode,
+                    piece_rect,
+                    sides(&padding),
+                    sides(&border),
+                    sides(&margin),
+                    CollapsedBlockMargins::zero(),
+                );
+                piece.children.extend(hoisted_children);
+                (index, Fragment::Box(piece))
+            })
+            .collect()
+    }
+}
+
+impl BreakOpportunities {
+    /// Returns the block position in the unfragmented flow at which each fragmentainer of
+    /// the given block size starts. There is always at least one fragmentainer.
+    pub fn fragmentainer_starts(&self, fragmentainer_block_size: Length) -> Vec<Length> {
+        // Repeated table headers take space from the fragmentainers they are repeated in.
+        let available_block_size = |start: Length| {
+            self.repeated_headers
+                .iter()
+                .filter(|header| header.body.0 <= start && start < header.body.1)
+                .fold(fragmentainer_block_size, |size, header| {
+                    size - header.block_size
+                })
+        };
+
+        let mut starts = vec![Length::zero()];
+        let mut available = available_block_size(Length::zero());
+        let mut forced_breaks = self.forced_breaks.iter().copied().peekable();
+        for content in &self.monolithic {
+            while let Some(forced_break) = forced_breaks.peek().copied() {
+                if forced_break > content.start {
+                    break;
+                }
+                forced_breaks.next();
+                if forced_break > *starts.last().unwrap() {
+                    starts.push(forced_break);
+                    available = available_block_size(forced_break);
+                }
+            }
+
+            // Content that does not fit is pushed to the next fragmentainer,
+            // unless it already is at the start of one.
+            let current_start = *starts.last().unwrap();
+            if content.end + content.reserved_end > current_start + available &&
+                content.start > current_start
+            {
+                starts.push(content.start);
+                available = available_block_size(content.start);
+            }
+        }
+        starts
+    }
+}
+
+impl RepeatedTableGroups {
+    fn header_block_size(&self) -> Length {
+        self.header
+            .map_or(Length::zero(), |(start, end)| end - start)
+    }
+
+    fn footer_block_size(&self) -> Length {
+        self.footer
+            .map_or(Length::zero(), |(start, end)| end - start)
+    }
+
+    /// The group of a child of the table, if it is entirely in one.
+    fn group_of(&self, fragment: &Fragment) -> Option<TableGroup> {
+        let (start, end) = match fragment {
+            Fragment::Box(fragment) => border_block_range(fragment),
+            _ => return None,
+        };
+        let contains = |range: Option<(Length, Length)>| {
+            range.map_or(false, |range| range.0 <= start && end <= range.1)
+        };
+        if contains(self.header) {
+            Some(TableGroup::Header)
+        } else if contains(self.footer) {
+            Some(TableGroup::Footer)
+        } else if contains(Some(self.body)) {
+            Some(TableGroup::Body)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the header is repeated at the start of a fragmentainer that starts at
+    /// `fragmentainer_start` in the flow, for a table whose content box starts at
+    /// `content_start`.
+    fn repeats_header_at(&self, content_start: Length, fragmentainer_start: Length) -> bool {
+        self.header.is_some() &&
+            content_start + self.body.0 <= fragmentainer_start &&
+            fragmentainer_start < content_start + self.body.1
+    }
+
+    /// The header and footer are not broken, so that they can be repeated.
+    fn collect_break_opportunities(
+        &self,
+        content_start: Length,
+        reserved_end: Length,
+        opportunities: &mut BreakOpportunities,
+    ) {
+        for (start, end) in self.header.iter().chain(&self.footer) {
+            opportunities.monolithic.push(MonolithicContent {
+                start: content_start + *start,
+                end: content_start + *end,
+                reserved_end,
+            });
+        }
+        if self.header.is_some() {
+            opportunities.repeated_headers.push(RepeatedHeader {
+                body: (content_start + self.body.0, content_start + self.body.1),
+                block_size: self.header_block_size(),
+            });
+        }
+    }
+}
+
+/// Returns the smallest fragmentainer block size, no larger than `max_block_size`,
+/// with which a flow of `content_block_size` fits in at most `count` fragmentainers.
+/// https://drafts.csswg.org/css-multicol/#cf
+pub(crate) fn balanced_fragmentainer_block_size(
+    kind: FragmentationContextKind,
+    writing_mode: WritingMode,
+    fragments: &[Fragment],
+    count: usize,
+    content_block_size: Length,
+    max_block_size: Option<Length>,
+) -> Length {
+    let fragmentainer_count = |fragmentainer_block_size| {
+        let fragmenter = Fragmenter {
+            kind,
+            writing_mode,
+            fragmentainer_block_size,
+        };
+        fragmenter.fragmentainer_starts(fragments).len()
+    };
+    balanced_block_size(
+        fragmentainer_count,
+        count,
+        content_block_size,
+        max_block_size,
+    )
+}
+
+/// Returns the smallest fragmentainer block size, no larger than `max_block_size`, with
+/// which a flow of `content_block_size` fits in at most `count` fragmentainers, given the
+/// number of fragmentainers that the flow needs with each block size.
+pub fn balanced_block_size(
+    fragmentainer_count: impl Fn(Length) -> usize,
+    count: usize,
+    content_block_size: Length,
+    max_block_size: Option<Length>,
+) -> Length {
+    let fits = |fragmentainer_block_size| fragmentainer_count(fragmentainer_block_size) <= count;
+
+    let mut high = max_block_size
+        .unwrap_or(content_block_size)
+        .max(Length::zero());
+    if high == Length::zero() || !fits(high) {
+        return high;
+    }
+    let mut low = (content_block_size / count.max(1) as f32).min(high);
+    if fits(low) {
+        return low;
+    }
+    // Balancing does not need to be more precise than half a pixel.
+    while high - low > Length::new(0.5) {
+        let middle = (low + high) / 2.;
+        if fits(middle) {
+            high = middle
+        } else {
+            low = middle
+        }
+    }
+    high
+}
+
+/// Whether flows in these writing modes progress in the same block direction.
+fn has_same_block_flow(a: WritingMode, b: WritingMode) -> bool {
+    a.is_vertical() == b.is_vertical() &&
+        (a.is_horizontal() || a.is_vertical_lr() == b.is_vertical_lr())
+}
+
+/// The index of the fragmentainer containing the given position of the flow.
+pub(crate) fn fragmentainer_index(starts: &[Length], position: Length) -> usize {
+    starts
+        .iter()
+        .rposition(|start| *start <= position)
+        .unwrap_or(0)
+}
+
+/// The index of the fragmentainer containing the content that ends at the given
+/// position of the flow.
+fn fragmentainer_index_before(starts: &[Length], position: Length) -> usize {
+    starts
+        .iter()
+        .rposition(|start| *start < position)
+        .unwrap_or(0)
+}
+
+/// The block-start and block-end positions of the border box of a box fragment,
+/// relative to its containing block.
+fn border_block_range(fragment: &BoxFragment) -> (Length, Length) {
+    let content_start = fragment.content_rect.start_corner.block;
+    (
+        content_start - fragment.padding.block_start - fragment.border.block_start,
+        content_start +
+            fragment.content_rect.size.block +
+            fragment.padding.block_end +
+            fragment.border.block_end,
+    )
+}
+
+/// The block-start and block-end positions of a fragment, relative to its containing block.
+pub(crate) fn fragment_block_range(fragment: &Fragment) -> (Length, Length) {
+    let rect = match fragment {
+        Fragment::Box(fragment) => return border_block_range(fragment),
+        Fragment::Anonymous(fragment) => &fragment.rect,
+        Fragment::Text(fragment) => &fragment.rect,
+        Fragment::Image(fragment) => &fragment.rect,
+        Fragment::AbsoluteOrFixedPositioned(_) => return (Length::zero(), Length::zero()),
+    };
+    (
+        rect.start_corner.block,
+        rect.start_corner.block + rect.size.block,
+    )
+}
+
+/// Whether a fragment is that of an absolutely positioned box, which is painted
+/// by its placeholder fragment.
+pub(crate) fn is_hoisted(fragment: &Fragment) -> bool {
+    match fragment {
+        Fragment::Box(fragment) => fragment.style.clone_position().is_absolutely_positioned(),
+        _ => false,
+    }
+}
+
+fn is_table_cell(fragment: &Fragment) -> bool {
+    match fragment {
+        Fragment::Box(fragment) => {
+            Display::from(fragment.style.get_box().display) ==
+                DisplayLayoutInternal::TableCell.into()
+        },
+        _ => false,
+    }
+}
@@ -86,9 +86,13 @@ pub(crate) struct BoxFragment {
     pub style: ServoArc<ComputedValues>,
     pub children: Vec<ArcRefCell<Fragment>>,
 
-    /// From the containing block’s start corner…?
-    /// This might be broken when the containing block is in a different writing mode:
+    /// The writing mode of the containing block, in which `content_rect`, `padding`,
+    /// `border` and `margin` are expressed. This differs from the writing mode of `style`
+    /// when the box has a different `direction` or `writing-mode` than its parent:
     /// https://drafts.csswg.org/css-writing-modes/#orthogonal-flows
+    pub containing_block_writing_mode: WritingMode,
+
+    /// From the containing block’s start corner.
     pub content_rect: Rect<Length>,
 
     pub padding: Sides<Length>,
@@ -97,6 +101,11 @@ pub(crate) struct BoxFragment {
 
     pub block_margins_collapsed_with_children: CollapsedBlockMargins,
 
+    /// The clearance of this box, or the space introduced before it so that it avoids floats,
+    /// which prevents its block-start margin from collapsing with the preceding ones.
+    /// https://drafts.csswg.org/css2/#clearance
+    pub clearance: Option<Length>,
+
     /// The scrollable overflow of this box fragment.
     pub scrollable_overflow_from_children: PhysicalRect<Length>,
 }
@@ -122,7 +131,8 @@ pub(crate) struct AnonymousFragment {
     pub children: Vec<ArcRefCell<Fragment>>,
     pub mode: WritingMode,
 
-    /// The scrollable overflow of this anonymous fragment's children.
+    /// The scrollable overflow of this anonymous fragment's children, relative to its own
+    /// top-left corner.
     pub scrollable_overflow: PhysicalRect<Length>,
 }
 
@@ -187,6 +197,18 @@ impl Fragment {
         position.inline += *offset;
     }
 
+    pub fn offset_block(&mut self, offset: &Length) {
+        let position = match self {
+            Fragment::Box(f) => &mut f.content_rect.start_corner,
+            Fragment::AbsoluteOrFixedPositioned(_) => return,
+            Fragment::Anonymous(f) => &mut f.rect.start_corner,
+            Fragment::Text(f) => &mut f.rect.start_corner,
+            Fragment::Image(f) => &mut f.rect.start_corner,
+        };
+
+        position.block += *offset;
+    }
+
     pub fn tag(&self) -> Option<Tag> {
         match self {
             Fragment::Box(fragment) => Some(fragment.tag),
@@ -214,7 +236,12 @@ impl Fragment {
         match self {
             Fragment::Box(fragment) => fragment.scrollable_overflow_for_parent(&containing_block),
             Fragment::AbsoluteOrFixedPositioned(_) => PhysicalRect::zero(),
-            Fragment::Anonymous(fragment) => fragment.scrollable_overflow.clone(),
+            Fragment::Anonymous(fragment) => {
+                let content_origin = fragment.rect.to_physical(fragment.mode, containing_block);
+                fragment
+                    .scrollable_overflow
+                    .translate(content_origin.origin.to_vector())
+            },
             Fragment::Text(fragment) => fragment
                 .rect
                 .to_physical(fragment.parent_style.writing_mode, &containing_block),
@@ -237,7 +264,7 @@ impl Fragment {
             Fragment::Box(fragment) => {
                 let new_containing_block = fragment
                     .content_rect
-                    .to_physical(fragment.style.writing_mode, containing_block)
+                    .to_physical(fragment.containing_block_writing_mode, containing_block)
                     .translate(containing_block.origin.to_vector());
                 fragment
                     .children
@@ -277,16 +304,10 @@ impl AnonymousFragment {
     }
 
     pub fn new(rect: Rect<Length>, children: Vec<Fragment>, mode: WritingMode) -> Self {
-        // FIXME(mrobinson, bug 25564): We should be using the containing block
-        // here to properly convert scrollable overflow to physical geometry.
-        let containing_block = PhysicalRect::zero();
-        let content_origin = rect.start_corner.to_physical(mode);
+        let containing_block =
+            PhysicalRect::new(PhysicalPoint::zero(), rect.size.to_physical(mode));
         let scrollable_overflow = children.iter().fold(PhysicalRect::zero(), |acc, child| {
-            acc.union(
-                &child
-                    .scrollable_overflow(&containing_block)
-                    .translate(content_origin.to_vector()),
-            )
+            acc.union(&child.scrollable_overflow(&containing_block))
         });
         AnonymousFragment {
             debug_id: DebugId::new(),
@@ -300,6 +321,18 @@ impl AnonymousFragment {
         }
     }
 
+    /// Returns a fragment at another position that shares the children of this one,
+    /// like the repeated headers and footers of a fragmented table.
+    pub fn repeated_at(&self, rect: Rect<Length>) -> Self {
+        AnonymousFragment {
+            debug_id: DebugId::new(),
+            rect,
+            children: self.children.clone(),
+            mode: self.mode,
+            scrollable_overflow: self.scrollable_overflow,
+        }
+    }
+
     pub fn print(&self, tree: &mut PrintTree) {
         tree.new_level(format!(
             "Anonymous\
@@ -320,15 +353,17 @@ impl BoxFragment {
         tag: Tag,
         style: ServoArc<ComputedValues>,
         children: Vec<Fragment>,
+        containing_block_writing_mode: WritingMode,
         content_rect: Rect<Length>,
         padding: Sides<Length>,
         border: Sides<Length>,
         margin: Sides<Length>,
         block_margins_collapsed_with_children: CollapsedBlockMargins,
     ) -> BoxFragment {
-        // FIXME(mrobinson, bug 25564): We should be using the containing block
-        // here to properly convert scrollable overflow to physical geometry.
-        let containing_block = PhysicalRect::zero();
+        let containing_block = PhysicalRect::new(
+            PhysicalPoint::zero(),
+            content_rect.size.to_physical(containing_block_writing_mode),
+        );
         let scrollable_overflow_from_children =
             children.iter().fold(PhysicalRect::zero(), |acc, child| {
                 acc.union(&child.scrollable_overflow(&containing_block))
@@ -341,11 +376,13 @@ impl BoxFragment {
                 .into_iter()
                 .map(|fragment| ArcRefCell::new(fragment))
                 .collect(),
+            containing_block_writing_mode,
             content_rect,
             padding,
             border,
             margin,
             block_margins_collapsed_with_children,
+            clearance: None,
             scrollable_overflow_from_children,
         }
     }
@@ -356,12 +393,12 @@ impl BoxFragment {
     ) -> PhysicalRect<Length> {
         let physical_padding_rect = self
             .padding_rect()
-            .to_physical(self.style.writing_mode, containing_block);
+            .to_physical(self.containing_block_writing_mode, containing_block);
 
         let content_origin = self
             .content_rect
-            .start_corner
-            .to_physical(self.style.writing_mode);
+            .to_physical(self.containing_block_writing_mode, containing_block)
+            .origin;
         physical_padding_rect.union(
             &self
                 .scrollable_overflow_from_children
@@ -407,7 +444,7 @@ impl BoxFragment {
     ) -> PhysicalRect<Length> {
         let mut overflow = self
             .border_rect()
-            .to_physical(self.style.writing_mode, containing_block);
+            .to_physical(self.containing_block_writing_mode, containing_block);
 
         if self.style.get_box().overflow_y != ComputedOverflow::Visible &&
             self.style.get_box().overflow_x != ComputedOverflow::Visible
@@ -202,8 +202,10 @@ impl<T: Clone> flow_relative::Sides<T> {
         let (is, ie) = match (block_flow, mode.inline_base_direction()) {
             (TopToBottom, InlineBaseDirection::LeftToRight) => (&sides.left, &sides.right),
             (TopToBottom, InlineBaseDirection::RightToLeft) => (&sides.right, &sides.left),
-            (_, InlineBaseDirection::LeftToRight) => (&sides.top, &sides.bottom),
-            (_, InlineBaseDirection::RightToLeft) => (&sides.bottom, &sides.top),
+            // Vertical writing modes, where sideways-lr flows from bottom to top
+            // regardless of `direction`.
+            (_, _) if mode.is_inline_tb() => (&sides.top, &sides.bottom),
+            (_, _) => (&sides.bottom, &sides.top),
         };
         flow_relative::Sides {
             inline_start: is.clone(),
@@ -290,6 +292,17 @@ impl<T> flow_relative::Sides<T> {
     }
 }
 
+impl flow_relative::Sides<Length> {
+    pub fn zero() -> Self {
+        Self {
+            inline_start: Length::zero(),
+            inline_end: Length::zero(),
+            block_start: Length::zero(),
+            block_end: Length::zero(),
+        }
+    }
+}
+
 impl flow_relative::Sides<&'_ LengthPercentage> {
     pub fn percentages_relative_to(&self, basis: Length) -> flow_relative::Sides<Length> {
         self.map(|s| s.percentage_relative_to(basis))
@@ -356,25 +369,32 @@ impl<T> flow_relative::Rect<T> {
         }
     }
 
+    /// Converts this rectangle, relative to the start corner of a containing block in the
+    /// given writing mode, to a physical rectangle relative to the top-left corner of that
+    /// containing block.
     pub fn to_physical(
         &self,
         mode: WritingMode,
-        // Will be needed for other writing modes
-        // FIXME: what if the containing block has a different mode?
-        // https://drafts.csswg.org/css-writing-modes/#orthogonal-flows
-        _containing_block: &PhysicalRect<T>,
+        containing_block: &PhysicalRect<T>,
     ) -> PhysicalRect<T>
     where
-        T: Clone,
+        T: Clone + Add<Output = T> + Sub<Output = T>,
     {
-        // Top-left corner
+        // https://drafts.csswg.org/css-writing-modes/#logical-to-physical
+        let size = self.size.to_physical(mode);
+        let (x, y) = if mode.is_horizontal() {
+            (&self.start_corner.inline, &self.start_corner.block)
+        } else {
+            (&self.start_corner.block, &self.start_corner.inline)
+        };
+        let flipped_x = || containing_block.size.width.clone() - x.clone() - size.width.clone();
+        let flipped_y = || containing_block.size.height.clone() - y.clone() - size.height.clone();
         let (tl_x, tl_y) = match mode.start_start_physical_corner() {
-            PhysicalCorner::TopLeft => (&self.start_corner.inline, &self.start_corner.block),
-            _ => unimplemented!(),
+            PhysicalCorner::TopLeft => (x.clone(), y.clone()),
+            PhysicalCorner::TopRight => (flipped_x(), y.clone()),
+            PhysicalCorner::BottomLeft => (x.clone(), flipped_y()),
+            PhysicalCorner::BottomRight => (flipped_x(), flipped_y()),
         };
-        PhysicalRect::new(
-            PhysicalPoint::new(tl_x.clone(), tl_y.clone()),
-            self.size.to_physical(mode),
-        )
+        PhysicalRect::new(PhysicalPoint::new(tl_x, tl_y), size)
     }
 }
@@ -0,0 +1,214 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use super::{GridContainer, GridLevelBox};
+use crate::cell::ArcRefCell;
+use crate::context::LayoutContext;
+use crate::dom_traversal::{
+    BoxSlot, Contents, NodeAndStyleInfo, NodeExt, NonReplacedContents, TraversalHandler,
+};
+use crate::element_data::LayoutBox;
+use crate::formatting_contexts::IndependentFormattingContext;
+use crate::fragments::Tag;
+use crate::positioned::AbsolutelyPositionedBox;
+use crate::style_ext::DisplayGeneratingBox;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::borrow::Cow;
+use style::values::specified::text::TextDecorationLine;
+
+impl GridContainer {
+    pub fn construct<'dom>(
+        context: &LayoutContext,
+        info: &NodeAndStyleInfo<impl NodeExt<'dom>>,
+        contents: NonReplacedContents,
+        propagated_text_decoration_line: TextDecorationLine,
+    ) -> Self {
+        let text_decoration_line =
+            propagated_text_decoration_line | info.style.clone_text_decoration_line();
+        let mut builder = GridContainerBuilder {
+            context,
+            info,
+            text_decoration_line,
+            contiguous_text_runs: Vec::new(),
+            jobs: Vec::new(),
+            has_text_runs: false,
+        };
+        contents.traverse(context, info, &mut builder);
+        builder.finish()
+    }
+}
+
+/// https://drafts.csswg.org/css-grid/#grid-items
+struct GridContainerBuilder<'a, 'dom, Node> {
+    context: &'a LayoutContext<'a>,
+    info: &'a NodeAndStyleInfo<Node>,
+    text_decoration_line: TextDecorationLine,
+    contiguous_text_runs: Vec<TextRun<'dom, Node>>,
+    /// To be run in parallel with rayon in `finish`
+    jobs: Vec<GridLevelJob<'dom, Node>>,
+    has_text_runs: bool,
+}
+
+enum GridLevelJob<'dom, Node> {
+    /// Or pseudo-element
+    Element {
+        info: NodeAndStyleInfo<Node>,
+        display: DisplayGeneratingBox,
+        contents: Contents,
+        box_slot: BoxSlot<'dom>,
+    },
+    TextRuns(Vec<TextRun<'dom, Node>>),
+}
+
+struct TextRun<'dom, Node> {
+    info: NodeAndStyleInfo<Node>,
+    text: Cow<'dom, str>,
+}
+
+impl<'a, 'dom, Node: 'dom> TraversalHandler<'dom, Node> for GridContainerBuilder<'a, 'dom, Node>
+where
+    Node: NodeExt<'dom>,
+{
+    fn handle_text(&mut self, info: &NodeAndStyleInfo<Node>, text: Cow<'dom, str>) {
+        self.contiguous_text_runs.push(TextRun {
+            info: info.clone(),
+            text,
+        })
+    }
+
+    /// Or pseudo-element
+    fn handle_element(
+        &mut self,
+        info: &NodeAndStyleInfo<Node>,
+        display: DisplayGeneratingBox,
+        contents: Contents,
+        box_slot: BoxSlot<'dom>,
+    ) {
+        // FIXME: are text runs considered "contiguous" if they are only separated
+        // by an out-of-flow abspos element?
+        // (That is, are they wrapped in the same anonymous grid item, or each its own?)
+        self.wrap_any_text_in_anonymous_block_container();
+
+        self.jobs.push(GridLevelJob::Element {
+            info: info.clone(),
+            display,
+            contents,
+            box_slot,
+        })
+    }
+}
+
+/// https://drafts.csswg.org/css-text/#white-space
+fn is_only_document_white_space<Node>(run: &TextRun<'_, Node>) -> bool {
+    // FIXME: is this the right definition? See
+    // https://github.com/w3c/csswg-drafts/issues/5146
+    // https://github.com/w3c/csswg-drafts/issues/5147
+    run.text
+        .bytes()
+        .all(|byte| matches!(byte, b' ' | b'\n' | b'\t'))
+}
+
+impl<'a, 'dom, Node: 'dom> GridContainerBuilder<'a, 'dom, Node>
+where
+    Node: NodeExt<'dom>,
+{
+    fn wrap_any_text_in_anonymous_block_container(&mut self) {
+        let runs = std::mem::take(&mut self.contiguous_text_runs);
+        if runs.iter().all(is_only_document_white_space) {
+            // There is no text run, or they all only contain document white space characters
+        } else {
+            self.jobs.push(GridLevelJob::TextRuns(runs));
+            self.has_text_runs = true;
+        }
+    }
+
+    fn finish(mut self) -> GridContainer {
+        self.wrap_any_text_in_anonymous_block_container();
+
+        let anonymous_style = if self.has_text_runs {
+            Some(
+                self.context
+                    .shared_context()
+                    .stylist
+                    .style_for_anonymous::<Node::ConcreteElement>(
+                        &self.context.shared_context().guards,
+                        &style::selector_parser::PseudoElement::ServoText,
+                        &self.info.style,
+                    ),
+            )
+        } else {
+            None
+        };
+
+        let mut children = std::mem::take(&mut self.jobs)
+            .into_par_iter()
+            .map(|job| match job {
+                GridLevelJob::TextRuns(runs) => ArcRefCell::new(GridLevelBox::GridItem(
+                    IndependentFormattingContext::construct_for_text_runs(
+                        &self
+                            .info
+                            .new_replacing_style(anonymous_style.clone().unwrap()),
+                        runs.into_iter().map(|run| crate::flow::inline::TextRun {
+                            tag: Tag::from_node_and_style_info(&run.info),
+                            text: run.text.into(),
+                            parent_style: run.info.style,
+                        }),
+                        self.text_decoration_line,
+                    ),
+                )),
+                GridLevelJob::Element {
+                    info,
+                    display,
+                    contents,
+                    box_slot,
+                } => {
+                    let display_inside = match display {
+                        DisplayGeneratingBox::OutsideInside { inside, .. } => inside,
+                        // Grid items are blockified, which turns internal table
+                        // displays into 'block'.
+                        DisplayGeneratingBox::LayoutInternal(_) => unreachable!(),
+                    };
+                    let box_ = if info.style.get_box().position.is_absolutely_positioned() {
+                        // https://drafts.csswg.org/css-grid/#abspos-items
+                        ArcRefCell::new(GridLevelBox::OutOfFlowAbsolutelyPositionedBox(
+                            ArcRefCell::new(AbsolutelyPositionedBox::construct(
+                                self.context,
+                                &info,
+                                display_inside,
+                                contents,
+                            )),
+                        ))
+                    } else {
+                        ArcRefCell::new(GridLevelBox::GridItem(
+                            IndependentFormattingContext::construct(
+                                self.context,
+                                &info,
+                                display_inside,
+                                contents,
+                                self.text_decoration_line,
+                            ),
+                        ))
+                    };
+                    box_slot.set(LayoutBox::GridLevel(box_.clone()));
+                    box_
+                },
+            })
+            .collect::<Vec<_>>();
+
+        // “Grid items are placed in order-modified document order”
+        // https://drafts.csswg.org/css-grid/#order-property
+        children.sort_by_key(|child| match &*child.borrow() {
+            GridLevelBox::GridItem(item) => item.style().clone_order(),
+
+            // Absolutely-positioned children do not participate in placement,
+            // and are painted as if they had `order: 0`, like in flex containers.
+            GridLevelBox::OutOfFlowAbsolutelyPositionedBox(_) => 0,
+        });
+
+        GridContainer {
+            style: self.info.style.clone(),
+            children,
+        }
+    }
+}
@@ -0,0 +1,1168 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! https://drafts.csswg.org/css-grid/#layout-algorithm
+
+use super::placement::{place_items, ExplicitTracks, GridPlacement};
+use super::{GridContainer, GridLevelBox};
+use crate::context::LayoutContext;
+use crate::formatting_contexts::{IndependentFormattingContext, IndependentLayout};
+use crate::fragments::{
+    AbsoluteOrFixedPositionedFragment, BoxFragment, CollapsedBlockMargins, Fragment,
+};
+use crate::geom::flow_relative::{Rect, Sides, Vec2};
+use crate::geom::LengthOrAuto;
+use crate::positioned::{AbsolutelyPositionedBox, PositioningContext};
+use crate::sizing::ContentSizes;
+use crate::style_ext::ComputedValuesExt;
+use crate::ContainingBlock;
+use atomic_refcell::AtomicRefMut;
+use std::ops::Range;
+use style::logical_geometry::WritingMode;
+use style::properties::longhands::align_content::computed_value::T as AlignContent;
+use style::properties::longhands::align_items::computed_value::T as AlignItems;
+use style::properties::longhands::align_self::computed_value::T as AlignSelf;
+use style::properties::longhands::justify_content::computed_value::T as JustifyContent;
+use style::properties::longhands::justify_items::computed_value::T as JustifyItems;
+use style::properties::longhands::justify_self::computed_value::T as JustifySelf;
+use style::properties::ComputedValues;
+use style::values::computed::length::NonNegativeLengthPercentageOrNormal;
+use style::values::computed::{Length, LengthPercentage, TrackSize};
+use style::values::generics::grid::TrackBreadth;
+use style::values::generics::length::GenericLengthPercentageOrNormal as LengthPercentageOrNormal;
+use style::Zero;
+
+/// The explicit grid of a grid container and the placement of its items.
+struct GridLayout {
+    explicit: Vec2<ExplicitTracks>,
+    placement: GridPlacement,
+    gaps: Vec2<Length>,
+}
+
+/// https://drafts.csswg.org/css-grid/#min-track-sizing-function
+#[derive(Clone, Copy, PartialEq)]
+enum MinSizing {
+    Fixed(Length),
+    MinContent,
+    MaxContent,
+    Auto,
+}
+
+/// https://drafts.csswg.org/css-grid/#max-track-sizing-function
+#[derive(Clone, Copy, PartialEq)]
+enum MaxSizing {
+    Fixed(Length),
+    MinContent,
+    MaxContent,
+    Auto,
+    FitContent(Length),
+    Flex(f32),
+}
+
+/// A track of the grid with some intermediate results of the track sizing algorithm.
+struct Track {
+    min: MinSizing,
+    max: MaxSizing,
+    base_size: Length,
+    /// `None` is infinite.
+    growth_limit: Option<Length>,
+    /// Whether this is an empty track from `repeat(auto-fit, …)`.
+    /// https://drafts.csswg.org/css-grid/#collapsed-track
+    collapsed: bool,
+}
+
+/// The contribution of a grid item to the size of the tracks it spans in one axis.
+struct Contribution {
+    span: Range<usize>,
+    sizes: ContentSizes,
+}
+
+/// The constraint the tracks of one axis are sized under.
+#[derive(Clone, Copy, PartialEq)]
+enum Constraint {
+    Definite(Length),
+    MinContent,
+    MaxContent,
+}
+
+/// How free space is distributed between the tracks of one axis,
+/// per `justify-content` or `align-content`.
+#[derive(Clone, Copy, PartialEq)]
+enum ContentDistribution {
+    Start,
+    End,
+    Center,
+    SpaceBetween,
+    SpaceAround,
+    Stretch,
+}
+
+/// The alignment of a grid item in its grid area in one axis,
+/// per `justify-self` or `align-self`.
+#[derive(Clone, Copy, PartialEq)]
+enum ItemAlignment {
+    Start,
+    End,
+    Center,
+    Stretch,
+}
+
+#[derive(Clone, Copy)]
+enum Axis {
+    Inline,
+    Block,
+}
+
+impl Axis {
+    fn of<T>(self, vec: &Vec2<T>) -> &T {
+        match self {
+            Axis::Inline => &vec.inline,
+            Axis::Block => &vec.block,
+        }
+    }
+}
+
+impl GridContainer {
+    pub fn inline_content_sizes(
+        &self,
+        layout_context: &LayoutContext,
+        writing_mode: WritingMode,
+    ) -> ContentSizes {
+        let mut grid_items = self
+            .children
+            .iter()
+            .filter_map(|child| {
+                let borrowed = child.borrow_mut();
+                match &*borrowed {
+                    GridLevelBox::GridItem(_) => {
+                        Some(AtomicRefMut::map(borrowed, |child| match child {
+                            GridLevelBox::GridItem(item) => item,
+                            _ => unreachable!(),
+                        }))
+                    },
+                    GridLevelBox::OutOfFlowAbsolutelyPositionedBox(_) => None,
+                }
+            })
+            .collect::<Vec<_>>();
+        let layout = GridLayout::new(
+            &self.style,
+            grid_items.iter().map(|item| &**item.style()),
+            &Vec2 {
+                inline: None,
+                block: None,
+            },
+        );
+        let contributions = grid_items
+            .iter_mut()
+            .zip(&layout.placement.areas)
+            .map(|(item, area)| Contribution {
+                span: area.inline.clone(),
+                sizes: item.outer_inline_content_sizes(layout_context, writing_mode),
+            })
+            .collect::<Vec<_>>();
+        let size_under = |constraint| {
+            let mut columns = layout.tracks(Axis::Inline, None);
+            size_tracks(
+                &mut columns,
+                layout.gaps.inline,
+                &contributions,
+                constraint,
+                false,
+            );
+            used_size(&columns, layout.gaps.inline)
+        };
+        ContentSizes {
+            min_content: size_under(Constraint::MinContent),
+            max_content: size_under(Constraint::MaxContent),
+        }
+    }
+
+    pub(crate) fn layout(
+        &self,
+        layout_context: &LayoutContext,
+        positioning_context: &mut PositioningContext,
+        containing_block: &ContainingBlock,
+        tree_rank: usize,
+    ) -> IndependentLayout {
+        let mut grid_items = Vec::with_capacity(self.children.len());
+
+        // Like in flex containers, absolutely-positioned children may be interleaved
+        // with grid items and their relative order is preserved for painting order.
+        let original_order_with_absolutely_positioned = self
+            .children
+            .iter()
+            .enumerate()
+            .map(|(tree_rank, arcrefcell)| {
+                let borrowed = arcrefcell.borrow_mut();
+                match &*borrowed {
+                    GridLevelBox::OutOfFlowAbsolutelyPositionedBox(absolutely_positioned) => {
+                        Ok(absolutely_positioned.clone())
+                    },
+                    GridLevelBox::GridItem(_) => {
+                        let item = AtomicRefMut::map(borrowed, |child| match child {
+                            GridLevelBox::GridItem(item) => item,
+                            _ => unreachable!(),
+                        });
+                        grid_items.push((tree_rank, item));
+                        Err(())
+                    },
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut content_block_size = Length::zero();
+        let fragments =
+            positioning_context.adjust_static_positions(tree_rank, |positioning_context| {
+                let mut grid_item_fragments = self
+                    .layout_items(
+                        layout_context,
+                        positioning_context,
+                        containing_block,
+                        &mut grid_items,
+                        &mut content_block_size,
+                    )
+                    .into_iter();
+                let fragments = original_order_with_absolutely_positioned
+                    .into_iter()
+                    .enumerate()
+                    .map(|(tree_rank, child_as_abspos)| match child_as_abspos {
+                        Err(()) => Fragment::Box(grid_item_fragments.next().unwrap()),
+                        Ok(absolutely_positioned) => {
+                            let position = absolutely_positioned
+                                .borrow()
+                                .context
+                                .style()
+                                .clone_position();
+                            // FIXME: use the grid area of the box as its containing block.
+                            // https://drafts.csswg.org/css-grid/#abspos-items
+                            let hoisted_box = AbsolutelyPositionedBox::to_hoisted(
+                                absolutely_positioned,
+                                Vec2::zero(),
+                                tree_rank,
+                                containing_block,
+                            );
+                            let hoisted_fragment = hoisted_box.fragment.clone();
+                            positioning_context.push(hoisted_box);
+                            Fragment::AbsoluteOrFixedPositioned(AbsoluteOrFixedPositionedFragment {
+                                hoisted_fragment,
+                                position,
+                            })
+                        },
+                    })
+                    .collect::<Vec<_>>();
+                // There should be no more grid items
+                assert!(grid_item_fragments.next().is_none());
+                fragments
+            });
+
+        IndependentLayout {
+            fragments,
+            content_block_size,
+        }
+    }
+
+    /// Return one fragment for each grid item, in the provided order.
+    fn layout_items(
+        &self,
+        layout_context: &LayoutContext,
+        positioning_context: &mut PositioningContext,
+        containing_block: &ContainingBlock,
+        grid_items: &mut [(usize, AtomicRefMut<IndependentFormattingContext>)],
+        content_block_size: &mut Length,
+    ) -> Vec<BoxFragment> {
+        let style = containing_block.style;
+        let writing_mode = style.writing_mode;
+        let container_size = Vec2 {
+            inline: Some(containing_block.inline_size),
+            block: containing_block.block_size.non_auto(),
+        };
+        let layout = GridLayout::new(
+            style,
+            grid_items.iter().map(|(_, item)| &**item.style()),
+            &container_size,
+        );
+        let position_style = style.get_position();
+
+        // https://drafts.csswg.org/css-grid/#algo-grid-sizing
+        // “First, the track sizing algorithm is used to resolve the sizes of the grid columns.”
+        let column_contributions = grid_items
+            .iter_mut()
+            .zip(&layout.placement.areas)
+            .map(|((_, item), area)| Contribution {
+                span: area.inline.clone(),
+                sizes: item.outer_inline_content_sizes(layout_context, writing_mode),
+            })
+            .collect::<Vec<_>>();
+        // FIXME: `normal`, the initial value of `justify-content` and `align-content`
+        // in the spec, behaves as `stretch` in grid containers.
+        let justify_content = ContentDistribution::from(position_style.justify_content);
+        let mut columns = layout.tracks(Axis::Inline, container_size.inline);
+        size_tracks(
+            &mut columns,
+            layout.gaps.inline,
+            &column_contributions,
+            Constraint::Definite(containing_block.inline_size),
+            justify_content == ContentDistribution::Stretch,
+        );
+
+        // “Next, the track sizing algorithm resolves the sizes of the grid rows.”
+        // The contribution of an item is the outer block size it has
+        // when laid out in the columns it spans.
+        let row_contributions = grid_items
+            .iter_mut()
+            .zip(&layout.placement.areas)
+            .map(|((tree_rank, item), area)| {
+                let inline_size = span_size(&columns, &area.inline, layout.gaps.inline);
+                let mut scratch_positioning_context = PositioningContext::new_for_rayon(
+                    positioning_context.collects_for_nearest_positioned_ancestor(),
+                );
+                let fragment = layout_grid_item(
+                    layout_context,
+                    &mut scratch_positioning_context,
+                    style,
+                    item,
+                    *tree_rank,
+                    Vec2 {
+                        inline: inline_size,
+                        block: None,
+                    },
+                );
+                let block_size = fragment.border_rect().size.block + fragment.margin.block_sum();
+                Contribution {
+                    span: area.block.clone(),
+                    sizes: ContentSizes {
+                        min_content: block_size,
+                        max_content: block_size,
+                    },
+                }
+            })
+            .collect::<Vec<_>>();
+        let align_content = ContentDistribution::from(position_style.align_content);
+        let mut rows = layout.tracks(Axis::Block, container_size.block);
+        size_tracks(
+            &mut rows,
+            layout.gaps.block,
+            &row_contributions,
+            container_size
+                .block
+                .map_or(Constraint::MaxContent, Constraint::Definite),
+            align_content == ContentDistribution::Stretch,
+        );
+        *content_block_size = container_size
+            .block
+            .unwrap_or_else(|| used_size(&rows, layout.gaps.block));
+
+        // https://drafts.csswg.org/css-grid/#grid-align
+        let column_starts = track_starts(
+            &columns,
+            layout.gaps.inline,
+            containing_block.inline_size - used_size(&columns, layout.gaps.inline),
+            justify_content,
+        );
+        let row_starts = track_starts(
+            &rows,
+            layout.gaps.block,
+            *content_block_size - used_size(&rows, layout.gaps.block),
+            align_content,
+        );
+
+        grid_items
+            .iter_mut()
+            .zip(&layout.placement.areas)
+            .map(|((tree_rank, item), area)| {
+                let area_start = Vec2 {
+                    inline: column_starts[area.inline.start],
+                    block: row_starts[area.block.start],
+                };
+                let area_size = Vec2 {
+                    inline: track_end(&columns, &column_starts, &area.inline) - area_start.inline,
+                    block: track_end(&rows, &row_starts, &area.block) - area_start.block,
+                };
+                let item_style = item.style().clone();
+                positioning_context.layout_maybe_position_relative_fragment(
+                    layout_context,
+                    containing_block,
+                    &item_style,
+                    |positioning_context| {
+                        let mut fragment = layout_grid_item(
+                            layout_context,
+                            positioning_context,
+                            style,
+                            item,
+                            *tree_rank,
+                            Vec2 {
+                                inline: area_size.inline,
+                                block: Some(area_size.block),
+                            },
+                        );
+                        fragment.content_rect.start_corner += &area_start;
+                        fragment
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+impl GridLayout {
+    fn new<'a>(
+        style: &'a ComputedValues,
+        item_styles: impl Iterator<Item = &'a ComputedValues>,
+        container_size: &Vec2<Option<Length>>,
+    ) -> Self {
+        let position_style = style.get_position();
+        let gaps = Vec2 {
+            inline: gap(&position_style.column_gap, container_size.inline),
+            block: gap(&position_style.row_gap, container_size.block),
+        };
+        let explicit = Vec2 {
+            inline: ExplicitTracks::new(
+                &position_style.grid_template_columns,
+                &position_style.grid_auto_columns,
+                &position_style.grid_template_areas,
+                true,
+                container_size.inline,
+                gaps.inline,
+            ),
+            block: ExplicitTracks::new(
+                &position_style.grid_template_rows,
+                &position_style.grid_auto_rows,
+                &position_style.grid_template_areas,
+                false,
+                container_size.block,
+                gaps.block,
+            ),
+        };
+        let placement = place_items(
+            &explicit,
+            position_style.grid_auto_flow,
+            item_styles.map(|item_style| {
+                let item_position_style = item_style.get_position();
+                Vec2 {
+                    inline: (
+                        &item_position_style.grid_column_start,
+                        &item_position_style.grid_column_end,
+                    ),
+                    block: (
+                        &item_position_style.grid_row_start,
+                        &item_position_style.grid_row_end,
+                    ),
+                }
+            }),
+        );
+        GridLayout {
+            explicit,
+            placement,
+            gaps,
+        }
+    }
+
+    /// The tracks of the implicit grid in the given axis, with percentages
+    /// in their sizing functions resolved against `percentage_basis` if any.
+    fn tracks(&self, axis: Axis, percentage_basis: Option<Length>) -> Vec<Track> {
+        let explicit = axis.of(&self.explicit);
+        let leading = *axis.of(&self.placement.leading_implicit_tracks);
+        (0..*axis.of(&self.placement.track_counts))
+            .map(|index| {
+                let explicit_index = index as isize - leading as isize;
+                // “If any tracks were created by auto-fit, any empty repeated tracks
+                //  are collapsed.”
+                let collapsed = explicit_index >= 0 &&
+                    explicit.is_auto_fit(explicit_index as usize) &&
+                    !self
+                        .placement
+                        .areas
+                        .iter()
+                        .any(|area| axis.of(area).contains(&index));
+                Track::new(explicit.size(explicit_index), percentage_basis, collapsed)
+            })
+            .collect()
+    }
+}
+
+/// The used value of `column-gap` or `row-gap`, where `normal` is zero in grid containers.
+fn gap(gap: &NonNegativeLengthPercentageOrNormal, percentage_basis: Option<Length>) -> Length {
+    match gap {
+        LengthPercentageOrNormal::Normal => Length::zero(),
+        // Percentages resolve against zero when the size of the container is indefinite.
+        LengthPercentageOrNormal::LengthPercentage(length) => length
+            .0
+            .percentage_relative_to(percentage_basis.unwrap_or_else(Length::zero)),
+    }
+}
+
+impl Track {
+    fn new(size: &TrackSize, percentage_basis: Option<Length>, collapsed: bool) -> Self {
+        // “If the size of the grid container depends on the size of its tracks,
+        //  then the <percentage> must be treated as auto.”
+        let breadth = |breadth: &TrackBreadth<LengthPercentage>| match breadth {
+            TrackBreadth::Breadth(length) => length
+                .maybe_percentage_relative_to(percentage_basis)
+                .map_or(MaxSizing::Auto, MaxSizing::Fixed),
+            TrackBreadth::Fr(flex) => MaxSizing::Flex(*flex),
+            TrackBreadth::Auto => MaxSizing::Auto,
+            TrackBreadth::MinContent => MaxSizing::MinContent,
+            TrackBreadth::MaxContent => MaxSizing::MaxContent,
+        };
+        let min_of = |max: MaxSizing| match max {
+            MaxSizing::Fixed(length) => MinSizing::Fixed(length),
+            MaxSizing::MinContent => MinSizing::MinContent,
+            MaxSizing::MaxContent => MinSizing::MaxContent,
+            // A flexible min track sizing function is invalid,
+            // `<flex>` alone means `minmax(auto, <flex>)`.
+            MaxSizing::Auto | MaxSizing::FitContent(_) | MaxSizing::Flex(_) => MinSizing::Auto,
+        };
+        let (min, max) = match size {
+            TrackSize::Breadth(size) => {
+                let max = breadth(size);
+                (min_of(max), max)
+            },
+            TrackSize::Minmax(min, max) => (min_of(breadth(min)), breadth(max)),
+            TrackSize::FitContent(limit) => (
+                MinSizing::Auto,
+                match breadth(limit) {
+                    MaxSizing::Fixed(limit) => MaxSizing::FitContent(limit),
+                    _ => MaxSizing::MaxContent,
+                },
+            ),
+        };
+        Track {
+            min,
+            max,
+            base_size: Length::zero(),
+            growth_limit: None,
+            collapsed,
+        }
+    }
+
+    fn flex_factor(&self) -> Option<f32> {
+        match self.max {
+            MaxSizing::Flex(flex) if !self.collapsed => Some(flex),
+            _ => None,
+        }
+    }
+
+    fn has_intrinsic_min(&self) -> bool {
+        !self.collapsed && !matches!(self.min, MinSizing::Fixed(_))
+    }
+
+    fn has_intrinsic_max(&self) -> bool {
+        !self.collapsed && !matches!(self.max, MaxSizing::Fixed(_) | MaxSizing::Flex(_))
+    }
+
+    fn growth_limit_or_base_size(&self) -> Length {
+        self.growth_limit.unwrap_or(self.base_size)
+    }
+}
+
+/// The sum of the sizes of the tracks and of the gaps between them.
+fn used_size(tracks: &[Track], gap: Length) -> Length {
+    let visible_tracks = tracks.iter().filter(|track| !track.collapsed).count();
+    tracks.iter().map(|track| track.base_size).sum::<Length>() +
+        gap * visible_tracks.saturating_sub(1) as f32
+}
+
+/// The size of the given tracks and of the gaps between them.
+fn span_size(tracks: &[Track], span: &Range<usize>, gap: Length) -> Length {
+    used_size(&tracks[span.clone()], gap)
+}
+
+/// https://drafts.csswg.org/css-grid/#algo-track-sizing
+fn size_tracks(
+    tracks: &mut [Track],
+    gap: Length,
+    contributions: &[Contribution],
+    constraint: Constraint,
+    stretch_auto_tracks: bool,
+) {
+    // https://drafts.csswg.org/css-grid/#algo-init
+    for track in tracks.iter_mut() {
+        if track.collapsed {
+            track.base_size = Length::zero();
+            track.growth_limit = Some(Length::zero());
+            continue;
+        }
+        track.base_size = match track.min {
+            MinSizing::Fixed(length) => length,
+            _ => Length::zero(),
+        };
+        track.growth_limit = match track.max {
+            MaxSizing::Fixed(length) => Some(length.max(track.base_size)),
+            _ => None,
+        };
+    }
+
+    // https://drafts.csswg.org/css-grid/#algo-content
+    // “Size tracks to fit non-spanning items”
+    for contribution in contributions {
+        if contribution.span.len() != 1 || spans_flexible_track(tracks, contribution) {
+            continue;
+        }
+        let track = &mut tracks[contribution.span.start];
+        let sizes = &contribution.sizes;
+        match track.min {
+            MinSizing::MinContent | MinSizing::Auto => {
+                track.base_size = track.base_size.max(sizes.min_content)
+            },
+            MinSizing::MaxContent => track.base_size = track.base_size.max(sizes.max_content),
+            MinSizing::Fixed(_) => {},
+        }
+        let growth_limit = match track.max {
+            MaxSizing::MinContent => sizes.min_content,
+            MaxSizing::MaxContent | MaxSizing::Auto => sizes.max_content,
+            MaxSizing::FitContent(limit) => sizes.max_content.min(limit.max(sizes.min_content)),
+            MaxSizing::Fixed(_) | MaxSizing::Flex(_) => continue,
+        };
+        track.growth_limit = Some(
+            track
+                .growth_limit
+                .map_or(growth_limit, |limit| limit.max(growth_limit)),
+        );
+    }
+
+    // “Increase sizes to accommodate spanning items crossing content-sized tracks”,
+    // by increasing span size.
+    let mut spanning = contributions
+        .iter()
+        .filter(|contribution| {
+            contribution.span.len() > 1 && !spans_flexible_track(tracks, contribution)
+        })
+        .collect::<Vec<_>>();
+    spanning.sort_by_key(|contribution| contribution.span.len());
+    for contribution in spanning {
+        let span = contribution.span.clone();
+        let gaps = gap * (span.len() - 1) as f32;
+
+        let base_sizes = tracks[span.clone()]
+            .iter()
+            .map(|track| track.base_size)
+            .sum::<Length>();
+        let intrinsic_min_tracks = span
+            .clone()
+            .filter(|&index| tracks[index].has_intrinsic_min())
+            .collect::<Vec<_>>();
+        let extra = contribution.sizes.min_content - base_sizes - gaps;
+        if extra > Length::zero() && !intrinsic_min_tracks.is_empty() {
+            let each = extra / intrinsic_min_tracks.len() as f32;
+            for &index in &intrinsic_min_tracks {
+                tracks[index].base_size += each;
+            }
+        }
+
+        let growth_limits = tracks[span.clone()]
+            .iter()
+            .map(Track::growth_limit_or_base_size)
+            .sum::<Length>();
+        let intrinsic_max_tracks = span
+            .clone()
+            .filter(|&index| tracks[index].has_intrinsic_max())
+            .collect::<Vec<_>>();
+        let extra = contribution.sizes.max_content - growth_limits - gaps;
+        if extra > Length::zero() && !intrinsic_max_tracks.is_empty() {
+            let each = extra / intrinsic_max_tracks.len() as f32;
+            for &index in &intrinsic_max_tracks {
+                let track = &mut tracks[index];
+                track.growth_limit = Some(track.growth_limit_or_base_size() + each);
+            }
+        }
+    }
+
+    // “Increase sizes to accommodate spanning items crossing flexible tracks”
+    for contribution in contributions {
+        if !spans_flexible_track(tracks, contribution) {
+            continue;
+        }
+        let span = contribution.span.clone();
+        let gaps = gap * (span.len() - 1) as f32;
+        let base_sizes = tracks[span.clone()]
+            .iter()
+            .map(|track| track.base_size)
+            .sum::<Length>();
+        let flexible_tracks = span
+            .filter(|&index| {
+                tracks[index].flex_factor().is_some() && tracks[index].has_intrinsic_min()
+            })
+            .collect::<Vec<_>>();
+        let extra = contribution.sizes.min_content - base_sizes - gaps;
+        if extra > Length::zero() && !flexible_tracks.is_empty() {
+            let flex_factors = flexible_tracks
+                .iter()
+                .map(|&index| tracks[index].flex_factor().unwrap())
+                .sum::<f32>();
+            for &index in &flexible_tracks {
+                let share = if flex_factors > 0. {
+                    tracks[index].flex_factor().unwrap() / flex_factors
+                } else {
+                    1. / flexible_tracks.len() as f32
+                };
+                tracks[index].base_size += extra * share;
+            }
+        }
+    }
+
+    // “If any track still has an infinite growth limit (because, for example,
+    //  it had no items placed in it or it is a flexible track),
+    //  set its growth limit to its base size.”
+    for track in tracks.iter_mut() {
+        let growth_limit = track.growth_limit_or_base_size().max(track.base_size);
+        track.growth_limit = Some(growth_limit);
+    }
+
+    // https://drafts.csswg.org/css-grid/#algo-grow-tracks
+    match constraint {
+        Constraint::Definite(available) => {
+            let mut free_space = available - used_size(tracks, gap);
+            loop {
+                let growable = tracks
+                    .iter()
+                    .filter(|track| track.growth_limit_or_base_size() > track.base_size)
+                    .count();
+                if growable == 0 || free_space <= Length::new(0.01) {
+                    break;
+                }
+                let each = free_space / growable as f32;
+                for track in tracks.iter_mut() {
+                    let room = track.growth_limit_or_base_size() - track.base_size;
+                    if room > Length::zero() {
+                        let growth = room.min(each);
+                        track.base_size += growth;
+                        free_space = free_space - growth;
+                    }
+                }
+            }
+        },
+        Constraint::MaxContent => {
+            for track in tracks.iter_mut() {
+                track.base_size = track.growth_limit_or_base_size();
+            }
+        },
+        Constraint::MinContent => {},
+    }
+
+    // https://drafts.csswg.org/css-grid/#algo-flex-tracks
+    if tracks.iter().any(|track| track.flex_factor().is_some()) {
+        let flex_fraction = match constraint {
+            // “If the free space is zero or if sizing the grid container
+            //  under a min-content constraint: The used flex fraction is zero.”
+            Constraint::MinContent => Length::zero(),
+            Constraint::Definite(available) => {
+                let visible_tracks = tracks.iter().filter(|track| !track.collapsed).count();
+                let gaps = gap * visible_tracks.saturating_sub(1) as f32;
+                find_the_size_of_an_fr(tracks, 0..tracks.len(), available - gaps)
+            },
+            Constraint::MaxContent => {
+                let from_tracks = tracks
+                    .iter()
+                    .filter_map(|track| {
+                        let flex = track.flex_factor()?;
+                        Some(if flex > 1. {
+                            track.base_size / flex
+                        } else {
+                            track.base_size
+                        })
+                    })
+                    .fold(Length::zero(), Length::max);
+                contributions
+                    .iter()
+                    .filter(|contribution| spans_flexible_track(tracks, contribution))
+                    .map(|contribution| {
+                        let gaps = gap * (contribution.span.len() - 1) as f32;
+                        find_the_size_of_an_fr(
+                            tracks,
+                            contribution.span.clone(),
+                            contribution.sizes.max_content - gaps,
+                        )
+                    })
+                    .fold(from_tracks, Length::max)
+            },
+        };
+        for track in tracks.iter_mut() {
+            if let Some(flex) = track.flex_factor() {
+                track.base_size = track.base_size.max(flex_fraction * flex);
+            }
+        }
+    }
+
+    // https://drafts.csswg.org/css-grid/#algo-stretch
+    if let Constraint::Definite(available) = constraint {
+        let free_space = available - used_size(tracks, gap);
+        let auto_tracks = tracks
+            .iter()
+            .filter(|track| !track.collapsed && track.max == MaxSizing::Auto)
+            .count();
+        if stretch_auto_tracks && free_space > Length::zero() && auto_tracks > 0 {
+            let each = free_space / auto_tracks as f32;
+            for track in tracks.iter_mut() {
+                if !track.collapsed && track.max == MaxSizing::Auto {
+                    track.base_size += each;
+                }
+            }
+        }
+    }
+}
+
+fn spans_flexible_track(tracks: &[Track], contribution: &Contribution) -> bool {
+    tracks[contribution.span.clone()]
+        .iter()
+        .any(|track| track.flex_factor().is_some())
+}
+
+/// https://drafts.csswg.org/css-grid/#algo-find-fr-size
+fn find_the_size_of_an_fr(tracks: &[Track], span: Range<usize>, space_to_fill: Length) -> Length {
+    let mut inflexible = tracks[span.clone()]
+        .iter()
+        .map(|track| track.flex_factor().is_none())
+        .collect::<Vec<_>>();
+    loop {
+        let leftover_space = space_to_fill -
+            tracks[span.clone()]
+                .iter()
+                .zip(&inflexible)
+                .filter(|(_, &inflexible)| inflexible)
+                .map(|(track, _)| track.base_size)
+                .sum::<Length>();
+        let flex_factors = tracks[span.clone()]
+            .iter()
+            .zip(&inflexible)
+            .filter(|(_, &inflexible)| !inflexible)
+            .map(|(track, _)| track.flex_factor().unwrap())
+            .sum::<f32>();
+        // “If this value is less than 1, set it to 1 instead.”
+        let hypothetical_fr_size = leftover_space.max(Length::zero()) / flex_factors.max(1.);
+        // “If the product of the hypothetical fr size and a flexible track’s flex factor
+        //  is less than the track’s base size, restart this algorithm treating
+        //  all such tracks as inflexible.”
+        let mut restart = false;
+        for (track, inflexible) in tracks[span.clone()].iter().zip(&mut inflexible) {
+            if !*inflexible && hypothetical_fr_size * track.flex_factor().unwrap() < track.base_size
+            {
+                *inflexible = true;
+                restart = true;
+            }
+        }
+        if !restart {
+            return hypothetical_fr_size;
+        }
+    }
+}
+
+/// The start position of each track, after distributing `free_space`.
+fn track_starts(
+    tracks: &[Track],
+    gap: Length,
+    free_space: Length,
+    distribution: ContentDistribution,
+) -> Vec<Length> {
+    let visible_tracks = tracks.iter().filter(|track| !track.collapsed).count();
+    let (mut position, between) = match distribution {
+        ContentDistribution::Start | ContentDistribution::Stretch => {
+            (Length::zero(), Length::zero())
+        },
+        ContentDistribution::End => (free_space, Length::zero()),
+        ContentDistribution::Center => (free_space / 2., Length::zero()),
+        // “space-between”’s fallback alignment is `start`.
+        ContentDistribution::SpaceBetween => {
+            if free_space > Length::zero() && visible_tracks > 1 {
+                (Length::zero(), free_space / (visible_tracks - 1) as f32)
+            } else {
+                (Length::zero(), Length::zero())
+            }
+        },
+        // “space-around”’s fallback alignment is `center`.
+        ContentDistribution::SpaceAround => {
+            if free_space > Length::zero() && visible_tracks > 0 {
+                let each = free_space / visible_tracks as f32;
+                (each / 2., each)
+            } else {
+                (free_space / 2., Length::zero())
+            }
+        },
+    };
+    let mut starts = Vec::with_capacity(tracks.len());
+    let mut first = true;
+    for track in tracks {
+        if !track.collapsed {
+            if !first {
+                position += gap + between;
+            }
+            first = false;
+        }
+        starts.push(position);
+        position += track.base_size;
+    }
+    starts
+}
+
+/// The end position of the last track of `span`.
+fn track_end(tracks: &[Track], starts: &[Length], span: &Range<usize>) -> Length {
+    let last = span.end - 1;
+    starts[last] + tracks[last].base_size
+}
+
+impl From<JustifyContent> for ContentDistribution {
+    fn from(value: JustifyContent) -> Self {
+        match value {
+            JustifyContent::FlexStart => ContentDistribution::Start,
+            JustifyContent::FlexEnd => ContentDistribution::End,
+            JustifyContent::Center => ContentDistribution::Center,
+            JustifyContent::SpaceBetween => ContentDistribution::SpaceBetween,
+            JustifyContent::SpaceAround => ContentDistribution::SpaceAround,
+            JustifyContent::Stretch => ContentDistribution::Stretch,
+        }
+    }
+}
+
+impl From<AlignContent> for ContentDistribution {
+    fn from(value: AlignContent) -> Self {
+        match value {
+            AlignContent::FlexStart => ContentDistribution::Start,
+            AlignContent::FlexEnd => ContentDistribution::End,
+            AlignContent::Center => ContentDistribution::Center,
+            AlignContent::SpaceBetween => ContentDistribution::SpaceBetween,
+            AlignContent::SpaceAround => ContentDistribution::SpaceAround,
+            AlignContent::Stretch => ContentDistribution::Stretch,
+        }
+    }
+}
+
+/// `justify-self` of a grid item, defaulting to `justify-items` of the container if `auto`.
+fn justify_self(container_style: &ComputedValues, item_style: &ComputedValues) -> ItemAlignment {
+    let justify_items = match container_style.clone_justify_items() {
+        JustifyItems::Stretch => ItemAlignment::Stretch,
+        JustifyItems::FlexStart => ItemAlignment::Start,
+        JustifyItems::FlexEnd => ItemAlignment::End,
+        JustifyItems::Center => ItemAlignment::Center,
+        // FIXME: handle baseline alignment
+        JustifyItems::Baseline => ItemAlignment::Start,
+    };
+    match item_style.clone_justify_self() {
+        JustifySelf::Auto => justify_items,
+        JustifySelf::Stretch => ItemAlignment::Stretch,
+        JustifySelf::FlexStart => ItemAlignment::Start,
+        JustifySelf::FlexEnd => ItemAlignment::End,
+        JustifySelf::Center => ItemAlignment::Center,
+        JustifySelf::Baseline => ItemAlignment::Start,
+    }
+}
+
+/// `align-self` of a grid item, defaulting to `align-items` of the container if `auto`.
+fn align_self(container_style: &ComputedValues, item_style: &ComputedValues) -> ItemAlignment {
+    let align_items = match container_style.clone_align_items() {
+        AlignItems::Stretch => ItemAlignment::Stretch,
+        AlignItems::FlexStart => ItemAlignment::Start,
+        AlignItems::FlexEnd => ItemAlignment::End,
+        AlignItems::Center => ItemAlignment::Center,
+        // FIXME: handle baseline alignment
+        AlignItems::Baseline => ItemAlignment::Start,
+    };
+    match item_style.clone_align_self() {
+        AlignSelf::Auto => align_items,
+        AlignSelf::Stretch => ItemAlignment::Stretch,
+        AlignSelf::FlexStart => ItemAlignment::Start,
+        AlignSelf::FlexEnd => ItemAlignment::End,
+        AlignSelf::Center => ItemAlignment::Center,
+        AlignSelf::Baseline => ItemAlignment::Start,
+    }
+}
+
+/// Resolve the margins of an item in one axis and its offset in its grid area,
+/// given the free space in the area with `auto` margins treated as zero.
+/// https://drafts.csswg.org/css-grid/#auto-margins
+fn align_in_area(
+    margin_start: LengthOrAuto,
+    margin_end: LengthOrAuto,
+    free_space: Option<Length>,
+    alignment: ItemAlignment,
+) -> (Length, Length, Length) {
+    let free_space = free_space.unwrap_or_else(Length::zero);
+    let positive_free_space = free_space.max(Length::zero());
+    match (margin_start, margin_end) {
+        (LengthOrAuto::Auto, LengthOrAuto::Auto) => (
+            positive_free_space / 2.,
+            positive_free_space / 2.,
+            Length::zero(),
+        ),
+        (LengthOrAuto::Auto, LengthOrAuto::LengthPercentage(end)) => {
+            (positive_free_space, end, Length::zero())
+        },
+        (LengthOrAuto::LengthPercentage(start), LengthOrAuto::Auto) => {
+            (start, positive_free_space, Length::zero())
+        },
+        (LengthOrAuto::LengthPercentage(start), LengthOrAuto::LengthPercentage(end)) => {
+            let offset = match alignment {
+                ItemAlignment::Start | ItemAlignment::Stretch => Length::zero(),
+                ItemAlignment::End => free_space,
+                ItemAlignment::Center => free_space / 2.,
+            };
+            (start, end, offset)
+        },
+    }
+}
+
+/// Lay out a grid item in a grid area of the given size and align it there,
+/// returning a fragment positioned relative to the area.
+/// The block size of the area is `None` when measuring the contribution
+/// of the item to the size of rows.
+fn layout_grid_item(
+    layout_context: &LayoutContext,
+    positioning_context: &mut PositioningContext,
+    container_style: &ComputedValues,
+    item: &mut IndependentFormattingContext,
+    tree_rank: usize,
+    area_size: Vec2<Option<Length>>,
+) -> BoxFragment {
+    // FIXME: percentages of the item should resolve against its grid area,
+    // but `ContainingBlock` carries the style of the grid container.
+    let area_inline_size = area_size.inline.unwrap();
+    let area_containing_block = ContainingBlock {
+        inline_size: area_inline_size,
+        block_size: area_size
+            .block
+            .map_or(LengthOrAuto::Auto, LengthOrAuto::LengthPercentage),
+        style: container_style,
+    };
+    let tag = item.tag();
+    let style = item.style().clone();
+    let pbm = style.padding_border_margin(&area_containing_block);
+    let box_size = style.content_box_size(&area_containing_block, &pbm);
+    let max_box_size = style.content_max_box_size(&area_containing_block, &pbm);
+    let min_box_size = style
+        .content_min_box_size(&area_containing_block, &pbm)
+        .auto_is(Length::zero);
+    let margin_auto_is_zero = pbm.margin.auto_is(Length::zero);
+    let justify = justify_self(container_style, &style);
+    let align = align_self(container_style, &style);
+
+    let (content_size, fragments) = match item {
+        IndependentFormattingContext::Replaced(replaced) => {
+            let size = replaced.contents.used_size_as_if_inline_element(
+                &area_containing_block,
+                &replaced.style,
+                &pbm,
+            );
+            let fragments = replaced
+                .contents
+                .make_fragments(&replaced.style, size.clone());
+            (size, fragments)
+        },
+        IndependentFormattingContext::NonReplaced(non_replaced) => {
+            let available_inline_size = (area_inline_size -
+                pbm.padding_border_sums.inline -
+                margin_auto_is_zero.inline_sum())
+            .max(Length::zero());
+            let inline_size = match box_size.inline {
+                LengthOrAuto::LengthPercentage(inline_size) => inline_size,
+                LengthOrAuto::Auto
+                    if justify == ItemAlignment::Stretch &&
+                        !pbm.margin.inline_start.is_auto() &&
+                        !pbm.margin.inline_end.is_auto() =>
+                {
+                    available_inline_size
+                },
+                LengthOrAuto::Auto => non_replaced
+                    .inline_content_sizes(layout_context)
+                    .shrink_to_fit(available_inline_size),
+            }
+            .clamp_between_extremums(min_box_size.inline, max_box_size.inline);
+
+            let block_size = match (box_size.block, area_size.block) {
+                (LengthOrAuto::Auto, Some(area_block_size))
+                    if align == ItemAlignment::Stretch &&
+                        !pbm.margin.block_start.is_auto() &&
+                        !pbm.margin.block_end.is_auto() =>
+                {
+                    LengthOrAuto::LengthPercentage(
+                        (area_block_size -
+                            pbm.padding_border_sums.block -
+                            margin_auto_is_zero.block_sum())
+                        .max(Length::zero())
+                        .clamp_between_extremums(min_box_size.block, max_box_size.block),
+                    )
+                },
+                (block_size, _) => block_size,
+            };
+            let item_as_containing_block = ContainingBlock {
+                inline_size,
+                block_size,
+                style: &non_replaced.style,
+            };
+            let IndependentLayout {
+                fragments,
+                content_block_size,
+            } = non_replaced.layout(
+                layout_context,
+                positioning_context,
+                &item_as_containing_block,
+                tree_rank,
+            );
+            let block_size = block_size
+                .auto_is(|| content_block_size)
+                .clamp_between_extremums(min_box_size.block, max_box_size.block);
+            (
+                Vec2 {
+                    inline: inline_size,
+                    block: block_size,
+                },
+                fragments,
+            )
+        },
+    };
+
+    let free_space = Vec2 {
+        inline: Some(
+            area_inline_size -
+                content_size.inline -
+                pbm.padding_border_sums.inline -
+                margin_auto_is_zero.inline_sum(),
+        ),
+        block: area_size.block.map(|area_block_size| {
+            area_block_size -
+                content_size.block -
+                pbm.padding_border_sums.block -
+                margin_auto_is_zero.block_sum()
+        }),
+    };
+    let (inline_start, inline_end, inline_offset) = align_in_area(
+        pbm.margin.inline_start,
+        pbm.margin.inline_end,
+        free_space.inline,
+        justify,
+    );
+    let (block_start, block_end, block_offset) = align_in_area(
+        pbm.margin.block_start,
+        pbm.margin.block_end,
+        free_space.block,
+        align,
+    );
+    let margin = Sides {
+        inline_start,
+        inline_end,
+        block_start,
+        block_end,
+    };
+    let content_rect = Rect {
+        start_corner: Vec2 {
+            inline: inline_offset +
+                margin.inline_start +
+                pbm.border.inline_start +
+                pbm.padding.inline_start,
+            block: block_offset +
+                margin.block_start +
+                pbm.border.block_start +
+                pbm.padding.block_start,
+        },
+        size: content_size,
+    };
+    BoxFragment::new(
+        tag,
+        style,
+        fragments,
+        container_style.writing_mode,
+        content_rect,
+        pbm.padding,
+        pbm.border,
+        margin,
+        CollapsedBlockMargins::from_margin(&margin),
+    )
+}
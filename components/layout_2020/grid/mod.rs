@@ -0,0 +1,29 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! CSS Grid Layout.
+//! https://drafts.csswg.org/css-grid/
+
+use crate::cell::ArcRefCell;
+use crate::formatting_contexts::IndependentFormattingContext;
+use crate::positioned::AbsolutelyPositionedBox;
+use servo_arc::Arc;
+use style::properties::ComputedValues;
+
+mod construct;
+mod layout;
+mod placement;
+
+#[derive(Debug, Serialize)]
+pub(crate) struct GridContainer {
+    #[serde(skip_serializing)]
+    style: Arc<ComputedValues>,
+    children: Vec<ArcRefCell<GridLevelBox>>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) enum GridLevelBox {
+    GridItem(IndependentFormattingContext),
+    OutOfFlowAbsolutelyPositionedBox(ArcRefCell<AbsolutelyPositionedBox>),
+}
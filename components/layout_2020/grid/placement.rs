@@ -0,0 +1,684 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! https://drafts.csswg.org/css-grid/#placement
+
+use crate::geom::flow_relative::Vec2;
+use std::ops::Range;
+use style::values::computed::{
+    GridAutoFlow, GridLine, GridTemplateAreas, GridTemplateComponent, ImplicitGridTracks, Length,
+    LengthPercentage, TrackSize,
+};
+use style::values::generics::grid::{RepeatCount, TrackBreadth, TrackListValue, MAX_GRID_LINE};
+use style::values::specified::position::NamedArea;
+use style::Atom;
+use style::Zero;
+
+/// A grid line, numbered like in `grid-row-start` and friends:
+/// the first line of the explicit grid is 1, and lines before it
+/// are numbered 0 and below.
+type Line = i32;
+
+/// The most tracks the explicit grid can have, so that its lines can be
+/// numbered within `MAX_GRID_LINE` like in `grid-row-start` and friends.
+const MAX_EXPLICIT_TRACKS: usize = MAX_GRID_LINE as usize - 1;
+
+/// The tracks and line names of one axis of the explicit grid.
+/// https://drafts.csswg.org/css-grid/#explicit-grids
+pub(super) struct ExplicitTracks {
+    /// The sizing function of each track from `grid-template-rows` or `grid-template-columns`.
+    /// There may be fewer of them than explicit tracks when `grid-template-areas`
+    /// describes a larger grid, the others are sized like implicit tracks.
+    sizes: Vec<TrackSize>,
+
+    /// Whether each track of `sizes` comes from a `repeat(auto-fit, …)`,
+    /// in which case it collapses if no item is placed in it.
+    auto_fit: Vec<bool>,
+
+    /// The names of each line of the explicit grid, one more than there are tracks.
+    line_names: Vec<Vec<Atom>>,
+
+    /// The sizing functions of implicit tracks, from `grid-auto-rows` or `grid-auto-columns`.
+    auto_sizes: Vec<TrackSize>,
+}
+
+/// The position of an item in one axis, before auto-placement.
+#[derive(Clone, Copy)]
+enum AxisPlacement {
+    Definite { start: Line, end: Line },
+    Auto { span: i32 },
+}
+
+/// The result of placing items in the grid.
+pub(super) struct GridPlacement {
+    /// The grid area of each item, as ranges of track indices in the implicit grid.
+    pub areas: Vec<Vec2<Range<usize>>>,
+
+    /// The number of tracks of the implicit grid in each axis.
+    pub track_counts: Vec2<usize>,
+
+    /// The number of implicit tracks before the explicit grid in each axis.
+    pub leading_implicit_tracks: Vec2<usize>,
+}
+
+/// The sides of a named area, for the implicitly-assigned line names.
+/// https://drafts.csswg.org/css-grid/#implicit-named-lines
+#[derive(Clone, Copy)]
+enum Side {
+    Start,
+    End,
+}
+
+impl ExplicitTracks {
+    /// Expand `template` into the tracks of the explicit grid, repeating an
+    /// `auto-fill` or `auto-fit` track list as many times as fits in `available`.
+    pub fn new(
+        template: &GridTemplateComponent,
+        auto_tracks: &ImplicitGridTracks,
+        areas: &GridTemplateAreas,
+        is_inline_axis: bool,
+        available: Option<Length>,
+        gap: Length,
+    ) -> Self {
+        let mut tracks = ExplicitTracks {
+            sizes: Vec::new(),
+            auto_fit: Vec::new(),
+            line_names: vec![Vec::new()],
+            auto_sizes: auto_tracks.0.iter().cloned().collect(),
+        };
+        if tracks.auto_sizes.is_empty() {
+            tracks
+                .auto_sizes
+                .push(TrackSize::Breadth(TrackBreadth::Auto));
+        }
+
+        if let GridTemplateComponent::TrackList(list) = template {
+            let auto_repetitions = if list.has_auto_repeat() {
+                auto_repetitions(&list.values, available, gap)
+            } else {
+                0
+            };
+            tracks.line_names[0].extend(names(&list.line_names[0]));
+            for (index, value) in list.values.iter().enumerate() {
+                match value {
+                    TrackListValue::TrackSize(size) => tracks.push(size.clone(), false),
+                    TrackListValue::TrackRepeat(repeat) => {
+                        let (count, auto_fit) = match repeat.count {
+                            RepeatCount::Number(count) => (count.max(0) as usize, false),
+                            RepeatCount::AutoFill => (auto_repetitions, false),
+                            RepeatCount::AutoFit => (auto_repetitions, true),
+                        };
+                        for _ in 0..count {
+                            if tracks.track_count() >= MAX_EXPLICIT_TRACKS {
+                                break;
+                            }
+                            for (size, line_names) in
+                                repeat.track_sizes.iter().zip(repeat.line_names.iter())
+                            {
+                                tracks.last_line_names().extend(names(line_names));
+                                tracks.push(size.clone(), auto_fit);
+                            }
+                            if let Some(line_names) = repeat.line_names.last() {
+                                tracks.last_line_names().extend(names(line_names));
+                            }
+                        }
+                    },
+                }
+                tracks
+                    .last_line_names()
+                    .extend(names(&list.line_names[index + 1]));
+            }
+        }
+
+        if let GridTemplateAreas::Areas(areas) = areas {
+            let areas = &areas.0;
+            let track_count = if is_inline_axis {
+                areas.width as usize
+            } else {
+                areas.strings.len()
+            };
+            while tracks.line_names.len() < track_count + 1 {
+                tracks.line_names.push(Vec::new());
+            }
+            for area in areas.areas.iter() {
+                let range = area_range(area, is_inline_axis);
+                tracks.line_names[range.start as usize - 1]
+                    .push(implicit_line_name(&area.name, Side::Start));
+                tracks.line_names[range.end as usize - 1]
+                    .push(implicit_line_name(&area.name, Side::End));
+            }
+        }
+        tracks
+    }
+
+    /// Add a track to the explicit grid, unless it already has as many as it can.
+    fn push(&mut self, size: TrackSize, auto_fit: bool) {
+        if self.track_count() >= MAX_EXPLICIT_TRACKS {
+            return;
+        }
+        self.sizes.push(size);
+        self.auto_fit.push(auto_fit);
+        self.line_names.push(Vec::new());
+    }
+
+    fn last_line_names(&mut self) -> &mut Vec<Atom> {
+        self.line_names.last_mut().unwrap()
+    }
+
+    /// The number of tracks of the explicit grid.
+    pub fn track_count(&self) -> usize {
+        self.line_names.len() - 1
+    }
+
+    fn line_count(&self) -> Line {
+        self.line_names.len() as Line
+    }
+
+    /// The sizing function of a track, given its index relative to the start
+    /// of the explicit grid. Negative indices are implicit tracks before it.
+    /// https://drafts.csswg.org/css-grid/#auto-tracks
+    pub fn size(&self, index: isize) -> &TrackSize {
+        if index >= 0 && (index as usize) < self.sizes.len() {
+            return &self.sizes[index as usize];
+        }
+        let count = self.auto_sizes.len() as isize;
+        let index = if index >= 0 {
+            (index - self.sizes.len() as isize) % count
+        } else {
+            // “The last implicit grid track before the explicit grid receives
+            //  the last specified size, and so on backwards.”
+            (count - (-index % count)) % count
+        };
+        &self.auto_sizes[index as usize]
+    }
+
+    /// Whether the track at the given index of the explicit grid comes from
+    /// `repeat(auto-fit, …)`.
+    pub fn is_auto_fit(&self, index: usize) -> bool {
+        self.auto_fit.get(index).copied().unwrap_or(false)
+    }
+
+    /// The `n`th line with the given name, counting from the start of the
+    /// explicit grid if `n` is positive, or from its end if negative.
+    /// “If not enough lines with that name exist, all implicit grid lines
+    ///  are assumed to have that name for the purpose of finding this position.”
+    /// https://drafts.csswg.org/css-grid/#line-placement
+    fn named_line(&self, name: &Atom, n: i32) -> Line {
+        let mut found = 0;
+        if n > 0 {
+            for (index, names) in self.line_names.iter().enumerate() {
+                if names.contains(name) {
+                    found += 1;
+                    if found == n {
+                        return index as Line + 1;
+                    }
+                }
+            }
+            self.line_count() + (n - found)
+        } else {
+            for (index, names) in self.line_names.iter().enumerate().rev() {
+                if names.contains(name) {
+                    found += 1;
+                    if found == -n {
+                        return index as Line + 1;
+                    }
+                }
+            }
+            1 - (-n - found)
+        }
+    }
+
+    /// The line a `span` reaches from `from`, forward or backward.
+    fn span_from(&self, from: Line, span: &GridLine, forward: bool) -> Line {
+        let n = span.line_num.max(1);
+        if span.ident.is_empty() {
+            return if forward { from + n } else { from - n };
+        }
+        let mut found = 0;
+        let matches = |line: Line| {
+            line >= 1 &&
+                line <= self.line_count() &&
+                self.line_names[line as usize - 1].contains(&span.ident)
+        };
+        if forward {
+            let mut line = from + 1;
+            while line <= self.line_count() {
+                if matches(line) {
+                    found += 1;
+                    if found == n {
+                        return line;
+                    }
+                }
+                line += 1;
+            }
+            from.max(self.line_count()) + (n - found)
+        } else {
+            let mut line = from - 1;
+            while line >= 1 {
+                if matches(line) {
+                    found += 1;
+                    if found == n {
+                        return line;
+                    }
+                }
+                line -= 1;
+            }
+            from.min(1) - (n - found)
+        }
+    }
+
+    /// Resolve a `<grid-line>` that is neither `auto` nor a span to a line.
+    fn definite_line(&self, line: &GridLine, side: Side) -> Option<Line> {
+        if line.is_span || line.is_auto() {
+            return None;
+        }
+        if line.ident.is_empty() {
+            return Some(if line.line_num > 0 {
+                line.line_num
+            } else {
+                self.line_count() + 1 + line.line_num
+            });
+        }
+        if line.line_num == 0 {
+            // “First attempt to match the grid area’s edge to a named grid area:
+            //  if there is a grid line whose line name is <custom-ident>-start
+            //  (for grid-*-start) / <custom-ident>-end (for grid-*-end),
+            //  contributes the first such line to the grid item’s placement.”
+            let implicit_name = implicit_line_name(&line.ident, side);
+            if let Some(index) = self
+                .line_names
+                .iter()
+                .position(|names| names.contains(&implicit_name))
+            {
+                return Some(index as Line + 1);
+            }
+            // “Otherwise, treat this as if the integer 1 had been specified
+            //  along with the <custom-ident>.”
+            return Some(self.named_line(&line.ident, 1));
+        }
+        Some(self.named_line(&line.ident, line.line_num))
+    }
+
+    /// https://drafts.csswg.org/css-grid/#line-placement
+    fn placement(&self, start: &GridLine, end: &GridLine) -> AxisPlacement {
+        match (
+            self.definite_line(start, Side::Start),
+            self.definite_line(end, Side::End),
+        ) {
+            (Some(start), Some(end)) => {
+                // “If the placement for a grid item contains two lines,
+                //  and the start line is further end-ward than the end line,
+                //  swap the two lines. If the start line is equal to the end line,
+                //  remove the end line.”
+                if end < start {
+                    AxisPlacement::Definite {
+                        start: end,
+                        end: start,
+                    }
+                } else if end == start {
+                    AxisPlacement::Definite {
+                        start,
+                        end: start + 1,
+                    }
+                } else {
+                    AxisPlacement::Definite { start, end }
+                }
+            },
+            (Some(start), None) => AxisPlacement::Definite {
+                start,
+                end: if end.is_span {
+                    self.span_from(start, end, true)
+                } else {
+                    start + 1
+                },
+            },
+            (None, Some(end)) => AxisPlacement::Definite {
+                start: if start.is_span {
+                    self.span_from(end, start, false)
+                } else {
+                    end - 1
+                },
+                end,
+            },
+            (None, None) => {
+                // “If the placement contains two spans, remove the one contributed
+                //  by the end grid-placement property.”
+                // “If the placement for a grid item contains only a span for a named line,
+                //  replace it with a span of 1.”
+                let span_of = |line: &GridLine| {
+                    if line.ident.is_empty() {
+                        line.line_num.max(1)
+                    } else {
+                        1
+                    }
+                };
+                AxisPlacement::Auto {
+                    span: if start.is_span {
+                        span_of(start)
+                    } else if end.is_span {
+                        span_of(end)
+                    } else {
+                        1
+                    },
+                }
+            },
+        }
+    }
+}
+
+fn names(idents: &[style::values::CustomIdent]) -> impl Iterator<Item = Atom> + '_ {
+    idents.iter().map(|ident| ident.0.clone())
+}
+
+fn implicit_line_name(name: &Atom, side: Side) -> Atom {
+    let suffix = match side {
+        Side::Start => "start",
+        Side::End => "end",
+    };
+    Atom::from(format!("{}-{}", name, suffix))
+}
+
+/// The lines of a named area in the given axis, numbered from 1.
+fn area_range(area: &NamedArea, is_inline_axis: bool) -> Range<u32> {
+    let range = if is_inline_axis {
+        &area.columns
+    } else {
+        &area.rows
+    };
+    range.start..range.end
+}
+
+/// https://drafts.csswg.org/css-grid/#auto-repeat
+fn auto_repetitions(
+    values: &[TrackListValue<LengthPercentage, i32>],
+    available: Option<Length>,
+    gap: Length,
+) -> usize {
+    // “When auto-fill is given as the repetition number, if the grid container
+    //  has a definite size or max size in the relevant axis, then the number
+    //  of repetitions is the largest possible positive integer that does not
+    //  cause the grid to overflow the content box of its grid container
+    //  (treating each track as its max track sizing function if that is definite
+    //  or as its minimum track sizing function otherwise).
+    //  Otherwise, the specified track list repeats only once.”
+    let available = match available {
+        Some(available) => available,
+        None => return 1,
+    };
+    let definite_size = |size: &TrackSize| {
+        let definite_breadth = |breadth: &TrackBreadth<LengthPercentage>| match breadth {
+            TrackBreadth::Breadth(length) => Some(length.percentage_relative_to(available)),
+            _ => None,
+        };
+        let size = match size {
+            TrackSize::Breadth(breadth) => definite_breadth(breadth),
+            TrackSize::Minmax(min, max) => definite_breadth(max).or_else(|| definite_breadth(min)),
+            TrackSize::FitContent(_) => None,
+        };
+        size.unwrap_or_else(Length::zero)
+    };
+
+    let mut other_tracks_size = Length::zero();
+    let mut other_tracks_count = 0;
+    let mut repeated_size = Length::zero();
+    let mut repeated_count = 0;
+    for value in values {
+        match value {
+            TrackListValue::TrackSize(size) => {
+                other_tracks_size += definite_size(size);
+                other_tracks_count += 1;
+            },
+            TrackListValue::TrackRepeat(repeat) => {
+                let size = repeat.track_sizes.iter().map(definite_size).sum::<Length>();
+                match repeat.count {
+                    RepeatCount::Number(count) => {
+                        other_tracks_size += size * count as f32;
+                        other_tracks_count += repeat.track_sizes.len() * count.max(0) as usize;
+                    },
+                    RepeatCount::AutoFill | RepeatCount::AutoFit => {
+                        repeated_size += size;
+                        repeated_count += repeat.track_sizes.len();
+                    },
+                }
+            },
+        }
+    }
+
+    let repetition_size = repeated_size + gap * repeated_count as f32;
+    if repetition_size <= Length::zero() {
+        return 1;
+    }
+    // With `n` repetitions, the tracks take `other_tracks_size + n * repeated_size`
+    // and there are `other_tracks_count + n * repeated_count - 1` gaps.
+    let space = available - other_tracks_size - gap * (other_tracks_count as f32 - 1.);
+    // Don't repeat past the number of tracks the explicit grid can have.
+    let max_repetitions = MAX_EXPLICIT_TRACKS.saturating_sub(other_tracks_count) / repeated_count;
+    ((space / repetition_size).floor() as usize)
+        .min(max_repetitions)
+        .max(1)
+}
+
+/// Occupied cells of the grid during auto-placement, indexed by
+/// their position in the major axis, then in the minor axis.
+struct OccupancyGrid(Vec<Vec<bool>>);
+
+impl OccupancyGrid {
+    fn is_free(&self, major: &Range<usize>, minor: &Range<usize>) -> bool {
+        self.0
+            .iter()
+            .skip(major.start)
+            .take(major.len())
+            .all(|cells| {
+                cells
+                    .iter()
+                    .skip(minor.start)
+                    .take(minor.len())
+                    .all(|occupied| !occupied)
+            })
+    }
+
+    fn occupy(&mut self, major: &Range<usize>, minor: &Range<usize>) {
+        if self.0.len() < major.end {
+            self.0.resize(major.end, Vec::new());
+        }
+        for cells in &mut self.0[major.clone()] {
+            if cells.len() < minor.end {
+                cells.resize(minor.end, false);
+            }
+            for occupied in &mut cells[minor.clone()] {
+                *occupied = true;
+            }
+        }
+    }
+}
+
+/// Place items in the grid given their `grid-{row,column}-{start,end}`,
+/// each in the explicit grid when definite and then auto-placed.
+/// Positions are given in the inline axis (columns) then the block axis (rows).
+/// https://drafts.csswg.org/css-grid/#auto-placement-algo
+pub(super) fn place_items<'a>(
+    explicit: &Vec2<ExplicitTracks>,
+    auto_flow: GridAutoFlow,
+    items: impl Iterator<Item = Vec2<(&'a GridLine, &'a GridLine)>>,
+) -> GridPlacement {
+    // Work in the major and minor axes of the auto-placement algorithm:
+    // auto-placed items fill each row in turn with `grid-auto-flow: row`,
+    // or each column in turn with `grid-auto-flow: column`.
+    let row_flow = !auto_flow.contains(GridAutoFlow::COLUMN);
+    let dense = auto_flow.contains(GridAutoFlow::DENSE);
+    let (major_tracks, minor_tracks) = if row_flow {
+        (&explicit.block, &explicit.inline)
+    } else {
+        (&explicit.inline, &explicit.block)
+    };
+    let placements: Vec<(AxisPlacement, AxisPlacement)> = items
+        .map(|lines| {
+            let inline = explicit.inline.placement(lines.inline.0, lines.inline.1);
+            let block = explicit.block.placement(lines.block.0, lines.block.1);
+            if row_flow {
+                (block, inline)
+            } else {
+                (inline, block)
+            }
+        })
+        .collect();
+
+    // Add implicit tracks before the explicit grid for definite lines that are before it.
+    let leading_tracks = |axis: fn(&(AxisPlacement, AxisPlacement)) -> AxisPlacement| {
+        placements
+            .iter()
+            .map(|placement| match axis(placement) {
+                AxisPlacement::Definite { start, .. } => (1 - start).max(0) as usize,
+                AxisPlacement::Auto { .. } => 0,
+            })
+            .max()
+            .unwrap_or(0)
+    };
+    let major_leading = leading_tracks(|placement| placement.0);
+    let minor_leading = leading_tracks(|placement| placement.1);
+    let to_range = |placement: AxisPlacement, leading: usize| match placement {
+        AxisPlacement::Definite { start, end } => {
+            let start = (start - 1 + leading as i32) as usize;
+            let end = (end - 1 + leading as i32) as usize;
+            Ok(start..end)
+        },
+        AxisPlacement::Auto { span } => Err(span as usize),
+    };
+    let placements: Vec<_> = placements
+        .into_iter()
+        .map(|(major, minor)| {
+            (
+                to_range(major, major_leading),
+                to_range(minor, minor_leading),
+            )
+        })
+        .collect();
+
+    let mut major_count = major_leading + major_tracks.track_count();
+    let mut minor_count = minor_leading + minor_tracks.track_count();
+    for (major, minor) in &placements {
+        match major {
+            Ok(range) => major_count = major_count.max(range.end),
+            Err(span) => major_count = major_count.max(*span),
+        }
+        match minor {
+            Ok(range) => minor_count = minor_count.max(range.end),
+            Err(span) => minor_count = minor_count.max(*span),
+        }
+    }
+
+    let mut occupancy = OccupancyGrid(Vec::new());
+    let mut areas: Vec<Option<(Range<usize>, Range<usize>)>> = vec![None; placements.len()];
+
+    // “Position anything that’s not auto-positioned.”
+    for (area, placement) in areas.iter_mut().zip(&placements) {
+        if let (Ok(major), Ok(minor)) = placement {
+            occupancy.occupy(major, minor);
+            *area = Some((major.clone(), minor.clone()));
+        }
+    }
+
+    // “Process the items locked to a given row.”
+    let mut cursors_per_major_track = std::collections::HashMap::new();
+    for (area, placement) in areas.iter_mut().zip(&placements) {
+        if let (Ok(major), Err(minor_span)) = placement {
+            let cursor = cursors_per_major_track.entry(major.start).or_insert(0);
+            let mut minor_start = if dense { 0 } else { *cursor };
+            while !occupancy.is_free(major, &(minor_start..minor_start + minor_span)) {
+                minor_start += 1;
+            }
+            let minor = minor_start..minor_start + minor_span;
+            occupancy.occupy(major, &minor);
+            *cursor = minor.end;
+            minor_count = minor_count.max(minor.end);
+            *area = Some((major.clone(), minor));
+        }
+    }
+
+    // “Position the remaining grid items.”
+    let mut cursor = (0, 0);
+    for (area, placement) in areas.iter_mut().zip(&placements) {
+        let major_span = match placement {
+            (Err(major_span), _) => *major_span,
+            (Ok(_), _) => continue,
+        };
+        if dense {
+            cursor = (0, 0);
+        }
+        let (major, minor) = match &placement.1 {
+            Ok(minor) => {
+                if !dense && minor.start < cursor.1 {
+                    cursor.0 += 1;
+                }
+                cursor.1 = minor.start;
+                while !occupancy.is_free(&(cursor.0..cursor.0 + major_span), minor) {
+                    cursor.0 += 1;
+                }
+                (cursor.0..cursor.0 + major_span, minor.clone())
+            },
+            Err(minor_span) => loop {
+                if cursor.1 + minor_span > minor_count {
+                    cursor.0 += 1;
+                    cursor.1 = 0;
+                    continue;
+                }
+                let major = cursor.0..cursor.0 + major_span;
+                let minor = cursor.1..cursor.1 + minor_span;
+                if occupancy.is_free(&major, &minor) {
+                    break (major, minor);
+                }
+                cursor.1 += 1;
+            },
+        };
+        occupancy.occupy(&major, &minor);
+        major_count = major_count.max(major.end);
+        *area = Some((major, minor));
+    }
+
+    let areas = areas
+        .into_iter()
+        .map(|area| {
+            let (major, minor) = area.unwrap();
+            if row_flow {
+                Vec2 {
+                    inline: minor,
+                    block: major,
+                }
+            } else {
+                Vec2 {
+                    inline: major,
+                    block: minor,
+                }
+            }
+        })
+        .collect();
+    let (track_counts, leading_implicit_tracks) = if row_flow {
+        (
+            Vec2 {
+                inline: minor_count,
+                block: major_count,
+            },
+            Vec2 {
+                inline: minor_leading,
+                block: major_leading,
+            },
+        )
+    } else {
+        (
+            Vec2 {
+                inline: major_count,
+                block: minor_count,
+            },
+            Vec2 {
+                inline: major_leading,
+                block: minor_leading,
+            },
+        )
+    };
+    GridPlacement {
+        areas,
+        track_counts,
+        leading_implicit_tracks,
+    }
+}
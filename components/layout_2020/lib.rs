@@ -4,6 +4,8 @@
 
 #![deny(unsafe_code)]
 
+#[macro_use]
+extern crate html5ever;
 #[macro_use]
 extern crate log;
 #[macro_use]
@@ -18,17 +20,21 @@ pub mod element_data;
 mod flexbox;
 pub mod flow;
 mod formatting_contexts;
+pub mod fragmentation;
 mod fragments;
 pub mod geom;
+mod grid;
 #[macro_use]
 pub mod layout_debug;
 mod lists;
 mod opaque_node;
+pub mod paged;
 mod positioned;
 pub mod query;
 mod replaced;
 mod sizing;
 mod style_ext;
+mod table;
 pub mod traversal;
 pub mod wrapper;
 
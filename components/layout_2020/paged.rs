@@ -0,0 +1,206 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Paged media, where the document is laid out across page boxes for printing.
+//! https://drafts.csswg.org/css-page-3/
+//!
+//! The document is laid out once in the page area of the first page, then its
+//! fragments are split across pages like the columns of a multi-column container.
+
+use crate::cell::ArcRefCell;
+use crate::context::LayoutContext;
+use crate::display_list::DisplayListBuilder;
+use crate::flow::{BoxTree, FragmentTree};
+use crate::fragmentation::{self, FragmentationContextKind, Fragmenter};
+use crate::fragments::{AnonymousFragment, Fragment};
+use crate::geom::flow_relative::Vec2;
+use crate::geom::{PhysicalPoint, PhysicalRect, PhysicalSize};
+use script_traits::PageDisplayList;
+use style::dom::TElement;
+use style::properties::ComputedValues;
+use style::values::computed::page::{PageOrientation, PageSize};
+use style::values::computed::{Length, LengthPercentageOrAuto};
+use style::Zero;
+use style_traits::CSSPixel;
+use webrender_api::{self as wr, units::LayoutSize};
+
+/// The size of a page box and the position of its page area.
+/// https://drafts.csswg.org/css-page-3/#page-model
+#[derive(Clone, Debug)]
+pub struct PageGeometry {
+    /// The size of the page box, which is that of the sheet of paper.
+    pub size: PhysicalSize<Length>,
+    /// The page area, which is the page box minus its margins,
+    /// relative to the page box.
+    pub page_area: PhysicalRect<Length>,
+}
+
+/// A page of a paginated document.
+pub struct Page {
+    pub geometry: PageGeometry,
+    /// The fragments of the page, whose initial containing block is the page area.
+    pub fragment_tree: FragmentTree,
+}
+
+impl PageGeometry {
+    /// Computes the geometry of the pages from the style of the page context, given
+    /// the size of the pages when the `size` property is `auto`.
+    pub fn new(page_style: &ComputedValues, default_size: euclid::Size2D<f32, CSSPixel>) -> Self {
+        let default_size = PhysicalSize::new(
+            Length::new(default_size.width),
+            Length::new(default_size.height),
+        );
+        let is_landscape = |size: &PhysicalSize<Length>| size.width > size.height;
+        let size = match page_style.get_page().size {
+            PageSize::Size(ref size) => PhysicalSize::new(size.width.0, size.height.0),
+            PageSize::Orientation(orientation) => {
+                let landscape = orientation == PageOrientation::Landscape;
+                if is_landscape(&default_size) == landscape {
+                    default_size
+                } else {
+                    PhysicalSize::new(default_size.height, default_size.width)
+                }
+            },
+            PageSize::Auto => default_size,
+        };
+
+        // Margins of page boxes are relative to their size, and their `auto`
+        // values are zero, as the page boxes have no containing block.
+        // https://drafts.csswg.org/css-page-3/#page-model
+        let margin = page_style.get_margin();
+        let resolve = |margin: &LengthPercentageOrAuto, basis: Length| {
+            margin.non_auto().map_or(Length::zero(), |margin| {
+                margin.percentage_relative_to(basis)
+            })
+        };
+        let top = resolve(&margin.margin_top, size.height);
+        let right = resolve(&margin.margin_right, size.width);
+        let bottom = resolve(&margin.margin_bottom, size.height);
+        let left = resolve(&margin.margin_left, size.width);
+        let page_area = PhysicalRect::new(
+            PhysicalPoint::new(left, top),
+            PhysicalSize::new(
+                (size.width - left - right).max(Length::zero()),
+                (size.height - top - bottom).max(Length::zero()),
+            ),
+        );
+        PageGeometry { size, page_area }
+    }
+}
+
+impl BoxTree {
+    /// Lays out the box tree across pages, whose geometry is given by the @page rules
+    /// and `default_page_size`. There is always at least one page.
+    ///
+    /// FIXME: Page selectors and margin boxes are not supported, so every page has the
+    /// same geometry, and fixed positioned boxes are not repeated on every page.
+    ///
+    /// TODO: The type parameter, which is only used to cascade the @page rules,
+    /// can go away when https://github.com/rust-lang/rust/issues/35121 is fixed.
+    pub fn layout_paged<E>(
+        &self,
+        layout_context: &LayoutContext,
+        default_page_size: euclid::Size2D<f32, CSSPixel>,
+    ) -> Vec<Page>
+    where
+        E: TElement,
+    {
+        // The page context inherits from the root element.
+        // https://drafts.csswg.org/css-page-3/#page-properties
+        let root_style = self.root_style();
+        let root_style = match &root_style {
+            Some(style) => &**style,
+            None => ComputedValues::initial_values(),
+        };
+        let style_context = layout_context.shared_context();
+        let page_style = style_context
+            .stylist
+            .compute_for_page_rules::<E>(&style_context.guards, root_style);
+        let geometry = PageGeometry::new(&page_style, default_page_size);
+
+        let page_area_size = &geometry.page_area.size;
+        let FragmentTree {
+            root_fragments,
+            canvas_background,
+            ..
+        } = self.layout(
+            layout_context,
+            euclid::Size2D::new(page_area_size.width.px(), page_area_size.height.px()),
+        );
+
+        // Absolutely positioned boxes are painted by their placeholders, which share
+        // their fragments, so they are moved to the page they start in without being split.
+        let writing_mode = root_style.writing_mode;
+        let mut flow_fragments = Vec::new();
+        let mut hoisted_fragments = Vec::new();
+        for fragment in root_fragments {
+            if fragmentation::is_hoisted(&fragment.borrow()) {
+                hoisted_fragments.push(fragment);
+                continue;
+            }
+            flow_fragments.push(std::mem::replace(
+                &mut *fragment.borrow_mut(),
+                Fragment::Anonymous(AnonymousFragment::no_op(writing_mode)),
+            ));
+        }
+
+        let fragmenter = Fragmenter {
+            kind: FragmentationContextKind::Page,
+            writing_mode,
+            fragmentainer_block_size: Vec2::from_physical_size(page_area_size, writing_mode).block,
+        };
+        let starts = fragmenter.fragmentainer_starts(&flow_fragments);
+        let mut hoisted_fragments_per_page: Vec<Vec<ArcRefCell<Fragment>>> =
+            starts.iter().map(|_| Vec::new()).collect();
+        for fragment in hoisted_fragments {
+            let (block_start, _) = fragmentation::fragment_block_range(&fragment.borrow());
+            let index = fragmentation::fragmentainer_index(&starts, block_start);
+            fragment.borrow_mut().offset_block(&-starts[index]);
+            hoisted_fragments_per_page[index].push(fragment);
+        }
+
+        fragmenter
+            .fragment(flow_fragments, &starts)
+            .into_iter()
+            .zip(hoisted_fragments_per_page)
+            .map(|(fragments, hoisted_fragments)| {
+                let root_fragments = fragments
+                    .into_iter()
+                    .map(ArcRefCell::new)
+                    .chain(hoisted_fragments)
+                    .collect();
+                let fragment_tree = FragmentTree {
+                    root_fragments,
+                    scrollable_overflow: PhysicalRect::new(PhysicalPoint::zero(), geometry.size),
+                    initial_containing_block: geometry.page_area,
+                    canvas_background: canvas_background.clone(),
+                };
+                Page {
+                    geometry: geometry.clone(),
+                    fragment_tree,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Page {
+    /// Builds the display list of the page on its own, with the origin at the
+    /// top-left corner of the page box, for a backend that prints pages or
+    /// renders them to a document format like PDF.
+    pub fn build_display_list(
+        &self,
+        pipeline_id: wr::PipelineId,
+        layout_context: &LayoutContext,
+    ) -> PageDisplayList {
+        let mut builder = DisplayListBuilder::new(pipeline_id, layout_context, &self.fragment_tree);
+        self.fragment_tree.build_display_list(&mut builder);
+        let (_, _, display_list) = builder.wr.finalize();
+        let page_size = LayoutSize::new(
+            self.geometry.size.width.px(),
+            self.geometry.size.height.px(),
+        );
+        PageDisplayList::new(page_size, display_list)
+    }
+}
@@ -248,14 +248,14 @@ impl PositioningContext {
         layout_context: &LayoutContext,
         new_fragment: &mut BoxFragment,
     ) {
-        let padding_rect = Rect {
-            size: new_fragment.content_rect.size.clone(),
-            // Ignore the content rect’s position in its own containing block:
-            start_corner: Vec2::zero(),
-        }
-        .inflate(&new_fragment.padding);
+        // The geometry of the fragment is in the writing mode of its own containing block,
+        // but its hoisted descendants are laid out in the writing mode of the fragment.
+        let padding_rect_size = new_fragment
+            .padding_rect()
+            .size
+            .to_physical(new_fragment.containing_block_writing_mode);
         let containing_block = DefiniteContainingBlock {
-            size: padding_rect.size.clone(),
+            size: Vec2::from_physical_size(&padding_rect_size, new_fragment.style.writing_mode),
             style: &new_fragment.style,
         };
 
@@ -296,7 +296,7 @@ impl PositioningContext {
             match position {
                 Position::Fixed => {}, // fall through
                 Position::Absolute => return nearest.push(box_),
-                Position::Static | Position::Relative => unreachable!(),
+                Position::Static | Position::Relative | Position::Sticky => unreachable!(),
             }
         }
         self.for_nearest_containing_block_for_all_descendants
@@ -487,6 +487,25 @@ impl HoistedAbsolutelyPositionedBox {
                         .contents
                         .make_fragments(style, content_size.clone());
                 },
+                IndependentFormattingContext::NonReplaced(non_replaced)
+                    if non_replaced.style.writing_mode.is_horizontal() !=
+                        containing_block.style.writing_mode.is_horizontal() =>
+                {
+                    let dummy_tree_rank = 0;
+                    let (independent_layout, size) = non_replaced.layout_in_orthogonal_flow(
+                        layout_context,
+                        &mut positioning_context,
+                        &containing_block.into(),
+                        &pbm,
+                        &Vec2 {
+                            inline: inline_axis.size,
+                            block: block_axis.size,
+                        },
+                        dummy_tree_rank,
+                    );
+                    content_size = size;
+                    fragments = independent_layout.fragments;
+                }
                 IndependentFormattingContext::NonReplaced(non_replaced) => {
                     // https://drafts.csswg.org/css2/visudet.html#abs-non-replaced-width
                     // https://drafts.csswg.org/css2/visudet.html#abs-non-replaced-height
@@ -507,12 +526,6 @@ impl HoistedAbsolutelyPositionedBox {
                         block_size: block_axis.size,
                         style: &non_replaced.style,
                     };
-                    // https://drafts.csswg.org/css-writing-modes/#orthogonal-flows
-                    assert_eq!(
-                        containing_block.style.writing_mode,
-                        containing_block_for_children.style.writing_mode,
-                        "Mixed writing modes are not supported yet"
-                    );
                     let dummy_tree_rank = 0;
                     let independent_layout = non_replaced.layout(
                         layout_context,
@@ -557,6 +570,7 @@ impl HoistedAbsolutelyPositionedBox {
                 absolutely_positioned_box.context.tag(),
                 absolutely_positioned_box.context.style().clone(),
                 fragments,
+                containing_block.style.writing_mode,
                 content_rect,
                 pbm.padding,
                 pbm.border,
@@ -4,7 +4,7 @@
 
 //! Utilities for querying the layout, as needed by the layout thread.
 use crate::context::LayoutContext;
-use crate::flow::FragmentTree;
+use crate::flow::{BoxTree, FragmentTree};
 use crate::fragments::{Fragment, Tag};
 use app_units::Au;
 use euclid::default::{Point2D, Rect};
@@ -15,19 +15,23 @@ use msg::constellation_msg::PipelineId;
 use script_layout_interface::rpc::TextIndexResponse;
 use script_layout_interface::rpc::{ContentBoxResponse, ContentBoxesResponse, LayoutRPC};
 use script_layout_interface::rpc::{NodeGeometryResponse, NodeScrollIdResponse};
-use script_layout_interface::rpc::{OffsetParentResponse, ResolvedStyleResponse};
+use script_layout_interface::rpc::{OffsetParentResponse, PagesResponse, ResolvedStyleResponse};
 use script_layout_interface::wrapper_traits::{
     LayoutNode, ThreadSafeLayoutElement, ThreadSafeLayoutNode,
 };
+use script_layout_interface::{LayoutElementType, LayoutNodeType};
 use script_traits::LayoutMsg as ConstellationMsg;
-use script_traits::UntrustedNodeAddress;
+use script_traits::{PageDisplayList, UntrustedNodeAddress};
 use servo_arc::Arc as ServoArc;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use style::computed_values::display::T as Display;
 use style::computed_values::position::T as Position;
+use style::computed_values::visibility::T as Visibility;
+use style::computed_values::white_space::T as WhiteSpace;
 use style::context::{StyleContext, ThreadLocalStyleContext};
 use style::dom::OpaqueNode;
-use style::dom::TElement;
+use style::dom::{TElement, TNode};
 use style::properties::style_structs::Font;
 use style::properties::{LonghandId, PropertyDeclarationId, PropertyId};
 use style::selector_parser::PseudoElement;
@@ -87,6 +91,12 @@ pub struct LayoutThreadData {
 
     /// A queued response for the viewport dimensions for a given browsing context.
     pub inner_window_dimensions_response: Option<Size2D<f32, CSSPixel>>,
+
+    /// A queued response for the page areas of the pages of the document.
+    pub pages_response: Vec<Rect<Au>>,
+
+    /// A queued response for the display lists of the pages of the document.
+    pub page_display_lists_response: Vec<PageDisplayList>,
 }
 
 pub struct LayoutRPCImpl(pub Arc<Mutex<LayoutThreadData>>);
@@ -173,6 +183,18 @@ impl LayoutRPC for LayoutRPCImpl {
         let rw_data = rw_data.lock().unwrap();
         rw_data.inner_window_dimensions_response.clone()
     }
+
+    fn pages(&self) -> PagesResponse {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let rw_data = rw_data.lock().unwrap();
+        PagesResponse(rw_data.pages_response.clone())
+    }
+
+    fn page_display_lists(&self) -> Vec<PageDisplayList> {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let rw_data = rw_data.lock().unwrap();
+        rw_data.page_display_lists_response.clone()
+    }
 }
 
 pub fn process_content_box_request(
@@ -182,8 +204,65 @@ pub fn process_content_box_request(
     Some(fragment_tree?.get_content_box_for_node(requested_node))
 }
 
-pub fn process_content_boxes_request(_requested_node: OpaqueNode) -> Vec<Rect<Au>> {
-    vec![]
+pub fn process_content_boxes_request(
+    requested_node: OpaqueNode,
+    fragment_tree: Option<Arc<FragmentTree>>,
+) -> Vec<Rect<Au>> {
+    fragment_tree
+        .map(|fragment_tree| fragment_tree.get_content_boxes_for_node(requested_node))
+        .unwrap_or_default()
+}
+
+/// Lays out the document across pages and returns the page area of each of them.
+pub fn process_pages_request<E>(
+    box_tree: Option<Arc<BoxTree>>,
+    context: &LayoutContext,
+    default_page_size: Size2D<f32, CSSPixel>,
+) -> Vec<Rect<Au>>
+where
+    E: TElement,
+{
+    let box_tree = match box_tree {
+        Some(box_tree) => box_tree,
+        None => return vec![],
+    };
+    box_tree
+        .layout_paged::<E>(context, default_page_size)
+        .iter()
+        .map(|page| {
+            let page_area = &page.geometry.page_area;
+            Rect::new(
+                Point2D::new(
+                    Au::from_f32_px(page_area.origin.x.px()),
+                    Au::from_f32_px(page_area.origin.y.px()),
+                ),
+                euclid::default::Size2D::new(
+                    Au::from_f32_px(page_area.size.width.px()),
+                    Au::from_f32_px(page_area.size.height.px()),
+                ),
+            )
+        })
+        .collect()
+}
+
+/// Lays out the document across pages and builds the display list of each of them.
+pub fn process_page_display_lists_request<E>(
+    box_tree: Option<Arc<BoxTree>>,
+    context: &LayoutContext,
+    default_page_size: Size2D<f32, CSSPixel>,
+) -> Vec<PageDisplayList>
+where
+    E: TElement,
+{
+    let box_tree = match box_tree {
+        Some(box_tree) => box_tree,
+        None => return vec![],
+    };
+    box_tree
+        .layout_paged::<E>(context, default_page_size)
+        .iter()
+        .map(|page| page.build_display_list(context.id.to_webrender(), context))
+        .collect()
 }
 
 pub fn process_node_geometry_request(
@@ -206,8 +285,15 @@ pub fn process_node_scroll_id_request<'dom>(
 }
 
 /// https://drafts.csswg.org/cssom-view/#scrolling-area
-pub fn process_node_scroll_area_request(_requested_node: OpaqueNode) -> Rect<i32> {
-    Rect::zero()
+pub fn process_node_scroll_area_request(
+    requested_node: OpaqueNode,
+    fragment_tree: Option<Arc<FragmentTree>>,
+) -> Rect<i32> {
+    if let Some(fragment_tree) = fragment_tree {
+        fragment_tree.get_scrolling_area_for_node(requested_node)
+    } else {
+        Rect::zero()
+    }
 }
 
 /// Return the resolved value of property for a given (pseudo)element.
@@ -305,16 +391,22 @@ pub fn process_resolved_style_request<'dom>(
                 _ => return None,
             };
 
-            let positioned = style.get_box().position != Position::Static;
-            let content_rect = box_fragment
-                .content_rect
-                .to_physical(box_fragment.style.writing_mode, &containing_block);
+            // The insets of sticky boxes resolve to their computed values, since their
+            // used values depend on scrolling.
+            let positioned = match style.get_box().position {
+                Position::Static | Position::Sticky => false,
+                Position::Relative | Position::Absolute | Position::Fixed => true,
+            };
+            let content_rect = box_fragment.content_rect.to_physical(
+                box_fragment.containing_block_writing_mode,
+                &containing_block,
+            );
             let margins = box_fragment
                 .margin
-                .to_physical(box_fragment.style.writing_mode);
+                .to_physical(box_fragment.containing_block_writing_mode);
             let padding = box_fragment
                 .padding
-                .to_physical(box_fragment.style.writing_mode);
+                .to_physical(box_fragment.containing_block_writing_mode);
             match longhand_id {
                 LonghandId::Width => Some(content_rect.size.width),
                 LonghandId::Height => Some(content_rect.size.height),
@@ -375,17 +467,224 @@ pub fn process_resolved_style_request_for_unstyled_node<'dom>(
     style.computed_value_to_string(PropertyDeclarationId::Longhand(longhand_id))
 }
 
-pub fn process_offset_parent_query(_requested_node: OpaqueNode) -> OffsetParentResponse {
-    OffsetParentResponse::empty()
+pub fn process_offset_parent_query(
+    requested_node: OpaqueNode,
+    fragment_tree: Option<Arc<FragmentTree>>,
+) -> OffsetParentResponse {
+    if let Some(fragment_tree) = fragment_tree {
+        fragment_tree.get_offset_parent_for_node(requested_node)
+    } else {
+        OffsetParentResponse::empty()
+    }
+}
+
+enum InnerTextItem {
+    Text(String),
+    RequiredLineBreakCount(u32),
 }
 
 // https://html.spec.whatwg.org/multipage/#the-innertext-idl-attribute
-pub fn process_element_inner_text_query<'dom>(_node: impl LayoutNode<'dom>) -> String {
-    "".to_owned()
+pub fn process_element_inner_text_query<'dom>(node: impl LayoutNode<'dom>) -> String {
+    // Step 1.
+    let mut results = Vec::new();
+    // Step 2.
+    for child in node.dom_children() {
+        inner_text_collection_steps(child, &mut results);
+    }
+    let mut max_req_line_break_count = 0;
+    let mut inner_text = String::new();
+    for item in results {
+        match item {
+            InnerTextItem::Text(s) => {
+                if max_req_line_break_count > 0 {
+                    // Step 5.
+                    for _ in 0..max_req_line_break_count {
+                        inner_text.push('\u{000A}');
+                    }
+                    max_req_line_break_count = 0;
+                }
+                // Step 3.
+                inner_text.push_str(&s);
+            },
+            InnerTextItem::RequiredLineBreakCount(count) => {
+                // Step 4.
+                if inner_text.is_empty() {
+                    // Remove required line break count at the start.
+                    continue;
+                }
+                // Store the count if it's the max of this run, but it may be ignored if no
+                // text item is found afterwards, which means that these are consecutive
+                // line breaks at the end.
+                if count > max_req_line_break_count {
+                    max_req_line_break_count = count;
+                }
+            },
+        }
+    }
+    inner_text
+}
+
+// https://html.spec.whatwg.org/multipage/#inner-text-collection-steps
+fn inner_text_collection_steps<'dom>(
+    node: impl LayoutNode<'dom>,
+    results: &mut Vec<InnerTextItem>,
+) {
+    // Text nodes have no style of their own, they inherit the one of their parent.
+    let style_node = match node.type_id() {
+        LayoutNodeType::Text => match node.parent_node() {
+            Some(parent) => parent,
+            None => return,
+        },
+        _ => node,
+    };
+    let style = match style_node
+        .get_style_and_opaque_layout_data()
+        .and_then(|data| {
+            data.style_data
+                .element_data
+                .borrow()
+                .styles
+                .get_primary()
+                .cloned()
+        }) {
+        Some(style) => style,
+        None => return,
+    };
+
+    // Step 3. The descendants of an element that is not being rendered are not being
+    // rendered either, so there is no need to collect their items.
+    let display = style.get_box().display;
+    if !node.is_connected() || display == Display::None {
+        return;
+    }
+
+    // Step 1.
+    let mut items = Vec::new();
+    for child in node.dom_children() {
+        inner_text_collection_steps(child, &mut items);
+    }
+
+    // Step 2.
+    if style.get_inherited_box().visibility != Visibility::Visible {
+        results.append(&mut items);
+        return;
+    }
+
+    match node.type_id() {
+        LayoutNodeType::Text => {
+            // Step 4.
+            let text = collapse_white_space(
+                &node.to_threadsafe().node_text_content(),
+                style.get_inherited_text().white_space,
+            );
+            if !text.is_empty() {
+                items.push(InnerTextItem::Text(text));
+            }
+        },
+        LayoutNodeType::Element(LayoutElementType::HTMLBRElement) => {
+            // Step 5.
+            items.push(InnerTextItem::Text(String::from(
+                "\u{000A}", /* line feed */
+            )));
+        },
+        LayoutNodeType::Element(LayoutElementType::HTMLParagraphElement) => {
+            // Step 8.
+            items.insert(0, InnerTextItem::RequiredLineBreakCount(2));
+            items.push(InnerTextItem::RequiredLineBreakCount(2));
+        },
+        _ => {},
+    }
+
+    match display {
+        Display::TableCell if !is_last_sibling_with_display(node, display) => {
+            // Step 6.
+            items.push(InnerTextItem::Text(String::from("\u{0009}" /* tab */)));
+        },
+        Display::TableRow if !is_last_sibling_with_display(node, display) => {
+            // Step 7.
+            items.push(InnerTextItem::Text(String::from(
+                "\u{000A}", /* line feed */
+            )));
+        },
+        Display::Block | Display::Flex | Display::TableCaption | Display::Table => {
+            // Step 9.
+            items.insert(0, InnerTextItem::RequiredLineBreakCount(1));
+            items.push(InnerTextItem::RequiredLineBreakCount(1));
+        },
+        _ => {},
+    }
+
+    results.append(&mut items);
 }
 
-pub fn process_text_index_request(_node: OpaqueNode, _point: Point2D<Au>) -> TextIndexResponse {
-    TextIndexResponse(None)
+/// Whether none of the following siblings of this node has the given display type, e.g. whether
+/// this is the last table cell of its table row.
+fn is_last_sibling_with_display<'dom>(node: impl LayoutNode<'dom>, display: Display) -> bool {
+    let mut sibling = node.next_sibling();
+    while let Some(sibling_node) = sibling {
+        let sibling_display = sibling_node
+            .get_style_and_opaque_layout_data()
+            .and_then(|data| {
+                data.style_data
+                    .element_data
+                    .borrow()
+                    .styles
+                    .get_primary()
+                    .map(|style| style.get_box().display)
+            });
+        if sibling_display == Some(display) {
+            return false;
+        }
+        sibling = sibling_node.next_sibling();
+    }
+    true
+}
+
+/// Applies the white space processing rules of the given `white-space` value to the text of a
+/// text node: https://drafts.csswg.org/css-text/#white-space-phase-1
+fn collapse_white_space(text: &str, white_space: WhiteSpace) -> String {
+    if white_space.preserve_spaces() {
+        return text.to_owned();
+    }
+
+    let preserve_newlines = white_space.preserve_newlines();
+    let mut result = String::with_capacity(text.len());
+    let mut pending_space = false;
+    for character in text.chars() {
+        match character {
+            '\n' if preserve_newlines => {
+                result.push(character);
+                pending_space = false;
+            },
+            ' ' | '\t' | '\n' | '\r' => {
+                if !result.ends_with('\n') {
+                    pending_space = true;
+                }
+            },
+            _ => {
+                if pending_space {
+                    result.push(' ');
+                    pending_space = false;
+                }
+                result.push(character);
+            },
+        }
+    }
+    if pending_space {
+        result.push(' ');
+    }
+    result
+}
+
+pub fn process_text_index_request(
+    node: OpaqueNode,
+    point_in_node: Point2D<Au>,
+    fragment_tree: Option<Arc<FragmentTree>>,
+) -> TextIndexResponse {
+    TextIndexResponse(
+        fragment_tree
+            .and_then(|fragment_tree| fragment_tree.get_text_index_for_node(node, point_in_node)),
+    )
 }
 
 pub fn process_resolved_font_style_query<'dom>(
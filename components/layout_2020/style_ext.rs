@@ -34,8 +34,21 @@ pub(crate) enum DisplayGeneratingBox {
         outside: DisplayOutside,
         inside: DisplayInside,
     },
-    // Layout-internal display types go here:
     // https://drafts.csswg.org/css-display-3/#layout-specific-display
+    LayoutInternal(DisplayLayoutInternal),
+}
+
+/// https://drafts.csswg.org/css-display-3/#layout-specific-display
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum DisplayLayoutInternal {
+    TableCaption,
+    TableCell,
+    TableColumn,
+    TableColumnGroup,
+    TableFooterGroup,
+    TableHeaderGroup,
+    TableRow,
+    TableRowGroup,
 }
 
 #[derive(Clone, Copy, Eq, PartialEq)]
@@ -51,6 +64,8 @@ pub(crate) enum DisplayInside {
     Flow { is_list_item: bool },
     FlowRoot { is_list_item: bool },
     Flex,
+    Grid,
+    Table,
 }
 
 /// Percentages resolved but not `auto` margins
@@ -116,6 +131,7 @@ pub(crate) trait ComputedValuesExt {
     fn establishes_containing_block(&self) -> bool;
     fn establishes_containing_block_for_all_descendants(&self) -> bool;
     fn background_is_transparent(&self) -> bool;
+    fn is_multicol_container(&self) -> bool;
     fn get_webrender_primitive_flags(&self) -> wr::PrimitiveFlags;
 }
 
@@ -377,8 +393,7 @@ impl ComputedValuesExt for ComputedValues {
         }
 
         // Fixed position and sticky position always create stacking contexts.
-        // TODO(mrobinson): We need to handle sticky positioning here when we support it.
-        if self.get_box().position == ComputedPosition::Fixed {
+        if let ComputedPosition::Fixed | ComputedPosition::Sticky = self.get_box().position {
             return true;
         }
 
@@ -432,6 +447,13 @@ impl ComputedValuesExt for ComputedValues {
                 .all(|layer| matches!(layer, ComputedImageLayer::None))
     }
 
+    /// Whether this style makes a block container into a multi-column container.
+    /// https://drafts.csswg.org/css-multicol/#multi-column-container
+    fn is_multicol_container(&self) -> bool {
+        let column = self.get_column();
+        !column.column_count.is_auto() || !column.column_width.is_auto()
+    }
+
     /// Generate appropriate WebRender `PrimitiveFlags` that should be used
     /// for display items generated by the `Fragment` which owns this style.
     fn get_webrender_primitive_flags(&self) -> wr::PrimitiveFlags {
@@ -452,14 +474,37 @@ impl From<stylo::Display> for Display {
                 is_list_item: packed.is_list_item(),
             },
             stylo::DisplayInside::Flex => DisplayInside::Flex,
+            stylo::DisplayInside::Grid => DisplayInside::Grid,
+            stylo::DisplayInside::Table => DisplayInside::Table,
 
             // These should not be values of DisplayInside, but oh well
             stylo::DisplayInside::None => return Display::None,
             stylo::DisplayInside::Contents => return Display::Contents,
+
+            stylo::DisplayInside::TableRowGroup => {
+                return DisplayLayoutInternal::TableRowGroup.into()
+            },
+            stylo::DisplayInside::TableColumn => return DisplayLayoutInternal::TableColumn.into(),
+            stylo::DisplayInside::TableColumnGroup => {
+                return DisplayLayoutInternal::TableColumnGroup.into()
+            },
+            stylo::DisplayInside::TableHeaderGroup => {
+                return DisplayLayoutInternal::TableHeaderGroup.into()
+            },
+            stylo::DisplayInside::TableFooterGroup => {
+                return DisplayLayoutInternal::TableFooterGroup.into()
+            },
+            stylo::DisplayInside::TableRow => return DisplayLayoutInternal::TableRow.into(),
+            stylo::DisplayInside::TableCell => return DisplayLayoutInternal::TableCell.into(),
         };
         let outside = match packed.outside() {
             stylo::DisplayOutside::Block => DisplayOutside::Block,
             stylo::DisplayOutside::Inline => DisplayOutside::Inline,
+            stylo::DisplayOutside::TableCaption => {
+                return DisplayLayoutInternal::TableCaption.into()
+            },
+            // Only the internal `DisplayInside` values above use this.
+            stylo::DisplayOutside::InternalTable => unreachable!(),
 
             // This should not be a value of DisplayInside, but oh well
             stylo::DisplayOutside::None => return Display::None,
@@ -468,6 +513,12 @@ impl From<stylo::Display> for Display {
     }
 }
 
+impl From<DisplayLayoutInternal> for Display {
+    fn from(internal: DisplayLayoutInternal) -> Self {
+        Display::GeneratingBox(DisplayGeneratingBox::LayoutInternal(internal))
+    }
+}
+
 fn size_to_length(size: &Size) -> LengthPercentageOrAuto {
     match size {
         Size::LengthPercentage(length) => LengthPercentageOrAuto::LengthPercentage(&length.0),
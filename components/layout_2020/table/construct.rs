@@ -0,0 +1,541 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use super::{Table, TableSlot, TableSlotCell, TableTrack, TableTrackGroup};
+use crate::cell::ArcRefCell;
+use crate::context::LayoutContext;
+use crate::dom_traversal::{
+    BoxSlot, Contents, NodeAndStyleInfo, NodeExt, NonReplacedContents, TraversalEvent,
+    TraversalHandler,
+};
+use crate::element_data::LayoutBox;
+use crate::flow::{BlockFormattingContext, BlockLevelBox};
+use crate::formatting_contexts::IndependentFormattingContext;
+use crate::fragments::Tag;
+use crate::style_ext::{
+    DisplayGeneratingBox, DisplayInside, DisplayLayoutInternal, DisplayOutside,
+};
+use script_layout_interface::wrapper_traits::{ThreadSafeLayoutElement, ThreadSafeLayoutNode};
+use servo_arc::Arc;
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use style::properties::ComputedValues;
+use style::selector_parser::PseudoElement;
+use style::values::specified::text::TextDecorationLine;
+
+impl Table {
+    pub fn construct<'dom>(
+        context: &LayoutContext,
+        info: &NodeAndStyleInfo<impl NodeExt<'dom>>,
+        contents: NonReplacedContents,
+        propagated_text_decoration_line: TextDecorationLine,
+    ) -> Self {
+        let mut builder = TableBuilder::new(context, info, propagated_text_decoration_line);
+        contents.traverse(context, info, &mut builder);
+        builder.finish()
+    }
+
+    /// Construct an anonymous table wrapping internal table boxes that were
+    /// found outside of a table.
+    /// <https://drafts.csswg.org/css2/tables.html#anonymous-boxes>
+    pub fn construct_for_anonymous_children<'dom, Node>(
+        context: &LayoutContext,
+        info: &NodeAndStyleInfo<Node>,
+        children: Vec<TraversalEvent<'dom, Node>>,
+        propagated_text_decoration_line: TextDecorationLine,
+    ) -> Self
+    where
+        Node: NodeExt<'dom>,
+    {
+        let mut builder = TableBuilder::new(context, info, propagated_text_decoration_line);
+        for child in children {
+            child.replay(&mut builder)
+        }
+        builder.finish()
+    }
+}
+
+/// The kind of box whose children are being traversed by a `TableBuilder`.
+#[derive(Clone, Copy, PartialEq)]
+enum CurrentParent {
+    Table,
+    RowGroup,
+    Row,
+    ColumnGroup,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum RowGroupKind {
+    Header,
+    Body,
+    Footer,
+}
+
+struct RowGroupBuilder {
+    /// The tag and style of the row group box, or `None` for the anonymous
+    /// row groups containing the rows that are children of the table.
+    group: Option<(Tag, Arc<ComputedValues>)>,
+    kind: RowGroupKind,
+    rows: Vec<RowBuilder>,
+}
+
+struct RowBuilder {
+    tag: Tag,
+    /// `None` for anonymous rows.
+    style: Option<Arc<ComputedValues>>,
+    cells: Vec<TableSlotCell>,
+}
+
+/// A builder for a table, fixing up its box tree as described in
+/// <https://drafts.csswg.org/css2/tables.html#anonymous-boxes>.
+///
+/// The children of the table, of its row groups, of its rows and of its column
+/// groups are all handled by this builder, `current_parent` telling which kind
+/// of box is being traversed.
+struct TableBuilder<'a, 'dom, Node> {
+    context: &'a LayoutContext<'a>,
+    info: &'a NodeAndStyleInfo<Node>,
+    text_decoration_line: TextDecorationLine,
+    current_parent: CurrentParent,
+    captions: Vec<ArcRefCell<BlockLevelBox>>,
+    column_groups: Vec<TableTrackGroup>,
+    columns: Vec<TableTrack>,
+    row_groups: Vec<RowGroupBuilder>,
+
+    /// The row that cells are currently being added to, which is an anonymous
+    /// row when the current parent is the table or a row group.
+    ongoing_row: Option<RowBuilder>,
+
+    /// The ongoing run of children of the current parent that are not proper
+    /// children of it, which get wrapped in an anonymous cell.
+    ongoing_anonymous_cell: Vec<TraversalEvent<'dom, Node>>,
+}
+
+impl<'a, 'dom, Node: 'dom> TraversalHandler<'dom, Node> for TableBuilder<'a, 'dom, Node>
+where
+    Node: NodeExt<'dom>,
+{
+    fn handle_text(&mut self, info: &NodeAndStyleInfo<Node>, text: Cow<'dom, str>) {
+        if self.current_parent == CurrentParent::ColumnGroup {
+            return;
+        }
+        // White space between internal table boxes is not rendered.
+        if self.ongoing_anonymous_cell.is_empty() &&
+            text.bytes().all(|byte| byte.is_ascii_whitespace())
+        {
+            return;
+        }
+        self.handle_anonymous_cell_child(TraversalEvent::Text(info.clone(), text))
+    }
+
+    /// Or pseudo-element
+    fn handle_element(
+        &mut self,
+        info: &NodeAndStyleInfo<Node>,
+        display: DisplayGeneratingBox,
+        contents: Contents,
+        box_slot: BoxSlot<'dom>,
+    ) {
+        let internal = match display {
+            DisplayGeneratingBox::LayoutInternal(internal) => internal,
+            DisplayGeneratingBox::OutsideInside { .. } => {
+                return self.handle_anonymous_cell_child(TraversalEvent::Element(
+                    info.clone(),
+                    display,
+                    contents,
+                    box_slot,
+                ));
+            },
+        };
+
+        // Columns and captions do not have children to traverse,
+        // or establish a formatting context of their own.
+        match (self.current_parent, internal) {
+            (CurrentParent::Table, DisplayLayoutInternal::TableColumn) |
+            (CurrentParent::ColumnGroup, DisplayLayoutInternal::TableColumn) => {
+                self.handle_column(info);
+                return box_slot.set(LayoutBox::TableLevel);
+            },
+            // Only columns are relevant in a column group, its other children are ignored.
+            (CurrentParent::ColumnGroup, _) => return box_slot.set(LayoutBox::TableLevel),
+            (CurrentParent::Table, DisplayLayoutInternal::TableCaption) => {
+                self.handle_caption(info, contents);
+                return box_slot.set(LayoutBox::TableLevel);
+            },
+            _ => {},
+        }
+
+        let contents = match NonReplacedContents::try_from(contents) {
+            Ok(contents) => contents,
+            Err(replaced) => {
+                // Replaced elements cannot be internal table boxes,
+                // lay them out as blocks in an anonymous cell instead.
+                let display = DisplayGeneratingBox::OutsideInside {
+                    outside: DisplayOutside::Block,
+                    inside: DisplayInside::Flow {
+                        is_list_item: false,
+                    },
+                };
+                return self.handle_anonymous_cell_child(TraversalEvent::Element(
+                    info.clone(),
+                    display,
+                    Contents::Replaced(replaced),
+                    box_slot,
+                ));
+            },
+        };
+
+        match (self.current_parent, internal) {
+            (CurrentParent::Table, DisplayLayoutInternal::TableColumnGroup) => {
+                self.handle_column_group(info, contents)
+            },
+            (CurrentParent::Table, DisplayLayoutInternal::TableHeaderGroup) => {
+                self.handle_row_group(info, RowGroupKind::Header, contents)
+            },
+            (CurrentParent::Table, DisplayLayoutInternal::TableRowGroup) => {
+                self.handle_row_group(info, RowGroupKind::Body, contents)
+            },
+            (CurrentParent::Table, DisplayLayoutInternal::TableFooterGroup) => {
+                self.handle_row_group(info, RowGroupKind::Footer, contents)
+            },
+            (CurrentParent::Table, DisplayLayoutInternal::TableRow) |
+            (CurrentParent::RowGroup, DisplayLayoutInternal::TableRow) => {
+                self.handle_row(info, contents)
+            },
+            (_, DisplayLayoutInternal::TableCell) => self.handle_cell(info, contents),
+            _ => {
+                // Misparented internal table boxes get wrapped in an anonymous
+                // cell, where they are wrapped in an anonymous table in turn.
+                return self.handle_anonymous_cell_child(TraversalEvent::Element(
+                    info.clone(),
+                    display,
+                    contents.into(),
+                    box_slot,
+                ));
+            },
+        }
+        box_slot.set(LayoutBox::TableLevel)
+    }
+}
+
+impl<'a, 'dom, Node: 'dom> TableBuilder<'a, 'dom, Node>
+where
+    Node: NodeExt<'dom>,
+{
+    fn new(
+        context: &'a LayoutContext,
+        info: &'a NodeAndStyleInfo<Node>,
+        propagated_text_decoration_line: TextDecorationLine,
+    ) -> Self {
+        Self {
+            context,
+            info,
+            text_decoration_line: propagated_text_decoration_line |
+                info.style.clone_text_decoration_line(),
+            current_parent: CurrentParent::Table,
+            captions: Vec::new(),
+            column_groups: Vec::new(),
+            columns: Vec::new(),
+            row_groups: Vec::new(),
+            ongoing_row: None,
+            ongoing_anonymous_cell: Vec::new(),
+        }
+    }
+
+    fn handle_caption(&mut self, info: &NodeAndStyleInfo<Node>, contents: Contents) {
+        self.end_ongoing_row();
+        let caption = IndependentFormattingContext::construct(
+            self.context,
+            info,
+            DisplayInside::FlowRoot {
+                is_list_item: false,
+            },
+            contents,
+            self.text_decoration_line,
+        );
+        self.captions
+            .push(ArcRefCell::new(BlockLevelBox::Independent(caption)));
+    }
+
+    fn handle_column_group(
+        &mut self,
+        info: &NodeAndStyleInfo<Node>,
+        contents: NonReplacedContents,
+    ) {
+        self.end_ongoing_row();
+        let first_column = self.columns.len();
+        self.current_parent = CurrentParent::ColumnGroup;
+        contents.traverse(self.context, info, self);
+        self.current_parent = CurrentParent::Table;
+
+        let tag = Tag::from_node_and_style_info(info);
+        if self.columns.len() == first_column {
+            // A column group without columns spans as many columns
+            // as its `span` attribute says.
+            for _ in 0..span_attribute(info) {
+                self.columns.push(TableTrack {
+                    tag,
+                    style: None,
+                    group_index: Some(self.column_groups.len()),
+                })
+            }
+        }
+        self.column_groups.push(TableTrackGroup {
+            tag,
+            style: info.style.clone(),
+            track_range: first_column..self.columns.len(),
+        });
+    }
+
+    fn handle_column(&mut self, info: &NodeAndStyleInfo<Node>) {
+        self.end_ongoing_row();
+        let group_index = match self.current_parent {
+            // The column group is pushed once all of its columns are.
+            CurrentParent::ColumnGroup => Some(self.column_groups.len()),
+            _ => None,
+        };
+        for _ in 0..span_attribute(info) {
+            self.columns.push(TableTrack {
+                tag: Tag::from_node_and_style_info(info),
+                style: Some(info.style.clone()),
+                group_index,
+            })
+        }
+    }
+
+    fn handle_row_group(
+        &mut self,
+        info: &NodeAndStyleInfo<Node>,
+        kind: RowGroupKind,
+        contents: NonReplacedContents,
+    ) {
+        self.end_ongoing_row();
+        self.row_groups.push(RowGroupBuilder {
+            group: Some((Tag::from_node_and_style_info(info), info.style.clone())),
+            kind,
+            rows: Vec::new(),
+        });
+        self.current_parent = CurrentParent::RowGroup;
+        contents.traverse(self.context, info, self);
+        self.end_ongoing_row();
+        self.current_parent = CurrentParent::Table;
+    }
+
+    fn handle_row(&mut self, info: &NodeAndStyleInfo<Node>, contents: NonReplacedContents) {
+        self.end_ongoing_row();
+        self.ongoing_row = Some(RowBuilder {
+            tag: Tag::from_node_and_style_info(info),
+            style: Some(info.style.clone()),
+            cells: Vec::new(),
+        });
+        let parent = std::mem::replace(&mut self.current_parent, CurrentParent::Row);
+        contents.traverse(self.context, info, self);
+        self.current_parent = parent;
+        self.end_ongoing_row();
+    }
+
+    fn handle_cell(&mut self, info: &NodeAndStyleInfo<Node>, contents: NonReplacedContents) {
+        self.end_anonymous_cell();
+        let (colspan, rowspan) = match info.pseudo_element_type {
+            Some(_) => (1, 1),
+            None => {
+                let node = info.node.to_threadsafe();
+                (node.get_colspan(), node.get_rowspan())
+            },
+        };
+        let cell = TableSlotCell {
+            tag: Tag::from_node_and_style_info(info),
+            style: info.style.clone(),
+            contents: BlockFormattingContext::construct(
+                self.context,
+                info,
+                contents,
+                self.text_decoration_line,
+                /* is_list_item = */ false,
+            ),
+            // The limits of https://html.spec.whatwg.org/multipage/#processing-model-2
+            colspan: colspan.max(1).min(1000) as usize,
+            // A `rowspan` of zero makes the cell span the remaining rows
+            // of its row group, see `TableBuilder::finish`.
+            rowspan: rowspan.min(65534) as usize,
+        };
+        self.ongoing_row().cells.push(cell);
+    }
+
+    fn handle_anonymous_cell_child(&mut self, child: TraversalEvent<'dom, Node>) {
+        self.ongoing_row();
+        self.ongoing_anonymous_cell.push(child);
+    }
+
+    fn ongoing_row(&mut self) -> &mut RowBuilder {
+        let tag = Tag::from_node_and_style_info(self.info);
+        self.ongoing_row.get_or_insert_with(|| RowBuilder {
+            tag,
+            style: None,
+            cells: Vec::new(),
+        })
+    }
+
+    fn end_anonymous_cell(&mut self) {
+        if self.ongoing_anonymous_cell.is_empty() {
+            return;
+        }
+
+        let children = std::mem::take(&mut self.ongoing_anonymous_cell);
+        let row_style = self.ongoing_row.as_ref().and_then(|row| row.style.clone());
+        let anonymous_style = self
+            .context
+            .shared_context()
+            .stylist
+            .style_for_anonymous::<Node::ConcreteElement>(
+                &self.context.shared_context().guards,
+                &PseudoElement::ServoAnonymousTableCell,
+                row_style.as_ref().unwrap_or(&self.info.style),
+            );
+        let info = self.info.new_replacing_style(anonymous_style);
+        let cell = TableSlotCell {
+            tag: Tag::from_node_and_style_info(&info),
+            contents: BlockFormattingContext::construct_for_anonymous_children(
+                self.context,
+                &info,
+                children,
+                self.text_decoration_line,
+            ),
+            style: info.style,
+            colspan: 1,
+            rowspan: 1,
+        };
+        self.ongoing_row().cells.push(cell);
+    }
+
+    fn end_ongoing_row(&mut self) {
+        self.end_anonymous_cell();
+        let row = match self.ongoing_row.take() {
+            Some(row) => row,
+            None => return,
+        };
+        match self.row_groups.last_mut() {
+            // Rows in a row group, including anonymous ones, belong to that group.
+            Some(group) if self.current_parent == CurrentParent::RowGroup => group.rows.push(row),
+            // Consecutive rows that are children of the table share an anonymous group.
+            Some(group) if group.group.is_none() => group.rows.push(row),
+            _ => self.row_groups.push(RowGroupBuilder {
+                group: None,
+                kind: RowGroupKind::Body,
+                rows: vec![row],
+            }),
+        }
+    }
+
+    fn finish(mut self) -> Table {
+        self.end_ongoing_row();
+
+        // The first header group is laid out before all other row groups,
+        // and the first footer group after them.
+        let mut groups = self.row_groups;
+        if let Some(index) = groups
+            .iter()
+            .position(|group| group.kind == RowGroupKind::Header)
+        {
+            let header = groups.remove(index);
+            groups.insert(0, header);
+        }
+        if let Some(index) = groups
+            .iter()
+            .position(|group| group.kind == RowGroupKind::Footer)
+        {
+            let footer = groups.remove(index);
+            groups.push(footer);
+        }
+
+        // https://html.spec.whatwg.org/multipage/#forming-a-table
+        let mut row_groups = Vec::new();
+        let mut rows = Vec::new();
+        let mut slots: Vec<Vec<TableSlot>> = Vec::new();
+        for group in groups {
+            let group_index = group.group.as_ref().map(|_| row_groups.len());
+            let first_row = rows.len();
+            let end_row = first_row + group.rows.len();
+            slots.resize_with(end_row, Vec::new);
+            for row in group.rows {
+                let row_index = rows.len();
+                rows.push(TableTrack {
+                    tag: row.tag,
+                    style: row.style,
+                    group_index,
+                });
+                let mut column = 0;
+                for mut cell in row.cells {
+                    while let Some(TableSlot::Cell(_)) | Some(TableSlot::Spanned) =
+                        slots[row_index].get(column)
+                    {
+                        column += 1;
+                    }
+                    // Cells do not span beyond the end of their row group.
+                    if cell.rowspan == 0 || row_index + cell.rowspan > end_row {
+                        cell.rowspan = end_row - row_index;
+                    }
+                    for spanned_row in &mut slots[row_index..row_index + cell.rowspan] {
+                        if spanned_row.len() < column + cell.colspan {
+                            spanned_row.resize_with(column + cell.colspan, || TableSlot::Empty);
+                        }
+                        for slot in &mut spanned_row[column..column + cell.colspan] {
+                            if let TableSlot::Empty = slot {
+                                *slot = TableSlot::Spanned;
+                            }
+                        }
+                    }
+                    let colspan = cell.colspan;
+                    slots[row_index][column] = TableSlot::Cell(cell);
+                    column += colspan;
+                }
+            }
+            if let Some((tag, style)) = group.group {
+                row_groups.push(TableTrackGroup {
+                    tag,
+                    style,
+                    track_range: first_row..end_row,
+                });
+            }
+        }
+
+        let column_count = slots
+            .iter()
+            .map(Vec::len)
+            .chain(std::iter::once(self.columns.len()))
+            .max()
+            .unwrap_or(0);
+        for row in &mut slots {
+            row.resize_with(column_count, || TableSlot::Empty);
+        }
+
+        Table {
+            style: self.info.style.clone(),
+            captions: self.captions,
+            column_groups: self.column_groups,
+            columns: self.columns,
+            row_groups,
+            rows,
+            slots,
+        }
+    }
+}
+
+/// The number of columns spanned by a column or a column group without columns.
+/// <https://html.spec.whatwg.org/multipage/#attr-col-span>
+fn span_attribute<'dom>(info: &NodeAndStyleInfo<impl NodeExt<'dom>>) -> usize {
+    if info.pseudo_element_type.is_some() {
+        return 1;
+    }
+    info.node
+        .to_threadsafe()
+        .as_element()
+        .and_then(|element| {
+            element
+                .get_attr(&ns!(), &local_name!("span"))
+                .and_then(|span| span.trim().parse::<usize>().ok())
+        })
+        .filter(|span| *span > 0)
+        .map_or(1, |span| span.min(1000))
+}
@@ -0,0 +1,942 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! https://drafts.csswg.org/css-tables/#table-layout-algorithm
+
+use super::{Table, TableSlot, TableSlotCell};
+use crate::context::LayoutContext;
+use crate::formatting_contexts::IndependentLayout;
+use crate::fragments::{AnonymousFragment, BoxFragment, CollapsedBlockMargins, Fragment, Tag};
+use crate::geom::flow_relative::{Rect, Sides, Vec2};
+use crate::positioned::PositioningContext;
+use crate::sizing::{self, ContentSizes};
+use crate::style_ext::ComputedValuesExt;
+use crate::ContainingBlock;
+use servo_arc::Arc;
+use style::computed_values::border_collapse::T as BorderCollapse;
+use style::computed_values::caption_side::T as CaptionSide;
+use style::computed_values::empty_cells::T as EmptyCells;
+use style::computed_values::table_layout::T as TableLayoutMode;
+use style::logical_geometry::WritingMode;
+use style::properties::longhands::box_sizing::computed_value::T as BoxSizing;
+use style::properties::ComputedValues;
+use style::values::computed::{Length, LengthOrAuto};
+use style::Zero;
+
+/// The intrinsic inline sizes of a column of the grid, border-box sizes of its cells.
+#[derive(Clone)]
+struct ColumnMeasure {
+    min_content: Length,
+    max_content: Length,
+
+    /// The largest percentage of the table width requested by the column or its cells.
+    percentage: f32,
+
+    /// Whether the column or one of its cells has a `width` that is a length,
+    /// in which case the column does not grow beyond its max-content width
+    /// before auto columns do.
+    has_length_width: bool,
+}
+
+impl ColumnMeasure {
+    fn zero() -> Self {
+        Self {
+            min_content: Length::zero(),
+            max_content: Length::zero(),
+            percentage: 0.,
+            has_length_width: false,
+        }
+    }
+}
+
+/// The parameters of the layout of a table that do not depend on its inline size.
+struct TableLayout<'a> {
+    table: &'a Table,
+    writing_mode: WritingMode,
+    collapses_borders: bool,
+    border_spacing: Vec2<Length>,
+
+    /// For each slot of the grid, the row and column of the slot where
+    /// the cell covering it starts, if any.
+    cell_origins: Vec<Vec<Option<(usize, usize)>>>,
+}
+
+impl Table {
+    pub fn inline_content_sizes(
+        &self,
+        layout_context: &LayoutContext,
+        writing_mode: WritingMode,
+    ) -> ContentSizes {
+        let layout = TableLayout::new(self, writing_mode);
+        let grid_sizes = if layout.uses_fixed_layout() {
+            let width = layout
+                .fixed_column_widths(Length::zero())
+                .into_iter()
+                .map(|width| width.unwrap_or_else(Length::zero))
+                .sum::<Length>() +
+                layout.total_border_spacing().inline;
+            ContentSizes {
+                min_content: width,
+                max_content: width,
+            }
+        } else {
+            layout.grid_content_sizes(&layout.column_measures(layout_context))
+        };
+        self.captions.iter().fold(grid_sizes, |sizes, caption| {
+            sizes.max(
+                caption
+                    .borrow_mut()
+                    .inline_content_sizes(layout_context, writing_mode),
+            )
+        })
+    }
+
+    pub fn layout(
+        &self,
+        layout_context: &LayoutContext,
+        positioning_context: &mut PositioningContext,
+        containing_block: &ContainingBlock,
+        tree_rank: usize,
+    ) -> IndependentLayout {
+        let layout = TableLayout::new(self, containing_block.style.writing_mode);
+        let mut content_block_size = Length::zero();
+        let fragments =
+            positioning_context.adjust_static_positions(tree_rank, |positioning_context| {
+                layout.layout(
+                    layout_context,
+                    positioning_context,
+                    containing_block,
+                    &mut content_block_size,
+                )
+            });
+        IndependentLayout {
+            fragments,
+            content_block_size,
+        }
+    }
+
+    /// The number of columns of the grid.
+    fn column_count(&self) -> usize {
+        self.slots.first().map_or(self.columns.len(), Vec::len)
+    }
+}
+
+impl<'a> TableLayout<'a> {
+    fn new(table: &'a Table, writing_mode: WritingMode) -> Self {
+        let inherited_table = table.style.get_inherited_table();
+        let collapses_borders = inherited_table.border_collapse == BorderCollapse::Collapse;
+        let border_spacing = if collapses_borders {
+            Vec2::zero()
+        } else {
+            let horizontal = Length::from(inherited_table.border_spacing.horizontal());
+            let vertical = Length::from(inherited_table.border_spacing.vertical());
+            if writing_mode.is_horizontal() {
+                Vec2 {
+                    inline: horizontal,
+                    block: vertical,
+                }
+            } else {
+                Vec2 {
+                    inline: vertical,
+                    block: horizontal,
+                }
+            }
+        };
+
+        let mut cell_origins = vec![vec![None; table.column_count()]; table.rows.len()];
+        for (row, column, cell) in table.cells() {
+            for origins in &mut cell_origins[row..row + cell.rowspan] {
+                for origin in &mut origins[column..column + cell.colspan] {
+                    *origin = Some((row, column))
+                }
+            }
+        }
+
+        Self {
+            table,
+            writing_mode,
+            collapses_borders,
+            border_spacing,
+            cell_origins,
+        }
+    }
+
+    /// Whether the fixed table layout algorithm is used, which is the case
+    /// when the table has `table-layout: fixed` and its width is not `auto`.
+    /// <https://drafts.csswg.org/css-tables/#fixed-layout>
+    fn uses_fixed_layout(&self) -> bool {
+        let style = &self.table.style;
+        style.get_table().table_layout == TableLayoutMode::Fixed &&
+            !style.box_size(self.writing_mode).inline.is_auto()
+    }
+
+    /// The border spacing between the columns and the rows of the grid,
+    /// and around them.
+    fn total_border_spacing(&self) -> Vec2<Length> {
+        let spacing = |count: usize, spacing: Length| {
+            if count == 0 {
+                Length::zero()
+            } else {
+                spacing * (count + 1) as f32
+            }
+        };
+        Vec2 {
+            inline: spacing(self.table.column_count(), self.border_spacing.inline),
+            block: spacing(self.table.rows.len(), self.border_spacing.block),
+        }
+    }
+
+    fn cell_at(&self, row: usize, column: usize) -> Option<&'a TableSlotCell> {
+        let (row, column) = self.cell_origins[row][column]?;
+        match &self.table.slots[row][column] {
+            TableSlot::Cell(cell) => Some(cell),
+            _ => unreachable!(),
+        }
+    }
+
+    /// The used border widths of a cell.
+    ///
+    /// In the collapsing border model, the border between two cells is shared
+    /// by both, and each of them gets half of the widest of their borders.
+    /// At the edges of the table, the cell border only extends beyond
+    /// the border of the table.
+    /// <https://drafts.csswg.org/css-tables/#border-conflict-resolution>
+    fn cell_border(&self, row: usize, column: usize, cell: &TableSlotCell) -> Sides<Length> {
+        let own = cell.style.border_width(self.writing_mode);
+        if !self.collapses_borders {
+            return own;
+        }
+
+        // FIXME: The borders of rows, row groups, columns and column groups
+        // should take part in the conflict resolution, and so should `border-style`.
+        let table = self.table.style.border_width(self.writing_mode);
+        let rows = row..row + cell.rowspan;
+        let columns = column..column + cell.colspan;
+        let outer = |own: Length, table: Length| (own - table).max(Length::zero());
+        let shared = |own: Length, neighbours: &mut dyn Iterator<Item = Length>| {
+            neighbours.fold(own, Length::max) / 2.
+        };
+        let neighbour_border = |row: usize, column: usize| {
+            self.cell_at(row, column)
+                .map(|cell| cell.style.border_width(self.writing_mode))
+        };
+
+        let inline_start = if column == 0 {
+            outer(own.inline_start, table.inline_start)
+        } else {
+            shared(
+                own.inline_start,
+                &mut rows
+                    .clone()
+                    .filter_map(|row| neighbour_border(row, column - 1))
+                    .map(|border| border.inline_end),
+            )
+        };
+        let inline_end = if columns.end == self.table.column_count() {
+            outer(own.inline_end, table.inline_end)
+        } else {
+            shared(
+                own.inline_end,
+                &mut rows
+                    .clone()
+                    .filter_map(|row| neighbour_border(row, columns.end))
+                    .map(|border| border.inline_start),
+            )
+        };
+        let block_start = if row == 0 {
+            outer(own.block_start, table.block_start)
+        } else {
+            shared(
+                own.block_start,
+                &mut columns
+                    .clone()
+                    .filter_map(|column| neighbour_border(row - 1, column))
+                    .map(|border| border.block_end),
+            )
+        };
+        let block_end = if rows.end == self.table.rows.len() {
+            outer(own.block_end, table.block_end)
+        } else {
+            shared(
+                own.block_end,
+                &mut columns
+                    .clone()
+                    .filter_map(|column| neighbour_border(rows.end, column))
+                    .map(|border| border.block_start),
+            )
+        };
+        Sides {
+            inline_start,
+            inline_end,
+            block_start,
+            block_end,
+        }
+    }
+
+    /// The outer inline content sizes of a cell, and the percentage of the
+    /// table width its `width` requests.
+    fn cell_content_sizes(
+        &self,
+        layout_context: &LayoutContext,
+        row: usize,
+        column: usize,
+        cell: &TableSlotCell,
+    ) -> (ContentSizes, f32) {
+        let mut sizes = sizing::outer_inline(&cell.style, self.writing_mode, || {
+            cell.contents
+                .contents
+                .inline_content_sizes(layout_context, cell.style.writing_mode)
+        });
+        if self.collapses_borders {
+            let computed = cell.style.border_width(self.writing_mode).inline_sum();
+            let used = self.cell_border(row, column, cell).inline_sum();
+            sizes = sizes.map(|size| size - computed + used);
+        }
+        let percentage = cell
+            .style
+            .box_size(self.writing_mode)
+            .inline
+            .non_auto()
+            .and_then(|width| width.to_percentage())
+            .map_or(0., |percentage| percentage.0);
+        (sizes, percentage)
+    }
+
+    /// <https://drafts.csswg.org/css-tables/#computing-column-measures>
+    fn column_measures(&self, layout_context: &LayoutContext) -> Vec<ColumnMeasure> {
+        let mut measures = vec![ColumnMeasure::zero(); self.table.column_count()];
+
+        // The `width` of columns is a minimum for the max-content width of their cells.
+        // FIXME: The `width` of column groups should be taken into account too.
+        for (measure, column) in measures.iter_mut().zip(&self.table.columns) {
+            let width = match &column.style {
+                Some(style) => style.box_size(self.writing_mode).inline.non_auto(),
+                None => None,
+            };
+            if let Some(width) = width {
+                if let Some(length) = width.to_length() {
+                    measure.max_content = length;
+                    measure.has_length_width = true;
+                }
+                if let Some(percentage) = width.to_percentage() {
+                    measure.percentage = percentage.0;
+                }
+            }
+        }
+
+        // Cells spanning a single column first, then cells spanning more
+        // columns distribute what their columns lack in order to fit them.
+        let mut spanning_cells = Vec::new();
+        for (row, column, cell) in self.table.cells() {
+            if cell.colspan > 1 {
+                spanning_cells.push((row, column, cell));
+                continue;
+            }
+            let (sizes, percentage) = self.cell_content_sizes(layout_context, row, column, cell);
+            let measure = &mut measures[column];
+            measure.min_content = measure.min_content.max(sizes.min_content);
+            measure.max_content = measure.max_content.max(sizes.max_content);
+            measure.percentage = measure.percentage.max(percentage);
+            measure.has_length_width |= cell.style.inline_size_is_length();
+        }
+        for measure in &mut measures {
+            if measure.has_length_width {
+                // The max-content width of a column with a specified width is
+                // that width, unless its cells cannot be made that narrow.
+                measure.max_content = measure.max_content.max(measure.min_content);
+            }
+        }
+
+        spanning_cells.sort_by_key(|(_, _, cell)| cell.colspan);
+        for (row, column, cell) in spanning_cells {
+            let (sizes, _) = self.cell_content_sizes(layout_context, row, column, cell);
+            let spanned = &mut measures[column..column + cell.colspan];
+            let spacing = self.border_spacing.inline * (cell.colspan - 1) as f32;
+            let weights: Vec<Length> = spanned.iter().map(|measure| measure.max_content).collect();
+
+            let current_min = spanned
+                .iter()
+                .map(|measure| measure.min_content)
+                .sum::<Length>() +
+                spacing;
+            let mut min_contents: Vec<Length> =
+                spanned.iter().map(|measure| measure.min_content).collect();
+            distribute(&mut min_contents, &weights, sizes.min_content - current_min);
+
+            let current_max = spanned
+                .iter()
+                .map(|measure| measure.max_content)
+                .sum::<Length>() +
+                spacing;
+            let mut max_contents: Vec<Length> =
+                spanned.iter().map(|measure| measure.max_content).collect();
+            distribute(&mut max_contents, &weights, sizes.max_content - current_max);
+
+            for ((measure, min_content), max_content) in
+                spanned.iter_mut().zip(min_contents).zip(max_contents)
+            {
+                measure.min_content = min_content;
+                measure.max_content = max_content.max(min_content);
+            }
+        }
+
+        measures
+    }
+
+    /// The intrinsic inline sizes of the grid, including border spacing.
+    fn grid_content_sizes(&self, measures: &[ColumnMeasure]) -> ContentSizes {
+        let min_content = measures.iter().map(|measure| measure.min_content).sum();
+        let mut max_content: Length = measures.iter().map(|measure| measure.max_content).sum();
+
+        // Percentage columns need the table to be wide enough for them to get
+        // their share while the other columns get their max-content width.
+        let total_percentage = measures
+            .iter()
+            .map(|measure| measure.percentage)
+            .sum::<f32>()
+            .min(1.);
+        let mut non_percentage_max_content = Length::zero();
+        for measure in measures {
+            if measure.percentage > 0. {
+                max_content = max_content.max(measure.max_content / measure.percentage);
+            } else {
+                non_percentage_max_content += measure.max_content;
+            }
+        }
+        // FIXME: When the percentages add up to 100% or more and other columns
+        // are not empty, browsers make the max-content width of the table infinite.
+        if total_percentage < 1. {
+            max_content = max_content.max(non_percentage_max_content / (1. - total_percentage));
+        }
+
+        let spacing = self.total_border_spacing().inline;
+        ContentSizes {
+            min_content: min_content + spacing,
+            max_content: max_content + spacing,
+        }
+    }
+
+    /// Distribute the inline size available to the columns of an automatic
+    /// layout table among them.
+    /// <https://drafts.csswg.org/css-tables/#width-distribution>
+    fn auto_column_widths(&self, measures: &[ColumnMeasure], available: Length) -> Vec<Length> {
+        let mut widths: Vec<Length> = measures.iter().map(|measure| measure.min_content).collect();
+        let mut remaining = available - widths.iter().copied().sum::<Length>();
+        if remaining <= Length::zero() {
+            return widths;
+        }
+
+        // Percentage columns get their share of the table first.
+        for (width, measure) in widths.iter_mut().zip(measures) {
+            if measure.percentage > 0. {
+                let wanted = (available * measure.percentage).max(*width);
+                let extra = (wanted - *width).min(remaining);
+                *width += extra;
+                remaining = remaining - extra;
+            }
+        }
+
+        // Then the other columns grow toward their max-content width,
+        // in proportion to how much they can grow.
+        let growth: Vec<Length> = widths
+            .iter()
+            .zip(measures)
+            .map(|(width, measure)| {
+                if measure.percentage > 0. {
+                    Length::zero()
+                } else {
+                    (measure.max_content - *width).max(Length::zero())
+                }
+            })
+            .collect();
+        let total_growth: Length = growth.iter().copied().sum();
+        if total_growth > Length::zero() {
+            let ratio = (remaining.px() / total_growth.px()).min(1.);
+            for (width, growth) in widths.iter_mut().zip(&growth) {
+                *width += *growth * ratio;
+            }
+            remaining = remaining - total_growth * ratio;
+        }
+        if remaining <= Length::zero() {
+            return widths;
+        }
+
+        // Any space left goes to the columns without a specified width if
+        // there are some, to the columns without a percentage width otherwise,
+        // and to all columns as a last resort.
+        let filters: [&dyn Fn(&ColumnMeasure) -> bool; 3] = [
+            &|measure| measure.percentage == 0. && !measure.has_length_width,
+            &|measure| measure.percentage == 0.,
+            &|_| true,
+        ];
+        for filter in filters.iter() {
+            if !measures.iter().any(|measure| filter(measure)) {
+                continue;
+            }
+            let mut weights: Vec<Length> = measures
+                .iter()
+                .map(|measure| {
+                    if filter(measure) {
+                        measure.max_content
+                    } else {
+                        Length::zero()
+                    }
+                })
+                .collect();
+            if weights.iter().all(|weight| *weight == Length::zero()) {
+                // The selected columns are all empty, they grow equally.
+                for (weight, measure) in weights.iter_mut().zip(measures) {
+                    if filter(measure) {
+                        *weight = Length::new(1.)
+                    }
+                }
+            }
+            distribute(&mut widths, &weights, remaining);
+            break;
+        }
+        widths
+    }
+
+    /// The column widths given by the `width` of the columns and of the cells
+    /// of the first row, with no space distributed to the other columns yet.
+    /// <https://drafts.csswg.org/css-tables/#fixed-layout>
+    fn fixed_column_widths(&self, available: Length) -> Vec<Option<Length>> {
+        let mut widths = vec![None; self.table.column_count()];
+        for (width, column) in widths.iter_mut().zip(&self.table.columns) {
+            if let Some(style) = &column.style {
+                *width = style
+                    .box_size(self.writing_mode)
+                    .inline
+                    .percentage_relative_to(available)
+                    .non_auto();
+            }
+        }
+        if let Some(first_row) = self.table.slots.first() {
+            for (column, slot) in first_row.iter().enumerate() {
+                let cell = match slot {
+                    TableSlot::Cell(cell) => cell,
+                    _ => continue,
+                };
+                let width = match cell
+                    .style
+                    .box_size(self.writing_mode)
+                    .inline
+                    .percentage_relative_to(available)
+                    .non_auto()
+                {
+                    Some(width) => width,
+                    None => continue,
+                };
+                let padding_border = self.cell_padding(cell, available).inline_sum() +
+                    self.cell_border(0, column, cell).inline_sum();
+                let width = match cell.style.get_position().box_sizing {
+                    BoxSizing::ContentBox => width + padding_border,
+                    BoxSizing::BorderBox => width.max(padding_border),
+                };
+                // A spanning cell shares its width equally among its columns.
+                let spacing = self.border_spacing.inline * (cell.colspan - 1) as f32;
+                let share = (width - spacing).max(Length::zero()) / cell.colspan as f32;
+                for column_width in &mut widths[column..column + cell.colspan] {
+                    if column_width.is_none() {
+                        *column_width = Some(share)
+                    }
+                }
+            }
+        }
+        widths
+    }
+
+    fn fixed_layout_column_widths(&self, available: Length) -> Vec<Length> {
+        let specified = self.fixed_column_widths(available);
+        let mut widths: Vec<Length> = specified
+            .iter()
+            .map(|width| width.unwrap_or_else(Length::zero))
+            .collect();
+        let remaining = available - widths.iter().copied().sum::<Length>();
+        if remaining <= Length::zero() {
+            return widths;
+        }
+        // The columns without a specified width share the remaining space equally,
+        // or all columns grow in proportion to their width if there are none.
+        let weights: Vec<Length> = if specified.iter().any(Option::is_none) {
+            specified
+                .iter()
+                .map(|width| match width {
+                    Some(_) => Length::zero(),
+                    None => Length::new(1.),
+                })
+                .collect()
+        } else {
+            widths.clone()
+        };
+        distribute(&mut widths, &weights, remaining);
+        widths
+    }
+
+    fn cell_padding(
+        &self,
+        cell: &TableSlotCell,
+        containing_block_inline_size: Length,
+    ) -> Sides<Length> {
+        cell.style
+            .padding(self.writing_mode)
+            .percentages_relative_to(containing_block_inline_size)
+    }
+
+    fn layout(
+        &self,
+        layout_context: &LayoutContext,
+        positioning_context: &mut PositioningContext,
+        containing_block: &ContainingBlock,
+        content_block_size: &mut Length,
+    ) -> Vec<Fragment> {
+        let table = self.table;
+        let mut fragments = Vec::new();
+
+        // FIXME: Captions should be laid out outside of the table box,
+        // in the table wrapper box.
+        let caption_containing_block = ContainingBlock {
+            inline_size: containing_block.inline_size,
+            block_size: LengthOrAuto::Auto,
+            style: containing_block.style,
+        };
+        let mut top_captions_block_size = Length::zero();
+        let mut bottom_captions_block_size = Length::zero();
+        for caption in &table.captions {
+            let mut caption = caption.borrow_mut();
+            let mut fragment = caption.layout(
+                layout_context,
+                positioning_context,
+                &caption_containing_block,
+                fragments.len(),
+                None,
+            );
+            if let Fragment::Box(fragment) = &mut fragment {
+                let block_size = fragment.border_rect().size.block + fragment.margin.block_sum();
+                // Bottom captions are moved below the grid once its size is known.
+                let offset = match fragment.style.get_inherited_table().caption_side {
+                    CaptionSide::Top => &mut top_captions_block_size,
+                    CaptionSide::Bottom => &mut bottom_captions_block_size,
+                };
+                fragment.content_rect.start_corner.block += *offset;
+                *offset += block_size;
+            }
+            fragments.push(fragment);
+        }
+
+        // https://drafts.csswg.org/css-tables/#width-distribution
+        let total_spacing = self.total_border_spacing();
+        let available = (containing_block.inline_size - total_spacing.inline).max(Length::zero());
+        let column_widths = if self.uses_fixed_layout() {
+            self.fixed_layout_column_widths(available)
+        } else {
+            self.auto_column_widths(&self.column_measures(layout_context), available)
+        };
+
+        // The fragments of the column groups, columns, row groups and rows come
+        // before those of the cells, which are laid out first.
+        let first_cell_index = fragments.len() +
+            table.column_groups.len() +
+            table
+                .columns
+                .iter()
+                .filter(|column| column.style.is_some())
+                .count() +
+            table.row_groups.len() +
+            table.rows.iter().filter(|row| row.style.is_some()).count();
+
+        // https://drafts.csswg.org/css-tables/#row-layout
+        let mut row_sizes: Vec<Length> = table
+            .rows
+            .iter()
+            .map(|row| {
+                row.style
+                    .as_ref()
+                    .and_then(|style| style.box_size(self.writing_mode).block.non_auto())
+                    .and_then(|height| height.to_length())
+                    .unwrap_or_else(Length::zero)
+            })
+            .collect();
+        let mut laid_out_cells = Vec::new();
+        for (index, (row, column, cell)) in table.cells().enumerate() {
+            let tree_rank = first_cell_index + index;
+            let inline_size = column_widths[column..column + cell.colspan]
+                .iter()
+                .copied()
+                .sum::<Length>() +
+                self.border_spacing.inline * (cell.colspan - 1) as f32;
+            let padding = self.cell_padding(cell, containing_block.inline_size);
+            let border = self.cell_border(row, column, cell);
+            let padding_border = &padding + &border;
+            let cell_containing_block = ContainingBlock {
+                inline_size: (inline_size - padding_border.inline_sum()).max(Length::zero()),
+                block_size: LengthOrAuto::Auto,
+                style: &cell.style,
+            };
+
+            let mut is_empty = false;
+            let fragment = positioning_context.layout_maybe_position_relative_fragment(
+                layout_context,
+                containing_block,
+                &cell.style,
+                |positioning_context| {
+                    let layout = cell.contents.layout(
+                        layout_context,
+                        positioning_context,
+                        &cell_containing_block,
+                        tree_rank,
+                    );
+                    is_empty = layout.fragments.is_empty();
+                    let content_rect = Rect {
+                        start_corner: Vec2 {
+                            inline: padding_border.inline_start,
+                            block: padding_border.block_start,
+                        },
+                        size: Vec2 {
+                            inline: cell_containing_block.inline_size,
+                            block: layout.content_block_size,
+                        },
+                    };
+                    // FIXME: Absolutely positioned descendants of a positioned cell are
+                    // laid out before the cell is stretched to the size of its rows.
+                    BoxFragment::new(
+                        cell.tag,
+                        cell.style.clone(),
+                        layout.fragments,
+                        self.writing_mode,
+                        content_rect,
+                        padding,
+                        border,
+                        Sides::zero(),
+                        CollapsedBlockMargins::zero(),
+                    )
+                },
+            );
+
+            let padding_border_block_sum =
+                fragment.padding.block_sum() + fragment.border.block_sum();
+            let mut block_size = fragment.content_rect.size.block + padding_border_block_sum;
+            if let Some(height) = cell
+                .style
+                .box_size(self.writing_mode)
+                .block
+                .non_auto()
+                .and_then(|height| height.to_length())
+            {
+                block_size = block_size.max(match cell.style.get_position().box_sizing {
+                    BoxSizing::ContentBox => height + padding_border_block_sum,
+                    BoxSizing::BorderBox => height,
+                });
+            }
+            if cell.rowspan == 1 {
+                row_sizes[row] = row_sizes[row].max(block_size);
+            }
+            laid_out_cells.push((row, column, cell, block_size, is_empty, fragment));
+        }
+
+        // Cells spanning several rows distribute what their rows lack in order
+        // to fit them equally among these rows.
+        for (row, _, cell, block_size, _, _) in &laid_out_cells {
+            if cell.rowspan == 1 {
+                continue;
+            }
+            let spanned = &mut row_sizes[*row..*row + cell.rowspan];
+            let current = spanned.iter().copied().sum::<Length>() +
+                self.border_spacing.block * (cell.rowspan - 1) as f32;
+            if *block_size > current {
+                let share = (*block_size - current) / cell.rowspan as f32;
+                for size in spanned {
+                    *size += share;
+                }
+            }
+        }
+
+        // The rows grow equally to fill the block size of the table.
+        let grid_block_size = row_sizes.iter().copied().sum::<Length>() + total_spacing.block;
+        if let LengthOrAuto::LengthPercentage(table_block_size) = containing_block.block_size {
+            let extra = table_block_size -
+                top_captions_block_size -
+                bottom_captions_block_size -
+                grid_block_size;
+            if extra > Length::zero() && !row_sizes.is_empty() {
+                let share = extra / row_sizes.len() as f32;
+                for size in &mut row_sizes {
+                    *size += share;
+                }
+            }
+        }
+        let grid_block_size = row_sizes.iter().copied().sum::<Length>() + total_spacing.block;
+
+        let column_offsets =
+            track_offsets(&column_widths, self.border_spacing.inline, Length::zero());
+        let row_offsets = track_offsets(
+            &row_sizes,
+            self.border_spacing.block,
+            top_captions_block_size,
+        );
+        let track_rect = |columns: std::ops::Range<usize>, rows: std::ops::Range<usize>| {
+            let start = Vec2 {
+                inline: column_offsets[columns.start],
+                block: row_offsets[rows.start],
+            };
+            let end = Vec2 {
+                inline: column_offsets[columns.end] - self.border_spacing.inline,
+                block: row_offsets[rows.end] - self.border_spacing.block,
+            };
+            Rect {
+                size: Vec2 {
+                    inline: (end.inline - start.inline).max(Length::zero()),
+                    block: (end.block - start.block).max(Length::zero()),
+                },
+                start_corner: start,
+            }
+        };
+
+        for fragment in &mut fragments {
+            if let Fragment::Box(fragment) = fragment {
+                if fragment.style.get_inherited_table().caption_side == CaptionSide::Bottom {
+                    fragment.content_rect.start_corner.block +=
+                        top_captions_block_size + grid_block_size;
+                }
+            }
+        }
+
+        // FIXME: Rows, row groups, columns and column groups do not have borders
+        // in the collapsing border model, they should be taken into account when
+        // resolving the borders of cells instead.
+        let all_rows = 0..table.rows.len();
+        let all_columns = 0..table.column_count();
+        for group in &table.column_groups {
+            fragments.push(track_fragment(
+                group.tag,
+                &group.style,
+                self.writing_mode,
+                track_rect(group.track_range.clone(), all_rows.clone()),
+            ));
+        }
+        for (index, column) in table.columns.iter().enumerate() {
+            if let Some(style) = &column.style {
+                fragments.push(track_fragment(
+                    column.tag,
+                    style,
+                    self.writing_mode,
+                    track_rect(index..index + 1, all_rows.clone()),
+                ));
+            }
+        }
+        for group in &table.row_groups {
+            fragments.push(track_fragment(
+                group.tag,
+                &group.style,
+                self.writing_mode,
+                track_rect(all_columns.clone(), group.track_range.clone()),
+            ));
+        }
+        for (index, row) in table.rows.iter().enumerate() {
+            if let Some(style) = &row.style {
+                fragments.push(track_fragment(
+                    row.tag,
+                    style,
+                    self.writing_mode,
+                    track_rect(all_columns.clone(), index..index + 1),
+                ));
+            }
+        }
+        debug_assert_eq!(fragments.len(), first_cell_index);
+
+        for (row, column, cell, _, is_empty, mut fragment) in laid_out_cells {
+            // https://drafts.csswg.org/css-tables/#empty-cell-rendering
+            if is_empty &&
+                !self.collapses_borders &&
+                cell.style.get_inherited_table().empty_cells == EmptyCells::Hide
+            {
+                fragments.push(Fragment::Anonymous(AnonymousFragment::no_op(
+                    self.writing_mode,
+                )));
+                continue;
+            }
+
+            // FIXME: Cells should be aligned according to `vertical-align`,
+            // which is not supported yet, they are all aligned to the top.
+            let area = track_rect(column..column + cell.colspan, row..row + cell.rowspan);
+            let padding_border_block_sum =
+                fragment.padding.block_sum() + fragment.border.block_sum();
+            fragment.content_rect.size.block = fragment
+                .content_rect
+                .size
+                .block
+                .max(area.size.block - padding_border_block_sum);
+            fragment.content_rect.start_corner += &area.start_corner;
+            fragments.push(Fragment::Box(fragment));
+        }
+
+        *content_block_size =
+            top_captions_block_size + grid_block_size + bottom_captions_block_size;
+        fragments
+    }
+}
+
+impl Table {
+    /// The cells of the table, with the row and column of their first slot,
+    /// in row-major order.
+    fn cells(&self) -> impl Iterator<Item = (usize, usize, &TableSlotCell)> {
+        self.slots.iter().enumerate().flat_map(|(row, slots)| {
+            slots
+                .iter()
+                .enumerate()
+                .filter_map(move |(column, slot)| match slot {
+                    TableSlot::Cell(cell) => Some((row, column, cell)),
+                    _ => None,
+                })
+        })
+    }
+}
+
+/// The start offsets of the tracks of the grid, followed by the offset
+/// of the end of the last track plus the spacing after it.
+fn track_offsets(sizes: &[Length], spacing: Length, start: Length) -> Vec<Length> {
+    let mut offset = start + spacing;
+    let mut offsets = Vec::with_capacity(sizes.len() + 1);
+    offsets.push(offset);
+    for size in sizes {
+        offset += *size + spacing;
+        offsets.push(offset);
+    }
+    offsets
+}
+
+/// Add `amount` to `sizes` in proportion to `weights`, or equally
+/// if all of the weights are zero. Does nothing if `amount` is not positive.
+fn distribute(sizes: &mut [Length], weights: &[Length], amount: Length) {
+    if amount <= Length::zero() || sizes.is_empty() {
+        return;
+    }
+    let total_weight: Length = weights.iter().copied().sum();
+    for (size, weight) in sizes.iter_mut().zip(weights) {
+        *size += if total_weight > Length::zero() {
+            amount * (weight.px() / total_weight.px())
+        } else {
+            amount / weights.len() as f32
+        };
+    }
+}
+
+fn track_fragment(
+    tag: Tag,
+    style: &Arc<ComputedValues>,
+    table_writing_mode: WritingMode,
+    content_rect: Rect<Length>,
+) -> Fragment {
+    Fragment::Box(BoxFragment::new(
+        tag,
+        style.clone(),
+        Vec::new(),
+        table_writing_mode,
+        content_rect,
+        Sides::zero(),
+        Sides::zero(),
+        Sides::zero(),
+        CollapsedBlockMargins::zero(),
+    ))
+}
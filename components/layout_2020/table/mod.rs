@@ -0,0 +1,95 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Table layout.
+//! https://drafts.csswg.org/css2/tables.html
+
+use crate::cell::ArcRefCell;
+use crate::flow::{BlockFormattingContext, BlockLevelBox};
+use crate::fragments::Tag;
+use servo_arc::Arc;
+use std::ops::Range;
+use style::properties::ComputedValues;
+
+mod construct;
+mod layout;
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Table {
+    #[serde(skip_serializing)]
+    style: Arc<ComputedValues>,
+
+    /// The `table-caption` boxes of this table, in tree order.
+    captions: Vec<ArcRefCell<BlockLevelBox>>,
+
+    /// The `table-column-group` boxes of this table.
+    column_groups: Vec<TableTrackGroup>,
+
+    /// One track per column of the grid that is described by a `table-column`
+    /// box or a `table-column-group` box. There may be fewer of them than grid columns.
+    columns: Vec<TableTrack>,
+
+    /// The `table-row-group`, `table-header-group` and `table-footer-group` boxes
+    /// of this table, in the order they are laid out in.
+    row_groups: Vec<TableTrackGroup>,
+
+    /// One track per row of the grid, in the order they are laid out in.
+    rows: Vec<TableTrack>,
+
+    /// The slots of the grid, indexed by row and then by column.
+    /// Every row has the same number of slots.
+    slots: Vec<Vec<TableSlot>>,
+}
+
+/// A row or a column of the table grid.
+#[derive(Debug, Serialize)]
+pub(crate) struct TableTrack {
+    tag: Tag,
+
+    /// The style of the box generating this track, or `None` for anonymous
+    /// rows and for the columns of a column group without column children.
+    #[serde(skip_serializing)]
+    style: Option<Arc<ComputedValues>>,
+
+    /// The index of the group of this track in its table, if any.
+    group_index: Option<usize>,
+}
+
+/// A row group or a column group.
+#[derive(Debug, Serialize)]
+pub(crate) struct TableTrackGroup {
+    tag: Tag,
+    #[serde(skip_serializing)]
+    style: Arc<ComputedValues>,
+
+    /// The tracks of this group.
+    track_range: Range<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) enum TableSlot {
+    /// The slot where a cell starts, that is its top-left slot in horizontal
+    /// writing modes.
+    Cell(TableSlotCell),
+
+    /// A slot that is covered by a cell starting in another slot.
+    Spanned,
+
+    /// A slot that is not covered by any cell.
+    Empty,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct TableSlotCell {
+    tag: Tag,
+    #[serde(skip_serializing)]
+    style: Arc<ComputedValues>,
+    contents: BlockFormattingContext,
+
+    /// The number of columns this cell spans, at least one.
+    colspan: usize,
+
+    /// The number of rows this cell spans, at least one.
+    rowspan: usize,
+}
@@ -728,6 +728,169 @@ fn check_floats_rule_10(placement: &FloatPlacement) {
     }
 }
 
+// Object placement predicates
+
+// An object that avoids floats without floating itself, such as a block formatting context root
+// or a line box, placed no higher than `block_start` between the given walls.
+#[derive(Clone, Debug)]
+struct ObjectInput {
+    size: Vec2<Length>,
+    block_start: u32,
+    left_wall: u32,
+    right_wall: u32,
+}
+
+impl Arbitrary for ObjectInput {
+    fn arbitrary<G>(generator: &mut G) -> ObjectInput
+    where
+        G: Gen,
+    {
+        let width: u32 = Arbitrary::arbitrary(generator);
+        let height: u32 = Arbitrary::arbitrary(generator);
+        ObjectInput {
+            size: Vec2 {
+                inline: Length::new(width as f32),
+                block: Length::new(height as f32),
+            },
+            block_start: Arbitrary::arbitrary(generator),
+            left_wall: Arbitrary::arbitrary(generator),
+            right_wall: Arbitrary::arbitrary(generator),
+        }
+    }
+}
+
+impl ObjectInput {
+    fn block_range_at(&self, block_position: Length) -> Range<Length> {
+        block_position..block_position + self.size.block
+    }
+
+    fn walls(&self) -> (Length, Length) {
+        (
+            Length::new(self.left_wall as f32),
+            Length::new(self.right_wall as f32),
+        )
+    }
+
+    // Whether the object fits in the given range of inline positions, or there is no float
+    // beside it.
+    fn fits(&self, available: &Range<Length>) -> bool {
+        let (left_wall, right_wall) = self.walls();
+        (available.start == left_wall && available.end == right_wall) ||
+            self.size.inline <= available.end - available.start
+    }
+}
+
+// Whether a float is beside some content in the given range of block positions. Content that is
+// empty in the block direction is beside the floats that cover its position.
+fn float_intersects(placed_float: &PlacedFloat, block_range: &Range<Length>) -> bool {
+    let rect = placed_float.rect();
+    rect.max_block_position() > rect.start_corner.block &&
+        rect.max_block_position() > block_range.start &&
+        (rect.start_corner.block <= block_range.start ||
+            rect.start_corner.block < block_range.end)
+}
+
+// The space between the walls is only narrowed by the floats that intersect the block range, up
+// to their outer edges.
+fn check_available_space(placement: &FloatPlacement, object: &ObjectInput) {
+    let (left_wall, right_wall) = object.walls();
+    let block_range = object.block_range_at(Length::new(object.block_start as f32));
+
+    let mut expected = left_wall..right_wall;
+    for placed_float in &placement.placed_floats {
+        if !float_intersects(placed_float, &block_range) {
+            continue;
+        }
+        match placed_float.info.side {
+            FloatSide::Left => {
+                expected.start = expected
+                    .start
+                    .max(placed_float.rect().max_inline_position())
+            },
+            FloatSide::Right => expected.end = expected.end.min(placed_float.origin.inline),
+        }
+    }
+
+    let available = placement
+        .float_context
+        .available_space(block_range, left_wall, right_wall);
+    assert_eq!(available, expected);
+}
+
+// An object placed beside floats must fit between the floats it intersects, or not intersect
+// any, and must be placed as high as possible.
+fn check_place_object_beside_floats(placement: &FloatPlacement, object: &ObjectInput) {
+    let float_context = &placement.float_context;
+    let (left_wall, right_wall) = object.walls();
+    let block_start = Length::new(object.block_start as f32);
+    let (block_position, available) =
+        float_context.place_object_beside_floats(&object.size, block_start, left_wall, right_wall);
+
+    assert!(block_position >= block_start);
+    assert_eq!(
+        available,
+        float_context.available_space(object.block_range_at(block_position), left_wall, right_wall)
+    );
+    assert!(object.fits(&available));
+
+    // The space beside the floats only grows past their bottom edges, so the object must not fit
+    // at any of those that are higher than its position.
+    let higher_positions = placement
+        .placed_floats
+        .iter()
+        .map(|placed_float| placed_float.rect().max_block_position())
+        .chain(Some(block_start))
+        .filter(|position| *position >= block_start && *position < block_position);
+    for position in higher_positions {
+        let available =
+            float_context.available_space(object.block_range_at(position), left_wall, right_wall);
+        assert!(!object.fits(&available));
+    }
+}
+
+// Clearing the floats on one side moves content past the bottom outer edge of all of them, and no
+// further. Neither those floats nor a float that clears them are then beside the content.
+fn check_clearance(placement: &FloatPlacement, object: &ObjectInput) {
+    let (left_wall, right_wall) = object.walls();
+    for &side in &[FloatSide::Left, FloatSide::Right] {
+        let (clear, clear_position) = match side {
+            FloatSide::Left => (ClearSide::Left, placement.float_context.clear_left_position),
+            FloatSide::Right => (
+                ClearSide::Right,
+                placement.float_context.clear_right_position,
+            ),
+        };
+
+        let lowest_float_edge = placement
+            .placed_floats
+            .iter()
+            .filter(|placed_float| placed_float.info.side == side)
+            .map(|placed_float| placed_float.rect().max_block_position())
+            .fold(Length::zero(), Length::max);
+        assert_eq!(clear_position, lowest_float_edge);
+
+        let available = placement.float_context.available_space(
+            object.block_range_at(clear_position),
+            left_wall,
+            right_wall,
+        );
+        match side {
+            FloatSide::Left => assert_eq!(available.start, left_wall),
+            FloatSide::Right => assert_eq!(available.end, right_wall),
+        }
+
+        let mut float_context = placement.float_context.clone();
+        let origin = float_context.add_float(&PlacementInfo {
+            size: object.size.clone(),
+            side,
+            clear,
+            left_wall,
+            right_wall,
+        });
+        assert!(origin.block >= clear_position);
+    }
+}
+
 // Checks that rule 1-7 and rule 10 hold (i.e. all rules that don't specify that floats are placed
 // "as far as possible" in some direction).
 fn check_basic_float_rules(placement: &FloatPlacement) {
@@ -832,3 +995,32 @@ fn test_floats_rule_10() {
         check_floats_rule_10(&FloatPlacement::place(floats));
     }
 }
+
+// Object placement unit tests
+
+#[test]
+fn test_available_space() {
+    let f: fn(Vec<FloatInput>, ObjectInput) = check;
+    quickcheck::quickcheck(f);
+    fn check(floats: Vec<FloatInput>, object: ObjectInput) {
+        check_available_space(&FloatPlacement::place(floats), &object);
+    }
+}
+
+#[test]
+fn test_place_object_beside_floats() {
+    let f: fn(Vec<FloatInput>, ObjectInput) = check;
+    quickcheck::quickcheck(f);
+    fn check(floats: Vec<FloatInput>, object: ObjectInput) {
+        check_place_object_beside_floats(&FloatPlacement::place(floats), &object);
+    }
+}
+
+#[test]
+fn test_clearance() {
+    let f: fn(Vec<FloatInput>, ObjectInput) = check;
+    quickcheck::quickcheck(f);
+    fn check(floats: Vec<FloatInput>, object: ObjectInput) {
+        check_clearance(&FloatPlacement::place(floats), &object);
+    }
+}
@@ -0,0 +1,157 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Tests for the distribution of a flow across fragmentainers, and for the balancing of
+//! the columns of multi-column containers.
+
+use layout::fragmentation::{
+    balanced_block_size, BreakOpportunities, MonolithicContent, RepeatedHeader,
+};
+use style::values::computed::Length;
+use style::Zero;
+
+fn px(value: f32) -> Length {
+    Length::new(value)
+}
+
+/// A flow of monolithic lines of the given block sizes, with forced breaks before the
+/// lines of the given indices.
+fn flow(line_block_sizes: &[f32], breaks_before: &[usize]) -> BreakOpportunities {
+    let mut monolithic = Vec::new();
+    let mut forced_breaks = Vec::new();
+    let mut position = Length::zero();
+    for (index, block_size) in line_block_sizes.iter().enumerate() {
+        if breaks_before.contains(&index) {
+            forced_breaks.push(position);
+        }
+        monolithic.push(MonolithicContent {
+            start: position,
+            end: position + px(*block_size),
+            reserved_end: Length::zero(),
+        });
+        position += px(*block_size);
+    }
+    BreakOpportunities {
+        monolithic,
+        forced_breaks,
+        repeated_headers: Vec::new(),
+    }
+}
+
+fn content_block_size(flow: &BreakOpportunities) -> Length {
+    flow.monolithic
+        .last()
+        .map_or(Length::zero(), |content| content.end)
+}
+
+fn balance(flow: &BreakOpportunities, count: usize, max_block_size: Option<f32>) -> Length {
+    balanced_block_size(
+        |block_size| flow.fragmentainer_starts(block_size).len(),
+        count,
+        content_block_size(flow),
+        max_block_size.map(px),
+    )
+}
+
+#[test]
+fn test_flow_that_fits_stays_in_one_fragmentainer() {
+    let flow = flow(&[10., 10., 10.], &[]);
+    assert_eq!(flow.fragmentainer_starts(px(30.)), vec![px(0.)]);
+    assert_eq!(flow.fragmentainer_starts(px(100.)), vec![px(0.)]);
+}
+
+#[test]
+fn test_content_that_does_not_fit_moves_to_the_next_fragmentainer() {
+    let flow = flow(&[10., 10., 10., 10., 10.], &[]);
+    assert_eq!(
+        flow.fragmentainer_starts(px(25.)),
+        vec![px(0.), px(20.), px(40.)]
+    );
+}
+
+#[test]
+fn test_content_taller_than_a_fragmentainer_is_not_split() {
+    let flow = flow(&[10., 50., 10.], &[]);
+    // The tall line starts a fragmentainer of its own and overflows it, instead of
+    // leaving an empty fragmentainer behind.
+    assert_eq!(
+        flow.fragmentainer_starts(px(20.)),
+        vec![px(0.), px(10.), px(60.)]
+    );
+}
+
+#[test]
+fn test_forced_breaks_start_fragmentainers() {
+    let flow = flow(&[10., 10., 10., 10.], &[1, 3]);
+    assert_eq!(
+        flow.fragmentainer_starts(px(100.)),
+        vec![px(0.), px(10.), px(30.)]
+    );
+}
+
+#[test]
+fn test_forced_break_at_the_start_of_the_flow_is_ignored() {
+    let flow = flow(&[10., 10.], &[0]);
+    assert_eq!(flow.fragmentainer_starts(px(100.)), vec![px(0.)]);
+}
+
+#[test]
+fn test_forced_breaks_combine_with_unforced_ones() {
+    let flow = flow(&[10., 10., 10., 10., 10.], &[3]);
+    assert_eq!(
+        flow.fragmentainer_starts(px(20.)),
+        vec![px(0.), px(20.), px(30.)]
+    );
+}
+
+#[test]
+fn test_repeated_headers_take_space_from_fragmentainers() {
+    let mut flow = flow(&[10., 10., 10., 10.], &[]);
+    flow.repeated_headers.push(RepeatedHeader {
+        body: (px(0.), px(40.)),
+        block_size: px(5.),
+    });
+    assert_eq!(flow.fragmentainer_starts(px(25.)), vec![px(0.), px(20.)]);
+}
+
+#[test]
+fn test_columns_are_balanced() {
+    let flow = flow(&[10., 10., 10., 10., 10., 10.], &[]);
+    assert_eq!(balance(&flow, 3, None), px(20.));
+    assert_eq!(balance(&flow, 2, None), px(30.));
+    assert_eq!(balance(&flow, 1, None), px(60.));
+}
+
+#[test]
+fn test_columns_are_balanced_around_monolithic_content() {
+    let flow = flow(&[10., 40., 10.], &[]);
+    // The tall line can't be split, so one of the columns has to hold it along with
+    // another line.
+    let block_size = balance(&flow, 2, None);
+    assert!(block_size >= px(50.) && block_size <= px(50.5));
+    assert!(flow.fragmentainer_starts(block_size).len() <= 2);
+}
+
+#[test]
+fn test_balanced_columns_are_limited_by_the_available_block_size() {
+    let flow = flow(&[10., 10., 10., 10., 10., 10.], &[]);
+    assert_eq!(balance(&flow, 2, Some(20.)), px(20.));
+    assert_eq!(balance(&flow, 3, Some(50.)), px(20.));
+}
+
+#[test]
+fn test_balanced_columns_account_for_forced_breaks() {
+    // Without the break, two columns of 20px would do.
+    let flow = flow(&[10., 10., 10., 10.], &[1]);
+    let block_size = balance(&flow, 2, None);
+    assert!(block_size >= px(30.) && block_size <= px(30.5));
+    assert_eq!(flow.fragmentainer_starts(block_size), vec![px(0.), px(10.)]);
+}
+
+#[test]
+fn test_empty_flow_has_empty_columns() {
+    let flow = flow(&[], &[]);
+    assert_eq!(balance(&flow, 3, None), Length::zero());
+    assert_eq!(flow.fragmentainer_starts(px(10.)), vec![px(0.)]);
+}
@@ -76,14 +76,15 @@ use parking_lot::RwLock;
 use profile_traits::mem::{self as profile_mem, Report, ReportKind, ReportsChan};
 use profile_traits::time::{self as profile_time, profile, TimerMetadata};
 use profile_traits::time::{TimerMetadataFrameType, TimerMetadataReflowType};
+use profile_traits::time::{TraceFlowKind, TraceFlowPhase};
 use script_layout_interface::message::{LayoutThreadInit, Msg, NodesFromPointQueryType, Reflow};
 use script_layout_interface::message::{QueryMsg, ReflowComplete, ReflowGoal, ScriptReflow};
 use script_layout_interface::rpc::TextIndexResponse;
 use script_layout_interface::rpc::{LayoutRPC, OffsetParentResponse};
 use script_layout_interface::wrapper_traits::LayoutNode;
+use script_traits::{display_list_trace_flow_id, Painter, WebrenderIpcSender};
 use script_traits::{ConstellationControlMsg, LayoutControlMsg, LayoutMsg as ConstellationMsg};
 use script_traits::{DrawAPaintImageResult, IFrameSizeMsg, PaintWorkletError, WindowSizeType};
-use script_traits::{Painter, WebrenderIpcSender};
 use script_traits::{ScrollState, UntrustedNodeAddress, WindowSizeData};
 use servo_arc::Arc as ServoArc;
 use servo_atoms::Atom;
@@ -342,6 +343,7 @@ impl LayoutThreadFactory for LayoutThread {
                             layout.start();
                         },
                         reporter_name,
+                        Some(id),
                         sender,
                         Msg::CollectReports,
                     );
@@ -566,6 +568,8 @@ impl LayoutThread {
                 nodes_from_point_response: vec![],
                 element_inner_text_response: String::new(),
                 inner_window_dimensions_response: None,
+                pages_response: vec![],
+                page_display_lists_response: vec![],
             })),
             webrender_image_cache: Arc::new(RwLock::new(FnvHashMap::default())),
             paint_time_metrics: paint_time_metrics,
@@ -695,6 +699,10 @@ impl LayoutThread {
                 self.paint_time_metrics.maybe_set_metric(epoch, paint_time);
                 true
             },
+            Request::FromPipeline(LayoutControlMsg::MemoryPressure) => {
+                self.handle_memory_pressure(possibly_locked_rw_data);
+                true
+            },
             Request::FromScript(msg) => self.handle_request_helper(msg, possibly_locked_rw_data),
             Request::FromFontCache => {
                 let _rw_data = possibly_locked_rw_data.lock();
@@ -747,6 +755,12 @@ impl LayoutThread {
             },
             Msg::Reflow(data) => {
                 let mut data = ScriptReflowResult::new(data);
+                profile_time::send_trace_flow(
+                    TraceFlowKind::Reflow,
+                    data.trace_flow_id,
+                    TraceFlowPhase::End,
+                    &self.time_profiler_chan,
+                );
                 profile(
                     profile_time::ProfilerCategory::LayoutPerform,
                     self.profiler_metadata(),
@@ -821,6 +835,26 @@ impl LayoutThread {
         true
     }
 
+    /// Drops the font caches and any unused style data, and reports how much
+    /// the stylist shrank to the memory profiler.
+    fn handle_memory_pressure<'a, 'b>(&self, possibly_locked_rw_data: &mut RwData<'a, 'b>) {
+        let _rw_data = possibly_locked_rw_data.lock();
+        let mut ops = MallocSizeOfOps::new(servo_allocator::usable_size, None, None);
+        let before = self.stylist.size_of(&mut ops);
+
+        font_context::invalidate_font_caches();
+        Stylist::purge_unused_ua_cascade_data();
+        self.stylist.rule_tree().gc();
+
+        let after = self.stylist.size_of(&mut ops);
+        let formatted_url = &format!("url({})", self.url);
+        self.mem_profiler_chan.report_reclaimed(vec![Report {
+            path: path![formatted_url, "layout-thread", "stylist"],
+            kind: ReportKind::ExplicitJemallocHeapSize,
+            size: before.saturating_sub(after),
+        }]);
+    }
+
     fn collect_reports<'a, 'b>(
         &self,
         reports_chan: ReportsChan,
@@ -1159,6 +1193,15 @@ impl LayoutThread {
                 self.paint_time_metrics
                     .maybe_observe_paint_time(self, epoch, is_contentful.0);
 
+                profile_time::send_trace_flow(
+                    TraceFlowKind::DisplayList,
+                    display_list_trace_flow_id(
+                        self.id.to_webrender(),
+                        webrender_api::Epoch(epoch.0),
+                    ),
+                    TraceFlowPhase::Start,
+                    &self.time_profiler_chan,
+                );
                 self.webrender_api
                     .send_display_list(epoch, viewport_size, builder.finalize());
             },
@@ -1234,6 +1277,12 @@ impl LayoutThread {
                         &QueryMsg::InnerWindowDimensionsQuery(_) => {
                             rw_data.inner_window_dimensions_response = None;
                         },
+                        &QueryMsg::PagesQuery => {
+                            rw_data.pages_response = vec![];
+                        },
+                        &QueryMsg::PageDisplayListsQuery => {
+                            rw_data.page_display_lists_response = vec![];
+                        },
                     },
                     ReflowGoal::Full | ReflowGoal::TickAnimations => {},
                 }
@@ -1602,6 +1651,14 @@ impl LayoutThread {
                         .get(&browsing_context_id)
                         .cloned();
                 },
+                &QueryMsg::PagesQuery => {
+                    // Paged media is only supported by layout 2020.
+                    rw_data.pages_response = vec![];
+                },
+                &QueryMsg::PageDisplayListsQuery => {
+                    // Paged media is only supported by layout 2020.
+                    rw_data.page_display_lists_response = vec![];
+                },
             },
             ReflowGoal::Full | ReflowGoal::TickAnimations => {},
         }
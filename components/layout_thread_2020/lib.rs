@@ -43,7 +43,8 @@ use layout::query::{
 use layout::query::{process_element_inner_text_query, process_node_geometry_request};
 use layout::query::{process_node_scroll_area_request, process_node_scroll_id_request};
 use layout::query::{
-    process_offset_parent_query, process_resolved_style_request, process_text_index_request,
+    process_offset_parent_query, process_page_display_lists_request, process_pages_request,
+    process_resolved_style_request, process_text_index_request,
 };
 use layout::traversal::RecalcStyle;
 use layout::{BoxTree, FragmentTree};
@@ -61,13 +62,14 @@ use parking_lot::RwLock;
 use profile_traits::mem::{self as profile_mem, Report, ReportKind, ReportsChan};
 use profile_traits::time::{self as profile_time, profile, TimerMetadata};
 use profile_traits::time::{TimerMetadataFrameType, TimerMetadataReflowType};
+use profile_traits::time::{TraceFlowKind, TraceFlowPhase};
 use script_layout_interface::message::{LayoutThreadInit, Msg, NodesFromPointQueryType};
 use script_layout_interface::message::{QueryMsg, ReflowComplete, ReflowGoal, ScriptReflow};
 use script_layout_interface::rpc::TextIndexResponse;
 use script_layout_interface::rpc::{LayoutRPC, OffsetParentResponse};
+use script_traits::{display_list_trace_flow_id, Painter, WebrenderIpcSender};
 use script_traits::{ConstellationControlMsg, LayoutControlMsg, LayoutMsg as ConstellationMsg};
 use script_traits::{DrawAPaintImageResult, PaintWorkletError};
-use script_traits::{Painter, WebrenderIpcSender};
 use script_traits::{ScrollState, UntrustedNodeAddress, WindowSizeData};
 use servo_arc::Arc as ServoArc;
 use servo_atoms::Atom;
@@ -309,6 +311,7 @@ impl LayoutThreadFactory for LayoutThread {
                             layout.start();
                         },
                         reporter_name,
+                        Some(id),
                         sender,
                         Msg::CollectReports,
                     );
@@ -532,6 +535,8 @@ impl LayoutThread {
                 nodes_from_point_response: vec![],
                 element_inner_text_response: String::new(),
                 inner_window_dimensions_response: None,
+                pages_response: vec![],
+                page_display_lists_response: vec![],
             })),
             webrender_image_cache: Default::default(),
             paint_time_metrics: paint_time_metrics,
@@ -658,6 +663,10 @@ impl LayoutThread {
                 self.paint_time_metrics.maybe_set_metric(epoch, paint_time);
                 true
             },
+            Request::FromPipeline(LayoutControlMsg::MemoryPressure) => {
+                self.handle_memory_pressure(possibly_locked_rw_data);
+                true
+            },
             Request::FromScript(msg) => self.handle_request_helper(msg, possibly_locked_rw_data),
             Request::FromFontCache => {
                 let _rw_data = possibly_locked_rw_data.lock();
@@ -710,6 +719,12 @@ impl LayoutThread {
             },
             Msg::Reflow(data) => {
                 let mut data = ScriptReflowResult::new(data);
+                profile_time::send_trace_flow(
+                    TraceFlowKind::Reflow,
+                    data.trace_flow_id,
+                    TraceFlowPhase::End,
+                    &self.time_profiler_chan,
+                );
                 profile(
                     profile_time::ProfilerCategory::LayoutPerform,
                     self.profiler_metadata(),
@@ -768,6 +783,26 @@ impl LayoutThread {
         true
     }
 
+    /// Drops the font caches and any unused style data, and reports how much
+    /// the stylist shrank to the memory profiler.
+    fn handle_memory_pressure<'a, 'b>(&self, possibly_locked_rw_data: &mut RwData<'a, 'b>) {
+        let _rw_data = possibly_locked_rw_data.lock();
+        let mut ops = MallocSizeOfOps::new(servo_allocator::usable_size, None, None);
+        let before = self.stylist.size_of(&mut ops);
+
+        font_context::invalidate_font_caches();
+        Stylist::purge_unused_ua_cascade_data();
+        self.stylist.rule_tree().gc();
+
+        let after = self.stylist.size_of(&mut ops);
+        let formatted_url = &format!("url({})", self.url);
+        self.mem_profiler_chan.report_reclaimed(vec![Report {
+            path: path![formatted_url, "layout-thread", "stylist"],
+            kind: ReportKind::ExplicitJemallocHeapSize,
+            size: before.saturating_sub(after),
+        }]);
+    }
+
     fn collect_reports<'a, 'b>(
         &self,
         reports_chan: ReportsChan,
@@ -926,6 +961,12 @@ impl LayoutThread {
                         &QueryMsg::InnerWindowDimensionsQuery(_) => {
                             rw_data.inner_window_dimensions_response = None;
                         },
+                        &QueryMsg::PagesQuery => {
+                            rw_data.pages_response = vec![];
+                        },
+                        &QueryMsg::PageDisplayListsQuery => {
+                            rw_data.page_display_lists_response = vec![];
+                        },
                     },
                     ReflowGoal::Full | ReflowGoal::TickAnimations => {},
                 }
@@ -1173,21 +1214,27 @@ impl LayoutThread {
                         process_content_box_request(node, self.fragment_tree.borrow().clone());
                 },
                 &QueryMsg::ContentBoxesQuery(node) => {
-                    rw_data.content_boxes_response = process_content_boxes_request(node);
+                    rw_data.content_boxes_response =
+                        process_content_boxes_request(node, self.fragment_tree.borrow().clone());
                 },
                 &QueryMsg::TextIndexQuery(node, point_in_node) => {
                     let point_in_node = Point2D::new(
                         Au::from_f32_px(point_in_node.x),
                         Au::from_f32_px(point_in_node.y),
                     );
-                    rw_data.text_index_response = process_text_index_request(node, point_in_node);
+                    rw_data.text_index_response = process_text_index_request(
+                        node,
+                        point_in_node,
+                        self.fragment_tree.borrow().clone(),
+                    );
                 },
                 &QueryMsg::ClientRectQuery(node) => {
                     rw_data.client_rect_response =
                         process_node_geometry_request(node, self.fragment_tree.borrow().clone());
                 },
                 &QueryMsg::NodeScrollGeometryQuery(node) => {
-                    rw_data.scroll_area_response = process_node_scroll_area_request(node);
+                    rw_data.scroll_area_response =
+                        process_node_scroll_area_request(node, self.fragment_tree.borrow().clone());
                 },
                 &QueryMsg::NodeScrollIdQuery(node) => {
                     let node = unsafe { ServoLayoutNode::new(&node) };
@@ -1211,7 +1258,8 @@ impl LayoutThread {
                         process_resolved_font_style_query(node, property, value);
                 },
                 &QueryMsg::OffsetParentQuery(node) => {
-                    rw_data.offset_parent_response = process_offset_parent_query(node);
+                    rw_data.offset_parent_response =
+                        process_offset_parent_query(node, self.fragment_tree.borrow().clone());
                 },
                 &QueryMsg::StyleQuery => {},
                 &QueryMsg::NodesFromPointQuery(client_point, ref reflow_goal) => {
@@ -1246,11 +1294,34 @@ impl LayoutThread {
                     //            builder in order to support query iframe sizing.
                     rw_data.inner_window_dimensions_response = None;
                 },
+                &QueryMsg::PagesQuery => {
+                    rw_data.pages_response = process_pages_request::<ServoLayoutElement>(
+                        self.box_tree.borrow().clone(),
+                        context,
+                        self.default_page_size(),
+                    );
+                },
+                &QueryMsg::PageDisplayListsQuery => {
+                    rw_data.page_display_lists_response =
+                        process_page_display_lists_request::<ServoLayoutElement>(
+                            self.box_tree.borrow().clone(),
+                            context,
+                            self.default_page_size(),
+                        );
+                },
             },
             ReflowGoal::Full | ReflowGoal::TickAnimations => {},
         }
     }
 
+    /// The size of the pages that the document is printed on, when `@page` does not set one.
+    fn default_page_size(&self) -> Size2D<f32, CSSPixel> {
+        Size2D::new(
+            self.viewport_size.width.to_f32_px(),
+            self.viewport_size.height.to_f32_px(),
+        )
+    }
+
     fn set_scroll_states<'a, 'b>(
         &mut self,
         new_scroll_states: Vec<ScrollState>,
@@ -1343,6 +1414,12 @@ impl LayoutThread {
             self.viewport_size.width.to_f32_px(),
             self.viewport_size.height.to_f32_px(),
         ));
+        profile_time::send_trace_flow(
+            TraceFlowKind::DisplayList,
+            display_list_trace_flow_id(self.id.to_webrender(), webrender_api::Epoch(epoch.0)),
+            TraceFlowPhase::Start,
+            &self.time_profiler_chan,
+        );
         self.webrender_api
             .send_display_list(epoch, viewport_size, display_list.wr.finalize());
 
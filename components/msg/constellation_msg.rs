@@ -501,7 +501,7 @@ pub enum InputMethodType {
     Week,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 /// The equivalent of script_layout_interface::message::Msg
 pub enum LayoutHangAnnotation {
     AddStylesheet,
@@ -522,7 +522,7 @@ pub enum LayoutHangAnnotation {
     SetNavigationStart,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 /// The equivalent of script::script_runtime::ScriptEventCategory
 pub enum ScriptHangAnnotation {
     AttachLayout,
@@ -555,7 +555,7 @@ pub enum ScriptHangAnnotation {
     WebGPUMsg,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum HangAnnotation {
     Layout(LayoutHangAnnotation),
     Script(ScriptHangAnnotation),
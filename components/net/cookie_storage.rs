@@ -92,6 +92,32 @@ impl CookieStorage {
         }
     }
 
+    /// Remove the cookies named `name` that would be sent to `url`.
+    pub fn delete_cookie(&mut self, url: &ServoUrl, name: &str) {
+        let domain = reg_host(url.host_str().unwrap_or(""));
+        if let Some(cookies) = self.cookies_map.get_mut(&domain) {
+            cookies.retain(|c| {
+                c.cookie.name() != name || !c.appropriate_for_url(url, CookieSource::HTTP)
+            });
+        }
+    }
+
+    pub fn clear_all(&mut self) {
+        self.cookies_map.clear();
+    }
+
+    /// The unexpired cookies of the registrable domain of `url`, or of every domain.
+    pub fn all_cookies(&self, url: Option<&ServoUrl>) -> Vec<cookie_rs::Cookie<'static>> {
+        let domain = url.map(|url| reg_host(url.host_str().unwrap_or("")));
+        self.cookies_map
+            .iter()
+            .filter(|&(key, _)| domain.as_ref().map_or(true, |domain| key == domain))
+            .flat_map(|(_, cookies)| cookies.iter())
+            .filter(|c| !is_cookie_expired(c))
+            .map(|c| c.cookie.clone())
+            .collect()
+    }
+
     // http://tools.ietf.org/html/rfc6265#section-5.3
     pub fn push(&mut self, mut cookie: Cookie, url: &ServoUrl, source: CookieSource) {
         // https://www.ietf.org/id/draft-ietf-httpbis-cookie-alone-01.txt Step 1
@@ -141,6 +141,14 @@ impl HstsList {
         }
     }
 
+    /// Remove the entries learned from responses for `host`, or for every host.
+    /// Preloaded entries, which have no timestamp, are kept.
+    pub fn clear_learned_entries(&mut self, host: Option<&str>) {
+        for entries in self.entries_map.values_mut() {
+            entries.retain(|e| e.timestamp.is_none() || host.map_or(false, |host| e.host != host));
+        }
+    }
+
     /// Step 2.9 of https://fetch.spec.whatwg.org/#concept-main-fetch.
     pub fn apply_hsts_rules(&self, url: &mut ServoUrl) {
         if url.scheme() != "http" && url.scheme() != "ws" {
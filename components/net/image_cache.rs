@@ -660,4 +660,22 @@ impl ImageCache for ImageCacheImpl {
             },
         }
     }
+
+    fn purge(&self) -> usize {
+        let mut store = self.store.lock().unwrap();
+        let mut reclaimed = 0;
+        // The images still referenced elsewhere would not be freed. Failed loads are kept,
+        // so that they aren't fetched again.
+        store
+            .completed_loads
+            .retain(|_, load| match load.image_response {
+                ImageResponse::Loaded(ref image, _) if Arc::strong_count(image) == 1 => {
+                    reclaimed += image.bytes.len();
+                    false
+                },
+                _ => true,
+            });
+        debug!("Purged {} bytes of decoded images", reclaimed);
+        reclaimed
+    }
 }
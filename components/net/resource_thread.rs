@@ -32,6 +32,7 @@ use net_traits::storage_thread::StorageThreadMsg;
 use net_traits::DiscardFetch;
 use net_traits::FetchTaskTarget;
 use net_traits::WebSocketNetworkEvent;
+use net_traits::{BrowsingProfile, ProfileId};
 use net_traits::{CookieSource, CoreResourceMsg, CoreResourceThread};
 use net_traits::{CustomResponseMediator, FetchChannels};
 use net_traits::{ResourceFetchTiming, ResourceTimingType};
@@ -62,7 +63,58 @@ pub fn new_resource_threads(
     config_dir: Option<PathBuf>,
     certificate_path: Option<String>,
 ) -> (ResourceThreads, ResourceThreads) {
-    let (public_core, private_core) = new_core_resource_thread(
+    create_resource_threads(
+        None,
+        user_agent,
+        devtools_chan,
+        time_profiler_chan,
+        mem_profiler_chan,
+        embedder_proxy,
+        config_dir,
+        certificate_path,
+    )
+}
+
+/// Returns a tuple of (public, private) senders to new threads that keep the cookies,
+/// storage, HSTS list, authentication cache and HTTP cache of an isolated browsing profile.
+pub fn new_profile_resource_threads(
+    profile: BrowsingProfile,
+    user_agent: Cow<'static, str>,
+    devtools_chan: Option<Sender<DevtoolsControlMsg>>,
+    time_profiler_chan: ProfilerChan,
+    mem_profiler_chan: MemProfilerChan,
+    embedder_proxy: EmbedderProxy,
+    certificate_path: Option<String>,
+) -> (ResourceThreads, ResourceThreads) {
+    if let Some(ref config_dir) = profile.config_dir {
+        if let Err(why) = fs::create_dir_all(config_dir) {
+            warn!("couldn't create {}: {}", config_dir.display(), why);
+        }
+    }
+    create_resource_threads(
+        Some(profile.id),
+        user_agent,
+        devtools_chan,
+        time_profiler_chan,
+        mem_profiler_chan,
+        embedder_proxy,
+        profile.config_dir,
+        certificate_path,
+    )
+}
+
+fn create_resource_threads(
+    profile_id: Option<ProfileId>,
+    user_agent: Cow<'static, str>,
+    devtools_chan: Option<Sender<DevtoolsControlMsg>>,
+    time_profiler_chan: ProfilerChan,
+    mem_profiler_chan: MemProfilerChan,
+    embedder_proxy: EmbedderProxy,
+    config_dir: Option<PathBuf>,
+    certificate_path: Option<String>,
+) -> (ResourceThreads, ResourceThreads) {
+    let (public_core, private_core) = spawn_core_resource_thread(
+        profile_id,
         user_agent,
         devtools_chan,
         time_profiler_chan,
@@ -87,11 +139,39 @@ pub fn new_core_resource_thread(
     embedder_proxy: EmbedderProxy,
     config_dir: Option<PathBuf>,
     certificate_path: Option<String>,
+) -> (CoreResourceThread, CoreResourceThread) {
+    spawn_core_resource_thread(
+        None,
+        user_agent,
+        devtools_chan,
+        time_profiler_chan,
+        mem_profiler_chan,
+        embedder_proxy,
+        config_dir,
+        certificate_path,
+    )
+}
+
+fn spawn_core_resource_thread(
+    profile_id: Option<ProfileId>,
+    user_agent: Cow<'static, str>,
+    devtools_chan: Option<Sender<DevtoolsControlMsg>>,
+    time_profiler_chan: ProfilerChan,
+    mem_profiler_chan: MemProfilerChan,
+    embedder_proxy: EmbedderProxy,
+    config_dir: Option<PathBuf>,
+    certificate_path: Option<String>,
 ) -> (CoreResourceThread, CoreResourceThread) {
     let (public_setup_chan, public_setup_port) = ipc::channel().unwrap();
     let (private_setup_chan, private_setup_port) = ipc::channel().unwrap();
     let (report_chan, report_port) = ipc::channel().unwrap();
 
+    // Each profile registers its own memory reporter, and reporter names must be unique.
+    let reporter_name = match profile_id {
+        Some(ref profile_id) => format!("network-cache-reporter-{}", profile_id),
+        None => String::from("network-cache-reporter"),
+    };
+
     thread::Builder::new()
         .name("ResourceManager".to_owned())
         .spawn(move || {
@@ -107,11 +187,14 @@ pub fn new_core_resource_thread(
                 resource_manager,
                 config_dir,
                 certificate_path,
+                profile_id,
+                mem_profiler_chan: mem_profiler_chan.clone(),
             };
 
             mem_profiler_chan.run_with_memory_reporting(
                 || (channel_manager.start(public_setup_port, private_setup_port, report_port)),
-                String::from("network-cache-reporter"),
+                reporter_name,
+                None,
                 report_chan,
                 |report_chan| report_chan,
             );
@@ -124,6 +207,10 @@ struct ResourceChannelManager {
     resource_manager: CoreResourceManager,
     config_dir: Option<PathBuf>,
     certificate_path: Option<String>,
+    /// The browsing profile whose state this manager keeps, if it isn't the default one.
+    profile_id: Option<ProfileId>,
+    /// A channel to the memory profiler, to report the memory reclaimed under memory pressure.
+    mem_profiler_chan: MemProfilerChan,
 }
 
 fn create_http_states(
@@ -254,13 +341,13 @@ impl ResourceChannelManager {
         let private_cache = private_http_state.http_cache.read().unwrap();
 
         let public_report = Report {
-            path: path!["memory-cache", "public"],
+            path: self.report_path("public"),
             kind: ReportKind::ExplicitJemallocHeapSize,
             size: public_cache.size_of(&mut ops),
         };
 
         let private_report = Report {
-            path: path!["memory-cache", "private"],
+            path: self.report_path("private"),
             kind: ReportKind::ExplicitJemallocHeapSize,
             size: private_cache.size_of(&mut ops),
         };
@@ -268,6 +355,13 @@ impl ResourceChannelManager {
         msg.send(vec![public_report, private_report]);
     }
 
+    fn report_path(&self, cache: &str) -> Vec<String> {
+        match self.profile_id {
+            Some(ref profile_id) => path!["memory-cache", format!("profile-{}", profile_id), cache],
+            None => path!["memory-cache", cache],
+        }
+    }
+
     /// Returns false if the thread should exit.
     fn process_msg(&mut self, msg: CoreResourceMsg, http_state: &Arc<HttpState>) -> bool {
         match msg {
@@ -298,6 +392,41 @@ impl ResourceChannelManager {
                     .clear_storage(&request);
                 return true;
             },
+            CoreResourceMsg::GetAllCookies(url, consumer) => {
+                let cookie_jar = http_state.cookie_jar.read().unwrap();
+                let cookies = cookie_jar
+                    .all_cookies(url.as_ref())
+                    .into_iter()
+                    .map(Serde)
+                    .collect();
+                consumer.send(cookies).unwrap();
+            },
+            CoreResourceMsg::DeleteCookie(url, name) => {
+                http_state
+                    .cookie_jar
+                    .write()
+                    .unwrap()
+                    .delete_cookie(&url, &name);
+            },
+            CoreResourceMsg::DeleteAllCookies => {
+                http_state.cookie_jar.write().unwrap().clear_all();
+            },
+            CoreResourceMsg::ClearHstsEntries(host) => {
+                http_state
+                    .hsts_list
+                    .write()
+                    .unwrap()
+                    .clear_learned_entries(host.as_ref().map(|host| &**host));
+            },
+            CoreResourceMsg::ClearAuthCache(origin) => {
+                let mut auth_cache = http_state.auth_cache.write().unwrap();
+                match origin {
+                    Some(origin) => {
+                        auth_cache.entries.remove(&origin.ascii_serialization());
+                    },
+                    None => auth_cache.entries.clear(),
+                }
+            },
             CoreResourceMsg::FetchRedirect(req_init, res_init, sender, cancel_chan) => self
                 .resource_manager
                 .fetch(req_init, Some(res_init), sender, http_state, cancel_chan),
@@ -341,6 +470,14 @@ impl ResourceChannelManager {
                     .send(history_states.get(&history_state_id).cloned())
                     .unwrap();
             },
+            CoreResourceMsg::GetHistoryStates(history_state_ids, consumer) => {
+                let history_states = http_state.history_states.read().unwrap();
+                let states = history_state_ids
+                    .iter()
+                    .map(|history_state_id| history_states.get(history_state_id).cloned())
+                    .collect();
+                consumer.send(states).unwrap();
+            },
             CoreResourceMsg::SetHistoryState(history_state_id, structured_data) => {
                 let mut history_states = http_state.history_states.write().unwrap();
                 history_states.insert(history_state_id, structured_data);
@@ -357,6 +494,17 @@ impl ResourceChannelManager {
             CoreResourceMsg::ClearCache => {
                 http_state.http_cache.write().unwrap().clear();
             },
+            CoreResourceMsg::MemoryPressure => {
+                let mut ops = MallocSizeOfOps::new(servo_allocator::usable_size, None, None);
+                let mut http_cache = http_state.http_cache.write().unwrap();
+                let size = http_cache.size_of(&mut ops);
+                http_cache.clear();
+                self.mem_profiler_chan.report_reclaimed(vec![Report {
+                    path: self.report_path("http-cache"),
+                    kind: ReportKind::ExplicitJemallocHeapSize,
+                    size,
+                }]);
+            },
             CoreResourceMsg::ToFileManager(msg) => self.resource_manager.filemanager.handle(msg),
             CoreResourceMsg::Exit(sender) => {
                 if let Some(ref config_dir) = self.config_dir {
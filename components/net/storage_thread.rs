@@ -82,6 +82,10 @@ impl StorageManager {
                     self.clear(sender, url, storage_type);
                     self.save_state()
                 },
+                StorageThreadMsg::ClearOrigins(url, storage_type) => {
+                    self.clear_origins(url, storage_type);
+                    self.save_state()
+                },
                 StorageThreadMsg::Exit(sender) => {
                     // Nothing to do since we save localstorage set eagerly.
                     let _ = sender.send(());
@@ -270,6 +274,17 @@ impl StorageManager {
             .unwrap();
     }
 
+    fn clear_origins(&mut self, url: Option<ServoUrl>, storage_type: StorageType) {
+        let origin = url.map(|url| self.origin_as_string(url));
+        let data = self.select_data_mut(storage_type);
+        match origin {
+            Some(origin) => {
+                data.remove(&origin);
+            },
+            None => data.clear(),
+        }
+    }
+
     fn origin_as_string(&self, url: ServoUrl) -> String {
         url.origin().ascii_serialization()
     }
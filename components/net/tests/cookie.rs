@@ -475,3 +475,41 @@ fn test_cookie_eviction_all_nonsecure_new_nonsecure() {
         "extra2=bar; extra3=bar; extra4=bar; extra5=bar; foo=bar"
     );
 }
+
+#[test]
+fn test_all_cookies_for_site() {
+    let mut storage = CookieStorage::new(5);
+    let url = ServoUrl::parse("http://home.example.org/").unwrap();
+    let other_url = ServoUrl::parse("http://mozilla.org/").unwrap();
+    add_cookie_to_storage(&mut storage, &url, "foo=bar");
+    add_cookie_to_storage(&mut storage, &other_url, "baz=qux");
+
+    let cookies = storage.all_cookies(Some(&url));
+    assert_eq!(cookies.len(), 1);
+    assert_eq!(cookies[0].name(), "foo");
+    assert_eq!(storage.all_cookies(None).len(), 2);
+}
+
+#[test]
+fn test_delete_cookie() {
+    let mut storage = CookieStorage::new(5);
+    let url = ServoUrl::parse("http://home.example.org/foo/").unwrap();
+    add_cookie_to_storage(&mut storage, &url, "foo=bar; Path=/foo");
+    add_cookie_to_storage(&mut storage, &url, "foo=bar; Path=/bar");
+    add_cookie_to_storage(&mut storage, &url, "baz=qux");
+
+    storage.delete_cookie(&url, "foo");
+    let mut names: Vec<_> = storage
+        .all_cookies(None)
+        .iter()
+        .map(|c| (c.name().to_owned(), c.path().map(str::to_owned)))
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec![
+            ("baz".to_owned(), Some("/foo".to_owned())),
+            ("foo".to_owned(), Some("/bar".to_owned())),
+        ]
+    );
+}
@@ -382,3 +382,45 @@ fn test_preload_hsts_domains_well_formed() {
     let hsts_list = HstsList::from_servo_preload();
     assert!(!hsts_list.entries_map.is_empty());
 }
+
+#[test]
+fn test_hsts_list_clear_learned_entries_keeps_preloaded_entries() {
+    let mut entries_map = HashMap::new();
+    entries_map.insert(
+        "mozilla.org".to_owned(),
+        vec![
+            HstsEntry {
+                host: "mozilla.org".to_owned(),
+                include_subdomains: false,
+                max_age: None,
+                timestamp: None,
+            },
+            HstsEntry::new(
+                "servo.mozilla.org".to_owned(),
+                IncludeSubdomains::NotIncluded,
+                None,
+            )
+            .unwrap(),
+        ],
+    );
+    entries_map.insert(
+        "example.org".to_owned(),
+        vec![HstsEntry::new(
+            "example.org".to_owned(),
+            IncludeSubdomains::NotIncluded,
+            None,
+        )
+        .unwrap()],
+    );
+    let mut hsts_list = HstsList {
+        entries_map: entries_map,
+    };
+
+    hsts_list.clear_learned_entries(Some("example.org"));
+    assert!(!hsts_list.is_host_secure("example.org"));
+    assert!(hsts_list.is_host_secure("servo.mozilla.org"));
+
+    hsts_list.clear_learned_entries(None);
+    assert!(!hsts_list.is_host_secure("servo.mozilla.org"));
+    assert!(hsts_list.is_host_secure("mozilla.org"));
+}
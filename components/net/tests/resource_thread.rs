@@ -3,12 +3,17 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::create_embedder_proxy;
+use hyper_serde::Serde;
 use ipc_channel::ipc;
-use net::resource_thread::new_core_resource_thread;
+use msg::constellation_msg::{HistoryStateId, PipelineNamespace, PipelineNamespaceId};
+use net::resource_thread::{new_core_resource_thread, new_profile_resource_threads};
 use net::test::parse_hostsfile;
-use net_traits::CoreResourceMsg;
+use net_traits::storage_thread::{StorageThreadMsg, StorageType};
+use net_traits::ResourceThreads;
+use net_traits::{BrowsingProfile, CookieSource, CoreResourceMsg, IpcSend, ProfileId};
 use profile_traits::mem::ProfilerChan as MemProfilerChan;
 use profile_traits::time::ProfilerChan;
+use servo_url::ServoUrl;
 use std::net::IpAddr;
 
 fn ip(s: &str) -> IpAddr {
@@ -33,6 +38,236 @@ fn test_exit() {
     receiver.recv().unwrap();
 }
 
+fn new_profile(id: &str) -> ResourceThreads {
+    let (tx, _rx) = ipc::channel().unwrap();
+    let (mtx, _mrx) = ipc::channel().unwrap();
+    let profile = BrowsingProfile {
+        id: ProfileId(id.to_owned()),
+        config_dir: None,
+    };
+    let (public, _private) = new_profile_resource_threads(
+        profile,
+        "".into(),
+        None,
+        ProfilerChan(tx),
+        MemProfilerChan(mtx),
+        create_embedder_proxy(),
+        None,
+    );
+    public
+}
+
+fn exit_profile(resource_threads: ResourceThreads) {
+    let (sender, receiver) = ipc::channel().unwrap();
+    resource_threads
+        .send(CoreResourceMsg::Exit(sender))
+        .unwrap();
+    receiver.recv().unwrap();
+    let (sender, receiver) = ipc::channel().unwrap();
+    resource_threads
+        .send(StorageThreadMsg::Exit(sender))
+        .unwrap();
+    receiver.recv().unwrap();
+}
+
+#[test]
+fn test_profiles_have_separate_cookie_jars() {
+    let first = new_profile("first");
+    let second = new_profile("second");
+    let url = ServoUrl::parse("http://example.com/").unwrap();
+    let cookie = cookie_rs::Cookie::parse("name=value").unwrap().into_owned();
+    first
+        .send(CoreResourceMsg::SetCookieForUrl(
+            url.clone(),
+            Serde(cookie),
+            CookieSource::HTTP,
+        ))
+        .unwrap();
+
+    let cookies = |resource_threads: &ResourceThreads| {
+        let (sender, receiver) = ipc::channel().unwrap();
+        resource_threads
+            .send(CoreResourceMsg::GetCookiesForUrl(
+                url.clone(),
+                sender,
+                CookieSource::HTTP,
+            ))
+            .unwrap();
+        receiver.recv().unwrap()
+    };
+    assert_eq!(cookies(&first), Some("name=value".to_owned()));
+    assert_eq!(cookies(&second), None);
+
+    exit_profile(first);
+    exit_profile(second);
+}
+
+#[test]
+fn test_profiles_have_separate_storage() {
+    let first = new_profile("first");
+    let second = new_profile("second");
+    let url = ServoUrl::parse("http://example.com/").unwrap();
+    let (sender, receiver) = ipc::channel().unwrap();
+    first
+        .send(StorageThreadMsg::SetItem(
+            sender,
+            url.clone(),
+            StorageType::Local,
+            "key".to_owned(),
+            "value".to_owned(),
+        ))
+        .unwrap();
+    assert_eq!(receiver.recv().unwrap(), Ok((true, None)));
+
+    let item = |resource_threads: &ResourceThreads| {
+        let (sender, receiver) = ipc::channel().unwrap();
+        resource_threads
+            .send(StorageThreadMsg::GetItem(
+                sender,
+                url.clone(),
+                StorageType::Local,
+                "key".to_owned(),
+            ))
+            .unwrap();
+        receiver.recv().unwrap()
+    };
+    assert_eq!(item(&first), Some("value".to_owned()));
+    assert_eq!(item(&second), None);
+
+    exit_profile(first);
+    exit_profile(second);
+}
+
+#[test]
+fn test_get_history_states() {
+    PipelineNamespace::install(PipelineNamespaceId(4321));
+    let resource_threads = new_profile("history");
+    let stored = HistoryStateId::new();
+    let missing = HistoryStateId::new();
+    resource_threads
+        .send(CoreResourceMsg::SetHistoryState(stored, vec![1, 2, 3]))
+        .unwrap();
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    resource_threads
+        .send(CoreResourceMsg::GetHistoryStates(
+            vec![missing, stored, stored],
+            sender,
+        ))
+        .unwrap();
+    assert_eq!(
+        receiver.recv().unwrap(),
+        vec![None, Some(vec![1, 2, 3]), Some(vec![1, 2, 3])]
+    );
+
+    exit_profile(resource_threads);
+}
+
+fn all_cookies(resource_threads: &ResourceThreads, url: Option<&ServoUrl>) -> Vec<String> {
+    let (sender, receiver) = ipc::channel().unwrap();
+    resource_threads
+        .send(CoreResourceMsg::GetAllCookies(url.cloned(), sender))
+        .unwrap();
+    let mut cookies: Vec<_> = receiver
+        .recv()
+        .unwrap()
+        .into_iter()
+        .map(|cookie| format!("{}={}", cookie.0.name(), cookie.0.value()))
+        .collect();
+    cookies.sort();
+    cookies
+}
+
+#[test]
+fn test_get_set_and_delete_cookies() {
+    let resource_threads = new_profile("cookies");
+    let url = ServoUrl::parse("http://example.com/").unwrap();
+    let other_url = ServoUrl::parse("http://mozilla.org/").unwrap();
+    for &(url, cookie) in &[(&url, "a=1"), (&url, "b=2"), (&other_url, "c=3")] {
+        let cookie = cookie_rs::Cookie::parse(cookie).unwrap().into_owned();
+        resource_threads
+            .send(CoreResourceMsg::SetCookieForUrl(
+                url.clone(),
+                Serde(cookie),
+                CookieSource::HTTP,
+            ))
+            .unwrap();
+    }
+    assert_eq!(
+        all_cookies(&resource_threads, None),
+        vec!["a=1", "b=2", "c=3"]
+    );
+    assert_eq!(
+        all_cookies(&resource_threads, Some(&url)),
+        vec!["a=1", "b=2"]
+    );
+
+    resource_threads
+        .send(CoreResourceMsg::DeleteCookie(url.clone(), "a".to_owned()))
+        .unwrap();
+    assert_eq!(all_cookies(&resource_threads, None), vec!["b=2", "c=3"]);
+
+    resource_threads
+        .send(CoreResourceMsg::DeleteCookies(url.clone()))
+        .unwrap();
+    assert_eq!(all_cookies(&resource_threads, None), vec!["c=3"]);
+
+    resource_threads
+        .send(CoreResourceMsg::DeleteAllCookies)
+        .unwrap();
+    assert!(all_cookies(&resource_threads, None).is_empty());
+
+    exit_profile(resource_threads);
+}
+
+#[test]
+fn test_clear_storage_of_origin() {
+    let resource_threads = new_profile("storage");
+    let url = ServoUrl::parse("http://example.com/").unwrap();
+    let other_url = ServoUrl::parse("http://mozilla.org/").unwrap();
+    for url in &[&url, &other_url] {
+        let (sender, receiver) = ipc::channel().unwrap();
+        resource_threads
+            .send(StorageThreadMsg::SetItem(
+                sender,
+                (*url).clone(),
+                StorageType::Local,
+                "key".to_owned(),
+                "value".to_owned(),
+            ))
+            .unwrap();
+        receiver.recv().unwrap().unwrap();
+    }
+
+    let length = |url: &ServoUrl| {
+        let (sender, receiver) = ipc::channel().unwrap();
+        resource_threads
+            .send(StorageThreadMsg::Length(
+                sender,
+                url.clone(),
+                StorageType::Local,
+            ))
+            .unwrap();
+        receiver.recv().unwrap()
+    };
+
+    resource_threads
+        .send(StorageThreadMsg::ClearOrigins(
+            Some(url.clone()),
+            StorageType::Local,
+        ))
+        .unwrap();
+    assert_eq!(length(&url), 0);
+    assert_eq!(length(&other_url), 1);
+
+    resource_threads
+        .send(StorageThreadMsg::ClearOrigins(None, StorageType::Local))
+        .unwrap();
+    assert_eq!(length(&other_url), 0);
+
+    exit_profile(resource_threads);
+}
+
 #[test]
 fn test_parse_hostsfile() {
     let mock_hosts_file_content = "127.0.0.1 foo.bar.com\n127.0.0.2 servo.test.server";
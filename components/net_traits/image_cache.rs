@@ -138,6 +138,10 @@ pub trait ImageCache: Sync + Send {
 
     /// Inform the image cache about a response for a pending request.
     fn notify_pending_response(&self, id: PendingImageId, action: FetchResponseMsg);
+
+    /// Drop the decoded images that are not used outside of the cache, because the system
+    /// is low on memory. Returns the number of bytes reclaimed.
+    fn purge(&self) -> usize;
 }
 
 /// Whether this response passed any CORS checks, and is thus safe to read from
@@ -18,7 +18,7 @@ extern crate serde;
 use crate::filemanager_thread::FileManagerThreadMsg;
 use crate::request::{Request, RequestBuilder};
 use crate::response::{HttpsState, Response, ResponseInit};
-use crate::storage_thread::StorageThreadMsg;
+use crate::storage_thread::{StorageThreadMsg, StorageType};
 use cookie::Cookie;
 use headers::{ContentType, HeaderMapExt, ReferrerPolicy as ReferrerPolicyHeader};
 use http::{Error as HttpError, HeaderMap};
@@ -32,6 +32,8 @@ use mime::Mime;
 use msg::constellation_msg::HistoryStateId;
 use servo_rand::RngCore;
 use servo_url::{ImmutableOrigin, ServoUrl};
+use std::fmt;
+use std::path::PathBuf;
 use time::precise_time_ns;
 use webrender_api::{ImageData, ImageDescriptor, ImageKey};
 
@@ -365,6 +367,10 @@ impl ResourceThreads {
     pub fn clear_cache(&self) {
         let _ = self.core_thread.send(CoreResourceMsg::ClearCache);
     }
+
+    pub fn handle_memory_pressure(&self) {
+        let _ = self.core_thread.send(CoreResourceMsg::MemoryPressure);
+    }
 }
 
 impl IpcSend<CoreResourceMsg> for ResourceThreads {
@@ -390,6 +396,51 @@ impl IpcSend<StorageThreadMsg> for ResourceThreads {
 // Ignore the sub-fields
 malloc_size_of_is_0!(ResourceThreads);
 
+/// Identifies an isolated browsing profile. Browsers in different profiles don't share
+/// cookies, web storage, HSTS entries, authentication credentials or HTTP caches.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
+pub struct ProfileId(pub String);
+
+impl fmt::Display for ProfileId {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.0)
+    }
+}
+
+/// The settings used to create the resource threads of an isolated browsing profile.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BrowsingProfile {
+    /// Browsers created with the same profile id share their state.
+    pub id: ProfileId,
+    /// The directory the profile's cookies, HSTS list, authentication cache and local
+    /// storage are read from when the profile is created, and written to at shutdown.
+    /// Without one, the profile only lives in memory.
+    pub config_dir: Option<PathBuf>,
+}
+
+/// A request from the embedder to inspect or remove the data sites keep in the
+/// resource threads of a browser. Cookies are read from and added to the public
+/// state only, while removals apply to both the public and the private state.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum SiteDataRequest {
+    /// Retrieve the cookies of the site of a URL, or every cookie.
+    GetCookies(Option<ServoUrl>, IpcSender<Vec<Serde<Cookie<'static>>>>),
+    /// Store a cookie as if it was set by a response from a URL.
+    SetCookie(ServoUrl, Serde<Cookie<'static>>),
+    /// Remove the cookies with the given name that would be sent to a URL.
+    DeleteCookie(ServoUrl, String),
+    /// Remove the cookies of the site of a URL, or every cookie.
+    ClearCookies(Option<ServoUrl>),
+    /// Remove the web storage data of the origin of a URL, or of every origin.
+    ClearStorage(Option<ServoUrl>, StorageType),
+    /// Remove the HSTS entries learned from responses for a host, or for every host.
+    /// Preloaded entries are kept.
+    ClearHstsEntries(Option<String>),
+    /// Remove the cached HTTP authentication credentials of the origin of a URL,
+    /// or of every origin.
+    ClearAuthCache(Option<ServoUrl>),
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub enum IncludeSubdomains {
     Included,
@@ -455,8 +506,20 @@ pub enum CoreResourceMsg {
         CookieSource,
     ),
     DeleteCookies(ServoUrl),
+    /// Retrieve the stored cookies of the site of a given URL, or every stored cookie
+    GetAllCookies(Option<ServoUrl>, IpcSender<Vec<Serde<Cookie<'static>>>>),
+    /// Remove the cookies with a given name that would be sent to a given URL
+    DeleteCookie(ServoUrl, String),
+    /// Remove every stored cookie
+    DeleteAllCookies,
+    /// Remove the HSTS entries learned from responses for a given host, or for every host
+    ClearHstsEntries(Option<String>),
+    /// Remove the cached authentication credentials of a given origin, or of every origin
+    ClearAuthCache(Option<ImmutableOrigin>),
     /// Get a history state by a given history state id
     GetHistoryState(HistoryStateId, IpcSender<Option<Vec<u8>>>),
+    /// Get the history states of the given ids, in the same order
+    GetHistoryStates(Vec<HistoryStateId>, IpcSender<Vec<Option<Vec<u8>>>>),
     /// Set a history state for a given history state id
     SetHistoryState(HistoryStateId, Vec<u8>),
     /// Removes history states for the given ids
@@ -465,6 +528,9 @@ pub enum CoreResourceMsg {
     Synchronize(IpcSender<()>),
     /// Clear the network cache.
     ClearCache,
+    /// Clear the network cache because the system is low on memory, and report the memory
+    /// that was reclaimed.
+    MemoryPressure,
     /// Send the service worker network mediator for an origin to CoreResourceThread
     NetworkMediator(IpcSender<CustomResponseMediator>, ImmutableOrigin),
     /// Message forwarded to file manager's handler
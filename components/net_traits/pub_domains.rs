@@ -17,6 +17,7 @@
 use embedder_traits::resources::{self, Resource};
 use servo_url::{Host, ImmutableOrigin, ServoUrl};
 use std::collections::HashSet;
+use std::fmt;
 use std::iter::FromIterator;
 
 #[derive(Clone, Debug)]
@@ -155,3 +156,32 @@ pub fn reg_host(url: &ServoUrl) -> Option<Host> {
         ImmutableOrigin::Opaque(_) => None,
     }
 }
+
+/// A site: the scheme and registered domain name of an origin.
+/// Documents from different sites never share an event loop.
+/// <https://html.spec.whatwg.org/multipage/#site>
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Site {
+    pub scheme: String,
+    pub host: Host,
+}
+
+impl fmt::Display for Site {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}://{}", self.scheme, self.host)
+    }
+}
+
+/// The site of a URL.
+/// Returns None if the URL has an opaque origin, since such a URL is not same-site with
+/// anything, not even itself.
+pub fn reg_site(url: &ServoUrl) -> Option<Site> {
+    match url.origin() {
+        ImmutableOrigin::Tuple(scheme, Host::Domain(domain), _) => Some(Site {
+            scheme,
+            host: Host::Domain(String::from(reg_suffix(&*domain))),
+        }),
+        ImmutableOrigin::Tuple(scheme, ip, _) => Some(Site { scheme, host: ip }),
+        ImmutableOrigin::Opaque(_) => None,
+    }
+}
@@ -41,6 +41,9 @@ pub enum StorageThreadMsg {
     /// clears the associated storage data by removing all the key/value pairs
     Clear(IpcSender<bool>, ServoUrl, StorageType),
 
+    /// removes all the key/value pairs of the origin of the given url, or of every origin
+    ClearOrigins(Option<ServoUrl>, StorageType),
+
     /// send a reply when done cleaning up thread resources and then shut it down
     Exit(IpcSender<()>),
 }
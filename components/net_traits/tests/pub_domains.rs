@@ -2,7 +2,8 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use net_traits::pub_domains::{is_pub_domain, is_reg_domain, pub_suffix, reg_suffix};
+use net_traits::pub_domains::{is_pub_domain, is_reg_domain, pub_suffix, reg_site, reg_suffix};
+use servo_url::ServoUrl;
 
 // These tests may need to be updated if the PSL changes.
 
@@ -120,3 +121,22 @@ fn test_weirdness() {
     );
     assert!(!is_pub_domain(pub_suffix("city.yokohama.jp")));
 }
+
+#[test]
+fn test_reg_site() {
+    let site = |url: &str| {
+        reg_site(&ServoUrl::parse(url).unwrap())
+            .unwrap()
+            .to_string()
+    };
+    assert_eq!(site("http://www.servo.org/"), "http://servo.org");
+    assert_eq!(site("http://servo.org:8000/"), "http://servo.org");
+    assert_eq!(site("https://www.servo.org/"), "https://servo.org");
+    assert_eq!(site("http://www.servo.co.uk/"), "http://servo.co.uk");
+    assert_eq!(site("http://127.0.0.1:8000/"), "http://127.0.0.1");
+    assert_eq!(
+        site("blob:https://www.servo.org/1d4b0ea6-64ab-4b2c-a32c-7ba71ec6c6b8"),
+        "https://servo.org"
+    );
+    assert!(reg_site(&ServoUrl::parse("data:text/html,servo").unwrap()).is_none());
+}
@@ -0,0 +1,212 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A module for writing time profiler traces in the Chrome Trace Event format, which
+//! chrome://tracing, Perfetto and other standard trace viewers can load.
+//!
+//! <https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU>
+
+use crate::time::category_name;
+use profile_traits::time::{ProfilerCategory, ProfilerThread, TimerMetadata};
+use profile_traits::time::{TimerMetadataFrameType, TimerMetadataReflowType};
+use profile_traits::time::{TraceFlowId, TraceFlowKind, TraceFlowPhase};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path;
+
+/// An RAII class for writing a Chrome trace file. Events are written as they arrive
+/// in the JSON array format, whose closing bracket is optional, so the file can still
+/// be loaded if Servo doesn't shut down cleanly.
+#[derive(Debug)]
+pub struct ChromeTrace {
+    file: BufWriter<fs::File>,
+    /// The threads whose name has already been written.
+    named_threads: HashSet<(u32, u64)>,
+    /// Whether no event has been written yet.
+    empty: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct TraceEvent<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cat: Option<&'static str>,
+    ph: &'static str,
+    /// The time of the event, in microseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ts: Option<f64>,
+    /// The duration of complete events, in microseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dur: Option<f64>,
+    pid: u32,
+    tid: u64,
+    /// The identifier of flow events, as a string since trace viewers parse numbers as
+    /// doubles.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    args: Option<Value>,
+}
+
+impl ChromeTrace {
+    /// Create a new ChromeTrace and write the start of the event array out to disk.
+    pub fn new<P>(trace_file_path: P) -> io::Result<ChromeTrace>
+    where
+        P: AsRef<path::Path>,
+    {
+        let mut file = BufWriter::new(fs::File::create(trace_file_path)?);
+        writeln!(file, "[")?;
+        Ok(ChromeTrace {
+            file,
+            named_threads: HashSet::new(),
+            empty: true,
+        })
+    }
+
+    /// Write a span of time spent in a profiler category as a complete event, on the
+    /// track of the thread it was recorded on. Spans of the same thread that contain
+    /// one another are shown nested.
+    pub fn write_span(
+        &mut self,
+        category: &(ProfilerCategory, Option<TimerMetadata>),
+        time: (u64, u64),
+        thread: &ProfilerThread,
+    ) {
+        self.name_thread(thread);
+        let args = category.1.as_ref().map(|meta| {
+            json!({
+                "url": meta.url,
+                "iframe": meta.iframe == TimerMetadataFrameType::IFrame,
+                "incremental": meta.incremental == TimerMetadataReflowType::Incremental,
+            })
+        });
+        self.write_event(&TraceEvent {
+            name: category_name(category.0),
+            cat: Some(category_group(category.0)),
+            ph: "X",
+            ts: Some(microseconds(time.0)),
+            dur: Some(microseconds(time.1.saturating_sub(time.0))),
+            pid: thread.process_id,
+            tid: thread.thread_id,
+            id: None,
+            args,
+        });
+    }
+
+    /// Write a flow event, which trace viewers draw as an arrow between the spans of
+    /// the threads that the flow went through.
+    pub fn write_flow(
+        &mut self,
+        kind: TraceFlowKind,
+        id: TraceFlowId,
+        phase: TraceFlowPhase,
+        time: u64,
+        thread: &ProfilerThread,
+    ) {
+        self.name_thread(thread);
+        let name = match kind {
+            TraceFlowKind::Reflow => "Reflow",
+            TraceFlowKind::DisplayList => "Display List",
+        };
+        // Start and step events bind to the span that encloses them, and the end event
+        // to the next span of its thread, since the receiving end often handles the
+        // message before starting to profile the resulting work.
+        let ph = match phase {
+            TraceFlowPhase::Start => "s",
+            TraceFlowPhase::Step => "t",
+            TraceFlowPhase::End => "f",
+        };
+        self.write_event(&TraceEvent {
+            name,
+            cat: Some("flow"),
+            ph,
+            ts: Some(microseconds(time)),
+            dur: None,
+            pid: thread.process_id,
+            tid: thread.thread_id,
+            id: Some(format!("{:#x}", id.0)),
+            args: None,
+        });
+    }
+
+    /// Write the metadata event that names the track of a thread, the first time the
+    /// thread is seen.
+    fn name_thread(&mut self, thread: &ProfilerThread) {
+        if !self
+            .named_threads
+            .insert((thread.process_id, thread.thread_id))
+        {
+            return;
+        }
+        let name = thread
+            .name
+            .as_ref()
+            .map_or("Unnamed thread", String::as_str);
+        self.write_event(&TraceEvent {
+            name: "thread_name",
+            cat: None,
+            ph: "M",
+            ts: None,
+            dur: None,
+            pid: thread.process_id,
+            tid: thread.thread_id,
+            id: None,
+            args: Some(json!({ "name": name })),
+        });
+    }
+
+    fn write_event(&mut self, event: &TraceEvent) {
+        if !self.empty {
+            writeln!(&mut self.file, ",").unwrap();
+        }
+        self.empty = false;
+        serde_json::to_writer(&mut self.file, event).unwrap();
+    }
+}
+
+impl Drop for ChromeTrace {
+    /// Close the event array on destruction.
+    fn drop(&mut self) {
+        writeln!(&mut self.file, "\n]").unwrap();
+        self.file.flush().unwrap();
+    }
+}
+
+fn microseconds(nanoseconds: u64) -> f64 {
+    nanoseconds as f64 / 1000.
+}
+
+/// The trace event category of a profiler category, which trace viewers can filter on.
+fn category_group(category: ProfilerCategory) -> &'static str {
+    match category {
+        ProfilerCategory::Compositing => "compositing",
+        ProfilerCategory::LayoutPerform |
+        ProfilerCategory::LayoutStyleRecalc |
+        ProfilerCategory::LayoutTextShaping |
+        ProfilerCategory::LayoutRestyleDamagePropagation |
+        ProfilerCategory::LayoutNonIncrementalReset |
+        ProfilerCategory::LayoutSelectorMatch |
+        ProfilerCategory::LayoutTreeBuilder |
+        ProfilerCategory::LayoutDamagePropagate |
+        ProfilerCategory::LayoutGeneratedContent |
+        ProfilerCategory::LayoutDisplayListSorting |
+        ProfilerCategory::LayoutFloatPlacementSpeculation |
+        ProfilerCategory::LayoutMain |
+        ProfilerCategory::LayoutStoreOverflow |
+        ProfilerCategory::LayoutParallelWarmup |
+        ProfilerCategory::LayoutDispListBuild => "layout",
+        ProfilerCategory::NetHTTPRequestResponse => "net",
+        ProfilerCategory::PaintingPerTile |
+        ProfilerCategory::PaintingPrepBuff |
+        ProfilerCategory::Painting => "painting",
+        ProfilerCategory::ImageDecoding | ProfilerCategory::ImageSaving => "image",
+        ProfilerCategory::TimeToFirstPaint |
+        ProfilerCategory::TimeToFirstContentfulPaint |
+        ProfilerCategory::TimeToInteractive => "metrics",
+        ProfilerCategory::IpcReceiver | ProfilerCategory::IpcBytesReceiver => "ipc",
+        _ => "script",
+    }
+}
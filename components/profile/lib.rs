@@ -9,6 +9,7 @@ extern crate profile_traits;
 #[macro_use]
 extern crate serde;
 
+pub mod chrome_trace;
 #[allow(unsafe_code)]
 pub mod mem;
 pub mod time;
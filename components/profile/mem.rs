@@ -7,11 +7,12 @@
 use crate::time::duration_from_seconds;
 use ipc_channel::ipc::{self, IpcReceiver};
 use ipc_channel::router::ROUTER;
-use profile_traits::mem::ReportsChan;
+use profile_traits::mem::{MemoryReportSnapshot, ReportsChan, SnapshotReport};
 use profile_traits::mem::{ProfilerChan, ProfilerMsg, ReportKind, Reporter, ReporterRequest};
 use std::borrow::ToOwned;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::process;
 use std::thread;
 use std::time::Instant;
 
@@ -24,10 +25,14 @@ pub struct Profiler {
 
     /// Instant at which this profiler was created.
     created: Instant,
+
+    /// The memory reclaimed in response to memory pressure, by process and path.
+    reclaimed: BTreeMap<(u32, Vec<String>), usize>,
 }
 
 const JEMALLOC_HEAP_ALLOCATED_STR: &'static str = "jemalloc-heap-allocated";
 const SYSTEM_HEAP_ALLOCATED_STR: &'static str = "system-heap-allocated";
+const MEMORY_PRESSURE_RECLAIMED_STR: &'static str = "memory-pressure-reclaimed";
 
 impl Profiler {
     pub fn create(period: Option<f64>) -> ProfilerChan {
@@ -72,7 +77,7 @@ impl Profiler {
         );
         mem_profiler_chan.send(ProfilerMsg::RegisterReporter(
             "system".to_owned(),
-            Reporter(system_reporter_sender),
+            Reporter::new(system_reporter_sender, None),
         ));
 
         mem_profiler_chan
@@ -83,6 +88,7 @@ impl Profiler {
             port: port,
             reporters: HashMap::new(),
             created: Instant::now(),
+            reclaimed: BTreeMap::new(),
         }
     }
 
@@ -121,14 +127,42 @@ impl Profiler {
                 true
             },
 
+            ProfilerMsg::Snapshot(sender) => {
+                let _ = sender.send(self.collect_snapshot());
+                true
+            },
+
+            ProfilerMsg::ReportReclaimed(process_id, reports) => {
+                for report in reports {
+                    *self.reclaimed.entry((process_id, report.path)).or_insert(0) += report.size;
+                }
+                true
+            },
+
             ProfilerMsg::Exit => false,
         }
     }
 
     fn handle_print_msg(&self) {
-        let elapsed = self.created.elapsed();
-        println!("Begin memory reports {}", elapsed.as_secs());
+        let snapshot = self.collect_snapshot();
+
+        println!("Begin memory reports {}", snapshot.time / 1000);
+        println!("|");
+
+        let mut forest = ReportsForest::new();
+        for report in &snapshot.reports {
+            forest.insert(&report.path, report.size);
+        }
+        forest.print();
+
         println!("|");
+        println!("End memory reports");
+        println!("");
+    }
+
+    /// Collect the reports of all the registered reporters.
+    pub fn collect_snapshot(&self) -> MemoryReportSnapshot {
+        let elapsed = self.created.elapsed();
 
         // Collect reports from memory reporters.
         //
@@ -141,7 +175,7 @@ impl Profiler {
         // use that to compute the special "jemalloc-heap-unclassified" and
         // "system-heap-unclassified" values.
 
-        let mut forest = ReportsForest::new();
+        let mut snapshot_reports = vec![];
 
         let mut jemalloc_heap_reported_size = 0;
         let mut system_heap_reported_size = 0;
@@ -186,32 +220,59 @@ impl Profiler {
                             system_heap_allocated_size = Some(report.size);
                         }
                     }
-
-                    // Insert the report.
-                    forest.insert(&report.path, report.size);
                 }
+
+                snapshot_reports.extend(reports.into_iter().map(|report| SnapshotReport {
+                    path: report.path,
+                    kind: report.kind,
+                    size: report.size,
+                    process_id: reporter.process_id,
+                    pipeline_id: reporter.pipeline_id,
+                }));
             }
         }
 
         // Compute and insert the heap-unclassified values.
+        let mut unclassified = |path, kind, size| {
+            snapshot_reports.push(SnapshotReport {
+                path,
+                kind,
+                size,
+                process_id: process::id(),
+                pipeline_id: None,
+            })
+        };
         if let Some(jemalloc_heap_allocated_size) = jemalloc_heap_allocated_size {
-            forest.insert(
-                &path!["explicit", "jemalloc-heap-unclassified"],
+            unclassified(
+                path!["explicit", "jemalloc-heap-unclassified"],
+                ReportKind::ExplicitJemallocHeapSize,
                 jemalloc_heap_allocated_size - jemalloc_heap_reported_size,
             );
         }
         if let Some(system_heap_allocated_size) = system_heap_allocated_size {
-            forest.insert(
-                &path!["explicit", "system-heap-unclassified"],
+            unclassified(
+                path!["explicit", "system-heap-unclassified"],
+                ReportKind::ExplicitSystemHeapSize,
                 system_heap_allocated_size - system_heap_reported_size,
             );
         }
 
-        forest.print();
+        for ((process_id, path), size) in &self.reclaimed {
+            let mut reclaimed_path = path![MEMORY_PRESSURE_RECLAIMED_STR];
+            reclaimed_path.extend(path.iter().cloned());
+            snapshot_reports.push(SnapshotReport {
+                path: reclaimed_path,
+                kind: ReportKind::NonExplicitSize,
+                size: *size,
+                process_id: *process_id,
+                pipeline_id: None,
+            });
+        }
 
-        println!("|");
-        println!("End memory reports");
-        println!("");
+        MemoryReportSnapshot {
+            time: elapsed.as_millis() as u64,
+            reports: snapshot_reports,
+        }
     }
 }
 
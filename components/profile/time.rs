@@ -4,6 +4,7 @@
 
 //! Timing functions.
 
+use crate::chrome_trace::ChromeTrace;
 use crate::trace_dump::TraceDump;
 use ipc_channel::ipc::{self, IpcReceiver};
 use profile_traits::time::{
@@ -90,67 +91,96 @@ impl Formattable for ProfilerCategory {
             ProfilerCategory::LayoutTextShaping => "| + ",
             _ => "",
         };
-        let name = match *self {
-            ProfilerCategory::Compositing => "Compositing",
-            ProfilerCategory::LayoutPerform => "Layout",
-            ProfilerCategory::LayoutStyleRecalc => "Style Recalc",
-            ProfilerCategory::LayoutTextShaping => "Text Shaping",
-            ProfilerCategory::LayoutRestyleDamagePropagation => "Restyle Damage Propagation",
-            ProfilerCategory::LayoutNonIncrementalReset => "Non-incremental reset (temporary)",
-            ProfilerCategory::LayoutSelectorMatch => "Selector Matching",
-            ProfilerCategory::LayoutTreeBuilder => "Tree Building",
-            ProfilerCategory::LayoutDamagePropagate => "Damage Propagation",
-            ProfilerCategory::LayoutDisplayListSorting => "Sorting Display List",
-            ProfilerCategory::LayoutGeneratedContent => "Generated Content Resolution",
-            ProfilerCategory::LayoutFloatPlacementSpeculation => "Float Placement Speculation",
-            ProfilerCategory::LayoutMain => "Primary Layout Pass",
-            ProfilerCategory::LayoutStoreOverflow => "Store Overflow",
-            ProfilerCategory::LayoutParallelWarmup => "Parallel Warmup",
-            ProfilerCategory::LayoutDispListBuild => "Display List Construction",
-            ProfilerCategory::NetHTTPRequestResponse => "Network HTTP Request/Response",
-            ProfilerCategory::PaintingPerTile => "Painting Per Tile",
-            ProfilerCategory::PaintingPrepBuff => "Buffer Prep",
-            ProfilerCategory::Painting => "Painting",
-            ProfilerCategory::ImageDecoding => "Image Decoding",
-            ProfilerCategory::ImageSaving => "Image Saving",
-            ProfilerCategory::ScriptAttachLayout => "Script Attach Layout",
-            ProfilerCategory::ScriptConstellationMsg => "Script Constellation Msg",
-            ProfilerCategory::ScriptDevtoolsMsg => "Script Devtools Msg",
-            ProfilerCategory::ScriptDocumentEvent => "Script Document Event",
-            ProfilerCategory::ScriptDomEvent => "Script Dom Event",
-            ProfilerCategory::ScriptEvaluate => "Script JS Evaluate",
-            ProfilerCategory::ScriptFileRead => "Script File Read",
-            ProfilerCategory::ScriptHistoryEvent => "Script History Event",
-            ProfilerCategory::ScriptImageCacheMsg => "Script Image Cache Msg",
-            ProfilerCategory::ScriptInputEvent => "Script Input Event",
-            ProfilerCategory::ScriptNetworkEvent => "Script Network Event",
-            ProfilerCategory::ScriptParseHTML => "Script Parse HTML",
-            ProfilerCategory::ScriptParseXML => "Script Parse XML",
-            ProfilerCategory::ScriptPlannedNavigation => "Script Planned Navigation",
-            ProfilerCategory::ScriptPortMessage => "Script Port Message",
-            ProfilerCategory::ScriptResize => "Script Resize",
-            ProfilerCategory::ScriptEvent => "Script Event",
-            ProfilerCategory::ScriptUpdateReplacedElement => "Script Update Replaced Element",
-            ProfilerCategory::ScriptSetScrollState => "Script Set Scroll State",
-            ProfilerCategory::ScriptSetViewport => "Script Set Viewport",
-            ProfilerCategory::ScriptTimerEvent => "Script Timer Event",
-            ProfilerCategory::ScriptStylesheetLoad => "Script Stylesheet Load",
-            ProfilerCategory::ScriptWebSocketEvent => "Script Web Socket Event",
-            ProfilerCategory::ScriptWorkerEvent => "Script Worker Event",
-            ProfilerCategory::ScriptServiceWorkerEvent => "Script Service Worker Event",
-            ProfilerCategory::ScriptEnterFullscreen => "Script Enter Fullscreen",
-            ProfilerCategory::ScriptExitFullscreen => "Script Exit Fullscreen",
-            ProfilerCategory::ScriptWebVREvent => "Script WebVR Event",
-            ProfilerCategory::ScriptWorkletEvent => "Script Worklet Event",
-            ProfilerCategory::ScriptPerformanceEvent => "Script Performance Event",
-            ProfilerCategory::ScriptWebGPUMsg => "Script WebGPU Message",
-            ProfilerCategory::TimeToFirstPaint => "Time To First Paint",
-            ProfilerCategory::TimeToFirstContentfulPaint => "Time To First Contentful Paint",
-            ProfilerCategory::TimeToInteractive => "Time to Interactive",
-            ProfilerCategory::IpcReceiver => "Blocked at IPC Receive",
-            ProfilerCategory::IpcBytesReceiver => "Blocked at IPC Bytes Receive",
-        };
-        format!("{}{}", padding, name)
+        format!("{}{}", padding, category_name(*self))
+    }
+}
+
+/// The name of a profiler category, as shown in profiler output.
+pub fn category_name(category: ProfilerCategory) -> &'static str {
+    match category {
+        ProfilerCategory::Compositing => "Compositing",
+        ProfilerCategory::LayoutPerform => "Layout",
+        ProfilerCategory::LayoutStyleRecalc => "Style Recalc",
+        ProfilerCategory::LayoutTextShaping => "Text Shaping",
+        ProfilerCategory::LayoutRestyleDamagePropagation => "Restyle Damage Propagation",
+        ProfilerCategory::LayoutNonIncrementalReset => "Non-incremental reset (temporary)",
+        ProfilerCategory::LayoutSelectorMatch => "Selector Matching",
+        ProfilerCategory::LayoutTreeBuilder => "Tree Building",
+        ProfilerCategory::LayoutDamagePropagate => "Damage Propagation",
+        ProfilerCategory::LayoutDisplayListSorting => "Sorting Display List",
+        ProfilerCategory::LayoutGeneratedContent => "Generated Content Resolution",
+        ProfilerCategory::LayoutFloatPlacementSpeculation => "Float Placement Speculation",
+        ProfilerCategory::LayoutMain => "Primary Layout Pass",
+        ProfilerCategory::LayoutStoreOverflow => "Store Overflow",
+        ProfilerCategory::LayoutParallelWarmup => "Parallel Warmup",
+        ProfilerCategory::LayoutDispListBuild => "Display List Construction",
+        ProfilerCategory::NetHTTPRequestResponse => "Network HTTP Request/Response",
+        ProfilerCategory::PaintingPerTile => "Painting Per Tile",
+        ProfilerCategory::PaintingPrepBuff => "Buffer Prep",
+        ProfilerCategory::Painting => "Painting",
+        ProfilerCategory::ImageDecoding => "Image Decoding",
+        ProfilerCategory::ImageSaving => "Image Saving",
+        ProfilerCategory::ScriptAttachLayout => "Script Attach Layout",
+        ProfilerCategory::ScriptConstellationMsg => "Script Constellation Msg",
+        ProfilerCategory::ScriptDevtoolsMsg => "Script Devtools Msg",
+        ProfilerCategory::ScriptDocumentEvent => "Script Document Event",
+        ProfilerCategory::ScriptDomEvent => "Script Dom Event",
+        ProfilerCategory::ScriptEvaluate => "Script JS Evaluate",
+        ProfilerCategory::ScriptFileRead => "Script File Read",
+        ProfilerCategory::ScriptHistoryEvent => "Script History Event",
+        ProfilerCategory::ScriptImageCacheMsg => "Script Image Cache Msg",
+        ProfilerCategory::ScriptInputEvent => "Script Input Event",
+        ProfilerCategory::ScriptNetworkEvent => "Script Network Event",
+        ProfilerCategory::ScriptParseHTML => "Script Parse HTML",
+        ProfilerCategory::ScriptParseXML => "Script Parse XML",
+        ProfilerCategory::ScriptPlannedNavigation => "Script Planned Navigation",
+        ProfilerCategory::ScriptPortMessage => "Script Port Message",
+        ProfilerCategory::ScriptResize => "Script Resize",
+        ProfilerCategory::ScriptEvent => "Script Event",
+        ProfilerCategory::ScriptUpdateReplacedElement => "Script Update Replaced Element",
+        ProfilerCategory::ScriptSetScrollState => "Script Set Scroll State",
+        ProfilerCategory::ScriptSetViewport => "Script Set Viewport",
+        ProfilerCategory::ScriptTimerEvent => "Script Timer Event",
+        ProfilerCategory::ScriptStylesheetLoad => "Script Stylesheet Load",
+        ProfilerCategory::ScriptWebSocketEvent => "Script Web Socket Event",
+        ProfilerCategory::ScriptWorkerEvent => "Script Worker Event",
+        ProfilerCategory::ScriptServiceWorkerEvent => "Script Service Worker Event",
+        ProfilerCategory::ScriptEnterFullscreen => "Script Enter Fullscreen",
+        ProfilerCategory::ScriptExitFullscreen => "Script Exit Fullscreen",
+        ProfilerCategory::ScriptWebVREvent => "Script WebVR Event",
+        ProfilerCategory::ScriptWorkletEvent => "Script Worklet Event",
+        ProfilerCategory::ScriptPerformanceEvent => "Script Performance Event",
+        ProfilerCategory::ScriptWebGPUMsg => "Script WebGPU Message",
+        ProfilerCategory::TimeToFirstPaint => "Time To First Paint",
+        ProfilerCategory::TimeToFirstContentfulPaint => "Time To First Contentful Paint",
+        ProfilerCategory::TimeToInteractive => "Time to Interactive",
+        ProfilerCategory::IpcReceiver => "Blocked at IPC Receive",
+        ProfilerCategory::IpcBytesReceiver => "Blocked at IPC Bytes Receive",
+    }
+}
+
+/// A file that profiler spans are written to as they arrive.
+pub enum ProfilerTrace {
+    Html(TraceDump),
+    Chrome(ChromeTrace),
+}
+
+impl ProfilerTrace {
+    /// Create a trace file in the Chrome Trace Event format if the path has a `json`
+    /// extension, and as a self-contained HTML timeline otherwise.
+    pub fn new<P>(trace_file_path: P) -> io::Result<ProfilerTrace>
+    where
+        P: AsRef<Path>,
+    {
+        let path = trace_file_path.as_ref();
+        if path
+            .extension()
+            .map_or(false, |extension| extension == "json")
+        {
+            ChromeTrace::new(path).map(ProfilerTrace::Chrome)
+        } else {
+            TraceDump::new(path).map(ProfilerTrace::Html)
+        }
     }
 }
 
@@ -162,7 +192,7 @@ pub struct Profiler {
     buckets: ProfilerBuckets,
     output: Option<OutputOptions>,
     pub last_msg: Option<ProfilerMsg>,
-    trace: Option<TraceDump>,
+    trace: Option<ProfilerTrace>,
     blocked_layout_queries: HashMap<String, u32>,
 }
 
@@ -176,7 +206,7 @@ impl Profiler {
                 thread::Builder::new()
                     .name("Time profiler".to_owned())
                     .spawn(move || {
-                        let trace = file_path.as_ref().and_then(|p| TraceDump::new(p).ok());
+                        let trace = file_path.as_ref().and_then(|p| ProfilerTrace::new(p).ok());
                         let mut profiler = Profiler::new(port, trace, Some(outputoption));
                         profiler.start();
                     })
@@ -206,7 +236,7 @@ impl Profiler {
                     thread::Builder::new()
                         .name("Time profiler".to_owned())
                         .spawn(move || {
-                            let trace = file_path.as_ref().and_then(|p| TraceDump::new(p).ok());
+                            let trace = file_path.as_ref().and_then(|p| ProfilerTrace::new(p).ok());
                             let mut profiler = Profiler::new(port, trace, None);
                             profiler.start();
                         })
@@ -235,7 +265,7 @@ impl Profiler {
 
     pub fn new(
         port: IpcReceiver<ProfilerMsg>,
-        trace: Option<TraceDump>,
+        trace: Option<ProfilerTrace>,
         output: Option<OutputOptions>,
     ) -> Profiler {
         Profiler {
@@ -262,13 +292,22 @@ impl Profiler {
 
     fn handle_msg(&mut self, msg: ProfilerMsg) -> bool {
         match msg.clone() {
-            ProfilerMsg::Time(k, t) => {
-                if let Some(ref mut trace) = self.trace {
-                    trace.write_one(&k, t);
+            ProfilerMsg::Time(k, t, thread) => {
+                match self.trace {
+                    Some(ProfilerTrace::Html(ref mut trace)) => trace.write_one(&k, t),
+                    Some(ProfilerTrace::Chrome(ref mut trace)) => trace.write_span(&k, t, &thread),
+                    None => {},
                 }
                 let ms = (t.1 - t.0) as f64 / 1000000f64;
                 self.find_or_insert(k, ms);
             },
+            ProfilerMsg::Flow(kind, id, phase, time, thread) => {
+                if let Some(ProfilerTrace::Chrome(ref mut trace)) = self.trace {
+                    trace.write_flow(kind, id, phase, time, &thread);
+                }
+                // Flows don't add any data to print.
+                return true;
+            },
             ProfilerMsg::Print => {
                 if let Some(ProfilerMsg::Time(..)) = self.last_msg {
                     // only print if more data has arrived since the last printout
@@ -9,7 +9,10 @@
 use crossbeam_channel::Sender;
 use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
+use msg::constellation_msg::PipelineId;
+use std::collections::{BTreeMap, BTreeSet};
 use std::marker::Send;
+use std::process;
 
 /// A trait to abstract away the various kinds of message senders we use.
 pub trait OpaqueSender<T> {
@@ -57,11 +60,13 @@ impl ProfilerChan {
         }
     }
 
-    /// Runs `f()` with memory profiling.
+    /// Runs `f()` with memory profiling. `pipeline_id` is the pipeline that all the reports of
+    /// the reporter belong to, if any.
     pub fn run_with_memory_reporting<F, M, T, C>(
         &self,
         f: F,
         reporter_name: String,
+        pipeline_id: Option<PipelineId>,
         channel_for_reporter: C,
         msg: M,
     ) where
@@ -82,13 +87,20 @@ impl ProfilerChan {
         );
         self.send(ProfilerMsg::RegisterReporter(
             reporter_name.clone(),
-            Reporter(reporter_sender),
+            Reporter::new(reporter_sender, pipeline_id),
         ));
 
         f();
 
         self.send(ProfilerMsg::UnregisterReporter(reporter_name));
     }
+
+    /// Records memory that this process reclaimed in response to memory pressure.
+    pub fn report_reclaimed(&self, reports: Vec<Report>) {
+        if !reports.is_empty() {
+            self.send(ProfilerMsg::ReportReclaimed(process::id(), reports));
+        }
+    }
 }
 
 /// The various kinds of memory measurement.
@@ -101,7 +113,7 @@ impl ProfilerChan {
 /// and thread stacks. "explicit" is not guaranteed to cover every explicit allocation, but it does
 /// cover most (including the entire heap), and therefore it is the single best number to focus on
 /// when trying to reduce memory usage.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum ReportKind {
     /// A size measurement for an explicit allocation on the jemalloc heap. This should be used
     /// for any measurements done via the `MallocSizeOf` trait.
@@ -165,12 +177,28 @@ pub struct ReporterRequest {
 /// registering the receiving end with the router so that messages from the memory profiler end up
 /// injected into the client's event loop.
 #[derive(Debug, Deserialize, Serialize)]
-pub struct Reporter(pub IpcSender<ReporterRequest>);
+pub struct Reporter {
+    /// The channel on which reporter requests are sent.
+    pub sender: IpcSender<ReporterRequest>,
+    /// The process that the reporter runs in.
+    pub process_id: u32,
+    /// The pipeline that all the reports of the reporter belong to, if any.
+    pub pipeline_id: Option<PipelineId>,
+}
 
 impl Reporter {
+    /// Create a reporter that runs in the current process.
+    pub fn new(sender: IpcSender<ReporterRequest>, pipeline_id: Option<PipelineId>) -> Reporter {
+        Reporter {
+            sender,
+            process_id: process::id(),
+            pipeline_id,
+        }
+    }
+
     /// Collect one or more memory reports. Returns true on success, and false on failure.
     pub fn collect_reports(&self, reports_chan: ReportsChan) {
-        self.0
+        self.sender
             .send(ReporterRequest {
                 reports_channel: reports_chan,
             })
@@ -203,6 +231,97 @@ pub enum ProfilerMsg {
     /// Triggers printing of the memory profiling metrics.
     Print,
 
+    /// Collects the reports of all the registered reporters and sends them back, without
+    /// printing them.
+    Snapshot(IpcSender<MemoryReportSnapshot>),
+
+    /// Records memory that the given process reclaimed in response to memory pressure. The
+    /// sizes are accumulated per path, and included in snapshots under
+    /// "memory-pressure-reclaimed".
+    ReportReclaimed(u32, Vec<Report>),
+
     /// Tells the memory profiler to shut down.
     Exit,
 }
+
+/// A memory report of a snapshot, with the reporter it came from.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SnapshotReport {
+    /// The identifying path for this report. Explicit reports start with "explicit".
+    pub path: Vec<String>,
+    /// The report kind.
+    pub kind: ReportKind,
+    /// The size, in bytes.
+    pub size: usize,
+    /// The process of the reporter that made this report.
+    pub process_id: u32,
+    /// The pipeline of the reporter that made this report, if it reports on a single pipeline.
+    pub pipeline_id: Option<PipelineId>,
+}
+
+/// All the memory reports collected at one point in time, in a form that can be saved as JSON
+/// and compared with other snapshots.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct MemoryReportSnapshot {
+    /// The time at which the snapshot was taken, in milliseconds since the memory profiler
+    /// started.
+    pub time: u64,
+    /// The reports, including the computed "heap-unclassified" ones.
+    pub reports: Vec<SnapshotReport>,
+}
+
+/// The change in size of the reports with a given path between two snapshots.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ReportDiff {
+    /// The path of the reports.
+    pub path: Vec<String>,
+    /// The total size of the reports with this path in the older snapshot, in bytes.
+    pub before: usize,
+    /// The total size of the reports with this path in the newer snapshot, in bytes.
+    pub after: usize,
+}
+
+impl ReportDiff {
+    /// The change in size, in bytes.
+    pub fn delta(&self) -> isize {
+        self.after as isize - self.before as isize
+    }
+}
+
+impl MemoryReportSnapshot {
+    /// The total size of the reports of each path, summed over processes and pipelines.
+    pub fn sizes_by_path(&self) -> BTreeMap<&[String], usize> {
+        let mut sizes = BTreeMap::new();
+        for report in &self.reports {
+            *sizes.entry(&report.path[..]).or_insert(0) += report.size;
+        }
+        sizes
+    }
+
+    /// Compare this snapshot with a newer one, by path. Returns the paths whose total size
+    /// changed, including those only present in one of the snapshots, with the largest
+    /// changes first.
+    pub fn diff(&self, newer: &MemoryReportSnapshot) -> Vec<ReportDiff> {
+        let before = self.sizes_by_path();
+        let after = newer.sizes_by_path();
+        let mut diffs: Vec<ReportDiff> = before
+            .keys()
+            .chain(after.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|path| ReportDiff {
+                path: path.to_vec(),
+                before: before.get(path).cloned().unwrap_or(0),
+                after: after.get(path).cloned().unwrap_or(0),
+            })
+            .filter(|diff| diff.before != diff.after)
+            .collect();
+        diffs.sort_by(|a, b| {
+            b.delta()
+                .abs()
+                .cmp(&a.delta().abs())
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        diffs
+    }
+}
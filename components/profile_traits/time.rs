@@ -4,6 +4,11 @@
 
 use ipc_channel::ipc::IpcSender;
 use servo_config::opts;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::process;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::thread;
 use time::precise_time_ns;
 
 #[derive(Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
@@ -13,6 +18,65 @@ pub struct TimerMetadata {
     pub incremental: TimerMetadataReflowType,
 }
 
+/// The thread that recorded a time profiler span, used to lay out exported traces in
+/// per-thread tracks.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ProfilerThread {
+    pub process_id: u32,
+    /// An identifier of the thread, unique within its process.
+    pub thread_id: u64,
+    pub name: Option<String>,
+}
+
+impl ProfilerThread {
+    pub fn current() -> ProfilerThread {
+        static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(1);
+        thread_local!(static CURRENT_THREAD: ProfilerThread = ProfilerThread {
+            process_id: process::id(),
+            thread_id: NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed),
+            name: thread::current().name().map(str::to_owned),
+        });
+        CURRENT_THREAD.with(|thread| thread.clone())
+    }
+}
+
+/// The kinds of work followed from one thread to another in exported traces.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum TraceFlowKind {
+    /// A reflow, from the script thread requesting it to the layout thread.
+    Reflow,
+    /// A display list, from the layout thread building it to the compositor.
+    DisplayList,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum TraceFlowPhase {
+    Start,
+    Step,
+    End,
+}
+
+/// Identifies one flow of work across threads and processes.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct TraceFlowId(pub u64);
+
+impl TraceFlowId {
+    /// A new identifier, unique across processes.
+    pub fn new() -> TraceFlowId {
+        static NEXT_FLOW_ID: AtomicU32 = AtomicU32::new(0);
+        let index = NEXT_FLOW_ID.fetch_add(1, Ordering::Relaxed);
+        TraceFlowId((process::id() as u64) << 32 | index as u64)
+    }
+
+    /// An identifier derived from a key known on both sides of a hop, like a pipeline
+    /// and an epoch, for messages that can't carry an identifier themselves.
+    pub fn from_key<K: Hash>(key: &K) -> TraceFlowId {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        TraceFlowId(hasher.finish())
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ProfilerChan(pub IpcSender<ProfilerMsg>);
 
@@ -33,7 +97,20 @@ pub enum ProfilerData {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum ProfilerMsg {
     /// Normal message used for reporting time
-    Time((ProfilerCategory, Option<TimerMetadata>), (u64, u64)),
+    Time(
+        (ProfilerCategory, Option<TimerMetadata>),
+        (u64, u64),
+        ProfilerThread,
+    ),
+    /// Report that a flow of work reached the current span of a thread, at a time in
+    /// nanoseconds. Only sent when a trace is being recorded.
+    Flow(
+        TraceFlowKind,
+        TraceFlowId,
+        TraceFlowPhase,
+        u64,
+        ProfilerThread,
+    ),
     /// Message used to get time spend entries for a particular ProfilerBuckets (in nanoseconds)
     Get(
         (ProfilerCategory, Option<TimerMetadata>),
@@ -156,5 +233,30 @@ pub fn send_profile_data(
     start_time: u64,
     end_time: u64,
 ) {
-    profiler_chan.send(ProfilerMsg::Time((category, meta), (start_time, end_time)));
+    profiler_chan.send(ProfilerMsg::Time(
+        (category, meta),
+        (start_time, end_time),
+        ProfilerThread::current(),
+    ));
+}
+
+/// Record that a flow of work reached the current thread, so that trace viewers can
+/// draw an arrow between the spans it went through. Does nothing unless a profiler
+/// trace is being written.
+pub fn send_trace_flow(
+    kind: TraceFlowKind,
+    id: TraceFlowId,
+    phase: TraceFlowPhase,
+    profiler_chan: &ProfilerChan,
+) {
+    if opts::get().time_profiler_trace_path.is_none() {
+        return;
+    }
+    profiler_chan.send(ProfilerMsg::Flow(
+        kind,
+        id,
+        phase,
+        precise_time_ns(),
+        ProfilerThread::current(),
+    ));
 }
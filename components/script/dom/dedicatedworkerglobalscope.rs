@@ -463,6 +463,7 @@ impl DedicatedWorkerGlobalScope {
                             }
                         },
                         reporter_name,
+                        None,
                         parent_sender,
                         CommonScriptMsg::CollectReports,
                     );
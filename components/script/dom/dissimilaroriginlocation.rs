@@ -10,7 +10,9 @@ use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::str::USVString;
 use crate::dom::dissimilaroriginwindow::DissimilarOriginWindow;
+use crate::dom::globalscope::GlobalScope;
 use dom_struct::dom_struct;
+use script_traits::HistoryEntryReplacement;
 
 /// Represents a dissimilar-origin `Location` that exists in another script thread.
 ///
@@ -52,21 +54,39 @@ impl DissimilarOriginLocationMethods for DissimilarOriginLocation {
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-location-href
-    fn SetHref(&self, _: USVString) -> ErrorResult {
-        // TODO: setting href on a cross-origin window should succeed?
-        Err(Error::Security)
+    fn SetHref(&self, value: USVString) -> ErrorResult {
+        // Step 2: Parse the given value relative to the entry settings object.
+        // If that failed, throw a TypeError exception.
+        let base_url = GlobalScope::entry().api_base_url();
+        let url = match base_url.join(&value.0) {
+            Ok(url) => url,
+            Err(e) => return Err(Error::Type(format!("Couldn't parse URL: {}", e))),
+        };
+        // Step 3: Location-object-setter navigate to the resulting URL record.
+        self.window.navigate(url, HistoryEntryReplacement::Disabled);
+        Ok(())
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-location-assign
     fn Assign(&self, _: USVString) -> Fallible<()> {
-        // TODO: setting href on a cross-origin window should succeed?
+        // Unlike replace(), assign() is not one of the cross-origin properties of Location.
+        // https://html.spec.whatwg.org/multipage/#crossoriginproperties-(-o-)
         Err(Error::Security)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-location-replace
-    fn Replace(&self, _: USVString) -> Fallible<()> {
-        // TODO: replacing href on a cross-origin window should succeed?
-        Err(Error::Security)
+    fn Replace(&self, url: USVString) -> Fallible<()> {
+        // Step 2: Parse url relative to the entry settings object. If that failed,
+        // throw a "SyntaxError" DOMException.
+        let base_url = GlobalScope::entry().api_base_url();
+        let url = match base_url.join(&url.0) {
+            Ok(url) => url,
+            Err(_) => return Err(Error::Syntax),
+        };
+        // Step 3: Location-object navigate to the resulting URL record with
+        // the replacement flag set.
+        self.window.navigate(url, HistoryEntryReplacement::Enabled);
+        Ok(())
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-location-reload
@@ -4,21 +4,27 @@
 
 use crate::dom::bindings::codegen::Bindings::DissimilarOriginWindowBinding;
 use crate::dom::bindings::codegen::Bindings::DissimilarOriginWindowBinding::DissimilarOriginWindowMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowPostMessageOptions;
 use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::USVString;
 use crate::dom::bindings::structuredclone;
 use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::dissimilaroriginlocation::DissimilarOriginLocation;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::window::Window;
 use crate::dom::windowproxy::WindowProxy;
+use crate::realms::InRealm;
 use crate::script_runtime::JSContext;
 use dom_struct::dom_struct;
+use ipc_channel::ipc;
 use js::jsapi::{Heap, JSObject};
-use js::jsval::{JSVal, UndefinedValue};
+use js::jsval::{JSVal, NullValue};
 use js::rust::{CustomAutoRooter, CustomAutoRooterGuard, HandleValue};
 use msg::constellation_msg::PipelineId;
+use script_traits::{HistoryEntryReplacement, LoadData, LoadOrigin};
 use script_traits::{ScriptMsg, StructuredSerializedData};
 use servo_url::ServoUrl;
 
@@ -117,19 +123,42 @@ impl DissimilarOriginWindowMethods for DissimilarOriginWindow {
 
     // https://html.spec.whatwg.org/multipage/#dom-length
     fn Length(&self) -> u32 {
-        // TODO: Implement x-origin length
-        0
+        if self.window_proxy.is_browsing_context_discarded() {
+            return 0;
+        }
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        let msg = ScriptMsg::GetChildBrowsingContextCount(
+            self.window_proxy.browsing_context_id(),
+            sender,
+        );
+        let _ = self.globalscope.script_to_constellation_chan().send(msg);
+        receiver.recv().unwrap_or(0)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-window-close
     fn Close(&self) {
-        // TODO: Implement x-origin close
+        // Step 1-2.
+        if self.window_proxy.is_browsing_context_discarded() {
+            return;
+        }
+        // Step 3. Whether the window is script-closable, and whether it is closing already,
+        // is known to the script thread of the window, which closes it if it can.
+        let msg = ScriptMsg::CloseBrowsingContext(self.window_proxy.browsing_context_id());
+        let _ = self.globalscope.script_to_constellation_chan().send(msg);
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-window-closed
     fn Closed(&self) -> bool {
-        // TODO: Implement x-origin close
-        false
+        if self.window_proxy.is_browsing_context_discarded() || self.window_proxy.is_closing() {
+            return true;
+        }
+        // The browsing context may have been discarded by its own script thread,
+        // in which case the constellation no longer knows about it.
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        let msg =
+            ScriptMsg::GetTopForBrowsingContext(self.window_proxy.browsing_context_id(), sender);
+        let _ = self.globalscope.script_to_constellation_chan().send(msg);
+        receiver.recv().map_or(true, |top| top.is_none())
     }
 
     /// https://html.spec.whatwg.org/multipage/#dom-window-postmessage
@@ -164,24 +193,36 @@ impl DissimilarOriginWindowMethods for DissimilarOriginWindow {
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-opener
-    fn Opener(&self, _: JSContext) -> JSVal {
-        // TODO: Implement x-origin opener
-        UndefinedValue()
+    fn Opener(&self, cx: JSContext, in_realm_proof: InRealm) -> JSVal {
+        // Step 1-2.
+        if self.window_proxy.is_browsing_context_discarded() {
+            return NullValue();
+        }
+        // Step 3 to 5.
+        self.window_proxy.opener(*cx, in_realm_proof)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-opener
     fn SetOpener(&self, _: JSContext, _: HandleValue) {
-        // TODO: Implement x-origin opener
+        // The opener of a cross-origin window can't be set: the cross-origin
+        // window proxy throws a security error before this is reached.
+        // https://html.spec.whatwg.org/multipage/#crossoriginproperties-(-o-)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-window-blur
     fn Blur(&self) {
-        // TODO: Implement x-origin blur
+        // The blur() method steps are to do nothing.
     }
 
-    // https://html.spec.whatwg.org/multipage/#dom-focus
+    // https://html.spec.whatwg.org/multipage/#dom-window-focus
     fn Focus(&self) {
-        // TODO: Implement x-origin focus
+        // Step 1-2.
+        if self.window_proxy.is_browsing_context_discarded() {
+            return;
+        }
+        // Step 3-4.
+        let msg = ScriptMsg::FocusBrowsingContext(self.window_proxy.browsing_context_id());
+        let _ = self.globalscope.script_to_constellation_chan().send(msg);
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-location
@@ -242,4 +283,30 @@ impl DissimilarOriginWindow {
         let _ = incumbent.script_to_constellation_chan().send(msg);
         Ok(())
     }
+
+    /// Navigate the browsing context of this window to `url`, on behalf of the entry settings
+    /// object. The navigation itself happens in the script thread of the window.
+    /// https://html.spec.whatwg.org/multipage/#location-object-navigate
+    pub fn navigate(&self, url: ServoUrl, replace: HistoryEntryReplacement) {
+        if self.window_proxy.is_browsing_context_discarded() {
+            return;
+        }
+        let entry = GlobalScope::entry();
+        let referrer_policy = entry
+            .downcast::<Window>()
+            .and_then(|window| window.Document().get_referrer_policy());
+        let load_data = LoadData::new(
+            LoadOrigin::Script(entry.origin().immutable().clone()),
+            url,
+            Some(entry.pipeline_id()),
+            entry.get_referrer(),
+            referrer_policy,
+        );
+        let msg = ScriptMsg::NavigateBrowsingContext(
+            self.window_proxy.browsing_context_id(),
+            load_data,
+            replace,
+        );
+        let _ = entry.script_to_constellation_chan().send(msg);
+    }
 }
@@ -16,6 +16,7 @@ use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
 use crate::dom::bindings::codegen::Bindings::EventBinding::EventBinding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLIFrameElementBinding::HTMLIFrameElementBinding::HTMLIFrameElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLInputElementBinding::HTMLInputElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLOptionElementBinding::HTMLOptionElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLTextAreaElementBinding::HTMLTextAreaElementMethods;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorBinding::NavigatorMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
@@ -70,8 +71,9 @@ use crate::dom::htmlheadelement::HTMLHeadElement;
 use crate::dom::htmlhtmlelement::HTMLHtmlElement;
 use crate::dom::htmliframeelement::HTMLIFrameElement;
 use crate::dom::htmlimageelement::HTMLImageElement;
-use crate::dom::htmlinputelement::HTMLInputElement;
+use crate::dom::htmlinputelement::{HTMLInputElement, InputType};
 use crate::dom::htmlscriptelement::{HTMLScriptElement, ScriptResult};
+use crate::dom::htmlselectelement::HTMLSelectElement;
 use crate::dom::htmltextareaelement::HTMLTextAreaElement;
 use crate::dom::htmltitleelement::HTMLTitleElement;
 use crate::dom::keyboardevent::KeyboardEvent;
@@ -104,7 +106,7 @@ use crate::dom::window::{ReflowReason, Window};
 use crate::dom::windowproxy::WindowProxy;
 use crate::fetch::FetchCanceller;
 use crate::realms::{AlreadyInRealm, InRealm};
-use crate::script_runtime::JSContext;
+use crate::script_runtime::{get_size, JSContext};
 use crate::script_runtime::{CommonScriptMsg, ScriptThreadEventCategory};
 use crate::script_thread::{MainThreadScriptMsg, ScriptThread};
 use crate::stylesheet_set::StylesheetSetRef;
@@ -119,6 +121,7 @@ use dom_struct::dom_struct;
 use embedder_traits::EmbedderMsg;
 use encoding_rs::{Encoding, UTF_8};
 use euclid::default::{Point2D, Rect, Size2D};
+use euclid::Vector2D;
 use html5ever::{LocalName, Namespace, QualName};
 use hyper_serde::Serde;
 use ipc_channel::ipc::{self, IpcSender};
@@ -144,6 +147,7 @@ use ref_slice::ref_slice;
 use script_layout_interface::message::{Msg, PendingRestyle, ReflowGoal};
 use script_layout_interface::TrustedNodeAddress;
 use script_traits::{AnimationState, DocumentActivity, MouseButton, MouseEventType};
+use script_traits::{FormControlState, FormControlValue, PageState};
 use script_traits::{
     MsDuration, ScriptMsg, TouchEventType, TouchId, UntrustedNodeAddress, WheelDelta,
 };
@@ -370,6 +374,8 @@ pub struct Document {
     throw_on_dynamic_markup_insertion_counter: Cell<u64>,
     /// https://html.spec.whatwg.org/multipage/#page-showing
     page_showing: Cell<bool>,
+    /// https://wicg.github.io/page-lifecycle/#frozenness-state
+    frozen: Cell<bool>,
     /// Whether the document is salvageable.
     salvageable: Cell<bool>,
     /// Whether the document was aborted with an active parser
@@ -413,6 +419,13 @@ pub struct Document {
     animations: DomRefCell<Animations>,
     /// The nearest inclusive ancestors to all the nodes that require a restyle.
     dirty_root: MutNullableDom<Element>,
+    /// Whether the state of the page that is reported to the constellation may have changed
+    /// since it was last reported.
+    page_state_changed: Cell<bool>,
+    /// The state of the form controls of the page when it was last reported, and the version
+    /// of the document then, unless one of the controls changed since.
+    #[ignore_malloc_size_of = "Defined in script_traits"]
+    reported_form_controls: DomRefCell<Option<(u64, Vec<FormControlState>)>>,
 }
 
 #[derive(JSTraceable, MallocSizeOf)]
@@ -641,8 +654,18 @@ impl Document {
             ClientContextId::build(pipeline_id.namespace_id.0, pipeline_id.index.0.get());
 
         if activity != DocumentActivity::FullyActive {
+            // A document that is kept in the back/forward cache is frozen,
+            // unless it already was while its browser was hidden.
+            if !self.frozen.get() {
+                self.upcast::<EventTarget>().fire_event(atom!("freeze"));
+                self.frozen.set(true);
+            }
             self.window().suspend();
             media.suspend(&client_context_id);
+            if activity == DocumentActivity::Inactive && self.salvageable.get() {
+                let event = ScriptMsg::CachedDocumentMemory(self.dom_tree_size());
+                self.window().send_to_constellation(event);
+            }
             return;
         }
 
@@ -653,6 +676,11 @@ impl Document {
         self.window().resume();
         media.resume(&client_context_id);
 
+        if self.frozen.get() {
+            self.frozen.set(false);
+            self.upcast::<EventTarget>().fire_event(atom!("resume"));
+        }
+
         if self.ready_state.get() != DocumentReadyState::Complete {
             return;
         }
@@ -693,6 +721,46 @@ impl Document {
             .unwrap();
     }
 
+    /// An estimate of the memory used by the nodes of this document, which is used
+    /// to limit the size of the back/forward cache.
+    #[allow(unsafe_code)]
+    fn dom_tree_size(&self) -> usize {
+        self.upcast::<Node>()
+            .traverse_preorder(ShadowIncluding::Yes)
+            .map(|node| unsafe { get_size(node.reflector().get_jsobject().get()) })
+            .sum()
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.get()
+    }
+
+    /// <https://wicg.github.io/page-lifecycle/#change-frozenness-of-a-top-level-document>
+    pub fn set_frozen(&self, frozen: bool) {
+        if !self.is_fully_active() || frozen == self.frozen.get() {
+            return;
+        }
+
+        let media = ServoMedia::get().unwrap();
+        let pipeline_id = self.window().pipeline_id();
+        let client_context_id =
+            ClientContextId::build(pipeline_id.namespace_id.0, pipeline_id.index.0.get());
+
+        if frozen {
+            // The freeze event runs before any of the document's work is
+            // suspended, so that the page can save its state.
+            self.upcast::<EventTarget>().fire_event(atom!("freeze"));
+            self.frozen.set(true);
+            self.window().upcast::<GlobalScope>().suspend();
+            media.suspend(&client_context_id);
+        } else {
+            self.frozen.set(false);
+            self.window().upcast::<GlobalScope>().resume();
+            media.resume(&client_context_id);
+            self.upcast::<EventTarget>().fire_event(atom!("resume"));
+        }
+    }
+
     pub fn origin(&self) -> &MutableOrigin {
         &self.origin
     }
@@ -1948,6 +2016,27 @@ impl Document {
         ident
     }
 
+    /// Reschedule the animation frame callbacks that were left pending while this
+    /// document was hidden.
+    pub fn restart_animation_frame_callbacks(&self) {
+        if self.animation_frame_list.borrow().is_empty() {
+            return;
+        }
+        if self.is_faking_animation_frames() {
+            let callback = FakeRequestAnimationFrameCallback {
+                document: Trusted::new(self),
+            };
+            self.global().schedule_callback(
+                OneshotTimerCallback::FakeRequestAnimationFrame(callback),
+                MsDuration::new(FAKE_REQUEST_ANIMATION_FRAME_DELAY),
+            );
+        } else {
+            let event =
+                ScriptMsg::ChangeRunningAnimationsState(AnimationState::AnimationCallbacksPresent);
+            self.window().send_to_constellation(event);
+        }
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#dom-window-cancelanimationframe>
     pub fn cancel_animation_frame(&self, ident: u32) {
         let mut list = self.animation_frame_list.borrow_mut();
@@ -2161,6 +2250,12 @@ impl Document {
 
     // https://html.spec.whatwg.org/multipage/#unload-a-document
     pub fn unload(&self, recursive_flag: bool) {
+        // A document with open connections can't be kept in the back/forward cache.
+        if !pref!(session_history.bfcache.enabled) ||
+            self.window.upcast::<GlobalScope>().has_open_connections()
+        {
+            self.salvageable.set(false);
+        }
         // TODO: Step 1, increase the event loop's termination nesting level by 1.
         // Step 2
         self.incr_ignore_opens_during_unload_counter();
@@ -3057,6 +3152,63 @@ fn get_registrable_domain_suffix_of_or_is_equal_to(
     Some(host)
 }
 
+/// The key that identifies a form control in the state of a page, made of its kind and its
+/// name or, if it has none, its ID.
+fn form_control_key(element: &Element) -> String {
+    let mut name = element.get_string_attribute(&local_name!("name"));
+    if name.is_empty() {
+        name = element.get_string_attribute(&local_name!("id"));
+    }
+    match element.downcast::<HTMLInputElement>() {
+        Some(input) => format!("input[type={}] {}", input.input_type().to_str(), name),
+        None => format!("{} {}", element.local_name(), name),
+    }
+}
+
+/// Whether the autofill field name of a form control, or of its form owner if it has none,
+/// is "off". The values of such controls, like one-time codes, aren't kept.
+/// <https://html.spec.whatwg.org/multipage/#attr-fe-autocomplete-off>
+fn autocomplete_is_off(element: &Element) -> bool {
+    let is_off = |element: &Element| {
+        let autocomplete = element.get_string_attribute(&local_name!("autocomplete"));
+        match autocomplete.trim() {
+            "" => None,
+            autocomplete => Some(autocomplete.eq_ignore_ascii_case("off")),
+        }
+    };
+    is_off(element).unwrap_or_else(|| {
+        element
+            .as_maybe_form_control()
+            .and_then(|control| control.form_owner())
+            .and_then(|form| is_off(form.upcast()))
+            .unwrap_or(false)
+    })
+}
+
+/// The value of a form control that is kept in the state of a page.
+fn form_control_value(element: &Element) -> FormControlValue {
+    if let Some(input) = element.downcast::<HTMLInputElement>() {
+        return match input.input_type() {
+            InputType::Checkbox | InputType::Radio => FormControlValue::Checked(input.Checked()),
+            _ => FormControlValue::Text(input.Value().into()),
+        };
+    }
+    if let Some(textarea) = element.downcast::<HTMLTextAreaElement>() {
+        return FormControlValue::Text(textarea.Value().into());
+    }
+    let options = element
+        .downcast::<HTMLSelectElement>()
+        .into_iter()
+        .flat_map(|select| select.list_of_options());
+    FormControlValue::Selected(
+        options
+            .enumerate()
+            .filter(|(_, option)| option.Selected())
+            .map(|(index, _)| index)
+            .collect(),
+    )
+}
+
 /// <https://url.spec.whatwg.org/#network-scheme>
 fn url_has_network_scheme(url: &ServoUrl) -> bool {
     match url.scheme() {
@@ -3206,6 +3358,7 @@ impl Document {
             canceller: canceller,
             throw_on_dynamic_markup_insertion_counter: Cell::new(0),
             page_showing: Cell::new(false),
+            frozen: Cell::new(false),
             salvageable: Cell::new(true),
             active_parser_was_aborted: Cell::new(false),
             fired_unload: Cell::new(false),
@@ -3228,7 +3381,146 @@ impl Document {
             },
             animations: DomRefCell::new(Animations::new()),
             dirty_root: Default::default(),
+            page_state_changed: Cell::new(false),
+            reported_form_controls: DomRefCell::new(None),
+        }
+    }
+
+    /// Note that the page was scrolled.
+    pub fn note_scroll_position_change(&self) {
+        self.page_state_changed.set(true);
+    }
+
+    /// Note that the value, checkedness or selectedness of one of the form controls of the
+    /// page changed.
+    pub fn note_form_control_change(&self) {
+        self.page_state_changed.set(true);
+        *self.reported_form_controls.borrow_mut() = None;
+    }
+
+    /// Report the state of the page to the constellation if it may have changed since it was
+    /// last reported, so that it can be restored if the page has to be loaded again. The state
+    /// of the form controls is only gathered again if one of them or the document changed.
+    pub fn report_page_state_if_changed(&self) {
+        if !self.page_state_changed.replace(false) {
+            return;
+        }
+        let version = self.upcast::<Node>().inclusive_descendants_version();
+        let mut reported_form_controls = self.reported_form_controls.borrow_mut();
+        let form_controls = match *reported_form_controls {
+            Some((reported_version, ref form_controls)) if reported_version == version => {
+                form_controls.clone()
+            },
+            _ => {
+                let form_controls = self.form_controls_state();
+                *reported_form_controls = Some((version, form_controls.clone()));
+                form_controls
+            },
+        };
+        let state = PageState {
+            scroll_position: self.scroll_position(),
+            form_controls,
+        };
+        self.window
+            .send_to_constellation(ScriptMsg::PageStateChanged(state));
+    }
+
+    /// The form controls of the document whose state is restored, in tree order.
+    fn restorable_form_controls(&self) -> Vec<DomRoot<Element>> {
+        self.upcast::<Node>()
+            .traverse_preorder(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<Element>)
+            .filter(|element| match element.downcast::<HTMLInputElement>() {
+                // The state of password and file inputs is never kept, and neither is the
+                // state of inputs that the user can't change.
+                Some(input) => match input.input_type() {
+                    InputType::Button |
+                    InputType::File |
+                    InputType::Hidden |
+                    InputType::Image |
+                    InputType::Password |
+                    InputType::Reset |
+                    InputType::Submit => false,
+                    _ => true,
+                },
+                None => element.is::<HTMLTextAreaElement>() || element.is::<HTMLSelectElement>(),
+            })
+            .filter(|element| !autocomplete_is_off(element))
+            .collect()
+    }
+
+    fn scroll_position(&self) -> Vector2D<f32, CSSPixel> {
+        let viewport = self.window.current_viewport();
+        Vector2D::new(viewport.origin.x.to_f32_px(), viewport.origin.y.to_f32_px())
+    }
+
+    fn form_controls_state(&self) -> Vec<FormControlState> {
+        self.restorable_form_controls()
+            .iter()
+            .enumerate()
+            .map(|(index, element)| FormControlState {
+                index,
+                key: form_control_key(element),
+                value: form_control_value(element),
+            })
+            .collect()
+    }
+
+    /// The current state of the page.
+    pub fn page_state(&self) -> PageState {
+        PageState {
+            scroll_position: self.scroll_position(),
+            form_controls: self.form_controls_state(),
+        }
+    }
+
+    /// Restore the state that the page had when it was last loaded. The state of a form
+    /// control is only restored if the control at the same position in the document is of
+    /// the same kind and has the same name.
+    pub fn restore_page_state(&self, state: &PageState) {
+        let controls = self.restorable_form_controls();
+        for control in &state.form_controls {
+            let element = match controls.get(control.index) {
+                Some(element) if form_control_key(element) == control.key => element,
+                _ => continue,
+            };
+            match control.value {
+                FormControlValue::Text(ref value) => {
+                    if let Some(input) = element.downcast::<HTMLInputElement>() {
+                        let _ = input.SetValue(DOMString::from(value.clone()));
+                    } else if let Some(textarea) = element.downcast::<HTMLTextAreaElement>() {
+                        textarea.SetValue(DOMString::from(value.clone()));
+                    }
+                },
+                FormControlValue::Checked(checked) => {
+                    if let Some(input) = element.downcast::<HTMLInputElement>() {
+                        input.SetChecked(checked);
+                    }
+                },
+                FormControlValue::Selected(ref indices) => {
+                    if let Some(select) = element.downcast::<HTMLSelectElement>() {
+                        // Unselect options first, so that a select element that shows a
+                        // single option doesn't pick another one in the meantime.
+                        let options: Vec<_> = select.list_of_options().collect();
+                        for (index, option) in options.iter().enumerate() {
+                            if !indices.contains(&index) {
+                                option.SetSelected(false);
+                            }
+                        }
+                        for index in indices {
+                            if let Some(option) = options.get(*index) {
+                                option.SetSelected(true);
+                            }
+                        }
+                    }
+                },
+            }
         }
+        self.window.scroll(
+            state.scroll_position.x as f64,
+            state.scroll_position.y as f64,
+            ScrollBehavior::Auto,
+        );
     }
 
     pub fn set_csp_list(&self, csp_list: Option<CspList>) {
@@ -5187,6 +5479,12 @@ impl DocumentMethods for Document {
         self.exit_fullscreen()
     }
 
+    // https://wicg.github.io/page-lifecycle/#dom-document-onfreeze
+    event_handler!(freeze, GetOnfreeze, SetOnfreeze);
+
+    // https://wicg.github.io/page-lifecycle/#dom-document-onresume
+    event_handler!(resume, GetOnresume, SetOnresume);
+
     // check-tidy: no specs after this line
     // Servo only API to get an instance of the controls of a specific
     // media element matching the given id.
@@ -5260,6 +5558,11 @@ pub struct FakeRequestAnimationFrameCallback {
 impl FakeRequestAnimationFrameCallback {
     pub fn invoke(self) {
         let document = self.document.root();
+        // Hidden documents don't get animation frames; the pending callbacks
+        // are rescheduled when the document becomes visible again.
+        if !document.window().visible() {
+            return;
+        }
         document.run_the_animation_frame_callbacks();
     }
 }
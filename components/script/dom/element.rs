@@ -739,6 +739,19 @@ impl<'dom> LayoutElementHelpers<'dom> for LayoutDom<'dom, Element> {
             ))
         }
 
+        // The language of the element is inherited through `-x-lang`, for hyphenation.
+        // https://html.spec.whatwg.org/multipage/#the-lang-and-xml:lang-attributes
+        let lang = self
+            .get_attr_val_for_layout(&ns!(xml), &local_name!("lang"))
+            .or_else(|| self.get_attr_val_for_layout(&ns!(), &local_name!("lang")));
+
+        if let Some(lang) = lang {
+            hints.push(from_declaration(
+                shared_lock,
+                PropertyDeclaration::XLang(specified::XLang(Atom::from(lang))),
+            ));
+        }
+
         let cellspacing = if let Some(this) = self.downcast::<HTMLTableElement>() {
             this.get_cellspacing()
         } else {
@@ -2802,7 +2815,9 @@ impl VirtualMethods for Element {
 
     fn attribute_affects_presentational_hints(&self, attr: &Attr) -> bool {
         // FIXME: This should be more fine-grained, not all elements care about these.
-        if attr.local_name() == &local_name!("width") || attr.local_name() == &local_name!("height")
+        if attr.local_name() == &local_name!("width") ||
+            attr.local_name() == &local_name!("height") ||
+            attr.local_name() == &local_name!("lang")
         {
             return true;
         }
@@ -0,0 +1,152 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::EmbedderChannelBinding::EmbedderChannelMethods;
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::structuredclone;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::messageevent::MessageEvent;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext as SafeJSContext;
+use dom_struct::dom_struct;
+use embedder_traits::{MessageFromPage, MessageToPage};
+use js::jsapi::{JSAutoRealm, JS_ClearPendingException};
+use js::jsval::UndefinedValue;
+use js::rust::wrappers::{JS_ParseJSON, JS_Stringify};
+use js::rust::{HandleObject, HandleValue};
+use libc::c_void;
+use script_traits::{ScriptMsg, StructuredSerializedData};
+use std::slice;
+
+/// A channel exchanging structured-cloned messages between a document and the embedder.
+/// Only documents whose origin passes `is_embedder_messaging_allowed` get one; the
+/// constellation checks the origin again before relaying messages in either direction.
+#[dom_struct]
+pub struct EmbedderChannel {
+    eventtarget: EventTarget,
+}
+
+impl EmbedderChannel {
+    fn new_inherited() -> EmbedderChannel {
+        EmbedderChannel {
+            eventtarget: EventTarget::new_inherited(),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<EmbedderChannel> {
+        reflect_dom_object(Box::new(EmbedderChannel::new_inherited()), window)
+    }
+
+    /// Queue a task to fire a `message` event carrying the deserialized `message`,
+    /// or a `messageerror` event if it cannot be deserialized.
+    pub fn dispatch_message(&self, message: MessageToPage) {
+        let this = Trusted::new(self);
+        let global = self.global();
+        let task = task!(embedder_message_event: move || {
+            let this = this.root();
+            this.fire_message_event(message);
+        });
+        global
+            .as_window()
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue(task, global.upcast())
+            .unwrap();
+    }
+
+    #[allow(unsafe_code)]
+    fn fire_message_event(&self, message: MessageToPage) {
+        let global = self.global();
+        let cx = global.get_cx();
+        let _ac = JSAutoRealm::new(*cx, self.reflector().get_jsobject().get());
+        rooted!(in(*cx) let mut data = UndefinedValue());
+        let deserialized = match message {
+            MessageToPage::StructuredClone(serialized) => {
+                let serialized = StructuredSerializedData {
+                    serialized,
+                    ports: None,
+                    blobs: None,
+                };
+                structuredclone::read(&global, serialized, data.handle_mut()).is_ok()
+            },
+            MessageToPage::Json(json) => {
+                let json: Vec<u16> = json.encode_utf16().collect();
+                let parsed = unsafe {
+                    JS_ParseJSON(*cx, json.as_ptr(), json.len() as u32, data.handle_mut())
+                };
+                if !parsed {
+                    unsafe { JS_ClearPendingException(*cx) };
+                }
+                parsed
+            },
+        };
+        if !deserialized {
+            return MessageEvent::dispatch_error(self.upcast(), &global);
+        }
+        MessageEvent::dispatch_jsval(self.upcast(), &global, data.handle(), None, None, vec![]);
+    }
+
+    /// Returns `message` as JSON, or `None` if it has no JSON representation.
+    #[allow(unsafe_code)]
+    fn to_json(cx: SafeJSContext, message: HandleValue) -> Option<String> {
+        rooted!(in(*cx) let mut value = message.get());
+        let mut json: Vec<u16> = vec![];
+        let stringified = unsafe {
+            JS_Stringify(
+                *cx,
+                value.handle_mut(),
+                HandleObject::null(),
+                HandleValue::null(),
+                Some(append_json),
+                &mut json as *mut Vec<u16> as *mut c_void,
+            )
+        };
+        // Cyclic values make stringifying throw, and `undefined` stringifies to nothing.
+        if !stringified {
+            unsafe { JS_ClearPendingException(*cx) };
+            return None;
+        }
+        if json.is_empty() {
+            return None;
+        }
+        Some(String::from_utf16_lossy(&json))
+    }
+}
+
+#[allow(unsafe_code)]
+unsafe extern "C" fn append_json(string: *const u16, len: u32, data: *mut c_void) -> bool {
+    let json = &mut *(data as *mut Vec<u16>);
+    json.extend_from_slice(slice::from_raw_parts(string, len as usize));
+    true
+}
+
+impl EmbedderChannelMethods for EmbedderChannel {
+    fn PostMessage(&self, cx: SafeJSContext, message: HandleValue) -> ErrorResult {
+        let data = structuredclone::write(cx, message, None)?;
+        // Blobs are kept alive by the documents they are posted to,
+        // which the embedder is not.
+        if data.blobs.is_some() || data.ports.is_some() {
+            return Err(Error::DataClone);
+        }
+        let message = MessageFromPage {
+            serialized: data.serialized,
+            json: EmbedderChannel::to_json(cx, message),
+        };
+
+        let global = self.global();
+        let msg = ScriptMsg::PostMessageToEmbedder(message);
+        global.as_window().send_to_constellation(msg);
+        Ok(())
+    }
+
+    // Servo-specific event handler for messages coming from the embedder.
+    event_handler!(message, GetOnmessage, SetOnmessage);
+
+    // Servo-specific event handler for embedder messages that cannot be deserialized.
+    event_handler!(messageerror, GetOnmessageerror, SetOnmessageerror);
+}
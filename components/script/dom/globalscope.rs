@@ -10,6 +10,7 @@ use crate::dom::bindings::codegen::Bindings::ImageBitmapBinding::{
 };
 use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::PermissionState;
 use crate::dom::bindings::codegen::Bindings::VoidFunctionBinding::VoidFunction;
+use crate::dom::bindings::codegen::Bindings::WebSocketBinding::WebSocketMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::Bindings::WorkerGlobalScopeBinding::WorkerGlobalScopeMethods;
 use crate::dom::bindings::conversions::{root_from_object, root_from_object_static};
@@ -47,6 +48,7 @@ use crate::dom::promise::Promise;
 use crate::dom::readablestream::{ExternalUnderlyingSource, ReadableStream};
 use crate::dom::serviceworker::ServiceWorker;
 use crate::dom::serviceworkerregistration::ServiceWorkerRegistration;
+use crate::dom::websocket::WebSocket;
 use crate::dom::window::Window;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
 use crate::dom::workletglobalscope::WorkletGlobalScope;
@@ -265,6 +267,9 @@ pub struct GlobalScope {
     /// Vector storing references of all eventsources.
     event_source_tracker: DOMTracker<EventSource>,
 
+    /// Vector storing references of all websockets.
+    websocket_tracker: DOMTracker<WebSocket>,
+
     /// Storage for watching rejected promises waiting for some client to
     /// consume their rejection.
     /// Promises in this list have been rejected in the last turn of the
@@ -751,6 +756,7 @@ impl GlobalScope {
             microtask_queue,
             list_auto_close_worker: Default::default(),
             event_source_tracker: DOMTracker::new(),
+            websocket_tracker: DOMTracker::new(),
             uncaught_rejections: Default::default(),
             consumed_rejections: Default::default(),
             is_headless,
@@ -2103,6 +2109,28 @@ impl GlobalScope {
         canceled_any_fetch
     }
 
+    pub fn track_websocket(&self, websocket: &WebSocket) {
+        self.websocket_tracker.track(websocket);
+    }
+
+    /// Whether this global has an event source or websocket that is not closed.
+    pub fn has_open_connections(&self) -> bool {
+        let mut has_open_connections = false;
+        self.event_source_tracker
+            .for_each(|event_source: DomRoot<EventSource>| {
+                if event_source.ReadyState() != 2 {
+                    has_open_connections = true;
+                }
+            });
+        self.websocket_tracker
+            .for_each(|websocket: DomRoot<WebSocket>| {
+                if websocket.ReadyState() != 3 {
+                    has_open_connections = true;
+                }
+            });
+        has_open_connections
+    }
+
     /// Returns the global scope of the realm that the given DOM object's reflector
     /// was created in.
     #[allow(unsafe_code)]
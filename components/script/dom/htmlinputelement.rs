@@ -148,7 +148,7 @@ impl InputType {
         *self == InputType::Time
     }
 
-    fn to_str(&self) -> &str {
+    pub fn to_str(&self) -> &str {
         match *self {
             InputType::Button => "button",
             InputType::Checkbox => "checkbox",
@@ -1298,6 +1298,7 @@ impl HTMLInputElementMethods for HTMLInputElement {
         }
 
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+        document_from_node(self).note_form_control_change();
         Ok(())
     }
 
@@ -1804,6 +1805,7 @@ impl HTMLInputElement {
 
         if dirty {
             self.checked_changed.set(true);
+            document_from_node(self).note_form_control_change();
         }
 
         if self.input_type() == InputType::Radio && checked {
@@ -2565,6 +2567,7 @@ impl VirtualMethods for HTMLInputElement {
                         self.value_dirty.set(true);
                         self.update_placeholder_shown_state();
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                        document_from_node(self).note_form_control_change();
                         event.mark_as_handled();
                     },
                     RedrawSelection => {
@@ -2605,6 +2608,7 @@ impl VirtualMethods for HTMLInputElement {
                         .borrow_mut()
                         .handle_compositionend(compositionevent);
                     self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                    document_from_node(self).note_form_control_change();
                 }
                 event.mark_as_handled();
             }
@@ -20,7 +20,7 @@ use crate::dom::htmlformelement::HTMLFormElement;
 use crate::dom::htmloptgroupelement::HTMLOptGroupElement;
 use crate::dom::htmlscriptelement::HTMLScriptElement;
 use crate::dom::htmlselectelement::HTMLSelectElement;
-use crate::dom::node::{BindContext, Node, ShadowIncluding, UnbindContext};
+use crate::dom::node::{document_from_node, BindContext, Node, ShadowIncluding, UnbindContext};
 use crate::dom::text::Text;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::dom::window::Window;
@@ -112,6 +112,9 @@ impl HTMLOptionElement {
 
     pub fn set_dirtiness(&self, dirtiness: bool) {
         self.dirtiness.set(dirtiness);
+        if dirtiness {
+            document_from_node(self).note_form_control_change();
+        }
     }
 
     fn pick_if_selected_and_reset(&self) {
@@ -256,7 +259,7 @@ impl HTMLOptionElementMethods for HTMLOptionElement {
 
     // https://html.spec.whatwg.org/multipage/#dom-option-selected
     fn SetSelected(&self, selected: bool) {
-        self.dirtiness.set(true);
+        self.set_dirtiness(true);
         self.selectedness.set(selected);
         self.pick_if_selected_and_reset();
     }
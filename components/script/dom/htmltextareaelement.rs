@@ -23,7 +23,7 @@ use crate::dom::htmlfieldsetelement::HTMLFieldSetElement;
 use crate::dom::htmlformelement::{FormControl, HTMLFormElement};
 use crate::dom::htmlinputelement::HTMLInputElement;
 use crate::dom::keyboardevent::KeyboardEvent;
-use crate::dom::node::window_from_node;
+use crate::dom::node::{document_from_node, window_from_node};
 use crate::dom::node::{
     BindContext, ChildrenMutation, CloneChildrenFlag, Node, NodeDamage, UnbindContext,
 };
@@ -337,6 +337,7 @@ impl HTMLTextAreaElementMethods for HTMLTextAreaElement {
         }
 
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+        document_from_node(self).note_form_control_change();
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-textarea-textlength
@@ -618,6 +619,7 @@ impl VirtualMethods for HTMLTextAreaElement {
                         self.value_dirty.set(true);
                         self.update_placeholder_shown_state();
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                        document_from_node(self).note_form_control_change();
                         event.mark_as_handled();
                     },
                     KeyReaction::RedrawSelection => {
@@ -654,6 +656,7 @@ impl VirtualMethods for HTMLTextAreaElement {
                         .borrow_mut()
                         .handle_compositionend(compositionevent);
                     self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                    document_from_node(self).note_form_control_change();
                 }
                 event.mark_as_handled();
             }
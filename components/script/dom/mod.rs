@@ -297,6 +297,7 @@ pub mod domstringmap;
 pub mod domtokenlist;
 pub mod dynamicmoduleowner;
 pub mod element;
+pub mod embedderchannel;
 pub mod errorevent;
 pub mod event;
 pub mod eventsource;
@@ -393,6 +393,7 @@ impl ServiceWorkerGlobalScope {
                             }
                         },
                         reporter_name,
+                        None,
                         scope.script_chan(),
                         CommonScriptMsg::CollectReports,
                     );
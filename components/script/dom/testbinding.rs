@@ -16,6 +16,7 @@ use crate::dom::bindings::codegen::Bindings::TestBindingBinding::{
 use crate::dom::bindings::codegen::Bindings::TestBindingBinding::{
     TestDictionaryDefaults, TestEnum, TestURLLike,
 };
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::UnionTypes;
 use crate::dom::bindings::codegen::UnionTypes::{
     BlobOrBlobSequence, BlobOrBoolean, LongOrLongSequenceSequence,
@@ -1090,6 +1091,20 @@ impl TestBindingMethods for TestBinding {
             stringMember: Some(s2),
         }
     }
+
+    fn CapturePageState(&self) -> DOMString {
+        let state = self.global().as_window().Document().page_state();
+        DOMString::from(serde_json::to_string(&state).unwrap())
+    }
+
+    fn RestorePageState(&self, state: DOMString) -> Fallible<()> {
+        let state = serde_json::from_str(&state).map_err(|_| Error::Syntax)?;
+        self.global()
+            .as_window()
+            .Document()
+            .restore_page_state(&state);
+        Ok(())
+    }
 }
 
 #[allow(non_snake_case)]
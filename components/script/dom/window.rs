@@ -34,7 +34,9 @@ use crate::dom::crypto::Crypto;
 use crate::dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
 use crate::dom::customelementregistry::CustomElementRegistry;
 use crate::dom::document::{AnimationFrameCallback, Document, ReflowTriggerCondition};
+use crate::dom::domrect::DOMRect;
 use crate::dom::element::Element;
+use crate::dom::embedderchannel::EmbedderChannel;
 use crate::dom::event::{Event, EventStatus};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
@@ -105,15 +107,17 @@ use num_traits::ToPrimitive;
 use parking_lot::Mutex as ParkMutex;
 use profile_traits::ipc as ProfiledIpc;
 use profile_traits::mem::ProfilerChan as MemProfilerChan;
+use profile_traits::time::{send_trace_flow, TraceFlowId, TraceFlowKind, TraceFlowPhase};
 use profile_traits::time::{ProfilerChan as TimeProfilerChan, ProfilerMsg};
 use script_layout_interface::message::{Msg, QueryMsg, Reflow, ReflowGoal, ScriptReflow};
 use script_layout_interface::rpc::{ContentBoxResponse, ContentBoxesResponse, LayoutRPC};
 use script_layout_interface::rpc::{
-    NodeScrollIdResponse, ResolvedStyleResponse, TextIndexResponse,
+    NodeScrollIdResponse, PagesResponse, ResolvedStyleResponse, TextIndexResponse,
 };
 use script_layout_interface::{PendingImageState, TrustedNodeAddress};
 use script_traits::webdriver_msg::{WebDriverJSError, WebDriverJSResult};
-use script_traits::{ConstellationControlMsg, DocumentState, HistoryEntryReplacement, LoadData};
+use script_traits::{is_embedder_messaging_allowed, ConstellationControlMsg, DocumentState};
+use script_traits::{HistoryEntryReplacement, LoadData, PageDisplayList};
 use script_traits::{
     ScriptMsg, ScriptToConstellationChan, ScrollState, StructuredSerializedData, TimerEventId,
 };
@@ -188,6 +192,7 @@ pub struct Window {
     script_chan: MainThreadScriptChan,
     task_manager: TaskManager,
     navigator: MutNullableDom<Navigator>,
+    embedder_channel: MutNullableDom<EmbedderChannel>,
     #[ignore_malloc_size_of = "Arc"]
     image_cache: Arc<dyn ImageCache>,
     #[ignore_malloc_size_of = "channels are hard"]
@@ -854,6 +859,14 @@ impl WindowMethods for Window {
         self.navigator.or_init(|| Navigator::new(self))
     }
 
+    // Servo-specific channel for exchanging messages with the embedder.
+    fn GetEmbedder(&self) -> Option<DomRoot<EmbedderChannel>> {
+        if !is_embedder_messaging_allowed(self.origin().immutable()) {
+            return None;
+        }
+        Some(self.embedder_channel.or_init(|| EmbedderChannel::new(self)))
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-windowtimers-settimeout
     fn SetTimeout(
         &self,
@@ -1096,6 +1109,36 @@ impl WindowMethods for Window {
         }
     }
 
+    fn Page_areas(&self) -> Vec<DomRoot<DOMRect>> {
+        self.pages_query()
+            .iter()
+            .map(|rect| {
+                DOMRect::new(
+                    self.upcast(),
+                    rect.origin.x.to_f64_px(),
+                    rect.origin.y.to_f64_px(),
+                    rect.size.width.to_f64_px(),
+                    rect.size.height.to_f64_px(),
+                )
+            })
+            .collect()
+    }
+
+    fn Page_display_lists(&self) -> Vec<DomRoot<DOMRect>> {
+        self.page_display_lists_query()
+            .iter()
+            .map(|page| {
+                DOMRect::new(
+                    self.upcast(),
+                    0.,
+                    0.,
+                    page.page_size.width as f64,
+                    page.page_size.height as f64,
+                )
+            })
+            .collect()
+    }
+
     #[allow(unsafe_code)]
     fn WebdriverCallback(&self, cx: JSContext, val: HandleValue) {
         let rv = unsafe { jsval_to_webdriver(*cx, &self.globalscope, val) };
@@ -1550,6 +1593,7 @@ impl Window {
         let x = x.to_f32().unwrap_or(0.0f32);
         let y = y.to_f32().unwrap_or(0.0f32);
         self.update_viewport_for_scroll(x, y);
+        self.Document().note_scroll_position_change();
         self.perform_a_scroll(
             x,
             y,
@@ -1700,6 +1744,7 @@ impl Window {
 
         // Send new document and relevant styles to layout.
         let needs_display = reflow_goal.needs_display();
+        let trace_flow_id = TraceFlowId::new();
         let reflow = ScriptReflow {
             reflow_info: Reflow {
                 page_clip_rect: self.page_clip_rect.get(),
@@ -1715,8 +1760,15 @@ impl Window {
             pending_restyles,
             animation_timeline_value: document.current_animation_timeline_value(),
             animations: document.animations().sets.clone(),
+            trace_flow_id,
         };
 
+        send_trace_flow(
+            TraceFlowKind::Reflow,
+            trace_flow_id,
+            TraceFlowPhase::Start,
+            self.time_profiler_chan(),
+        );
         match self.layout_chan() {
             Some(layout_chan) => layout_chan
                 .send(Msg::Reflow(reflow))
@@ -1975,6 +2027,21 @@ impl Window {
         self.layout_rpc.inner_window_dimensions()
     }
 
+    pub fn pages_query(&self) -> Vec<UntypedRect<Au>> {
+        if !self.layout_reflow(QueryMsg::PagesQuery) {
+            return vec![];
+        }
+        let PagesResponse(page_areas) = self.layout_rpc.pages();
+        page_areas
+    }
+
+    pub fn page_display_lists_query(&self) -> Vec<PageDisplayList> {
+        if !self.layout_reflow(QueryMsg::PageDisplayListsQuery) {
+            return vec![];
+        }
+        self.layout_rpc.page_display_lists()
+    }
+
     #[allow(unsafe_code)]
     pub fn offset_parent_query(&self, node: &Node) -> (Option<DomRoot<Element>>, UntypedRect<Au>) {
         if !self.layout_reflow(QueryMsg::OffsetParentQuery(node.to_opaque())) {
@@ -2288,6 +2355,7 @@ impl Window {
         self.visible.set(visible);
         if visible {
             self.upcast::<GlobalScope>().speed_up_timers();
+            self.Document().restart_animation_frame_callbacks();
         } else {
             self.upcast::<GlobalScope>().slow_down_timers();
         }
@@ -2406,6 +2474,7 @@ impl Window {
             image_cache_chan,
             image_cache,
             navigator: Default::default(),
+            embedder_channel: Default::default(),
             location: Default::default(),
             history: Default::default(),
             custom_element_registry: Default::default(),
@@ -2549,6 +2618,8 @@ fn debug_reflow_events(id: PipelineId, reflow_goal: &ReflowGoal, reason: &Reflow
             &QueryMsg::TextIndexQuery(..) => "\tTextIndexQuery",
             &QueryMsg::ElementInnerTextQuery(_) => "\tElementInnerTextQuery",
             &QueryMsg::InnerWindowDimensionsQuery(_) => "\tInnerWindowDimensionsQuery",
+            &QueryMsg::PagesQuery => "\tPagesQuery",
+            &QueryMsg::PageDisplayListsQuery => "\tPageDisplayListsQuery",
         },
     };
 
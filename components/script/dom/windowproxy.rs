@@ -3,7 +3,9 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::cell::DomRefCell;
-use crate::dom::bindings::conversions::{root_from_handleobject, ToJSValConvertible};
+use crate::dom::bindings::conversions::{
+    jsid_to_string, root_from_handleobject, ToJSValConvertible,
+};
 use crate::dom::bindings::error::{throw_dom_exception, Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::proxyhandler::fill_property_descriptor;
@@ -1125,12 +1127,20 @@ unsafe extern "C" fn get_xorigin(
 #[allow(unsafe_code)]
 unsafe extern "C" fn set_xorigin(
     cx: *mut JSContext,
-    _: RawHandleObject,
-    _: RawHandleId,
-    _: RawHandleValue,
-    _: RawHandleValue,
-    _: *mut ObjectOpResult,
+    proxy: RawHandleObject,
+    id: RawHandleId,
+    v: RawHandleValue,
+    receiver: RawHandleValue,
+    res: *mut ObjectOpResult,
 ) -> bool {
+    // The only cross-origin property that can be set is `location`, which forwards to
+    // `location.href`.
+    // https://html.spec.whatwg.org/multipage/#crossoriginproperties-(-o-)
+    let is_location =
+        jsid_to_string(cx, Handle::from_raw(id)).map_or(false, |name| &*name == "location");
+    if is_location {
+        return set(cx, proxy, id, v, receiver, res);
+    }
     let in_realm_proof = AlreadyInRealm::assert_for_cx(SafeJSContext::from_ptr(cx));
     throw_security_error(cx, InRealm::Already(&in_realm_proof))
 }
@@ -90,7 +90,7 @@ use crossbeam_channel::{unbounded, Receiver, Sender};
 use devtools_traits::CSSError;
 use devtools_traits::{DevtoolScriptControlMsg, DevtoolsPageInfo};
 use devtools_traits::{NavigationState, ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::{EmbedderMsg, EventLoopWaker};
+use embedder_traits::{EmbedderMsg, EventLoopWaker, MessageToPage};
 use euclid::default::{Point2D, Rect};
 use euclid::Vector2D;
 use headers::ReferrerPolicy as ReferrerPolicyHeader;
@@ -100,6 +100,7 @@ use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use js::glue::GetWindowProxyClass;
 use js::jsapi::JS_SetWrapObjectCallbacks;
+use js::jsapi::{GCReason, JSGCParamKey, JS_GetGCParameter, JS_GC};
 use js::jsapi::{
     JSContext as UnsafeJSContext, JSTracer, JS_AddInterruptCallback, SetWindowProxyClass,
 };
@@ -125,7 +126,7 @@ use net_traits::{
 };
 use parking_lot::Mutex;
 use percent_encoding::percent_decode;
-use profile_traits::mem::{self as profile_mem, OpaqueSender, ReportsChan};
+use profile_traits::mem::{self as profile_mem, OpaqueSender, Report, ReportKind, ReportsChan};
 use profile_traits::time::{self as profile_time, profile, ProfilerCategory};
 use script_layout_interface::message::{self, LayoutThreadInit, Msg, ReflowGoal};
 use script_traits::webdriver_msg::WebDriverScriptCommand;
@@ -137,10 +138,10 @@ use script_traits::{
     AnimationTickType, CompositorEvent, ConstellationControlMsg, DiscardBrowsingContext,
     DocumentActivity, EventResult, HistoryEntryReplacement, InitialScriptState, JsEvalResult,
     LayoutMsg, LoadData, LoadOrigin, MediaSessionActionType, MouseButton, MouseEventType,
-    NewLayoutInfo, Painter, ProgressiveWebMetricType, ScriptMsg, ScriptThreadFactory,
-    ScriptToConstellationChan, StructuredSerializedData, TimerSchedulerMsg, TouchEventType,
-    TouchId, UntrustedNodeAddress, UpdatePipelineIdReason, WebrenderIpcSender, WheelDelta,
-    WindowSizeData, WindowSizeType,
+    NewLayoutInfo, PageDisplayList, PageState, Painter, ProgressiveWebMetricType, ScriptMsg,
+    ScriptThreadFactory, ScriptToConstellationChan, StructuredSerializedData, TimerSchedulerMsg,
+    TouchEventType, TouchId, UntrustedNodeAddress, UpdatePipelineIdReason, WebrenderIpcSender,
+    WheelDelta, WindowSizeData, WindowSizeType,
 };
 use servo_atoms::Atom;
 use servo_config::opts;
@@ -158,7 +159,7 @@ use std::result::Result;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 use style::dom::OpaqueNode;
 use style::thread_state::{self, ThreadState};
 use time::{at_utc, get_time, precise_time_ns, Timespec};
@@ -826,6 +827,7 @@ impl ScriptThreadFactory for ScriptThread {
                         let _ = script_thread.content_process_shutdown_chan.send(());
                     },
                     reporter_name,
+                    None,
                     script_chan,
                     CommonScriptMsg::CollectReports,
                 );
@@ -1089,7 +1091,7 @@ impl ScriptThread {
                     .borrow()
                     .iter()
                     .filter_map(|(id, document)| {
-                        if document.is_fully_active() {
+                        if document.is_fully_active() && !document.is_frozen() {
                             Some(id.clone())
                         } else {
                             None
@@ -1468,6 +1470,14 @@ impl ScriptThread {
         // Notify the background-hang-monitor we are waiting for an event.
         self.background_hang_monitor.notify_wait();
 
+        // A timeout corresponding to when the earliest task held back by the execution
+        // budget of a hidden document can run, if any.
+        let budget_timeout = self
+            .task_queue
+            .budget_timeout()
+            .map(|timeout| crossbeam_channel::after(timeout))
+            .unwrap_or(crossbeam_channel::never());
+
         // Receive at least one message so we don't spinloop.
         debug!("Waiting for event.");
         let mut event = select! {
@@ -1479,6 +1489,14 @@ impl ScriptThread {
                     .expect("Spurious wake-up of the event-loop, task-queue has no tasks available");
                 FromScript(event)
             },
+            recv(budget_timeout) -> _ => {
+                self.task_queue.take_tasks(MainThreadScriptMsg::WakeUp);
+                match self.task_queue.recv() {
+                    Ok(event) => FromScript(event),
+                    // No held-back task could run yet, go back to waiting.
+                    Err(()) => return true,
+                }
+            },
             recv(self.control_port) -> msg => FromConstellation(msg.unwrap()),
             recv(self.devtools_chan.as_ref().map(|_| &self.devtools_port).unwrap_or(&crossbeam_channel::never())) -> msg
                 => FromDevtools(msg.unwrap()),
@@ -1689,6 +1707,8 @@ impl ScriptThread {
                 // minimize unnecessary work.
                 window.reflow(ReflowGoal::Full, ReflowReason::MissingExplicitReflow);
             }
+
+            document.report_page_state_if_changed();
         }
 
         true
@@ -1784,9 +1804,12 @@ impl ScriptThread {
                 SetScrollState(id, ..) => Some(id),
                 GetTitle(id) => Some(id),
                 SetDocumentActivity(id, ..) => Some(id),
+                SetDocumentFrozen(id, ..) => Some(id),
                 ChangeFrameVisibilityStatus(id, ..) => Some(id),
                 NotifyVisibilityChange(id, ..) => Some(id),
                 NavigateIframe(id, ..) => Some(id),
+                NavigateWindow(id, ..) => Some(id),
+                CloseWindow(id) => Some(id),
                 PostMessage { target: id, .. } => Some(id),
                 UpdatePipelineId(_, _, _, id, _) => Some(id),
                 UpdateHistoryState(id, ..) => Some(id),
@@ -1806,7 +1829,11 @@ impl ScriptThread {
                 PaintMetric(..) => None,
                 ExitFullScreen(id, ..) => Some(id),
                 MediaSessionAction(..) => None,
+                PostMessageFromEmbedder(id, ..) => Some(id),
                 SetWebGPUPort(..) => None,
+                RestorePageState(id, ..) => Some(id),
+                GetPageDisplayLists(id, ..) => Some(id),
+                MemoryPressure => None,
             },
             MixedMessage::FromDevtools(_) => None,
             MixedMessage::FromScript(ref inner_msg) => match *inner_msg {
@@ -1928,6 +1955,12 @@ impl ScriptThread {
                 load_data,
                 replace,
             ),
+            ConstellationControlMsg::NavigateWindow(pipeline_id, load_data, replace) => {
+                self.handle_navigate_window(pipeline_id, load_data, replace)
+            },
+            ConstellationControlMsg::CloseWindow(pipeline_id) => {
+                self.handle_close_window(pipeline_id)
+            },
             ConstellationControlMsg::UnloadDocument(pipeline_id) => {
                 self.handle_unload_document(pipeline_id)
             },
@@ -1941,6 +1974,9 @@ impl ScriptThread {
             ConstellationControlMsg::SetDocumentActivity(pipeline_id, activity) => {
                 self.handle_set_document_activity_msg(pipeline_id, activity)
             },
+            ConstellationControlMsg::SetDocumentFrozen(pipeline_id, frozen) => {
+                self.handle_set_document_frozen_msg(pipeline_id, frozen)
+            },
             ConstellationControlMsg::ChangeFrameVisibilityStatus(pipeline_id, visible) => {
                 self.handle_visibility_change_msg(pipeline_id, visible)
             },
@@ -2025,6 +2061,16 @@ impl ScriptThread {
             ConstellationControlMsg::MediaSessionAction(pipeline_id, action) => {
                 self.handle_media_session_action(pipeline_id, action)
             },
+            ConstellationControlMsg::PostMessageFromEmbedder(pipeline_id, message) => {
+                self.handle_post_message_from_embedder(pipeline_id, message)
+            },
+            ConstellationControlMsg::RestorePageState(pipeline_id, state) => {
+                self.handle_restore_page_state(pipeline_id, state)
+            },
+            ConstellationControlMsg::GetPageDisplayLists(pipeline_id, sender) => {
+                self.handle_get_page_display_lists(pipeline_id, sender)
+            },
+            ConstellationControlMsg::MemoryPressure => self.handle_memory_pressure(),
             ConstellationControlMsg::SetWebGPUPort(port) => {
                 if self.webgpu_port.borrow().is_some() {
                     warn!("WebGPU port already exists for this content process");
@@ -2087,7 +2133,20 @@ impl ScriptThread {
 
     fn handle_msg_from_script(&self, msg: MainThreadScriptMsg) {
         match msg {
-            MainThreadScriptMsg::Common(CommonScriptMsg::Task(_, task, _, _)) => task.run_box(),
+            // Timers are limited to a budget of their own.
+            MainThreadScriptMsg::Common(CommonScriptMsg::Task(
+                _,
+                task,
+                _,
+                TaskSourceName::Timer,
+            )) => task.run_box(),
+            MainThreadScriptMsg::Common(CommonScriptMsg::Task(_, task, pipeline_id, _)) => {
+                let start = Instant::now();
+                task.run_box();
+                if let Some(pipeline_id) = pipeline_id {
+                    self.task_queue.spend_budget(pipeline_id, start.elapsed());
+                }
+            },
             MainThreadScriptMsg::Common(CommonScriptMsg::CollectReports(chan)) => {
                 self.collect_reports(chan)
             },
@@ -2450,7 +2509,8 @@ impl ScriptThread {
                 scroll_offsets.insert(OpaqueNode(node_address.0 as usize), -*scroll_offset);
             }
         }
-        window.set_scroll_offsets(scroll_offsets)
+        window.set_scroll_offsets(scroll_offsets);
+        window.Document().note_scroll_position_change();
     }
 
     fn handle_new_layout(&self, new_layout_info: NewLayoutInfo, origin: MutableOrigin) {
@@ -2543,6 +2603,37 @@ impl ScriptThread {
         reports_chan.send(reports);
     }
 
+    /// Drops the decoded images nobody is using and runs a full garbage collection,
+    /// reporting how much memory was reclaimed to the memory profiler.
+    #[allow(unsafe_code)]
+    fn handle_memory_pressure(&self) {
+        let image_cache_size = self.image_cache.purge();
+
+        let cx = self.get_cx();
+        let gc_heap_size = unsafe {
+            let before = JS_GetGCParameter(*cx, JSGCParamKey::JSGC_BYTES);
+            JS_GC(*cx, GCReason::API);
+            let after = JS_GetGCParameter(*cx, JSGCParamKey::JSGC_BYTES);
+            before.saturating_sub(after) as usize
+        };
+
+        let documents = self.documents.borrow();
+        let urls = itertools::join(documents.iter().map(|(_, d)| d.url().to_string()), ", ");
+        let path_seg = format!("url({})", urls);
+        self.mem_profiler_chan.report_reclaimed(vec![
+            Report {
+                path: path![path_seg, "image-cache"],
+                kind: ReportKind::ExplicitJemallocHeapSize,
+                size: image_cache_size,
+            },
+            Report {
+                path: path![path_seg, "js", "gc-heap"],
+                kind: ReportKind::ExplicitJemallocHeapSize,
+                size: gc_heap_size,
+            },
+        ]);
+    }
+
     /// Updates iframe element after a change in visibility
     fn handle_visibility_change_complete_msg(
         &self,
@@ -2571,6 +2662,7 @@ impl ScriptThread {
         match window {
             Some(window) => {
                 window.alter_resource_utilization(visible);
+                self.task_queue.set_budget_throttling(id, !visible);
                 return;
             },
             None => {
@@ -2606,6 +2698,15 @@ impl ScriptThread {
         warn!("change of activity sent to nonexistent pipeline");
     }
 
+    /// Handles a request to freeze or resume a document
+    fn handle_set_document_frozen_msg(&self, id: PipelineId, frozen: bool) {
+        let document = self.documents.borrow().find_document(id);
+        match document {
+            Some(document) => document.set_frozen(frozen),
+            None => warn!("change of frozenness sent to nonexistent pipeline"),
+        }
+    }
+
     fn handle_focus_iframe_msg(
         &self,
         parent_pipeline_id: PipelineId,
@@ -3420,6 +3521,8 @@ impl ScriptThread {
 
         if !incomplete.is_visible {
             window.alter_resource_utilization(false);
+            self.task_queue
+                .set_budget_throttling(incomplete.pipeline_id, true);
         }
 
         document.get_current_parser().unwrap()
@@ -3708,6 +3811,37 @@ impl ScriptThread {
         }
     }
 
+    /// Handle a "navigate a window" message from the constellation, sent on behalf of a
+    /// cross-origin script that set the location of this window.
+    fn handle_navigate_window(
+        &self,
+        pipeline_id: PipelineId,
+        load_data: LoadData,
+        replace: HistoryEntryReplacement,
+    ) {
+        let window = match self.documents.borrow().find_window(pipeline_id) {
+            Some(window) => window,
+            None => return warn!("Navigate sent to closed pipeline {}.", pipeline_id),
+        };
+        // A javascript: URL only runs when its initiator is same origin-domain with the
+        // navigated document, which a script in another script thread never is.
+        // https://html.spec.whatwg.org/multipage/#navigate step 12.
+        if load_data.url.scheme() == "javascript" {
+            return;
+        }
+        window.load_url(replace, false, load_data);
+    }
+
+    /// Handle a "close a window" message from the constellation, sent on behalf of a
+    /// cross-origin script that called `close()` on this window.
+    fn handle_close_window(&self, pipeline_id: PipelineId) {
+        let window = match self.documents.borrow().find_window(pipeline_id) {
+            Some(window) => window,
+            None => return warn!("Close sent to closed pipeline {}.", pipeline_id),
+        };
+        window.Close();
+    }
+
     /// Turn javascript: URL into JS code to eval, according to the steps in
     /// https://html.spec.whatwg.org/multipage/#javascript-protocol
     pub fn eval_js_url(global_scope: &GlobalScope, load_data: &mut LoadData) {
@@ -3987,6 +4121,44 @@ impl ScriptThread {
         };
     }
 
+    fn handle_restore_page_state(&self, pipeline_id: PipelineId, state: PageState) {
+        match self.documents.borrow().find_document(pipeline_id) {
+            Some(document) => document.restore_page_state(&state),
+            None => warn!("Page state restored after pipeline {} closed.", pipeline_id),
+        }
+    }
+
+    fn handle_get_page_display_lists(
+        &self,
+        pipeline_id: PipelineId,
+        sender: IpcSender<Vec<PageDisplayList>>,
+    ) {
+        let page_display_lists = match self.documents.borrow().find_window(pipeline_id) {
+            Some(window) => window.page_display_lists_query(),
+            None => {
+                warn!(
+                    "Page display lists requested after pipeline {} closed.",
+                    pipeline_id
+                );
+                vec![]
+            },
+        };
+        if let Err(e) = sender.send(page_display_lists) {
+            warn!("Sending page display lists failed ({:?}).", e);
+        }
+    }
+
+    fn handle_post_message_from_embedder(&self, pipeline_id: PipelineId, message: MessageToPage) {
+        let window = match self.documents.borrow().find_window(pipeline_id) {
+            Some(window) => window,
+            None => return warn!("Embedder message after pipeline {} closed.", pipeline_id),
+        };
+        match window.GetEmbedder() {
+            Some(channel) => channel.dispatch_message(message),
+            None => warn!("Pipeline {} has no embedder channel.", pipeline_id),
+        }
+    }
+
     pub fn enqueue_microtask(job: Microtask) {
         SCRIPT_THREAD_ROOT.with(|root| {
             let script_thread = unsafe { &*root.get().unwrap() };
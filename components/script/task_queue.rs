@@ -10,11 +10,14 @@ use crate::script_runtime::ScriptThreadEventCategory;
 use crate::script_thread::ScriptThread;
 use crate::task::TaskBox;
 use crate::task_source::TaskSourceName;
+use crate::timers::ExecutionBudget;
 use crossbeam_channel::{self, Receiver, Sender};
 use msg::constellation_msg::PipelineId;
+use servo_config::pref;
 use std::cell::Cell;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::default::Default;
+use std::time::{Duration, Instant};
 
 pub type QueuedTask = (
     Option<TrustedWorkerAddress>,
@@ -48,6 +51,12 @@ pub struct TaskQueue<T> {
     throttled: DomRefCell<HashMap<TaskSourceName, VecDeque<QueuedTask>>>,
     /// Tasks for not fully-active documents.
     inactive: DomRefCell<HashMap<PipelineId, VecDeque<QueuedTask>>>,
+    /// The execution budgets of pipelines whose documents are hidden, which their tasks
+    /// other than timers are limited to. Timers have a budget of their own.
+    budgets: DomRefCell<HashMap<PipelineId, ExecutionBudget>>,
+    /// Tasks of pipelines whose documents are hidden, held back until their execution
+    /// budget allows them to run.
+    over_budget: DomRefCell<HashMap<PipelineId, VecDeque<QueuedTask>>>,
 }
 
 impl<T: QueuedTaskConversion> TaskQueue<T> {
@@ -59,9 +68,103 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
             taken_task_counter: Default::default(),
             throttled: Default::default(),
             inactive: Default::default(),
+            budgets: Default::default(),
+            over_budget: Default::default(),
         }
     }
 
+    /// Start or stop limiting the tasks of a pipeline to an execution budget,
+    /// as its document is hidden or shown.
+    pub fn set_budget_throttling(&self, pipeline_id: PipelineId, throttled: bool) {
+        if throttled {
+            if pref!(dom.background_throttling.enabled) {
+                self.budgets
+                    .borrow_mut()
+                    .entry(pipeline_id)
+                    .or_insert_with(|| ExecutionBudget::new(Instant::now()));
+            }
+            return;
+        }
+        self.budgets.borrow_mut().remove(&pipeline_id);
+        let held_back = match self.over_budget.borrow_mut().remove(&pipeline_id) {
+            Some(held_back) => held_back,
+            None => return,
+        };
+        self.msg_queue
+            .borrow_mut()
+            .extend(held_back.into_iter().map(T::from_queued_task));
+        // Ensure the select wakes up to handle the released tasks.
+        let _ = self.wake_up_sender.send(T::wake_up_msg());
+    }
+
+    /// Spend the execution budget of a pipeline, if it has one, on a task that ran
+    /// for `duration`.
+    pub fn spend_budget(&self, pipeline_id: PipelineId, duration: Duration) {
+        if let Some(budget) = self.budgets.borrow_mut().get_mut(&pipeline_id) {
+            budget.spend(duration, Instant::now());
+        }
+    }
+
+    /// The time until a held-back task can run, if there is any.
+    pub fn budget_timeout(&self) -> Option<Duration> {
+        let now = Instant::now();
+        let budgets = self.budgets.borrow();
+        self.over_budget
+            .borrow()
+            .keys()
+            .filter_map(|pipeline_id| budgets.get(pipeline_id))
+            .map(|budget| budget.time_until_available(now))
+            .min()
+    }
+
+    /// Whether a task is limited to the execution budget of its pipeline.
+    fn is_budget_throttled(&self, msg: &T) -> bool {
+        match (msg.task_source_name(), msg.pipeline_id()) {
+            (Some(TaskSourceName::Timer), _) | (_, None) => false,
+            (_, Some(pipeline_id)) => self.budgets.borrow().contains_key(&pipeline_id),
+        }
+    }
+
+    /// Hold back a task until the execution budget of its pipeline allows it to run.
+    fn store_task_over_budget(&self, msg: T, pipeline_id: PipelineId) {
+        self.over_budget
+            .borrow_mut()
+            .entry(pipeline_id)
+            .or_default()
+            .push_back(
+                msg.into_queued_task()
+                    .expect("Incoming messages should always be convertible into queued tasks"),
+            );
+        let mut msg_queue = self.msg_queue.borrow_mut();
+        if msg_queue.is_empty() {
+            // Ensure there is at least one message, see `store_task_for_inactive_pipeline`.
+            msg_queue.push_back(T::inactive_msg());
+        }
+    }
+
+    /// Release one held-back task for each pipeline that has budget left.
+    /// Pipelines with more tasks to run are woken up through `budget_timeout`.
+    fn release_tasks_within_budget(&self, fully_active: &HashSet<PipelineId>) {
+        let now = Instant::now();
+        let budgets = self.budgets.borrow();
+        let mut over_budget = self.over_budget.borrow_mut();
+        for (pipeline_id, held_back) in over_budget.iter_mut() {
+            if !fully_active.contains(pipeline_id) {
+                continue;
+            }
+            match budgets.get(pipeline_id) {
+                Some(budget) if budget.is_available(now) => {},
+                _ => continue,
+            }
+            if let Some(queued_task) = held_back.pop_front() {
+                self.msg_queue
+                    .borrow_mut()
+                    .push_back(T::from_queued_task(queued_task));
+            }
+        }
+        over_budget.retain(|_, held_back| !held_back.is_empty());
+    }
+
     /// Release previously held-back tasks for documents that are now fully-active.
     /// https://html.spec.whatwg.org/multipage/#event-loop-processing-model:fully-active
     fn release_tasks_for_fully_active_documents(
@@ -142,6 +245,10 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
                     self.store_task_for_inactive_pipeline(msg, &pipeline_id);
                     continue;
                 }
+                if self.is_budget_throttled(&msg) {
+                    self.store_task_over_budget(msg, pipeline_id);
+                    continue;
+                }
             }
             // Immediately send non-throttled tasks for processing.
             let _ = self.msg_queue.borrow_mut().push_back(msg);
@@ -191,6 +298,7 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
         let fully_active = ScriptThread::get_fully_active_document_ids();
         // Always first check for new tasks, but don't reset 'taken_task_counter'.
         self.process_incoming_tasks(first_msg, &fully_active);
+        self.release_tasks_within_budget(&fully_active);
         let mut throttled = self.throttled.borrow_mut();
         let mut throttled_length: usize = throttled.values().map(|queue| queue.len()).sum();
         let task_source_names = TaskSourceName::all();
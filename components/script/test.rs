@@ -67,3 +67,7 @@ pub mod srcset {
 pub mod timeranges {
     pub use crate::dom::timeranges::TimeRangesContainer;
 }
+
+pub mod timers {
+    pub use crate::timers::ExecutionBudget;
+}
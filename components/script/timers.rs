@@ -28,6 +28,7 @@ use std::cmp::{self, Ord, Ordering};
 use std::collections::HashMap;
 use std::default::Default;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 #[derive(Clone, Copy, Debug, Eq, Hash, JSTraceable, MallocSizeOf, Ord, PartialEq, PartialOrd)]
 pub struct OneshotTimerHandle(i32);
@@ -58,6 +59,64 @@ pub struct OneshotTimers {
     ///  - a timer was added with an earlier callback time. In this case the
     ///    original timer is rescheduled when it is the next one to get called.
     expected_event_id: Cell<TimerEventId>,
+    /// The time the timers can run for while the associated document is hidden,
+    /// if background throttling is enabled.
+    #[ignore_malloc_size_of = "Defined in std"]
+    budget: DomRefCell<Option<ExecutionBudget>>,
+}
+
+/// The rate at which an execution budget is replenished: timers, or other tasks,
+/// can run for 10ms per second.
+const BUDGET_RECOVERY_RATE: f64 = 0.01;
+/// The most budget, in milliseconds, that can be accumulated.
+const MAX_BUDGET: f64 = 50.0;
+/// The least budget, in milliseconds, that can be left, which bounds the delay
+/// of the timers, or other tasks, to one minute.
+const MIN_BUDGET: f64 = -600.0;
+
+/// The time that the timers of a hidden document, or its other tasks, can run for.
+/// It is replenished over time, and spent by running them.
+#[derive(Debug, JSTraceable)]
+pub struct ExecutionBudget {
+    /// The budget in milliseconds, which is negative if it was overspent.
+    level: f64,
+    /// When the level was last replenished.
+    last_update: Instant,
+}
+
+impl ExecutionBudget {
+    pub fn new(now: Instant) -> ExecutionBudget {
+        ExecutionBudget {
+            level: MAX_BUDGET,
+            last_update: now,
+        }
+    }
+
+    fn level_at(&self, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.last_update);
+        let recovered = elapsed.as_secs_f64() * 1000. * BUDGET_RECOVERY_RATE;
+        (self.level + recovered).min(MAX_BUDGET)
+    }
+
+    /// Spend the budget on timers, or other tasks, that ran for `duration`, until `now`.
+    pub fn spend(&mut self, duration: Duration, now: Instant) {
+        let level = self.level_at(now) - duration.as_secs_f64() * 1000.;
+        self.level = level.max(MIN_BUDGET);
+        self.last_update = now;
+    }
+
+    pub fn is_available(&self, now: Instant) -> bool {
+        self.level_at(now) >= 0.
+    }
+
+    /// The time until the budget is no longer overspent.
+    pub fn time_until_available(&self, now: Instant) -> Duration {
+        let level = self.level_at(now);
+        if level >= 0. {
+            return Duration::from_millis(0);
+        }
+        Duration::from_millis((-level / BUDGET_RECOVERY_RATE).ceil() as u64)
+    }
 }
 
 #[derive(DenyPublicFields, JSTraceable, MallocSizeOf)]
@@ -125,6 +184,7 @@ impl OneshotTimers {
             suspended_since: Cell::new(None),
             suspension_offset: Cell::new(Length::new(0)),
             expected_event_id: Cell::new(TimerEventId(0)),
+            budget: DomRefCell::new(None),
         }
     }
 
@@ -204,6 +264,15 @@ impl OneshotTimers {
             return;
         }
 
+        // Wait for the budget to be replenished if it was overspent.
+        let budget_available = match *self.budget.borrow() {
+            Some(ref budget) => budget.is_available(Instant::now()),
+            None => true,
+        };
+        if !budget_available {
+            return self.schedule_timer_call();
+        }
+
         // select timers to run to prevent firing timers
         // that were installed during fire of another timer
         let mut timers_to_run = Vec::new();
@@ -218,7 +287,8 @@ impl OneshotTimers {
             timers_to_run.push(timers.pop().unwrap());
         }
 
-        for timer in timers_to_run {
+        let mut timers_to_run = timers_to_run.into_iter();
+        while let Some(timer) = timers_to_run.next() {
             // Since timers can be coalesced together inside a task,
             // this loop can keep running, including after an interrupt of the JS,
             // and prevent a clean-shutdown of a JS-running thread.
@@ -226,8 +296,23 @@ impl OneshotTimers {
             if !global.can_continue_running() {
                 return;
             }
+            let start = Instant::now();
             let callback = timer.callback;
             callback.invoke(global, &self.js_timers);
+
+            let now = Instant::now();
+            if let Some(ref mut budget) = *self.budget.borrow_mut() {
+                budget.spend(now - start, now);
+                if !budget.is_available(now) {
+                    // Hold the remaining timers back until the budget is replenished.
+                    let mut timers = self.timers.borrow_mut();
+                    for timer in timers_to_run {
+                        let insertion_index = timers.binary_search(&timer).err().unwrap();
+                        timers.insert(insertion_index, timer);
+                    }
+                    break;
+                }
+            }
         }
 
         self.schedule_timer_call();
@@ -245,10 +330,20 @@ impl OneshotTimers {
     pub fn slow_down(&self) {
         let duration = pref!(js.timers.minimum_duration) as u64;
         self.js_timers.set_min_duration(MsDuration::new(duration));
+        if pref!(dom.background_throttling.enabled) {
+            let mut budget = self.budget.borrow_mut();
+            if budget.is_none() {
+                *budget = Some(ExecutionBudget::new(Instant::now()));
+            }
+        }
     }
 
     pub fn speed_up(&self) {
         self.js_timers.remove_min_duration();
+        if self.budget.borrow_mut().take().is_some() {
+            // Timers held back by the budget can run again.
+            self.schedule_timer_call();
+        }
     }
 
     pub fn suspend(&self) {
@@ -288,12 +383,17 @@ impl OneshotTimers {
         if let Some(timer) = timers.last() {
             let expected_event_id = self.invalidate_expected_event_id();
 
-            let delay = Length::new(
+            let budget_delay = match *self.budget.borrow() {
+                Some(ref budget) => budget.time_until_available(Instant::now()).as_millis() as u64,
+                None => 0,
+            };
+            let delay = Length::new(cmp::max(
                 timer
                     .scheduled_for
                     .get()
                     .saturating_sub(precise_time_ms().get()),
-            );
+                budget_delay,
+            ));
             let request = TimerEventRequest(
                 self.timer_event_chan
                     .borrow()
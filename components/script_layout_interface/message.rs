@@ -13,6 +13,7 @@ use metrics::PaintTimeMetrics;
 use msg::constellation_msg::{BackgroundHangMonitorRegister, BrowsingContextId, PipelineId};
 use net_traits::image_cache::ImageCache;
 use profile_traits::mem::ReportsChan;
+use profile_traits::time::TraceFlowId;
 use script_traits::Painter;
 use script_traits::{
     ConstellationControlMsg, LayoutControlMsg, LayoutMsg as ConstellationMsg, ScrollState,
@@ -117,6 +118,8 @@ pub enum QueryMsg {
     ElementInnerTextQuery(TrustedNodeAddress),
     ResolvedFontStyleQuery(TrustedNodeAddress, PropertyId, String),
     InnerWindowDimensionsQuery(BrowsingContextId),
+    PagesQuery,
+    PageDisplayListsQuery,
 }
 
 /// Any query to perform with this reflow.
@@ -146,6 +149,8 @@ impl ReflowGoal {
                 QueryMsg::ResolvedStyleQuery(..) |
                 QueryMsg::ResolvedFontStyleQuery(..) |
                 QueryMsg::OffsetParentQuery(_) |
+                QueryMsg::PagesQuery |
+                QueryMsg::PageDisplayListsQuery |
                 QueryMsg::StyleQuery => false,
             },
         }
@@ -169,6 +174,8 @@ impl ReflowGoal {
                 QueryMsg::ResolvedFontStyleQuery(..) |
                 QueryMsg::OffsetParentQuery(_) |
                 QueryMsg::InnerWindowDimensionsQuery(_) |
+                QueryMsg::PagesQuery |
+                QueryMsg::PageDisplayListsQuery |
                 QueryMsg::StyleQuery => false,
             },
         }
@@ -214,6 +221,8 @@ pub struct ScriptReflow {
     pub animation_timeline_value: f64,
     /// The set of animations for this document.
     pub animations: DocumentAnimationSet,
+    /// The trace flow that follows this reflow from script to layout.
+    pub trace_flow_id: TraceFlowId,
 }
 
 pub struct LayoutThreadInit {
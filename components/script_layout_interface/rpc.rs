@@ -5,7 +5,7 @@
 use app_units::Au;
 use euclid::default::Rect;
 use euclid::Size2D;
-use script_traits::UntrustedNodeAddress;
+use script_traits::{PageDisplayList, UntrustedNodeAddress};
 use servo_arc::Arc;
 use style::properties::style_structs::Font;
 use style_traits::CSSPixel;
@@ -42,12 +42,19 @@ pub trait LayoutRPC {
     fn element_inner_text(&self) -> String;
     /// Get the dimensions of an iframe's inner window.
     fn inner_window_dimensions(&self) -> Option<Size2D<f32, CSSPixel>>;
+    /// Requests the page areas of the pages the document is printed on.
+    fn pages(&self) -> PagesResponse;
+    /// Requests the display list of each page the document is printed on.
+    fn page_display_lists(&self) -> Vec<PageDisplayList>;
 }
 
 pub struct ContentBoxResponse(pub Option<Rect<Au>>);
 
 pub struct ContentBoxesResponse(pub Vec<Rect<Au>>);
 
+/// The page area of each page, relative to the top-left corner of its page box.
+pub struct PagesResponse(pub Vec<Rect<Au>>);
+
 pub struct NodeGeometryResponse {
     pub client_rect: Rect<i32>,
 }
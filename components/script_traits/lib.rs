@@ -30,7 +30,7 @@ use bluetooth_traits::BluetoothRequest;
 use canvas_traits::webgl::WebGLPipeline;
 use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
 use devtools_traits::{DevtoolScriptControlMsg, ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::EventLoopWaker;
+use embedder_traits::{EventLoopWaker, MessageToPage};
 use euclid::{default::Point2D, Length, Rect, Scale, Size2D, UnknownUnit, Vector2D};
 use gfx_traits::Epoch;
 use http::HeaderMap;
@@ -57,6 +57,7 @@ use profile_traits::mem;
 use profile_traits::time as profile_time;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use servo_atoms::Atom;
+use servo_config::pref;
 use servo_url::ImmutableOrigin;
 use servo_url::ServoUrl;
 use std::borrow::Cow;
@@ -135,6 +136,35 @@ pub enum LayoutControlMsg {
     GetWebFontLoadState(IpcSender<bool>),
     /// Send the paint time for a specific epoch to the layout thread.
     PaintMetric(Epoch, u64),
+    /// The system is running low on memory; drop font and style caches.
+    MemoryPressure,
+}
+
+/// The display list of a page of a paginated document, for an embedder that prints the
+/// document or saves it in a format like PDF.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct PageDisplayList {
+    /// The size of the page box, whose top-left corner is the origin of the display list.
+    pub page_size: LayoutSize,
+    data: Vec<u8>,
+    descriptor: BuiltDisplayListDescriptor,
+}
+
+impl PageDisplayList {
+    /// Wraps the display list of a page so that it can be sent over IPC.
+    pub fn new(page_size: LayoutSize, display_list: BuiltDisplayList) -> Self {
+        let (data, descriptor) = display_list.into_data();
+        PageDisplayList {
+            page_size,
+            data,
+            descriptor,
+        }
+    }
+
+    /// The display list of the page, in a form that WebRender can render.
+    pub fn into_display_list(self) -> BuiltDisplayList {
+        BuiltDisplayList::from_data(self.data, self.descriptor)
+    }
 }
 
 /// The origin where a given load was initiated.
@@ -323,6 +353,9 @@ pub enum ConstellationControlMsg {
     GetTitle(PipelineId),
     /// Notifies script thread of a change to one of its document's activity
     SetDocumentActivity(PipelineId, DocumentActivity),
+    /// Notifies script thread whether a document should be frozen or resumed.
+    /// <https://wicg.github.io/page-lifecycle/#freezing>
+    SetDocumentFrozen(PipelineId, bool),
     /// Notifies script thread whether frame is visible
     ChangeFrameVisibilityStatus(PipelineId, bool),
     /// Notifies script thread that frame visibility change is complete
@@ -336,6 +369,12 @@ pub enum ConstellationControlMsg {
         LoadData,
         HistoryEntryReplacement,
     ),
+    /// Notifies script thread that a url should be loaded in the window of a pipeline, on behalf
+    /// of a script running in another script thread.
+    NavigateWindow(PipelineId, LoadData, HistoryEntryReplacement),
+    /// Notifies script thread that the window of a pipeline should be closed, on behalf of a
+    /// script running in another script thread.
+    CloseWindow(PipelineId),
     /// Post a message to a given window.
     PostMessage {
         /// The target of the message.
@@ -402,8 +441,18 @@ pub enum ConstellationControlMsg {
     PaintMetric(PipelineId, ProgressiveWebMetricType, u64),
     /// Notifies the media session about a user requested media session action.
     MediaSessionAction(PipelineId, MediaSessionActionType),
+    /// Delivers a message from the embedder to the embedder messaging channel
+    /// of the given pipeline.
+    PostMessageFromEmbedder(PipelineId, MessageToPage),
     /// Notifies script thread that WebGPU server has started
     SetWebGPUPort(IpcReceiver<WebGPUMsg>),
+    /// Restore the state of a page that was lost, like when it is reloaded after a crash.
+    RestorePageState(PipelineId, PageState),
+    /// Lays out the document of the given pipeline across pages and sends the display list
+    /// of each page, for an embedder that prints it.
+    GetPageDisplayLists(PipelineId, IpcSender<Vec<PageDisplayList>>),
+    /// The system is running low on memory; drop the image cache and collect garbage.
+    MemoryPressure,
 }
 
 impl fmt::Debug for ConstellationControlMsg {
@@ -423,9 +472,12 @@ impl fmt::Debug for ConstellationControlMsg {
             SetScrollState(..) => "SetScrollState",
             GetTitle(..) => "GetTitle",
             SetDocumentActivity(..) => "SetDocumentActivity",
+            SetDocumentFrozen(..) => "SetDocumentFrozen",
             ChangeFrameVisibilityStatus(..) => "ChangeFrameVisibilityStatus",
             NotifyVisibilityChange(..) => "NotifyVisibilityChange",
             NavigateIframe(..) => "NavigateIframe",
+            NavigateWindow(..) => "NavigateWindow",
+            CloseWindow(..) => "CloseWindow",
             PostMessage { .. } => "PostMessage",
             UpdatePipelineId(..) => "UpdatePipelineId",
             UpdateHistoryState(..) => "UpdateHistoryState",
@@ -441,7 +493,11 @@ impl fmt::Debug for ConstellationControlMsg {
             PaintMetric(..) => "PaintMetric",
             ExitFullScreen(..) => "ExitFullScreen",
             MediaSessionAction(..) => "MediaSessionAction",
+            PostMessageFromEmbedder(..) => "PostMessageFromEmbedder",
             SetWebGPUPort(..) => "SetWebGPUPort",
+            RestorePageState(..) => "RestorePageState",
+            GetPageDisplayLists(..) => "GetPageDisplayLists",
+            MemoryPressure => "MemoryPressure",
         };
         write!(formatter, "ConstellationControlMsg::{}", variant)
     }
@@ -782,6 +838,78 @@ bitflags! {
     }
 }
 
+/// The state of a page that its session history entry doesn't record, so that it can be
+/// restored when the page has to be loaded again, like after its content process crashed.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PageState {
+    /// The scroll position of the viewport.
+    pub scroll_position: Vector2D<f32, CSSPixel>,
+    /// The state of the form controls of the document that the user can change.
+    pub form_controls: Vec<FormControlState>,
+}
+
+impl Default for PageState {
+    fn default() -> PageState {
+        PageState {
+            scroll_position: Vector2D::zero(),
+            form_controls: vec![],
+        }
+    }
+}
+
+/// The state of a form control of a page.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct FormControlState {
+    /// The index of the control among the restorable form controls of the document, in tree
+    /// order.
+    pub index: usize,
+    /// A key that identifies the control, made of its kind and its name or ID, so that the
+    /// state isn't restored into a different control if the document changed.
+    pub key: String,
+    /// The value of the control.
+    pub value: FormControlValue,
+}
+
+/// The value of a form control.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum FormControlValue {
+    /// The value of a text control.
+    Text(String),
+    /// The checkedness of a checkbox or radio button.
+    Checked(bool),
+    /// The indices of the selected options of a select element.
+    Selected(Vec<usize>),
+}
+
+/// The saved state of top-level browsers, from which they can be restored, like when the
+/// embedder starts again.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct SessionState {
+    /// The state of each top-level browser.
+    pub browsers: Vec<BrowserSessionState>,
+}
+
+/// The saved state of a top-level browser.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct BrowserSessionState {
+    /// The entries of the session history of the top-level browsing context, from the oldest
+    /// to the most recent. The navigations of nested browsing contexts aren't kept.
+    pub entries: Vec<SessionHistoryEntryState>,
+    /// The index of the current entry.
+    pub current_index: usize,
+}
+
+/// The saved state of an entry of a session history.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SessionHistoryEntryState {
+    /// The URL of the entry.
+    pub url: ServoUrl,
+    /// The serialized state object of the entry, if it has one.
+    pub history_state: Option<Vec<u8>>,
+    /// The state of the page of the entry, if it is known.
+    pub page_state: Option<PageState>,
+}
+
 /// The scroll state of a stacking context.
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub struct ScrollState {
@@ -843,6 +971,8 @@ pub enum WebDriverCommandMsg {
         Option<Rect<f32, CSSPixel>>,
         IpcSender<Option<Image>>,
     ),
+    /// Take a screenshot of the whole scrollable area of the document in the window.
+    TakeFullPageScreenshot(TopLevelBrowsingContextId, IpcSender<Option<Image>>),
 }
 
 /// Resources required by workerglobalscopes
@@ -1098,6 +1228,30 @@ impl From<i32> for MediaSessionActionType {
     }
 }
 
+/// Whether documents of the given origin may exchange messages with the embedder.
+/// Embedder messaging must be enabled and the origin must either appear in the comma-separated
+/// `dom.embedder_messaging.allowed_origins` preference, or that preference must contain `*`.
+/// Opaque origins are never allowed.
+pub fn is_embedder_messaging_allowed(origin: &ImmutableOrigin) -> bool {
+    if !pref!(dom.embedder_messaging.enabled) || !origin.is_tuple() {
+        return false;
+    }
+    let origin = origin.ascii_serialization();
+    pref!(dom.embedder_messaging.allowed_origins)
+        .split(',')
+        .map(str::trim)
+        .any(|allowed| allowed == "*" || allowed == origin)
+}
+
+/// The identifier of the trace flow of the display list that layout sends for a pipeline
+/// at an epoch, which both layout and the compositor can compute.
+pub fn display_list_trace_flow_id(
+    pipeline: webrender_api::PipelineId,
+    epoch: webrender_api::Epoch,
+) -> profile_time::TraceFlowId {
+    profile_time::TraceFlowId::from_key(&(pipeline.0, pipeline.1, epoch.0))
+}
+
 /// The set of WebRender operations that can be initiated by the content process.
 #[derive(Deserialize, Serialize)]
 pub enum WebrenderMsg {
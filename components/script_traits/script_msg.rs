@@ -10,6 +10,7 @@ use crate::IFrameLoadInfoWithData;
 use crate::LayoutControlMsg;
 use crate::LoadData;
 use crate::MessagePortMsg;
+use crate::PageState;
 use crate::PortMessageTask;
 use crate::StructuredSerializedData;
 use crate::WindowSizeType;
@@ -17,7 +18,7 @@ use crate::WorkerGlobalScopeInit;
 use crate::WorkerScriptLoadOrigin;
 use canvas_traits::canvas::{CanvasId, CanvasMsg};
 use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::{EmbedderMsg, MediaSessionEvent};
+use embedder_traits::{EmbedderMsg, MediaSessionEvent, MessageFromPage};
 use euclid::default::Size2D as UntypedSize2D;
 use euclid::Size2D;
 use gfx_traits::Epoch;
@@ -198,6 +199,8 @@ pub enum ScriptMsg {
         usize,
         IpcSender<Option<BrowsingContextId>>,
     ),
+    /// Get the number of child browsing contexts of a given browsing context.
+    GetChildBrowsingContextCount(BrowsingContextId, IpcSender<u32>),
     /// All pending loads are complete, and the `load` event for this pipeline
     /// has been dispatched.
     LoadComplete,
@@ -220,6 +223,13 @@ pub enum ScriptMsg {
         /// The data to be posted.
         data: StructuredSerializedData,
     },
+    /// Navigate a browsing context whose active document lives in another script thread,
+    /// as when setting the location of a cross-origin window.
+    NavigateBrowsingContext(BrowsingContextId, LoadData, HistoryEntryReplacement),
+    /// Close a browsing context whose active document lives in another script thread.
+    CloseBrowsingContext(BrowsingContextId),
+    /// Focus a browsing context whose active document lives in another script thread.
+    FocusBrowsingContext(BrowsingContextId),
     /// Inform the constellation that a fragment was navigated to and whether or not it was a replacement navigation.
     NavigatedToFragment(ServoUrl, HistoryEntryReplacement),
     /// HTMLIFrameElement Forward or Back traversal.
@@ -256,6 +266,9 @@ pub enum ScriptMsg {
     LogEntry(Option<String>, LogEntry),
     /// Discard the document.
     DiscardDocument,
+    /// Notify the constellation that the document was kept in the back/forward cache,
+    /// with an estimate of the memory it uses in bytes.
+    CachedDocumentMemory(usize),
     /// Discard the browsing context.
     DiscardTopLevelBrowsingContext,
     /// Notifies the constellation that this pipeline has exited.
@@ -284,6 +297,11 @@ pub enum ScriptMsg {
     GetWebGPUChan(IpcSender<WebGPU>),
     /// Notify the constellation of a pipeline's document's title.
     TitleChanged(PipelineId, String),
+    /// Send a message from a document's embedder messaging channel to the embedder.
+    PostMessageToEmbedder(MessageFromPage),
+    /// Notify the constellation of a change to the state of a pipeline's page, like its scroll
+    /// position or the values of its form controls.
+    PageStateChanged(PageState),
 }
 
 impl fmt::Debug for ScriptMsg {
@@ -313,10 +331,14 @@ impl fmt::Debug for ScriptMsg {
             GetBrowsingContextInfo(..) => "GetBrowsingContextInfo",
             GetTopForBrowsingContext(..) => "GetParentBrowsingContext",
             GetChildBrowsingContextId(..) => "GetChildBrowsingContextId",
+            GetChildBrowsingContextCount(..) => "GetChildBrowsingContextCount",
             LoadComplete => "LoadComplete",
             LoadUrl(..) => "LoadUrl",
             AbortLoadUrl => "AbortLoadUrl",
             PostMessage { .. } => "PostMessage",
+            NavigateBrowsingContext(..) => "NavigateBrowsingContext",
+            CloseBrowsingContext(..) => "CloseBrowsingContext",
+            FocusBrowsingContext(..) => "FocusBrowsingContext",
             NavigatedToFragment(..) => "NavigatedToFragment",
             TraverseHistory(..) => "TraverseHistory",
             PushHistoryState(..) => "PushHistoryState",
@@ -333,6 +355,7 @@ impl fmt::Debug for ScriptMsg {
             TouchEventProcessed(..) => "TouchEventProcessed",
             LogEntry(..) => "LogEntry",
             DiscardDocument => "DiscardDocument",
+            CachedDocumentMemory(..) => "CachedDocumentMemory",
             DiscardTopLevelBrowsingContext => "DiscardTopLevelBrowsingContext",
             PipelineExited => "PipelineExited",
             ForwardDOMMessage(..) => "ForwardDOMMessage",
@@ -344,6 +367,8 @@ impl fmt::Debug for ScriptMsg {
             RequestAdapter(..) => "RequestAdapter",
             GetWebGPUChan(..) => "GetWebGPUChan",
             TitleChanged(..) => "TitleChanged",
+            PostMessageToEmbedder(..) => "PostMessageToEmbedder",
+            PageStateChanged(..) => "PageStateChanged",
         };
         write!(formatter, "ScriptMsg::{}", variant)
     }
@@ -77,21 +77,26 @@ use compositing::{CompositingReason, ConstellationMsg, IOCompositor, ShutdownSta
     not(target_os = "ios"),
     not(target_os = "android"),
     not(target_arch = "arm"),
-    not(target_arch = "aarch64")
+    not(target_arch = "aarch64"),
+    not(all(target_os = "linux", target_arch = "x86_64"))
 ))]
 use constellation::content_process_sandbox_profile;
-use constellation::{Constellation, InitialConstellationState, UnprivilegedContent};
+use constellation::{
+    Constellation, ContentProcessBootstrap, FileBrokerRequest, InitialConstellationState,
+    UnprivilegedContent,
+};
 use constellation::{FromCompositorLogger, FromScriptLogger};
 use crossbeam_channel::{unbounded, Sender};
 use embedder_traits::{EmbedderMsg, EmbedderProxy, EmbedderReceiver, EventLoopWaker};
 use env_logger::Builder as EnvLoggerBuilder;
-use euclid::{Scale, Size2D};
+use euclid::{Rect, Scale, Size2D};
 #[cfg(all(
     not(target_os = "windows"),
     not(target_os = "ios"),
     not(target_os = "android"),
     not(target_arch = "arm"),
-    not(target_arch = "aarch64")
+    not(target_arch = "aarch64"),
+    not(all(target_os = "linux", target_arch = "x86_64"))
 ))]
 use gaol::sandbox::{ChildSandbox, ChildSandboxMethods};
 use gfx::font_cache_thread::FontCacheThread;
@@ -99,7 +104,8 @@ use ipc_channel::ipc::{self, IpcSender};
 use log::{Log, Metadata, Record};
 use media::{GLPlayerThreads, WindowGLContext};
 use msg::constellation_msg::{PipelineNamespace, PipelineNamespaceId};
-use net::resource_thread::new_resource_threads;
+use net::resource_thread::{new_resource_threads, read_json_from_file, write_json_to_file};
+use net_traits::image::base::Image;
 use net_traits::IpcSend;
 use profile::mem as profile_mem;
 use profile::time as profile_time;
@@ -107,7 +113,7 @@ use profile_traits::mem;
 use profile_traits::time;
 use script::serviceworker_manager::ServiceWorkerManager;
 use script::JSEngineSetup;
-use script_traits::{ScriptToConstellationChan, WindowSizeData};
+use script_traits::{PageDisplayList, ScriptToConstellationChan, SessionState, WindowSizeData};
 use servo_config::opts;
 use servo_config::{pref, prefs};
 use servo_media::player::context::GlContext;
@@ -115,10 +121,12 @@ use servo_media::ServoMedia;
 use std::borrow::Cow;
 use std::cmp::max;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use style_traits::CSSPixel;
 use surfman::GLApi;
 use webrender::ShaderPrecacheFlags;
 use webrender_traits::WebrenderExternalImageHandlers;
@@ -228,10 +236,13 @@ pub struct Servo<Window: WindowMethods + 'static + ?Sized> {
     embedder_receiver: EmbedderReceiver,
     embedder_events: Vec<(Option<BrowserId>, EmbedderMsg)>,
     profiler_enabled: bool,
+    mem_profiler_chan: mem::ProfilerChan,
     /// For single-process Servo instances, this field controls the initialization
     /// and deinitialization of the JS Engine. Multiprocess Servo instances have their
     /// own instance that exists in the content process instead.
     _js_engine_setup: Option<JSEngineSetup>,
+    /// The thread that saves the session when quitting, which `deinit` waits for.
+    session_writer: Option<JoinHandle<()>>,
 }
 
 #[derive(Clone)]
@@ -525,7 +536,7 @@ where
                 receiver: compositor_receiver,
                 constellation_chan: constellation_chan.clone(),
                 time_profiler_chan: time_profiler_chan,
-                mem_profiler_chan: mem_profiler_chan,
+                mem_profiler_chan: mem_profiler_chan.clone(),
                 webrender,
                 webrender_document,
                 webrender_api,
@@ -545,7 +556,9 @@ where
             embedder_receiver: embedder_receiver,
             embedder_events: Vec::new(),
             profiler_enabled: false,
+            mem_profiler_chan,
             _js_engine_setup: js_engine_setup,
+            session_writer: None,
         }
     }
 
@@ -585,6 +598,13 @@ where
                 }
             },
 
+            WindowEvent::MemoryPressure => {
+                let msg = ConstellationMsg::MemoryPressure;
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending memory pressure to constellation failed ({:?}).", e);
+                }
+            },
+
             WindowEvent::MouseWindowEventClass(mouse_window_event) => {
                 self.compositor
                     .on_mouse_window_event_class(mouse_window_event);
@@ -645,6 +665,9 @@ where
             },
 
             WindowEvent::Quit => {
+                if self.compositor.shutdown_state == ShutdownState::NotShuttingDown {
+                    self.start_saving_session();
+                }
                 self.compositor.maybe_start_shutting_down();
             },
 
@@ -692,6 +715,26 @@ where
                 }
             },
 
+            WindowEvent::RestoreBrowser(browser_id, state) => {
+                let msg = ConstellationMsg::RestoreBrowser(browser_id, state);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending RestoreBrowser message to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            WindowEvent::NewBrowserWithProfile(url, browser_id, profile) => {
+                let msg = ConstellationMsg::NewBrowserWithProfile(url, browser_id, profile);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending NewBrowserWithProfile message to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
             WindowEvent::SelectBrowser(ctx) => {
                 let msg = ConstellationMsg::SelectBrowser(ctx);
                 if let Err(e) = self.constellation_chan.send(msg) {
@@ -744,6 +787,27 @@ where
                     );
                 }
             },
+
+            WindowEvent::PostMessageToPage(top_level_browsing_context_id, pipeline_id, message) => {
+                let msg = ConstellationMsg::PostMessageToPage(
+                    top_level_browsing_context_id,
+                    pipeline_id,
+                    message,
+                );
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending PostMessageToPage to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            WindowEvent::SiteData(top_level_browsing_context_id, request) => {
+                let msg = ConstellationMsg::SiteData(top_level_browsing_context_id, request);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending SiteData to constellation failed ({:?}).", e);
+                }
+            },
         }
     }
 
@@ -798,6 +862,87 @@ where
         self.compositor.pinch_zoom_level()
     }
 
+    /// Composite and read back the RGBA pixels of the current frame, see
+    /// `IOCompositor::capture_frame`.
+    pub fn capture_frame(&mut self, rect: Option<Rect<f32, CSSPixel>>) -> Option<Image> {
+        self.compositor.capture_frame(rect)
+    }
+
+    /// Capture the whole scrollable area of the current document, see
+    /// `IOCompositor::capture_full_page`.
+    pub fn capture_full_page(&mut self) -> Option<Image> {
+        self.compositor.capture_full_page()
+    }
+
+    /// Ask the memory profiler to collect the reports of every memory reporter and send
+    /// them to `sender`.
+    pub fn request_memory_report(&self, sender: IpcSender<mem::MemoryReportSnapshot>) {
+        self.mem_profiler_chan
+            .send(mem::ProfilerMsg::Snapshot(sender));
+    }
+
+    /// The current state of all the top-level browsers, from which they can be restored with
+    /// `WindowEvent::RestoreBrowser`.
+    pub fn session_state(&self) -> Option<SessionState> {
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        if let Err(e) = self
+            .constellation_chan
+            .send(ConstellationMsg::GetSessionState(sender))
+        {
+            warn!("Sending GetSessionState to constellation failed ({:?}).", e);
+            return None;
+        }
+        receiver.recv().ok()
+    }
+
+    /// The display list of each page of the document of a top-level browser, laid out for
+    /// printing, with the origin of each at the top-left corner of its page box.
+    pub fn page_display_lists(&self, browser_id: BrowserId) -> Vec<PageDisplayList> {
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        if let Err(e) = self
+            .constellation_chan
+            .send(ConstellationMsg::GetPageDisplayLists(browser_id, sender))
+        {
+            warn!(
+                "Sending GetPageDisplayLists to constellation failed ({:?}).",
+                e
+            );
+            return vec![];
+        }
+        receiver.recv().unwrap_or_default()
+    }
+
+    /// The session that was saved in the config directory when Servo last quit, if
+    /// session restore is enabled and it has any browsers.
+    pub fn saved_session(&self) -> Option<SessionState> {
+        read_session(&session_config_dir()?)
+    }
+
+    /// Start saving the state of the browsers in the config directory, if session restore
+    /// is enabled. The constellation gathers the state and it is written on another thread,
+    /// so that quitting doesn't wait for either; `deinit` waits for the file to be written.
+    fn start_saving_session(&mut self) {
+        let config_dir = match session_config_dir() {
+            Some(config_dir) => config_dir,
+            None => return,
+        };
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        if let Err(e) = self
+            .constellation_chan
+            .send(ConstellationMsg::GetSessionState(sender))
+        {
+            return warn!("Sending GetSessionState to constellation failed ({:?}).", e);
+        }
+        let session_writer = thread::Builder::new()
+            .name("SessionWriter".to_owned())
+            .spawn(move || match receiver.recv() {
+                Ok(session) => write_session(&session, &config_dir),
+                Err(e) => warn!("Failed to receive the session state ({:?}).", e),
+            })
+            .expect("Thread spawning failed");
+        self.session_writer = Some(session_writer);
+    }
+
     pub fn setup_logging(&self) {
         let constellation_chan = self.constellation_chan.clone();
         let env = env_logger::Env::default();
@@ -817,9 +962,37 @@ where
 
     pub fn deinit(self) {
         self.compositor.deinit();
+        if let Some(session_writer) = self.session_writer {
+            if session_writer.join().is_err() {
+                warn!("Failed to save the session.");
+            }
+        }
     }
 }
 
+/// The file in the config directory that the session is saved to.
+const SESSION_FILE: &'static str = "session.json";
+
+/// The directory the session is saved to, if the embedder opted in to restore sessions.
+fn session_config_dir() -> Option<PathBuf> {
+    if !pref!(session_history.restore.enabled) {
+        return None;
+    }
+    opts::get().config_dir.clone()
+}
+
+/// Save a session in a config directory.
+fn write_session(session: &SessionState, config_dir: &Path) {
+    write_json_to_file(session, config_dir, SESSION_FILE);
+}
+
+/// The session saved in a config directory, if it has any browsers.
+fn read_session(config_dir: &Path) -> Option<SessionState> {
+    let mut session = SessionState::default();
+    read_json_from_file(&mut session, config_dir, SESSION_FILE);
+    Some(session).filter(|session| !session.browsers.is_empty())
+}
+
 fn create_embedder_channel(
     event_loop_waker: Box<dyn EventLoopWaker>,
 ) -> (EmbedderProxy, EmbedderReceiver) {
@@ -1015,15 +1188,15 @@ pub fn set_logger(script_to_constellation_chan: ScriptToConstellationChan) {
 
 /// Content process entry point.
 pub fn run_content_process(token: String) {
-    let (unprivileged_content_sender, unprivileged_content_receiver) =
-        ipc::channel::<UnprivilegedContent>().unwrap();
-    let connection_bootstrap: IpcSender<IpcSender<UnprivilegedContent>> =
+    let (bootstrap_sender, bootstrap_receiver) = ipc::channel::<ContentProcessBootstrap>().unwrap();
+    let connection_bootstrap: IpcSender<IpcSender<ContentProcessBootstrap>> =
         IpcSender::connect(token).unwrap();
-    connection_bootstrap
-        .send(unprivileged_content_sender)
-        .unwrap();
+    connection_bootstrap.send(bootstrap_sender).unwrap();
 
-    let unprivileged_content = unprivileged_content_receiver.recv().unwrap();
+    let ContentProcessBootstrap {
+        content: unprivileged_content,
+        file_broker,
+    } = bootstrap_receiver.recv().unwrap();
     opts::set_options(unprivileged_content.opts());
     prefs::pref_map()
         .set_all(unprivileged_content.prefs())
@@ -1031,7 +1204,7 @@ pub fn run_content_process(token: String) {
 
     // Enter the sandbox if necessary.
     if opts::get().sandbox {
-        create_sandbox();
+        create_sandbox(file_broker);
     }
 
     let _js_engine_setup = script::init();
@@ -1058,14 +1231,21 @@ pub fn run_content_process(token: String) {
     }
 }
 
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+fn create_sandbox(file_broker: Option<IpcSender<FileBrokerRequest>>) {
+    let file_broker = file_broker.expect("Sandboxed content process has no file broker!");
+    constellation::enter_sandbox(file_broker, opts::get().sandbox_report_syscalls);
+}
+
 #[cfg(all(
     not(target_os = "windows"),
     not(target_os = "ios"),
     not(target_os = "android"),
     not(target_arch = "arm"),
-    not(target_arch = "aarch64")
+    not(target_arch = "aarch64"),
+    not(all(target_os = "linux", target_arch = "x86_64"))
 ))]
-fn create_sandbox() {
+fn create_sandbox(_file_broker: Option<IpcSender<FileBrokerRequest>>) {
     ChildSandbox::new(content_process_sandbox_profile())
         .activate()
         .expect("Failed to activate sandbox!");
@@ -1078,7 +1258,7 @@ fn create_sandbox() {
     target_arch = "arm",
     target_arch = "aarch64"
 ))]
-fn create_sandbox() {
+fn create_sandbox(_file_broker: Option<IpcSender<FileBrokerRequest>>) {
     panic!("Sandboxing is not supported on Windows, iOS, ARM targets and android.");
 }
 
@@ -1125,3 +1305,65 @@ const DEFAULT_USER_AGENT: UserAgent = UserAgent::iOS;
 
 #[cfg(not(any(target_os = "android", target_os = "ios")))]
 const DEFAULT_USER_AGENT: UserAgent = UserAgent::Desktop;
+
+#[cfg(test)]
+mod tests {
+    use super::{read_session, write_session};
+    use euclid::Vector2D;
+    use script_traits::{BrowserSessionState, SessionHistoryEntryState, SessionState};
+    use script_traits::{FormControlState, FormControlValue, PageState};
+    use servo_url::ServoUrl;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    #[test]
+    fn test_session_round_trip() {
+        let entry = |url: &str, page_state: Option<PageState>| SessionHistoryEntryState {
+            url: ServoUrl::parse(url).unwrap(),
+            history_state: None,
+            page_state,
+        };
+        let session = SessionState {
+            browsers: vec![
+                BrowserSessionState {
+                    entries: vec![
+                        entry("https://servo.org/", None),
+                        SessionHistoryEntryState {
+                            history_state: Some(vec![1, 2, 3]),
+                            ..entry("https://servo.org/#about", None)
+                        },
+                        entry(
+                            "https://example.com/form",
+                            Some(PageState {
+                                scroll_position: Vector2D::new(0., 250.),
+                                form_controls: vec![FormControlState {
+                                    index: 0,
+                                    key: "input[type=text] name".to_owned(),
+                                    value: FormControlValue::Text("value".to_owned()),
+                                }],
+                            }),
+                        ),
+                    ],
+                    current_index: 1,
+                },
+                BrowserSessionState {
+                    entries: vec![entry("about:blank", Some(PageState::default()))],
+                    current_index: 0,
+                },
+            ],
+        };
+
+        let config_dir = env::temp_dir().join(format!("servo-session-{}", process::id()));
+        fs::create_dir_all(&config_dir).unwrap();
+        assert_eq!(read_session(&config_dir), None);
+        write_session(&session, &config_dir);
+        assert_eq!(read_session(&config_dir), Some(session));
+
+        // A session without browsers isn't restored.
+        write_session(&SessionState::default(), &config_dir);
+        assert_eq!(read_session(&config_dir), None);
+
+        fs::remove_dir_all(&config_dir).unwrap();
+    }
+}
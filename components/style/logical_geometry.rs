@@ -115,7 +115,7 @@ impl WritingMode {
                     flags.insert(WritingMode::INLINE_REVERSED);
                 }
             },
-            #[cfg(feature = "gecko")]
+            #[cfg(any(feature = "gecko", feature = "servo-layout-2020"))]
             SpecifiedWritingMode::SidewaysRl => {
                 flags.insert(WritingMode::VERTICAL);
                 flags.insert(WritingMode::VERTICAL_SIDEWAYS);
@@ -123,7 +123,7 @@ impl WritingMode {
                     flags.insert(WritingMode::INLINE_REVERSED);
                 }
             },
-            #[cfg(feature = "gecko")]
+            #[cfg(any(feature = "gecko", feature = "servo-layout-2020"))]
             SpecifiedWritingMode::SidewaysLr => {
                 flags.insert(WritingMode::VERTICAL);
                 flags.insert(WritingMode::VERTICAL_LR);
@@ -38,6 +38,7 @@ STYLE_STRUCT_LIST = [
     "margin",
     "outline",
     "padding",
+    "page",
     "position",
     "svg",
     "table",
@@ -44,7 +44,7 @@ ${helpers.single_keyword(
 
 <%helpers:single_keyword
     name="position"
-    values="static absolute relative fixed ${'sticky' if engine in ['gecko', 'servo-2013'] else ''}"
+    values="static absolute relative fixed sticky"
     engines="gecko servo-2013 servo-2020"
     animation_value_type="discrete"
     gecko_enum_prefix="StylePositionProperty"
@@ -67,7 +67,7 @@ ${helpers.predefined_type(
     "Float",
     "computed::Float::None",
     engines="gecko servo-2013 servo-2020",
-    servo_2020_pref="layout.2020.unimplemented",
+    servo_2020_pref="layout.floats.enabled",
     initial_specified_value="specified::Float::None",
     spec="https://drafts.csswg.org/css-box/#propdef-float",
     animation_value_type="discrete",
@@ -80,7 +80,8 @@ ${helpers.predefined_type(
     "clear",
     "Clear",
     "computed::Clear::None",
-    engines="gecko servo-2013",
+    engines="gecko servo-2013 servo-2020",
+    servo_2020_pref="layout.floats.enabled",
     animation_value_type="discrete",
     needs_context=False,
     gecko_ffi_name="mBreakType",
@@ -493,31 +494,34 @@ ${helpers.predefined_type(
     "break-after",
     "BreakBetween",
     "computed::BreakBetween::Auto",
-    engines="gecko",
+    engines="gecko servo-2020",
     needs_context=False,
     spec="https://drafts.csswg.org/css-break/#propdef-break-after",
     animation_value_type="discrete",
+    servo_restyle_damage="reflow",
 )}
 
 ${helpers.predefined_type(
     "break-before",
     "BreakBetween",
     "computed::BreakBetween::Auto",
-    engines="gecko",
+    engines="gecko servo-2020",
     needs_context=False,
     spec="https://drafts.csswg.org/css-break/#propdef-break-before",
     animation_value_type="discrete",
+    servo_restyle_damage="reflow",
 )}
 
 ${helpers.predefined_type(
     "break-inside",
     "BreakWithin",
     "computed::BreakWithin::Auto",
-    engines="gecko",
+    engines="gecko servo-2020",
     needs_context=False,
     alias="page-break-inside",
     spec="https://drafts.csswg.org/css-break/#propdef-break-inside",
     animation_value_type="discrete",
+    servo_restyle_damage="reflow",
 )}
 
 // CSS Basic User Interface Module Level 3
@@ -11,7 +11,7 @@ ${helpers.predefined_type(
     "length::NonNegativeLengthOrAuto",
     "computed::length::NonNegativeLengthOrAuto::auto()",
     engines="gecko servo-2013 servo-2020",
-    servo_2020_pref="layout.2020.unimplemented",
+    servo_2020_pref="layout.columns.enabled",
     initial_specified_value="specified::length::NonNegativeLengthOrAuto::auto()",
     extra_prefixes="moz:layout.css.prefixes.columns",
     animation_value_type="NonNegativeLengthOrAuto",
@@ -25,7 +25,7 @@ ${helpers.predefined_type(
     "ColumnCount",
     "computed::ColumnCount::auto()",
     engines="gecko servo-2013 servo-2020",
-    servo_2020_pref="layout.2020.unimplemented",
+    servo_2020_pref="layout.columns.enabled",
     initial_specified_value="specified::ColumnCount::auto()",
     servo_2013_pref="layout.columns.enabled",
     animation_value_type="AnimatedColumnCount",
@@ -37,18 +37,21 @@ ${helpers.predefined_type(
 ${helpers.single_keyword(
     "column-fill",
     "balance auto",
-    engines="gecko",
+    engines="gecko servo-2020",
+    servo_2020_pref="layout.columns.enabled",
     extra_prefixes="moz:layout.css.prefixes.columns",
     animation_value_type="discrete",
     gecko_enum_prefix="StyleColumnFill",
     spec="https://drafts.csswg.org/css-multicol/#propdef-column-fill",
+    servo_restyle_damage="reflow",
 )}
 
 ${helpers.predefined_type(
     "column-rule-width",
     "BorderSideWidth",
     "crate::values::computed::NonNegativeLength::new(3.)",
-    engines="gecko",
+    engines="gecko servo-2020",
+    servo_2020_pref="layout.columns.enabled",
     initial_specified_value="specified::BorderSideWidth::Medium",
     computed_type="crate::values::computed::NonNegativeLength",
     spec="https://drafts.csswg.org/css-multicol/#propdef-column-rule-width",
@@ -61,7 +64,8 @@ ${helpers.predefined_type(
     "column-rule-color",
     "Color",
     "computed_value::T::currentcolor()",
-    engines="gecko",
+    engines="gecko servo-2020",
+    servo_2020_pref="layout.columns.enabled",
     initial_specified_value="specified::Color::currentcolor()",
     animation_value_type="AnimatedColor",
     extra_prefixes="moz:layout.css.prefixes.columns",
@@ -72,18 +76,21 @@ ${helpers.predefined_type(
 ${helpers.single_keyword(
     "column-span",
     "none all",
-    engines="gecko",
+    engines="gecko servo-2020",
+    servo_2020_pref="layout.columns.enabled",
     animation_value_type="discrete",
     gecko_enum_prefix="StyleColumnSpan",
     spec="https://drafts.csswg.org/css-multicol/#propdef-column-span",
     extra_prefixes="moz:layout.css.prefixes.columns",
+    servo_restyle_damage="reflow",
 )}
 
 ${helpers.predefined_type(
     "column-rule-style",
     "BorderStyle",
     "computed::BorderStyle::None",
-    engines="gecko",
+    engines="gecko servo-2020",
+    servo_2020_pref="layout.columns.enabled",
     needs_context=False,
     initial_specified_value="specified::BorderStyle::None",
     extra_prefixes="moz:layout.css.prefixes.columns",
@@ -209,11 +209,12 @@ ${helpers.single_keyword_system(
 ${helpers.predefined_type(
     "-x-lang",
     "XLang",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     initial_value="computed::XLang::get_initial_value()",
     animation_value_type="none",
     enabled_in="",
     spec="Internal (not web-exposed)",
+    servo_restyle_damage="rebuild_and_reflow",
 )}
 
 ${helpers.predefined_type(
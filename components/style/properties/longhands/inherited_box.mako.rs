@@ -24,6 +24,7 @@ ${helpers.single_keyword(
     "horizontal-tb vertical-rl vertical-lr",
     engines="gecko servo-2013 servo-2020",
     extra_gecko_values="sideways-rl sideways-lr",
+    extra_servo_2020_values="sideways-rl sideways-lr",
     gecko_aliases="lr=horizontal-tb lr-tb=horizontal-tb \
                          rl=horizontal-tb rl-tb=horizontal-tb \
                          tb=vertical-rl   tb-rl=vertical-rl",
@@ -38,7 +39,6 @@ ${helpers.single_keyword(
     "direction",
     "ltr rtl",
     engines="gecko servo-2013 servo-2020",
-    servo_2020_pref="layout.2020.unimplemented",
     animation_value_type="none",
     spec="https://drafts.csswg.org/css-writing-modes/#propdef-direction",
     gecko_enum_prefix="StyleDirection",
@@ -9,7 +9,8 @@
 ${helpers.single_keyword(
     "border-collapse",
     "separate collapse",
-    engines="gecko servo-2013",
+    engines="gecko servo-2013 servo-2020",
+    servo_2020_pref="layout.tables.enabled",
     gecko_enum_prefix="StyleBorderCollapse",
     animation_value_type="discrete",
     spec="https://drafts.csswg.org/css-tables/#propdef-border-collapse",
@@ -19,7 +20,8 @@ ${helpers.single_keyword(
 ${helpers.single_keyword(
     "empty-cells",
     "show hide",
-    engines="gecko servo-2013",
+    engines="gecko servo-2013 servo-2020",
+    servo_2020_pref="layout.tables.enabled",
     gecko_enum_prefix="StyleEmptyCells",
     animation_value_type="discrete",
     spec="https://drafts.csswg.org/css-tables/#propdef-empty-cells",
@@ -29,7 +31,8 @@ ${helpers.single_keyword(
 ${helpers.single_keyword(
     "caption-side",
     "top bottom",
-    engines="gecko servo-2013",
+    engines="gecko servo-2013 servo-2020",
+    servo_2020_pref="layout.tables.enabled",
     extra_gecko_values="right left top-outside bottom-outside",
     needs_conversion="True",
     animation_value_type="discrete",
@@ -42,7 +45,7 @@ ${helpers.predefined_type(
     "BorderSpacing",
     "computed::BorderSpacing::zero()",
     engines="gecko servo-2013 servo-2020",
-    servo_2020_pref="layout.2020.unimplemented",
+    servo_2020_pref="layout.tables.enabled",
     animation_value_type="BorderSpacing",
     boxed=True,
     spec="https://drafts.csswg.org/css-tables/#propdef-border-spacing",
@@ -41,11 +41,12 @@ ${helpers.predefined_type(
 ${helpers.single_keyword(
     "hyphens",
     "manual none auto",
-    engines="gecko",
+    engines="gecko servo-2013 servo-2020",
     gecko_enum_prefix="StyleHyphens",
     animation_value_type="discrete",
     extra_prefixes="moz",
     spec="https://drafts.csswg.org/css-text/#propdef-hyphens",
+    servo_restyle_damage="rebuild_and_reflow",
 )}
 
 // TODO: Support <percentage>
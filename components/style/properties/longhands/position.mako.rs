@@ -93,12 +93,13 @@ ${helpers.single_keyword(
     gecko_enum_prefix = "StyleFlexWrap",
 )}
 
-% if engine == "servo-2013":
+% if engine in ["servo-2013", "servo-2020"]:
     // FIXME: Update Servo to support the same Syntax as Gecko.
     ${helpers.single_keyword(
         "justify-content",
         "flex-start stretch flex-end center space-between space-around",
-        engines="servo-2013",
+        engines="servo-2013 servo-2020",
+        servo_2020_pref="layout.grid.enabled",
         extra_prefixes="webkit",
         spec="https://drafts.csswg.org/css-align/#propdef-justify-content",
         animation_value_type="discrete",
@@ -134,7 +135,8 @@ ${helpers.single_keyword(
     ${helpers.single_keyword(
         "align-content",
         "stretch flex-start flex-end center space-between space-around",
-        engines="servo-2013",
+        engines="servo-2013 servo-2020",
+        servo_2020_pref="layout.grid.enabled",
         extra_prefixes="webkit",
         spec="https://drafts.csswg.org/css-align/#propdef-align-content",
         animation_value_type="discrete",
@@ -151,6 +153,16 @@ ${helpers.single_keyword(
         animation_value_type="discrete",
         servo_restyle_damage="reflow",
     )}
+
+    ${helpers.single_keyword(
+        "justify-items",
+        "stretch flex-start flex-end center baseline",
+        engines="servo-2020",
+        servo_2020_pref="layout.grid.enabled",
+        spec="https://drafts.csswg.org/css-align/#propdef-justify-items",
+        animation_value_type="discrete",
+        servo_restyle_damage="reflow",
+    )}
 % endif
 % if engine == "gecko":
     ${helpers.predefined_type(
@@ -240,6 +252,16 @@ ${helpers.predefined_type(
         animation_value_type="discrete",
         servo_restyle_damage = "reflow",
     )}
+
+    ${helpers.single_keyword(
+        "justify-self",
+        "auto stretch flex-start flex-end center baseline",
+        engines="servo-2020",
+        servo_2020_pref="layout.grid.enabled",
+        spec="https://drafts.csswg.org/css-align/#propdef-justify-self",
+        animation_value_type="discrete",
+        servo_restyle_damage = "reflow",
+    )}
 % endif
 % if engine == "gecko":
     ${helpers.predefined_type(
@@ -374,7 +396,8 @@ ${helpers.predefined_type(
             "grid-%s-%s" % (kind, range),
             "GridLine",
             "Default::default()",
-            engines="gecko",
+            engines="gecko servo-2020",
+            servo_2020_pref="layout.grid.enabled",
             animation_value_type="discrete",
             spec="https://drafts.csswg.org/css-grid/#propdef-grid-%s-%s" % (kind, range),
         )}
@@ -384,7 +407,8 @@ ${helpers.predefined_type(
         "grid-auto-%ss" % kind,
         "ImplicitGridTracks",
         "Default::default()",
-        engines="gecko",
+        engines="gecko servo-2020",
+        servo_2020_pref="layout.grid.enabled",
         animation_value_type="discrete",
         spec="https://drafts.csswg.org/css-grid/#propdef-grid-auto-%ss" % kind,
     )}
@@ -393,7 +417,8 @@ ${helpers.predefined_type(
         "grid-template-%ss" % kind,
         "GridTemplateComponent",
         "specified::GenericGridTemplateComponent::None",
-        engines="gecko",
+        engines="gecko servo-2020",
+        servo_2020_pref="layout.grid.enabled",
         spec="https://drafts.csswg.org/css-grid/#propdef-grid-template-%ss" % kind,
         animation_value_type="ComputedValue",
     )}
@@ -414,7 +439,8 @@ ${helpers.predefined_type(
     "grid-auto-flow",
     "GridAutoFlow",
     "computed::GridAutoFlow::ROW",
-    engines="gecko",
+    engines="gecko servo-2020",
+    servo_2020_pref="layout.grid.enabled",
     animation_value_type="discrete",
     spec="https://drafts.csswg.org/css-grid/#propdef-grid-auto-flow",
 )}
@@ -423,7 +449,8 @@ ${helpers.predefined_type(
     "grid-template-areas",
     "GridTemplateAreas",
     "computed::GridTemplateAreas::none()",
-    engines="gecko",
+    engines="gecko servo-2020",
+    servo_2020_pref="layout.grid.enabled",
     animation_value_type="discrete",
     spec="https://drafts.csswg.org/css-grid/#propdef-grid-template-areas",
 )}
@@ -432,8 +459,8 @@ ${helpers.predefined_type(
     "column-gap",
     "length::NonNegativeLengthPercentageOrNormal",
     "computed::length::NonNegativeLengthPercentageOrNormal::normal()",
-    engines="gecko servo-2013",
-    alias="grid-column-gap" if engine == "gecko" else "",
+    engines="gecko servo-2013 servo-2020",
+    alias="grid-column-gap" if engine in ["gecko", "servo-2020"] else "",
     extra_prefixes="moz:layout.css.prefixes.columns",
     servo_2013_pref="layout.columns.enabled",
     spec="https://drafts.csswg.org/css-align-3/#propdef-column-gap",
@@ -446,7 +473,8 @@ ${helpers.predefined_type(
     "row-gap",
     "length::NonNegativeLengthPercentageOrNormal",
     "computed::length::NonNegativeLengthPercentageOrNormal::normal()",
-    engines="gecko",
+    engines="gecko servo-2020",
+    servo_2020_pref="layout.grid.enabled",
     alias="grid-row-gap",
     spec="https://drafts.csswg.org/css-align-3/#propdef-row-gap",
     animation_value_type="NonNegativeLengthPercentageOrNormal",
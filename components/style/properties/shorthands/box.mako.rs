@@ -318,7 +318,7 @@ ${helpers.two_properties_shorthand(
 )}
 
 <%helpers:shorthand
-    engines="gecko"
+    engines="gecko servo-2020"
     name="page-break-before"
     flags="SHORTHAND_IN_GETCS IS_LEGACY_SHORTHAND"
     sub_properties="break-before"
@@ -342,7 +342,7 @@ ${helpers.two_properties_shorthand(
 </%helpers:shorthand>
 
 <%helpers:shorthand
-    engines="gecko"
+    engines="gecko servo-2020"
     name="page-break-after"
     flags="SHORTHAND_IN_GETCS IS_LEGACY_SHORTHAND"
     sub_properties="break-after"
@@ -5,9 +5,10 @@
 <%namespace name="helpers" file="/helpers.mako.rs" />
 
 <%helpers:shorthand name="columns"
-                    engines="gecko servo-2013"
+                    engines="gecko servo-2013 servo-2020"
                     sub_properties="column-width column-count"
                     servo_2013_pref="layout.columns.enabled",
+                    servo_2020_pref="layout.columns.enabled",
                     derive_serialize="True"
                     extra_prefixes="moz:layout.css.prefixes.columns"
                     spec="https://drafts.csswg.org/css-multicol/#propdef-columns">
@@ -59,7 +60,8 @@
 
 <%helpers:shorthand
     name="column-rule"
-    engines="gecko"
+    engines="gecko servo-2020"
+    servo_2020_pref="layout.columns.enabled"
     extra_prefixes="moz:layout.css.prefixes.columns"
     sub_properties="column-rule-width column-rule-style column-rule-color"
     derive_serialize="True"
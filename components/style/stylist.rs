@@ -29,8 +29,8 @@ use crate::stylesheets::viewport_rule::{self, MaybeNew, ViewportRule};
 use crate::stylesheets::StyleRule;
 use crate::stylesheets::StylesheetInDocument;
 #[cfg(feature = "gecko")]
-use crate::stylesheets::{CounterStyleRule, FontFaceRule, FontFeatureValuesRule, PageRule};
-use crate::stylesheets::{CssRule, Origin, OriginSet, PerOrigin, PerOriginIter};
+use crate::stylesheets::{CounterStyleRule, FontFaceRule, FontFeatureValuesRule};
+use crate::stylesheets::{CssRule, Origin, OriginSet, PageRule, PerOrigin, PerOriginIter};
 use crate::thread_state::{self, ThreadState};
 use crate::{Atom, LocalName, Namespace, WeakAtom};
 use fallible::FallibleVec;
@@ -1348,6 +1348,74 @@ impl Stylist {
         )
     }
 
+    /// Computes the style of page boxes from the effective @page rules, which
+    /// are cascaded like the rules that match an element.
+    ///
+    /// FIXME: Page selectors are not supported, so every @page rule applies
+    /// to every page.
+    pub fn compute_for_page_rules<E>(
+        &self,
+        guards: &StylesheetGuards,
+        parent_style: &ComputedValues,
+    ) -> Arc<ComputedValues>
+    where
+        E: TElement,
+    {
+        use crate::font_metrics::get_metrics_provider_for_product;
+
+        // The declarations are in decreasing order of precedence: important ones
+        // from the user agent to the author, then normal ones from the author to
+        // the user agent, and the later rules of each origin first.
+        let mut important_declarations = Vec::new();
+        for (data, origin) in self.iter_extra_data_origins_rev() {
+            let guard = guards.for_origin(origin);
+            for rule in data.pages.iter().rev() {
+                let block = rule.read_with(guard).block.read_with(guard);
+                important_declarations.extend(
+                    block
+                        .declaration_importance_iter()
+                        .filter(|(_, importance)| importance.important())
+                        .map(|(declaration, _)| (declaration, origin)),
+                );
+            }
+        }
+        let mut normal_declarations = Vec::new();
+        for (data, origin) in self.iter_extra_data_origins() {
+            let guard = guards.for_origin(origin);
+            for rule in data.pages.iter().rev() {
+                let block = rule.read_with(guard).block.read_with(guard);
+                normal_declarations.extend(
+                    block
+                        .declaration_importance_iter()
+                        .filter(|(_, importance)| !importance.important())
+                        .map(|(declaration, _)| (declaration, origin)),
+                );
+            }
+        }
+
+        let metrics = get_metrics_provider_for_product();
+        properties::apply_declarations::<E, _>(
+            &self.device,
+            /* pseudo = */ None,
+            self.rule_tree.root(),
+            guards,
+            important_declarations
+                .into_iter()
+                .chain(normal_declarations),
+            Some(parent_style),
+            Some(parent_style),
+            Some(parent_style),
+            &metrics,
+            CascadeMode::Unvisited {
+                visited_rules: None,
+            },
+            self.quirks_mode,
+            /* rule_cache = */ None,
+            &mut Default::default(),
+            /* element = */ None,
+        )
+    }
+
     /// Accessor for a shared reference to the device.
     #[inline]
     pub fn device(&self) -> &Device {
@@ -1375,6 +1443,12 @@ impl Stylist {
         // We may measure other fields in the future if DMD says it's worth it.
     }
 
+    /// Drop the cached user-agent cascade data that no stylist is using
+    /// anymore, to release memory under pressure.
+    pub fn purge_unused_ua_cascade_data() {
+        let _unused = UA_CASCADE_DATA_CACHE.lock().unwrap().take_unused();
+    }
+
     /// Shutdown the static data that this module stores.
     pub fn shutdown() {
         let _entries = UA_CASCADE_DATA_CACHE.lock().unwrap().take_all();
@@ -1399,7 +1473,7 @@ pub struct ExtraStyleData {
     pub counter_styles: PrecomputedHashMap<Atom, Arc<Locked<CounterStyleRule>>>,
 
     /// A map of effective page rules.
-    #[cfg(feature = "gecko")]
+    #[cfg_attr(feature = "servo", ignore_malloc_size_of = "Arc")]
     pub pages: Vec<Arc<Locked<PageRule>>>,
 }
 
@@ -1429,22 +1503,22 @@ impl ExtraStyleData {
         let name = rule.read_with(guard).name().0.clone();
         self.counter_styles.insert(name, rule.clone());
     }
+}
 
+impl ExtraStyleData {
     /// Add the given @page rule.
     fn add_page(&mut self, rule: &Arc<Locked<PageRule>>) {
         self.pages.push(rule.clone());
     }
-}
 
-impl ExtraStyleData {
     fn clear(&mut self) {
         #[cfg(feature = "gecko")]
         {
             self.font_faces.clear();
             self.font_feature_values.clear();
             self.counter_styles.clear();
-            self.pages.clear();
         }
+        self.pages.clear();
     }
 }
 
@@ -2136,7 +2210,6 @@ impl CascadeData {
                 CssRule::CounterStyle(ref rule) => {
                     self.extra_data.add_counter_style(guard, rule);
                 },
-                #[cfg(feature = "gecko")]
                 CssRule::Page(ref rule) => {
                     self.extra_data.add_page(rule);
                 },
@@ -0,0 +1,15 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Computed types for the page properties.
+
+use crate::values::computed::NonNegativeLength;
+use crate::values::generics::page::PageSize as GenericPageSize;
+use crate::values::generics::size::Size2D;
+
+pub use crate::values::generics::page::{PageOrientation, PaperSize};
+
+/// A computed value for the `size` property of page boxes, where standard
+/// paper sizes are resolved to their dimensions.
+pub type PageSize = GenericPageSize<Size2D<NonNegativeLength>>;
@@ -0,0 +1,89 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Generic types for the page properties.
+
+/// A standard paper size.
+/// https://drafts.csswg.org/css-page-3/#typedef-page-size-page-size
+#[allow(missing_docs)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Eq,
+    MallocSizeOf,
+    Parse,
+    PartialEq,
+    SpecifiedValueInfo,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+pub enum PaperSize {
+    A5,
+    A4,
+    A3,
+    B5,
+    B4,
+    JisB5,
+    JisB4,
+    Letter,
+    Legal,
+    Ledger,
+}
+
+/// The orientation of a page.
+/// https://drafts.csswg.org/css-page-3/#valdef-page-size-portrait
+#[allow(missing_docs)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Eq,
+    MallocSizeOf,
+    Parse,
+    PartialEq,
+    SpecifiedValueInfo,
+    ToComputedValue,
+    ToCss,
+    ToResolvedValue,
+    ToShmem,
+)]
+pub enum PageOrientation {
+    Portrait,
+    Landscape,
+}
+
+impl PageOrientation {
+    /// Whether this is the `portrait` orientation, which is the default one.
+    #[inline]
+    pub fn is_portrait(&self) -> bool {
+        *self == PageOrientation::Portrait
+    }
+}
+
+/// A generic value for the `size` property of page boxes.
+/// https://drafts.csswg.org/css-page-3/#page-size-prop
+#[derive(
+    Clone, Copy, Debug, MallocSizeOf, PartialEq, SpecifiedValueInfo, ToCss, ToResolvedValue, ToShmem,
+)]
+pub enum GenericPageSize<S> {
+    /// The width and height of the page.
+    Size(S),
+    /// The default page size, in the given orientation.
+    Orientation(PageOrientation),
+    /// The keyword `auto`.
+    Auto,
+}
+
+pub use self::GenericPageSize as PageSize;
+
+impl<S> PageSize<S> {
+    /// Returns `auto`.
+    #[inline]
+    pub fn auto() -> Self {
+        PageSize::Auto
+    }
+}
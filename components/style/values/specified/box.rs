@@ -47,6 +47,32 @@ fn flexbox_enabled() -> bool {
         .unwrap_or(false)
 }
 
+#[cfg(feature = "gecko")]
+fn grid_enabled() -> bool {
+    true
+}
+
+#[cfg(feature = "servo-layout-2020")]
+fn grid_enabled() -> bool {
+    servo_config::prefs::pref_map()
+        .get("layout.grid.enabled")
+        .as_bool()
+        .unwrap_or(false)
+}
+
+#[cfg(not(feature = "servo-layout-2020"))]
+fn tables_enabled() -> bool {
+    true
+}
+
+#[cfg(feature = "servo-layout-2020")]
+fn tables_enabled() -> bool {
+    servo_config::prefs::pref_map()
+        .get("layout.tables.enabled")
+        .as_bool()
+        .unwrap_or(false)
+}
+
 /// Defines an element’s display type, which consists of
 /// the two basic qualities of how an element generates boxes
 /// <https://drafts.csswg.org/css-display/#propdef-display>
@@ -57,9 +83,7 @@ pub enum DisplayOutside {
     None = 0,
     Inline,
     Block,
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     TableCaption,
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     InternalTable,
     #[cfg(feature = "gecko")]
     InternalRuby,
@@ -77,23 +101,15 @@ pub enum DisplayInside {
     Flow,
     FlowRoot,
     Flex,
-    #[cfg(feature = "gecko")]
+    #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
     Grid,
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     Table,
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     TableRowGroup,
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     TableColumn,
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     TableColumnGroup,
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     TableHeaderGroup,
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     TableFooterGroup,
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     TableRow,
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     TableCell,
     #[cfg(feature = "gecko")]
     Ruby,
@@ -160,15 +176,12 @@ impl Display {
     pub const FlowRoot: Self = Self::new(DisplayOutside::Block, DisplayInside::FlowRoot);
     pub const Flex: Self = Self::new(DisplayOutside::Block, DisplayInside::Flex);
     pub const InlineFlex: Self = Self::new(DisplayOutside::Inline, DisplayInside::Flex);
-    #[cfg(feature = "gecko")]
+    #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
     pub const Grid: Self = Self::new(DisplayOutside::Block, DisplayInside::Grid);
-    #[cfg(feature = "gecko")]
+    #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
     pub const InlineGrid: Self = Self::new(DisplayOutside::Inline, DisplayInside::Grid);
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     pub const Table: Self = Self::new(DisplayOutside::Block, DisplayInside::Table);
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     pub const InlineTable: Self = Self::new(DisplayOutside::Inline, DisplayInside::Table);
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     pub const TableCaption: Self = Self::new(DisplayOutside::TableCaption, DisplayInside::Flow);
     #[cfg(feature = "gecko")]
     pub const Ruby: Self = Self::new(DisplayOutside::Inline, DisplayInside::Ruby);
@@ -179,36 +192,29 @@ impl Display {
 
     // Internal table boxes.
 
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     pub const TableRowGroup: Self =
         Self::new(DisplayOutside::InternalTable, DisplayInside::TableRowGroup);
 
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     pub const TableHeaderGroup: Self = Self::new(
         DisplayOutside::InternalTable,
         DisplayInside::TableHeaderGroup,
     );
 
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     pub const TableFooterGroup: Self = Self::new(
         DisplayOutside::InternalTable,
         DisplayInside::TableFooterGroup,
     );
 
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     pub const TableColumn: Self =
         Self::new(DisplayOutside::InternalTable, DisplayInside::TableColumn);
 
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     pub const TableColumnGroup: Self = Self::new(
         DisplayOutside::InternalTable,
         DisplayInside::TableColumnGroup,
     );
 
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     pub const TableRow: Self = Self::new(DisplayOutside::InternalTable, DisplayInside::TableRow);
 
-    #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     pub const TableCell: Self = Self::new(DisplayOutside::InternalTable, DisplayInside::TableCell);
 
     /// Internal ruby boxes.
@@ -328,8 +334,9 @@ impl Display {
     pub fn is_atomic_inline_level(&self) -> bool {
         match *self {
             Display::InlineBlock | Display::InlineFlex => true,
-            #[cfg(any(feature = "servo-layout-2013"))]
             Display::InlineTable => true,
+            #[cfg(feature = "servo-layout-2020")]
+            Display::InlineGrid => true,
             _ => false,
         }
     }
@@ -341,7 +348,7 @@ impl Display {
     pub fn is_item_container(&self) -> bool {
         match self.inside() {
             DisplayInside::Flex => true,
-            #[cfg(feature = "gecko")]
+            #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
             DisplayInside::Grid => true,
             _ => false,
         }
@@ -382,7 +389,6 @@ impl Display {
                 Display::from3(DisplayOutside::Block, inside, self.is_list_item())
             },
             DisplayOutside::Block | DisplayOutside::None => *self,
-            #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
             _ => Display::Block,
         }
     }
@@ -436,13 +442,11 @@ impl ToCss for Display {
             Display::WebkitInlineBox => dest.write_str("-webkit-inline-box"),
             #[cfg(feature = "gecko")]
             Display::MozInlineBox => dest.write_str("-moz-inline-box"),
-            #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
             Display::TableCaption => dest.write_str("table-caption"),
             _ => match (outside, inside) {
-                #[cfg(feature = "gecko")]
+                #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
                 (DisplayOutside::Inline, DisplayInside::Grid) => dest.write_str("inline-grid"),
                 (DisplayOutside::Inline, DisplayInside::Flex) => dest.write_str("inline-flex"),
-                #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
                 (DisplayOutside::Inline, DisplayInside::Table) => dest.write_str("inline-table"),
                 #[cfg(feature = "gecko")]
                 (DisplayOutside::Block, DisplayInside::Ruby) => dest.write_str("block ruby"),
@@ -476,10 +480,9 @@ fn parse_display_inside<'i, 't>(
         "flex" if flexbox_enabled() => DisplayInside::Flex,
         #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
         "flow-root" => DisplayInside::FlowRoot,
-        #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
-        "table" => DisplayInside::Table,
-        #[cfg(feature = "gecko")]
-        "grid" => DisplayInside::Grid,
+        "table" if tables_enabled() => DisplayInside::Table,
+        #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
+        "grid" if grid_enabled() => DisplayInside::Grid,
         #[cfg(feature = "gecko")]
         "ruby" => DisplayInside::Ruby,
     })
@@ -576,28 +579,19 @@ impl Parse for Display {
             #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
             "contents" => Display::Contents,
             "inline-block" => Display::InlineBlock,
-            #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
-            "inline-table" => Display::InlineTable,
+            "inline-table" if tables_enabled() => Display::InlineTable,
             "-webkit-flex" if flexbox_enabled() => Display::Flex,
             "inline-flex" | "-webkit-inline-flex" if flexbox_enabled() => Display::InlineFlex,
-            #[cfg(feature = "gecko")]
-            "inline-grid" => Display::InlineGrid,
-            #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
-            "table-caption" => Display::TableCaption,
-            #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
-            "table-row-group" => Display::TableRowGroup,
-            #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
-            "table-header-group" => Display::TableHeaderGroup,
-            #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
-            "table-footer-group" => Display::TableFooterGroup,
-            #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
-            "table-column" => Display::TableColumn,
-            #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
-            "table-column-group" => Display::TableColumnGroup,
-            #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
-            "table-row" => Display::TableRow,
-            #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
-            "table-cell" => Display::TableCell,
+            #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
+            "inline-grid" if grid_enabled() => Display::InlineGrid,
+            "table-caption" if tables_enabled() => Display::TableCaption,
+            "table-row-group" if tables_enabled() => Display::TableRowGroup,
+            "table-header-group" if tables_enabled() => Display::TableHeaderGroup,
+            "table-footer-group" if tables_enabled() => Display::TableFooterGroup,
+            "table-column" if tables_enabled() => Display::TableColumn,
+            "table-column-group" if tables_enabled() => Display::TableColumnGroup,
+            "table-row" if tables_enabled() => Display::TableRow,
+            "table-cell" if tables_enabled() => Display::TableCell,
             #[cfg(feature = "gecko")]
             "ruby-base" => Display::RubyBase,
             #[cfg(feature = "gecko")]
@@ -1895,6 +1889,12 @@ pub enum BreakBetween {
     Avoid,
     Left,
     Right,
+    #[cfg(feature = "servo-layout-2020")]
+    AvoidPage,
+    #[cfg(feature = "servo-layout-2020")]
+    AvoidColumn,
+    #[cfg(feature = "servo-layout-2020")]
+    Column,
 }
 
 impl BreakBetween {
@@ -1921,6 +1921,11 @@ impl BreakBetween {
                 Err(location
                     .new_custom_error(SelectorParseErrorKind::UnexpectedIdent(ident.clone())))
             },
+            #[cfg(feature = "servo-layout-2020")]
+            BreakBetween::AvoidPage | BreakBetween::AvoidColumn | BreakBetween::Column => {
+                Err(location
+                    .new_custom_error(SelectorParseErrorKind::UnexpectedIdent(ident.clone())))
+            },
         }
     }
 
@@ -1937,6 +1942,8 @@ impl BreakBetween {
             },
             BreakBetween::Page => dest.write_str("always"),
             BreakBetween::Always => Ok(()),
+            #[cfg(feature = "servo-layout-2020")]
+            BreakBetween::AvoidPage | BreakBetween::AvoidColumn | BreakBetween::Column => Ok(()),
         }
     }
 }
@@ -1964,6 +1971,10 @@ impl BreakBetween {
 pub enum BreakWithin {
     Auto,
     Avoid,
+    #[cfg(feature = "servo-layout-2020")]
+    AvoidColumn,
+    #[cfg(feature = "servo-layout-2020")]
+    AvoidPage,
 }
 
 /// The value for the `overflow-x` / `overflow-y` properties.
@@ -57,11 +57,16 @@ fn conic_gradients_enabled() -> bool {
     static_prefs::pref!("layout.css.conic-gradient.enabled")
 }
 
-#[cfg(feature = "servo")]
+#[cfg(all(feature = "servo", not(feature = "servo-layout-2020")))]
 fn conic_gradients_enabled() -> bool {
     false
 }
 
+#[cfg(feature = "servo-layout-2020")]
+fn conic_gradients_enabled() -> bool {
+    true
+}
+
 impl SpecifiedValueInfo for Gradient {
     const SUPPORTED_TYPES: u8 = CssType::GRADIENT;
 
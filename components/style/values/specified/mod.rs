@@ -70,6 +70,7 @@ pub use self::list::MozListReversed;
 pub use self::list::Quotes;
 pub use self::motion::{OffsetPath, OffsetRotate};
 pub use self::outline::OutlineStyle;
+pub use self::page::PageSize;
 pub use self::percentage::Percentage;
 pub use self::position::AspectRatio;
 pub use self::position::{
@@ -120,6 +121,7 @@ pub mod length;
 pub mod list;
 pub mod motion;
 pub mod outline;
+pub mod page;
 pub mod percentage;
 pub mod position;
 pub mod rect;
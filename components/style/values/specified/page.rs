@@ -0,0 +1,119 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Specified types for the page properties.
+
+use crate::parser::{Parse, ParserContext};
+use crate::values::computed::{self, Context, ToComputedValue};
+use crate::values::generics::page::PageSize as GenericPageSize;
+use crate::values::generics::page::{PageOrientation, PaperSize};
+use crate::values::generics::size::Size2D;
+use crate::values::specified::length::NonNegativeLength;
+use cssparser::Parser;
+use style_traits::ParseError;
+
+/// A specified value for the `size` property of page boxes.
+/// https://drafts.csswg.org/css-page-3/#page-size-prop
+#[derive(Clone, Debug, MallocSizeOf, PartialEq, SpecifiedValueInfo, ToCss, ToShmem)]
+pub enum PageSize {
+    /// The width and height of the page.
+    Size(Size2D<NonNegativeLength>),
+    /// A standard paper size, in the given orientation.
+    PaperSize(
+        PaperSize,
+        #[css(skip_if = "PageOrientation::is_portrait")] PageOrientation,
+    ),
+    /// The default page size, in the given orientation.
+    Orientation(PageOrientation),
+    /// The keyword `auto`.
+    Auto,
+}
+
+impl Parse for PageSize {
+    fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        // <length>{1,2} | auto | [ <page-size> || [ portrait | landscape ] ]
+        if let Ok(size) =
+            input.try_parse(|input| Size2D::parse_with(context, input, NonNegativeLength::parse))
+        {
+            return Ok(PageSize::Size(size));
+        }
+        if input
+            .try_parse(|input| input.expect_ident_matching("auto"))
+            .is_ok()
+        {
+            return Ok(PageSize::Auto);
+        }
+        if let Ok(paper_size) = input.try_parse(|input| PaperSize::parse(context, input)) {
+            let orientation = input
+                .try_parse(|input| PageOrientation::parse(context, input))
+                .unwrap_or(PageOrientation::Portrait);
+            return Ok(PageSize::PaperSize(paper_size, orientation));
+        }
+        let orientation = PageOrientation::parse(context, input)?;
+        if let Ok(paper_size) = input.try_parse(|input| PaperSize::parse(context, input)) {
+            return Ok(PageSize::PaperSize(paper_size, orientation));
+        }
+        Ok(PageSize::Orientation(orientation))
+    }
+}
+
+impl ToComputedValue for PageSize {
+    type ComputedValue = computed::PageSize;
+
+    fn to_computed_value(&self, context: &Context) -> Self::ComputedValue {
+        match *self {
+            PageSize::Size(ref size) => GenericPageSize::Size(size.to_computed_value(context)),
+            PageSize::PaperSize(paper_size, orientation) => {
+                GenericPageSize::Size(paper_size_in_px(paper_size, orientation))
+            },
+            PageSize::Orientation(orientation) => GenericPageSize::Orientation(orientation),
+            PageSize::Auto => GenericPageSize::Auto,
+        }
+    }
+
+    fn from_computed_value(computed: &Self::ComputedValue) -> Self {
+        match *computed {
+            GenericPageSize::Size(ref size) => {
+                PageSize::Size(ToComputedValue::from_computed_value(size))
+            },
+            GenericPageSize::Orientation(orientation) => PageSize::Orientation(orientation),
+            GenericPageSize::Auto => PageSize::Auto,
+        }
+    }
+}
+
+/// The size of a standard paper size in the given orientation.
+/// https://drafts.csswg.org/css-page-3/#typedef-page-size-page-size
+fn paper_size_in_px(
+    paper_size: PaperSize,
+    orientation: PageOrientation,
+) -> Size2D<computed::NonNegativeLength> {
+    let (width, height) = match paper_size {
+        PaperSize::A5 => (mm(148.), mm(210.)),
+        PaperSize::A4 => (mm(210.), mm(297.)),
+        PaperSize::A3 => (mm(297.), mm(420.)),
+        PaperSize::B5 => (mm(176.), mm(250.)),
+        PaperSize::B4 => (mm(250.), mm(353.)),
+        PaperSize::JisB5 => (mm(182.), mm(257.)),
+        PaperSize::JisB4 => (mm(257.), mm(364.)),
+        PaperSize::Letter => (inches(8.5), inches(11.)),
+        PaperSize::Legal => (inches(8.5), inches(14.)),
+        PaperSize::Ledger => (inches(11.), inches(17.)),
+    };
+    match orientation {
+        PageOrientation::Portrait => Size2D::new(width, height),
+        PageOrientation::Landscape => Size2D::new(height, width),
+    }
+}
+
+fn mm(value: f32) -> computed::NonNegativeLength {
+    inches(value / 25.4)
+}
+
+fn inches(value: f32) -> computed::NonNegativeLength {
+    computed::NonNegativeLength::new(value * 96.)
+}
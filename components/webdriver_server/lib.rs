@@ -23,11 +23,12 @@ use compositing::ConstellationMsg;
 use crossbeam_channel::{after, unbounded, Receiver, Sender};
 use euclid::{Rect, Size2D};
 use hyper::Method;
-use image::{DynamicImage, ImageFormat, RgbImage};
+use image::{DynamicImage, ImageFormat, RgbImage, RgbaImage};
 use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use keyboard_types::webdriver::send_keys;
 use msg::constellation_msg::{BrowsingContextId, TopLevelBrowsingContextId, TraversalDirection};
+use net_traits::image::base::Image;
 use net_traits::request::Referrer;
 use pixels::PixelFormat;
 use script_traits::webdriver_msg::{LoadStatus, WebDriverCookieError, WebDriverFrameId};
@@ -89,6 +90,11 @@ fn extension_routes() -> Vec<(Method, &'static str, ServoExtensionRoute)> {
             "/session/{sessionId}/servo/prefs/reset",
             ServoExtensionRoute::ResetPrefs,
         ),
+        (
+            Method::GET,
+            "/session/{sessionId}/servo/screenshot/full",
+            ServoExtensionRoute::TakeFullPageScreenshot,
+        ),
     ];
 }
 
@@ -195,6 +201,7 @@ enum ServoExtensionRoute {
     GetPrefs,
     SetPrefs,
     ResetPrefs,
+    TakeFullPageScreenshot,
 }
 
 impl WebDriverExtensionRoute for ServoExtensionRoute {
@@ -218,6 +225,9 @@ impl WebDriverExtensionRoute for ServoExtensionRoute {
                 let parameters: GetPrefsParameters = serde_json::from_value(body_data.clone())?;
                 ServoExtensionCommand::ResetPrefs(parameters)
             },
+            ServoExtensionRoute::TakeFullPageScreenshot => {
+                ServoExtensionCommand::TakeFullPageScreenshot
+            },
         };
         Ok(WebDriverCommand::Extension(command))
     }
@@ -228,6 +238,7 @@ enum ServoExtensionCommand {
     GetPrefs(GetPrefsParameters),
     SetPrefs(SetPrefsParameters),
     ResetPrefs(GetPrefsParameters),
+    TakeFullPageScreenshot,
 }
 
 impl WebDriverExtensionCommand for ServoExtensionCommand {
@@ -236,6 +247,7 @@ impl WebDriverExtensionCommand for ServoExtensionCommand {
             ServoExtensionCommand::GetPrefs(ref x) => serde_json::to_value(x).ok(),
             ServoExtensionCommand::SetPrefs(ref x) => serde_json::to_value(x).ok(),
             ServoExtensionCommand::ResetPrefs(ref x) => serde_json::to_value(x).ok(),
+            ServoExtensionCommand::TakeFullPageScreenshot => None,
         }
     }
 }
@@ -1537,6 +1549,16 @@ impl Handler {
     }
 
     fn take_screenshot(&self, rect: Option<Rect<f32, CSSPixel>>) -> WebDriverResult<String> {
+        let top_level_browsing_context_id = self.session()?.top_level_browsing_context_id;
+        self.capture_screenshot(|sender| {
+            WebDriverCommandMsg::TakeScreenshot(top_level_browsing_context_id, rect, sender)
+        })
+    }
+
+    fn capture_screenshot<F>(&self, command: F) -> WebDriverResult<String>
+    where
+        F: Fn(IpcSender<Option<Image>>) -> WebDriverCommandMsg,
+    {
         let mut img = None;
 
         let interval = 1000;
@@ -1545,11 +1567,7 @@ impl Handler {
         for _ in 0..iterations {
             let (sender, receiver) = ipc::channel().unwrap();
 
-            let cmd_msg = WebDriverCommandMsg::TakeScreenshot(
-                self.session()?.top_level_browsing_context_id,
-                rect,
-                sender,
-            );
+            let cmd_msg = command(sender);
             self.constellation_chan
                 .send(ConstellationMsg::WebDriverCommand(cmd_msg))
                 .unwrap();
@@ -1572,18 +1590,19 @@ impl Handler {
             },
         };
 
-        // The compositor always sends RGB pixels.
-        assert_eq!(
-            img.format,
-            PixelFormat::RGB8,
-            "Unexpected screenshot pixel format"
-        );
-
-        let rgb = RgbImage::from_raw(img.width, img.height, img.bytes.to_vec()).unwrap();
+        // The compositor sends RGB pixels for viewport screenshots and RGBA pixels for
+        // full page ones.
+        let image = match img.format {
+            PixelFormat::RGB8 => DynamicImage::ImageRgb8(
+                RgbImage::from_raw(img.width, img.height, img.bytes.to_vec()).unwrap(),
+            ),
+            PixelFormat::RGBA8 => DynamicImage::ImageRgba8(
+                RgbaImage::from_raw(img.width, img.height, img.bytes.to_vec()).unwrap(),
+            ),
+            format => panic!("Unexpected screenshot pixel format {:?}", format),
+        };
         let mut png_data = Vec::new();
-        DynamicImage::ImageRgb8(rgb)
-            .write_to(&mut png_data, ImageFormat::Png)
-            .unwrap();
+        image.write_to(&mut png_data, ImageFormat::Png).unwrap();
 
         Ok(base64::encode(&png_data))
     }
@@ -1596,6 +1615,17 @@ impl Handler {
         )))
     }
 
+    fn handle_take_full_page_screenshot(&self) -> WebDriverResult<WebDriverResponse> {
+        let top_level_browsing_context_id = self.session()?.top_level_browsing_context_id;
+        let encoded = self.capture_screenshot(|sender| {
+            WebDriverCommandMsg::TakeFullPageScreenshot(top_level_browsing_context_id, sender)
+        })?;
+
+        Ok(WebDriverResponse::Generic(ValueResponse(
+            serde_json::to_value(encoded)?,
+        )))
+    }
+
     fn handle_take_element_screenshot(
         &self,
         element: &WebElement,
@@ -1769,6 +1799,9 @@ impl WebDriverHandler<ServoExtensionRoute> for Handler {
                 ServoExtensionCommand::GetPrefs(ref x) => self.handle_get_prefs(x),
                 ServoExtensionCommand::SetPrefs(ref x) => self.handle_set_prefs(x),
                 ServoExtensionCommand::ResetPrefs(ref x) => self.handle_reset_prefs(x),
+                ServoExtensionCommand::TakeFullPageScreenshot => {
+                    self.handle_take_full_page_screenshot()
+                },
             },
             _ => Err(WebDriverError::new(
                 ErrorStatus::UnsupportedOperation,
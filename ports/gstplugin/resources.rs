@@ -36,6 +36,9 @@ fn filename(file: Resource) -> &'static str {
         Resource::RippyPNG => "rippy.png",
         Resource::MediaControlsCSS => "media-controls.css",
         Resource::MediaControlsJS => "media-controls.js",
+        Resource::EnglishHyphenationPatterns => "hyphenation/hyph-en-us.txt",
+        Resource::FrenchHyphenationPatterns => "hyphenation/hyph-fr.txt",
+        Resource::GermanHyphenationPatterns => "hyphenation/hyph-de-1996.txt",
     }
 }
 
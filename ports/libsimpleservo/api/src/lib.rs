@@ -12,11 +12,15 @@ pub use servo::embedder_traits::{
     ContextMenuResult, MediaSessionPlaybackState, PermissionPrompt, PermissionRequest, PromptResult,
 };
 pub use servo::msg::constellation_msg::InputMethodType;
+pub use servo::profile_traits::mem::{MemoryReportSnapshot, ReportDiff};
 pub use servo::script_traits::{MediaSessionActionType, MouseButton};
+pub use servo::style_traits::CSSPixel;
 pub use servo::webrender_api::units::DeviceIntRect;
 
+use cookie::Cookie;
 use getopts::Options;
-use ipc_channel::ipc::IpcSender;
+use hyper_serde::Serde;
+use ipc_channel::ipc::{self, IpcSender};
 use servo::compositing::windowing::{
     AnimationState, EmbedderCoordinates, EmbedderMethods, MouseWindowEvent, WindowEvent,
     WindowMethods,
@@ -24,11 +28,13 @@ use servo::compositing::windowing::{
 use servo::config::prefs::pref_map;
 use servo::embedder_traits::resources::{self, Resource, ResourceReaderMethods};
 use servo::embedder_traits::{
-    EmbedderMsg, EmbedderProxy, MediaSessionEvent, PromptDefinition, PromptOrigin,
+    EmbedderMsg, EmbedderProxy, MediaSessionEvent, MessageToPage, PromptDefinition, PromptOrigin,
 };
 use servo::euclid::{Point2D, Rect, Scale, Size2D, Vector2D};
 use servo::keyboard_types::{Key, KeyState, KeyboardEvent};
 use servo::msg::constellation_msg::TraversalDirection;
+use servo::net_traits::storage_thread::StorageType;
+use servo::net_traits::SiteDataRequest;
 use servo::script_traits::{TouchEventType, TouchId};
 use servo::servo_config::{opts, pref};
 use servo::servo_url::ServoUrl;
@@ -37,7 +43,7 @@ use servo::webrender_api::ScrollLocation;
 use servo::webrender_surfman::WebrenderSurfman;
 use servo::{self, gl, BrowserId, Servo};
 use servo_media::player::context as MediaPlayerContext;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::mem;
 use std::os::raw::c_void;
@@ -45,6 +51,7 @@ use std::path::PathBuf;
 use std::rc::Rc;
 use surfman::Adapter;
 use surfman::Connection;
+use surfman::GLApi;
 use surfman::SurfaceType;
 
 thread_local! {
@@ -150,6 +157,39 @@ pub trait HostTrait {
     fn on_devtools_started(&self, port: Result<u16, ()>, token: String);
     /// Called when we get a panic message from constellation
     fn on_panic(&self, reason: String, backtrace: Option<String>);
+    /// Called when a page posts a message through `window.embedder`, with the message
+    /// as JSON, or `None` if it has no JSON representation.
+    fn on_message_from_page(&self, url: String, message: Option<String>);
+}
+
+/// The pixels of a frame captured by `ServoGlue::capture_frame` or
+/// `ServoGlue::capture_full_page`, in rows of RGBA
+/// bytes from top to bottom.
+pub struct RgbaFrame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// Compare two memory reports serialized as JSON by `ServoGlue::memory_report_json`,
+/// and return the paths whose size changed as a JSON array of `ReportDiff`, largest
+/// changes first.
+pub fn diff_memory_reports_json(before: &str, after: &str) -> Result<String, &'static str> {
+    let before: MemoryReportSnapshot =
+        serde_json::from_str(before).map_err(|_| "Can't parse memory report")?;
+    let after: MemoryReportSnapshot =
+        serde_json::from_str(after).map_err(|_| "Can't parse memory report")?;
+    serde_json::to_string(&before.diff(&after)).map_err(|_| "Can't serialize memory report diff")
+}
+
+/// The kinds of site data removed by `ServoGlue::clear_site_data`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SiteDataTypes {
+    pub cookies: bool,
+    pub local_storage: bool,
+    pub session_storage: bool,
+    pub hsts: bool,
+    pub auth_cache: bool,
 }
 
 pub struct ServoGlue {
@@ -179,6 +219,10 @@ pub fn is_uri_valid(url: &str) -> bool {
     ServoUrl::parse(url).is_ok()
 }
 
+fn parse_url(url: &str) -> Result<ServoUrl, &'static str> {
+    ServoUrl::parse(url).map_err(|_| "Can't parse URL")
+}
+
 /// Retrieve a snapshot of the current preferences
 pub fn get_prefs() -> HashMap<String, (PrefValue, bool)> {
     pref_map()
@@ -225,21 +269,7 @@ pub fn init(
     waker: Box<dyn EventLoopWaker>,
     callbacks: Box<dyn HostTrait>,
 ) -> Result<(), &'static str> {
-    resources::set(Box::new(ResourceReaderInstance::new()));
-
-    if let Some(prefs) = init_opts.prefs {
-        add_user_prefs(prefs);
-    }
-
-    let mut args = mem::replace(&mut init_opts.args, vec![]);
-    // opts::from_cmdline_args expects the first argument to be the binary name.
-    args.insert(0, "servo".to_string());
-    opts::from_cmdline_args(Options::new(), &args);
-
-    let pref_url = ServoUrl::parse(&pref!(shell.homepage)).ok();
-    let blank_url = ServoUrl::parse("about:blank").ok();
-
-    let url = pref_url.or(blank_url).unwrap();
+    let url = init_prefs_and_opts(&mut init_opts);
 
     gl.clear_color(1.0, 1.0, 1.0, 1.0);
     gl.clear(gl::COLOR_BUFFER_BIT);
@@ -263,13 +293,90 @@ pub fn init(
     let webrender_surfman = WebrenderSurfman::create(&connection, &adapter, surface_type)
         .or(Err("Failed to create surface manager"))?;
 
+    start(
+        init_opts,
+        url,
+        webrender_surfman,
+        gl,
+        false,
+        waker,
+        callbacks,
+    )
+}
+
+/// Initialize Servo without a native widget or GL context from the embedder. Servo
+/// renders with a software GL implementation into an offscreen surface of the size of
+/// the framebuffer, whose pixels can be read back with `ServoGlue::capture_frame`.
+/// `init_opts.native_widget` is ignored.
+pub fn init_headless(
+    mut init_opts: InitOptions,
+    waker: Box<dyn EventLoopWaker>,
+    callbacks: Box<dyn HostTrait>,
+) -> Result<(), &'static str> {
+    let url = init_prefs_and_opts(&mut init_opts);
+
+    let connection = Connection::new().or(Err("Failed to create connection"))?;
+    let adapter = connection
+        .create_software_adapter()
+        .or(Err("Failed to create software adapter"))?;
+    let size = init_opts.coordinates.framebuffer.to_untyped();
+    let surface_type = SurfaceType::Generic { size };
+    let webrender_surfman = WebrenderSurfman::create(&connection, &adapter, surface_type)
+        .or(Err("Failed to create surface manager"))?;
+
+    let gl = match webrender_surfman.connection().gl_api() {
+        GLApi::GL => unsafe { gl::GlFns::load_with(|s| webrender_surfman.get_proc_address(s)) },
+        GLApi::GLES => unsafe { gl::GlesFns::load_with(|s| webrender_surfman.get_proc_address(s)) },
+    };
+
+    start(
+        init_opts,
+        url,
+        webrender_surfman,
+        gl,
+        true,
+        waker,
+        callbacks,
+    )
+}
+
+/// Apply the preferences and command line arguments of `init_opts`, and return the
+/// URL of the first browser.
+fn init_prefs_and_opts(init_opts: &mut InitOptions) -> ServoUrl {
+    resources::set(Box::new(ResourceReaderInstance::new()));
+
+    if let Some(prefs) = init_opts.prefs.take() {
+        add_user_prefs(prefs);
+    }
+
+    let mut args = mem::replace(&mut init_opts.args, vec![]);
+    // opts::from_cmdline_args expects the first argument to be the binary name.
+    args.insert(0, "servo".to_string());
+    opts::from_cmdline_args(Options::new(), &args);
+
+    let pref_url = ServoUrl::parse(&pref!(shell.homepage)).ok();
+    let blank_url = ServoUrl::parse("about:blank").ok();
+
+    pref_url.or(blank_url).unwrap()
+}
+
+fn start(
+    init_opts: InitOptions,
+    url: ServoUrl,
+    webrender_surfman: WebrenderSurfman,
+    gl: Rc<dyn gl::Gl>,
+    headless: bool,
+    waker: Box<dyn EventLoopWaker>,
+    callbacks: Box<dyn HostTrait>,
+) -> Result<(), &'static str> {
     let window_callbacks = Rc::new(ServoWindowCallbacks {
         host_callbacks: callbacks,
         coordinates: RefCell::new(init_opts.coordinates),
-        density: init_opts.density,
+        density: Cell::new(init_opts.density),
         gl_context_pointer: init_opts.gl_context_pointer,
         native_display_pointer: init_opts.native_display_pointer,
         webrender_surfman,
+        headless,
     });
 
     let embedder_callbacks = Box::new(ServoEmbedderCallbacks {
@@ -364,6 +471,13 @@ impl ServoGlue {
         self.process_event(event)
     }
 
+    /// Purge caches because the system is running low on memory.
+    pub fn memory_pressure(&mut self) -> Result<(), &'static str> {
+        info!("memory_pressure");
+        let event = WindowEvent::MemoryPressure;
+        self.process_event(event)
+    }
+
     /// Reload the page.
     pub fn reload(&mut self) -> Result<(), &'static str> {
         info!("reload");
@@ -403,10 +517,54 @@ impl ServoGlue {
     /// Let Servo know that the window has been resized.
     pub fn resize(&mut self, coordinates: Coordinates) -> Result<(), &'static str> {
         info!("resize");
+        if self.callbacks.headless {
+            // Nothing else resizes the offscreen surface.
+            self.callbacks
+                .webrender_surfman
+                .resize(coordinates.framebuffer.to_untyped())
+                .or(Err("Failed to resize surface"))?;
+        }
         *self.callbacks.coordinates.borrow_mut() = coordinates;
         self.process_event(WindowEvent::Resize)
     }
 
+    /// Change the number of device pixels per CSS pixel.
+    pub fn set_density(&mut self, density: f32) -> Result<(), &'static str> {
+        info!("set_density");
+        self.callbacks.density.set(density);
+        self.process_event(WindowEvent::Resize)
+    }
+
+    /// Composite and return the RGBA pixels of the viewport, or of a rect of it in CSS
+    /// pixels. Returns `None` until the page is loaded, laid out and not animating;
+    /// embedders typically try again on `on_load_ended`, `on_animating_changed` or their
+    /// next event loop wakeup.
+    pub fn capture_frame(
+        &mut self,
+        rect: Option<Rect<f32, CSSPixel>>,
+    ) -> Result<Option<RgbaFrame>, &'static str> {
+        debug!("capture_frame");
+        self.perform_updates()?;
+        Ok(self.servo.capture_frame(rect).map(|image| RgbaFrame {
+            width: image.width,
+            height: image.height,
+            pixels: image.bytes.to_vec(),
+        }))
+    }
+
+    /// Capture the RGBA pixels of the whole scrollable area of the page, in device
+    /// pixels. The page is scrolled over one viewport at a time while capturing and
+    /// scrolled back afterwards. Returns `None` when `capture_frame` would.
+    pub fn capture_full_page(&mut self) -> Result<Option<RgbaFrame>, &'static str> {
+        debug!("capture_full_page");
+        self.perform_updates()?;
+        Ok(self.servo.capture_full_page().map(|image| RgbaFrame {
+            width: image.width,
+            height: image.height,
+            pixels: image.bytes.to_vec(),
+        }))
+    }
+
     /// Start scrolling.
     /// x/y are scroll coordinates.
     /// dx/dy are scroll deltas.
@@ -565,6 +723,115 @@ impl ServoGlue {
         self.process_event(WindowEvent::IMEDismissed)
     }
 
+    /// Post a message in JSON to the `window.embedder` channel of the current page.
+    pub fn post_message_to_page(&mut self, message: String) -> Result<(), &'static str> {
+        info!("post_message_to_page");
+        let browser_id = self.get_browser_id()?;
+        let message = MessageToPage::Json(message);
+        self.process_event(WindowEvent::PostMessageToPage(browser_id, None, message))
+    }
+
+    /// Collect the reports of every memory reporter. This blocks until all the reporters
+    /// have replied.
+    pub fn memory_report(&mut self) -> Result<MemoryReportSnapshot, &'static str> {
+        info!("memory_report");
+        let (sender, receiver) = ipc::channel().map_err(|_| "Can't create IPC channel")?;
+        self.servo.request_memory_report(sender);
+        receiver.recv().map_err(|_| "Can't get memory report")
+    }
+
+    /// Collect the reports of every memory reporter, serialized as JSON.
+    pub fn memory_report_json(&mut self) -> Result<String, &'static str> {
+        let snapshot = self.memory_report()?;
+        serde_json::to_string(&snapshot).map_err(|_| "Can't serialize memory report")
+    }
+
+    /// Retrieve the cookies of the site of `url`, or every cookie, as `Set-Cookie`
+    /// header values. This blocks until the resource thread replies.
+    pub fn get_cookies(&mut self, url: Option<&str>) -> Result<Vec<String>, &'static str> {
+        info!("get_cookies");
+        let url = url.map(parse_url).transpose()?;
+        let browser_id = self.get_browser_id()?;
+        let (sender, receiver) = ipc::channel().map_err(|_| "Can't create IPC channel")?;
+        // The request needs to reach the constellation even in batch mode.
+        self.events.push(WindowEvent::SiteData(
+            browser_id,
+            SiteDataRequest::GetCookies(url, sender),
+        ));
+        self.perform_updates()?;
+        let cookies = receiver.recv().map_err(|_| "Can't get cookies")?;
+        Ok(cookies
+            .into_iter()
+            .map(|cookie| cookie.into_inner().to_string())
+            .collect())
+    }
+
+    /// Store a cookie, given as a `Set-Cookie` header value, as if it was set by a
+    /// response from `url`.
+    pub fn set_cookie(&mut self, url: &str, cookie: &str) -> Result<(), &'static str> {
+        info!("set_cookie");
+        let url = parse_url(url)?;
+        let cookie = Cookie::parse(cookie.to_owned()).map_err(|_| "Can't parse cookie")?;
+        let browser_id = self.get_browser_id()?;
+        let request = SiteDataRequest::SetCookie(url, Serde(cookie));
+        self.process_event(WindowEvent::SiteData(browser_id, request))
+    }
+
+    /// Remove the cookies named `name` that would be sent to `url`.
+    pub fn delete_cookie(&mut self, url: &str, name: &str) -> Result<(), &'static str> {
+        info!("delete_cookie");
+        let url = parse_url(url)?;
+        let browser_id = self.get_browser_id()?;
+        let request = SiteDataRequest::DeleteCookie(url, name.to_owned());
+        self.process_event(WindowEvent::SiteData(browser_id, request))
+    }
+
+    /// Remove the site data of `url`, or of every site. Cookies are removed for the
+    /// whole registrable domain, the other kinds of data for the origin of `url`.
+    pub fn clear_site_data(
+        &mut self,
+        url: Option<&str>,
+        types: SiteDataTypes,
+    ) -> Result<(), &'static str> {
+        info!("clear_site_data");
+        let url = url.map(parse_url).transpose()?;
+        let browser_id = self.get_browser_id()?;
+        let mut requests = vec![];
+        if types.cookies {
+            requests.push(SiteDataRequest::ClearCookies(url.clone()));
+        }
+        if types.local_storage {
+            requests.push(SiteDataRequest::ClearStorage(
+                url.clone(),
+                StorageType::Local,
+            ));
+        }
+        if types.session_storage {
+            requests.push(SiteDataRequest::ClearStorage(
+                url.clone(),
+                StorageType::Session,
+            ));
+        }
+        if types.hsts {
+            // A URL without a host has no HSTS entries, don't clear every host's.
+            match url {
+                Some(ref url) => {
+                    if let Some(host) = url.host_str() {
+                        requests.push(SiteDataRequest::ClearHstsEntries(Some(host.to_owned())));
+                    }
+                },
+                None => requests.push(SiteDataRequest::ClearHstsEntries(None)),
+            }
+        }
+        if types.auth_cache {
+            requests.push(SiteDataRequest::ClearAuthCache(url.clone()));
+        }
+        for request in requests {
+            self.process_event(WindowEvent::SiteData(browser_id, request))?;
+        }
+        Ok(())
+    }
+
     pub fn on_context_menu_closed(
         &mut self,
         result: ContextMenuResult,
@@ -759,6 +1026,11 @@ impl ServoGlue {
                 EmbedderMsg::Panic(reason, backtrace) => {
                     self.callbacks.host_callbacks.on_panic(reason, backtrace);
                 },
+                EmbedderMsg::MessageFromPage(_pipeline_id, url, message) => {
+                    self.callbacks
+                        .host_callbacks
+                        .on_message_from_page(url.into_string(), message.json);
+                },
                 EmbedderMsg::Status(..) |
                 EmbedderMsg::SelectFiles(..) |
                 EmbedderMsg::MoveTo(..) |
@@ -768,7 +1040,9 @@ impl ServoGlue {
                 EmbedderMsg::NewFavicon(..) |
                 EmbedderMsg::HeadParsed |
                 EmbedderMsg::SetFullscreenState(..) |
-                EmbedderMsg::ReportProfile(..) => {},
+                EmbedderMsg::ReportProfile(..) |
+                EmbedderMsg::ReportHangProfile(..) |
+                EmbedderMsg::CrashReport(..) => {},
             }
         }
         Ok(())
@@ -785,10 +1059,12 @@ struct ServoEmbedderCallbacks {
 struct ServoWindowCallbacks {
     host_callbacks: Box<dyn HostTrait>,
     coordinates: RefCell<Coordinates>,
-    density: f32,
+    density: Cell<f32>,
     gl_context_pointer: Option<*const c_void>,
     native_display_pointer: Option<*const c_void>,
     webrender_surfman: WebrenderSurfman,
+    /// Whether Servo renders to an offscreen surface, see `init_headless`.
+    headless: bool,
 }
 
 impl EmbedderMethods for ServoEmbedderCallbacks {
@@ -901,7 +1177,7 @@ impl WindowMethods for ServoWindowCallbacks {
             window: (coords.viewport.size, Point2D::new(0, 0)),
             screen: coords.viewport.size,
             screen_avail: coords.viewport.size,
-            hidpi_factor: Scale::new(self.density),
+            hidpi_factor: Scale::new(self.density.get()),
         }
     }
 
@@ -959,6 +1235,15 @@ impl ResourceReaderMethods for ResourceReaderInstance {
             Resource::MediaControlsJS => {
                 &include_bytes!("../../../../resources/media-controls.js")[..]
             },
+            Resource::EnglishHyphenationPatterns => {
+                &include_bytes!("../../../../resources/hyphenation/hyph-en-us.txt")[..]
+            },
+            Resource::FrenchHyphenationPatterns => {
+                &include_bytes!("../../../../resources/hyphenation/hyph-fr.txt")[..]
+            },
+            Resource::GermanHyphenationPatterns => {
+                &include_bytes!("../../../../resources/hyphenation/hyph-de-1996.txt")[..]
+            },
         })
     }
 
@@ -15,17 +15,20 @@ mod vslogger;
 
 #[cfg(not(target_os = "windows"))]
 use env_logger;
+use euclid::{Point2D, Rect, Size2D};
 use keyboard_types::Key;
 use log::LevelFilter;
 use simpleservo::{self, gl_glue, ServoGlue, SERVO};
 use simpleservo::{
     ContextMenuResult, Coordinates, DeviceIntRect, EventLoopWaker, HostTrait, InitOptions,
     InputMethodType, MediaSessionActionType, MediaSessionPlaybackState, MouseButton, PromptResult,
+    SiteDataTypes,
 };
 use std::ffi::{CStr, CString};
 #[cfg(target_os = "windows")]
 use std::mem;
 use std::os::raw::{c_char, c_uint, c_void};
+use std::ptr;
 use std::slice;
 use std::str::FromStr;
 use std::sync::{Mutex, RwLock};
@@ -225,6 +228,8 @@ pub struct CHostCallbacks {
     pub show_context_menu:
         extern "C" fn(title: *const c_char, items_list: *const *const c_char, items_size: u32),
     pub on_log_output: extern "C" fn(buffer: *const c_char, buffer_length: u32),
+    /// `message` is the JSON of the message, or null if it has no JSON representation.
+    pub on_message_from_page: extern "C" fn(url: *const c_char, message: *const c_char),
 }
 
 /// Servo options
@@ -326,6 +331,28 @@ impl CMediaSessionActionType {
     }
 }
 
+/// The kinds of site data removed by `clear_site_data`
+#[repr(C)]
+pub struct CSiteDataTypes {
+    pub cookies: bool,
+    pub local_storage: bool,
+    pub session_storage: bool,
+    pub hsts: bool,
+    pub auth_cache: bool,
+}
+
+impl CSiteDataTypes {
+    pub fn convert(&self) -> SiteDataTypes {
+        SiteDataTypes {
+            cookies: self.cookies,
+            local_storage: self.local_storage,
+            session_storage: self.session_storage,
+            hsts: self.hsts,
+            auth_cache: self.auth_cache,
+        }
+    }
+}
+
 #[repr(C)]
 pub enum CContextMenuResult {
     Ignored,
@@ -381,7 +408,7 @@ fn init_logger(_modules: &[*const c_char], _level: LevelFilter) {
 
 unsafe fn init(
     opts: CInitOptions,
-    gl: gl_glue::ServoGl,
+    gl: Option<gl_glue::ServoGl>,
     gl_context: Option<*const c_void>,
     display: Option<*const c_void>,
     wakeup: extern "C" fn(),
@@ -469,7 +496,10 @@ unsafe fn init(
     let wakeup = Box::new(WakeupCallback::new(wakeup));
     let callbacks = Box::new(HostCallbacks::new(callbacks));
 
-    simpleservo::init(opts, gl, wakeup, callbacks).unwrap();
+    match gl {
+        Some(gl) => simpleservo::init(opts, gl, wakeup, callbacks).unwrap(),
+        None => simpleservo::init_headless(opts, wakeup, callbacks).unwrap(),
+    }
 }
 
 #[cfg(target_os = "windows")]
@@ -483,7 +513,7 @@ pub extern "C" fn init_with_egl(
     unsafe {
         init(
             opts,
-            gl.gl_wrapper,
+            Some(gl.gl_wrapper),
             Some(gl.gl_context),
             Some(gl.display),
             wakeup,
@@ -500,7 +530,18 @@ pub extern "C" fn init_with_gl(
     callbacks: CHostCallbacks,
 ) {
     let gl = gl_glue::gl::init().unwrap();
-    unsafe { init(opts, gl, None, None, wakeup, callbacks) }
+    unsafe { init(opts, Some(gl), None, None, wakeup, callbacks) }
+}
+
+/// Initialize Servo with software rendering to an offscreen surface, see
+/// `capture_frame`. `opts.native_widget` is ignored.
+#[no_mangle]
+pub extern "C" fn init_headless(
+    opts: CInitOptions,
+    wakeup: extern "C" fn(),
+    callbacks: CHostCallbacks,
+) {
+    unsafe { init(opts, None, None, None, wakeup, callbacks) }
 }
 
 #[no_mangle]
@@ -567,6 +608,12 @@ pub extern "C" fn clear_cache() {
     call(|s| s.clear_cache())
 }
 
+#[no_mangle]
+pub extern "C" fn memory_pressure() {
+    debug!("memory_pressure");
+    call(|s| s.memory_pressure())
+}
+
 #[no_mangle]
 pub extern "C" fn reload() {
     debug!("reload");
@@ -728,6 +775,158 @@ pub extern "C" fn ime_dismissed() {
     call(|s| s.ime_dismissed());
 }
 
+#[no_mangle]
+pub extern "C" fn post_message_to_page(message: *const c_char) {
+    debug!("post_message_to_page");
+    let message = unsafe { CStr::from_ptr(message) };
+    let message = message.to_str().expect("Can't read string").to_owned();
+    call(|s| s.post_message_to_page(message));
+}
+
+#[no_mangle]
+pub extern "C" fn set_density(density: f32) {
+    debug!("set_density");
+    call(|s| s.set_density(density));
+}
+
+/// A rect in CSS pixels
+#[repr(C)]
+pub struct CRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl CRect {
+    fn to_rect<U>(&self) -> Rect<f32, U> {
+        Rect::new(
+            Point2D::new(self.x, self.y),
+            Size2D::new(self.width, self.height),
+        )
+    }
+}
+
+/// Call `callback` with the RGBA pixels of the viewport, or of `rect` if it isn't null,
+/// and return true. Return false without calling it if the page isn't ready to be
+/// captured yet. The pixels are only valid during the callback.
+#[no_mangle]
+pub extern "C" fn capture_frame(
+    rect: *const CRect,
+    callback: extern "C" fn(pixels: *const u8, width: u32, height: u32),
+) -> bool {
+    debug!("capture_frame");
+    let rect = unsafe { rect.as_ref() }.map(CRect::to_rect);
+    match call(|s| s.capture_frame(rect)) {
+        Some(frame) => {
+            callback(frame.pixels.as_ptr(), frame.width, frame.height);
+            true
+        },
+        None => false,
+    }
+}
+
+/// Like `capture_frame`, but with the whole scrollable area of the page.
+#[no_mangle]
+pub extern "C" fn capture_full_page(
+    callback: extern "C" fn(pixels: *const u8, width: u32, height: u32),
+) -> bool {
+    debug!("capture_full_page");
+    match call(|s| s.capture_full_page()) {
+        Some(frame) => {
+            callback(frame.pixels.as_ptr(), frame.width, frame.height);
+            true
+        },
+        None => false,
+    }
+}
+
+fn optional_str<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        None
+    } else {
+        let s = unsafe { CStr::from_ptr(ptr) };
+        Some(s.to_str().expect("Can't read string"))
+    }
+}
+
+/// Call `callback` with the reports of every memory reporter, serialized as JSON.
+#[no_mangle]
+pub extern "C" fn memory_report(callback: extern "C" fn(json: *const c_char)) -> bool {
+    debug!("memory_report");
+    call(|s| Ok(s.memory_report_json())).map_or(false, |json| {
+        let json = CString::new(json).expect("Can't create string");
+        callback(json.as_ptr());
+        true
+    })
+}
+
+/// Compare two JSON memory reports from `memory_report`, and call `callback` with the
+/// paths whose size changed, serialized as JSON.
+#[no_mangle]
+pub extern "C" fn diff_memory_reports(
+    before: *const c_char,
+    after: *const c_char,
+    callback: extern "C" fn(json: *const c_char),
+) -> bool {
+    debug!("diff_memory_reports");
+    let before = unsafe { CStr::from_ptr(before) };
+    let before = before.to_str().expect("Can't read string");
+    let after = unsafe { CStr::from_ptr(after) };
+    let after = after.to_str().expect("Can't read string");
+    simpleservo::diff_memory_reports_json(before, after).map_or(false, |json| {
+        let json = CString::new(json).expect("Can't create string");
+        callback(json.as_ptr());
+        true
+    })
+}
+
+/// Call `callback` with each cookie of the site of `url` (or of every site if `url`
+/// is null), formatted as a `Set-Cookie` header value.
+#[no_mangle]
+pub extern "C" fn get_cookies(
+    url: *const c_char,
+    callback: extern "C" fn(cookie: *const c_char),
+) -> bool {
+    debug!("get_cookies");
+    let url = optional_str(url);
+    call(|s| Ok(s.get_cookies(url))).map_or(false, |cookies| {
+        for cookie in cookies {
+            let cookie = CString::new(cookie).expect("Can't create string");
+            callback(cookie.as_ptr());
+        }
+        true
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn set_cookie(url: *const c_char, cookie: *const c_char) -> bool {
+    debug!("set_cookie");
+    let url = unsafe { CStr::from_ptr(url) };
+    let url = url.to_str().expect("Can't read string");
+    let cookie = unsafe { CStr::from_ptr(cookie) };
+    let cookie = cookie.to_str().expect("Can't read string");
+    call(|s| Ok(s.set_cookie(url, cookie).is_ok()))
+}
+
+#[no_mangle]
+pub extern "C" fn delete_cookie(url: *const c_char, name: *const c_char) -> bool {
+    debug!("delete_cookie");
+    let url = unsafe { CStr::from_ptr(url) };
+    let url = url.to_str().expect("Can't read string");
+    let name = unsafe { CStr::from_ptr(name) };
+    let name = name.to_str().expect("Can't read string");
+    call(|s| Ok(s.delete_cookie(url, name).is_ok()))
+}
+
+/// Remove the site data of `url`, or of every site if `url` is null.
+#[no_mangle]
+pub extern "C" fn clear_site_data(url: *const c_char, types: CSiteDataTypes) -> bool {
+    debug!("clear_site_data");
+    let url = optional_str(url);
+    call(|s| Ok(s.clear_site_data(url, types.convert()).is_ok()))
+}
+
 pub struct WakeupCallback(extern "C" fn());
 
 impl WakeupCallback {
@@ -909,6 +1108,16 @@ impl HostTrait for HostCallbacks {
         report_panic(&reason, details);
     }
 
+    fn on_message_from_page(&self, url: String, message: Option<String>) {
+        debug!("on_message_from_page");
+        let url = CString::new(url).expect("Can't create string");
+        let message = message.map(|message| CString::new(message).expect("Can't create string"));
+        let message_ptr = message
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr());
+        (self.0.on_message_from_page)(url.as_ptr(), message_ptr);
+    }
+
     fn on_devtools_started(&self, port: Result<u16, ()>, token: String) {
         let token = CString::new(token).expect("Can't create string");
         match port {
@@ -943,3 +1152,31 @@ impl HostTrait for HostCallbacks {
         (self.0.show_context_menu)(title_ptr, items.as_ptr(), items_size);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{optional_str, CRect};
+    use euclid::default::Rect;
+    use euclid::{Point2D, Size2D};
+    use std::ffi::CString;
+
+    #[test]
+    fn test_crect_to_rect() {
+        let rect = CRect {
+            x: 10.,
+            y: 20.5,
+            width: 300.,
+            height: 150.25,
+        };
+        let rect: Rect<f32> = rect.to_rect();
+        assert_eq!(rect.origin, Point2D::new(10., 20.5));
+        assert_eq!(rect.size, Size2D::new(300., 150.25));
+    }
+
+    #[test]
+    fn test_optional_str() {
+        assert_eq!(optional_str(std::ptr::null()), None);
+        let string = CString::new("about:blank").unwrap();
+        assert_eq!(optional_str(string.as_ptr()), Some("about:blank"));
+    }
+}
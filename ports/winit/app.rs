@@ -79,8 +79,17 @@ impl App {
         let browser = Browser::new(window.clone());
 
         let mut servo = Servo::new(embedder, window.clone(), user_agent);
-        let browser_id = BrowserId::new();
-        servo.handle_events(vec![WindowEvent::NewBrowser(get_default_url(), browser_id)]);
+        // Restore the browsers of the last session if it was saved, rather than opening the
+        // default page.
+        let events = match servo.saved_session() {
+            Some(session) => session
+                .browsers
+                .into_iter()
+                .map(|state| WindowEvent::RestoreBrowser(BrowserId::new(), state))
+                .collect(),
+            None => vec![WindowEvent::NewBrowser(get_default_url(), BrowserId::new())],
+        };
+        servo.handle_events(events);
         servo.setup_logging();
 
         register_window(window);
@@ -26,7 +26,7 @@ use std::io::Write;
 use std::mem;
 use std::rc::Rc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tinyfiledialogs::{self, MessageBoxIcon, OkCancel, YesNo};
 
 pub struct Browser<Window: WindowPortsMethods + ?Sized> {
@@ -473,6 +473,17 @@ where
                     self.shutdown_requested = true;
                 },
                 EmbedderMsg::Panic(_reason, _backtrace) => {},
+                EmbedderMsg::CrashReport(report) => {
+                    let outcome = if report.recovered {
+                        "reloaded"
+                    } else {
+                        "replaced by an error page"
+                    };
+                    warn!(
+                        "Page {} crashed ({} recent crashes) and was {}: {}",
+                        report.url, report.recent_crashes, outcome, report.reason
+                    );
+                },
                 EmbedderMsg::GetSelectedBluetoothDevice(devices, sender) => {
                     let selected = platform_get_selected_devices(devices);
                     if let Err(e) = sender.send(selected) {
@@ -511,6 +522,19 @@ where
                         error!("Failed to store profile: {}", e);
                     }
                 },
+                EmbedderMsg::ReportHangProfile(bytes) => {
+                    // Hang profiles get their own files, so that they don't overwrite
+                    // the sampled profile or each other.
+                    let timestamp = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |duration| duration.as_millis());
+                    let filename = format!("hang-{}.json", timestamp);
+                    let result = File::create(&filename).and_then(|mut f| f.write_all(&bytes));
+                    match result {
+                        Ok(()) => info!("Stored hang profile in {}", filename),
+                        Err(e) => error!("Failed to store hang profile: {}", e),
+                    }
+                },
                 EmbedderMsg::MediaSessionEvent(_) => {
                     debug!("MediaSessionEvent received");
                     // TODO(ferjm): MediaSession support for winit based browsers.
@@ -524,6 +548,12 @@ where
                 EmbedderMsg::ShowContextMenu(sender, ..) => {
                     let _ = sender.send(ContextMenuResult::Ignored);
                 }
+                EmbedderMsg::MessageFromPage(_pipeline_id, url, message) => {
+                    match message.json {
+                        Some(json) => info!("Message from {}: {}", url, json),
+                        None => info!("Message from {} without a JSON representation", url),
+                    }
+                }
             }
         }
     }
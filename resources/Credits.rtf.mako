@@ -4,4 +4,10 @@
 \vieww12000\viewh15840\viewkind0
 \pard\tx720\tx1440\tx2160\tx2880\tx3600\tx4320\tx5040\tx5760\tx6480\tx7200\tx7920\tx8640\pardirnatural\qc\partightenfactor0
 
-\f0\fs24 \cf0 ${version}}
+\f0\fs24 \cf0 ${version}\par
+\par
+\pard\pardirnatural\partightenfactor0
+\fs20 \cf0 Hyphenation patterns from the hyph-utf8 project (https://github.com/hyphenation/tex-hyphen), available under the MIT license:\par
+American English: Copyright (C) 1990, 2004, 2005 Gerard D.C. Kuiken\par
+French: Copyright (C) 1990-2016 Daniel Flipo, Bernard Gaulle, Arthur Reutenauer\par
+German: Copyright (C) 2007-2016 Werner Lemberg and the Deutschsprachige Trennmustermannschaft}
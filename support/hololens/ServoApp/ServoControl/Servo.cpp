@@ -86,6 +86,13 @@ const char *get_clipboard_contents() {
   return nullptr;
 }
 
+void on_message_from_page(const char *url, const char *message) {
+  // Messages without a JSON representation are reported as empty strings,
+  // which are not valid JSON.
+  auto json = message == nullptr ? hstring() : char2hstring(message);
+  sServo->Delegate().OnServoMessageFromPage(char2hstring(url), json);
+}
+
 void on_media_session_set_position_state(double duration, double position,
                                          double playback_rate) {
   return sServo->Delegate().OnServoMediaSessionPosition(duration, position,
@@ -318,6 +325,7 @@ Servo::Servo(std::optional<hstring> initUrl, hstring args, GLsizei width,
       .on_devtools_started = &on_devtools_started,
       .show_context_menu = &show_context_menu,
       .on_log_output = &on_log_output,
+      .on_message_from_page = &on_message_from_page,
   };
 
   capi::register_panic_handler(&on_panic);
@@ -95,6 +95,9 @@ public:
     on_context_menu_closed(res, idx);
   }
   void IMEDismissed() { ime_dismissed(); }
+  void PostMessageToPage(hstring message) {
+    post_message_to_page(*hstring2char(message));
+  }
 
 private:
   ServoDelegate &mDelegate;
@@ -123,6 +126,7 @@ public:
                               int32_t height) = 0;
   virtual void OnServoIMEHide() = 0;
   virtual void OnServoDevtoolsStarted(bool, const unsigned int, hstring) = 0;
+  virtual void OnServoMessageFromPage(hstring, hstring) = 0;
   virtual void OnServoMediaSessionMetadata(hstring, hstring, hstring) = 0;
   virtual void OnServoMediaSessionPosition(double, double, double) = 0;
   virtual void OnServoMediaSessionPlaybackStateChange(int) = 0;
@@ -375,6 +375,10 @@ void ServoControl::SendMediaSessionAction(int32_t action) {
   });
 }
 
+void ServoControl::PostMessageToPage(hstring message) {
+  RunOnGLThread([=] { mServo->PostMessageToPage(message); });
+}
+
 void ServoControl::TryLoadUri(hstring input) {
   if (mLooping) {
     RunOnGLThread([=] {
@@ -691,6 +695,10 @@ void ServoControl::OnServoDevtoolsStarted(bool success, const unsigned int port,
   });
 }
 
+void ServoControl::OnServoMessageFromPage(hstring url, hstring message) {
+  RunOnUIThread([=] { mOnMessageFromPageEvent(url, message); });
+}
+
 void ServoControl::OnServoShowContextMenu(std::optional<hstring> title,
                                           std::vector<winrt::hstring> items) {
   RunOnUIThread([=] {
@@ -50,6 +50,7 @@ struct ServoControl : ServoControlT<ServoControl>, public servo::ServoDelegate {
   hstring LoadURIOrSearch(hstring);
   void GoHome();
   void SendMediaSessionAction(int32_t);
+  void PostMessageToPage(hstring);
 
   ServoApp::Pref SetBoolPref(hstring aKey, bool aVal) {
     auto [key, val, isDefault] = servo::Servo::SetBoolPref(aKey, aVal);
@@ -159,6 +160,13 @@ struct ServoControl : ServoControlT<ServoControl>, public servo::ServoDelegate {
     mOnMediaSessionPositionEvent.remove(token);
   }
 
+  winrt::event_token OnMessageFromPage(MessageFromPageDelegate const &handler) {
+    return mOnMessageFromPageEvent.add(handler);
+  };
+  void OnMessageFromPage(winrt::event_token const &token) noexcept {
+    mOnMessageFromPageEvent.remove(token);
+  }
+
   winrt::event_token
   OnMediaSessionMetadata(MediaSessionMetadataDelegate const &handler) {
     return mOnMediaSessionMetadataEvent.add(handler);
@@ -205,6 +213,7 @@ struct ServoControl : ServoControlT<ServoControl>, public servo::ServoDelegate {
   virtual std::optional<hstring> OnServoPromptInput(winrt::hstring,
                                                     winrt::hstring, bool);
   virtual void OnServoDevtoolsStarted(bool, const unsigned int, winrt::hstring);
+  virtual void OnServoMessageFromPage(winrt::hstring, winrt::hstring);
 
   DevtoolsStatus GetDevtoolsStatus();
 
@@ -220,6 +229,7 @@ private:
   winrt::event<EventDelegate> mOnCaptureGesturesEndedEvent;
   winrt::event<MediaSessionMetadataDelegate> mOnMediaSessionMetadataEvent;
   winrt::event<MediaSessionPositionDelegate> mOnMediaSessionPositionEvent;
+  winrt::event<MessageFromPageDelegate> mOnMessageFromPageEvent;
   winrt::event<Windows::Foundation::EventHandler<int>>
       mOnMediaSessionPlaybackStateChangeEvent;
 
@@ -0,0 +1,89 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use profile::chrome_trace::ChromeTrace;
+use profile_traits::time::{ProfilerCategory, ProfilerThread, TimerMetadata};
+use profile_traits::time::{TimerMetadataFrameType, TimerMetadataReflowType};
+use profile_traits::time::{TraceFlowId, TraceFlowKind, TraceFlowPhase};
+use serde_json::Value;
+use std::env;
+use std::fs;
+
+fn layout_thread() -> ProfilerThread {
+    ProfilerThread {
+        process_id: 7,
+        thread_id: 3,
+        name: Some("LayoutThread".to_owned()),
+    }
+}
+
+#[test]
+fn test_chrome_trace_events() {
+    let path = env::temp_dir().join(format!("servo-chrome-trace-{}.json", std::process::id()));
+    {
+        let mut trace = ChromeTrace::new(&path).unwrap();
+        let metadata = TimerMetadata {
+            url: "http://example.com/".to_owned(),
+            iframe: TimerMetadataFrameType::RootWindow,
+            incremental: TimerMetadataReflowType::Incremental,
+        };
+        trace.write_span(
+            &(ProfilerCategory::LayoutPerform, Some(metadata)),
+            (2_000_000, 5_000_000),
+            &layout_thread(),
+        );
+        trace.write_span(
+            &(ProfilerCategory::LayoutMain, None),
+            (3_000_000, 4_000_000),
+            &layout_thread(),
+        );
+        trace.write_flow(
+            TraceFlowKind::DisplayList,
+            TraceFlowId(0xff),
+            TraceFlowPhase::Start,
+            3_500_000,
+            &layout_thread(),
+        );
+    }
+    let contents = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    let events: Vec<Value> = serde_json::from_str(&contents).unwrap();
+
+    // The thread is named once, before its first event.
+    assert_eq!(events.len(), 4);
+    assert_eq!(events[0]["ph"], "M");
+    assert_eq!(events[0]["name"], "thread_name");
+    assert_eq!(events[0]["args"]["name"], "LayoutThread");
+    assert_eq!(events[0]["pid"], 7);
+    assert_eq!(events[0]["tid"], 3);
+
+    assert_eq!(events[1]["ph"], "X");
+    assert_eq!(events[1]["name"], "Layout");
+    assert_eq!(events[1]["cat"], "layout");
+    assert_eq!(events[1]["ts"], 2000.);
+    assert_eq!(events[1]["dur"], 3000.);
+    assert_eq!(events[1]["args"]["url"], "http://example.com/");
+    assert_eq!(events[1]["args"]["iframe"], false);
+    assert_eq!(events[1]["args"]["incremental"], true);
+
+    assert_eq!(events[2]["name"], "Primary Layout Pass");
+    assert!(events[2].get("args").is_none());
+
+    assert_eq!(events[3]["ph"], "s");
+    assert_eq!(events[3]["id"], "0xff");
+    assert_eq!(events[3]["cat"], "flow");
+}
+
+#[test]
+fn test_trace_flow_id_from_key() {
+    assert_eq!(
+        TraceFlowId::from_key(&(1u32, 2u32, 3u32)),
+        TraceFlowId::from_key(&(1u32, 2u32, 3u32))
+    );
+    assert_ne!(
+        TraceFlowId::from_key(&(1u32, 2u32, 3u32)),
+        TraceFlowId::from_key(&(1u32, 2u32, 4u32))
+    );
+    assert_ne!(TraceFlowId::new(), TraceFlowId::new());
+}
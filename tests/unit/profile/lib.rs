@@ -4,4 +4,6 @@
 
 #![cfg(test)]
 
+mod chrome_trace;
+mod mem;
 mod time;
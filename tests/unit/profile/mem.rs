@@ -0,0 +1,116 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use ipc_channel::ipc;
+use profile::mem;
+use profile_traits::mem::SnapshotReport;
+use profile_traits::mem::{MemoryReportSnapshot, ProfilerMsg, Report, ReportDiff, ReportKind};
+
+fn report(path: &[&str], size: usize, process_id: u32) -> SnapshotReport {
+    SnapshotReport {
+        path: path.iter().map(|seg| seg.to_string()).collect(),
+        kind: ReportKind::ExplicitJemallocHeapSize,
+        size,
+        process_id,
+        pipeline_id: None,
+    }
+}
+
+#[test]
+fn test_memory_report_snapshot_diff() {
+    let before = MemoryReportSnapshot {
+        time: 0,
+        reports: vec![
+            report(&["explicit", "a"], 100, 1),
+            report(&["explicit", "a"], 50, 2),
+            report(&["explicit", "b"], 10, 1),
+            report(&["explicit", "gone"], 7, 1),
+        ],
+    };
+    let after = MemoryReportSnapshot {
+        time: 1000,
+        reports: vec![
+            report(&["explicit", "a"], 100, 1),
+            report(&["explicit", "a"], 150, 2),
+            report(&["explicit", "b"], 10, 1),
+            report(&["explicit", "new"], 20, 1),
+        ],
+    };
+
+    let path = |segs: &[&str]| segs.iter().map(|seg| seg.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+        before.diff(&after),
+        vec![
+            ReportDiff {
+                path: path(&["explicit", "a"]),
+                before: 150,
+                after: 250,
+            },
+            ReportDiff {
+                path: path(&["explicit", "new"]),
+                before: 0,
+                after: 20,
+            },
+            ReportDiff {
+                path: path(&["explicit", "gone"]),
+                before: 7,
+                after: 0,
+            },
+        ]
+    );
+    assert_eq!(before.diff(&after)[2].delta(), -7);
+    assert!(after.diff(&after).is_empty());
+}
+
+#[test]
+fn test_memory_profiler_snapshot() {
+    let chan = mem::Profiler::create(None);
+    let (sender, receiver) = ipc::channel().unwrap();
+    chan.send(ProfilerMsg::Snapshot(sender));
+    let snapshot = receiver.recv().unwrap();
+
+    // The system reporter is always registered, and reports the resident size on the
+    // platforms it supports.
+    if cfg!(any(target_os = "linux", target_os = "macos")) {
+        assert!(snapshot
+            .reports
+            .iter()
+            .any(|report| report.path == ["resident"] && report.size > 0));
+    }
+    assert!(snapshot
+        .reports
+        .iter()
+        .all(|report| report.process_id == std::process::id()));
+    chan.send(ProfilerMsg::Exit);
+}
+
+#[test]
+fn test_memory_profiler_reclaimed() {
+    let chan = mem::Profiler::create(None);
+    let reclaimed = |path: &[&str], size| Report {
+        path: path.iter().map(|seg| seg.to_string()).collect(),
+        kind: ReportKind::ExplicitJemallocHeapSize,
+        size,
+    };
+    chan.report_reclaimed(vec![reclaimed(&["image-cache"], 100)]);
+    chan.report_reclaimed(vec![
+        reclaimed(&["image-cache"], 50),
+        reclaimed(&["js", "gc-heap"], 20),
+    ]);
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    chan.send(ProfilerMsg::Snapshot(sender));
+    let snapshot = receiver.recv().unwrap();
+    let sizes = snapshot.sizes_by_path();
+    let path = |segs: &[&str]| segs.iter().map(|seg| seg.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+        sizes.get(&path(&["memory-pressure-reclaimed", "image-cache"])[..]),
+        Some(&150)
+    );
+    assert_eq!(
+        sizes.get(&path(&["memory-pressure-reclaimed", "js", "gc-heap"])[..]),
+        Some(&20)
+    );
+    chan.send(ProfilerMsg::Exit);
+}
@@ -0,0 +1,62 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::timers::ExecutionBudget;
+use std::time::{Duration, Instant};
+
+#[test]
+fn budget_is_initially_available() {
+    let now = Instant::now();
+    let budget = ExecutionBudget::new(now);
+    assert!(budget.is_available(now));
+    assert_eq!(budget.time_until_available(now), Duration::from_millis(0));
+}
+
+#[test]
+fn budget_is_spent_by_running_timers() {
+    let now = Instant::now();
+    let mut budget = ExecutionBudget::new(now);
+    budget.spend(Duration::from_millis(40), now);
+    assert!(budget.is_available(now));
+    budget.spend(Duration::from_millis(20), now);
+    assert!(!budget.is_available(now));
+}
+
+#[test]
+fn overspent_budget_is_released_after_recovering() {
+    let now = Instant::now();
+    let mut budget = ExecutionBudget::new(now);
+    // Overspend by 10ms, which takes a second to recover.
+    budget.spend(Duration::from_millis(60), now);
+    assert_eq!(budget.time_until_available(now), Duration::from_secs(1));
+
+    let later = now + Duration::from_millis(500);
+    assert!(!budget.is_available(later));
+    assert_eq!(
+        budget.time_until_available(later),
+        Duration::from_millis(500)
+    );
+
+    let recovered = now + Duration::from_secs(1);
+    assert!(budget.is_available(recovered));
+    assert_eq!(
+        budget.time_until_available(recovered),
+        Duration::from_millis(0)
+    );
+}
+
+#[test]
+fn budget_recovery_is_bounded() {
+    let now = Instant::now();
+    let mut budget = ExecutionBudget::new(now);
+    // A long time hidden does not let timers run for longer than 50ms at once.
+    let later = now + Duration::from_secs(3600);
+    budget.spend(Duration::from_millis(60), later);
+    assert!(!budget.is_available(later));
+
+    // A long-running timer delays the next ones by at most a minute.
+    let mut budget = ExecutionBudget::new(now);
+    budget.spend(Duration::from_secs(10), now);
+    assert_eq!(budget.time_until_available(now), Duration::from_secs(60));
+}
@@ -0,0 +1,8 @@
+import os
+import sys
+
+# Reuse the fixtures of the upstream WebDriver tests.
+sys.path.insert(0, os.path.join(os.path.dirname(__file__),
+                                "..", "..", "..", "web-platform-tests", "webdriver"))
+
+pytest_plugins = "tests.support.fixtures"
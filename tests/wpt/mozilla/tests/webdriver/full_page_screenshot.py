@@ -0,0 +1,49 @@
+from tests.support.asserts import assert_png, assert_success
+from tests.support.image import png_dimensions
+from tests.support.inline import inline
+
+
+def take_full_page_screenshot(session):
+    return session.transport.send(
+        "GET", "session/{session_id}/servo/screenshot/full".format(**vars(session)))
+
+
+def page_dimensions(session):
+    return tuple(session.execute_script("""
+        const {devicePixelRatio, innerWidth} = window;
+        const {scrollHeight} = document.documentElement;
+
+        return [
+          Math.floor(innerWidth * devicePixelRatio),
+          Math.ceil(scrollHeight * devicePixelRatio),
+        ];
+        """))
+
+
+TALL_PAGE = inline("""
+    <style>body { margin: 0 }</style>
+    <div style="width: 10px; height: 5000px; background: green"></div>
+""")
+
+
+def test_page_taller_than_viewport(session):
+    session.url = TALL_PAGE
+    session.execute_script("window.scrollTo(0, 100)")
+
+    response = take_full_page_screenshot(session)
+    value = assert_success(response)
+
+    assert_png(value)
+    width, height = page_dimensions(session)
+    assert png_dimensions(value) == (width, height)
+    assert height > session.execute_script("return innerHeight * devicePixelRatio")
+
+
+def test_scroll_position_restored(session):
+    session.url = TALL_PAGE
+    session.execute_script("window.scrollTo(0, 100)")
+
+    assert_success(take_full_page_screenshot(session))
+
+    # The page keeps working after the capture, and script never saw it scroll.
+    assert session.execute_script("return window.scrollY") == 100